@@ -20,6 +20,8 @@ use nerust_core_traits::{
     touch::{TouchOverlayAction, TouchPoint},
 };
 use nerust_gui_runtime::{
+    rom::RomFileError,
+    rom_library::RomLibraryError,
     settings::{
         BackendPresentationCapabilities, HostBackendCapabilities, HostWindowCapabilities,
         SettingsPaths, SettingsSnapshot,
@@ -179,6 +181,8 @@ struct AndroidFrontend {
     gpu_factory: Rc<dyn GpuFactory>,
     overlay: Option<PortraitTouchOverlay>,
     active_touches: HashMap<u64, TouchTarget>,
+    /// Touch that started off the overlay buttons; it drives light guns.
+    pointer_touch: Option<u64>,
     is_resumed: bool,
     foreground_resume_pending: bool,
     foreground_retry_attempts: u32,
@@ -186,6 +190,13 @@ struct AndroidFrontend {
     last_foreground_error: Option<String>,
     lifecycle_auto_paused: bool,
     lifecycle_restore_pending: bool,
+    pending_archive: Option<PendingArchive>,
+}
+
+/// A zip holding several ROMs, kept while the user picks which one to import.
+struct PendingArchive {
+    display_name: String,
+    bytes: Vec<u8>,
 }
 
 impl AndroidFrontend {
@@ -232,6 +243,7 @@ impl AndroidFrontend {
             gpu_factory,
             overlay: None,
             active_touches: HashMap::new(),
+            pointer_touch: None,
             is_resumed: false,
             foreground_resume_pending: false,
             foreground_retry_attempts: 0,
@@ -239,6 +251,7 @@ impl AndroidFrontend {
             last_foreground_error: None,
             lifecycle_auto_paused: false,
             lifecycle_restore_pending: restore_pending,
+            pending_archive: None,
         };
         if frontend.lifecycle_restore_pending {
             log::info!(
@@ -322,7 +335,24 @@ impl AndroidFrontend {
 
     fn handle_library_result(&mut self, event_loop: &ActiveEventLoop, result: LibraryDialogResult) {
         match result {
-            LibraryDialogResult::Dismissed => {}
+            LibraryDialogResult::Dismissed => {
+                self.pending_archive = None;
+            }
+            LibraryDialogResult::ArchiveEntrySelected(entry) => {
+                let Some(archive) = self.pending_archive.take() else {
+                    log::warn!("archive entry {entry} selected with no archive pending");
+                    return;
+                };
+                if let Err(error) = self.import_rom(
+                    event_loop,
+                    &archive.display_name,
+                    "",
+                    &archive.bytes,
+                    Some(&entry),
+                ) {
+                    log::error!("{error}");
+                }
+            }
             LibraryDialogResult::Selected(id) => {
                 if let Err(error) = self.load_from_library(event_loop, &id) {
                     log::error!("{error}");
@@ -355,11 +385,49 @@ impl AndroidFrontend {
             display_name,
             bytes.len()
         );
-        let entry = self
-            .storage
-            .rom_library
-            .import_bytes(&display_name, &extension, &bytes)
-            .map_err(|error| format!("failed to import Android ROM into library: {error}"))?;
+        self.import_rom(event_loop, &display_name, &extension, &bytes, None)
+    }
+
+    /// Imports `bytes` into the library and starts it. A zip holding several
+    /// ROMs is kept aside while the user picks `archive_entry`.
+    fn import_rom(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        display_name: &str,
+        extension: &str,
+        bytes: &[u8],
+        archive_entry: Option<&str>,
+    ) -> Result<(), String> {
+        let imported = match archive_entry {
+            Some(archive_entry) => {
+                self.storage
+                    .rom_library
+                    .import_archive_entry(display_name, bytes, archive_entry)
+            }
+            None => self
+                .storage
+                .rom_library
+                .import_bytes(display_name, extension, bytes),
+        };
+        let entry = match imported {
+            Ok(entry) => entry,
+            Err(RomLibraryError::Rom(RomFileError::MultipleRoms(candidates))) => {
+                log::info!("import_rom: asking which of {candidates:?} to import");
+                if !library::request_show_archive_entries(&self.app, &candidates)? {
+                    return Err("ROM archive picker is already open".to_string());
+                }
+                self.pending_archive = Some(PendingArchive {
+                    display_name: display_name.to_string(),
+                    bytes: bytes.to_vec(),
+                });
+                return Ok(());
+            }
+            Err(error) => {
+                return Err(format!(
+                    "failed to import Android ROM into library: {error}"
+                ));
+            }
+        };
         let path = self
             .storage
             .rom_library
@@ -370,6 +438,13 @@ impl AndroidFrontend {
                     entry.id
                 )
             })?;
+        // Zipped imports are stored unpacked; load the extracted ROM back.
+        let bytes = self
+            .storage
+            .rom_library
+            .load_bytes(&entry.id)
+            .map_err(|error| format!("failed to read imported Android ROM: {error}"))?
+            .ok_or_else(|| format!("imported Android ROM {} is missing", entry.id))?;
         let media = MediaObject::new(Some(path), bytes);
 
         let (factory, system_id) = {
@@ -417,7 +492,7 @@ impl AndroidFrontend {
         }
         self.finish_rom_load(event_loop, &entry.id, false);
         log::info!(
-            "import_rom_from_uri: imported '{}' as id={}",
+            "import_rom: imported '{}' as id={}",
             entry.display_name,
             entry.id
         );
@@ -487,6 +562,7 @@ impl AndroidFrontend {
         }
         self.session.clear_input();
         self.active_touches.clear();
+        self.pointer_touch = None;
         self.lifecycle_restore_pending = self.session.save_hidden_lifecycle_state();
         if !self.lifecycle_restore_pending {
             self.session.clear_hidden_lifecycle_state();
@@ -510,6 +586,7 @@ impl AndroidFrontend {
         self.renderer = None;
         self.overlay = None;
         self.active_touches.clear();
+        self.pointer_touch = None;
         self.shell.needs_redraw = true;
     }
 
@@ -965,6 +1042,13 @@ impl AndroidFrontend {
                 y: touch.location.y as f32,
             })
         });
+        let starts_pointer = touch.phase == TouchPhase::Started
+            && next_target.is_none()
+            && self.pointer_touch.is_none();
+        if starts_pointer || self.pointer_touch == Some(touch.id) {
+            self.handle_pointer_touch(touch);
+            return;
+        }
         match touch.phase {
             TouchPhase::Started | TouchPhase::Moved => {
                 self.sync_touch_target(touch.id, next_target);
@@ -975,6 +1059,23 @@ impl AndroidFrontend {
         }
     }
 
+    /// Aims light guns at the touched point and holds the trigger until the
+    /// touch ends.
+    fn handle_pointer_touch(&mut self, touch: Touch) {
+        let Some(window) = self.window.as_ref() else {
+            return;
+        };
+        let size = window.inner_size();
+        let position = self.session.pointer_frame_position(
+            (touch.location.x, touch.location.y),
+            (f64::from(size.width), f64::from(size.height)),
+        );
+        let pressed = matches!(touch.phase, TouchPhase::Started | TouchPhase::Moved);
+        self.pointer_touch = pressed.then_some(touch.id);
+        self.session.apply_pointer(position, pressed);
+        self.request_redraw();
+    }
+
     fn exec(&mut self, cmd: SessionCommand) -> Option<SessionCommandOutcome> {
         match self.session.run_command(cmd) {
             Ok(o) => {
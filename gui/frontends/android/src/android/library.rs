@@ -20,6 +20,9 @@ static CACHED_ENTRIES: Mutex<(Vec<String>, Vec<String>)> = Mutex::new((Vec::new(
 /// Must match `MainActivity.IMPORT_ACTION_ID`.
 const IMPORT_ACTION_ID: &str = "__import__";
 
+/// Prefixes the ids when the library dialog lists the ROMs inside a zip archive.
+const ARCHIVE_ENTRY_ID_PREFIX: &str = "__archive_entry__:";
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum LibraryDialogResult {
     /// The dialog was dismissed without a selection.
@@ -28,6 +31,8 @@ pub(crate) enum LibraryDialogResult {
     Selected(String),
     /// The user requested to import a new ROM via the SAF picker.
     ImportRequested,
+    /// The user picked this member of the archive being imported.
+    ArchiveEntrySelected(String),
 }
 
 static LIBRARY_RESULT: Mutex<Option<LibraryDialogResult>> = Mutex::new(None);
@@ -91,14 +96,35 @@ pub(crate) fn take_result() -> Option<LibraryDialogResult> {
 pub(crate) fn request_show_library(
     app: &AndroidApp,
     entries: &[RomLibraryEntry],
+) -> Result<bool, String> {
+    let names: Vec<String> = entries.iter().map(|e| e.display_name.clone()).collect();
+    let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+    request_show_dialog(app, names, ids)
+}
+
+/// Reuses the library dialog to let the user pick one ROM out of a zip archive.
+///
+/// Returns `Ok(false)` when a dialog is already in flight (idempotent guard).
+pub(crate) fn request_show_archive_entries(
+    app: &AndroidApp,
+    candidates: &[String],
+) -> Result<bool, String> {
+    let ids = candidates
+        .iter()
+        .map(|name| format!("{ARCHIVE_ENTRY_ID_PREFIX}{name}"))
+        .collect();
+    request_show_dialog(app, candidates.to_vec(), ids)
+}
+
+fn request_show_dialog(
+    app: &AndroidApp,
+    names: Vec<String>,
+    ids: Vec<String>,
 ) -> Result<bool, String> {
     if LIBRARY_REQUEST_IN_FLIGHT.swap(true, Ordering::AcqRel) {
         return Ok(false);
     }
 
-    let names: Vec<String> = entries.iter().map(|e| e.display_name.clone()).collect();
-    let ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
-
     let app = app.clone();
     let callback_app = app.clone();
     app.run_on_java_main_thread(Box::new(move || {
@@ -193,6 +219,7 @@ fn wake_main_thread() {
 ///
 /// * `id == null`             → dialog was dismissed
 /// * `id == IMPORT_ACTION_ID` → user wants to import a new ROM
+/// * `ARCHIVE_ENTRY_ID_PREFIX` → user picked a member of the archive being imported
 /// * otherwise               → user selected the library entry with that id
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_github_chalharu_nerust_MainActivity_onRomLibrarySelected(
@@ -208,6 +235,8 @@ pub extern "system" fn Java_io_github_chalharu_nerust_MainActivity_onRomLibraryS
                 let id = id.try_to_string(env)?;
                 Ok(if id == IMPORT_ACTION_ID {
                     LibraryDialogResult::ImportRequested
+                } else if let Some(entry) = id.strip_prefix(ARCHIVE_ENTRY_ID_PREFIX) {
+                    LibraryDialogResult::ArchiveEntrySelected(entry.to_string())
                 } else {
                     LibraryDialogResult::Selected(id)
                 })
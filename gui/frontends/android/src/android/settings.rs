@@ -15,6 +15,7 @@ use nerust_core_traits::{
     identity::SystemId,
 };
 use nerust_gui_runtime::settings::SettingsSnapshot;
+use nerust_gui_settings::shared::{RewindSettings, SpeedSettings};
 use nerust_gui_shell::registry::SystemRegistry;
 use nerust_settings_core::factory::{apply_settings_choice, resolve_label, settings_view};
 use winit::platform::android::activity::{AndroidApp, AndroidAppWaker};
@@ -45,6 +46,9 @@ pub(crate) struct AndroidSettings {
     pub latency_ms: u16,
     pub sample_rate: u32,
     pub vsync: bool,
+    pub rewind_interval_frames: u32,
+    pub fast_forward_percent: Option<u16>,
+    pub slow_motion_percent: u16,
     system_choices: Vec<AndroidSystemChoice>,
 }
 
@@ -71,9 +75,13 @@ impl AndroidSettings {
                     .settings_page(&view)
                     .fields
                     .iter()
-                    .map(|field| {
-                        let SystemSettingsFieldKind::Choice { selected, options } = &field.kind;
-                        AndroidSystemChoice {
+                    // Path fields need a file browser, which only the desktop frontends have
+                    .filter_map(|field| {
+                        let SystemSettingsFieldKind::Choice { selected, options } = &field.kind
+                        else {
+                            return None;
+                        };
+                        Some(AndroidSystemChoice {
                             system_id: system_id.clone(),
                             field_id: field.id.clone(),
                             label: resolve_label(field.label_id, language, factory.as_ref()),
@@ -87,7 +95,7 @@ impl AndroidSettings {
                                     )
                                 })
                                 .collect(),
-                        }
+                        })
                     })
                     .collect::<Vec<_>>()
             })
@@ -99,6 +107,9 @@ impl AndroidSettings {
             latency_ms: snapshot.local.audio.latency_ms,
             sample_rate: snapshot.local.audio.sample_rate,
             vsync: snapshot.local.video.presentation.vsync,
+            rewind_interval_frames: snapshot.shared.rewind.interval_frames,
+            fast_forward_percent: snapshot.shared.speed.fast_forward_percent,
+            slow_motion_percent: snapshot.shared.speed.slow_motion_percent,
             system_choices,
         }
     }
@@ -116,6 +127,9 @@ impl AndroidSettings {
         snapshot.local.audio.latency_ms = self.latency_ms;
         snapshot.local.audio.sample_rate = self.sample_rate;
         snapshot.local.video.presentation.vsync = self.vsync;
+        snapshot.shared.rewind.interval_frames = self.rewind_interval_frames;
+        snapshot.shared.speed.fast_forward_percent = self.fast_forward_percent;
+        snapshot.shared.speed.slow_motion_percent = self.slow_motion_percent;
 
         for choice in &self.system_choices {
             let factory = registry
@@ -143,6 +157,9 @@ impl AndroidSettings {
             "latency_ms".to_string(),
             "sample_rate".to_string(),
             "vsync".to_string(),
+            "rewind_interval".to_string(),
+            "fast_forward".to_string(),
+            "slow_motion".to_string(),
         ];
         keys.extend(
             self.system_choices
@@ -160,6 +177,9 @@ impl AndroidSettings {
             "Audio Latency (ms)".to_string(),
             "Sample Rate (Hz)".to_string(),
             "VSync".to_string(),
+            "Rewind Interval (frames)".to_string(),
+            "Fast-forward Speed".to_string(),
+            "Slow-motion Speed".to_string(),
         ];
         labels.extend(
             self.system_choices
@@ -181,6 +201,19 @@ impl AndroidSettings {
                     .map(|value| format!("{value} Hz")),
             ),
             "Off\tOn".to_string(),
+            join_tab_labels(
+                RewindSettings::INTERVAL_CHOICES
+                    .iter()
+                    .map(|value| value.to_string()),
+            ),
+            join_tab_labels(SpeedSettings::FAST_FORWARD_CHOICES.iter().map(|value| {
+                value.map_or_else(|| "Unlimited".to_string(), |value| format!("{value}%"))
+            })),
+            join_tab_labels(
+                SpeedSettings::SLOW_MOTION_CHOICES
+                    .iter()
+                    .map(|value| format!("{value}%")),
+            ),
         ];
         choices.extend(
             self.system_choices.iter().map(|choice| {
@@ -199,12 +232,37 @@ impl AndroidSettings {
             .iter()
             .position(|&v| v == self.sample_rate)
             .unwrap_or(SAMPLE_RATE_CHOICES.len().saturating_sub(1)); // default: highest rate
+        let rewind_idx = RewindSettings::INTERVAL_CHOICES
+            .iter()
+            .position(|&v| v == self.rewind_interval_frames)
+            .or_else(|| {
+                RewindSettings::INTERVAL_CHOICES
+                    .iter()
+                    .position(|&v| v == RewindSettings::default().interval_frames)
+            })
+            .unwrap_or_default();
+        let fast_forward_idx = SpeedSettings::FAST_FORWARD_CHOICES
+            .iter()
+            .position(|&v| v == self.fast_forward_percent)
+            .unwrap_or_default(); // default: unlimited
+        let slow_motion_idx = SpeedSettings::SLOW_MOTION_CHOICES
+            .iter()
+            .position(|&v| v == self.slow_motion_percent)
+            .or_else(|| {
+                SpeedSettings::SLOW_MOTION_CHOICES
+                    .iter()
+                    .position(|&v| v == SpeedSettings::default().slow_motion_percent)
+            })
+            .unwrap_or_default();
         let mut indices = vec![
             (self.audio_muted as usize).to_string(),
             volume_idx.to_string(),
             latency_idx.to_string(),
             sample_rate_idx.to_string(),
             (self.vsync as usize).to_string(),
+            rewind_idx.to_string(),
+            fast_forward_idx.to_string(),
+            slow_motion_idx.to_string(),
         ];
         indices.extend(self.system_choices.iter().map(|choice| {
             choice
@@ -249,8 +307,11 @@ impl AndroidSettings {
             1 => true,
             _ => return None,
         };
+        let rewind_interval_frames = *RewindSettings::INTERVAL_CHOICES.get(indices[5])?;
+        let fast_forward_percent = *SpeedSettings::FAST_FORWARD_CHOICES.get(indices[6])?;
+        let slow_motion_percent = *SpeedSettings::SLOW_MOTION_CHOICES.get(indices[7])?;
         let mut system_choices = current.system_choices.clone();
-        for (choice, selected_index) in system_choices.iter_mut().zip(&indices[5..]) {
+        for (choice, selected_index) in system_choices.iter_mut().zip(&indices[8..]) {
             choice.selected = choice.options.get(*selected_index)?.0.clone();
         }
 
@@ -260,6 +321,9 @@ impl AndroidSettings {
             latency_ms,
             sample_rate,
             vsync,
+            rewind_interval_frames,
+            fast_forward_percent,
+            slow_motion_percent,
             system_choices,
         })
     }
@@ -626,6 +690,8 @@ mod tests {
             out.local.video.presentation.vsync,
             snapshot.local.video.presentation.vsync
         );
+        assert_eq!(out.shared.rewind, snapshot.shared.rewind);
+        assert_eq!(out.shared.speed, snapshot.shared.speed);
     }
 
     #[test]
@@ -659,6 +725,9 @@ mod tests {
         android.latency_ms = 75;
         android.sample_rate = 44_100;
         android.vsync = false;
+        android.rewind_interval_frames = 8;
+        android.fast_forward_percent = Some(300);
+        android.slow_motion_percent = 25;
         set_system_choice(&mut android, "video.filter", "ntsc_rgb");
         android.apply_to_snapshot(&mut snapshot, &registry).unwrap();
 
@@ -667,6 +736,9 @@ mod tests {
         assert_eq!(snapshot.local.audio.latency_ms, 75);
         assert_eq!(snapshot.local.audio.sample_rate, 44_100);
         assert!(!snapshot.local.video.presentation.vsync);
+        assert_eq!(snapshot.shared.rewind.interval_frames, 8);
+        assert_eq!(snapshot.shared.speed.fast_forward_percent, Some(300));
+        assert_eq!(snapshot.shared.speed.slow_motion_percent, 25);
         let nes = snapshot
             .shared
             .systems
@@ -683,8 +755,15 @@ mod tests {
         let android = android_settings(&snapshot, &registry);
         let indices = android.current_indices();
         // Default: not muted → 0; volume 100% → index 100; latency 50 ms → index 40;
-        // sample rate 48000 → index 1; vsync on → 1; NtscComposite → index 1
-        assert_eq!(indices, vec!["0", "100", "40", "1", "1", "1", "0"]);
+        // sample rate 48000 → index 1; vsync on → 1; rewind every 2 frames → index 1;
+        // unlimited fast-forward → 0; 50% slow motion → 1;
+        // NtscComposite → index 1; default palette, auto MMC3 IRQ and auto region → 0
+        assert_eq!(
+            indices,
+            vec![
+                "0", "100", "40", "1", "1", "1", "0", "1", "1", "0", "0", "0"
+            ]
+        );
     }
 
     #[test]
@@ -697,6 +776,9 @@ mod tests {
         original.latency_ms = 100;
         original.sample_rate = 44_100;
         original.vsync = false;
+        original.rewind_interval_frames = 4;
+        original.fast_forward_percent = Some(800);
+        original.slow_motion_percent = 75;
         set_system_choice(&mut original, "video.filter", "ntsc_svideo");
         original
             .apply_to_snapshot(&mut snapshot, &registry)
@@ -728,10 +810,27 @@ mod tests {
     fn from_choice_indices_rejects_out_of_range() {
         let registry = registry();
         let current = android_settings(&default_snapshot(), &registry);
-        assert!(AndroidSettings::from_choice_indices("0,101,1,1,1,1,0", &current).is_none());
-        assert!(AndroidSettings::from_choice_indices("0,4,191,1,1,1,0", &current).is_none());
-        assert!(AndroidSettings::from_choice_indices("2,4,1,1,1,1,0", &current).is_none());
-        assert!(AndroidSettings::from_choice_indices("0,4,1,1,2,1,0", &current).is_none());
+        assert!(
+            AndroidSettings::from_choice_indices("0,101,1,1,1,1,0,1,1,0,0,0", &current).is_none()
+        );
+        assert!(
+            AndroidSettings::from_choice_indices("0,4,191,1,1,1,0,1,1,0,0,0", &current).is_none()
+        );
+        assert!(
+            AndroidSettings::from_choice_indices("2,4,1,1,1,1,0,1,1,0,0,0", &current).is_none()
+        );
+        assert!(
+            AndroidSettings::from_choice_indices("0,4,1,1,2,1,0,1,1,0,0,0", &current).is_none()
+        );
+        assert!(
+            AndroidSettings::from_choice_indices("0,4,1,1,1,4,0,1,1,0,0,0", &current).is_none()
+        );
+        assert!(
+            AndroidSettings::from_choice_indices("0,4,1,1,1,1,5,1,1,0,0,0", &current).is_none()
+        );
+        assert!(
+            AndroidSettings::from_choice_indices("0,4,1,1,1,1,0,3,1,0,0,0", &current).is_none()
+        );
     }
 
     #[test]
@@ -774,7 +873,9 @@ mod tests {
         let registry = registry();
         let current = android_settings(&default_snapshot(), &registry);
         assert!(AndroidSettings::from_choice_indices("0,4,1,1,1", &current).is_none());
-        assert!(AndroidSettings::from_choice_indices("0,4,1,1,1,1,0,0", &current).is_none());
+        assert!(
+            AndroidSettings::from_choice_indices("0,4,1,1,1,1,0,1,1,0,0,0,0", &current).is_none()
+        );
     }
 
     #[test]
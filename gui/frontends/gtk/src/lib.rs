@@ -20,6 +20,7 @@ use nerust_gui_runtime::settings::{
 use nerust_gui_settings::language::AppLanguage;
 use nerust_gui_shell::{
     context::FrontendContext,
+    load::RomLoaderError,
     session::{
         KeyboardShortcut, SessionError, SessionHandle,
         access::{FrontendSession, SettingsResult},
@@ -40,6 +41,9 @@ pub(crate) struct State {
     session: SessionHandle,
     ctx: FrontendContext,
     renderer_reload_pending: bool,
+    /// Pointer position in window coordinates, `None` while outside the window.
+    pointer: Option<(f64, f64)>,
+    pointer_pressed: bool,
 }
 
 impl State {
@@ -66,6 +70,8 @@ impl State {
             session,
             ctx,
             renderer_reload_pending: false,
+            pointer: None,
+            pointer_pressed: false,
         }
     }
 
@@ -89,13 +95,18 @@ impl State {
         self.session.can_resume()
     }
 
-    pub(crate) fn load_path(&mut self, path: &Path) {
-        if let Err(e) = self.ctx.rom_loader.load_rom(path, &mut self.session) {
-            log::warn!("ROM load failed: {e}");
+    /// Returns the candidates when `path` is an archive holding several ROMs
+    /// and `entry` did not pick one.
+    pub(crate) fn load_path(&mut self, path: &Path, entry: Option<&str>) -> Option<Vec<String>> {
+        match self.ctx.rom_loader.load_rom(path, entry, &mut self.session) {
+            Ok(()) => {}
+            Err(RomLoaderError::MultipleRoms(candidates)) => return Some(candidates),
+            Err(e) => log::warn!("ROM load failed: {e}"),
         }
         if self.session.loaded() {
             self.renderer_reload_pending = true;
         }
+        None
     }
 
     pub(crate) fn loaded(&self) -> bool {
@@ -215,9 +226,27 @@ impl State {
     }
 
     pub(crate) fn clear_input(&mut self) {
+        self.pointer_pressed = false;
         self.session.clear_input();
     }
 
+    pub(crate) fn move_pointer(&mut self, pointer: Option<(f64, f64)>, window: (f64, f64)) {
+        self.pointer = pointer;
+        self.sync_pointer(window);
+    }
+
+    pub(crate) fn press_pointer(&mut self, pressed: bool, window: (f64, f64)) {
+        self.pointer_pressed = pressed;
+        self.sync_pointer(window);
+    }
+
+    fn sync_pointer(&mut self, window: (f64, f64)) {
+        let position = self
+            .pointer
+            .and_then(|pointer| self.session.pointer_frame_position(pointer, window));
+        self.session.apply_pointer(position, self.pointer_pressed);
+    }
+
     pub(crate) fn slots(&self) -> &[StateSlotSummary] {
         self.session.slots()
     }
@@ -400,8 +429,7 @@ pub fn run(ctx: FrontendContext, _options: RunOptions) {
         let _ = app.connect_open(move |app, files, _| {
             let window = ensure_window(app, &gpu_factory, &state, &current_window);
             if let Some(path) = files.iter().find_map(|file| file.path()) {
-                state.borrow_mut().load_path(&path);
-                window.update_actions();
+                window.load_path(&path);
             }
             window.window().present();
         });
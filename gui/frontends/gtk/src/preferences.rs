@@ -10,7 +10,8 @@ use gio::glib::{
 };
 use gtk::prelude::{
     BoxExt as _, ButtonExt as _, CheckButtonExt as _, ComboBoxExt as _, DialogExt as _,
-    EditableExt as _, GtkWindowExt as _, WidgetExt as _,
+    EditableExt as _, EntryExt as _, FileChooserExt as _, FileExt as _, GtkWindowExt as _,
+    NativeDialogExt as _, WidgetExt as _,
 };
 use nerust_core_traits::factory::CoreFactory;
 use nerust_gui_shell::session::access::FrontendSession as _;
@@ -18,7 +19,7 @@ use nerust_gui_viewmodel::settings::{
     SettingsViewModel, StoragePathError, StoragePathValidator, Subscription,
     dto::{
         AudioView, BindingRowView, BindingValueView, ControllerSlotView, GeneralView, InputTabView,
-        SystemTabView, VideoView,
+        SystemFieldKindView, SystemTabView, VideoView,
     },
 };
 use nerust_settings_core::{
@@ -43,6 +44,12 @@ struct GeneralWidgets {
     language_label: gtk::Label,
     storage_policy_label: gtk::Label,
     storage_dir_label: gtk::Label,
+    rewind_interval_combo: gtk::ComboBoxText,
+    rewind_interval_label: gtk::Label,
+    fast_forward_combo: gtk::ComboBoxText,
+    fast_forward_label: gtk::Label,
+    slow_motion_combo: gtk::ComboBoxText,
+    slow_motion_label: gtk::Label,
 }
 
 struct VideoWidgets {
@@ -106,6 +113,7 @@ struct PreferencesBinding {
     dialog: gtk::Dialog,
     stack: gtk::Stack,
     page_ids: Vec<&'static str>,
+    path_chooser: RefCell<Option<gtk::FileChooserNative>>,
     refreshing: Cell<bool>,
 }
 
@@ -199,6 +207,7 @@ impl PreferencesBinding {
                 dialog,
                 stack,
                 page_ids,
+                path_chooser: RefCell::new(None),
                 refreshing: Cell::new(false),
             }
         });
@@ -258,6 +267,15 @@ impl PreferencesBinding {
         self.general
             .storage_dir_label
             .set_text(ui_text(lang, UiText::SaveStorageDirectory));
+        self.general
+            .rewind_interval_label
+            .set_text(ui_text(lang, UiText::RewindInterval));
+        self.general
+            .fast_forward_label
+            .set_text(ui_text(lang, UiText::FastForwardSpeed));
+        self.general
+            .slow_motion_label
+            .set_text(ui_text(lang, UiText::SlowMotionSpeed));
         self.video
             .fullscreen_check
             .set_label(Some(ui_text(lang, UiText::FullscreenDefault)));
@@ -306,6 +324,33 @@ impl PreferencesBinding {
                 .storage_dir_entry
                 .set_text(&view.storage_directory);
         }
+        self.general.rewind_interval_combo.remove_all();
+        for choice in &view.rewind_interval_choices {
+            self.general
+                .rewind_interval_combo
+                .append(Some(&choice.value.to_string()), &choice.label);
+        }
+        self.general
+            .rewind_interval_combo
+            .set_active_id(Some(&view.rewind_interval_frames.to_string()));
+        self.general.fast_forward_combo.remove_all();
+        for choice in &view.fast_forward_choices {
+            self.general
+                .fast_forward_combo
+                .append(Some(&fast_forward_id(choice.value)), &choice.label);
+        }
+        self.general
+            .fast_forward_combo
+            .set_active_id(Some(&fast_forward_id(view.fast_forward_percent)));
+        self.general.slow_motion_combo.remove_all();
+        for choice in &view.slow_motion_choices {
+            self.general
+                .slow_motion_combo
+                .append(Some(&choice.value.to_string()), &choice.label);
+        }
+        self.general
+            .slow_motion_combo
+            .set_active_id(Some(&view.slow_motion_percent.to_string()));
     }
 
     fn refresh_video(&self, view: &VideoView) {
@@ -382,13 +427,29 @@ impl PreferencesBinding {
         };
         clear_box(page);
         for field in &view.fields {
+            let (selected, choices) = match &field.kind {
+                SystemFieldKindView::Choice { selected, choices } => (selected, choices),
+                SystemFieldKindView::Path {
+                    selected,
+                    extensions,
+                } => {
+                    self.append_system_path_row(
+                        page,
+                        index,
+                        field,
+                        selected.as_deref(),
+                        extensions,
+                    );
+                    continue;
+                }
+            };
             let combo = gtk::ComboBoxText::new();
-            for choice in &field.choices {
+            for choice in choices {
                 combo.append(Some(choice.value.as_str()), &choice.label);
             }
-            combo.set_active_id(Some(field.selected.as_str()));
+            combo.set_active_id(Some(selected.as_str()));
             let field_id = field.id.clone();
-            let choices = field.choices.clone();
+            let choices = choices.clone();
             let weak = self.self_weak.clone();
             combo.connect_changed(move |combo| {
                 let Some(binding) = weak.upgrade() else {
@@ -424,6 +485,87 @@ impl PreferencesBinding {
         }
     }
 
+    fn append_system_path_row(
+        &self,
+        page: &gtk::Box,
+        index: usize,
+        field: &nerust_gui_viewmodel::settings::dto::SystemFieldView,
+        selected: Option<&std::path::Path>,
+        extensions: &'static [&'static str],
+    ) {
+        let entry = gtk::Entry::new();
+        entry.set_hexpand(true);
+        if let Some(path) = selected {
+            entry.set_text(&path.to_string_lossy());
+        }
+        // Commit on Enter: each change rebuilds the page and would drop focus
+        entry.connect_activate({
+            let weak = self.self_weak.clone();
+            let field_id = field.id.clone();
+            move |entry| {
+                let Some(b) = weak.upgrade() else { return };
+                let text = entry.text();
+                let path = (!text.is_empty()).then(|| std::path::PathBuf::from(text.as_str()));
+                b.set_system_path(index, &field_id, path.as_deref());
+            }
+        });
+        let browse = gtk::Button::with_label(ui_text(self.language(), UiText::Browse));
+        browse.connect_clicked({
+            let weak = self.self_weak.clone();
+            let field_id = field.id.clone();
+            let title = field.label.clone();
+            move |_| {
+                let Some(b) = weak.upgrade() else { return };
+                let chooser = gtk::FileChooserNative::new(
+                    Some(&title),
+                    Some(&b.dialog),
+                    gtk::FileChooserAction::Open,
+                    None,
+                    None,
+                );
+                let filter = gtk::FileFilter::new();
+                for extension in extensions {
+                    filter.add_pattern(&format!("*.{extension}"));
+                }
+                chooser.set_filter(&filter);
+                chooser.connect_response({
+                    let weak = weak.clone();
+                    let field_id = field_id.clone();
+                    move |chooser, response| {
+                        let Some(b) = weak.upgrade() else { return };
+                        if response == gtk::ResponseType::Accept
+                            && let Some(path) = chooser.file().and_then(|file| file.path())
+                        {
+                            b.set_system_path(index, &field_id, Some(&path));
+                        }
+                        chooser.hide();
+                        b.path_chooser.replace(None);
+                    }
+                });
+                chooser.show();
+                b.path_chooser.replace(Some(chooser));
+            }
+        });
+        let row = gtk::Box::new(gtk::Orientation::Horizontal, 6);
+        row.set_hexpand(true);
+        row.append(&entry);
+        row.append(&browse);
+        page.append(&labeled_row(&field.label, &row));
+    }
+
+    fn set_system_path(
+        &self,
+        index: usize,
+        field: &nerust_core_traits::factory::descriptor::SystemSettingsFieldId,
+        path: Option<&std::path::Path>,
+    ) {
+        if let Some(vm) = self.vm.systems().get(index)
+            && let Err(e) = vm.set_path(field, path)
+        {
+            self.error_label.set_text(&e.to_string());
+        }
+    }
+
     fn rebuild_all_input_pages(&self) {
         for (index, vm) in self.vm.inputs().iter().enumerate() {
             self.rebuild_input_page(index, &vm.view.get());
@@ -673,6 +815,27 @@ pub(crate) fn present_preferences_dialog(
     general_page.append(&storage_dir_row);
     general_page.append(&storage_error_label);
 
+    let rewind_interval_label = gtk::Label::new(Some("Rewind Interval"));
+    let rewind_interval_combo = gtk::ComboBoxText::new();
+    let rewind_row = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    rewind_row.append(&rewind_interval_label);
+    rewind_row.append(&rewind_interval_combo);
+    general_page.append(&rewind_row);
+
+    let fast_forward_label = gtk::Label::new(Some("Fast-forward Speed"));
+    let fast_forward_combo = gtk::ComboBoxText::new();
+    let fast_forward_row = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    fast_forward_row.append(&fast_forward_label);
+    fast_forward_row.append(&fast_forward_combo);
+    general_page.append(&fast_forward_row);
+
+    let slow_motion_label = gtk::Label::new(Some("Slow-motion Speed"));
+    let slow_motion_combo = gtk::ComboBoxText::new();
+    let slow_motion_row = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    slow_motion_row.append(&slow_motion_label);
+    slow_motion_row.append(&slow_motion_combo);
+    general_page.append(&slow_motion_row);
+
     // ---- Video page ----
     let fullscreen_check = gtk::CheckButton::with_label("Fullscreen Default");
     video_page.append(&fullscreen_check);
@@ -770,6 +933,12 @@ pub(crate) fn present_preferences_dialog(
         language_label,
         storage_policy_label,
         storage_dir_label,
+        rewind_interval_combo: rewind_interval_combo.clone(),
+        rewind_interval_label,
+        fast_forward_combo: fast_forward_combo.clone(),
+        fast_forward_label,
+        slow_motion_combo: slow_motion_combo.clone(),
+        slow_motion_label,
     };
     let video_w = VideoWidgets {
         fullscreen_check: fullscreen_check.clone(),
@@ -855,6 +1024,48 @@ pub(crate) fn present_preferences_dialog(
             cmd(&b, b.vm.general.set_storage_directory(path));
         }
     });
+    rewind_interval_combo.connect_changed({
+        let w = weak_handler(&_binding);
+        move |combo| {
+            let Some(b) = w.upgrade() else { return };
+            if b.refreshing.get() {
+                return;
+            }
+            if let Some(frames) = combo.active_id().and_then(|v| v.parse::<u32>().ok()) {
+                cmd(&b, b.vm.general.set_rewind_interval(frames));
+            }
+        }
+    });
+    fast_forward_combo.connect_changed({
+        let w = weak_handler(&_binding);
+        move |combo| {
+            let Some(b) = w.upgrade() else { return };
+            if b.refreshing.get() {
+                return;
+            }
+            let Some(id) = combo.active_id() else { return };
+            let percent = match id.as_str() {
+                FAST_FORWARD_UNLIMITED_ID => None,
+                id => match id.parse::<u16>() {
+                    Ok(percent) => Some(percent),
+                    Err(_) => return,
+                },
+            };
+            cmd(&b, b.vm.general.set_fast_forward(percent));
+        }
+    });
+    slow_motion_combo.connect_changed({
+        let w = weak_handler(&_binding);
+        move |combo| {
+            let Some(b) = w.upgrade() else { return };
+            if b.refreshing.get() {
+                return;
+            }
+            if let Some(percent) = combo.active_id().and_then(|v| v.parse::<u16>().ok()) {
+                cmd(&b, b.vm.general.set_slow_motion(percent));
+            }
+        }
+    });
     fullscreen_check.connect_toggled({
         let w = weak_handler(&_binding);
         move |button| {
@@ -1024,6 +1235,13 @@ fn labeled_row(label: &str, widget: &impl IsA<gtk::Widget>) -> gtk::Box {
     row
 }
 
+/// Combo id of the uncapped fast-forward choice.
+const FAST_FORWARD_UNLIMITED_ID: &str = "unlimited";
+
+fn fast_forward_id(percent: Option<u16>) -> String {
+    percent.map_or_else(|| FAST_FORWARD_UNLIMITED_ID.to_string(), |p| p.to_string())
+}
+
 fn clear_box(container: &gtk::Box) {
     while let Some(child) = container.first_child() {
         container.remove(&child);
@@ -1364,6 +1582,12 @@ mod tests {
                     language_label: gtk::Label::new(None),
                     storage_policy_label: gtk::Label::new(None),
                     storage_dir_label: gtk::Label::new(None),
+                    rewind_interval_combo: gtk::ComboBoxText::new(),
+                    rewind_interval_label: gtk::Label::new(None),
+                    fast_forward_combo: gtk::ComboBoxText::new(),
+                    fast_forward_label: gtk::Label::new(None),
+                    slow_motion_combo: gtk::ComboBoxText::new(),
+                    slow_motion_label: gtk::Label::new(None),
                 },
                 video: VideoWidgets {
                     fullscreen_check: gtk::CheckButton::new(),
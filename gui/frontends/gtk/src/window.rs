@@ -7,9 +7,12 @@ use gtk::{
 };
 use nerust_gui_runtime::slots::slot_label;
 use nerust_gui_settings::{input::ShortcutAction, local::ScalingMode};
-use nerust_gui_shell::session::{KeyboardShortcut, SessionError, access::FrontendSession};
+use nerust_gui_shell::session::{
+    KeyboardShortcut, SessionError, access::FrontendSession, commands::SessionCommand,
+};
 use nerust_persistence::model::StateSlotSummary;
 use nerust_render_traits::renderer::GpuFactory;
+use nerust_settings_core::i18n::{UiText, text};
 
 use super::{
     State, TITLE_UPDATE_INTERVAL, build_menu_model,
@@ -62,12 +65,14 @@ pub(crate) trait WindowExtend {
     fn close_request(&self) -> bool;
     fn open(&self);
     fn load_path(&self, path: &Path);
+    fn choose_archive_entry(&self, path: &Path, candidates: Vec<String>);
     fn close(&self);
     fn update_actions(&self);
     fn rebuild_menubar(&self);
     fn refresh_title(&self);
     fn sync_fullscreen_from_settings(&self);
     fn key_event(&self, key: gdk::Key, enevt: KeyEventState) -> bool;
+    fn pointer_event(&self, f: impl FnOnce(&mut State, (f64, f64)));
     fn apply_keyboard_shortcut(&self, shortcut: KeyboardShortcut);
 }
 
@@ -173,6 +178,41 @@ impl WindowExtend for Window {
         }
         window.add_controller(key_controller);
 
+        // Light guns aim with the mouse and fire with the primary button.
+        let motion_controller = gtk::EventControllerMotion::new();
+        {
+            let result = result.clone();
+            let _ = motion_controller.connect_motion(move |_, x, y| {
+                result.pointer_event(|state, window| state.move_pointer(Some((x, y)), window));
+            });
+        }
+        {
+            let result = result.clone();
+            let _ = motion_controller.connect_leave(move |_| {
+                result.pointer_event(|state, window| state.move_pointer(None, window));
+            });
+        }
+        window.add_controller(motion_controller);
+
+        let click_gesture = gtk::GestureClick::new();
+        click_gesture.set_button(gdk::BUTTON_PRIMARY);
+        {
+            let result = result.clone();
+            let _ = click_gesture.connect_pressed(move |_, _, x, y| {
+                result.pointer_event(|state, window| {
+                    state.move_pointer(Some((x, y)), window);
+                    state.press_pointer(true, window);
+                });
+            });
+        }
+        {
+            let result = result.clone();
+            let _ = click_gesture.connect_released(move |_, _, _, _| {
+                result.pointer_event(|state, window| state.press_pointer(false, window));
+            });
+        }
+        window.add_controller(click_gesture);
+
         let open_action = gio::SimpleAction::new("open", None);
 
         {
@@ -350,8 +390,51 @@ impl WindowExtend for Window {
     }
 
     fn load_path(&self, path: &Path) {
-        self.state().borrow_mut().load_path(path);
+        let candidates = self.state().borrow_mut().load_path(path, None);
         self.update_actions();
+        if let Some(candidates) = candidates {
+            self.choose_archive_entry(path, candidates);
+        }
+    }
+
+    fn choose_archive_entry(&self, path: &Path, candidates: Vec<String>) {
+        let language = self
+            .state()
+            .borrow()
+            .settings_snapshot()
+            .shared
+            .general
+            .language;
+        let dialog = gtk::Dialog::builder()
+            .transient_for(&self.window())
+            .modal(true)
+            .title(text(language, UiText::ChooseRomInArchive))
+            .build();
+        let _ = dialog.add_button(text(language, UiText::Cancel), gtk::ResponseType::Cancel);
+        let _ = dialog.add_button(text(language, UiText::Ok), gtk::ResponseType::Ok);
+        dialog.set_default_response(gtk::ResponseType::Ok);
+
+        let names: Vec<&str> = candidates.iter().map(String::as_str).collect();
+        let drop_down = gtk::DropDown::from_strings(&names);
+        let content = dialog.content_area();
+        content.set_margin_start(12);
+        content.set_margin_end(12);
+        content.set_margin_top(12);
+        content.set_margin_bottom(12);
+        content.append(&drop_down);
+
+        let result = self.clone();
+        let path = path.to_path_buf();
+        let _ = dialog.connect_response(move |dialog, response| {
+            if response == gtk::ResponseType::Ok
+                && let Some(entry) = candidates.get(drop_down.selected() as usize)
+            {
+                let _ = result.state().borrow_mut().load_path(&path, Some(entry));
+                result.update_actions();
+            }
+            dialog.close();
+        });
+        dialog.present();
     }
 
     fn close(&self) {
@@ -495,6 +578,12 @@ impl WindowExtend for Window {
         false
     }
 
+    fn pointer_event(&self, f: impl FnOnce(&mut State, (f64, f64))) {
+        let window = self.window();
+        let size = (f64::from(window.width()), f64::from(window.height()));
+        f(&mut self.state().borrow_mut(), size);
+    }
+
     fn apply_keyboard_shortcut(&self, shortcut: KeyboardShortcut) {
         match shortcut {
             KeyboardShortcut::Session(action) => match action {
@@ -519,6 +608,38 @@ impl WindowExtend for Window {
                 ShortcutAction::ToggleFullscreen => {
                     toggle_window_fullscreen(self);
                 }
+                ShortcutAction::ToggleFastForward => {
+                    self.state()
+                        .borrow_mut()
+                        .run_command(SessionCommand::ToggleFastForward);
+                }
+                ShortcutAction::ToggleSlowMotion => {
+                    self.state()
+                        .borrow_mut()
+                        .run_command(SessionCommand::ToggleSlowMotion);
+                }
+                ShortcutAction::EjectDisk => {
+                    self.state()
+                        .borrow_mut()
+                        .run_command(SessionCommand::EjectDisk);
+                }
+                ShortcutAction::SwitchDiskSide => {
+                    self.state()
+                        .borrow_mut()
+                        .run_command(SessionCommand::SwitchDiskSide);
+                }
+                ShortcutAction::NextTrack => {
+                    self.state()
+                        .borrow_mut()
+                        .run_command(SessionCommand::NextTrack);
+                }
+                ShortcutAction::PreviousTrack => {
+                    self.state()
+                        .borrow_mut()
+                        .run_command(SessionCommand::PreviousTrack);
+                }
+                // 押しっぱなし系はセッションが押下/解放を直接処理する
+                ShortcutAction::Rewind | ShortcutAction::FastForward => {}
             },
             KeyboardShortcut::ToggleFullscreen => {
                 toggle_window_fullscreen(self);
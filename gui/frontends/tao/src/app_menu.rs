@@ -7,6 +7,9 @@ use tao::window::Window as TaoWindow;
 pub(crate) enum MenuCommand {
     Open,
     Settings,
+    Debugger,
+    PpuViewer,
+    MemoryViewer,
     Session(SessionCommand),
     Quit,
 }
@@ -65,6 +68,9 @@ pub(crate) mod imp {
         pause: MenuItem,
         resume: MenuItem,
         reset: MenuItem,
+        debugger: MenuItem,
+        ppu_viewer: MenuItem,
+        memory_viewer: MenuItem,
         quit: MenuItem,
         create_slot: MenuItem,
         save_active: MenuItem,
@@ -111,6 +117,9 @@ pub(crate) mod imp {
             let pause = MenuItem::new("Pause", true, None);
             let resume = MenuItem::new("Resume", false, None);
             let reset = MenuItem::new("Reset", true, None);
+            let debugger = MenuItem::new("Debugger...", false, None);
+            let ppu_viewer = MenuItem::new("PPU Viewer...", false, None);
+            let memory_viewer = MenuItem::new("Memory Viewer...", false, None);
             let quit = MenuItem::new("Quit", true, None);
             let create_slot = MenuItem::new("Create New Slot", true, None);
             let save_active = MenuItem::new("Save Active Slot (F5)", true, None);
@@ -121,6 +130,9 @@ pub(crate) mod imp {
             let pause_id = pause.id().clone();
             let resume_id = resume.id().clone();
             let reset_id = reset.id().clone();
+            let debugger_id = debugger.id().clone();
+            let ppu_viewer_id = ppu_viewer.id().clone();
+            let memory_viewer_id = memory_viewer.id().clone();
             let quit_id = quit.id().clone();
             let create_slot_id = create_slot.id().clone();
             let save_active_id = save_active.id().clone();
@@ -141,6 +153,9 @@ pub(crate) mod imp {
             emulation_menu.append(&pause).unwrap();
             emulation_menu.append(&resume).unwrap();
             emulation_menu.append(&reset).unwrap();
+            emulation_menu.append(&debugger).unwrap();
+            emulation_menu.append(&ppu_viewer).unwrap();
+            emulation_menu.append(&memory_viewer).unwrap();
             emulation_menu.append(&state_menu).unwrap();
 
             menu_bar.append(&file_menu).unwrap();
@@ -157,6 +172,12 @@ pub(crate) mod imp {
                     Some(MenuCommand::Session(SessionCommand::Resume))
                 } else if event.id() == &reset_id {
                     Some(MenuCommand::Session(SessionCommand::Reset))
+                } else if event.id() == &debugger_id {
+                    Some(MenuCommand::Debugger)
+                } else if event.id() == &ppu_viewer_id {
+                    Some(MenuCommand::PpuViewer)
+                } else if event.id() == &memory_viewer_id {
+                    Some(MenuCommand::MemoryViewer)
                 } else if event.id() == &quit_id {
                     Some(MenuCommand::Quit)
                 } else if event.id() == &create_slot_id {
@@ -189,6 +210,9 @@ pub(crate) mod imp {
                 pause,
                 resume,
                 reset,
+                debugger,
+                ppu_viewer,
+                memory_viewer,
                 quit,
                 create_slot,
                 save_active,
@@ -242,6 +266,9 @@ pub(crate) mod imp {
             self.settings.set_enabled(!settings_open);
             self.pause.set_enabled(!settings_open && loaded && !paused);
             self.resume.set_enabled(!settings_open && loaded && paused);
+            self.debugger.set_enabled(!settings_open && loaded);
+            self.ppu_viewer.set_enabled(!settings_open && loaded);
+            self.memory_viewer.set_enabled(!settings_open && loaded);
             self.create_slot.set_enabled(!settings_open && loaded);
             self.save_active.set_enabled(!settings_open && loaded);
             self.load_active
@@ -319,6 +346,12 @@ pub(crate) mod imp {
             self.pause.set_text(text(language, UiText::Pause));
             self.resume.set_text(text(language, UiText::Resume));
             self.reset.set_text(text(language, UiText::Reset));
+            self.debugger
+                .set_text(format!("{}...", text(language, UiText::Debugger)));
+            self.ppu_viewer
+                .set_text(format!("{}...", text(language, UiText::PpuViewer)));
+            self.memory_viewer
+                .set_text(format!("{}...", text(language, UiText::MemoryViewer)));
             self.quit.set_text(text(language, UiText::Quit));
             self.create_slot
                 .set_text(text(language, UiText::CreateSaveSlot));
@@ -0,0 +1,680 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use iced::{
+    Length, Size,
+    advanced::renderer,
+    alignment::Alignment,
+    keyboard, mouse, theme,
+    widget::{
+        Column, button, checkbox, column, container, pick_list, row, scrollable, text, text_input,
+    },
+};
+use iced_winit::{
+    Clipboard,
+    graphics::Viewport,
+    runtime::user_interface::{Cache, UserInterface},
+};
+use nerust_core_traits::debug::{
+    BreakReason, Breakpoint, BreakpointKind, DebugOp, DebugState, StepMode,
+};
+
+#[cfg(target_os = "macos")]
+use tao::platform::macos::WindowBuilderExtMacOS;
+use tao::{
+    event_loop::EventLoopWindowTarget,
+    window::{Window as TaoWindow, WindowBuilder},
+};
+
+use crate::settings_window::{SettingsRenderer, convert_tao_window_event};
+
+type El<'a> = iced::Element<'a, Message, iced::Theme, iced_tiny_skia::Renderer>;
+
+const KIND_CHOICES: [KindChoice; 5] = [
+    KindChoice(BreakpointKind::Execute),
+    KindChoice(BreakpointKind::Read),
+    KindChoice(BreakpointKind::Write),
+    KindChoice(BreakpointKind::Irq),
+    KindChoice(BreakpointKind::Nmi),
+];
+
+/// `BreakpointKind` with a label for the pick list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct KindChoice(BreakpointKind);
+
+impl std::fmt::Display for KindChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self.0 {
+            BreakpointKind::Execute => "Execute",
+            BreakpointKind::Read => "Read",
+            BreakpointKind::Write => "Write",
+            BreakpointKind::Irq => "IRQ",
+            BreakpointKind::Nmi => "NMI",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Message {
+    Continue,
+    Pause,
+    Refresh,
+    Step(StepMode),
+    SetScanline(String),
+    RunToScanline,
+    SetRegisterInput(&'static str, String),
+    CommitRegister(&'static str),
+    ToggleFlag(&'static str, bool),
+    SelectKind(KindChoice),
+    SetStart(String),
+    SetEnd(String),
+    SetCondition(String),
+    AddBreakpoint,
+    ToggleBreakpoint(usize, bool),
+    RemoveBreakpoint(usize),
+}
+
+/// Work the window hands to the host, which owns the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DebuggerRequest {
+    Op(DebugOp),
+    Continue,
+    Pause,
+}
+
+/// Debugger window contents. Kept apart from the Tao window so the update
+/// logic can be tested without a display.
+#[derive(Debug, Default)]
+pub(crate) struct DebuggerView {
+    state: Option<DebugState>,
+    stop_reason: Option<BreakReason>,
+    error: Option<String>,
+    register_inputs: Vec<(&'static str, String)>,
+    scanline_input: String,
+    /// Breakpoints the core accepted last. Restored when the core rejects an edit.
+    applied: Vec<Breakpoint>,
+    breakpoints: Vec<Breakpoint>,
+    kind: Option<KindChoice>,
+    start_input: String,
+    end_input: String,
+    condition_input: String,
+    requests: Vec<DebuggerRequest>,
+}
+
+impl DebuggerView {
+    pub(crate) fn update(&mut self, message: Message) {
+        match message {
+            Message::Continue => {
+                self.stop_reason = None;
+                self.requests.push(DebuggerRequest::Continue);
+            }
+            Message::Pause => self.requests.push(DebuggerRequest::Pause),
+            Message::Refresh => self.requests.push(DebuggerRequest::Op(DebugOp::Inspect)),
+            Message::Step(mode) => self.step(mode),
+            Message::SetScanline(value) => self.scanline_input = value,
+            Message::RunToScanline => match self.scanline_input.trim().parse::<u16>() {
+                Ok(line) => self.step(StepMode::Scanline(line)),
+                Err(_) => self.error = Some(format!("invalid scanline: {}", self.scanline_input)),
+            },
+            Message::SetRegisterInput(name, value) => {
+                if let Some((_, input)) = self.register_inputs.iter_mut().find(|(n, _)| *n == name)
+                {
+                    *input = value;
+                }
+            }
+            Message::CommitRegister(name) => {
+                let Some((_, input)) = self.register_inputs.iter().find(|(n, _)| *n == name) else {
+                    return;
+                };
+                match parse_hex(input) {
+                    Some(value) => self.set_register(name, value),
+                    None => self.error = Some(format!("invalid value for {name}: {input}")),
+                }
+            }
+            Message::ToggleFlag(name, set) => self.set_register(name, u32::from(set)),
+            Message::SelectKind(kind) => self.kind = Some(kind),
+            Message::SetStart(value) => self.start_input = value,
+            Message::SetEnd(value) => self.end_input = value,
+            Message::SetCondition(value) => self.condition_input = value,
+            Message::AddBreakpoint => self.add_breakpoint(),
+            Message::ToggleBreakpoint(index, enabled) => {
+                if let Some(breakpoint) = self.breakpoints.get_mut(index) {
+                    breakpoint.enabled = enabled;
+                    self.send_breakpoints();
+                }
+            }
+            Message::RemoveBreakpoint(index) => {
+                if index < self.breakpoints.len() {
+                    self.breakpoints.remove(index);
+                    self.send_breakpoints();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn take_requests(&mut self) -> Vec<DebuggerRequest> {
+        std::mem::take(&mut self.requests)
+    }
+
+    /// Shows the core's reply to `op`.
+    pub(crate) fn apply_reply(&mut self, op: &DebugOp, reply: Result<DebugState, String>) {
+        match reply {
+            Ok(state) => {
+                if matches!(op, DebugOp::SetBreakpoints(_)) {
+                    self.applied = self.breakpoints.clone();
+                }
+                self.error = None;
+                self.show_state(state);
+            }
+            Err(error) => {
+                if matches!(op, DebugOp::SetBreakpoints(_)) {
+                    self.breakpoints = self.applied.clone();
+                }
+                self.error = Some(error);
+            }
+        }
+    }
+
+    pub(crate) fn show_break(&mut self, reason: BreakReason) {
+        self.stop_reason = Some(reason);
+    }
+
+    pub(crate) fn has_breakpoints(&self) -> bool {
+        !self.applied.is_empty()
+    }
+
+    /// Re-sends the breakpoints after the core was replaced by a ROM load.
+    pub(crate) fn resync(&mut self) {
+        self.breakpoints = self.applied.clone();
+        if !self.breakpoints.is_empty() {
+            self.send_breakpoints();
+        }
+        self.requests.push(DebuggerRequest::Op(DebugOp::Inspect));
+    }
+
+    fn show_state(&mut self, state: DebugState) {
+        self.register_inputs = state
+            .registers
+            .iter()
+            .map(|register| (register.name, format_hex(register.value, register.bits)))
+            .collect();
+        self.state = Some(state);
+    }
+
+    fn step(&mut self, mode: StepMode) {
+        self.stop_reason = None;
+        self.requests.push(DebuggerRequest::Op(DebugOp::Step(mode)));
+    }
+
+    fn set_register(&mut self, name: &'static str, value: u32) {
+        self.requests
+            .push(DebuggerRequest::Op(DebugOp::SetRegister {
+                name: name.to_string(),
+                value,
+            }));
+    }
+
+    fn add_breakpoint(&mut self) {
+        let kind = self.kind.map_or(BreakpointKind::Execute, |choice| choice.0);
+        let watches_address = !matches!(kind, BreakpointKind::Irq | BreakpointKind::Nmi);
+        let (start, end) = if watches_address {
+            let Some(start) = parse_hex(&self.start_input) else {
+                self.error = Some(format!("invalid address: {}", self.start_input));
+                return;
+            };
+            let end = if self.end_input.trim().is_empty() {
+                start
+            } else {
+                match parse_hex(&self.end_input) {
+                    Some(end) if end >= start => end,
+                    _ => {
+                        self.error = Some(format!("invalid end address: {}", self.end_input));
+                        return;
+                    }
+                }
+            };
+            (start, end)
+        } else {
+            (0, 0)
+        };
+        self.breakpoints
+            .push(Breakpoint::range(kind, start, end).with_condition(self.condition_input.trim()));
+        self.start_input.clear();
+        self.end_input.clear();
+        self.condition_input.clear();
+        self.send_breakpoints();
+    }
+
+    fn send_breakpoints(&mut self) {
+        self.requests
+            .push(DebuggerRequest::Op(DebugOp::SetBreakpoints(
+                self.breakpoints.clone(),
+            )));
+    }
+
+    fn view(&self) -> El<'_> {
+        let controls = row![
+            button("Continue").on_press(Message::Continue),
+            button("Pause").on_press(Message::Pause),
+            button("Step").on_press(Message::Step(StepMode::Instruction)),
+            button("Step Over").on_press(Message::Step(StepMode::Over)),
+            button("Step Out").on_press(Message::Step(StepMode::Out)),
+            text_input("Scanline", &self.scanline_input)
+                .on_input(Message::SetScanline)
+                .on_submit(Message::RunToScanline)
+                .width(Length::Fixed(90.0)),
+            button("Run to Scanline").on_press(Message::RunToScanline),
+            button("Refresh").on_press(Message::Refresh),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let mut root = column![controls].spacing(16).padding(16);
+        root = root.push(text(self.status_line()));
+        if let Some(error) = self.error.as_ref() {
+            root = root.push(text(error.clone()));
+        }
+        if let Some(state) = self.state.as_ref() {
+            root = root.push(self.registers_view(state));
+        }
+        root = root.push(self.breakpoints_view());
+
+        scrollable(container(root).width(Length::Fill))
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn status_line(&self) -> String {
+        let stop = match self.stop_reason {
+            Some(BreakReason::Breakpoint { index, address }) => {
+                format!("Stopped at breakpoint #{index} (${address:04X})")
+            }
+            Some(BreakReason::Step) => "Step finished".to_string(),
+            None => "Running or paused".to_string(),
+        };
+        match self.state.as_ref() {
+            Some(state) => format!(
+                "{stop}  |  PC ${:04X}  scanline {}  dot {}  frame {}  cycle {}",
+                state.pc, state.scanline, state.dot, state.frame, state.cycle
+            ),
+            None => stop,
+        }
+    }
+
+    fn registers_view<'a>(&'a self, state: &'a DebugState) -> El<'a> {
+        let registers = row(self.register_inputs.iter().map(|(name, input)| {
+            let name = *name;
+            row![
+                text(name),
+                text_input("", input)
+                    .on_input(move |value| Message::SetRegisterInput(name, value))
+                    .on_submit(Message::CommitRegister(name))
+                    .width(Length::Fixed(64.0)),
+            ]
+            .spacing(4)
+            .align_y(Alignment::Center)
+            .into()
+        }))
+        .spacing(12);
+        let flags = row(state.flags.iter().map(|flag| {
+            let name = flag.name;
+            checkbox(flag.set)
+                .label(name)
+                .on_toggle(move |set| Message::ToggleFlag(name, set))
+                .into()
+        }))
+        .spacing(12);
+        column![text("Registers (hex, Enter to apply)"), registers, flags]
+            .spacing(8)
+            .into()
+    }
+
+    fn breakpoints_view(&self) -> El<'_> {
+        let editor = row![
+            pick_list(
+                KIND_CHOICES,
+                Some(self.kind.unwrap_or(KindChoice(BreakpointKind::Execute))),
+                Message::SelectKind,
+            ),
+            text_input("Start", &self.start_input)
+                .on_input(Message::SetStart)
+                .width(Length::Fixed(80.0)),
+            text_input("End", &self.end_input)
+                .on_input(Message::SetEnd)
+                .width(Length::Fixed(80.0)),
+            text_input("Condition (e.g. a == $10 && x < 4)", &self.condition_input)
+                .on_input(Message::SetCondition)
+                .on_submit(Message::AddBreakpoint)
+                .width(Length::Fill),
+            button("Add").on_press(Message::AddBreakpoint),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let mut list = Column::new().spacing(6);
+        for (index, breakpoint) in self.breakpoints.iter().enumerate() {
+            list = list.push(
+                row![
+                    checkbox(breakpoint.enabled)
+                        .label(breakpoint_label(index, breakpoint))
+                        .on_toggle(move |enabled| Message::ToggleBreakpoint(index, enabled)),
+                    button("Remove").on_press(Message::RemoveBreakpoint(index)),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center),
+            );
+        }
+        column![text("Breakpoints"), editor, list].spacing(8).into()
+    }
+}
+
+pub(crate) struct DebuggerWindowHandle {
+    pub(crate) window: Arc<TaoWindow>,
+    view: DebuggerView,
+    cache: Option<Cache>,
+    renderer: SettingsRenderer,
+    viewport_physical: (u32, u32),
+    scale_factor: f32,
+    modifiers: keyboard::Modifiers,
+    should_close: Arc<AtomicBool>,
+    cursor: mouse::Cursor,
+    clipboard: Clipboard,
+}
+
+impl DebuggerWindowHandle {
+    pub(crate) fn new(
+        event_loop: &EventLoopWindowTarget<crate::app_menu::UserEvent>,
+    ) -> Option<Self> {
+        #[cfg_attr(not(target_os = "macos"), expect(unused_mut))]
+        let mut wb = WindowBuilder::new()
+            .with_title("Debugger")
+            .with_inner_size(tao::dpi::LogicalSize::new(900.0, 600.0));
+        #[cfg(target_os = "macos")]
+        {
+            wb = wb.with_automatic_window_tabbing(false);
+        }
+        let window = Arc::new(match wb.build(event_loop) {
+            Ok(w) => w,
+            Err(e) => {
+                log::error!("failed to create debugger window: {e}");
+                return None;
+            }
+        });
+        let window_size = window.inner_size();
+        let renderer = SettingsRenderer::new(&window);
+        window.request_redraw();
+
+        Some(Self {
+            scale_factor: window.scale_factor() as f32,
+            window,
+            view: DebuggerView::default(),
+            cache: Some(Cache::default()),
+            renderer,
+            viewport_physical: (window_size.width, window_size.height),
+            modifiers: keyboard::Modifiers::default(),
+            should_close: Arc::new(AtomicBool::new(false)),
+            cursor: mouse::Cursor::default(),
+            clipboard: Clipboard::unconnected(),
+        })
+    }
+
+    pub(crate) fn view(&self) -> &DebuggerView {
+        &self.view
+    }
+
+    pub(crate) fn view_mut(&mut self) -> &mut DebuggerView {
+        &mut self.view
+    }
+
+    pub(crate) fn should_close(&self) -> bool {
+        self.should_close.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn handle_tao_event(&mut self, event: tao::event::WindowEvent) {
+        match &event {
+            tao::event::WindowEvent::Resized(size) => self.resize(size.width, size.height),
+            tao::event::WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor as f32;
+            }
+            _ => {}
+        }
+        let Some(mapped) = convert_tao_window_event(
+            event,
+            &mut self.cursor,
+            self.scale_factor,
+            &mut self.modifiers,
+            &self.should_close,
+        ) else {
+            return;
+        };
+
+        // 表示が小さいので、イベントごとに UI を組み直す
+        let mut messages = Vec::new();
+        let mut ui = build_ui(
+            &self.view,
+            self.viewport().logical_size(),
+            self.cache.take().unwrap_or_default(),
+            &mut self.renderer.backend,
+        );
+        let _ = ui.update(
+            &[mapped],
+            self.cursor,
+            &mut self.renderer.backend,
+            &mut self.clipboard,
+            &mut messages,
+        );
+        self.cache = Some(ui.into_cache());
+        for message in messages {
+            self.view.update(message);
+        }
+        self.window.request_redraw();
+    }
+
+    pub(crate) fn render(&mut self) {
+        let theme = iced::Theme::Dark;
+        let style = <iced::Theme as theme::Base>::base(&theme);
+        let vp = self.viewport();
+
+        let redraw_event = iced::Event::Window(iced::window::Event::RedrawRequested(
+            std::time::Instant::now(),
+        ));
+        let mut ui = build_ui(
+            &self.view,
+            vp.logical_size(),
+            self.cache.take().unwrap_or_default(),
+            &mut self.renderer.backend,
+        );
+        let _ = ui.update(
+            &[redraw_event],
+            self.cursor,
+            &mut self.renderer.backend,
+            &mut self.clipboard,
+            &mut Vec::new(),
+        );
+        ui.draw(
+            &mut self.renderer.backend,
+            &theme,
+            &renderer::Style {
+                text_color: style.text_color,
+            },
+            self.cursor,
+        );
+        self.cache = Some(ui.into_cache());
+
+        if let Err(e) = self.renderer.present(&vp, iced::Color::BLACK) {
+            log::warn!("debugger render present failed: {e:?}");
+        }
+    }
+
+    fn viewport(&self) -> Viewport {
+        Viewport::with_physical_size(
+            Size::new(self.viewport_physical.0, self.viewport_physical.1),
+            self.scale_factor,
+        )
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.viewport_physical = (width, height);
+        self.renderer.resize(width, height);
+    }
+}
+
+fn build_ui<'a>(
+    view: &'a DebuggerView,
+    bounds: Size,
+    cache: Cache,
+    renderer: &mut iced_tiny_skia::Renderer,
+) -> UserInterface<'a, Message, iced::Theme, iced_tiny_skia::Renderer> {
+    UserInterface::build(view.view(), bounds, cache, renderer)
+}
+
+fn breakpoint_label(index: usize, breakpoint: &Breakpoint) -> String {
+    let kind = KindChoice(breakpoint.kind);
+    let range = match breakpoint.kind {
+        BreakpointKind::Irq | BreakpointKind::Nmi => String::new(),
+        _ if breakpoint.start == breakpoint.end => format!(" ${:04X}", breakpoint.start),
+        _ => format!(" ${:04X}-${:04X}", breakpoint.start, breakpoint.end),
+    };
+    if breakpoint.condition.is_empty() {
+        format!("#{index} {kind}{range}")
+    } else {
+        format!("#{index} {kind}{range} if {}", breakpoint.condition)
+    }
+}
+
+fn format_hex(value: u32, bits: u8) -> String {
+    format!("{value:0width$X}", width = usize::from(bits.div_ceil(4)))
+}
+
+/// Parses `$1234`, `0x1234` or `1234` as hexadecimal.
+pub(crate) fn parse_hex(input: &str) -> Option<u32> {
+    let input = input.trim();
+    let digits = input
+        .strip_prefix('$')
+        .or_else(|| input.strip_prefix("0x"))
+        .or_else(|| input.strip_prefix("0X"))
+        .unwrap_or(input);
+    u32::from_str_radix(digits, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_core_traits::debug::RegisterValue;
+
+    use super::*;
+
+    fn state() -> DebugState {
+        DebugState {
+            pc: 0x8000,
+            registers: vec![
+                RegisterValue {
+                    name: "PC",
+                    value: 0x8000,
+                    bits: 16,
+                },
+                RegisterValue {
+                    name: "A",
+                    value: 0x0F,
+                    bits: 8,
+                },
+            ],
+            flags: Vec::new(),
+            scanline: 0,
+            dot: 0,
+            frame: 0,
+            cycle: 0,
+        }
+    }
+
+    #[test]
+    fn add_breakpoint_parses_range_and_sends_list() {
+        let mut view = DebuggerView::default();
+        view.update(Message::SelectKind(KindChoice(BreakpointKind::Write)));
+        view.update(Message::SetStart("$2000".into()));
+        view.update(Message::SetEnd("0x2007".into()));
+        view.update(Message::SetCondition("value == 0x80".into()));
+        view.update(Message::AddBreakpoint);
+
+        let expected = Breakpoint::range(BreakpointKind::Write, 0x2000, 0x2007)
+            .with_condition("value == 0x80");
+        assert_eq!(
+            view.take_requests(),
+            vec![DebuggerRequest::Op(DebugOp::SetBreakpoints(vec![expected]))]
+        );
+        assert!(view.start_input.is_empty());
+    }
+
+    #[test]
+    fn invalid_address_reports_error_without_request() {
+        let mut view = DebuggerView::default();
+        view.update(Message::SetStart("zz".into()));
+        view.update(Message::AddBreakpoint);
+        assert!(view.take_requests().is_empty());
+        assert!(view.error.is_some());
+    }
+
+    #[test]
+    fn rejected_breakpoints_restore_applied_list() {
+        let mut view = DebuggerView::default();
+        view.update(Message::SetStart("8000".into()));
+        view.update(Message::AddBreakpoint);
+        let requests = view.take_requests();
+        let DebuggerRequest::Op(op) = &requests[0] else {
+            panic!("expected a debugger op");
+        };
+        view.apply_reply(op, Ok(state()));
+        assert!(view.has_breakpoints());
+
+        view.update(Message::SetStart("9000".into()));
+        view.update(Message::SetCondition("a ==".into()));
+        view.update(Message::AddBreakpoint);
+        let requests = view.take_requests();
+        let DebuggerRequest::Op(op) = &requests[0] else {
+            panic!("expected a debugger op");
+        };
+        view.apply_reply(op, Err("bad condition".into()));
+        assert_eq!(view.breakpoints.len(), 1);
+        assert_eq!(view.error.as_deref(), Some("bad condition"));
+    }
+
+    #[test]
+    fn register_edit_sends_parsed_value() {
+        let mut view = DebuggerView::default();
+        view.apply_reply(&DebugOp::Inspect, Ok(state()));
+        assert_eq!(view.register_inputs[0], ("PC", "8000".to_string()));
+        assert_eq!(view.register_inputs[1], ("A", "0F".to_string()));
+
+        view.update(Message::SetRegisterInput("A", "$42".into()));
+        view.update(Message::CommitRegister("A"));
+        view.update(Message::ToggleFlag("C", true));
+        assert_eq!(
+            view.take_requests(),
+            vec![
+                DebuggerRequest::Op(DebugOp::SetRegister {
+                    name: "A".into(),
+                    value: 0x42,
+                }),
+                DebuggerRequest::Op(DebugOp::SetRegister {
+                    name: "C".into(),
+                    value: 1,
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn run_to_scanline_requires_a_number() {
+        let mut view = DebuggerView::default();
+        view.update(Message::SetScanline("241".into()));
+        view.update(Message::RunToScanline);
+        view.update(Message::SetScanline("vblank".into()));
+        view.update(Message::RunToScanline);
+        assert_eq!(
+            view.take_requests(),
+            vec![DebuggerRequest::Op(DebugOp::Step(StepMode::Scanline(241)))]
+        );
+        assert!(view.error.is_some());
+    }
+}
@@ -1,4 +1,7 @@
 mod app_menu;
+pub(crate) mod debugger_window;
+pub(crate) mod memory_viewer_window;
+pub(crate) mod ppu_viewer_window;
 pub(crate) mod settings;
 pub mod settings_window;
 mod tao_conversions;
@@ -0,0 +1,800 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use iced::{
+    Length, Size,
+    advanced::renderer,
+    alignment::Alignment,
+    keyboard, mouse, theme,
+    widget::{
+        Column, Row, button, column, container, pick_list, row, scrollable, text, text_input,
+    },
+};
+use iced_winit::{
+    Clipboard,
+    graphics::Viewport,
+    runtime::user_interface::{Cache, UserInterface},
+};
+use nerust_core_traits::debug::{MemoryBlock, MemoryOp, MemorySpace};
+use nerust_gui_shell::ram_search::{self, RamSearch, SearchFilter};
+
+#[cfg(target_os = "macos")]
+use tao::platform::macos::WindowBuilderExtMacOS;
+use tao::{
+    event_loop::EventLoopWindowTarget,
+    window::{Window as TaoWindow, WindowBuilder},
+};
+
+use crate::{
+    debugger_window::parse_hex,
+    settings_window::{SettingsRenderer, convert_tao_window_event},
+};
+
+type El<'a> = iced::Element<'a, Message, iced::Theme, iced_tiny_skia::Renderer>;
+
+const BYTES_PER_ROW: u32 = 16;
+const PAGE_LEN: u32 = 0x100;
+/// Work RAM and cartridge RAM on the CPU bus, where game state lives. Mirrors
+/// of work RAM are left out so every byte is a candidate once.
+const SEARCH_RANGES: [(u32, u32); 2] = [(0x0000, 0x0800), (0x6000, 0x2000)];
+/// Candidates listed at most; the count is always shown.
+const CANDIDATE_LIMIT: usize = 200;
+
+const SPACE_CHOICES: [SpaceChoice; 5] = [
+    SpaceChoice(MemorySpace::CpuBus),
+    SpaceChoice(MemorySpace::VideoBus),
+    SpaceChoice(MemorySpace::SpriteRam),
+    SpaceChoice(MemorySpace::ProgramRom),
+    SpaceChoice(MemorySpace::CharacterMemory),
+];
+
+const FILTER_CHOICES: [FilterChoice; 5] = [
+    FilterChoice::Equal,
+    FilterChoice::Changed,
+    FilterChoice::Unchanged,
+    FilterChoice::Greater,
+    FilterChoice::Less,
+];
+
+/// `MemorySpace` with a label for the pick list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SpaceChoice(MemorySpace);
+
+impl std::fmt::Display for SpaceChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self.0 {
+            MemorySpace::CpuBus => "CPU bus",
+            MemorySpace::VideoBus => "PPU bus",
+            MemorySpace::SpriteRam => "OAM",
+            MemorySpace::ProgramRom => "PRG-ROM",
+            MemorySpace::CharacterMemory => "CHR",
+        })
+    }
+}
+
+/// `SearchFilter` without its value, for the pick list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FilterChoice {
+    Equal,
+    Changed,
+    Unchanged,
+    Greater,
+    Less,
+}
+
+impl std::fmt::Display for FilterChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Equal => "Equal to",
+            Self::Changed => "Changed",
+            Self::Unchanged => "Unchanged",
+            Self::Greater => "Greater than before",
+            Self::Less => "Less than before",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Message {
+    SelectSpace(SpaceChoice),
+    SetAddress(String),
+    GoTo,
+    PreviousPage,
+    NextPage,
+    SelectByte(u32),
+    SetEditAddress(String),
+    SetEditBytes(String),
+    Write,
+    NewSearch,
+    SelectFilter(FilterChoice),
+    SetFilterValue(String),
+    ApplyFilter,
+    ClearSearch,
+    Watch(u32),
+    EditWatch(u32),
+    Unwatch(usize),
+    Refresh,
+}
+
+/// Search step waiting for fresh reads of `SEARCH_RANGES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PendingSearch {
+    Start,
+    Filter(SearchFilter),
+}
+
+/// Memory viewer window contents. Kept apart from the Tao window so the update
+/// logic can be tested without a display.
+#[derive(Debug)]
+pub(crate) struct MemoryViewerView {
+    space: MemorySpace,
+    page_start: u32,
+    page: Option<MemoryBlock>,
+    address_input: String,
+    edit_address_input: String,
+    edit_bytes_input: String,
+    search: RamSearch,
+    /// Latest reads of `SEARCH_RANGES`, in the same order.
+    search_blocks: Vec<MemoryBlock>,
+    pending_search: Option<PendingSearch>,
+    filter: FilterChoice,
+    filter_value_input: String,
+    watches: Vec<u32>,
+    error: Option<String>,
+    requests: Vec<MemoryOp>,
+}
+
+impl Default for MemoryViewerView {
+    fn default() -> Self {
+        Self {
+            space: MemorySpace::CpuBus,
+            page_start: 0,
+            page: None,
+            address_input: String::new(),
+            edit_address_input: String::new(),
+            edit_bytes_input: String::new(),
+            search: RamSearch::default(),
+            search_blocks: Vec::new(),
+            pending_search: None,
+            filter: FilterChoice::Equal,
+            filter_value_input: String::new(),
+            watches: Vec::new(),
+            error: None,
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl MemoryViewerView {
+    pub(crate) fn update(&mut self, message: Message) {
+        match message {
+            Message::SelectSpace(choice) => {
+                self.space = choice.0;
+                self.show_page(0);
+            }
+            Message::SetAddress(value) => self.address_input = value,
+            Message::GoTo => match parse_hex(&self.address_input) {
+                Some(address) => self.show_page(address),
+                None => self.error = Some(format!("invalid address: {}", self.address_input)),
+            },
+            Message::PreviousPage => self.show_page(self.page_start.saturating_sub(PAGE_LEN)),
+            Message::NextPage => {
+                let next = self.page_start.saturating_add(PAGE_LEN);
+                if self.page.as_ref().is_none_or(|page| next < page.space_len) {
+                    self.show_page(next);
+                }
+            }
+            Message::SelectByte(address) => {
+                self.edit_address_input = format!("{address:04X}");
+                self.edit_bytes_input = self
+                    .page
+                    .as_ref()
+                    .and_then(|page| page.get(address))
+                    .map(|value| format!("{value:02X}"))
+                    .unwrap_or_default();
+            }
+            Message::SetEditAddress(value) => self.edit_address_input = value,
+            Message::SetEditBytes(value) => self.edit_bytes_input = value,
+            Message::Write => self.write(),
+            Message::NewSearch => self.read_search_ranges(PendingSearch::Start),
+            Message::SelectFilter(choice) => self.filter = choice,
+            Message::SetFilterValue(value) => self.filter_value_input = value,
+            Message::ApplyFilter => self.apply_filter(),
+            Message::ClearSearch => {
+                self.search.clear();
+                self.pending_search = None;
+            }
+            Message::Watch(address) => {
+                if !self.watches.contains(&address) {
+                    self.watches.push(address);
+                }
+            }
+            Message::EditWatch(address) => {
+                self.space = MemorySpace::CpuBus;
+                self.show_page(address);
+                self.update(Message::SelectByte(address));
+                self.edit_bytes_input = ram_search::read(&self.search_blocks, address)
+                    .map(|value| format!("{value:02X}"))
+                    .unwrap_or_default();
+            }
+            Message::Unwatch(index) => {
+                if index < self.watches.len() {
+                    self.watches.remove(index);
+                }
+            }
+            Message::Refresh => self.refresh(),
+        }
+    }
+
+    pub(crate) fn take_requests(&mut self) -> Vec<MemoryOp> {
+        std::mem::take(&mut self.requests)
+    }
+
+    /// Re-reads the page and, while a search or watch list needs them, the
+    /// search ranges.
+    pub(crate) fn refresh(&mut self) {
+        self.read_page();
+        if self.search.is_started() || !self.watches.is_empty() {
+            self.push_search_reads();
+        }
+    }
+
+    /// Shows the core's reply to `op`.
+    pub(crate) fn apply_reply(&mut self, op: &MemoryOp, reply: Result<MemoryBlock, String>) {
+        let block = match reply {
+            Ok(block) => block,
+            Err(error) => {
+                // Don't wait forever for a search range that failed to read.
+                if matches!(op, MemoryOp::Read { .. }) {
+                    self.pending_search = None;
+                }
+                self.error = Some(error);
+                return;
+            }
+        };
+        self.error = None;
+        // A write is always followed by a page read, which is what gets shown.
+        let MemoryOp::Read { space, start, len } = *op else {
+            return;
+        };
+        let search_index = SEARCH_RANGES
+            .iter()
+            .position(|&range| space == MemorySpace::CpuBus && range == (start, len));
+        let Some(index) = search_index else {
+            if space == self.space && start == self.page_start {
+                self.page = Some(block);
+            }
+            return;
+        };
+        if self.search_blocks.len() != SEARCH_RANGES.len() {
+            self.search_blocks = SEARCH_RANGES
+                .iter()
+                .map(|&(start, _)| MemoryBlock {
+                    space: MemorySpace::CpuBus,
+                    start,
+                    bytes: Vec::new(),
+                    space_len: block.space_len,
+                })
+                .collect();
+        }
+        self.search_blocks[index] = block;
+        // Ranges are requested in order, so the last one completes the set.
+        if index + 1 == SEARCH_RANGES.len() {
+            match self.pending_search.take() {
+                Some(PendingSearch::Start) => self.search.start(&self.search_blocks),
+                Some(PendingSearch::Filter(filter)) => {
+                    self.search.filter(filter, &self.search_blocks);
+                }
+                None => {}
+            }
+        }
+    }
+
+    fn show_page(&mut self, address: u32) {
+        self.page_start = address - address % PAGE_LEN;
+        self.page = None;
+        self.read_page();
+    }
+
+    fn read_page(&mut self) {
+        self.requests.push(MemoryOp::Read {
+            space: self.space,
+            start: self.page_start,
+            len: PAGE_LEN,
+        });
+    }
+
+    fn push_search_reads(&mut self) {
+        for (start, len) in SEARCH_RANGES {
+            self.requests.push(MemoryOp::Read {
+                space: MemorySpace::CpuBus,
+                start,
+                len,
+            });
+        }
+    }
+
+    fn read_search_ranges(&mut self, step: PendingSearch) {
+        self.pending_search = Some(step);
+        self.push_search_reads();
+    }
+
+    fn write(&mut self) {
+        let Some(start) = parse_hex(&self.edit_address_input) else {
+            self.error = Some(format!("invalid address: {}", self.edit_address_input));
+            return;
+        };
+        let bytes: Option<Vec<u8>> = self
+            .edit_bytes_input
+            .split_whitespace()
+            .map(|byte| parse_hex(byte).and_then(|value| u8::try_from(value).ok()))
+            .collect();
+        match bytes {
+            Some(bytes) if !bytes.is_empty() => {
+                self.requests.push(MemoryOp::Write {
+                    space: self.space,
+                    start,
+                    bytes,
+                });
+                self.read_page();
+            }
+            _ => self.error = Some(format!("invalid bytes: {}", self.edit_bytes_input)),
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        if !self.search.is_started() {
+            self.error = Some("start a new search first".to_string());
+            return;
+        }
+        let filter = match self.filter {
+            FilterChoice::Equal => match parse_hex(&self.filter_value_input)
+                .and_then(|value| u8::try_from(value).ok())
+            {
+                Some(value) => SearchFilter::Equal(value),
+                None => {
+                    self.error = Some(format!("invalid value: {}", self.filter_value_input));
+                    return;
+                }
+            },
+            FilterChoice::Changed => SearchFilter::Changed,
+            FilterChoice::Unchanged => SearchFilter::Unchanged,
+            FilterChoice::Greater => SearchFilter::Greater,
+            FilterChoice::Less => SearchFilter::Less,
+        };
+        self.read_search_ranges(PendingSearch::Filter(filter));
+    }
+
+    fn view(&self) -> El<'_> {
+        let mut root = column![self.editor_view()].spacing(16).padding(16);
+        if let Some(error) = self.error.as_ref() {
+            root = root.push(text(error.clone()));
+        }
+        root = root.push(self.page_view());
+        root = root.push(self.search_view());
+        root = root.push(self.watch_view());
+
+        scrollable(container(root).width(Length::Fill))
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn editor_view(&self) -> El<'_> {
+        let navigation = row![
+            pick_list(
+                SPACE_CHOICES,
+                Some(SpaceChoice(self.space)),
+                Message::SelectSpace
+            ),
+            text_input("Address", &self.address_input)
+                .on_input(Message::SetAddress)
+                .on_submit(Message::GoTo)
+                .width(Length::Fixed(90.0)),
+            button("Go").on_press(Message::GoTo),
+            button("<").on_press(Message::PreviousPage),
+            button(">").on_press(Message::NextPage),
+            button("Refresh").on_press(Message::Refresh),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+        let edit = row![
+            text_input("Address", &self.edit_address_input)
+                .on_input(Message::SetEditAddress)
+                .width(Length::Fixed(90.0)),
+            text_input("Bytes (hex, e.g. 09 FF)", &self.edit_bytes_input)
+                .on_input(Message::SetEditBytes)
+                .on_submit(Message::Write)
+                .width(Length::Fill),
+            button("Write").on_press(Message::Write),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+        column![navigation, edit].spacing(8).into()
+    }
+
+    fn page_view(&self) -> El<'_> {
+        let Some(page) = self.page.as_ref() else {
+            return text("Reading...").into();
+        };
+        let digits = address_digits(page.space_len);
+        let mut rows = Column::new().spacing(2);
+        for row_start in
+            (page.start..page.start + page.bytes.len() as u32).step_by(BYTES_PER_ROW as usize)
+        {
+            let mut cells = Row::new()
+                .spacing(2)
+                .align_y(Alignment::Center)
+                .push(text(format!("{row_start:0digits$X}")).width(Length::Fixed(56.0)));
+            for address in
+                row_start..(row_start + BYTES_PER_ROW).min(page.start + page.bytes.len() as u32)
+            {
+                let label = page
+                    .get(address)
+                    .map_or_else(|| "--".to_string(), |value| format!("{value:02X}"));
+                cells = cells.push(
+                    button(text(label))
+                        .padding([2, 4])
+                        .style(button::text)
+                        .on_press(Message::SelectByte(address)),
+                );
+            }
+            rows = rows.push(cells);
+        }
+        column![
+            text(format!(
+                "{} ${:0digits$X}-${:0digits$X} of ${:X} bytes (-- cannot be read without side effects)",
+                SpaceChoice(page.space),
+                page.start,
+                page.start + page.bytes.len().saturating_sub(1) as u32,
+                page.space_len,
+            )),
+            rows,
+        ]
+        .spacing(8)
+        .into()
+    }
+
+    fn search_view(&self) -> El<'_> {
+        let controls = row![
+            button("New Search").on_press(Message::NewSearch),
+            pick_list(FILTER_CHOICES, Some(self.filter), Message::SelectFilter),
+            text_input("Value (hex)", &self.filter_value_input)
+                .on_input(Message::SetFilterValue)
+                .on_submit(Message::ApplyFilter)
+                .width(Length::Fixed(90.0)),
+            button("Filter").on_press(Message::ApplyFilter),
+            button("Clear").on_press(Message::ClearSearch),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let candidates = self.search.candidates();
+        let status = if self.search.is_started() {
+            format!("{} candidates", candidates.len())
+        } else {
+            "Take a snapshot with New Search, then filter after the game changes the value"
+                .to_string()
+        };
+        let mut list = Column::new().spacing(4);
+        for candidate in candidates.iter().take(CANDIDATE_LIMIT) {
+            let current = ram_search::read(&self.search_blocks, candidate.address);
+            list = list.push(
+                row![
+                    text(format!(
+                        "${:04X}  was {:02X}  now {}",
+                        candidate.address,
+                        candidate.previous,
+                        format_value(current),
+                    ))
+                    .width(Length::Fixed(260.0)),
+                    button("Watch").on_press(Message::Watch(candidate.address)),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center),
+            );
+        }
+        column![text("RAM Search"), controls, text(status), list]
+            .spacing(8)
+            .into()
+    }
+
+    fn watch_view(&self) -> El<'_> {
+        let mut list = Column::new().spacing(4);
+        for (index, &address) in self.watches.iter().enumerate() {
+            let value = ram_search::read(&self.search_blocks, address);
+            list = list.push(
+                row![
+                    text(format!("${address:04X}  {}", format_value(value)))
+                        .width(Length::Fixed(260.0)),
+                    button("Edit").on_press(Message::EditWatch(address)),
+                    button("Remove").on_press(Message::Unwatch(index)),
+                ]
+                .spacing(12)
+                .align_y(Alignment::Center),
+            );
+        }
+        column![text("Watch List"), list].spacing(8).into()
+    }
+}
+
+pub(crate) struct MemoryViewerWindowHandle {
+    pub(crate) window: Arc<TaoWindow>,
+    view: MemoryViewerView,
+    cache: Option<Cache>,
+    renderer: SettingsRenderer,
+    viewport_physical: (u32, u32),
+    scale_factor: f32,
+    modifiers: keyboard::Modifiers,
+    should_close: Arc<AtomicBool>,
+    cursor: mouse::Cursor,
+    clipboard: Clipboard,
+}
+
+impl MemoryViewerWindowHandle {
+    pub(crate) fn new(
+        event_loop: &EventLoopWindowTarget<crate::app_menu::UserEvent>,
+    ) -> Option<Self> {
+        #[cfg_attr(not(target_os = "macos"), expect(unused_mut))]
+        let mut wb = WindowBuilder::new()
+            .with_title("Memory Viewer")
+            .with_inner_size(tao::dpi::LogicalSize::new(760.0, 720.0));
+        #[cfg(target_os = "macos")]
+        {
+            wb = wb.with_automatic_window_tabbing(false);
+        }
+        let window = Arc::new(match wb.build(event_loop) {
+            Ok(w) => w,
+            Err(e) => {
+                log::error!("failed to create memory viewer window: {e}");
+                return None;
+            }
+        });
+        let window_size = window.inner_size();
+        let renderer = SettingsRenderer::new(&window);
+        window.request_redraw();
+
+        Some(Self {
+            scale_factor: window.scale_factor() as f32,
+            window,
+            view: MemoryViewerView::default(),
+            cache: Some(Cache::default()),
+            renderer,
+            viewport_physical: (window_size.width, window_size.height),
+            modifiers: keyboard::Modifiers::default(),
+            should_close: Arc::new(AtomicBool::new(false)),
+            cursor: mouse::Cursor::default(),
+            clipboard: Clipboard::unconnected(),
+        })
+    }
+
+    pub(crate) fn view_mut(&mut self) -> &mut MemoryViewerView {
+        &mut self.view
+    }
+
+    pub(crate) fn should_close(&self) -> bool {
+        self.should_close.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn handle_tao_event(&mut self, event: tao::event::WindowEvent) {
+        match &event {
+            tao::event::WindowEvent::Resized(size) => self.resize(size.width, size.height),
+            tao::event::WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor as f32;
+            }
+            _ => {}
+        }
+        let Some(mapped) = convert_tao_window_event(
+            event,
+            &mut self.cursor,
+            self.scale_factor,
+            &mut self.modifiers,
+            &self.should_close,
+        ) else {
+            return;
+        };
+
+        let mut messages = Vec::new();
+        let mut ui = build_ui(
+            &self.view,
+            self.viewport().logical_size(),
+            self.cache.take().unwrap_or_default(),
+            &mut self.renderer.backend,
+        );
+        let _ = ui.update(
+            &[mapped],
+            self.cursor,
+            &mut self.renderer.backend,
+            &mut self.clipboard,
+            &mut messages,
+        );
+        self.cache = Some(ui.into_cache());
+        for message in messages {
+            self.view.update(message);
+        }
+        self.window.request_redraw();
+    }
+
+    pub(crate) fn render(&mut self) {
+        let theme = iced::Theme::Dark;
+        let style = <iced::Theme as theme::Base>::base(&theme);
+        let vp = self.viewport();
+
+        let redraw_event = iced::Event::Window(iced::window::Event::RedrawRequested(
+            std::time::Instant::now(),
+        ));
+        let mut ui = build_ui(
+            &self.view,
+            vp.logical_size(),
+            self.cache.take().unwrap_or_default(),
+            &mut self.renderer.backend,
+        );
+        let _ = ui.update(
+            &[redraw_event],
+            self.cursor,
+            &mut self.renderer.backend,
+            &mut self.clipboard,
+            &mut Vec::new(),
+        );
+        ui.draw(
+            &mut self.renderer.backend,
+            &theme,
+            &renderer::Style {
+                text_color: style.text_color,
+            },
+            self.cursor,
+        );
+        self.cache = Some(ui.into_cache());
+
+        if let Err(e) = self.renderer.present(&vp, iced::Color::BLACK) {
+            log::warn!("memory viewer render present failed: {e:?}");
+        }
+    }
+
+    fn viewport(&self) -> Viewport {
+        Viewport::with_physical_size(
+            Size::new(self.viewport_physical.0, self.viewport_physical.1),
+            self.scale_factor,
+        )
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.viewport_physical = (width, height);
+        self.renderer.resize(width, height);
+    }
+}
+
+fn build_ui<'a>(
+    view: &'a MemoryViewerView,
+    bounds: Size,
+    cache: Cache,
+    renderer: &mut iced_tiny_skia::Renderer,
+) -> UserInterface<'a, Message, iced::Theme, iced_tiny_skia::Renderer> {
+    UserInterface::build(view.view(), bounds, cache, renderer)
+}
+
+/// Hex digits needed for the last address of a space of `len` bytes.
+fn address_digits(len: u32) -> usize {
+    let last = len.saturating_sub(1).max(0xFFFF);
+    (32 - last.leading_zeros()).div_ceil(4) as usize
+}
+
+fn format_value(value: Option<u8>) -> String {
+    value.map_or_else(
+        || "--".to_string(),
+        |value| format!("{value:02X} ({value})"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(space: MemorySpace, start: u32, bytes: &[u8]) -> MemoryBlock {
+        MemoryBlock {
+            space,
+            start,
+            bytes: bytes.iter().copied().map(Some).collect(),
+            space_len: 0x10000,
+        }
+    }
+
+    /// Answers every queued read with `value` at each address.
+    fn answer_reads(view: &mut MemoryViewerView, value: impl Fn(u32) -> u8) {
+        for op in view.take_requests() {
+            let MemoryOp::Read { space, start, len } = op else {
+                panic!("expected a read");
+            };
+            let bytes: Vec<u8> = (start..start + len).map(&value).collect();
+            view.apply_reply(&op, Ok(block(space, start, &bytes)));
+        }
+    }
+
+    #[test]
+    fn go_to_reads_the_aligned_page_of_the_selected_space() {
+        let mut view = MemoryViewerView::default();
+        view.update(Message::SelectSpace(SpaceChoice(MemorySpace::VideoBus)));
+        view.take_requests();
+        view.update(Message::SetAddress("$23C5".into()));
+        view.update(Message::GoTo);
+        view.update(Message::SetAddress("nope".into()));
+        view.update(Message::GoTo);
+
+        assert_eq!(
+            view.take_requests(),
+            vec![MemoryOp::Read {
+                space: MemorySpace::VideoBus,
+                start: 0x2300,
+                len: PAGE_LEN,
+            }]
+        );
+        assert!(view.error.is_some());
+    }
+
+    #[test]
+    fn write_sends_parsed_bytes_then_rereads_the_page() {
+        let mut view = MemoryViewerView::default();
+        view.update(Message::SetEditAddress("0x0300".into()));
+        view.update(Message::SetEditBytes("09 ff".into()));
+        view.update(Message::Write);
+        view.update(Message::SetEditBytes("100".into()));
+        view.update(Message::Write);
+
+        assert_eq!(
+            view.take_requests(),
+            vec![
+                MemoryOp::Write {
+                    space: MemorySpace::CpuBus,
+                    start: 0x0300,
+                    bytes: vec![0x09, 0xFF],
+                },
+                MemoryOp::Read {
+                    space: MemorySpace::CpuBus,
+                    start: 0,
+                    len: PAGE_LEN,
+                },
+            ]
+        );
+        assert_eq!(view.error.as_deref(), Some("invalid bytes: 100"));
+    }
+
+    #[test]
+    fn search_narrows_candidates_after_both_ranges_arrive() {
+        let mut view = MemoryViewerView::default();
+        view.update(Message::ApplyFilter);
+        assert!(view.take_requests().is_empty());
+        assert!(view.error.is_some());
+
+        view.update(Message::NewSearch);
+        answer_reads(&mut view, |_| 3);
+        assert_eq!(view.search.candidates().len(), 0x0800 + 0x2000);
+
+        // Lives went from 3 to 2 at $0075 and $6010
+        view.update(Message::SelectFilter(FilterChoice::Less));
+        view.update(Message::ApplyFilter);
+        answer_reads(&mut view, |address| {
+            if matches!(address, 0x0075 | 0x6010) {
+                2
+            } else {
+                3
+            }
+        });
+        let addresses: Vec<u32> = view.search.candidates().iter().map(|c| c.address).collect();
+        assert_eq!(addresses, [0x0075, 0x6010]);
+
+        view.update(Message::Watch(0x0075));
+        view.update(Message::Watch(0x0075));
+        assert_eq!(view.watches, [0x0075]);
+        view.refresh();
+        assert_eq!(view.take_requests().len(), 1 + SEARCH_RANGES.len());
+        view.update(Message::EditWatch(0x0075));
+        assert_eq!(
+            (
+                view.edit_address_input.as_str(),
+                view.edit_bytes_input.as_str()
+            ),
+            ("0075", "02")
+        );
+    }
+}
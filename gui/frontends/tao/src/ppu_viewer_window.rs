@@ -0,0 +1,607 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
+use iced::{
+    Color, Element, Length, Rectangle, Size,
+    advanced::{
+        Layout, Widget, layout,
+        renderer::{self, Renderer as _},
+        widget::Tree,
+    },
+    alignment::Alignment,
+    keyboard, mouse, theme,
+    widget::{
+        Column, Row, button, column, container, pick_list, row, scrollable, text, text_input,
+    },
+};
+use iced_winit::{
+    Clipboard,
+    graphics::Viewport,
+    runtime::user_interface::{Cache, UserInterface},
+};
+use nerust_core_traits::debug::{IndexedImage, SpriteView, VideoMemoryView, ViewRect};
+
+#[cfg(target_os = "macos")]
+use tao::platform::macos::WindowBuilderExtMacOS;
+use tao::{
+    event_loop::EventLoopWindowTarget,
+    window::{Window as TaoWindow, WindowBuilder},
+};
+
+use crate::settings_window::{SettingsRenderer, convert_tao_window_event};
+
+type El<'a> = iced::Element<'a, Message, iced::Theme, iced_tiny_skia::Renderer>;
+
+/// First line of vertical blank: the frame just drawn is complete.
+const DEFAULT_CAPTURE_SCANLINE: u16 = 241;
+const SCROLL_OUTLINE: Color = Color::from_rgb(1.0, 0.2, 0.2);
+
+const PALETTE_CHOICES: [PaletteChoice; 8] = [
+    PaletteChoice(0),
+    PaletteChoice(1),
+    PaletteChoice(2),
+    PaletteChoice(3),
+    PaletteChoice(4),
+    PaletteChoice(5),
+    PaletteChoice(6),
+    PaletteChoice(7),
+];
+
+/// Palette used to draw the pattern tables: 0-3 background, 4-7 sprites.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PaletteChoice(u8);
+
+impl std::fmt::Display for PaletteChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 < 4 {
+            write!(f, "Background {}", self.0)
+        } else {
+            write!(f, "Sprite {}", self.0 - 4)
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Message {
+    SetScanline(String),
+    ApplyScanline,
+    SelectPalette(PaletteChoice),
+    Refresh,
+}
+
+/// What the host asks the core for. Sent again on every refresh so a newly
+/// loaded core starts capturing too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PpuViewerRequest {
+    pub(crate) capture_scanline: u16,
+    pub(crate) pattern_palette: u8,
+}
+
+/// PPU viewer window contents. Kept apart from the Tao window so the update
+/// logic can be tested without a display.
+#[derive(Debug)]
+pub(crate) struct PpuViewerView {
+    memory: Option<VideoMemoryView>,
+    /// `memory.palette` laid out as two rows of 16 swatches.
+    palette_image: IndexedImage,
+    /// Master palette as 0xRRGGBBAA, indexed by the image pixels.
+    colors: Vec<u32>,
+    capture_scanline: u16,
+    scanline_input: String,
+    pattern_palette: u8,
+    error: Option<String>,
+    requests: Vec<PpuViewerRequest>,
+}
+
+impl Default for PpuViewerView {
+    fn default() -> Self {
+        Self {
+            memory: None,
+            palette_image: IndexedImage::default(),
+            colors: Vec::new(),
+            capture_scanline: DEFAULT_CAPTURE_SCANLINE,
+            scanline_input: DEFAULT_CAPTURE_SCANLINE.to_string(),
+            pattern_palette: 0,
+            error: None,
+            requests: Vec::new(),
+        }
+    }
+}
+
+impl PpuViewerView {
+    pub(crate) fn update(&mut self, message: Message) {
+        match message {
+            Message::SetScanline(value) => self.scanline_input = value,
+            Message::ApplyScanline => match self.scanline_input.trim().parse::<u16>() {
+                Ok(line) => {
+                    self.capture_scanline = line;
+                    self.requests.push(self.request());
+                }
+                Err(_) => self.error = Some(format!("invalid scanline: {}", self.scanline_input)),
+            },
+            Message::SelectPalette(choice) => {
+                self.pattern_palette = choice.0;
+                self.requests.push(self.request());
+            }
+            Message::Refresh => self.requests.push(self.request()),
+        }
+    }
+
+    pub(crate) fn take_requests(&mut self) -> Vec<PpuViewerRequest> {
+        std::mem::take(&mut self.requests)
+    }
+
+    pub(crate) fn request(&self) -> PpuViewerRequest {
+        PpuViewerRequest {
+            capture_scanline: self.capture_scanline,
+            pattern_palette: self.pattern_palette,
+        }
+    }
+
+    /// Shows the core's reply. `colors` is the master palette of the running core.
+    pub(crate) fn apply_reply(
+        &mut self,
+        reply: Result<VideoMemoryView, String>,
+        colors: Option<&[u32]>,
+    ) {
+        match reply {
+            Ok(memory) => {
+                self.palette_image = IndexedImage::new(16, memory.palette.len().div_ceil(16));
+                for (entry, &color) in memory.palette.iter().enumerate() {
+                    self.palette_image.set_pixel(entry % 16, entry / 16, color);
+                }
+                if let Some(colors) = colors {
+                    self.colors = colors.to_vec();
+                }
+                self.memory = Some(memory);
+                self.error = None;
+            }
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn view(&self) -> El<'_> {
+        let controls = row![
+            text_input("Scanline", &self.scanline_input)
+                .on_input(Message::SetScanline)
+                .on_submit(Message::ApplyScanline)
+                .width(Length::Fixed(90.0)),
+            button("Capture at Scanline").on_press(Message::ApplyScanline),
+            pick_list(
+                PALETTE_CHOICES,
+                Some(PaletteChoice(self.pattern_palette)),
+                Message::SelectPalette,
+            ),
+            button("Refresh").on_press(Message::Refresh),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let mut root = column![controls].spacing(16).padding(16);
+        if let Some(error) = self.error.as_ref() {
+            root = root.push(text(error.clone()));
+        }
+        if let Some(memory) = self.memory.as_ref() {
+            root = root
+                .push(text(format!(
+                    "Frame {}  |  scanline {}  |  scroll {}, {}",
+                    memory.frame, memory.scanline, memory.scroll.x, memory.scroll.y
+                )))
+                .push(text("Nametables"))
+                .push(self.image(&memory.tile_maps, 1.0, Some(memory.scroll)))
+                .push(text("Pattern tables"))
+                .push(
+                    Row::with_children(
+                        memory
+                            .pattern_tables
+                            .iter()
+                            .map(|table| self.image(table, 2.0, None)),
+                    )
+                    .spacing(16),
+                )
+                .push(text("Sprites"))
+                .push(self.sprites_view(&memory.sprites))
+                .push(text("Palette"))
+                .push(self.image(&self.palette_image, 24.0, None));
+        }
+
+        scrollable(container(root).width(Length::Fill))
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn sprites_view<'a>(&'a self, sprites: &'a [SpriteView]) -> El<'a> {
+        let mut grid = Column::new().spacing(8);
+        for chunk in sprites.chunks(8) {
+            grid = grid.push(
+                Row::with_children(chunk.iter().map(|sprite| {
+                    column![
+                        self.image(&sprite.image, 3.0, None),
+                        text(sprite_label(sprite)).size(11),
+                    ]
+                    .spacing(2)
+                    .width(Length::Fixed(96.0))
+                    .into()
+                }))
+                .spacing(8),
+            );
+        }
+        grid.into()
+    }
+
+    fn image<'a>(
+        &'a self,
+        image: &'a IndexedImage,
+        scale: f32,
+        outline: Option<ViewRect>,
+    ) -> El<'a> {
+        Element::new(PixelImage {
+            image,
+            colors: &self.colors,
+            scale,
+            outline,
+        })
+    }
+}
+
+fn sprite_label(sprite: &SpriteView) -> String {
+    format!(
+        "#{:02} ({}, {})\n${:02X} P{} {}{}{}",
+        sprite.index,
+        sprite.x,
+        sprite.y,
+        sprite.tile,
+        sprite.palette,
+        if sprite.flip_horizontal { 'H' } else { '-' },
+        if sprite.flip_vertical { 'V' } else { '-' },
+        if sprite.behind_background { 'B' } else { 'F' },
+    )
+}
+
+/// Draws an indexed image as one quad per run of equal pixels. iced is built
+/// without its image feature, so raster data goes through quads.
+struct PixelImage<'a> {
+    image: &'a IndexedImage,
+    colors: &'a [u32],
+    scale: f32,
+    outline: Option<ViewRect>,
+}
+
+impl PixelImage<'_> {
+    fn color(&self, index: u8) -> Color {
+        let rgba = self.colors.get(usize::from(index)).copied().unwrap_or(0);
+        Color::from_rgb8((rgba >> 24) as u8, (rgba >> 16) as u8, (rgba >> 8) as u8)
+    }
+
+    /// Draws the scroll rectangle, split where it wraps past the right or bottom edge.
+    fn draw_outline(
+        &self,
+        renderer: &mut iced_tiny_skia::Renderer,
+        bounds: Rectangle,
+        rect: ViewRect,
+    ) {
+        let spans = |start: usize, length: usize, limit: usize| {
+            let start = start % limit;
+            let first = length.min(limit - start);
+            [(start, first), (0, length - first)]
+        };
+        for (x, width) in spans(rect.x, rect.width, self.image.width) {
+            for (y, height) in spans(rect.y, rect.height, self.image.height) {
+                if width == 0 || height == 0 {
+                    continue;
+                }
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds: Rectangle {
+                            x: bounds.x + x as f32 * self.scale,
+                            y: bounds.y + y as f32 * self.scale,
+                            width: width as f32 * self.scale,
+                            height: height as f32 * self.scale,
+                        },
+                        border: iced::Border {
+                            color: SCROLL_OUTLINE,
+                            width: 1.0,
+                            radius: 0.0.into(),
+                        },
+                        ..renderer::Quad::default()
+                    },
+                    Color::TRANSPARENT,
+                );
+            }
+        }
+    }
+}
+
+impl<Message> Widget<Message, iced::Theme, iced_tiny_skia::Renderer> for PixelImage<'_> {
+    fn size(&self) -> Size<Length> {
+        Size::new(
+            Length::Fixed(self.image.width as f32 * self.scale),
+            Length::Fixed(self.image.height as f32 * self.scale),
+        )
+    }
+
+    fn layout(
+        &mut self,
+        _tree: &mut Tree,
+        _renderer: &iced_tiny_skia::Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        let size = Widget::<Message, iced::Theme, iced_tiny_skia::Renderer>::size(self);
+        layout::atomic(limits, size.width, size.height)
+    }
+
+    fn draw(
+        &self,
+        _tree: &Tree,
+        renderer: &mut iced_tiny_skia::Renderer,
+        _theme: &iced::Theme,
+        _style: &renderer::Style,
+        layout: Layout<'_>,
+        _cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        let bounds = layout.bounds();
+        let width = self.image.width;
+        for (y, pixels) in self.image.pixels.chunks_exact(width.max(1)).enumerate() {
+            let row_y = bounds.y + y as f32 * self.scale;
+            // Rows scrolled out of view are skipped
+            if row_y + self.scale < viewport.y || row_y > viewport.y + viewport.height {
+                continue;
+            }
+            let mut start = 0;
+            while start < width {
+                let index = pixels[start];
+                let end = pixels[start..]
+                    .iter()
+                    .position(|&pixel| pixel != index)
+                    .map_or(width, |run| start + run);
+                renderer.fill_quad(
+                    renderer::Quad {
+                        bounds: Rectangle {
+                            x: bounds.x + start as f32 * self.scale,
+                            y: row_y,
+                            width: (end - start) as f32 * self.scale,
+                            height: self.scale,
+                        },
+                        ..renderer::Quad::default()
+                    },
+                    self.color(index),
+                );
+                start = end;
+            }
+        }
+        if let Some(rect) = self.outline {
+            self.draw_outline(renderer, bounds, rect);
+        }
+    }
+}
+
+pub(crate) struct PpuViewerWindowHandle {
+    pub(crate) window: Arc<TaoWindow>,
+    view: PpuViewerView,
+    cache: Option<Cache>,
+    renderer: SettingsRenderer,
+    viewport_physical: (u32, u32),
+    scale_factor: f32,
+    modifiers: keyboard::Modifiers,
+    should_close: Arc<AtomicBool>,
+    cursor: mouse::Cursor,
+    clipboard: Clipboard,
+}
+
+impl PpuViewerWindowHandle {
+    pub(crate) fn new(
+        event_loop: &EventLoopWindowTarget<crate::app_menu::UserEvent>,
+    ) -> Option<Self> {
+        #[cfg_attr(not(target_os = "macos"), expect(unused_mut))]
+        let mut wb = WindowBuilder::new()
+            .with_title("PPU Viewer")
+            .with_inner_size(tao::dpi::LogicalSize::new(900.0, 760.0));
+        #[cfg(target_os = "macos")]
+        {
+            wb = wb.with_automatic_window_tabbing(false);
+        }
+        let window = Arc::new(match wb.build(event_loop) {
+            Ok(w) => w,
+            Err(e) => {
+                log::error!("failed to create PPU viewer window: {e}");
+                return None;
+            }
+        });
+        let window_size = window.inner_size();
+        let renderer = SettingsRenderer::new(&window);
+        window.request_redraw();
+
+        Some(Self {
+            scale_factor: window.scale_factor() as f32,
+            window,
+            view: PpuViewerView::default(),
+            cache: Some(Cache::default()),
+            renderer,
+            viewport_physical: (window_size.width, window_size.height),
+            modifiers: keyboard::Modifiers::default(),
+            should_close: Arc::new(AtomicBool::new(false)),
+            cursor: mouse::Cursor::default(),
+            clipboard: Clipboard::unconnected(),
+        })
+    }
+
+    pub(crate) fn view(&self) -> &PpuViewerView {
+        &self.view
+    }
+
+    pub(crate) fn view_mut(&mut self) -> &mut PpuViewerView {
+        &mut self.view
+    }
+
+    pub(crate) fn should_close(&self) -> bool {
+        self.should_close.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn handle_tao_event(&mut self, event: tao::event::WindowEvent) {
+        match &event {
+            tao::event::WindowEvent::Resized(size) => self.resize(size.width, size.height),
+            tao::event::WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor as f32;
+            }
+            _ => {}
+        }
+        let Some(mapped) = convert_tao_window_event(
+            event,
+            &mut self.cursor,
+            self.scale_factor,
+            &mut self.modifiers,
+            &self.should_close,
+        ) else {
+            return;
+        };
+
+        let mut messages = Vec::new();
+        let mut ui = build_ui(
+            &self.view,
+            self.viewport().logical_size(),
+            self.cache.take().unwrap_or_default(),
+            &mut self.renderer.backend,
+        );
+        let _ = ui.update(
+            &[mapped],
+            self.cursor,
+            &mut self.renderer.backend,
+            &mut self.clipboard,
+            &mut messages,
+        );
+        self.cache = Some(ui.into_cache());
+        for message in messages {
+            self.view.update(message);
+        }
+        self.window.request_redraw();
+    }
+
+    pub(crate) fn render(&mut self) {
+        let theme = iced::Theme::Dark;
+        let style = <iced::Theme as theme::Base>::base(&theme);
+        let vp = self.viewport();
+
+        let redraw_event = iced::Event::Window(iced::window::Event::RedrawRequested(
+            std::time::Instant::now(),
+        ));
+        let mut ui = build_ui(
+            &self.view,
+            vp.logical_size(),
+            self.cache.take().unwrap_or_default(),
+            &mut self.renderer.backend,
+        );
+        let _ = ui.update(
+            &[redraw_event],
+            self.cursor,
+            &mut self.renderer.backend,
+            &mut self.clipboard,
+            &mut Vec::new(),
+        );
+        ui.draw(
+            &mut self.renderer.backend,
+            &theme,
+            &renderer::Style {
+                text_color: style.text_color,
+            },
+            self.cursor,
+        );
+        self.cache = Some(ui.into_cache());
+
+        if let Err(e) = self.renderer.present(&vp, iced::Color::BLACK) {
+            log::warn!("PPU viewer render present failed: {e:?}");
+        }
+    }
+
+    fn viewport(&self) -> Viewport {
+        Viewport::with_physical_size(
+            Size::new(self.viewport_physical.0, self.viewport_physical.1),
+            self.scale_factor,
+        )
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.viewport_physical = (width, height);
+        self.renderer.resize(width, height);
+    }
+}
+
+fn build_ui<'a>(
+    view: &'a PpuViewerView,
+    bounds: Size,
+    cache: Cache,
+    renderer: &mut iced_tiny_skia::Renderer,
+) -> UserInterface<'a, Message, iced::Theme, iced_tiny_skia::Renderer> {
+    UserInterface::build(view.view(), bounds, cache, renderer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory() -> VideoMemoryView {
+        VideoMemoryView {
+            scanline: 241,
+            frame: 10,
+            tile_maps: IndexedImage::new(512, 480),
+            scroll: ViewRect {
+                x: 0,
+                y: 0,
+                width: 256,
+                height: 240,
+            },
+            pattern_tables: vec![IndexedImage::new(128, 128); 2],
+            sprites: Vec::new(),
+            palette: (0..32).collect(),
+        }
+    }
+
+    #[test]
+    fn scanline_and_palette_changes_request_a_new_capture() {
+        let mut view = PpuViewerView::default();
+        view.update(Message::SetScanline("100".into()));
+        view.update(Message::ApplyScanline);
+        view.update(Message::SelectPalette(PaletteChoice(5)));
+        assert_eq!(
+            view.take_requests(),
+            vec![
+                PpuViewerRequest {
+                    capture_scanline: 100,
+                    pattern_palette: 0,
+                },
+                PpuViewerRequest {
+                    capture_scanline: 100,
+                    pattern_palette: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_scanline_keeps_the_previous_capture() {
+        let mut view = PpuViewerView::default();
+        view.update(Message::SetScanline("vblank".into()));
+        view.update(Message::ApplyScanline);
+        assert!(view.take_requests().is_empty());
+        assert!(view.error.is_some());
+        assert_eq!(view.request().capture_scanline, DEFAULT_CAPTURE_SCANLINE);
+    }
+
+    #[test]
+    fn reply_lays_out_palette_swatches() {
+        let mut view = PpuViewerView::default();
+        view.apply_reply(Ok(memory()), Some(&[0xFF00_00FF; 64][..]));
+        assert_eq!(
+            (view.palette_image.width, view.palette_image.height),
+            (16, 2)
+        );
+        assert_eq!(view.palette_image.pixel(3, 1), 19);
+        assert_eq!(view.colors.len(), 64);
+
+        view.apply_reply(Err("no core".into()), None);
+        assert_eq!(view.error.as_deref(), Some("no core"));
+        assert!(view.memory.is_some());
+    }
+}
@@ -27,7 +27,8 @@ use nerust_settings_core::{
 };
 
 use nerust_gui_viewmodel::settings::{
-    SettingsViewModel, StoragePathError, StoragePathValidator, dto::ChoiceView,
+    SettingsViewModel, StoragePathError, StoragePathValidator,
+    dto::{ChoiceView, SystemFieldKindView},
 };
 use nerust_input_traits::AttachmentId;
 use nerust_keyboard::Key;
@@ -64,6 +65,9 @@ pub(crate) enum Message {
     SetStoragePolicy(ChoiceView<StoragePolicy>),
     SetStorageDirectory(String),
     BrowseStorageDirectory,
+    SetRewindInterval(ChoiceView<u32>),
+    SetFastForward(ChoiceView<Option<u16>>),
+    SetSlowMotion(ChoiceView<u16>),
     ToggleFullscreenDefault(bool),
     SetScaling(ChoiceView<ScalingMode>),
     ToggleVsync(bool),
@@ -75,6 +79,8 @@ pub(crate) enum Message {
         String,
         ChoiceView<nerust_core_traits::factory::descriptor::SystemSettingsChoiceId>,
     ),
+    SetSystemPath(String, String),
+    BrowseSystemPath(String, &'static [&'static str]),
     StartCapture(CaptureTarget),
     ClearCapture(CaptureTarget),
     CaptureKey(Key),
@@ -294,6 +300,15 @@ impl SettingsAppState {
             }
             Message::SetStorageDirectory(value) => self.set_storage_directory(value),
             Message::BrowseStorageDirectory => self.browse_storage_directory(),
+            Message::SetRewindInterval(choice) => {
+                self.err(self.vm.general.set_rewind_interval(choice.value))
+            }
+            Message::SetFastForward(choice) => {
+                self.err(self.vm.general.set_fast_forward(choice.value))
+            }
+            Message::SetSlowMotion(choice) => {
+                self.err(self.vm.general.set_slow_motion(choice.value))
+            }
             Message::ToggleFullscreenDefault(value) => {
                 self.err(self.vm.video.set_fullscreen_default(value))
             }
@@ -304,6 +319,17 @@ impl SettingsAppState {
             Message::SetSampleRate(choice) => self.err(self.vm.audio.set_sample_rate(choice.value)),
             Message::SetLatency(value) => self.err(self.vm.audio.set_latency(value)),
             Message::SetSystemChoice(field, choice) => self.set_system_choice(field, choice),
+            Message::SetSystemPath(field, value) => {
+                self.set_system_path(field, (!value.is_empty()).then(|| value.into()))
+            }
+            Message::BrowseSystemPath(field, extensions) => {
+                if let Some(path) = FileDialog::new()
+                    .add_filter(field.as_str(), extensions)
+                    .pick_file()
+                {
+                    self.set_system_path(field, Some(path));
+                }
+            }
             Message::SetControllerSlot {
                 slot,
                 controller_id,
@@ -381,6 +407,18 @@ impl SettingsAppState {
         }
     }
 
+    fn set_system_path(&mut self, field: String, path: Option<std::path::PathBuf>) {
+        if let Some(idx) = self.system_tab_index
+            && let Some(system_vm) = self.vm.systems().get(idx)
+            && let Err(e) = system_vm.set_path(
+                &nerust_core_traits::factory::descriptor::SystemSettingsFieldId(field.into()),
+                path.as_deref(),
+            )
+        {
+            self.error_message = Some(e.to_string());
+        }
+    }
+
     fn set_controller_slot(&mut self, slot: AttachmentId, controller_id: Option<String>) {
         let input_tab_index = self.input_tab_index;
         if let Some(idx) = input_tab_index
@@ -481,6 +519,27 @@ impl SettingsAppState {
                 pick_selected(&general.storage_policy_choices, &general.storage_policy),
                 Message::SetStoragePolicy
             ),
+            labeled_pick_list(
+                ui_text(language, UiText::RewindInterval),
+                general.rewind_interval_choices.clone(),
+                pick_selected(
+                    &general.rewind_interval_choices,
+                    &general.rewind_interval_frames
+                ),
+                Message::SetRewindInterval
+            ),
+            labeled_pick_list(
+                ui_text(language, UiText::FastForwardSpeed),
+                general.fast_forward_choices.clone(),
+                pick_selected(&general.fast_forward_choices, &general.fast_forward_percent),
+                Message::SetFastForward
+            ),
+            labeled_pick_list(
+                ui_text(language, UiText::SlowMotionSpeed),
+                general.slow_motion_choices.clone(),
+                pick_selected(&general.slow_motion_choices, &general.slow_motion_percent),
+                Message::SetSlowMotion
+            ),
         ]
         .spacing(16);
 
@@ -702,22 +761,46 @@ impl SettingsAppState {
 
         // Fields
         for field in &view.fields {
-            let choices: Vec<
-                nerust_gui_viewmodel::settings::dto::ChoiceView<
-                    nerust_core_traits::factory::descriptor::SystemSettingsChoiceId,
-                >,
-            > = field.choices.clone();
-            let selected = choices.iter().find(|c| c.value == field.selected).cloned();
             let label = field.label.clone();
             let field_id_str = field.id.0.to_string();
-            content = content.push(labeled_pick_list(
-                &label,
-                choices,
-                selected,
-                move |choice: nerust_gui_viewmodel::settings::dto::ChoiceView<
-                    nerust_core_traits::factory::descriptor::SystemSettingsChoiceId,
-                >| { Message::SetSystemChoice(field_id_str.clone(), choice) },
-            ));
+            match &field.kind {
+                SystemFieldKindView::Choice { selected, choices } => {
+                    let selected = choices.iter().find(|c| &c.value == selected).cloned();
+                    content = content.push(labeled_pick_list(
+                        &label,
+                        choices.clone(),
+                        selected,
+                        move |choice: nerust_gui_viewmodel::settings::dto::ChoiceView<
+                            nerust_core_traits::factory::descriptor::SystemSettingsChoiceId,
+                        >| {
+                            Message::SetSystemChoice(field_id_str.clone(), choice)
+                        },
+                    ));
+                }
+                SystemFieldKindView::Path {
+                    selected,
+                    extensions,
+                } => {
+                    let value = selected
+                        .as_deref()
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let browse = Message::BrowseSystemPath(field_id_str.clone(), *extensions);
+                    content = content.push(
+                        row![
+                            text(label).width(Length::Fixed(220.0)),
+                            text_input("", &value)
+                                .on_input(move |value| {
+                                    Message::SetSystemPath(field_id_str.clone(), value)
+                                })
+                                .width(Length::Fill),
+                            button(ui_text(self.language(), UiText::Browse)).on_press(browse),
+                        ]
+                        .spacing(12)
+                        .align_y(Alignment::Center),
+                    );
+                }
+            }
         }
         content.spacing(16).into()
     }
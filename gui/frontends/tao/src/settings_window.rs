@@ -161,14 +161,34 @@ pub(crate) struct SettingsWindowHandle {
     clipboard: Clipboard,
 }
 
+/// tiny-skia surface shared by the settings and debugger windows.
 pub(crate) struct SettingsRenderer {
     compositor: Compositor,
     surface: Surface,
-    backend: Renderer,
+    pub(crate) backend: Renderer,
 }
 
 impl SettingsRenderer {
-    fn present(
+    pub(crate) fn new(window: &Arc<TaoWindow>) -> Self {
+        let window_size = window.inner_size();
+        let mut compositor = compositor::new(
+            iced_tiny_skia::Settings {
+                default_font: default_font(),
+                default_text_size: iced::Pixels(16.0),
+            },
+            Arc::clone(window),
+        );
+        let backend = compositor.create_renderer();
+        let surface =
+            compositor.create_surface(Arc::clone(window), window_size.width, window_size.height);
+        Self {
+            compositor,
+            surface,
+            backend,
+        }
+    }
+
+    pub(crate) fn present(
         &mut self,
         viewport: &Viewport,
         background_color: iced::Color,
@@ -182,7 +202,7 @@ impl SettingsRenderer {
         )
     }
 
-    fn resize(&mut self, width: u32, height: u32) {
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
         self.compositor
             .configure_surface(&mut self.surface, width, height);
     }
@@ -231,16 +251,7 @@ impl SettingsWindowHandle {
         let logical_size = window_size.to_logical::<f64>(scale_factor as f64);
         let viewport = Size::new(logical_size.width as f32, logical_size.height as f32);
 
-        let mut compositor = compositor::new(
-            iced_tiny_skia::Settings {
-                default_font: default_font(),
-                default_text_size: iced::Pixels(16.0),
-            },
-            Arc::clone(&window),
-        );
-        let mut renderer = compositor.create_renderer();
-        let surface =
-            compositor.create_surface(Arc::clone(&window), window_size.width, window_size.height);
+        let mut renderer = SettingsRenderer::new(&window);
 
         // Eagerly create the UserInterface so ensure_ui() is not needed.
         let bounds = Viewport::with_physical_size(
@@ -252,7 +263,7 @@ impl SettingsWindowHandle {
             instance,
             window_id,
             bounds,
-            &mut renderer,
+            &mut renderer.backend,
             Rc::clone(&view_invalidated),
         );
 
@@ -262,11 +273,7 @@ impl SettingsWindowHandle {
             window,
             window_id,
             ui_state,
-            renderer: SettingsRenderer {
-                compositor,
-                surface,
-                backend: renderer,
-            },
+            renderer,
             viewport,
             viewport_physical,
             scale_factor,
@@ -383,7 +390,7 @@ impl SettingsWindowHandle {
 /// Convert Tao WindowEvent to iced Event, updating cursor/modifiers/should_close.
 /// Returns Some(event) for events that should be forwarded to handle_event(),
 /// None for events that are fully handled here (CursorLeft, CloseRequested, etc.).
-fn convert_tao_window_event(
+pub(crate) fn convert_tao_window_event(
     event: tao::event::WindowEvent,
     cursor: &mut mouse::Cursor,
     scale_factor: f32,
@@ -123,6 +123,11 @@ impl WindowRuntime {
                     self.host.request_redraw();
                 }
                 WindowEvent::KeyboardInput { event, .. } => self.host.on_keyboard_input(event),
+                WindowEvent::CursorMoved { position, .. } => self.host.on_cursor_moved(position),
+                WindowEvent::CursorLeft { .. } => self.host.on_cursor_left(),
+                WindowEvent::MouseInput { state, button, .. } => {
+                    self.host.on_mouse_input(state, button);
+                }
                 _ => (),
             },
             Event::WindowEvent {
@@ -159,12 +164,36 @@ impl WindowRuntime {
                     self.host.request_redraw();
                 }
             }
+            Event::WindowEvent {
+                event, window_id, ..
+            } if self.host.is_debugger_window(window_id) => {
+                self.host.on_debugger_window_event(event);
+            }
+            Event::WindowEvent {
+                event, window_id, ..
+            } if self.host.is_ppu_viewer_window(window_id) => {
+                self.host.on_ppu_viewer_window_event(event);
+            }
+            Event::WindowEvent {
+                event, window_id, ..
+            } if self.host.is_memory_viewer_window(window_id) => {
+                self.host.on_memory_viewer_window_event(event);
+            }
             Event::RedrawRequested(window_id) if self.host.is_window(window_id) => self.on_update(),
             Event::RedrawRequested(window_id) if self.host.is_settings_window(window_id) => {
                 if let Some(handle) = self.host.settings_window.as_mut() {
                     handle.render();
                 }
             }
+            Event::RedrawRequested(window_id) if self.host.is_debugger_window(window_id) => {
+                self.host.render_debugger_window();
+            }
+            Event::RedrawRequested(window_id) if self.host.is_ppu_viewer_window(window_id) => {
+                self.host.render_ppu_viewer_window();
+            }
+            Event::RedrawRequested(window_id) if self.host.is_memory_viewer_window(window_id) => {
+                self.host.render_memory_viewer_window();
+            }
             Event::MainEventsCleared => self.host.update_control_flow(control_flow),
             Event::UserEvent(command) => match command {
                 UserEvent::Menu(command) => {
@@ -1,5 +1,11 @@
-use std::{path::Path, rc::Rc, sync::Arc, time::Instant};
+use std::{
+    path::Path,
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
+use nerust_core_traits::debug::DebugOp;
 use nerust_gui_runtime::{
     settings::{
         BackendPresentationCapabilities, HostBackendCapabilities, HostWindowCapabilities,
@@ -10,6 +16,7 @@ use nerust_gui_runtime::{
 use nerust_gui_settings::{app_state::RememberedWindowSize, input::ShortcutAction};
 use nerust_gui_shell::{
     context::FrontendContext,
+    load::RomLoaderError,
     session::{
         KeyboardShortcut, SessionError, SessionHandle,
         access::{FrontendSession, SettingsResult},
@@ -19,19 +26,27 @@ use nerust_gui_shell::{
     settings::scaling_factor,
 };
 use nerust_render_traits::{
-    SurfaceSize,
+    FrameBuffer, SurfaceSize,
     renderer::{GpuFactory, RenderResult},
 };
 use nerust_settings_core::i18n::{UiText, text};
-use rfd::FileDialog;
+use rfd::{FileDialog, MessageButtons, MessageDialog, MessageDialogResult};
 use tao::{
-    dpi::{LogicalSize as TaoLogicalSize, PhysicalSize as TaoPhysicalSize},
-    event::{ElementState, KeyEvent},
+    dpi::{
+        LogicalSize as TaoLogicalSize, PhysicalPosition as TaoPhysicalPosition,
+        PhysicalSize as TaoPhysicalSize,
+    },
+    event::{ElementState, KeyEvent, MouseButton},
     event_loop::{ControlFlow, EventLoopWindowTarget},
     window::{Fullscreen, Window as TaoWindow, WindowBuilder, WindowId},
 };
 
-use crate::app_menu::{MenuCommand, UserEvent, imp::AppMenu};
+use crate::{
+    app_menu::{MenuCommand, UserEvent, imp::AppMenu},
+    debugger_window::{DebuggerRequest, DebuggerWindowHandle},
+    memory_viewer_window::MemoryViewerWindowHandle,
+    ppu_viewer_window::{PpuViewerRequest, PpuViewerWindowHandle},
+};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub(crate) enum HostAction {
@@ -42,6 +57,12 @@ pub(crate) enum HostAction {
 
 const DEFAULT_FIT_WINDOW_WIDTH: f64 = 960.0;
 const DEFAULT_FIT_WINDOW_HEIGHT: f64 = 720.0;
+/// How often a running core is checked for a breakpoint hit while the
+/// debugger window is open. The main window may be unfocused and idle.
+const DEBUG_BREAK_POLL_INTERVAL: Duration = Duration::from_millis(16);
+/// How often the PPU viewer asks a running core for a new capture.
+const PPU_VIEWER_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+const MEMORY_VIEWER_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
 
 pub(crate) struct HostState {
     window: Option<Arc<TaoWindow>>,
@@ -50,11 +71,19 @@ pub(crate) struct HostState {
     app_menu: AppMenu,
     shell: NativeShellState,
     pub(crate) settings_window: Option<crate::settings_window::SettingsWindowHandle>,
+    debugger_window: Option<DebuggerWindowHandle>,
+    ppu_viewer_window: Option<PpuViewerWindowHandle>,
+    ppu_viewer_refreshed_at: Instant,
+    memory_viewer_window: Option<MemoryViewerWindowHandle>,
+    memory_viewer_refreshed_at: Instant,
     settings_open: bool,
     resume_after_settings: bool,
     pending_fullscreen_sync: Option<bool>,
     pub(crate) active: bool,
     auto_paused: bool,
+    /// Cursor position in window pixels, `None` while outside the window.
+    cursor: Option<(f64, f64)>,
+    pointer_pressed: bool,
 }
 
 impl HostState {
@@ -85,11 +114,18 @@ impl HostState {
             app_menu,
             shell: NativeShellState::new(),
             settings_window: None,
+            debugger_window: None,
+            ppu_viewer_window: None,
+            ppu_viewer_refreshed_at: Instant::now(),
+            memory_viewer_window: None,
+            memory_viewer_refreshed_at: Instant::now(),
             settings_open: false,
             resume_after_settings: false,
             pending_fullscreen_sync: None,
             active: true,
             auto_paused: false,
+            cursor: None,
+            pointer_pressed: false,
         }
     }
 
@@ -170,6 +206,24 @@ impl HostState {
             .is_some_and(|h| h.window.id() == window_id)
     }
 
+    pub(crate) fn is_debugger_window(&self, window_id: WindowId) -> bool {
+        self.debugger_window
+            .as_ref()
+            .is_some_and(|h| h.window.id() == window_id)
+    }
+
+    pub(crate) fn is_ppu_viewer_window(&self, window_id: WindowId) -> bool {
+        self.ppu_viewer_window
+            .as_ref()
+            .is_some_and(|h| h.window.id() == window_id)
+    }
+
+    pub(crate) fn is_memory_viewer_window(&self, window_id: WindowId) -> bool {
+        self.memory_viewer_window
+            .as_ref()
+            .is_some_and(|h| h.window.id() == window_id)
+    }
+
     pub(crate) fn window_surface_size(&self) -> Option<SurfaceSize> {
         self.window
             .as_ref()
@@ -177,7 +231,16 @@ impl HostState {
     }
 
     pub(crate) fn load_path(&mut self, path: &Path) -> bool {
-        let result = self.ctx.rom_loader.load_rom(path, &mut self.session);
+        let mut result = self.ctx.rom_loader.load_rom(path, None, &mut self.session);
+        if let Err(RomLoaderError::MultipleRoms(candidates)) = &result {
+            let Some(entry) = self.choose_archive_entry(candidates) else {
+                return false;
+            };
+            result = self
+                .ctx
+                .rom_loader
+                .load_rom(path, Some(&entry), &mut self.session);
+        }
         match result {
             Ok(()) => {
                 self.after_rom_load();
@@ -219,6 +282,18 @@ impl HostState {
                 self.open_settings_window(event_loop);
                 HostAction::None
             }
+            MenuCommand::Debugger => {
+                self.open_debugger_window(event_loop);
+                HostAction::None
+            }
+            MenuCommand::PpuViewer => {
+                self.open_ppu_viewer_window(event_loop);
+                HostAction::None
+            }
+            MenuCommand::MemoryViewer => {
+                self.open_memory_viewer_window(event_loop);
+                HostAction::None
+            }
             MenuCommand::Session(command) => {
                 self.run_command(command);
                 self.sync_menu_state();
@@ -247,6 +322,41 @@ impl HostState {
         }
     }
 
+    pub(crate) fn on_cursor_moved(&mut self, position: TaoPhysicalPosition<f64>) {
+        self.cursor = Some((position.x, position.y));
+        self.sync_pointer();
+    }
+
+    pub(crate) fn on_cursor_left(&mut self) {
+        self.cursor = None;
+        self.sync_pointer();
+    }
+
+    pub(crate) fn on_mouse_input(&mut self, state: ElementState, button: MouseButton) {
+        if button != MouseButton::Left {
+            return;
+        }
+        if let Some(pressed) = element_state_to_pressed(state) {
+            self.pointer_pressed = pressed;
+            self.sync_pointer();
+        }
+    }
+
+    fn sync_pointer(&mut self) {
+        if self.settings_open {
+            return;
+        }
+        let Some(window) = self.window.as_ref() else {
+            return;
+        };
+        let size = window.inner_size();
+        let position = self.cursor.and_then(|cursor| {
+            self.session
+                .pointer_frame_position(cursor, (f64::from(size.width), f64::from(size.height)))
+        });
+        self.session.apply_pointer(position, self.pointer_pressed);
+    }
+
     pub(crate) fn clear_keys(&mut self) {
         self.session.clear_input();
     }
@@ -284,7 +394,33 @@ impl HostState {
     pub(crate) fn update_control_flow(&mut self, control_flow: &mut ControlFlow) {
         self.sync_fullscreen_default_from_window();
         self.maybe_refresh_window_title(Instant::now());
+        self.poll_debug_break();
         *control_flow = ControlFlow::Wait;
+        if self.debugger_window.is_some() && self.session.loaded() && !self.session.paused() {
+            *control_flow = ControlFlow::WaitUntil(Instant::now() + DEBUG_BREAK_POLL_INTERVAL);
+        }
+        if self.ppu_viewer_window.is_some() && self.session.loaded() && !self.session.paused() {
+            let now = Instant::now();
+            if now >= self.ppu_viewer_refreshed_at + PPU_VIEWER_REFRESH_INTERVAL {
+                self.refresh_ppu_viewer();
+            }
+            let next = self.ppu_viewer_refreshed_at + PPU_VIEWER_REFRESH_INTERVAL;
+            *control_flow = match *control_flow {
+                ControlFlow::WaitUntil(deadline) => ControlFlow::WaitUntil(deadline.min(next)),
+                _ => ControlFlow::WaitUntil(next),
+            };
+        }
+        if self.memory_viewer_window.is_some() && self.session.loaded() && !self.session.paused() {
+            let now = Instant::now();
+            if now >= self.memory_viewer_refreshed_at + MEMORY_VIEWER_REFRESH_INTERVAL {
+                self.refresh_memory_viewer();
+            }
+            let next = self.memory_viewer_refreshed_at + MEMORY_VIEWER_REFRESH_INTERVAL;
+            *control_flow = match *control_flow {
+                ControlFlow::WaitUntil(deadline) => ControlFlow::WaitUntil(deadline.min(next)),
+                _ => ControlFlow::WaitUntil(next),
+            };
+        }
 
         // On macOS, request_redraw() integrates with CVDisplayLink/vsync.
         // On other platforms, it fires on the next event loop iteration.
@@ -323,6 +459,9 @@ impl HostState {
         self.settings_open = false;
         self.resume_after_settings = false;
         self.settings_window.take();
+        self.debugger_window.take();
+        self.ppu_viewer_window.take();
+        self.memory_viewer_window.take();
         self.session.flush_before_exit();
         true
     }
@@ -365,12 +504,36 @@ impl HostState {
                 self.session.settings_snapshot().shared.general.language,
                 UiText::Open,
             ))
-            .add_filter("NES ROM", &["nes"])
+            .add_filter("NES ROM", &["nes", "fds", "nsf", "nsfe", "zip", "gz"])
             .pick_file()
             .is_some_and(|path| self.load_path(&path))
     }
 
+    /// rfd has no list picker, so candidates are offered one at a time.
+    fn choose_archive_entry(&self, candidates: &[String]) -> Option<String> {
+        let language = self.session.settings_snapshot().shared.general.language;
+        candidates
+            .iter()
+            .find(|candidate| {
+                MessageDialog::new()
+                    .set_title(text(language, UiText::ChooseRomInArchive))
+                    .set_description(candidate.as_str())
+                    .set_buttons(MessageButtons::YesNo)
+                    .show()
+                    == MessageDialogResult::Yes
+            })
+            .cloned()
+    }
+
     fn after_rom_load(&mut self) {
+        // A fresh core starts without breakpoints; re-send the window's list.
+        if let Some(handle) = self.debugger_window.as_mut() {
+            handle.view_mut().resync();
+            self.process_debugger_requests();
+        }
+        // The new core has not captured anything yet; arm it at the window's scanline.
+        self.refresh_ppu_viewer();
+        self.refresh_memory_viewer();
         self.sync_menu_state();
         self.request_redraw();
         self.refresh_window_title();
@@ -411,6 +574,20 @@ impl HostState {
                 }
                 ShortcutAction::Reset => self.reset(),
                 ShortcutAction::ToggleFullscreen => self.toggle_fullscreen(),
+                ShortcutAction::ToggleFastForward => {
+                    self.run_command(SessionCommand::ToggleFastForward);
+                }
+                ShortcutAction::ToggleSlowMotion => {
+                    self.run_command(SessionCommand::ToggleSlowMotion);
+                }
+                ShortcutAction::EjectDisk => self.run_command(SessionCommand::EjectDisk),
+                ShortcutAction::SwitchDiskSide => {
+                    self.run_command(SessionCommand::SwitchDiskSide);
+                }
+                ShortcutAction::NextTrack => self.run_command(SessionCommand::NextTrack),
+                ShortcutAction::PreviousTrack => self.run_command(SessionCommand::PreviousTrack),
+                // 押しっぱなし系はセッションが押下/解放を直接処理する
+                ShortcutAction::Rewind | ShortcutAction::FastForward => {}
             },
             KeyboardShortcut::ToggleFullscreen => self.toggle_fullscreen(),
         }
@@ -464,6 +641,245 @@ impl HostState {
         }
     }
 
+    fn open_debugger_window(&mut self, event_loop: &EventLoopWindowTarget<UserEvent>) {
+        if let Some(handle) = self.debugger_window.as_ref() {
+            handle.window.set_focus();
+            return;
+        }
+        let Some(handle) = DebuggerWindowHandle::new(event_loop) else {
+            log::error!("failed to open debugger window");
+            return;
+        };
+        self.debugger_window = Some(handle);
+        self.run_debugger_request(DebuggerRequest::Op(DebugOp::Inspect));
+    }
+
+    /// Forwards a debugger window event, then runs the requests it produced.
+    pub(crate) fn on_debugger_window_event(&mut self, event: tao::event::WindowEvent) {
+        let Some(handle) = self.debugger_window.as_mut() else {
+            return;
+        };
+        handle.handle_tao_event(event);
+        self.process_debugger_requests();
+        if self
+            .debugger_window
+            .as_ref()
+            .is_some_and(DebuggerWindowHandle::should_close)
+        {
+            self.close_debugger_window();
+        }
+    }
+
+    pub(crate) fn render_debugger_window(&mut self) {
+        if let Some(handle) = self.debugger_window.as_mut() {
+            handle.render();
+        }
+    }
+
+    /// Breakpoints stop slowing the core down once nobody can see them.
+    fn close_debugger_window(&mut self) {
+        let Some(handle) = self.debugger_window.take() else {
+            return;
+        };
+        if handle.view().has_breakpoints()
+            && let Err(error) = self.session.debug_op(DebugOp::SetBreakpoints(Vec::new()))
+        {
+            log::warn!("clearing breakpoints failed: {error}");
+        }
+    }
+
+    fn process_debugger_requests(&mut self) {
+        let requests = self
+            .debugger_window
+            .as_mut()
+            .map(|handle| handle.view_mut().take_requests())
+            .unwrap_or_default();
+        for request in requests {
+            self.run_debugger_request(request);
+        }
+    }
+
+    fn run_debugger_request(&mut self, request: DebuggerRequest) {
+        let op = match request {
+            DebuggerRequest::Op(op) => op,
+            DebuggerRequest::Continue => {
+                self.resume();
+                self.sync_menu_state();
+                return;
+            }
+            DebuggerRequest::Pause => {
+                self.pause();
+                self.sync_menu_state();
+                DebugOp::Inspect
+            }
+        };
+        let resumes = matches!(op, DebugOp::Step(_));
+        let reply = self
+            .session
+            .debug_op(op.clone())
+            .map_err(|error| error.to_string());
+        if let Some(handle) = self.debugger_window.as_mut() {
+            handle.view_mut().apply_reply(&op, reply);
+            handle.window.request_redraw();
+        }
+        if resumes {
+            self.sync_menu_state();
+        }
+    }
+
+    /// Shows a breakpoint hit or finished step in the debugger window.
+    fn poll_debug_break(&mut self) {
+        let Some(reason) = self.session.take_debug_break() else {
+            return;
+        };
+        self.sync_menu_state();
+        self.refresh_window_title();
+        if let Some(handle) = self.debugger_window.as_mut() {
+            handle.view_mut().show_break(reason);
+        }
+        self.run_debugger_request(DebuggerRequest::Op(DebugOp::Inspect));
+        self.refresh_ppu_viewer();
+        self.refresh_memory_viewer();
+    }
+
+    fn open_ppu_viewer_window(&mut self, event_loop: &EventLoopWindowTarget<UserEvent>) {
+        if let Some(handle) = self.ppu_viewer_window.as_ref() {
+            handle.window.set_focus();
+            return;
+        }
+        let Some(handle) = PpuViewerWindowHandle::new(event_loop) else {
+            log::error!("failed to open PPU viewer window");
+            return;
+        };
+        self.ppu_viewer_window = Some(handle);
+        self.refresh_ppu_viewer();
+    }
+
+    /// Forwards a PPU viewer window event, then runs the requests it produced.
+    pub(crate) fn on_ppu_viewer_window_event(&mut self, event: tao::event::WindowEvent) {
+        let Some(handle) = self.ppu_viewer_window.as_mut() else {
+            return;
+        };
+        handle.handle_tao_event(event);
+        let requests = handle.view_mut().take_requests();
+        for request in requests {
+            self.run_ppu_viewer_request(request);
+        }
+        if self
+            .ppu_viewer_window
+            .as_ref()
+            .is_some_and(PpuViewerWindowHandle::should_close)
+        {
+            self.close_ppu_viewer_window();
+        }
+    }
+
+    pub(crate) fn render_ppu_viewer_window(&mut self) {
+        if let Some(handle) = self.ppu_viewer_window.as_mut() {
+            handle.render();
+        }
+    }
+
+    /// Capturing keeps the core off its fast path, so it stops with the window.
+    fn close_ppu_viewer_window(&mut self) {
+        if self.ppu_viewer_window.take().is_none() || !self.session.loaded() {
+            return;
+        }
+        if let Err(error) = self.session.video_view(None, 0) {
+            log::warn!("stopping PPU capture failed: {error}");
+        }
+    }
+
+    fn refresh_ppu_viewer(&mut self) {
+        let Some(request) = self
+            .ppu_viewer_window
+            .as_ref()
+            .map(|handle| handle.view().request())
+        else {
+            return;
+        };
+        self.run_ppu_viewer_request(request);
+    }
+
+    fn run_ppu_viewer_request(&mut self, request: PpuViewerRequest) {
+        self.ppu_viewer_refreshed_at = Instant::now();
+        let reply = self
+            .session
+            .video_view(Some(request.capture_scanline), request.pattern_palette)
+            .map_err(|error| error.to_string());
+        let colors = self.session.frame_buffer().and_then(FrameBuffer::palette);
+        if let Some(handle) = self.ppu_viewer_window.as_mut() {
+            handle.view_mut().apply_reply(reply, colors);
+            handle.window.request_redraw();
+        }
+    }
+
+    fn open_memory_viewer_window(&mut self, event_loop: &EventLoopWindowTarget<UserEvent>) {
+        if let Some(handle) = self.memory_viewer_window.as_ref() {
+            handle.window.set_focus();
+            return;
+        }
+        let Some(handle) = MemoryViewerWindowHandle::new(event_loop) else {
+            log::error!("failed to open memory viewer window");
+            return;
+        };
+        self.memory_viewer_window = Some(handle);
+        self.refresh_memory_viewer();
+    }
+
+    /// Forwards a memory viewer window event, then runs the reads and writes it produced.
+    pub(crate) fn on_memory_viewer_window_event(&mut self, event: tao::event::WindowEvent) {
+        let Some(handle) = self.memory_viewer_window.as_mut() else {
+            return;
+        };
+        handle.handle_tao_event(event);
+        self.process_memory_viewer_requests();
+        if self
+            .memory_viewer_window
+            .as_ref()
+            .is_some_and(MemoryViewerWindowHandle::should_close)
+        {
+            self.memory_viewer_window = None;
+        }
+    }
+
+    pub(crate) fn render_memory_viewer_window(&mut self) {
+        if let Some(handle) = self.memory_viewer_window.as_mut() {
+            handle.render();
+        }
+    }
+
+    fn refresh_memory_viewer(&mut self) {
+        let Some(handle) = self.memory_viewer_window.as_mut() else {
+            return;
+        };
+        handle.view_mut().refresh();
+        self.process_memory_viewer_requests();
+    }
+
+    fn process_memory_viewer_requests(&mut self) {
+        let Some(handle) = self.memory_viewer_window.as_mut() else {
+            return;
+        };
+        let requests = handle.view_mut().take_requests();
+        if requests.is_empty() {
+            return;
+        }
+        self.memory_viewer_refreshed_at = Instant::now();
+        for op in requests {
+            let reply = self
+                .session
+                .memory_op(op.clone())
+                .map_err(|error| error.to_string());
+            if let Some(handle) = self.memory_viewer_window.as_mut() {
+                handle.view_mut().apply_reply(&op, reply);
+            }
+        }
+        if let Some(handle) = self.memory_viewer_window.as_ref() {
+            handle.window.request_redraw();
+        }
+    }
+
     pub(crate) fn close_settings_window(
         &mut self,
         mut handle: crate::settings_window::SettingsWindowHandle,
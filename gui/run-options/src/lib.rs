@@ -5,4 +5,6 @@ use std::path::PathBuf;
 pub struct RunOptions {
     /// Path to a ROM file to load on startup.
     pub rom_path: Option<PathBuf>,
+    /// IPS / BPS / UPS patches applied to the startup ROM, in order.
+    pub patch_paths: Vec<PathBuf>,
 }
@@ -1,5 +1,6 @@
 pub mod rom;
 pub mod rom_library;
+pub mod rom_patch;
 pub mod settings;
 pub mod shell;
 pub mod slots;
@@ -1,12 +1,44 @@
 use std::{
     fs::File,
-    io::{self, BufReader, Read},
+    io::{self, BufReader, Cursor, Read},
     path::{Path, PathBuf},
 };
 
+use flate2::read::GzDecoder;
+use zip::ZipArchive;
+
+use crate::rom_patch::ROM_PATCH_EXTENSIONS;
+
+/// Extensions treated as ROM candidates when scanning an archive.
+pub const ROM_EXTENSIONS: [&str; 4] = ["nes", "fds", "nsf", "nsfe"];
+
+/// Largest ROM unpacked from an archive or built by a BPS / UPS patch. Headers
+/// are not trusted for the size, so this also stops decompression bombs.
+pub(crate) const MAX_UNPACKED_SIZE: u64 = 64 * 1024 * 1024;
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const GZIP_MAGIC: &[u8] = b"\x1F\x8B";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RomFileError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("zip error: {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("archive contains no ROM")]
+    NoRomInArchive,
+    #[error("archive contains several ROMs: {0:?}")]
+    MultipleRoms(Vec<String>),
+    #[error("archive entry not found: {0}")]
+    EntryNotFound(String),
+    #[error("unpacked ROM is larger than {0} bytes")]
+    TooLarge(u64),
+}
+
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct LoadedRom {
     path: PathBuf,
+    entry: Option<String>,
     data: Vec<u8>,
 }
 
@@ -15,17 +47,242 @@ impl LoadedRom {
         &self.path
     }
 
+    /// Name of the archive member the ROM was extracted from, if any.
+    pub fn entry(&self) -> Option<&str> {
+        self.entry.as_deref()
+    }
+
     pub fn into_parts(self) -> (PathBuf, Vec<u8>) {
         (self.path, self.data)
     }
 }
 
-pub fn load_rom_path(path: &Path) -> io::Result<LoadedRom> {
+/// ROM bytes unpacked from a zip or gzip container (or passed through as-is).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ExtractedRom {
+    pub entry: Option<String>,
+    pub data: Vec<u8>,
+}
+
+impl ExtractedRom {
+    /// Lower-cased extension of the archive member, if the ROM came from one.
+    pub fn extension(&self) -> Option<String> {
+        self.entry.as_deref().and_then(entry_extension)
+    }
+}
+
+/// Reads a ROM file, unpacking zip and gzip containers.
+///
+/// `entry` selects the archive member to load. When it is `None` and a zip
+/// holds several ROMs, [`RomFileError::MultipleRoms`] lists the candidates so
+/// the caller can ask the user and retry.
+pub fn load_rom_path(path: &Path, entry: Option<&str>) -> Result<LoadedRom, RomFileError> {
     let mut reader = BufReader::new(File::open(path)?);
     let mut data = Vec::new();
     reader.read_to_end(&mut data)?;
+    let extracted = extract_rom(data, entry)?;
     Ok(LoadedRom {
         path: path.to_path_buf(),
-        data,
+        entry: extracted.entry,
+        data: extracted.data,
+    })
+}
+
+/// Lists `.ips` / `.bps` / `.ups` files sharing the ROM's stem, next to it.
+pub fn sidecar_patch_paths(rom_path: &Path) -> Vec<PathBuf> {
+    ROM_PATCH_EXTENSIONS
+        .iter()
+        .map(|extension| rom_path.with_extension(extension))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Returns whether `data` is a zip or gzip container.
+pub fn is_rom_archive(data: &[u8]) -> bool {
+    data.starts_with(ZIP_MAGIC) || data.starts_with(GZIP_MAGIC)
+}
+
+/// Unpacks `data` when it is a zip or gzip container; other data is returned
+/// unchanged.
+pub fn extract_rom(data: Vec<u8>, entry: Option<&str>) -> Result<ExtractedRom, RomFileError> {
+    if data.starts_with(ZIP_MAGIC) {
+        extract_zip(&data, entry)
+    } else if data.starts_with(GZIP_MAGIC) {
+        let mut decoder = GzDecoder::new(data.as_slice());
+        let unpacked = read_limited(&mut decoder, MAX_UNPACKED_SIZE)?;
+        let entry = decoder
+            .header()
+            .and_then(|header| header.filename())
+            .map(|name| String::from_utf8_lossy(name).into_owned());
+        Ok(ExtractedRom {
+            entry,
+            data: unpacked,
+        })
+    } else {
+        Ok(ExtractedRom { entry: None, data })
+    }
+}
+
+fn extract_zip(data: &[u8], entry: Option<&str>) -> Result<ExtractedRom, RomFileError> {
+    let mut archive = ZipArchive::new(Cursor::new(data))?;
+    let name = match entry {
+        Some(entry) => entry.to_string(),
+        None => {
+            let mut candidates: Vec<String> = archive
+                .file_names()
+                .filter(|name| {
+                    entry_extension(name).is_some_and(|ext| ROM_EXTENSIONS.contains(&ext.as_str()))
+                })
+                .map(str::to_string)
+                .collect();
+            match candidates.len() {
+                0 => return Err(RomFileError::NoRomInArchive),
+                1 => candidates.remove(0),
+                _ => {
+                    candidates.sort();
+                    return Err(RomFileError::MultipleRoms(candidates));
+                }
+            }
+        }
+    };
+    let mut file = match archive.by_name(&name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => {
+            return Err(RomFileError::EntryNotFound(name));
+        }
+        Err(error) => return Err(error.into()),
+    };
+    let unpacked = read_limited(&mut file, MAX_UNPACKED_SIZE)?;
+    Ok(ExtractedRom {
+        entry: Some(name),
+        data: unpacked,
     })
 }
+
+fn read_limited(reader: impl Read, limit: u64) -> Result<Vec<u8>, RomFileError> {
+    let mut data = Vec::new();
+    reader.take(limit + 1).read_to_end(&mut data)?;
+    if data.len() as u64 > limit {
+        return Err(RomFileError::TooLarge(limit));
+    }
+    Ok(data)
+}
+
+/// Builds a zip archive in memory, for tests.
+#[cfg(any(test, feature = "test-support"))]
+pub fn zip_bytes(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    use std::io::Write;
+
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in entries {
+        writer
+            .start_file(*name, SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(data).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+}
+
+fn entry_extension(name: &str) -> Option<String> {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, GzBuilder};
+
+    use super::{RomFileError, extract_rom, read_limited, sidecar_patch_paths, zip_bytes};
+
+    #[test]
+    fn raw_rom_bytes_pass_through() {
+        let extracted = extract_rom(b"NES\x1A".to_vec(), None).unwrap();
+        assert_eq!(extracted.entry, None);
+        assert_eq!(extracted.data, b"NES\x1A");
+    }
+
+    #[test]
+    fn zip_with_single_rom_ignores_other_members() {
+        let data = zip_bytes(&[("readme.txt", b"hello"), ("Game.NES", b"rom")]);
+        let extracted = extract_rom(data, None).unwrap();
+        assert_eq!(extracted.entry.as_deref(), Some("Game.NES"));
+        assert_eq!(extracted.extension().as_deref(), Some("nes"));
+        assert_eq!(extracted.data, b"rom");
+    }
+
+    #[test]
+    fn zip_with_several_roms_requires_a_choice() {
+        let data = zip_bytes(&[("b.fds", b"disk"), ("a.nes", b"rom")]);
+        match extract_rom(data.clone(), None) {
+            Err(RomFileError::MultipleRoms(candidates)) => {
+                assert_eq!(candidates, ["a.nes", "b.fds"]);
+            }
+            other => panic!("expected MultipleRoms, got {other:?}"),
+        }
+        assert_eq!(
+            extract_rom(data.clone(), Some("b.fds")).unwrap().data,
+            b"disk"
+        );
+        assert!(matches!(
+            extract_rom(data, Some("missing.nes")),
+            Err(RomFileError::EntryNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn zip_without_rom_is_rejected() {
+        let data = zip_bytes(&[("readme.txt", b"hello")]);
+        assert!(matches!(
+            extract_rom(data, None),
+            Err(RomFileError::NoRomInArchive)
+        ));
+    }
+
+    #[test]
+    fn gzip_is_unpacked_with_original_name() {
+        let mut encoder = GzBuilder::new()
+            .filename("game.nes")
+            .write(Vec::new(), Compression::default());
+        encoder.write_all(b"rom").unwrap();
+        let extracted = extract_rom(encoder.finish().unwrap(), None).unwrap();
+        assert_eq!(extracted.entry.as_deref(), Some("game.nes"));
+        assert_eq!(extracted.data, b"rom");
+    }
+
+    #[test]
+    fn unpacking_stops_at_the_size_limit() {
+        assert_eq!(read_limited(&b"rom"[..], 3).unwrap(), b"rom");
+        assert!(matches!(
+            read_limited(&b"rom!"[..], 3),
+            Err(RomFileError::TooLarge(3))
+        ));
+    }
+
+    #[test]
+    fn sidecar_patches_share_the_rom_stem() {
+        let dir = std::env::temp_dir().join(format!(
+            "nerust-rom-patches-{}",
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["Game.nes", "Game.bps", "Game.ips", "Other.ups"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+
+        assert_eq!(
+            sidecar_patch_paths(&dir.join("Game.nes")),
+            [dir.join("Game.ips"), dir.join("Game.bps")]
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
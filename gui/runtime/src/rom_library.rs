@@ -7,6 +7,8 @@ use std::{
 use crc::{CRC_32_ISO_HDLC, Crc};
 use serde::{Deserialize, Serialize};
 
+use crate::rom::{RomFileError, extract_rom, is_rom_archive};
+
 const ROM_LIBRARY_SCHEMA_VERSION: u32 = 1;
 const CATALOG_FILE_NAME: &str = "catalog.yaml";
 const ROMS_DIR_NAME: &str = "roms";
@@ -19,6 +21,8 @@ pub enum RomLibraryError {
     Serialize(Box<dyn std::error::Error + Send + 'static>),
     #[error("ROM library I/O failed: {0}")]
     Io(#[from] std::io::Error),
+    #[error("ROM archive could not be unpacked: {0}")]
+    Rom(#[from] RomFileError),
     #[error("system time is unavailable: {0}")]
     Clock(#[from] std::time::SystemTimeError),
 }
@@ -111,16 +115,47 @@ impl RomLibrary {
         &self.document.entries
     }
 
+    /// Imports a ROM, unpacking zip and gzip containers.
+    ///
+    /// A zip holding several ROMs fails with [`RomFileError::MultipleRoms`];
+    /// retry with [`Self::import_archive_entry`] once the user picked one.
     pub fn import_bytes(
         &mut self,
         display_name: &str,
         extension: &str,
         bytes: &[u8],
     ) -> Result<RomLibraryEntry, RomLibraryError> {
+        self.import_entry(display_name, extension, bytes, None)
+    }
+
+    /// Imports the named member of a zip archive.
+    pub fn import_archive_entry(
+        &mut self,
+        display_name: &str,
+        bytes: &[u8],
+        entry: &str,
+    ) -> Result<RomLibraryEntry, RomLibraryError> {
+        self.import_entry(display_name, "", bytes, Some(entry))
+    }
+
+    fn import_entry(
+        &mut self,
+        display_name: &str,
+        extension: &str,
+        bytes: &[u8],
+        entry: Option<&str>,
+    ) -> Result<RomLibraryEntry, RomLibraryError> {
+        let (extension, bytes) = if is_rom_archive(bytes) {
+            let extracted = extract_rom(bytes.to_vec(), entry)?;
+            (extracted.extension().unwrap_or_default(), extracted.data)
+        } else {
+            (extension.to_string(), bytes.to_vec())
+        };
+        let bytes = bytes.as_slice();
         let imported_at_unix_seconds = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
         let checksum = Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(bytes);
         let id = format!("{imported_at_unix_seconds:016x}-{checksum:08x}");
-        let file_name = match normalize_extension(extension) {
+        let file_name = match normalize_extension(&extension) {
             Some(extension) => format!("{id}.{extension}"),
             None => id.clone(),
         };
@@ -218,7 +253,8 @@ mod tests {
         time::{SystemTime, UNIX_EPOCH},
     };
 
-    use super::{RomLibrary, RomLibraryPaths};
+    use super::{RomLibrary, RomLibraryError, RomLibraryPaths};
+    use crate::rom::{RomFileError, zip_bytes};
 
     fn test_root(label: &str) -> PathBuf {
         let nonce = SystemTime::now()
@@ -317,4 +353,49 @@ mod tests {
 
         let _ = fs::remove_dir_all(root);
     }
+
+    #[test]
+    fn import_unpacks_zipped_rom() {
+        let root = test_root("import-zip");
+        let paths = RomLibraryPaths::new(root.clone());
+        let mut library = RomLibrary::open(paths).unwrap();
+
+        let archive = zip_bytes(&[("Game.nes", b"rom-bytes")]);
+        let entry = library.import_bytes("Game", "zip", &archive).unwrap();
+
+        assert!(entry.file_name.ends_with(".nes"));
+        assert_eq!(
+            library.load_bytes(&entry.id).unwrap(),
+            Some(b"rom-bytes".to_vec())
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn import_of_multi_rom_zip_needs_entry_choice() {
+        let root = test_root("import-zip-choice");
+        let paths = RomLibraryPaths::new(root.clone());
+        let mut library = RomLibrary::open(paths).unwrap();
+
+        let archive = zip_bytes(&[("a.nes", b"first"), ("b.fds", b"second")]);
+        let error = library.import_bytes("Set", "zip", &archive).unwrap_err();
+        assert!(matches!(
+            error,
+            RomLibraryError::Rom(RomFileError::MultipleRoms(ref candidates))
+                if candidates == &["a.nes", "b.fds"]
+        ));
+        assert!(library.entries().is_empty());
+
+        let entry = library
+            .import_archive_entry("Set", &archive, "b.fds")
+            .unwrap();
+        assert!(entry.file_name.ends_with(".fds"));
+        assert_eq!(
+            library.load_bytes(&entry.id).unwrap(),
+            Some(b"second".to_vec())
+        );
+
+        let _ = fs::remove_dir_all(root);
+    }
 }
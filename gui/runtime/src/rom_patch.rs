@@ -0,0 +1,429 @@
+//! IPS / BPS / UPS soft-patching applied to ROM bytes before they are parsed.
+
+use std::path::PathBuf;
+
+use crc::{CRC_32_ISO_HDLC, Crc};
+use nerust_core_traits::CoreError;
+
+use crate::rom::MAX_UNPACKED_SIZE;
+
+/// File extensions recognised as ROM patches, in the order they are applied.
+pub const ROM_PATCH_EXTENSIONS: [&str; 3] = ["ips", "bps", "ups"];
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const BPS_MAGIC: &[u8] = b"BPS1";
+const UPS_MAGIC: &[u8] = b"UPS1";
+/// BPS / UPS footer: source, target and patch CRC32.
+const FOOTER_LEN: usize = 12;
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum RomPatchError {
+    #[error("unknown ROM patch format")]
+    UnknownFormat,
+    #[error("ROM patch is truncated")]
+    Truncated,
+    #[error("ROM patch writes outside the target ({0} bytes)")]
+    OutOfBounds(usize),
+    #[error("ROM patch expects a {expected}-byte source, found {actual} bytes")]
+    SourceSize { expected: usize, actual: usize },
+    #[error("ROM patch declares a {0}-byte target, larger than the {MAX_UNPACKED_SIZE}-byte limit")]
+    TargetTooLarge(usize),
+    #[error("ROM patch {kind} CRC32 mismatch: expected {expected:08X}, found {actual:08X}")]
+    Checksum {
+        kind: &'static str,
+        expected: u32,
+        actual: u32,
+    },
+}
+
+/// Reads each patch file and applies it to `rom` in order.
+///
+/// Malformed patches and checksum mismatches are reported as
+/// [`CoreError::RomParse`].
+pub fn apply_patch_files(rom: &[u8], paths: &[PathBuf]) -> Result<Vec<u8>, CoreError> {
+    let mut patched = rom.to_vec();
+    for path in paths {
+        let patch = std::fs::read(path).map_err(|e| {
+            CoreError::Core(format!("failed to read ROM patch {}: {e}", path.display()).into())
+        })?;
+        patched = apply_patch(&patched, &patch).map_err(|e| CoreError::RomParse(Box::new(e)))?;
+    }
+    Ok(patched)
+}
+
+/// Applies an IPS, BPS or UPS patch, detected from its magic bytes.
+pub fn apply_patch(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, RomPatchError> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(rom, patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom, patch)
+    } else if patch.starts_with(UPS_MAGIC) {
+        apply_ups(rom, patch)
+    } else {
+        Err(RomPatchError::UnknownFormat)
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    Crc::<u32>::new(&CRC_32_ISO_HDLC).checksum(data)
+}
+
+struct PatchReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], RomPatchError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(RomPatchError::Truncated)?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, RomPatchError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn be(&mut self, len: usize) -> Result<usize, RomPatchError> {
+        Ok(self
+            .bytes(len)?
+            .iter()
+            .fold(0, |value, &byte| (value << 8) | usize::from(byte)))
+    }
+
+    /// Variable-length integer used by BPS and UPS.
+    fn varint(&mut self) -> Result<usize, RomPatchError> {
+        let mut value = 0usize;
+        let mut shift = 1usize;
+        loop {
+            let byte = self.u8()?;
+            value = usize::from(byte & 0x7F)
+                .checked_mul(shift)
+                .and_then(|part| value.checked_add(part))
+                .ok_or(RomPatchError::Truncated)?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.checked_shl(7).ok_or(RomPatchError::Truncated)?;
+            value = value.checked_add(shift).ok_or(RomPatchError::Truncated)?;
+        }
+    }
+}
+
+fn apply_ips(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, RomPatchError> {
+    let mut target = rom.to_vec();
+    let mut reader = PatchReader::new(patch, IPS_MAGIC.len());
+    loop {
+        if reader.data[reader.pos..].starts_with(IPS_EOF) {
+            reader.pos += IPS_EOF.len();
+            break;
+        }
+        let offset = reader.be(3)?;
+        let len = reader.be(2)?;
+        let (len, rle) = if len == 0 {
+            (reader.be(2)?, Some(reader.u8()?))
+        } else {
+            (len, None)
+        };
+        let end = offset + len;
+        if target.len() < end {
+            target.resize(end, 0);
+        }
+        match rle {
+            Some(value) => target[offset..end].fill(value),
+            None => target[offset..end].copy_from_slice(reader.bytes(len)?),
+        }
+    }
+    // Extension: three bytes after EOF give the truncated size.
+    if reader.data.len() - reader.pos >= 3 {
+        target.truncate(reader.be(3)?);
+    }
+    Ok(target)
+}
+
+fn check_footer(patch: &[u8], source: &[u8], target: &[u8]) -> Result<(), RomPatchError> {
+    let mut footer = PatchReader::new(patch, patch.len() - FOOTER_LEN);
+    let mut next = || {
+        footer
+            .bytes(4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let expected = [next()?, next()?, next()?];
+    let actual = [
+        crc32(source),
+        crc32(target),
+        crc32(&patch[..patch.len() - 4]),
+    ];
+    for ((kind, expected), actual) in ["source", "target", "patch"]
+        .into_iter()
+        .zip(expected)
+        .zip(actual)
+    {
+        if expected != actual {
+            return Err(RomPatchError::Checksum {
+                kind,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_sizes(
+    patch: &[u8],
+    reader: &mut PatchReader<'_>,
+    source: &[u8],
+) -> Result<usize, RomPatchError> {
+    if patch.len() < reader.pos + FOOTER_LEN {
+        return Err(RomPatchError::Truncated);
+    }
+    let source_len = reader.varint()?;
+    let target_len = reader.varint()?;
+    if source_len != source.len() {
+        return Err(RomPatchError::SourceSize {
+            expected: source_len,
+            actual: source.len(),
+        });
+    }
+    // The header is untrusted, so cap the size before allocating the target.
+    if target_len as u64 > MAX_UNPACKED_SIZE {
+        return Err(RomPatchError::TargetTooLarge(target_len));
+    }
+    Ok(target_len)
+}
+
+fn apply_bps(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, RomPatchError> {
+    let mut reader = PatchReader::new(patch, BPS_MAGIC.len());
+    let target_len = check_sizes(patch, &mut reader, source)?;
+    let metadata_len = reader.varint()?;
+    reader.bytes(metadata_len)?;
+
+    let body_end = patch.len() - FOOTER_LEN;
+    let mut target = Vec::with_capacity(target_len);
+    let mut source_rel = 0usize;
+    let mut target_rel = 0usize;
+    while reader.pos < body_end {
+        let action = reader.varint()?;
+        let len = (action >> 2) + 1;
+        if target.len() + len > target_len {
+            return Err(RomPatchError::OutOfBounds(target.len() + len));
+        }
+        match action & 3 {
+            // SourceRead
+            0 => {
+                let start = target.len();
+                let bytes = source
+                    .get(start..start + len)
+                    .ok_or(RomPatchError::OutOfBounds(start + len))?;
+                target.extend_from_slice(bytes);
+            }
+            // TargetRead
+            1 => target.extend_from_slice(reader.bytes(len)?),
+            // SourceCopy
+            2 => {
+                source_rel = relative_offset(source_rel, reader.varint()?)?;
+                let bytes = source
+                    .get(source_rel..source_rel + len)
+                    .ok_or(RomPatchError::OutOfBounds(source_rel + len))?;
+                target.extend_from_slice(bytes);
+                source_rel += len;
+            }
+            // TargetCopy: may overlap the bytes being written, so copy one at a time
+            _ => {
+                target_rel = relative_offset(target_rel, reader.varint()?)?;
+                for _ in 0..len {
+                    let byte = *target
+                        .get(target_rel)
+                        .ok_or(RomPatchError::OutOfBounds(target_rel))?;
+                    target.push(byte);
+                    target_rel += 1;
+                }
+            }
+        }
+    }
+    if target.len() != target_len {
+        return Err(RomPatchError::Truncated);
+    }
+    check_footer(patch, source, &target)?;
+    Ok(target)
+}
+
+fn relative_offset(base: usize, encoded: usize) -> Result<usize, RomPatchError> {
+    let delta = encoded >> 1;
+    if encoded & 1 == 0 {
+        base.checked_add(delta)
+    } else {
+        base.checked_sub(delta)
+    }
+    .ok_or(RomPatchError::OutOfBounds(base))
+}
+
+fn apply_ups(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, RomPatchError> {
+    let mut reader = PatchReader::new(patch, UPS_MAGIC.len());
+    let target_len = check_sizes(patch, &mut reader, source)?;
+
+    let body_end = patch.len() - FOOTER_LEN;
+    let mut target = source.to_vec();
+    target.resize(target_len, 0);
+    let mut pos = 0usize;
+    while reader.pos < body_end {
+        pos = pos
+            .checked_add(reader.varint()?)
+            .ok_or(RomPatchError::OutOfBounds(pos))?;
+        loop {
+            let xor = reader.u8()?;
+            if xor == 0 {
+                pos += 1;
+                break;
+            }
+            *target.get_mut(pos).ok_or(RomPatchError::OutOfBounds(pos))? ^= xor;
+            pos += 1;
+        }
+    }
+    check_footer(patch, source, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_core_traits::CoreError;
+
+    use super::{RomPatchError, apply_patch, apply_patch_files, crc32};
+
+    fn varint(mut value: usize, out: &mut Vec<u8>) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte | 0x80);
+                return;
+            }
+            out.push(byte);
+            value -= 1;
+        }
+    }
+
+    fn with_footer(mut patch: Vec<u8>, source: &[u8], target: &[u8]) -> Vec<u8> {
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&crc32(target).to_le_bytes());
+        patch.extend_from_slice(&crc32(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn ips_applies_records_rle_and_growth() {
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x02, 0xAA, 0xBB]);
+        patch.extend_from_slice(&[0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x03, 0xCC]);
+        patch.extend_from_slice(b"EOF");
+
+        let patched = apply_patch(&[0, 1, 2, 3], &patch).unwrap();
+        assert_eq!(patched, [0, 0xAA, 0xBB, 3, 0xCC, 0xCC, 0xCC]);
+
+        patch.extend_from_slice(&[0x00, 0x00, 0x02]);
+        assert_eq!(apply_patch(&[0, 1, 2, 3], &patch).unwrap(), [0, 0xAA]);
+    }
+
+    #[test]
+    fn bps_reads_source_and_target_runs() {
+        let source = b"ABCDEFGH";
+        let target = b"ABCDxyxyxyEF";
+        let mut patch = b"BPS1".to_vec();
+        varint(source.len(), &mut patch);
+        varint(target.len(), &mut patch);
+        varint(0, &mut patch);
+        // SourceRead 4
+        varint(3 << 2, &mut patch);
+        // TargetRead "xy"
+        varint((1 << 2) | 1, &mut patch);
+        patch.extend_from_slice(b"xy");
+        // TargetCopy 4 from offset 4
+        varint((3 << 2) | 3, &mut patch);
+        varint(4 << 1, &mut patch);
+        // SourceCopy 2 from offset 4
+        varint((1 << 2) | 2, &mut patch);
+        varint(4 << 1, &mut patch);
+        let patch = with_footer(patch, source, target);
+
+        assert_eq!(apply_patch(source, &patch).unwrap(), target);
+        assert!(matches!(
+            apply_patch(b"ABCDEFGX", &patch),
+            Err(RomPatchError::Checksum { kind: "source", .. })
+        ));
+    }
+
+    #[test]
+    fn ups_xors_changed_runs_and_validates_crc() {
+        let source = [1, 2, 3, 4];
+        let target = [1, 0x22, 3, 4, 5];
+        let mut patch = b"UPS1".to_vec();
+        varint(source.len(), &mut patch);
+        varint(target.len(), &mut patch);
+        varint(1, &mut patch);
+        patch.extend_from_slice(&[2 ^ 0x22, 0]);
+        varint(1, &mut patch);
+        patch.extend_from_slice(&[5, 0]);
+        let mut patch = with_footer(patch, &source, &target);
+
+        assert_eq!(apply_patch(&source, &patch).unwrap(), target);
+
+        let last = patch.len() - 1;
+        patch[last] ^= 0xFF;
+        assert!(matches!(
+            apply_patch(&source, &patch),
+            Err(RomPatchError::Checksum { kind: "patch", .. })
+        ));
+    }
+
+    #[test]
+    fn oversized_targets_are_rejected_before_allocating() {
+        for magic in [b"BPS1", b"UPS1"] {
+            let mut patch = magic.to_vec();
+            varint(1, &mut patch);
+            varint(usize::MAX >> 8, &mut patch);
+            patch.extend_from_slice(&[0; 12]);
+            assert_eq!(
+                apply_patch(&[0], &patch),
+                Err(RomPatchError::TargetTooLarge(usize::MAX >> 8))
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_patch_format_is_rejected() {
+        assert_eq!(
+            apply_patch(&[0], b"nope"),
+            Err(RomPatchError::UnknownFormat)
+        );
+    }
+
+    #[test]
+    fn patch_files_apply_in_order_and_report_bad_checksums_as_parse_errors() {
+        let dir = std::env::temp_dir();
+        let ips_path = dir.join(format!("nerust-patch-{}.ips", std::process::id()));
+        let bad_path = dir.join(format!("nerust-patch-{}.ups", std::process::id()));
+        std::fs::write(&ips_path, b"PATCH\x00\x00\x01\x00\x01\xEAEOF").unwrap();
+        std::fs::write(&bad_path, b"UPS1\x81\x81\x80\x00\0\0\0\0\0\0\0\0\0\0\0\0").unwrap();
+
+        let patched = apply_patch_files(&[0, 0], std::slice::from_ref(&ips_path));
+        let bad = apply_patch_files(&[0], std::slice::from_ref(&bad_path));
+        let missing = apply_patch_files(&[0], &[dir.join("nerust-missing-patch.ips")]);
+        let _ = std::fs::remove_file(&ips_path);
+        let _ = std::fs::remove_file(&bad_path);
+
+        assert_eq!(patched.unwrap(), [0, 0xEA]);
+        assert!(matches!(bad, Err(CoreError::RomParse(_))));
+        assert!(matches!(missing, Err(CoreError::Core(_))));
+    }
+}
@@ -78,6 +78,14 @@ pub enum ShortcutAction {
     LoadActiveSlot,
     ToggleFullscreen,
     Reset,
+    Rewind,
+    FastForward,
+    ToggleFastForward,
+    ToggleSlowMotion,
+    EjectDisk,
+    SwitchDiskSide,
+    NextTrack,
+    PreviousTrack,
 }
 
 #[derive(
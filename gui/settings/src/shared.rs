@@ -14,6 +14,8 @@ pub struct DesktopSharedSettings {
     pub general: GeneralSettings,
     pub persistence: PersistenceSettings,
     pub input: InputSettings,
+    pub speed: SpeedSettings,
+    pub rewind: RewindSettings,
     pub systems: HashMap<Box<dyn SystemId>, Box<dyn SystemSettingsTrait>>,
 }
 
@@ -24,6 +26,8 @@ impl Default for DesktopSharedSettings {
             general: GeneralSettings::default(),
             persistence: PersistenceSettings::default(),
             input: InputSettings::default(),
+            speed: SpeedSettings::default(),
+            rewind: RewindSettings::default(),
             systems: HashMap::new(),
         }
     }
@@ -51,6 +55,52 @@ impl Default for PersistenceSettings {
     }
 }
 
+/// Fast-forward / slow-motion rates, in percent of native speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SpeedSettings {
+    /// `None` runs fast-forward uncapped.
+    pub fast_forward_percent: Option<u16>,
+    pub slow_motion_percent: u16,
+}
+
+impl SpeedSettings {
+    /// Fast-forward rates offered by the settings UIs.
+    pub const FAST_FORWARD_CHOICES: [Option<u16>; 5] =
+        [None, Some(200), Some(300), Some(400), Some(800)];
+    /// Slow-motion rates offered by the settings UIs.
+    pub const SLOW_MOTION_CHOICES: [u16; 3] = [25, 50, 75];
+}
+
+impl Default for SpeedSettings {
+    fn default() -> Self {
+        Self {
+            fast_forward_percent: None,
+            slow_motion_percent: 50,
+        }
+    }
+}
+
+/// Rewind history tuning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct RewindSettings {
+    /// Frames emulated between two snapshots. The history holds a fixed number of
+    /// snapshots, so a longer interval rewinds further in coarser steps.
+    pub interval_frames: u32,
+}
+
+impl RewindSettings {
+    /// Intervals offered by the settings UIs.
+    pub const INTERVAL_CHOICES: [u32; 4] = [1, 2, 4, 8];
+}
+
+impl Default for RewindSettings {
+    fn default() -> Self {
+        Self { interval_frames: 2 }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum StoragePolicy {
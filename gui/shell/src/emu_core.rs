@@ -1,5 +1,6 @@
 use std::{
     collections::HashMap,
+    path::PathBuf,
     sync::{
         Arc, Mutex,
         atomic::{AtomicBool, Ordering},
@@ -8,7 +9,10 @@ use std::{
 };
 
 use nerust_core_traits::{
-    CoreConfig, CoreOptions, EmuCommand, LoadCommand, StateDataCommand,
+    CheatCommand, CoreConfig, CoreOptions, DebugCommand, EmuCommand, EmuSpeed, LoadCommand,
+    MemoryCommand, RewindConfig, StateDataCommand, VideoViewCommand,
+    cheat::{Cheat, CheatOp},
+    debug::{BreakReason, DebugOp, DebugState, MemoryBlock, MemoryOp, VideoMemoryView},
     factory::{CoreParts, load::MediaObject},
     identity::SystemIdentity,
 };
@@ -85,6 +89,7 @@ impl EmuCore {
         let pixel_format = PixelFormat::PaletteIndex {
             palette: parts.palette.clone(),
         };
+        let frame_len = src_w * src_h * pixel_format.bytes_per_pixel();
 
         let shared_fb = Arc::new(Mutex::new(FrameBuffer::with_capacity(
             src_w,
@@ -93,12 +98,12 @@ impl EmuCore {
         )));
         if let Ok(mut guard) = shared_fb.lock() {
             guard.resize(src_w, src_h);
-            guard.resize_data(src_w * src_h);
+            guard.resize_data(frame_len);
         }
 
         let mut disp_fb = FrameBuffer::with_capacity(src_w, src_h, pixel_format);
         disp_fb.resize(src_w, src_h);
-        disp_fb.resize_data(src_w * src_h);
+        disp_fb.resize_data(frame_len);
 
         let frame_ready = Arc::new(AtomicBool::new(false));
         let emu = EmuThread::spawn(
@@ -164,6 +169,7 @@ impl EmuCore {
         });
         guard.frame_counter = self.emu.frame_count();
         guard.emulation_fps = self.emu.fps();
+        guard.speed_multiplier = self.emu.speed_multiplier();
         *guard
     }
 
@@ -199,6 +205,7 @@ impl EmuCore {
         &self,
         media: &MediaObject,
         core_options: Option<Box<dyn CoreOptions>>,
+        bios_paths: &HashMap<String, PathBuf>,
     ) -> Result<(), OperationError> {
         let (reply_tx, reply_rx) = mpsc::channel();
         self.emu
@@ -206,7 +213,7 @@ impl EmuCore {
                 rom: media.bytes.as_ref().to_vec(),
                 config: CoreConfig {
                     region: None,
-                    bios_paths: HashMap::new(),
+                    bios_paths: bios_paths.clone(),
                     controllers: HashMap::new(),
                     core_options,
                 },
@@ -240,12 +247,149 @@ impl EmuCore {
         Ok(())
     }
 
+    pub fn set_speed(&self, speed: EmuSpeed) -> Result<(), OperationError> {
+        self.emu
+            .send(EmuCommand::SetSpeed(speed))
+            .map_err(|_| OperationError::WorkerUnavailable)
+    }
+
+    pub fn configure_rewind(&self, config: RewindConfig) -> Result<(), OperationError> {
+        self.emu
+            .send(EmuCommand::ConfigureRewind(config))
+            .map_err(|_| OperationError::WorkerUnavailable)
+    }
+
+    pub fn set_rewinding(&self, rewinding: bool) -> Result<(), OperationError> {
+        self.emu
+            .send(EmuCommand::SetRewinding(rewinding))
+            .map_err(|_| OperationError::WorkerUnavailable)
+    }
+
     pub fn reset(&self) -> Result<(), OperationError> {
         self.emu
             .send(EmuCommand::Reset)
             .map_err(|_| OperationError::WorkerUnavailable)
     }
 
+    pub fn eject_disk(&self) -> Result<(), OperationError> {
+        self.emu
+            .send(EmuCommand::EjectDisk)
+            .map_err(|_| OperationError::WorkerUnavailable)
+    }
+
+    pub fn switch_disk_side(&self) -> Result<(), OperationError> {
+        self.emu
+            .send(EmuCommand::SwitchDiskSide)
+            .map_err(|_| OperationError::WorkerUnavailable)
+    }
+
+    pub fn next_track(&self) -> Result<(), OperationError> {
+        self.emu
+            .send(EmuCommand::NextTrack)
+            .map_err(|_| OperationError::WorkerUnavailable)
+    }
+
+    pub fn previous_track(&self) -> Result<(), OperationError> {
+        self.emu
+            .send(EmuCommand::PreviousTrack)
+            .map_err(|_| OperationError::WorkerUnavailable)
+    }
+
+    /// Applies one cheat list edit and returns the core's list afterwards.
+    pub fn cheat_op(&self, op: CheatOp) -> Result<Vec<Cheat>, OperationError> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.emu
+            .send(EmuCommand::Cheat(Box::new(CheatCommand {
+                op,
+                reply: reply_tx,
+            })))
+            .map_err(|_| OperationError::WorkerUnavailable)?;
+        reply_rx
+            .recv()
+            .map_err(|_| OperationError::NoReply)?
+            .map_err(|e| OperationError::Reply(e.to_string()))
+    }
+
+    /// Replaces the core's cheat list. Stops at the first code the core rejects.
+    pub fn set_cheats(&self, cheats: &[Cheat]) -> Result<Vec<Cheat>, OperationError> {
+        let mut current = self.cheat_op(CheatOp::Clear)?;
+        for cheat in cheats {
+            current = self.cheat_op(CheatOp::Add(cheat.clone()))?;
+        }
+        Ok(current)
+    }
+
+    /// Applies one debugger request and returns the CPU state afterwards.
+    /// A step resumes the core until the step finishes.
+    pub fn debug_op(&self, op: DebugOp) -> Result<DebugState, OperationError> {
+        let resumes = matches!(op, DebugOp::Step(_));
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.emu
+            .send(EmuCommand::Debug(Box::new(DebugCommand {
+                op,
+                reply: reply_tx,
+            })))
+            .map_err(|_| OperationError::WorkerUnavailable)?;
+        let state = reply_rx
+            .recv()
+            .map_err(|_| OperationError::NoReply)?
+            .map_err(|e| OperationError::Reply(e.to_string()))?;
+        if resumes {
+            match self.metrics.lock() {
+                Ok(mut guard) => guard.paused = false,
+                Err(e) => log::warn!("metrics lock poisoned in debug_op: {e}"),
+            }
+        }
+        Ok(state)
+    }
+
+    /// Sets the scanline video memory is captured at and returns the latest
+    /// capture. `None` stops capturing.
+    pub fn video_view(
+        &self,
+        capture_scanline: Option<u16>,
+        pattern_palette: u8,
+    ) -> Result<VideoMemoryView, OperationError> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.emu
+            .send(EmuCommand::VideoView(Box::new(VideoViewCommand {
+                capture_scanline,
+                pattern_palette,
+                reply: reply_tx,
+            })))
+            .map_err(|_| OperationError::WorkerUnavailable)?;
+        reply_rx
+            .recv()
+            .map_err(|_| OperationError::NoReply)?
+            .map_err(|e| OperationError::Reply(e.to_string()))
+    }
+
+    /// Reads or writes memory through the memory viewer interface.
+    pub fn memory_op(&self, op: MemoryOp) -> Result<MemoryBlock, OperationError> {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        self.emu
+            .send(EmuCommand::Memory(Box::new(MemoryCommand {
+                op,
+                reply: reply_tx,
+            })))
+            .map_err(|_| OperationError::WorkerUnavailable)?;
+        reply_rx
+            .recv()
+            .map_err(|_| OperationError::NoReply)?
+            .map_err(|e| OperationError::Reply(e.to_string()))
+    }
+
+    /// Returns why the core stopped on a breakpoint or step, if it has since the
+    /// last call. The emu thread has already paused the core.
+    pub fn take_debug_break(&self) -> Option<BreakReason> {
+        let reason = self.emu.take_debug_break()?;
+        match self.metrics.lock() {
+            Ok(mut guard) => guard.paused = true,
+            Err(e) => log::warn!("metrics lock poisoned in take_debug_break: {e}"),
+        }
+        Some(reason)
+    }
+
     pub fn save_mapper_raw(&self) -> Result<Option<Vec<u8>>, OperationError> {
         let (reply_tx, reply_rx) = mpsc::channel();
         self.emu
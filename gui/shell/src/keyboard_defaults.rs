@@ -26,14 +26,58 @@ pub fn default_keyboard_key(abstract_key: AbstractKey) -> Vec<Key> {
     }
 }
 
+/// Alternate layouts for additional players, kept clear of the primary layout
+/// and of the default shortcuts. `layout` 0 is the IJKL cluster, 1 the numpad.
+pub fn alternate_keyboard_key(layout: usize, abstract_key: AbstractKey) -> Vec<Key> {
+    let key = match (layout, abstract_key) {
+        (0, AbstractKey::Button1) => Key::KeyO,
+        (0, AbstractKey::Button2) => Key::KeyU,
+        (0, AbstractKey::Select) => Key::Digit7,
+        (0, AbstractKey::Start) => Key::Digit8,
+        (0, AbstractKey::DpadUp) => Key::KeyI,
+        (0, AbstractKey::DpadDown) => Key::KeyK,
+        (0, AbstractKey::DpadLeft) => Key::KeyJ,
+        (0, AbstractKey::DpadRight) => Key::KeyL,
+        (1, AbstractKey::Button1) => Key::Numpad9,
+        (1, AbstractKey::Button2) => Key::Numpad7,
+        (1, AbstractKey::Select) => Key::Numpad1,
+        (1, AbstractKey::Start) => Key::Numpad3,
+        (1, AbstractKey::DpadUp) => Key::Numpad8,
+        (1, AbstractKey::DpadDown) => Key::Numpad5,
+        (1, AbstractKey::DpadLeft) => Key::Numpad4,
+        (1, AbstractKey::DpadRight) => Key::Numpad6,
+        _ => return vec![],
+    };
+    vec![key]
+}
+
 /// Generate default keyboard bindings for a system using abstract key mappings.
 ///
 /// `attachment_id` is the attachment prefix (e.g. "nes.attachment.player1").
 /// `control_prefix` is the control ID prefix (e.g. "nes.control").
 pub fn default_system_bindings(attachment_id: &str, control_prefix: &str) -> Vec<KeyboardBinding> {
+    system_bindings(attachment_id, control_prefix, default_keyboard_key)
+}
+
+/// Default bindings for an additional player using alternate layout `layout`.
+pub fn alternate_system_bindings(
+    attachment_id: &str,
+    control_prefix: &str,
+    layout: usize,
+) -> Vec<KeyboardBinding> {
+    system_bindings(attachment_id, control_prefix, |ak| {
+        alternate_keyboard_key(layout, ak)
+    })
+}
+
+fn system_bindings(
+    attachment_id: &str,
+    control_prefix: &str,
+    keys: impl Fn(AbstractKey) -> Vec<Key>,
+) -> Vec<KeyboardBinding> {
     use AbstractKey::*;
     let p1 = |control: &str, ak: AbstractKey| -> Vec<KeyboardBinding> {
-        default_keyboard_key(ak)
+        keys(ak)
             .into_iter()
             .map(|key| {
                 KeyboardBinding::new(
@@ -55,3 +99,23 @@ pub fn default_system_bindings(attachment_id: &str, control_prefix: &str) -> Vec
     b.extend(p1("right", DpadRight));
     b
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{alternate_system_bindings, default_system_bindings};
+
+    #[test]
+    fn player_layouts_do_not_share_keys() {
+        let mut keys = HashSet::new();
+        let bindings = default_system_bindings("p1", "test")
+            .into_iter()
+            .chain(alternate_system_bindings("p3", "test", 0))
+            .chain(alternate_system_bindings("p4", "test", 1))
+            .collect::<Vec<_>>();
+
+        assert_eq!(bindings.len(), 24);
+        assert!(bindings.iter().all(|binding| keys.insert(binding.key)));
+    }
+}
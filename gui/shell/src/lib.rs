@@ -2,6 +2,7 @@ pub mod context;
 pub mod emu_core;
 pub mod keyboard_defaults;
 pub mod load;
+pub mod ram_search;
 pub mod registry;
 pub mod session;
 pub mod settings;
@@ -18,6 +18,8 @@ pub enum SystemActivationError {
 pub enum RomLoaderError {
     #[error("I/O error: {0}")]
     Io(String),
+    #[error("archive contains several ROMs: {0:?}")]
+    MultipleRoms(Vec<String>),
     #[error("ROM detection failed: {0}")]
     Detect(String),
     #[error("load request resolution failed: {0}")]
@@ -54,15 +56,20 @@ pub trait RomLoadTarget {
 /// Loads and resolves a ROM file into a [`RomLoadTarget`].
 ///
 /// Implementations handle:
-/// - Reading the file from disk
+/// - Reading the file from disk, unpacking zip/gzip archives
 /// - Creating a `MediaObject` from the file contents
 /// - Resolving system-specific load options (e.g., MMC3 IRQ variant)
 /// - Calling `target.load_resolved()` to start emulation
 /// - Calling `SessionCommand::Resume` after successful load
+///
+/// `entry` names the archive member to load. When it is `None` and the archive
+/// holds several ROMs, `RomLoaderError::MultipleRoms` is returned so the
+/// frontend can ask the user and call again with the chosen entry.
 pub trait RomLoader {
     fn load_rom(
         &mut self,
         path: &Path,
+        entry: Option<&str>,
         target: &mut dyn RomLoadTarget,
     ) -> Result<(), RomLoaderError>;
 }
@@ -0,0 +1,162 @@
+//! RAM search for finding cheat addresses: take a snapshot, then narrow the
+//! candidates down by comparing each new read with the value seen last time.
+
+use nerust_core_traits::debug::MemoryBlock;
+
+/// How a candidate's current value must relate to the value seen at the last step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFilter {
+    /// Equal to the given value.
+    Equal(u8),
+    Changed,
+    Unchanged,
+    Greater,
+    Less,
+}
+
+impl SearchFilter {
+    pub fn matches(self, previous: u8, current: u8) -> bool {
+        match self {
+            Self::Equal(value) => current == value,
+            Self::Changed => current != previous,
+            Self::Unchanged => current == previous,
+            Self::Greater => current > previous,
+            Self::Less => current < previous,
+        }
+    }
+}
+
+/// An address still in the running, with the value seen at the last step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    pub address: u32,
+    pub previous: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RamSearch {
+    candidates: Vec<Candidate>,
+    started: bool,
+}
+
+impl RamSearch {
+    /// Starts over with every readable byte of `blocks` as a candidate.
+    pub fn start(&mut self, blocks: &[MemoryBlock]) {
+        self.candidates = blocks
+            .iter()
+            .flat_map(|block| {
+                (block.start..)
+                    .zip(&block.bytes)
+                    .filter_map(|(address, value)| {
+                        value.map(|previous| Candidate { address, previous })
+                    })
+            })
+            .collect();
+        self.started = true;
+    }
+
+    /// Keeps the candidates whose value in `blocks` passes `filter` and remembers
+    /// that value for the next step. Candidates that can no longer be read drop out.
+    pub fn filter(&mut self, filter: SearchFilter, blocks: &[MemoryBlock]) {
+        self.candidates.retain_mut(|candidate| {
+            let Some(current) = read(blocks, candidate.address) else {
+                return false;
+            };
+            let keep = filter.matches(candidate.previous, current);
+            candidate.previous = current;
+            keep
+        });
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// Whether a snapshot has been taken since the search was created or cleared.
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    pub fn clear(&mut self) {
+        self.candidates.clear();
+        self.started = false;
+    }
+}
+
+/// The value at `address` in whichever block covers it.
+pub fn read(blocks: &[MemoryBlock], address: u32) -> Option<u8> {
+    blocks.iter().find_map(|block| block.get(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_core_traits::debug::MemorySpace;
+
+    use super::*;
+
+    fn block(start: u32, bytes: &[Option<u8>]) -> MemoryBlock {
+        MemoryBlock {
+            space: MemorySpace::CpuBus,
+            start,
+            bytes: bytes.to_vec(),
+            space_len: 0x10000,
+        }
+    }
+
+    #[test]
+    fn start_skips_unreadable_bytes() {
+        let mut search = RamSearch::default();
+        assert!(!search.is_started());
+
+        search.start(&[block(0x10, &[Some(1), None]), block(0x6000, &[Some(3)])]);
+
+        assert!(search.is_started());
+        assert_eq!(
+            search.candidates(),
+            [
+                Candidate {
+                    address: 0x10,
+                    previous: 1,
+                },
+                Candidate {
+                    address: 0x6000,
+                    previous: 3,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn filters_compare_with_the_previous_step() {
+        let mut search = RamSearch::default();
+        search.start(&[block(0, &[Some(5), Some(5), Some(5), Some(5)])]);
+
+        // 0 went down, 1 stayed, 2 and 3 went up
+        search.filter(
+            SearchFilter::Greater,
+            &[block(0, &[Some(4), Some(5), Some(6), Some(9)])],
+        );
+        let addresses: Vec<u32> = search.candidates().iter().map(|c| c.address).collect();
+        assert_eq!(addresses, [2, 3]);
+
+        // The previous values are now 6 and 9
+        search.filter(
+            SearchFilter::Changed,
+            &[block(0, &[Some(4), Some(5), Some(6), Some(8)])],
+        );
+        assert_eq!(
+            search.candidates(),
+            [Candidate {
+                address: 3,
+                previous: 8,
+            }]
+        );
+
+        search.filter(
+            SearchFilter::Equal(7),
+            &[block(0, &[None, None, None, Some(8)])],
+        );
+        assert!(search.candidates().is_empty());
+        assert!(search.is_started());
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, path::Path, sync::Arc};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use nerust_core_traits::{
     factory::{
@@ -7,7 +11,7 @@ use nerust_core_traits::{
     },
     identity::SystemId,
 };
-use nerust_gui_runtime::rom::load_rom_path;
+use nerust_gui_runtime::rom::{RomFileError, load_rom_path, sidecar_patch_paths};
 
 use crate::load::{RomLoadTarget, RomLoader, RomLoaderError};
 use nerust_settings_core::factory::settings_view;
@@ -82,9 +86,12 @@ impl SystemRegistry {
     /// Each option is consumed on the first
     /// load of the corresponding system; subsequent loads fall back to
     /// `RomLoadTarget::default_load_options()`.
+    /// `pending_patches` are CLI-provided ROM patches, applied after the
+    /// ROM's sidecar patches on the first load only.
     pub fn create_loader(
         self: &Arc<Self>,
         pending_options: HashMap<Box<dyn SystemId>, Box<dyn DynSystemLoadOptions>>,
+        pending_patches: Vec<PathBuf>,
     ) -> Result<Box<dyn RomLoader>, RegistryError> {
         if let Some(system_id) = pending_options
             .keys()
@@ -99,6 +106,7 @@ impl SystemRegistry {
         Ok(Box::new(RegistryRomLoader {
             registry: Arc::clone(self),
             pending_options,
+            pending_patches,
         }))
     }
 }
@@ -108,17 +116,29 @@ impl SystemRegistry {
 struct RegistryRomLoader {
     registry: Arc<SystemRegistry>,
     pending_options: HashMap<Box<dyn SystemId>, Option<Box<dyn DynSystemLoadOptions>>>,
+    pending_patches: Vec<PathBuf>,
 }
 
 impl RomLoader for RegistryRomLoader {
     fn load_rom(
         &mut self,
         path: &Path,
+        entry: Option<&str>,
         target: &mut dyn RomLoadTarget,
     ) -> Result<(), RomLoaderError> {
-        let loaded = load_rom_path(path).map_err(|e| RomLoaderError::Io(e.to_string()))?;
+        let loaded = load_rom_path(path, entry).map_err(|e| match e {
+            RomFileError::MultipleRoms(candidates) => RomLoaderError::MultipleRoms(candidates),
+            e => RomLoaderError::Io(e.to_string()),
+        })?;
+        let entry_extension = loaded
+            .entry()
+            .and_then(|entry| Path::new(entry).extension())
+            .map(|ext| ext.to_string_lossy().to_lowercase());
         let (rom_path, data) = loaded.into_parts();
-        let media = MediaObject::new(Some(rom_path), data);
+        let mut media = MediaObject::new(Some(rom_path), data);
+        if entry_extension.is_some() {
+            media.extension = entry_extension;
+        }
 
         let factory = self
             .registry
@@ -145,9 +165,13 @@ impl RomLoader for RegistryRomLoader {
                     .unwrap_or_else(|| factory.default_load_options())
             });
 
-        let resolved = factory
+        let mut resolved = factory
             .resolve_load_request(&view, options)
             .map_err(|e| RomLoaderError::Resolve(e.to_string()))?;
+        resolved.patch_paths = sidecar_patch_paths(path);
+        resolved
+            .patch_paths
+            .extend(std::mem::take(&mut self.pending_patches));
         target.load_resolved(media, resolved)?;
 
         target.resume();
@@ -216,6 +240,8 @@ mod tests {
         ) -> Result<ResolvedLoadRequest, FactoryError> {
             Ok(ResolvedLoadRequest {
                 options: Box::<NoopCoreOptions>::default(),
+                bios_paths: Default::default(),
+                patch_paths: Vec::new(),
             })
         }
         fn default_load_options(&self) -> Box<dyn DynSystemLoadOptions> {
@@ -255,6 +281,7 @@ mod tests {
         snapshot: SettingsSnapshot,
         active_system: Option<Box<dyn SystemId>>,
         loaded_media: Option<MediaObject>,
+        patch_paths: Vec<PathBuf>,
         resumed: bool,
     }
 
@@ -268,6 +295,7 @@ mod tests {
                 },
                 active_system: None,
                 loaded_media: None,
+                patch_paths: Vec::new(),
                 resumed: false,
             }
         }
@@ -285,9 +313,10 @@ mod tests {
         fn load_resolved(
             &mut self,
             media: MediaObject,
-            _resolved: ResolvedLoadRequest,
+            resolved: ResolvedLoadRequest,
         ) -> Result<(), RomLoaderError> {
             self.loaded_media = Some(media);
+            self.patch_paths = resolved.patch_paths;
             Ok(())
         }
 
@@ -382,7 +411,7 @@ mod tests {
         let registry = Arc::new(SystemRegistry::new(vec![factory.clone()]));
         let opts = factory.default_load_options();
         let _loader = registry
-            .create_loader(HashMap::from([(factory.system_id(), opts)]))
+            .create_loader(HashMap::from([(factory.system_id(), opts)]), Vec::new())
             .unwrap();
     }
 
@@ -392,7 +421,7 @@ mod tests {
         let options = NoopSystemLoadOptions.into();
 
         assert!(matches!(
-            registry.create_loader(HashMap::from([(Box::new(DummyOtherSystemId) as Box<_>, options)])),
+            registry.create_loader(HashMap::from([(Box::new(DummyOtherSystemId) as Box<_>, options)]), Vec::new()),
             Err(RegistryError::UnregisteredOptions(id)) if id.as_ref() == &DummyOtherSystemId as &dyn SystemId
         ));
     }
@@ -402,12 +431,12 @@ mod tests {
         let factory: Arc<dyn CoreFactory> =
             Arc::new(MatchingStubFactory(Box::new(DummySystemId), true));
         let registry = Arc::new(SystemRegistry::new(vec![factory]));
-        let mut loader = registry.create_loader(HashMap::new()).unwrap();
+        let mut loader = registry.create_loader(HashMap::new(), Vec::new()).unwrap();
         let path = temp_rom_path("success.nes");
         fs::write(&path, [0x4e, 0x45, 0x53, 0x1a]).unwrap();
         let mut target = RecordingTarget::new();
 
-        loader.load_rom(&path, &mut target).unwrap();
+        loader.load_rom(&path, None, &mut target).unwrap();
 
         assert!(
             target
@@ -426,12 +455,12 @@ mod tests {
     #[test]
     fn loader_rejects_unsupported_media_without_resuming() {
         let registry = Arc::new(SystemRegistry::new(vec![stub_factory()]));
-        let mut loader = registry.create_loader(HashMap::new()).unwrap();
+        let mut loader = registry.create_loader(HashMap::new(), Vec::new()).unwrap();
         let path = temp_rom_path("unsupported.rom");
         fs::write(&path, [1, 2, 3]).unwrap();
         let mut target = RecordingTarget::new();
 
-        let error = loader.load_rom(&path, &mut target).unwrap_err();
+        let error = loader.load_rom(&path, None, &mut target).unwrap_err();
 
         assert!(
             matches!(error, RomLoaderError::Detect(message) if message == "unsupported ROM format")
@@ -443,13 +472,66 @@ mod tests {
     #[test]
     fn loader_reports_missing_rom_as_io_error() {
         let registry = Arc::new(SystemRegistry::new(vec![stub_factory()]));
-        let mut loader = registry.create_loader(HashMap::new()).unwrap();
+        let mut loader = registry.create_loader(HashMap::new(), Vec::new()).unwrap();
         let path = temp_rom_path("missing.nes");
         let _ = fs::remove_file(&path);
         let mut target = RecordingTarget::new();
 
-        let error = loader.load_rom(&path, &mut target).unwrap_err();
+        let error = loader.load_rom(&path, None, &mut target).unwrap_err();
 
         assert!(matches!(error, RomLoaderError::Io(_)));
     }
+
+    #[test]
+    fn loader_unpacks_zip_and_asks_for_entry_when_ambiguous() {
+        let factory: Arc<dyn CoreFactory> =
+            Arc::new(MatchingStubFactory(Box::new(DummySystemId), true));
+        let registry = Arc::new(SystemRegistry::new(vec![factory]));
+        let mut loader = registry.create_loader(HashMap::new(), Vec::new()).unwrap();
+        let path = temp_rom_path("set.zip");
+        fs::write(
+            &path,
+            nerust_gui_runtime::rom::zip_bytes(&[("a.nes", &[1]), ("b.nes", &[2])]),
+        )
+        .unwrap();
+
+        let mut target = RecordingTarget::new();
+        let error = loader.load_rom(&path, None, &mut target).unwrap_err();
+        assert!(
+            matches!(error, RomLoaderError::MultipleRoms(ref candidates) if candidates == &["a.nes", "b.nes"])
+        );
+        assert!(!target.resumed);
+
+        loader.load_rom(&path, Some("b.nes"), &mut target).unwrap();
+        let media = target.loaded_media.as_ref().unwrap();
+        assert_eq!(media.bytes.as_ref(), [2]);
+        assert_eq!(media.extension.as_deref(), Some("nes"));
+        assert!(target.resumed);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn loader_forwards_sidecar_patches_then_pending_cli_patches_once() {
+        let factory: Arc<dyn CoreFactory> =
+            Arc::new(MatchingStubFactory(Box::new(DummySystemId), true));
+        let registry = Arc::new(SystemRegistry::new(vec![factory]));
+        let cli_patch = temp_rom_path("cli.bps");
+        let mut loader = registry
+            .create_loader(HashMap::new(), vec![cli_patch.clone()])
+            .unwrap();
+        let path = temp_rom_path("patched.nes");
+        let sidecar = path.with_extension("ips");
+        fs::write(&path, [0x4e, 0x45, 0x53, 0x1a]).unwrap();
+        fs::write(&sidecar, b"PATCHEOF").unwrap();
+
+        let mut target = RecordingTarget::new();
+        loader.load_rom(&path, None, &mut target).unwrap();
+        assert_eq!(target.patch_paths, [sidecar.clone(), cli_patch]);
+
+        loader.load_rom(&path, None, &mut target).unwrap();
+        assert_eq!(target.patch_paths, std::slice::from_ref(&sidecar));
+
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(sidecar);
+    }
 }
@@ -5,15 +5,19 @@ pub mod lifecycle;
 pub mod persistence;
 #[cfg(test)]
 mod persistence_test;
+mod speed;
 pub mod title;
 
 use std::{
     collections::{BTreeSet, HashMap},
+    path::PathBuf,
     sync::Arc,
 };
 
 use nerust_core_traits::{
+    CoreError,
     audio::AudioBackendRegistry,
+    cheat::Cheat,
     factory::{
         CoreFactory, FactoryError,
         load::{DynSystemLoadOptions, MediaObject, ResolvedLoadRequest},
@@ -34,7 +38,10 @@ use nerust_settings_core::factory::settings_view;
 use thiserror::Error;
 
 use crate::{
-    emu_core::EmuCore, registry::SystemRegistry, session::persistence::PersistenceManager, settings,
+    emu_core::EmuCore,
+    registry::SystemRegistry,
+    session::{persistence::PersistenceManager, speed::SpeedControl},
+    settings,
 };
 
 struct CoreRuntime {
@@ -62,6 +69,7 @@ struct CoreCreation {
 #[derive(Debug, Clone)]
 pub(super) struct LoadedMedia {
     media: MediaObject,
+    bios_paths: HashMap<String, PathBuf>,
 }
 
 #[derive(Debug, Clone)]
@@ -92,7 +100,9 @@ pub struct SessionHandle {
     pressed_keys: BTreeSet<Key>,
     loaded_media: Option<LoadedMedia>,
     persistence: PersistenceManager,
+    cheats: Vec<Cheat>,
     audio_registry: Arc<AudioBackendRegistry>,
+    speed: SpeedControl,
 }
 
 impl SessionHandle {
@@ -280,7 +290,9 @@ impl SessionHandle {
             pressed_keys: BTreeSet::new(),
             loaded_media: None,
             persistence: PersistenceManager::new(),
+            cheats: Vec::new(),
             audio_registry,
+            speed: SpeedControl::default(),
         };
         result.rebuild_key_field_map();
         Ok(result)
@@ -423,6 +435,8 @@ pub enum SessionError {
     Persistence(#[from] PersistenceError),
     #[error("factory: {0}")]
     Factory(#[from] FactoryError),
+    #[error("core: {0}")]
+    Core(#[from] CoreError),
     #[error("no emulation core active")]
     NoCore,
 }
@@ -489,7 +503,12 @@ impl RomLoadTarget for SessionHandle {
         self.field_map = created.runtime.field_map;
         self.loaded_media = None;
         self.persistence.reset();
+        self.cheats.clear();
         self.pressed_keys.clear();
+        self.speed = SpeedControl::default();
+        if let Err(e) = self.apply_rewind() {
+            log::warn!("configure_rewind failed: {e}");
+        }
         self.rebuild_key_field_map();
         Ok(())
     }
@@ -13,6 +13,16 @@ pub enum SessionCommand {
     DeleteSlot(u64),
     SelectNextSlot,
     SelectPreviousSlot,
+    /// Hold-to-rewind: `true` while the rewind input is held.
+    SetRewinding(bool),
+    /// Hold-to-fast-forward: `true` while the fast-forward input is held.
+    SetFastForward(bool),
+    ToggleFastForward,
+    ToggleSlowMotion,
+    EjectDisk,
+    SwitchDiskSide,
+    NextTrack,
+    PreviousTrack,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
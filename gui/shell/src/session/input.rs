@@ -2,14 +2,15 @@ use std::{collections::HashMap, hash::Hash};
 
 use nerust_gui_settings::input::{KeyboardBinding, ShortcutAction};
 use nerust_input_traits::{
-    AttachmentId, DigitalControlId, DigitalInputEvent, InputAssignments, InputValue,
+    AbstractKey, AttachmentId, ControlKind, DigitalControlId, DigitalInputEvent, InputAssignments,
+    InputValue,
 };
 use nerust_keyboard::Key;
 use nerust_settings_core::factory::settings_view;
 
 use crate::{
     session::{KeyboardShortcut, SessionError, SessionHandle},
-    settings::bindings::events::shortcut::shortcut_action_for_key,
+    settings::bindings::events::shortcut::{hold_shortcut_command, shortcut_action_for_key},
 };
 
 pub use nerust_settings_core::input::{build_topology, clear_multi_port_conflicts, device_kind};
@@ -46,6 +47,23 @@ fn rebuild_input_map<B: InputBinding>(
     }
 }
 
+fn frame_position(
+    (cursor_x, cursor_y): (f64, f64),
+    (window_width, window_height): (f64, f64),
+    (content_width, content_height): (f64, f64),
+) -> Option<(f64, f64)> {
+    if window_width <= 0.0 || window_height <= 0.0 || content_width <= 0.0 || content_height <= 0.0
+    {
+        return None;
+    }
+    let rate = (window_width / content_width).min(window_height / content_height);
+    let width = content_width * rate;
+    let height = content_height * rate;
+    let x = (cursor_x - (window_width - width) * 0.5) / width;
+    let y = (cursor_y - (window_height - height) * 0.5) / height;
+    ((0.0..1.0).contains(&x) && (0.0..1.0).contains(&y)).then_some((x, y))
+}
+
 impl SessionHandle {
     /// Reassign controllers and rebuild the core.
     pub fn reassign_controllers(
@@ -67,7 +85,9 @@ impl SessionHandle {
             .unwrap_or_default()
             .paused;
         if let Some(loaded_media) = self.loaded_media.clone() {
-            rebuilt_core.load(&loaded_media.media, None)?;
+            rebuilt_core.load(&loaded_media.media, None, &loaded_media.bios_paths)?;
+            // 読み込み直したコアはチートを持たないので、表示中の一覧をかけ直す
+            self.cheats = rebuilt_core.set_cheats(&self.cheats)?;
             if !was_paused {
                 rebuilt_core.resume()?;
             }
@@ -76,6 +96,8 @@ impl SessionHandle {
         self.gui_input = Some(gui_input);
         self.field_map = field_map;
         self.current_assignments = assignments.clone();
+        self.apply_speed()?;
+        self.apply_rewind()?;
         self.rebuild_key_field_map();
         Ok(())
     }
@@ -91,6 +113,63 @@ impl SessionHandle {
         }
     }
 
+    /// Feeds the host pointer to every assigned pointer device (e.g. a light
+    /// gun). `position` is in normalized frame coordinates, `None` while the
+    /// pointer is outside the frame; `pressed` drives the device's primary button.
+    pub fn apply_pointer(&mut self, position: Option<(f64, f64)>, pressed: bool) {
+        let Some(ref mut gui_input) = self.gui_input else {
+            return;
+        };
+        let (x, y) = position.unwrap_or((-1.0, -1.0));
+        for (attachment, profile) in &self.current_assignments.slots {
+            let Some(profile) = profile else {
+                continue;
+            };
+            for ps in profile.port_sets() {
+                let Some(controls) = ps
+                    .ports
+                    .iter()
+                    .position(|port| port == attachment)
+                    .and_then(|gi| profile.port_groups().get(gi))
+                else {
+                    continue;
+                };
+                if !controls.iter().any(|c| c.kind == ControlKind::Mouse) {
+                    continue;
+                }
+                for control in *controls {
+                    let value = match control.kind {
+                        ControlKind::Mouse => InputValue::Position { x, y },
+                        ControlKind::Digital
+                            if control.abstract_key == Some(AbstractKey::Button1) =>
+                        {
+                            InputValue::Digital(pressed)
+                        }
+                        _ => continue,
+                    };
+                    if let Some(&field) = self.field_map.get(&(*attachment, control.id)) {
+                        let _ = gui_input.state.set(field, value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts a cursor position in window pixels to normalized frame
+    /// coordinates, assuming the frame is fitted and centered in the window.
+    pub fn pointer_frame_position(
+        &self,
+        cursor: (f64, f64),
+        window: (f64, f64),
+    ) -> Option<(f64, f64)> {
+        let content = self.window_size();
+        frame_position(
+            cursor,
+            window,
+            (f64::from(content.width), f64::from(content.height)),
+        )
+    }
+
     pub fn handle_keyboard_key(&mut self, key: Key, pressed: bool) -> Option<KeyboardShortcut> {
         let first_press = if pressed {
             self.pressed_keys.insert(key)
@@ -105,8 +184,19 @@ impl SessionHandle {
             let _ = gui_input.state.set(field, InputValue::Digital(pressed));
         }
 
+        let action = shortcut_action_for_key(&self.settings_snapshot.shared, key);
+        if let Some(action) = action
+            && hold_shortcut_command(action, pressed).is_some()
+        {
+            // キーリピートによる再送は無視し、押下と解放の境界でのみ送る
+            if first_press || !pressed {
+                self.apply_hold_shortcut(action, pressed);
+            }
+            return None;
+        }
+
         if first_press {
-            return shortcut_action_for_key(&self.settings_snapshot.shared, key).map(|action| {
+            return action.map(|action| {
                 if matches!(action, ShortcutAction::ToggleFullscreen) {
                     KeyboardShortcut::ToggleFullscreen
                 } else {
@@ -118,12 +208,25 @@ impl SessionHandle {
     }
 
     pub fn clear_input(&mut self) {
-        self.pressed_keys.clear();
+        // フォーカス喪失などで解放イベントが届かない場合も、押しっぱなし系の操作を止める
+        for key in std::mem::take(&mut self.pressed_keys) {
+            if let Some(action) = shortcut_action_for_key(&self.settings_snapshot.shared, key) {
+                self.apply_hold_shortcut(action, false);
+            }
+        }
         if let Some(ref mut gui_input) = self.gui_input {
             gui_input.clear();
         }
     }
 
+    fn apply_hold_shortcut(&mut self, action: ShortcutAction, pressed: bool) {
+        if let Some(command) = hold_shortcut_command(action, pressed)
+            && let Err(e) = self.run_command(command)
+        {
+            log::warn!("hold shortcut {action:?} failed: {e}");
+        }
+    }
+
     pub fn rebuild_key_field_map(&mut self) {
         self.key_field_map.clear();
         let Some(factory) = self.active_factory() else {
@@ -316,6 +419,25 @@ mod tests {
         assert_eq!(key_map, HashMap::from([(Key::KeyA, 7)]));
     }
 
+    #[test]
+    fn frame_position_accounts_for_letterboxing() {
+        // 256x240 を 512x240 の窓に収めると左右に 128px ずつ余白ができる
+        let content = (256.0, 240.0);
+        let window = (512.0, 240.0);
+
+        assert_eq!(
+            frame_position((128.0, 0.0), window, content),
+            Some((0.0, 0.0))
+        );
+        assert_eq!(
+            frame_position((256.0, 120.0), window, content),
+            Some((0.5, 0.5))
+        );
+        assert_eq!(frame_position((100.0, 120.0), window, content), None);
+        assert_eq!(frame_position((384.0, 120.0), window, content), None);
+        assert_eq!(frame_position((0.0, 0.0), (0.0, 0.0), content), None);
+    }
+
     #[test]
     fn device_kind_delegates_to_profile_method() {
         let profile = MockSinglePort;
@@ -1,10 +1,16 @@
 use std::{path::Path, sync::Arc};
 
-use nerust_core_traits::factory::{
-    CoreFactory,
-    load::{MediaObject, ResolvedLoadRequest},
+use nerust_core_traits::{
+    RewindConfig,
+    cheat::Cheat,
+    debug::{BreakReason, DebugOp, DebugState, MemoryBlock, MemoryOp, VideoMemoryView},
+    factory::{
+        CoreFactory,
+        load::{MediaObject, ResolvedLoadRequest},
+    },
 };
-use nerust_emu_thread::ConsoleMetrics;
+use nerust_emu_thread::{ConsoleMetrics, OperationError};
+use nerust_gui_runtime::rom_patch::apply_patch_files;
 use nerust_input_traits::InputAssignments;
 use nerust_settings_core::factory::settings_view;
 
@@ -14,6 +20,7 @@ use crate::{
         SessionError, SessionHandle,
         commands::{SessionCommand, SessionCommandOutcome},
         persistence::PersistenceManager,
+        speed::SpeedControl,
         title::window_title,
     },
 };
@@ -127,6 +134,12 @@ impl SessionHandle {
         self.pressed_keys.clear();
         self.clear_input();
         self.rebuild_key_field_map();
+        if let Err(e) = self.apply_speed() {
+            log::warn!("set_speed failed: {e}");
+        }
+        if let Err(e) = self.apply_rewind() {
+            log::warn!("configure_rewind failed: {e}");
+        }
         Ok(plan)
     }
 
@@ -166,12 +179,21 @@ impl SessionHandle {
             };
             self.persistence.flush_mapper_save(core)?;
         }
-        self.emu_core
-            .as_mut()
-            .ok_or(SessionError::NoCore)?
-            .load(&media, Some(resolved.options))?;
+        // 差分パッチは読み込み時に一度だけ当て、作り直し時は当てた後のデータを使う
+        let media = if resolved.patch_paths.is_empty() {
+            media
+        } else {
+            let patched = apply_patch_files(&media.bytes, &resolved.patch_paths)?;
+            MediaObject::new(media.path, patched)
+        };
+        self.emu_core.as_mut().ok_or(SessionError::NoCore)?.load(
+            &media,
+            Some(resolved.options),
+            &resolved.bios_paths,
+        )?;
         self.loaded_media = Some(super::LoadedMedia {
             media: media.clone(),
+            bios_paths: resolved.bios_paths,
         });
 
         self.setup_persistence(media.path.as_deref(), true);
@@ -189,9 +211,59 @@ impl SessionHandle {
         }
         self.loaded_media = None;
         self.persistence.reset();
+        self.cheats.clear();
         Ok(())
     }
 
+    /// Cheats of the loaded content, in the order the core applies them.
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    /// Replaces the loaded content's cheats and saves them next to its state slots.
+    /// The previous list stays active when the core rejects a code.
+    pub fn set_cheats(&mut self, cheats: Vec<Cheat>) -> Result<(), SessionError> {
+        let core = self.emu_core.as_ref().ok_or(SessionError::NoCore)?;
+        match core.set_cheats(&cheats) {
+            Ok(applied) => self.cheats = applied,
+            Err(error) => {
+                if let Err(restore_error) = core.set_cheats(&self.cheats) {
+                    log::warn!("restoring previous cheats failed: {restore_error}");
+                }
+                return Err(error.into());
+            }
+        }
+        self.persistence.write_cheats(core, &self.cheats)?;
+        Ok(())
+    }
+
+    /// Sends one debugger request to the core and returns the CPU state afterwards.
+    pub fn debug_op(&self, op: DebugOp) -> Result<DebugState, SessionError> {
+        let core = self.emu_core.as_ref().ok_or(SessionError::NoCore)?;
+        Ok(core.debug_op(op)?)
+    }
+
+    /// Sets the scanline the core captures video memory at and returns the latest capture.
+    pub fn video_view(
+        &self,
+        capture_scanline: Option<u16>,
+        pattern_palette: u8,
+    ) -> Result<VideoMemoryView, SessionError> {
+        let core = self.emu_core.as_ref().ok_or(SessionError::NoCore)?;
+        Ok(core.video_view(capture_scanline, pattern_palette)?)
+    }
+
+    /// Reads or writes core memory for the memory viewer.
+    pub fn memory_op(&self, op: MemoryOp) -> Result<MemoryBlock, SessionError> {
+        let core = self.emu_core.as_ref().ok_or(SessionError::NoCore)?;
+        Ok(core.memory_op(op)?)
+    }
+
+    /// Returns why the core paused itself on a breakpoint or step since the last call.
+    pub fn take_debug_break(&self) -> Option<BreakReason> {
+        self.emu_core.as_ref()?.take_debug_break()
+    }
+
     pub fn flush_before_exit(&mut self) {
         if let Some(ref core) = self.emu_core
             && let Err(error) = self.persistence.flush_mapper_save(core)
@@ -234,6 +306,20 @@ impl SessionHandle {
             SessionCommand::DeleteSlot(id) => Ok(self.slot_op(|p, c| p.delete_slot(id, c))),
             SessionCommand::SelectNextSlot => Ok(self.cmd_adjacent_slot(true)),
             SessionCommand::SelectPreviousSlot => Ok(self.cmd_adjacent_slot(false)),
+            SessionCommand::SetRewinding(rewinding) => self.cmd_set_rewinding(rewinding),
+            SessionCommand::SetFastForward(held) => {
+                self.cmd_update_speed(|speed| speed.fast_forward_held = held)
+            }
+            SessionCommand::ToggleFastForward => self.cmd_update_speed(|speed| {
+                speed.fast_forward_toggled = !speed.fast_forward_toggled;
+            }),
+            SessionCommand::ToggleSlowMotion => {
+                self.cmd_update_speed(|speed| speed.slow_motion = !speed.slow_motion)
+            }
+            SessionCommand::EjectDisk => self.cmd_media_control(EmuCore::eject_disk),
+            SessionCommand::SwitchDiskSide => self.cmd_media_control(EmuCore::switch_disk_side),
+            SessionCommand::NextTrack => self.cmd_media_control(EmuCore::next_track),
+            SessionCommand::PreviousTrack => self.cmd_media_control(EmuCore::previous_track),
         }
     }
 
@@ -275,6 +361,71 @@ impl SessionHandle {
         })
     }
 
+    fn cmd_media_control(
+        &mut self,
+        operation: fn(&EmuCore) -> Result<(), OperationError>,
+    ) -> Result<SessionCommandOutcome, SessionError> {
+        if !self.loaded() {
+            return Ok(SessionCommandOutcome::default());
+        }
+        operation(self.core_mut()?)?;
+        Ok(SessionCommandOutcome {
+            executed: true,
+            needs_redraw: false,
+        })
+    }
+
+    fn cmd_update_speed(
+        &mut self,
+        update: impl FnOnce(&mut SpeedControl),
+    ) -> Result<SessionCommandOutcome, SessionError> {
+        let before = self.speed;
+        update(&mut self.speed);
+        if self.speed == before {
+            return Ok(SessionCommandOutcome::default());
+        }
+        self.apply_speed()?;
+        Ok(SessionCommandOutcome {
+            executed: true,
+            needs_redraw: false,
+        })
+    }
+
+    /// Sends the current speed to the core. Called again whenever the core is
+    /// rebuilt because a fresh emu thread starts at native speed.
+    pub(super) fn apply_speed(&self) -> Result<(), SessionError> {
+        if let Some(ref core) = self.emu_core {
+            core.set_speed(self.speed.emu_speed(&self.settings_snapshot.shared.speed))?;
+        }
+        Ok(())
+    }
+
+    /// Sends the rewind interval from the settings. A fresh emu thread starts with
+    /// the default interval, so this follows every core rebuild like `apply_speed`.
+    pub(super) fn apply_rewind(&self) -> Result<(), SessionError> {
+        if let Some(ref core) = self.emu_core {
+            core.configure_rewind(RewindConfig {
+                interval_frames: self.settings_snapshot.shared.rewind.interval_frames,
+                ..RewindConfig::default()
+            })?;
+        }
+        Ok(())
+    }
+
+    fn cmd_set_rewinding(
+        &mut self,
+        rewinding: bool,
+    ) -> Result<SessionCommandOutcome, SessionError> {
+        if !self.loaded() {
+            return Ok(SessionCommandOutcome::default());
+        }
+        self.core_mut()?.set_rewinding(rewinding)?;
+        Ok(SessionCommandOutcome {
+            executed: true,
+            needs_redraw: false,
+        })
+    }
+
     fn cmd_select_active_slot(&mut self, slot_id: u64) -> SessionCommandOutcome {
         self.persistence.select_active_slot(slot_id);
         SessionCommandOutcome {
@@ -371,7 +522,7 @@ impl SessionHandle {
         let rebuilt_core = rebuilt.emu_core;
 
         if let Some(loaded_media) = self.loaded_media.clone() {
-            rebuilt_core.load(&loaded_media.media, None)?;
+            rebuilt_core.load(&loaded_media.media, None, &loaded_media.bios_paths)?;
             if let Some(core_bytes) = exported_core_bytes.as_ref() {
                 rebuilt_core.load_state_raw(core_bytes.clone())?;
                 if !was_paused {
@@ -383,6 +534,8 @@ impl SessionHandle {
         self.emu_core = Some(rebuilt_core);
         self.gui_input = Some(rebuilt.gui_input);
         self.field_map = rebuilt.field_map;
+        self.apply_speed()?;
+        self.apply_rewind()?;
         if was_loaded {
             let rom_path = self
                 .loaded_media
@@ -426,6 +579,8 @@ impl SessionHandle {
     }
 
     fn setup_persistence(&mut self, rom_path: Option<&Path>, load_mapper_save: bool) -> bool {
+        // 新しく読み込んだコアはチートを持たない
+        self.cheats.clear();
         let sidecars = self
             .emu_core
             .as_ref()
@@ -441,6 +596,14 @@ impl SessionHandle {
             if load_mapper_save && let Err(e) = self.persistence.load_mapper_save_if_needed(core) {
                 log::warn!("load_mapper_save_if_needed failed: {e}");
             }
+            let stored = self.persistence.load_cheats(core);
+            self.cheats = match core.set_cheats(&stored) {
+                Ok(applied) => applied,
+                Err(error) => {
+                    log::warn!("applying stored cheats failed: {error}");
+                    core.set_cheats(&[]).unwrap_or_default()
+                }
+            };
         }
         true
     }
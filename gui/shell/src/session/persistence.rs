@@ -6,8 +6,11 @@ use std::{
 use std::io::Error as IoError;
 
 use crate::state::resolve_state_format;
-use nerust_core_traits::{identity::SystemIdentity, save_state::save_state_with_header};
+use nerust_core_traits::{
+    cheat::Cheat, identity::SystemIdentity, save_state::save_state_with_header,
+};
 use nerust_persistence::{
+    cheats::{load_cheats_for_identity, write_cheats_for_identity},
     error::PersistenceError,
     model::{LoadedStateSlot, StateSlotSummary},
     sidecar::{load_mapper_save, write_mapper_save, write_recovery_mapper_save},
@@ -44,6 +47,17 @@ pub trait SlotBackend: Send {
         slot_id: u64,
     ) -> Result<Option<LoadedStateSlot>, PersistenceError>;
     fn delete_slot(&self, dir: &Path, slot_id: u64) -> Result<(), PersistenceError>;
+    fn read_cheats(
+        &self,
+        dir: &Path,
+        identity: &SystemIdentity,
+    ) -> Result<Vec<Cheat>, PersistenceError>;
+    fn write_cheats(
+        &self,
+        dir: &Path,
+        identity: &SystemIdentity,
+        cheats: &[Cheat],
+    ) -> Result<(), PersistenceError>;
 }
 
 /// Platform abstraction for autosave I/O.
@@ -112,6 +126,21 @@ impl SlotBackend for FailingSlotBackend {
     fn delete_slot(&self, _dir: &Path, _slot_id: u64) -> Result<(), PersistenceError> {
         Err(PersistenceError::Io(IoError::other("simulated failure")))
     }
+    fn read_cheats(
+        &self,
+        _dir: &Path,
+        _identity: &SystemIdentity,
+    ) -> Result<Vec<Cheat>, PersistenceError> {
+        Err(PersistenceError::Io(IoError::other("simulated failure")))
+    }
+    fn write_cheats(
+        &self,
+        _dir: &Path,
+        _identity: &SystemIdentity,
+        _cheats: &[Cheat],
+    ) -> Result<(), PersistenceError> {
+        Err(PersistenceError::Io(IoError::other("simulated failure")))
+    }
 }
 impl AutoSaveBackend for FailingSlotBackend {
     fn write_autosave(
@@ -184,6 +213,21 @@ impl SlotBackend for FsSlotBackend {
     fn delete_slot(&self, dir: &Path, slot_id: u64) -> Result<(), PersistenceError> {
         delete_state_slot(&state_slot_path(dir, slot_id))
     }
+    fn read_cheats(
+        &self,
+        dir: &Path,
+        identity: &SystemIdentity,
+    ) -> Result<Vec<Cheat>, PersistenceError> {
+        load_cheats_for_identity(dir, identity)
+    }
+    fn write_cheats(
+        &self,
+        dir: &Path,
+        identity: &SystemIdentity,
+        cheats: &[Cheat],
+    ) -> Result<(), PersistenceError> {
+        write_cheats_for_identity(dir, identity, cheats)
+    }
 }
 impl AutoSaveBackend for FsSlotBackend {
     fn write_autosave(
@@ -466,6 +510,38 @@ impl PersistenceManager {
         }
     }
 
+    /// Reads the cheat list stored for the loaded content. Missing or unreadable
+    /// lists yield an empty list.
+    pub fn load_cheats(&self, emu: &impl CorePersistence) -> Vec<Cheat> {
+        let (Some(dir), Some(identity)) =
+            (self.states_dir.as_ref(), emu.canonical_media_identity())
+        else {
+            return Vec::new();
+        };
+        self.slot_backend
+            .read_cheats(dir, &identity)
+            .unwrap_or_else(|error| {
+                log::warn!("loading cheat list failed: {error}");
+                Vec::new()
+            })
+    }
+
+    pub fn write_cheats(
+        &self,
+        emu: &impl CorePersistence,
+        cheats: &[Cheat],
+    ) -> Result<(), PersistenceError> {
+        let Some(dir) = self.states_dir.as_ref() else {
+            log::warn!("write_cheats: no states_dir configured; cheat list not saved");
+            return Ok(());
+        };
+        let Some(identity) = emu.canonical_media_identity() else {
+            log::warn!("write_cheats: no persistence identity available; cheat list not saved");
+            return Ok(());
+        };
+        self.slot_backend.write_cheats(dir, &identity, cheats)
+    }
+
     pub fn configure(&mut self, states_dir: PathBuf, mapper_save_path: PathBuf) {
         self.states_dir = Some(states_dir);
         self.mapper_save_path = Some(mapper_save_path);
@@ -1,6 +1,9 @@
 use std::fs;
 
-use nerust_core_traits::factory::load::MediaObject;
+use nerust_core_traits::{
+    cheat::{Cheat, CheatOp},
+    factory::load::MediaObject,
+};
 use nerust_persistence::slots::autosave_state_slot_path;
 
 use crate::session::test_util::*;
@@ -41,6 +44,71 @@ fn rebuild_preserves_restored_runtime_state_without_reloading_mapper_save() {
     let _ = fs::remove_dir_all(temp_dir);
 }
 
+#[test]
+fn cheats_are_saved_per_rom_and_restored_on_reload() {
+    let temp_dir = unique_temp_dir("cheats");
+    let rom_path = temp_dir.join("test.nes");
+
+    let mut session = test_session();
+    let load = |session: &mut crate::session::SessionHandle| {
+        let factory = session.factory().expect("no active system");
+        let resolved = factory
+            .resolve_load_request(&test_view(session), factory.default_load_options())
+            .unwrap();
+        session
+            .load_resolved(
+                MediaObject::new(Some(rom_path.clone()), test_rom()),
+                resolved,
+            )
+            .unwrap();
+    };
+    load(&mut session);
+    assert!(session.cheats().is_empty());
+
+    let cheats = vec![Cheat::new("SXIOPO", "infinite lives")];
+    session.set_cheats(cheats.clone()).unwrap();
+    assert!(session.set_cheats(vec![Cheat::new("BAD", "")]).is_err());
+    assert_eq!(session.cheats(), cheats);
+
+    session.unload().unwrap();
+    assert!(session.cheats().is_empty());
+    load(&mut session);
+    assert_eq!(session.cheats(), cheats);
+    let _ = fs::remove_dir_all(temp_dir);
+}
+
+#[test]
+fn cheats_survive_controller_reassignment() {
+    let temp_dir = unique_temp_dir("cheats-reassign");
+    let rom_path = temp_dir.join("test.nes");
+
+    let mut session = test_session();
+    let factory = session.factory().expect("no active system");
+    let resolved = factory
+        .resolve_load_request(&test_view(&session), factory.default_load_options())
+        .unwrap();
+    session
+        .load_resolved(MediaObject::new(Some(rom_path), test_rom()), resolved)
+        .unwrap();
+    let cheats = vec![Cheat::new("0075:09", "lives")];
+    session.set_cheats(cheats.clone()).unwrap();
+
+    let assignments = session.current_assignments().clone();
+    session.reassign_controllers(&assignments).unwrap();
+
+    assert_eq!(session.cheats(), cheats);
+    // 一覧だけでなく、作り直したコアにもかかっている
+    let extra = Cheat::new("0076:01", "");
+    let applied = session
+        .emu_core
+        .as_ref()
+        .expect("core should be rebuilt")
+        .cheat_op(CheatOp::Add(extra.clone()))
+        .unwrap();
+    assert_eq!(applied, [cheats[0].clone(), extra]);
+    let _ = fs::remove_dir_all(temp_dir);
+}
+
 #[test]
 fn hidden_lifecycle_state_round_trips_without_visible_slot() {
     let temp_dir = unique_temp_dir("hidden-lifecycle-state");
@@ -0,0 +1,62 @@
+use nerust_core_traits::EmuSpeed;
+use nerust_gui_settings::shared::SpeedSettings;
+
+/// Fast-forward / slow-motion state driven by shortcuts.
+/// Fast-forward wins while both are active.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SpeedControl {
+    pub(crate) fast_forward_held: bool,
+    pub(crate) fast_forward_toggled: bool,
+    pub(crate) slow_motion: bool,
+}
+
+impl SpeedControl {
+    pub(crate) fn emu_speed(&self, settings: &SpeedSettings) -> EmuSpeed {
+        if self.fast_forward_held || self.fast_forward_toggled {
+            match settings.fast_forward_percent {
+                Some(percent) => percent_speed(percent),
+                None => EmuSpeed::Uncapped,
+            }
+        } else if self.slow_motion {
+            percent_speed(settings.slow_motion_percent)
+        } else {
+            EmuSpeed::NORMAL
+        }
+    }
+}
+
+fn percent_speed(percent: u16) -> EmuSpeed {
+    // 0% は停止と区別できないため 1% に丸める
+    EmuSpeed::Multiplier(f32::from(percent.max(1)) / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_core_traits::EmuSpeed;
+    use nerust_gui_settings::shared::SpeedSettings;
+
+    use super::SpeedControl;
+
+    #[test]
+    fn fast_forward_overrides_slow_motion() {
+        let settings = SpeedSettings {
+            fast_forward_percent: Some(300),
+            slow_motion_percent: 25,
+        };
+        let mut speed = SpeedControl::default();
+        assert_eq!(speed.emu_speed(&settings), EmuSpeed::NORMAL);
+
+        speed.slow_motion = true;
+        assert_eq!(speed.emu_speed(&settings), EmuSpeed::Multiplier(0.25));
+
+        speed.fast_forward_held = true;
+        assert_eq!(speed.emu_speed(&settings), EmuSpeed::Multiplier(3.0));
+
+        speed.fast_forward_held = false;
+        speed.fast_forward_toggled = true;
+        assert_eq!(
+            speed.emu_speed(&SpeedSettings::default()),
+            EmuSpeed::Uncapped
+        );
+    }
+}
@@ -1,6 +1,10 @@
-use std::sync::{Arc, atomic::Ordering::AcqRel};
+use std::{
+    path::PathBuf,
+    sync::{Arc, atomic::Ordering::AcqRel},
+};
 
 use nerust_core_traits::{
+    CoreError,
     audio::AudioBackend,
     factory::{
         CoreFactory, CoreParts, FactoryError,
@@ -120,6 +124,77 @@ fn shortcut_key_returns_shortcut_action_without_controller_event() {
     );
 }
 
+#[test]
+fn rewind_shortcut_is_handled_on_press_and_release() {
+    let mut session = test_session();
+    let resolved = session
+        .factory()
+        .unwrap()
+        .resolve_load_request(&test_view(&session), NoopSystemLoadOptions.into())
+        .unwrap();
+    session
+        .load_resolved(MediaObject::new(None, test_rom()), resolved)
+        .unwrap();
+
+    assert_eq!(
+        session.handle_keyboard_key(nerust_keyboard::Key::Backspace, true),
+        None
+    );
+    assert_eq!(
+        session.handle_keyboard_key(nerust_keyboard::Key::Backspace, false),
+        None
+    );
+    assert!(
+        session
+            .run_command(SessionCommand::SetRewinding(true))
+            .unwrap()
+            .executed
+    );
+    session.clear_input();
+}
+
+#[test]
+fn speed_commands_track_hold_and_toggle_state() {
+    let mut session = test_session();
+
+    assert_eq!(
+        session.handle_keyboard_key(nerust_keyboard::Key::Tab, true),
+        None
+    );
+    assert!(session.speed.fast_forward_held);
+    // 押しっぱなし中の再送は状態を変えない
+    assert!(
+        !session
+            .run_command(SessionCommand::SetFastForward(true))
+            .unwrap()
+            .executed
+    );
+    assert_eq!(
+        session.handle_keyboard_key(nerust_keyboard::Key::Tab, false),
+        None
+    );
+    assert!(!session.speed.fast_forward_held);
+
+    assert!(
+        session
+            .run_command(SessionCommand::ToggleSlowMotion)
+            .unwrap()
+            .executed
+    );
+    assert!(
+        session
+            .run_command(SessionCommand::ToggleFastForward)
+            .unwrap()
+            .executed
+    );
+    assert!(session.speed.slow_motion && session.speed.fast_forward_toggled);
+
+    session
+        .run_command(SessionCommand::ToggleFastForward)
+        .unwrap();
+    assert!(!session.speed.fast_forward_toggled);
+}
+
 #[test]
 fn system_load_options_flow_into_session_load() {
     let mut session = test_session();
@@ -180,6 +255,30 @@ fn session_commands_drive_pause_resume_toggle_and_reset() {
             .executed
     );
     assert!(session.run_command(SessionCommand::Reset).unwrap().executed);
+    assert!(
+        session
+            .run_command(SessionCommand::SwitchDiskSide)
+            .unwrap()
+            .executed
+    );
+    assert!(
+        session
+            .run_command(SessionCommand::EjectDisk)
+            .unwrap()
+            .executed
+    );
+    assert!(
+        session
+            .run_command(SessionCommand::NextTrack)
+            .unwrap()
+            .executed
+    );
+    assert!(
+        session
+            .run_command(SessionCommand::PreviousTrack)
+            .unwrap()
+            .executed
+    );
 }
 
 #[test]
@@ -229,6 +328,35 @@ fn session_rebuild_reuses_previously_resolved_load_request() {
     assert!(session.loaded());
 }
 
+#[test]
+fn load_resolved_patches_the_rom_before_the_core_sees_it() {
+    let temp_dir = unique_temp_dir("rom-patch");
+    let ips_path = temp_dir.join("test.ips");
+    let bad_path = temp_dir.join("test.ups");
+    // PRG の先頭 1 バイトを書き換える
+    std::fs::write(&ips_path, b"PATCH\x00\x00\x10\x00\x01\xEAEOF").unwrap();
+    std::fs::write(&bad_path, b"UPS1\x81\x81\x80\x00\0\0\0\0\0\0\0\0\0\0\0\0").unwrap();
+
+    let mut session = test_session();
+    let load = |session: &mut SessionHandle, patch_paths: Vec<PathBuf>| {
+        let factory = session.factory().expect("no active system");
+        let mut resolved = factory
+            .resolve_load_request(&test_view(session), factory.default_load_options())
+            .unwrap();
+        resolved.patch_paths = patch_paths;
+        session.load_resolved(MediaObject::new(None, test_rom()), resolved)
+    };
+
+    load(&mut session, vec![ips_path]).unwrap();
+    let loaded = session.loaded_media.as_ref().expect("media should load");
+    assert_eq!(loaded.media.bytes[0x10], 0xEA);
+    assert!(matches!(
+        load(&mut session, vec![bad_path]),
+        Err(SessionError::Core(CoreError::RomParse(_)))
+    ));
+    let _ = std::fs::remove_dir_all(temp_dir);
+}
+
 #[test]
 fn apply_settings_skips_rebuild_when_assignments_unchanged() {
     let mut session = test_session();
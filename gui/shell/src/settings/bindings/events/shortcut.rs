@@ -1,7 +1,6 @@
 use nerust_gui_settings::{input::ShortcutAction, shared::DesktopSharedSettings};
 use nerust_keyboard::Key;
 
-#[cfg(test)]
 use crate::session::commands::SessionCommand;
 
 #[cfg(test)]
@@ -34,16 +33,34 @@ fn shortcut_action_to_command(action: ShortcutAction) -> Option<SessionCommand>
         ShortcutAction::SelectNextSlot => SessionCommand::SelectNextSlot,
         ShortcutAction::SelectPreviousSlot => SessionCommand::SelectPreviousSlot,
         ShortcutAction::LoadActiveSlot => SessionCommand::LoadActiveSlot,
-        ShortcutAction::ToggleFullscreen => return None,
+        ShortcutAction::ToggleFastForward => SessionCommand::ToggleFastForward,
+        ShortcutAction::ToggleSlowMotion => SessionCommand::ToggleSlowMotion,
+        ShortcutAction::EjectDisk => SessionCommand::EjectDisk,
+        ShortcutAction::SwitchDiskSide => SessionCommand::SwitchDiskSide,
+        ShortcutAction::NextTrack => SessionCommand::NextTrack,
+        ShortcutAction::PreviousTrack => SessionCommand::PreviousTrack,
+        ShortcutAction::ToggleFullscreen | ShortcutAction::Rewind | ShortcutAction::FastForward => {
+            return None;
+        }
     })
 }
 
+/// Shortcuts that act while their key is held. The session sends the returned
+/// command on both press and release instead of reporting a `KeyboardShortcut`.
+pub fn hold_shortcut_command(action: ShortcutAction, pressed: bool) -> Option<SessionCommand> {
+    match action {
+        ShortcutAction::Rewind => Some(SessionCommand::SetRewinding(pressed)),
+        ShortcutAction::FastForward => Some(SessionCommand::SetFastForward(pressed)),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use nerust_gui_settings::input::ShortcutAction;
     use nerust_keyboard::Key;
 
-    use super::{shortcut_action_for_key, shortcut_command_for_key};
+    use super::{hold_shortcut_command, shortcut_action_for_key, shortcut_command_for_key};
     use crate::{
         session::commands::SessionCommand, settings::defaults::seed::default_shared_settings,
     };
@@ -68,4 +85,42 @@ mod tests {
         );
         assert_eq!(shortcut_command_for_key(&settings, Key::F11), None);
     }
+
+    #[test]
+    fn rewind_shortcut_is_held_not_triggered() {
+        let settings = default_shared_settings(&[]);
+
+        assert_eq!(
+            shortcut_action_for_key(&settings, Key::Backspace),
+            Some(ShortcutAction::Rewind)
+        );
+        assert_eq!(shortcut_command_for_key(&settings, Key::Backspace), None);
+        assert_eq!(
+            hold_shortcut_command(ShortcutAction::Rewind, true),
+            Some(SessionCommand::SetRewinding(true))
+        );
+        assert_eq!(
+            hold_shortcut_command(ShortcutAction::Rewind, false),
+            Some(SessionCommand::SetRewinding(false))
+        );
+        assert_eq!(hold_shortcut_command(ShortcutAction::Reset, true), None);
+    }
+
+    #[test]
+    fn fast_forward_shortcut_is_held_by_default() {
+        let settings = default_shared_settings(&[]);
+
+        assert_eq!(
+            shortcut_action_for_key(&settings, Key::Tab),
+            Some(ShortcutAction::FastForward)
+        );
+        assert_eq!(
+            hold_shortcut_command(ShortcutAction::FastForward, true),
+            Some(SessionCommand::SetFastForward(true))
+        );
+        assert_eq!(
+            hold_shortcut_command(ShortcutAction::ToggleSlowMotion, true),
+            None
+        );
+    }
 }
@@ -23,8 +23,16 @@ pub fn default_shared_settings(factories: &[Arc<dyn CoreFactory>]) -> DesktopSha
             && let Some(control_prefix) = sd.default_input_control_prefix()
         {
             let mut input = nerust_gui_settings::input::SystemInputSettings::default();
-            input.implicit_keyboard_profile_mut().bindings =
+            let bindings = &mut input.implicit_keyboard_profile_mut().bindings;
+            *bindings =
                 crate::keyboard_defaults::default_system_bindings(attachment, control_prefix);
+            for (layout, extra) in sd.default_input_extra_attachment_ids().iter().enumerate() {
+                bindings.extend(crate::keyboard_defaults::alternate_system_bindings(
+                    extra,
+                    control_prefix,
+                    layout,
+                ));
+            }
             let _ = input
                 .keyboard_profiles
                 .entry(IMPLICIT_PROFILE_ID.to_string())
@@ -66,6 +74,38 @@ fn seed_global_shortcuts(settings: &mut DesktopSharedSettings) {
             action: ShortcutAction::Reset,
             key: None,
         },
+        ShortcutBinding {
+            action: ShortcutAction::Rewind,
+            key: Some(Key::Backspace),
+        },
+        ShortcutBinding {
+            action: ShortcutAction::FastForward,
+            key: Some(Key::Tab),
+        },
+        ShortcutBinding {
+            action: ShortcutAction::ToggleFastForward,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::ToggleSlowMotion,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::EjectDisk,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::SwitchDiskSide,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::NextTrack,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::PreviousTrack,
+            key: None,
+        },
     ];
 }
 
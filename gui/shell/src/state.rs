@@ -37,10 +37,9 @@ pub fn generate_preview(emu: &EmuThread) -> Option<PreviewFrame> {
         return None;
     }
     let rgba = if let Some(palette) = guard.palette() {
-        let indices = guard.as_ref();
         let mut rgba = Vec::with_capacity(w * h * 4);
-        for &idx in indices.iter().take(w * h) {
-            let color = palette[idx as usize];
+        for entry in guard.pixels() {
+            let color = palette[usize::from(entry)];
             rgba.push((color >> 24) as u8);
             rgba.push((color >> 16) as u8);
             rgba.push((color >> 8) as u8);
@@ -1,5 +1,5 @@
 use nerust_core_traits::{
-    ConsoleCore, CoreCapabilities, CoreConfig, CoreError, identity::SystemIdentity,
+    ConsoleCore, CoreCapabilities, CoreConfig, CoreError, cheat::Cheat, identity::SystemIdentity,
 };
 use nerust_render_traits::{
     FrameBuffer, VideoRenderProfile, logical::LogicalSize, physical::PhysicalSize,
@@ -11,6 +11,7 @@ pub(crate) struct MockConsoleCore {
     loaded: bool,
     paused: bool,
     identity: Option<SystemIdentity>,
+    cheats: Vec<Cheat>,
 }
 
 impl MockConsoleCore {
@@ -19,6 +20,7 @@ impl MockConsoleCore {
             loaded: false,
             paused: true,
             identity: None,
+            cheats: Vec::new(),
         }
     }
 }
@@ -40,6 +42,7 @@ impl ConsoleCore for MockConsoleCore {
             Box::new(DummySystemId),
             rom.get(6..8).unwrap_or(&[0, 0]).to_vec(),
         ));
+        self.cheats.clear();
         Ok(())
     }
     fn unload(&mut self) {
@@ -61,6 +64,20 @@ impl ConsoleCore for MockConsoleCore {
     fn identity(&self) -> Result<SystemIdentity, CoreError> {
         self.identity.clone().ok_or(CoreError::NoRomLoaded)
     }
+    // "BAD" で始まるコードだけを不正として扱う
+    fn add_cheat(&mut self, cheat: Cheat) -> Result<(), CoreError> {
+        if cheat.code.starts_with("BAD") {
+            return Err(CoreError::Core("invalid cheat code".into()));
+        }
+        self.cheats.push(cheat);
+        Ok(())
+    }
+    fn clear_cheats(&mut self) {
+        self.cheats.clear();
+    }
+    fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
 }
 
 pub(crate) fn build_test_core_parts() -> nerust_core_traits::factory::CoreParts {
@@ -88,6 +105,6 @@ pub(crate) fn build_test_core_parts() -> nerust_core_traits::factory::CoreParts
         gui_input,
         field_map: std::collections::HashMap::new(),
         render_profile,
-        palette: Box::new([0u32; 256]),
+        palette: Box::new([0u32; 512]),
     }
 }
@@ -58,6 +58,8 @@ impl CoreFactory for MockFactory {
     ) -> Result<ResolvedLoadRequest, FactoryError> {
         Ok(ResolvedLoadRequest {
             options: NoopCoreOptions.into(),
+            bios_paths: Default::default(),
+            patch_paths: Vec::new(),
         })
     }
     fn default_load_options(&self) -> Box<dyn DynSystemLoadOptions> {
@@ -58,6 +58,38 @@ pub fn test_nes_defaults() -> DesktopSharedSettings {
             action: ShortcutAction::Reset,
             key: None,
         },
+        ShortcutBinding {
+            action: ShortcutAction::Rewind,
+            key: Some(Key::Backspace),
+        },
+        ShortcutBinding {
+            action: ShortcutAction::FastForward,
+            key: Some(Key::Tab),
+        },
+        ShortcutBinding {
+            action: ShortcutAction::ToggleFastForward,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::ToggleSlowMotion,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::EjectDisk,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::SwitchDiskSide,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::NextTrack,
+            key: None,
+        },
+        ShortcutBinding {
+            action: ShortcutAction::PreviousTrack,
+            key: None,
+        },
     ];
     settings
 }
@@ -0,0 +1,178 @@
+use nerust_core_traits::{cheat::Cheat, identity::SystemId};
+
+use super::{
+    EditorState,
+    dto::{CheatRowView, CheatsView},
+    editor::{SettingsEditor, ViewModelError},
+    property::ReadOnlyObservableProperty,
+    state::CheatDraft,
+};
+
+/// Cheat list of the running ROM. The frontend loads the session's cheats
+/// with [`load`](Self::load) and writes [`entries`](Self::entries) back on finish.
+#[derive(Clone)]
+pub struct CheatsViewModel {
+    editor: SettingsEditor,
+    pub view: ReadOnlyObservableProperty<CheatsView>,
+}
+
+impl CheatsViewModel {
+    pub fn new(editor: &SettingsEditor) -> Self {
+        let current = editor.current();
+        let initial = project_view(&current);
+        drop(current);
+        let view = editor
+            .projections()
+            .register("cheats", initial, project_view);
+        Self {
+            editor: editor.clone(),
+            view,
+        }
+    }
+
+    pub fn load(&self, system_id: &dyn SystemId, cheats: &[Cheat]) -> Result<(), ViewModelError> {
+        self.editor.transact(|state| {
+            if state.catalog.find_by_id(system_id).is_none() {
+                return Err(ViewModelError::UnknownSystem(system_id.to_string()));
+            }
+            state.cheats = Some(CheatDraft {
+                system_id: system_id.clone_box(),
+                entries: cheats.to_vec(),
+            });
+            Ok(())
+        })
+    }
+
+    pub fn add(&self, code: &str, description: &str) -> Result<(), ViewModelError> {
+        let code = code.trim();
+        self.editor.transact(|state| {
+            let factory = {
+                let draft = state.cheats.as_ref().ok_or(ViewModelError::NoCheatList)?;
+                state
+                    .catalog
+                    .find_by_id(draft.system_id.as_ref())
+                    .cloned()
+                    .ok_or_else(|| ViewModelError::UnknownSystem(draft.system_id.to_string()))?
+            };
+            factory
+                .validate_cheat_code(code)
+                .map_err(|_| ViewModelError::InvalidCheat(code.to_string()))?;
+            cheats_mut(state)?
+                .entries
+                .push(Cheat::new(code, description.trim()));
+            Ok(())
+        })
+    }
+
+    pub fn set_enabled(&self, index: usize, enabled: bool) -> Result<(), ViewModelError> {
+        self.editor.transact(|state| {
+            cheats_mut(state)?
+                .entries
+                .get_mut(index)
+                .ok_or(ViewModelError::UnknownCheat(index))?
+                .enabled = enabled;
+            Ok(())
+        })
+    }
+
+    pub fn remove(&self, index: usize) -> Result<(), ViewModelError> {
+        self.editor.transact(|state| {
+            let draft = cheats_mut(state)?;
+            if index >= draft.entries.len() {
+                return Err(ViewModelError::UnknownCheat(index));
+            }
+            draft.entries.remove(index);
+            Ok(())
+        })
+    }
+
+    /// The edited list, or `None` if no cheat list was loaded.
+    pub fn entries(&self) -> Option<Vec<Cheat>> {
+        self.editor
+            .current()
+            .cheats
+            .as_ref()
+            .map(|draft| draft.entries.clone())
+    }
+}
+
+fn cheats_mut(state: &mut EditorState) -> Result<&mut CheatDraft, ViewModelError> {
+    state.cheats.as_mut().ok_or(ViewModelError::NoCheatList)
+}
+
+fn project_view(state: &EditorState) -> CheatsView {
+    let Some(draft) = state.cheats.as_ref() else {
+        return CheatsView::default();
+    };
+    CheatsView {
+        available: true,
+        rows: draft
+            .entries
+            .iter()
+            .map(|cheat| CheatRowView {
+                code: cheat.code.clone(),
+                description: cheat.description.clone(),
+                enabled: cheat.enabled,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_core_traits::cheat::Cheat;
+
+    use crate::settings::{
+        ViewModelError,
+        test_support::{TestSystemId, test_vm},
+    };
+
+    #[test]
+    fn page_is_unavailable_until_loaded() {
+        let vm = test_vm();
+        assert!(!vm.cheats.view.get().available);
+        assert!(matches!(
+            vm.cheats.add("SXIOPO", ""),
+            Err(ViewModelError::NoCheatList)
+        ));
+        assert_eq!(vm.cheats.entries(), None);
+    }
+
+    #[test]
+    fn edits_update_projection_and_entries() {
+        let vm = test_vm();
+        vm.cheats
+            .load(&TestSystemId, &[Cheat::new("SXIOPO", "lives")])
+            .unwrap();
+        vm.cheats.add(" 0075:09 ", "level").unwrap();
+        vm.cheats.set_enabled(0, false).unwrap();
+
+        let view = vm.cheats.view.get();
+        assert!(view.available);
+        assert_eq!(view.rows.len(), 2);
+        assert!(!view.rows[0].enabled);
+        assert_eq!(view.rows[1].code, "0075:09");
+
+        vm.cheats.remove(0).unwrap();
+        assert_eq!(
+            vm.cheats.entries(),
+            Some(vec![Cheat::new("0075:09", "level")])
+        );
+    }
+
+    #[test]
+    fn invalid_code_and_index_are_rejected() {
+        let vm = test_vm();
+        vm.cheats.load(&TestSystemId, &[]).unwrap();
+        let rev_before = vm.revision.get();
+        assert!(matches!(
+            vm.cheats.add("BAD", ""),
+            Err(ViewModelError::InvalidCheat(_))
+        ));
+        assert!(matches!(
+            vm.cheats.remove(3),
+            Err(ViewModelError::UnknownCheat(3))
+        ));
+        assert_eq!(vm.revision.get(), rev_before);
+    }
+}
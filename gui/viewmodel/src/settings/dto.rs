@@ -1,4 +1,4 @@
-use std::fmt;
+use std::{fmt, path::PathBuf};
 
 use nerust_core_traits::{
     factory::descriptor::{SystemSettingsChoiceId, SystemSettingsFieldId},
@@ -45,6 +45,12 @@ pub struct GeneralView {
     pub storage_policy_choices: Vec<ChoiceView<StoragePolicy>>,
     pub storage_directory: String,
     pub show_storage_directory: bool,
+    pub rewind_interval_frames: u32,
+    pub rewind_interval_choices: Vec<ChoiceView<u32>>,
+    pub fast_forward_percent: Option<u16>,
+    pub fast_forward_choices: Vec<ChoiceView<Option<u16>>>,
+    pub slow_motion_percent: u16,
+    pub slow_motion_choices: Vec<ChoiceView<u16>>,
 }
 
 // ── Video ────────────────────────────────────────────────────────────
@@ -87,8 +93,19 @@ pub struct SystemTabView {
 pub struct SystemFieldView {
     pub id: SystemSettingsFieldId,
     pub label: String,
-    pub selected: SystemSettingsChoiceId,
-    pub choices: Vec<ChoiceView<SystemSettingsChoiceId>>,
+    pub kind: SystemFieldKindView,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SystemFieldKindView {
+    Choice {
+        selected: SystemSettingsChoiceId,
+        choices: Vec<ChoiceView<SystemSettingsChoiceId>>,
+    },
+    Path {
+        selected: Option<PathBuf>,
+        extensions: &'static [&'static str],
+    },
 }
 
 // ── Input tab ────────────────────────────────────────────────────────
@@ -136,6 +153,22 @@ pub struct InputConflictView {
     pub message: String,
 }
 
+// ── Cheats ───────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CheatsView {
+    /// `false` while no ROM is loaded; the page should be disabled.
+    pub available: bool,
+    pub rows: Vec<CheatRowView>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheatRowView {
+    pub code: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
 // ── Capture ──────────────────────────────────────────────────────────
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -40,6 +40,7 @@ impl SettingsEditor {
             cached_snapshot: Arc::new(snapshot.clone()),
             draft: Arc::new(snapshot),
             capture_target: None,
+            cheats: None,
             validation: ValidationState { issues: vec![] },
             revision: 0,
             catalog,
@@ -93,15 +94,20 @@ impl SettingsEditor {
         }
 
         // Capture pre-mutation state for no-op detection
-        let (prev_draft, prev_capture, prev_revision) = {
+        let (prev_draft, prev_capture, prev_cheats, prev_revision) = {
             let s = self.current.borrow();
-            (Arc::clone(&s.draft), s.capture_target.clone(), s.revision)
+            (
+                Arc::clone(&s.draft),
+                s.capture_target.clone(),
+                s.cheats.clone(),
+                s.revision,
+            )
         };
         let mut candidate = self.current.borrow().clone();
         let result = mutate(&mut candidate)?;
 
-        if candidate.capture_target != prev_capture {
-            // capture_target changed — cannot skip
+        if candidate.capture_target != prev_capture || candidate.cheats != prev_cheats {
+            // capture_target or cheats changed — cannot skip
         } else if Arc::ptr_eq(&candidate.draft, &prev_draft) {
             // draft not touched by mutate — true no-op
             return Ok(result);
@@ -1,4 +1,7 @@
-use nerust_gui_settings::{language::AppLanguage, shared::StoragePolicy};
+use nerust_gui_settings::{
+    language::AppLanguage,
+    shared::{RewindSettings, SpeedSettings, StoragePolicy},
+};
 use nerust_settings_core::i18n::{UiText, text as ui_text};
 use std::path::PathBuf;
 
@@ -49,6 +52,28 @@ impl GeneralSettingsViewModel {
             Ok(())
         })
     }
+
+    pub fn set_rewind_interval(&self, frames: u32) -> Result<(), ViewModelError> {
+        self.editor.transact(|state| {
+            state.draft_mut().shared.rewind.interval_frames = frames;
+            Ok(())
+        })
+    }
+
+    /// `None` runs fast-forward uncapped.
+    pub fn set_fast_forward(&self, percent: Option<u16>) -> Result<(), ViewModelError> {
+        self.editor.transact(|state| {
+            state.draft_mut().shared.speed.fast_forward_percent = percent;
+            Ok(())
+        })
+    }
+
+    pub fn set_slow_motion(&self, percent: u16) -> Result<(), ViewModelError> {
+        self.editor.transact(|state| {
+            state.draft_mut().shared.speed.slow_motion_percent = percent;
+            Ok(())
+        })
+    }
 }
 
 #[cfg(test)]
@@ -84,6 +109,31 @@ mod tests {
         assert_eq!(view.storage_directory, "/tmp/test");
     }
 
+    #[test]
+    fn set_rewind_interval_updates_projection() {
+        let vm = test_vm();
+        vm.general.set_rewind_interval(4).unwrap();
+        let view = vm.general.view.get();
+        assert_eq!(view.rewind_interval_frames, 4);
+        assert!(
+            view.rewind_interval_choices
+                .iter()
+                .any(|choice| choice.value == 4)
+        );
+    }
+
+    #[test]
+    fn speed_setters_update_projection() {
+        let vm = test_vm();
+        vm.general.set_fast_forward(Some(400)).unwrap();
+        vm.general.set_slow_motion(25).unwrap();
+        let view = vm.general.view.get();
+        assert_eq!(view.fast_forward_percent, Some(400));
+        assert_eq!(view.slow_motion_percent, 25);
+        assert_eq!(view.fast_forward_choices[0].value, None);
+        assert_eq!(view.fast_forward_choices[0].label, "Unlimited");
+    }
+
     #[test]
     fn language_choices_are_localized() {
         let vm = test_vm();
@@ -146,5 +196,32 @@ fn project_view(state: &super::EditorState) -> GeneralView {
             state.draft.shared.persistence.storage_policy,
             StoragePolicy::CustomDirectory
         ),
+        rewind_interval_frames: state.draft.shared.rewind.interval_frames,
+        rewind_interval_choices: RewindSettings::INTERVAL_CHOICES
+            .iter()
+            .map(|&frames| ChoiceView {
+                value: frames,
+                label: format!("{frames}"),
+            })
+            .collect(),
+        fast_forward_percent: state.draft.shared.speed.fast_forward_percent,
+        fast_forward_choices: SpeedSettings::FAST_FORWARD_CHOICES
+            .iter()
+            .map(|&percent| ChoiceView {
+                value: percent,
+                label: percent.map_or_else(
+                    || ui_text(lang, UiText::Unlimited).to_string(),
+                    |percent| format!("{percent}%"),
+                ),
+            })
+            .collect(),
+        slow_motion_percent: state.draft.shared.speed.slow_motion_percent,
+        slow_motion_choices: SpeedSettings::SLOW_MOTION_CHOICES
+            .iter()
+            .map(|&percent| ChoiceView {
+                value: percent,
+                label: format!("{percent}%"),
+            })
+            .collect(),
     }
 }
@@ -1,6 +1,7 @@
 mod audio;
 mod capture;
 pub(crate) mod catalog;
+mod cheats;
 pub mod dto;
 mod editor;
 mod general;
@@ -27,6 +28,7 @@ pub use validation::{ValidationIssue, ValidationScope, ValidationState};
 
 pub use audio::AudioSettingsViewModel;
 pub use capture::CaptureViewModel;
+pub use cheats::CheatsViewModel;
 pub use general::GeneralSettingsViewModel;
 pub use input::InputSettingsViewModel;
 pub use system::SystemSettingsViewModel;
@@ -10,6 +10,7 @@ use super::{
     ValidationState,
     audio::AudioSettingsViewModel,
     capture::CaptureViewModel,
+    cheats::CheatsViewModel,
     editor::{SettingsEditor, StoragePathValidator},
     general::GeneralSettingsViewModel,
     input::InputSettingsViewModel,
@@ -27,6 +28,7 @@ pub struct SettingsViewModel {
     pub video: VideoSettingsViewModel,
     pub audio: AudioSettingsViewModel,
     pub capture: CaptureViewModel,
+    pub cheats: CheatsViewModel,
     systems: Vec<SystemSettingsViewModel>,
     inputs: Vec<InputSettingsViewModel>,
 }
@@ -55,6 +57,7 @@ impl SettingsViewModel {
         let video = VideoSettingsViewModel::new(&editor);
         let audio = AudioSettingsViewModel::new(&editor);
         let capture = CaptureViewModel::new(&editor);
+        let cheats = CheatsViewModel::new(&editor);
         let systems: Vec<SystemSettingsViewModel> = factories
             .iter()
             .map(|f| SystemSettingsViewModel::new(&editor, f))
@@ -72,6 +75,7 @@ impl SettingsViewModel {
             video,
             audio,
             capture,
+            cheats,
             systems,
             inputs,
             editor,
@@ -6,7 +6,7 @@ use std::{
     sync::Arc,
 };
 
-use nerust_core_traits::identity::SystemId;
+use nerust_core_traits::{cheat::Cheat, identity::SystemId};
 use nerust_gui_settings::snapshot::SettingsSnapshot;
 use nerust_keyboard::Key;
 use nerust_settings_core::editor::CaptureTarget;
@@ -61,6 +61,19 @@ pub enum ViewModelError {
     InvalidSystemChoice,
     #[error("capture target is not available in the current topology")]
     InvalidCaptureTarget,
+    #[error("no cheat list is loaded")]
+    NoCheatList,
+    #[error("unknown cheat index: {0}")]
+    UnknownCheat(usize),
+    #[error("invalid cheat code: {0}")]
+    InvalidCheat(String),
+}
+
+/// Cheat list of the loaded ROM, edited alongside the settings draft.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CheatDraft {
+    pub(crate) system_id: Box<dyn SystemId>,
+    pub(crate) entries: Vec<Cheat>,
 }
 
 /// No-op validator for use in tests.
@@ -78,6 +91,8 @@ impl StoragePathValidator for NoopStoragePathValidator {
 pub struct EditorState {
     pub(crate) draft: Arc<SettingsSnapshot>,
     pub(crate) capture_target: Option<CaptureTarget>,
+    /// `None` until the frontend loads the cheats of the running ROM.
+    pub(crate) cheats: Option<CheatDraft>,
     pub validation: ValidationState,
     pub revision: u64,
     pub(crate) catalog: FactoryCatalog,
@@ -97,6 +112,7 @@ impl Clone for EditorState {
         Self {
             draft: Arc::clone(&self.draft),
             capture_target: self.capture_target.clone(),
+            cheats: self.cheats.clone(),
             validation: self.validation.clone(),
             revision: self.revision,
             catalog: self.catalog.clone(),
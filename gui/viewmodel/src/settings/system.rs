@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
 use nerust_core_traits::{
     factory::{
@@ -7,11 +7,13 @@ use nerust_core_traits::{
     },
     identity::SystemId,
 };
-use nerust_settings_core::factory::{apply_settings_choice, resolve_label, settings_view};
+use nerust_settings_core::factory::{
+    apply_settings_choice, apply_settings_path, resolve_label, settings_view,
+};
 
 use super::{
     EditorState,
-    dto::{ChoiceView, SystemFieldView, SystemTabView},
+    dto::{ChoiceView, SystemFieldKindView, SystemFieldView, SystemTabView},
     editor::{SettingsEditor, ViewModelError},
     property::ReadOnlyObservableProperty,
 };
@@ -73,6 +75,25 @@ impl SystemSettingsViewModel {
                 .map_err(|_| ViewModelError::InvalidSystemChoice)
         })
     }
+
+    pub fn set_path(
+        &self,
+        field: &SystemSettingsFieldId,
+        path: Option<&Path>,
+    ) -> Result<(), ViewModelError> {
+        let factory_id = self.factory_id.clone_box();
+        let field = field.clone();
+        let path = path.map(Path::to_path_buf);
+        self.editor.transact(move |state| {
+            let factory = state
+                .catalog
+                .find_by_id(factory_id.as_ref())
+                .cloned()
+                .ok_or(ViewModelError::UnknownSystem(factory_id.to_string()))?;
+            apply_settings_path(factory.as_ref(), state.draft_mut(), &field, path.as_deref())
+                .map_err(|_| ViewModelError::InvalidSystemChoice)
+        })
+    }
 }
 
 fn project_view(state: &EditorState, factory: &dyn CoreFactory) -> SystemTabView {
@@ -86,21 +107,30 @@ fn project_view(state: &EditorState, factory: &dyn CoreFactory) -> SystemTabView
         fields: model
             .fields
             .iter()
-            .map(|field| {
-                let SystemSettingsFieldKind::Choice { selected, options } = &field.kind;
-                let choices: Vec<ChoiceView<SystemSettingsChoiceId>> = options
-                    .iter()
-                    .map(|opt| ChoiceView {
-                        value: opt.id.clone(),
-                        label: resolve_label(opt.label_id, language, factory),
-                    })
-                    .collect();
-                SystemFieldView {
-                    id: field.id.clone(),
-                    label: resolve_label(field.label_id, language, factory),
-                    selected: selected.clone(),
-                    choices,
-                }
+            .map(|field| SystemFieldView {
+                id: field.id.clone(),
+                label: resolve_label(field.label_id, language, factory),
+                kind: match &field.kind {
+                    SystemSettingsFieldKind::Choice { selected, options } => {
+                        SystemFieldKindView::Choice {
+                            selected: selected.clone(),
+                            choices: options
+                                .iter()
+                                .map(|opt| ChoiceView {
+                                    value: opt.id.clone(),
+                                    label: resolve_label(opt.label_id, language, factory),
+                                })
+                                .collect(),
+                        }
+                    }
+                    SystemSettingsFieldKind::Path {
+                        selected,
+                        extensions,
+                    } => SystemFieldKindView::Path {
+                        selected: selected.clone(),
+                        extensions,
+                    },
+                },
             })
             .collect(),
     }
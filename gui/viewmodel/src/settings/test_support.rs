@@ -153,6 +153,12 @@ impl CoreFactory for TestCoreFactory {
     ) -> Box<dyn nerust_core_traits::factory::load::DynSystemLoadOptionsSchema> {
         unreachable!("not called in tests")
     }
+    fn validate_cheat_code(&self, code: &str) -> Result<(), FactoryError> {
+        if code.starts_with("BAD") {
+            return Err(FactoryError::InvalidCheat(code.to_string()));
+        }
+        Ok(())
+    }
 }
 
 /// Helper to create a SettingsViewModel with a test factory.
@@ -5,7 +5,9 @@ use nerust_core_traits::audio::AudioBackend;
 use nerust_input_traits::{Controller, ControllerHub, OpenBusReadResult, Port};
 use nerust_render_traits::FrameBuffer;
 
-use super::fft_test::CPU_CLOCK_HZ;
+use super::fft_test::{
+    CPU_CLOCK_HZ, FFT_SAMPLE_COUNT, dominant_frequency, dominant_frequency_tolerance,
+};
 use crate::{
     Core, cartridge_data_parts::CartridgeDataParts, cartridge_rom::CartridgeData,
     mirror::MirrorMode, rom_format::RomFormat,
@@ -32,7 +34,7 @@ fn null_fb() -> FrameBuffer {
         256,
         240,
         nerust_render_traits::PixelFormat::PaletteIndex {
-            palette: Box::new([0u32; 256]),
+            palette: Box::new([0u32; 512]),
         },
     );
     fb.resize(256, 240);
@@ -211,6 +213,11 @@ fn emit_write(program: &mut Vec<u8>, address: u16, value: u8) {
 }
 
 fn build_apu_rom(writes: &[(u16, u8)]) -> Vec<u8> {
+    build_rom(0, writes)
+}
+
+/// 32KiB PRG / 8KiB CHR の iNES イメージ。FME-7 でも電源投入時の配置で $8000-$FFFF に並ぶ。
+fn build_rom(mapper: u8, writes: &[(u16, u8)]) -> Vec<u8> {
     const PROGRAM_START: u16 = 0x8000;
 
     let mut program = Vec::new();
@@ -227,8 +234,14 @@ fn build_apu_rom(writes: &[(u16, u8)]) -> Vec<u8> {
     program.extend_from_slice(&[0x4C, loop_address as u8, (loop_address >> 8) as u8]);
 
     let mut rom = vec![
-        0x4E, 0x45, 0x53, 0x1A, 0x02, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-        0x00,
+        0x4E,
+        0x45,
+        0x53,
+        0x1A,
+        0x02,
+        0x01,
+        mapper << 4,
+        mapper & 0xF0,
     ];
     rom.resize(16 + 0x8000 + 0x2000, 0);
 
@@ -264,6 +277,15 @@ fn pulse_constant_volume_rom(length_index: u8, frame_counter_value: u8) -> Vec<u
     ])
 }
 
+/// Sunsoft 5B のレジスタ書き込み ($C000 で選択、$E000 に値)。
+fn sunsoft_5b_rom(registers: &[(u8, u8)]) -> Vec<u8> {
+    let writes = registers
+        .iter()
+        .flat_map(|&(register, value)| [(0xC000, register), (0xE000, value)])
+        .collect::<Vec<_>>();
+    build_rom(69, &writes)
+}
+
 fn cartridge_data_from_rom(rom: &[u8]) -> CartridgeData {
     const HEADER_LEN: usize = 16;
     const PRG_ROM_LEN: usize = 0x8000;
@@ -283,11 +305,12 @@ fn cartridge_data_from_rom(rom: &[u8]) -> CartridgeData {
         save_pram_length: 0,
         vram_length: 0,
         save_vram_length: 0,
-        mapper_type: 0,
+        mapper_type: u16::from((rom[6] >> 4) | (rom[7] & 0xF0)),
         mirror_mode: MirrorMode::Horizontal,
         has_battery: false,
         sub_mapper_type: 0,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("test cartridge data should be valid")
 }
@@ -316,6 +339,21 @@ fn run_rom_until_silence(
     decode_wav(&encode_wav(&mixer.samples, sample_rate))
 }
 
+fn run_rom_for_frames(rom: Vec<u8>, sample_rate: u32, frames: usize) -> AudioCapture {
+    let cartridge_data = cartridge_data_from_rom(&rom);
+    let mut core = Core::new(cartridge_data).expect("test ROM should load");
+    core.reset();
+
+    let mut screen = null_fb();
+    let mut controller = NullController;
+    let mut mixer = CapturingMixer::new(sample_rate);
+    for _ in 0..frames {
+        core.run_frame_inner(&mut screen, &mut controller, &mut mixer);
+    }
+
+    decode_wav(&encode_wav(&mixer.samples, sample_rate))
+}
+
 fn four_step_half_tick_cycles(index: usize) -> u64 {
     let sequence = (index / 2) as u64;
     match index % 2 {
@@ -508,3 +546,49 @@ fn rom_driven_frame_counter_modes_change_envelope_onset_and_length_duration() {
         "5-step mode should last about one half-frame longer than 4-step mode for this short length"
     );
 }
+
+#[test]
+fn rom_driven_sunsoft_5b_tone_matches_period_register() {
+    const TONE_PERIOD: u16 = 0x40;
+    let capture = run_rom_for_frames(
+        sunsoft_5b_rom(&[
+            (0x00, TONE_PERIOD as u8),
+            (0x01, 0x00),
+            (0x07, 0x3E),
+            (0x08, 0x0F),
+        ]),
+        TEST_SAMPLE_RATE,
+        45,
+    );
+    let samples = &capture.samples[capture.samples.len() - FFT_SAMPLE_COUNT..];
+    let frequency = dominant_frequency(samples, TEST_SAMPLE_RATE as f32);
+    let expected = CPU_CLOCK_HZ / (32.0 * f32::from(TONE_PERIOD));
+
+    assert!(
+        (frequency - expected).abs()
+            <= dominant_frequency_tolerance(TEST_SAMPLE_RATE as f32, FFT_SAMPLE_COUNT),
+        "5B tone should play at CPU/(32*period) = {expected:.1}Hz, got {frequency:.1}Hz"
+    );
+}
+
+#[test]
+fn rom_driven_sunsoft_5b_volume_follows_logarithmic_curve() {
+    // トーンを止めて固定レベルを出し、音量 15 と 11 (-12dB) を比べる
+    let level = |volume: u8| {
+        let capture = run_rom_for_frames(
+            sunsoft_5b_rom(&[(0x07, 0x3F), (0x08, volume)]),
+            TEST_SAMPLE_RATE,
+            3,
+        );
+        capture.segment_rms(0.02, 0.045)
+    };
+    let full = level(0x0F);
+    let quieter = level(0x0B);
+
+    assert!(full > SILENCE_THRESHOLD, "5B output should be audible");
+    assert!(
+        (quieter / full - 0.25).abs() <= 0.02,
+        "four volume steps should be about -12dB, got ratio {:.3}",
+        quieter / full
+    );
+}
@@ -1,5 +1,5 @@
 use super::timer::*;
-use crate::interrupt::*;
+use crate::{interrupt::*, status::console_type::ConsoleType};
 
 // NTSC
 // https://wiki.nesdev.com/w/index.php/APU_DMC
@@ -8,6 +8,19 @@ const DMC_TABLE: [u8; 16] = [
     214, 190, 170, 160, 143, 127, 113, 107, 95, 80, 71, 64, 53, 42, 36, 27,
 ];
 
+// PAL
+const DMC_TABLE_PAL: [u8; 16] = [
+    199, 177, 158, 149, 138, 118, 105, 99, 88, 74, 66, 59, 49, 39, 33, 25,
+];
+
+fn dmc_table(console_type: ConsoleType) -> &'static [u8; 16] {
+    if console_type.uses_pal_apu() {
+        &DMC_TABLE_PAL
+    } else {
+        &DMC_TABLE
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Copy, Clone)]
 #[expect(
     clippy::upper_case_acronyms,
@@ -50,20 +63,25 @@ impl DMC {
         }
     }
 
-    pub(crate) fn reset(&mut self) {
+    pub(crate) fn reset(&mut self, console_type: ConsoleType) {
         self.timer.reset();
-        let period = (u16::from(DMC_TABLE[0]) << 1) - 1;
+        let period = (u16::from(dmc_table(console_type)[0]) << 1) - 1;
         self.timer.set_period(period);
         self.timer.set_value(period);
         self.is_loop = false;
         self.irq = false;
     }
 
-    pub(crate) fn write_control(&mut self, value: u8, interrupt: &mut Interrupt) {
+    pub(crate) fn write_control(
+        &mut self,
+        value: u8,
+        console_type: ConsoleType,
+        interrupt: &mut Interrupt,
+    ) {
         self.irq = (value & 0x80) != 0;
         self.is_loop = (value & 0x40) != 0;
         self.timer
-            .set_period((u16::from(DMC_TABLE[usize::from(value & 0x0f)]) << 1) - 1);
+            .set_period((u16::from(dmc_table(console_type)[usize::from(value & 0x0f)]) << 1) - 1);
         if !self.irq {
             interrupt.clear_irq(IrqSource::DMC);
         }
@@ -204,9 +222,9 @@ mod tests {
             CPU_CLOCK_HZ, FFT_SAMPLE_COUNT, capture_samples, dominant_frequency,
             dominant_frequency_tolerance,
         },
-        DMC, DMC_TABLE,
+        DMC, DMC_TABLE, DMC_TABLE_PAL,
     };
-    use crate::interrupt::Interrupt;
+    use crate::{interrupt::Interrupt, status::console_type::ConsoleType};
 
     fn expected_single_sample_frequency(rate_index: usize) -> f32 {
         CPU_CLOCK_HZ / (16.0 * f32::from(DMC_TABLE[rate_index]))
@@ -219,8 +237,8 @@ mod tests {
     ) -> (DMC, Interrupt) {
         let mut interrupt = Interrupt::new();
         let mut dmc = DMC::new();
-        dmc.reset();
-        dmc.write_control(0x40 | rate_index, &mut interrupt);
+        dmc.reset(ConsoleType::Ntsc);
+        dmc.write_control(0x40 | rate_index, ConsoleType::Ntsc, &mut interrupt);
         dmc.write_value(initial_value);
         dmc.write_length(0);
         dmc.set_enabled(true, &mut interrupt);
@@ -337,4 +355,17 @@ mod tests {
 
         assert!(samples.iter().all(|sample| *sample == 126.0));
     }
+
+    #[test]
+    fn write_control_uses_region_specific_rate_table() {
+        let mut interrupt = Interrupt::new();
+        let mut ntsc = DMC::new();
+        let mut pal = DMC::new();
+
+        ntsc.write_control(0x0F, ConsoleType::Ntsc, &mut interrupt);
+        pal.write_control(0x0F, ConsoleType::Pal, &mut interrupt);
+
+        assert_eq!(ntsc.timer.period(), (u16::from(DMC_TABLE[15]) << 1) - 1);
+        assert_eq!(pal.timer.period(), (u16::from(DMC_TABLE_PAL[15]) << 1) - 1);
+    }
 }
@@ -1,4 +1,34 @@
-use crate::interrupt::{Interrupt, IrqSource};
+use crate::{
+    interrupt::{Interrupt, IrqSource},
+    status::console_type::ConsoleType,
+};
+
+/// フレームシーケンサの各ステップが発生する CPU サイクル。
+/// `step4` は 4-step モードの IRQ 開始、`step5` は 5-step モードの最終 half frame。
+struct FrameSequence {
+    step1: u16,
+    step2: u16,
+    step3: u16,
+    step4: u16,
+    step5: u16,
+}
+
+// https://wiki.nesdev.com/w/index.php/APU_Frame_Counter
+const NTSC_SEQUENCE: FrameSequence = FrameSequence {
+    step1: 7457,
+    step2: 14913,
+    step3: 22371,
+    step4: 29828,
+    step5: 37281,
+};
+
+const PAL_SEQUENCE: FrameSequence = FrameSequence {
+    step1: 8313,
+    step2: 16627,
+    step3: 24939,
+    step4: 33252,
+    step5: 41565,
+};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Eq, PartialEq)]
 pub(crate) enum FrameType {
@@ -16,10 +46,12 @@ pub(crate) struct FrameCounter {
     new_value: u8,
     clock_cycle: u64,
     cycle: u16,
+    #[serde(default)]
+    console_type: ConsoleType,
 }
 
 impl FrameCounter {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(console_type: ConsoleType) -> Self {
         Self {
             period: false,
             irq: true,
@@ -28,6 +60,19 @@ impl FrameCounter {
             new_value: 0,
             clock_cycle: 0,
             cycle: 0,
+            console_type,
+        }
+    }
+
+    pub(crate) fn console_type(&self) -> ConsoleType {
+        self.console_type
+    }
+
+    fn sequence(&self) -> &'static FrameSequence {
+        if self.console_type.uses_pal_apu() {
+            &PAL_SEQUENCE
+        } else {
+            &NTSC_SEQUENCE
         }
     }
 
@@ -56,38 +101,38 @@ impl FrameCounter {
         self.clock_cycle = self.clock_cycle.wrapping_add(1);
         self.cycle += 1;
 
-        // https://wiki.nesdev.com/w/index.php/APU_Frame_Counter
+        let sequence = self.sequence();
         let mut result = if self.period {
             // mode 1 -- 5step
             match self.cycle {
-                7457 | 22371 => FrameType::Quarter,
-                14913 | 37281 => FrameType::Half,
-                37282 => {
+                c if c == sequence.step1 || c == sequence.step3 => FrameType::Quarter,
+                c if c == sequence.step2 || c == sequence.step5 => FrameType::Half,
+                c if c == sequence.step5 + 1 => {
                     self.cycle = 0;
                     FrameType::None
                 }
-                0..=7456 | 7458..=14912 | 14914..=22370 | 22372..=37280 => FrameType::None,
+                c if c < sequence.step5 => FrameType::None,
                 _ => unreachable!(),
             }
         } else {
             // mode 0 -- 4step
             match self.cycle {
-                7457 | 22371 => FrameType::Quarter,
-                14913 => FrameType::Half,
-                29828 => {
+                c if c == sequence.step1 || c == sequence.step3 => FrameType::Quarter,
+                c if c == sequence.step2 => FrameType::Half,
+                c if c == sequence.step4 => {
                     self.fire_irq(interrupt);
                     FrameType::None
                 }
-                29829 => {
+                c if c == sequence.step4 + 1 => {
                     self.fire_irq(interrupt);
                     FrameType::Half
                 }
-                29830 => {
+                c if c == sequence.step4 + 2 => {
                     self.fire_irq(interrupt);
                     self.cycle = 0;
                     FrameType::None
                 }
-                0..=7456 | 7458..=14912 | 14914..=22370 | 22372..=29827 => FrameType::None,
+                c if c < sequence.step4 => FrameType::None,
                 _ => unreachable!(),
             }
         };
@@ -131,31 +176,25 @@ impl FrameCounter {
             return max_cycles + 1;
         }
 
+        let step4 = self.sequence().step4;
         match self.cycle {
-            0..=29827 => u64::from(29828 - self.cycle),
-            29828 | 29829 => 1,
+            c if c < step4 => u64::from(step4 - c),
+            c if c <= step4 + 1 => 1,
             _ => max_cycles + 1,
         }
     }
 
     pub(crate) fn cycles_until_next_frame_event(&self) -> u64 {
-        let mut cycles = if self.period {
-            match self.cycle {
-                0..=7456 => 7457 - self.cycle,
-                7457..=14912 => 14913 - self.cycle,
-                14913..=22370 => 22371 - self.cycle,
-                22371..=37280 => 37281 - self.cycle,
-                _ => 37282 - self.cycle,
-            }
-        } else {
-            match self.cycle {
-                0..=7456 => 7457 - self.cycle,
-                7457..=14912 => 14913 - self.cycle,
-                14913..=22370 => 22371 - self.cycle,
-                22371..=29827 => 29828 - self.cycle,
-                29828..=29829 => 1,
-                _ => 29830 - self.cycle,
-            }
+        let sequence = self.sequence();
+        let mut cycles = match self.cycle {
+            c if c < sequence.step1 => sequence.step1 - c,
+            c if c < sequence.step2 => sequence.step2 - c,
+            c if c < sequence.step3 => sequence.step3 - c,
+            c if self.period && c < sequence.step5 => sequence.step5 - c,
+            c if self.period => sequence.step5 + 1 - c,
+            c if c < sequence.step4 => sequence.step4 - c,
+            c if c <= sequence.step4 + 1 => 1,
+            c => sequence.step4 + 2 - c,
         };
 
         if self.write_counter > 0 {
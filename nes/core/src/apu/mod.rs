@@ -22,12 +22,12 @@ use crate::{
     Cpu,
     interrupt::{Interrupt, IrqSource},
     persistence_error::PersistenceError,
+    status::console_type::ConsoleType,
 };
 
 // // 240Hz フレームシーケンサ
 // const FRAME_COUNTER_RATE: f64 = 7457.3875;
 // const FRAME_COUNTER_RATE: f64 = 29829.55;
-const CLOCK_RATE: u64 = 1_789_773;
 const MIN_BULK_SAMPLE_INTERVAL: u64 = 32;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -62,9 +62,17 @@ fn make_tnd_table() -> Vec<f32> {
 }
 
 impl Core {
+    #[cfg(test)]
     pub(crate) fn new(
         // sample_rate: u32,
         interrupt: &mut Interrupt,
+    ) -> Self {
+        Self::new_with_console_type(ConsoleType::Ntsc, interrupt)
+    }
+
+    pub(crate) fn new_with_console_type(
+        console_type: ConsoleType,
+        interrupt: &mut Interrupt,
     ) -> Self {
         // let sample_reset_cycle = CLOCK_RATE * sample_rate as u64;
         // let filter_sample_rate = CLOCK_RATE as f64 / f64::from(sample_rate);
@@ -82,12 +90,21 @@ impl Core {
             noise: Noise::new(),
             dmc: DMC::new(),
             sample_accumulator: 0,
-            frame_counter: FrameCounter::new(),
+            frame_counter: FrameCounter::new(console_type),
         };
         result.initialize(interrupt);
         result
     }
 
+    pub(crate) fn console_type(&self) -> ConsoleType {
+        self.frame_counter.console_type()
+    }
+
+    #[inline]
+    fn clock_rate(&self) -> u64 {
+        self.console_type().cpu_clock_rate()
+    }
+
     pub(crate) fn validate_runtime_state(&self) -> Result<(), PersistenceError> {
         self.pulse1.validate_runtime_state()?;
         self.pulse2.validate_runtime_state()?;
@@ -103,8 +120,9 @@ impl Core {
         self.pulse1.reset();
         self.pulse2.reset();
         self.triangle.reset();
-        self.noise.reset();
-        self.dmc.reset();
+        let console_type = self.console_type();
+        self.noise.reset(console_type);
+        self.dmc.reset(console_type);
         self.sample_accumulator = 0;
         self.frame_counter.reset();
         self.initialize(interrupt);
@@ -142,7 +160,9 @@ impl Core {
             0x4007 => self.pulse2.write_timer_high(value),
             0x4008 => self.triangle.write_control(value),
             0x4009 => (),
-            0x4010 => self.dmc.write_control(value, interrupt),
+            0x4010 => self
+                .dmc
+                .write_control(value, self.console_type(), interrupt),
             0x4011 => self.dmc.write_value(value),
             0x4012 => self.dmc.write_address(value),
             0x4013 => self.dmc.write_length(value),
@@ -150,7 +170,7 @@ impl Core {
             0x400B => self.triangle.write_timer_high(value),
             0x400C => self.noise.write_control(value),
             0x400D => (),
-            0x400E => self.noise.write_period(value),
+            0x400E => self.noise.write_period(value, self.console_type()),
             0x400F => self.noise.write_length(value),
             0x4015 => self.write_control(value, interrupt),
             0x4017 => self.frame_counter.write_frame_counter(value, interrupt),
@@ -187,12 +207,13 @@ impl Core {
         expansion_audio_inverted: bool,
     ) {
         self.step_frame(cpu.interrupt_mut());
-        if self.sample_accumulator >= CLOCK_RATE {
-            self.sample_accumulator %= CLOCK_RATE;
+        let clock_rate = self.clock_rate();
+        if self.sample_accumulator >= clock_rate {
+            self.sample_accumulator %= clock_rate;
         }
-        self.sample_accumulator += u64::from(mixer_sample_rate).min(CLOCK_RATE);
-        if self.sample_accumulator >= CLOCK_RATE {
-            self.sample_accumulator -= CLOCK_RATE;
+        self.sample_accumulator += u64::from(mixer_sample_rate).min(clock_rate);
+        if self.sample_accumulator >= clock_rate {
+            self.sample_accumulator -= clock_rate;
             self.send_sample(mixer, expansion_audio_output, expansion_audio_inverted);
         }
     }
@@ -206,14 +227,15 @@ impl Core {
         expansion_audio_inverted: bool,
         cycles: u64,
     ) {
+        let clock_rate = self.clock_rate();
         for _ in 0..cycles {
             self.step_frame(cpu.interrupt_mut());
-            if self.sample_accumulator >= CLOCK_RATE {
-                self.sample_accumulator %= CLOCK_RATE;
+            if self.sample_accumulator >= clock_rate {
+                self.sample_accumulator %= clock_rate;
             }
-            self.sample_accumulator += u64::from(mixer_sample_rate).min(CLOCK_RATE);
-            if self.sample_accumulator >= CLOCK_RATE {
-                self.sample_accumulator -= CLOCK_RATE;
+            self.sample_accumulator += u64::from(mixer_sample_rate).min(clock_rate);
+            if self.sample_accumulator >= clock_rate {
+                self.sample_accumulator -= clock_rate;
                 self.send_sample(mixer, expansion_audio_output, expansion_audio_inverted);
             }
         }
@@ -260,21 +282,23 @@ impl Core {
         }
     }
 
-    pub(crate) fn should_step_many_exact(mixer_sample_rate: u32) -> bool {
+    pub(crate) fn should_step_many_exact(&self, mixer_sample_rate: u32) -> bool {
+        let clock_rate = self.clock_rate();
         u64::from(mixer_sample_rate)
-            .min(CLOCK_RATE)
+            .min(clock_rate)
             .saturating_mul(MIN_BULK_SAMPLE_INTERVAL)
-            > CLOCK_RATE
+            > clock_rate
     }
 
     pub(crate) fn cycles_until_next_sample(&self, mixer_sample_rate: u32) -> u64 {
-        let increment = u64::from(mixer_sample_rate).min(CLOCK_RATE);
+        let clock_rate = self.clock_rate();
+        let increment = u64::from(mixer_sample_rate).min(clock_rate);
         if increment == 0 {
             return u64::MAX;
         }
 
-        let accumulator = self.sample_accumulator % CLOCK_RATE;
-        (CLOCK_RATE - accumulator).div_ceil(increment).max(1)
+        let accumulator = self.sample_accumulator % clock_rate;
+        (clock_rate - accumulator).div_ceil(increment).max(1)
     }
 
     pub(crate) fn cycles_until_next_scheduler_event(
@@ -379,18 +403,19 @@ impl Core {
         expansion_audio_output: f32,
         expansion_audio_inverted: bool,
     ) {
-        let increment = u64::from(mixer_sample_rate).min(CLOCK_RATE);
+        let clock_rate = self.clock_rate();
+        let increment = u64::from(mixer_sample_rate).min(clock_rate);
         if increment == 0 {
             return;
         }
 
-        if self.sample_accumulator >= CLOCK_RATE {
-            self.sample_accumulator %= CLOCK_RATE;
+        if self.sample_accumulator >= clock_rate {
+            self.sample_accumulator %= clock_rate;
         }
         let total = self.sample_accumulator + cycles * increment;
-        debug_assert!(total < CLOCK_RATE * 2);
-        self.sample_accumulator = total % CLOCK_RATE;
-        if total >= CLOCK_RATE {
+        debug_assert!(total < clock_rate * 2);
+        self.sample_accumulator = total % clock_rate;
+        if total >= clock_rate {
             self.send_sample(mixer, expansion_audio_output, expansion_audio_inverted);
         }
     }
@@ -448,7 +473,11 @@ mod tests {
     use nerust_core_traits::audio::AudioBackend;
 
     use super::Core;
-    use crate::{cpu::Core as Cpu, interrupt::Interrupt};
+    use crate::{
+        cpu::Core as Cpu,
+        interrupt::{Interrupt, IrqSource},
+        status::console_type::ConsoleType,
+    };
 
     struct CapturingMixer {
         samples: Vec<f32>,
@@ -483,8 +512,12 @@ mod tests {
     }
 
     fn configured_test_apu() -> (Core, Interrupt) {
+        configured_test_apu_with_console_type(ConsoleType::Ntsc)
+    }
+
+    fn configured_test_apu_with_console_type(console_type: ConsoleType) -> (Core, Interrupt) {
         let mut interrupt = Interrupt::new();
-        let mut apu = Core::new(&mut interrupt);
+        let mut apu = Core::new_with_console_type(console_type, &mut interrupt);
         apu.write_register(0x4015, 0x0F, &mut interrupt);
         apu.write_register(0x4000, 0x3F, &mut interrupt);
         apu.write_register(0x4002, 0x08, &mut interrupt);
@@ -561,7 +594,7 @@ mod tests {
         let mut many_mixer = CapturingMixer::new(192_000);
         let sample_rate = exact_mixer.sample_rate();
 
-        assert!(Core::should_step_many_exact(sample_rate));
+        assert!(exact.should_step_many_exact(sample_rate));
         for _ in 0..40_000 {
             exact.step(&mut exact_cpu, &mut exact_mixer, sample_rate, 0.0, false);
         }
@@ -581,4 +614,72 @@ mod tests {
             format!("{:?}", exact_cpu.interrupt_ref())
         );
     }
+
+    #[test]
+    fn pal_step_many_matches_repeated_step_across_samples_and_frame_events() {
+        let (mut exact, interrupt) = configured_test_apu_with_console_type(ConsoleType::Pal);
+        let mut batched = exact.clone();
+        let mut exact_cpu = Cpu::new();
+        let mut batched_cpu = Cpu::new();
+        *exact_cpu.interrupt_mut() = interrupt;
+        *batched_cpu.interrupt_mut() = interrupt;
+        let mut exact_mixer = CapturingMixer::default();
+        let mut batched_mixer = CapturingMixer::default();
+        let sample_rate = exact_mixer.sample_rate();
+
+        for _ in 0..45_000 {
+            exact.step(&mut exact_cpu, &mut exact_mixer, sample_rate, 0.0, false);
+        }
+        batched.step_many_batched(
+            &mut batched_cpu,
+            &mut batched_mixer,
+            sample_rate,
+            0.0,
+            false,
+            45_000,
+        );
+
+        assert_eq!(batched_mixer.samples, exact_mixer.samples);
+        assert_eq!(format!("{:?}", batched), format!("{:?}", exact));
+        assert_eq!(
+            format!("{:?}", batched_cpu.interrupt_ref()),
+            format!("{:?}", exact_cpu.interrupt_ref())
+        );
+    }
+
+    #[test]
+    fn frame_irq_period_follows_console_type() {
+        for (console_type, expected) in [
+            (ConsoleType::Ntsc, 29_828),
+            (ConsoleType::Pal, 33_252),
+            (ConsoleType::Dendy, 29_828),
+        ] {
+            let mut cpu = Cpu::new();
+            let mut apu = Core::new_with_console_type(console_type, cpu.interrupt_mut());
+            let mut mixer = CapturingMixer::default();
+            let sample_rate = mixer.sample_rate();
+            apu.write_register(0x4017, 0x00, cpu.interrupt_mut());
+            cpu.interrupt_mut().clear_irq(IrqSource::FRAME_COUNTER);
+
+            let mut cycles = 0;
+            while !cpu
+                .interrupt_ref()
+                .irq_flag
+                .contains(IrqSource::FRAME_COUNTER)
+            {
+                apu.step(&mut cpu, &mut mixer, sample_rate, 0.0, false);
+                cycles += 1;
+                assert!(
+                    cycles < 40_000,
+                    "frame IRQ should fire for {console_type:?}"
+                );
+            }
+
+            // $4017 書き込みは 3-4 サイクル遅れて反映される
+            assert!(
+                (expected + 2..=expected + 4).contains(&cycles),
+                "{console_type:?}: {cycles}"
+            );
+        }
+    }
 }
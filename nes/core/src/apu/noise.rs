@@ -1,4 +1,5 @@
 use super::{envelope::*, length_counter::*, timer::*};
+use crate::status::console_type::ConsoleType;
 
 // NTSC
 // https://wiki.nesdev.com/w/index.php/APU_Noise
@@ -6,6 +7,19 @@ const NOISE_TABLE: [u16; 16] = [
     4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068,
 ];
 
+// PAL
+const NOISE_TABLE_PAL: [u16; 16] = [
+    4, 8, 14, 30, 60, 88, 118, 148, 188, 236, 354, 472, 708, 944, 1890, 3778,
+];
+
+fn noise_table(console_type: ConsoleType) -> &'static [u16; 16] {
+    if console_type.uses_pal_apu() {
+        &NOISE_TABLE_PAL
+    } else {
+        &NOISE_TABLE
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Copy, Clone)]
 pub(crate) struct Noise {
     mode: bool,
@@ -52,11 +66,11 @@ impl Noise {
         }
     }
 
-    pub(crate) fn reset(&mut self) {
+    pub(crate) fn reset(&mut self, console_type: ConsoleType) {
         self.length_counter.reset();
         self.envelope.reset();
         self.timer.reset();
-        self.timer.set_period(NOISE_TABLE[0] - 1);
+        self.timer.set_period(noise_table(console_type)[0] - 1);
         self.mode = false;
         self.shift_register = 1;
     }
@@ -67,10 +81,10 @@ impl Noise {
         self.envelope.set_period(value & 0x0F);
     }
 
-    pub(crate) fn write_period(&mut self, value: u8) {
+    pub(crate) fn write_period(&mut self, value: u8, console_type: ConsoleType) {
         self.mode = (value & 0x80) != 0;
         self.timer
-            .set_period(NOISE_TABLE[usize::from(value & 0x0F)] - 1);
+            .set_period(noise_table(console_type)[usize::from(value & 0x0F)] - 1);
     }
 
     pub(crate) fn write_length(&mut self, value: u8) {
@@ -113,8 +127,9 @@ mod tests {
             CPU_CLOCK_HZ, FFT_SAMPLE_COUNT, average_band_power, capture_samples,
             dominant_frequency, dominant_frequency_tolerance, power_spectrum, spectral_flatness,
         },
-        NOISE_TABLE, Noise,
+        NOISE_TABLE, NOISE_TABLE_PAL, Noise,
     };
+    use crate::status::console_type::ConsoleType;
 
     const SHORT_MODE_SEQUENCE_LENGTH: f32 = 93.0;
     const SHORT_MODE_DOMINANT_HARMONIC: f32 = 31.0;
@@ -129,7 +144,7 @@ mod tests {
         let mut noise = Noise::new();
         noise.write_control(0x3F);
         noise.length_counter.set_enabled(true);
-        noise.write_period(0x80 | period_index);
+        noise.write_period(0x80 | period_index, ConsoleType::Ntsc);
         noise.write_length(0xF8);
         noise.length_counter.step();
         noise
@@ -139,7 +154,7 @@ mod tests {
         let mut noise = Noise::new();
         noise.write_control(0x3F);
         noise.length_counter.set_enabled(true);
-        noise.write_period(period_index);
+        noise.write_period(period_index, ConsoleType::Ntsc);
         noise.write_length(0xF8);
         noise.length_counter.step();
         noise
@@ -183,7 +198,7 @@ mod tests {
     #[test]
     fn long_mode_shift_register_cycles_through_full_32767_state_sequence() {
         let mut noise = Noise::new();
-        noise.write_period(0x00);
+        noise.write_period(0x00, ConsoleType::Ntsc);
         let initial = noise.shift_register;
 
         for _ in 1..32_767 {
@@ -202,7 +217,7 @@ mod tests {
         let mut noise = Noise::new();
         noise.write_control(0x10 | 0x0F);
         noise.length_counter.set_enabled(true);
-        noise.write_period(0x09);
+        noise.write_period(0x09, ConsoleType::Ntsc);
         noise.write_length(0x00);
         noise.length_counter.step();
 
@@ -221,4 +236,19 @@ mod tests {
         });
         assert!(trailing.iter().all(|sample| *sample == 0.0));
     }
+
+    #[test]
+    fn write_period_uses_region_specific_table() {
+        let mut ntsc = Noise::new();
+        let mut pal = Noise::new();
+        let mut dendy = Noise::new();
+
+        ntsc.write_period(0x0F, ConsoleType::Ntsc);
+        pal.write_period(0x0F, ConsoleType::Pal);
+        dendy.write_period(0x0F, ConsoleType::Dendy);
+
+        assert_eq!(ntsc.timer.period(), NOISE_TABLE[15] - 1);
+        assert_eq!(pal.timer.period(), NOISE_TABLE_PAL[15] - 1);
+        assert_eq!(dendy.timer.period(), NOISE_TABLE[15] - 1);
+    }
 }
@@ -5,7 +5,7 @@ use nerust_input_traits::OpenBusReadResult;
 use crate::{
     cartridge_runtime_state::CartridgeRuntimeState, interrupt::Interrupt, mapper::Mapper,
     mapper_state::MappingMode, mirror::MirrorMode, persistence_error::PersistenceError,
-    ppu_memory_access::PpuReadAccess,
+    ppu_memory_access::PpuReadAccess, status::console_type::ConsoleType,
 };
 
 fn mirror_lut(mode: MirrorMode) -> [u8; 4] {
@@ -265,6 +265,28 @@ pub(crate) trait Cartridge: Mapper {
         Ok(())
     }
 
+    /// ディスクを取り出す。ディスクドライブを持たないカートリッジでは何もしない。
+    fn eject_disk(&mut self) {}
+
+    /// 次の面に入れ替える。ディスクドライブを持たないカートリッジでは何もしない。
+    fn switch_disk_side(&mut self) {}
+
+    /// 実行する地域のタイミングを通知する。
+    fn set_console_type(&mut self, _console_type: ConsoleType) {}
+
+    /// 次の曲を選ぶ。曲を切り替えた場合は `true` を返し、呼び出し側が本体をリセットする。
+    fn next_track(&mut self) -> bool {
+        false
+    }
+
+    /// 前の曲を選ぶ。曲を切り替えた場合は `true` を返す。
+    fn previous_track(&mut self) -> bool {
+        false
+    }
+
+    /// 本体のリセットボタンが押されたことを通知する。電源投入時は `initialize` だけが呼ばれる。
+    fn soft_reset(&mut self) {}
+
     fn notify_ppu_ctrl(&mut self, _value: u8) {}
 
     fn notify_ppu_mask(&mut self, _value: u8) {}
@@ -307,6 +329,54 @@ pub(crate) trait Cartridge: Mapper {
     fn peek_ppu_nametable(&self, address: usize, ciram: &[u8]) -> Option<u8> {
         Some(ciram[mirror_address(self.mirror_mode(), address) & 0x7FF])
     }
+
+    /// メモリビューアから $6000-$7FFF の RAM だけを書き換える。
+    /// レジスタや RAM のない番地、書き込み禁止で値が変わらなかった場合は false を返す。
+    fn poke_ram(&mut self, address: usize, value: u8) -> bool {
+        let index = address - 0x6000;
+        if self.register_addr(address) || self.ram_address(index).is_none() {
+            return false;
+        }
+        Mapper::write_ram(self, index, value);
+        Mapper::read_ram(self, index) == Some(value)
+    }
+
+    /// メモリビューアから PRG-ROM を書き換える。
+    /// PRG をフラッシュに写して動かす基板は、そちらにも書き込む。
+    fn poke_program_rom(&mut self, index: usize, value: u8) -> bool {
+        self.data_mut().write_prog_rom(index, value);
+        true
+    }
+
+    /// メモリビューアから PPU の $0000-$1FFF を書き換える。CHR-ROM なら false。
+    fn poke_character(&mut self, address: usize, value: u8) -> bool {
+        if self.mapper_state_ref().character_mapping_mode != MappingMode::Ram {
+            return false;
+        }
+        self.write_character(address, value);
+        true
+    }
+
+    /// CHR-ROM 全体、CHR-ROM がなければ CHR-RAM 全体。
+    fn character_memory(&self) -> &[u8] {
+        if self.data_ref().char_rom_len() > 0 {
+            self.data_ref().char_rom()
+        } else {
+            &self.mapper_state_ref().vram
+        }
+    }
+
+    fn poke_character_memory(&mut self, index: usize, value: u8) -> bool {
+        if index >= self.character_memory().len() {
+            return false;
+        }
+        if self.data_ref().char_rom_len() > 0 {
+            self.data_mut().write_char_rom(index, value);
+        } else {
+            self.mapper_state_mut().vram[index] = value;
+        }
+        true
+    }
 }
 
 // 本当はこうしたい
@@ -0,0 +1,424 @@
+use nerust_input_traits::OpenBusReadResult;
+
+use self::eeprom::{EepromKind, SerialEeprom};
+use super::Cartridge;
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_BANDAI_FCG},
+    interrupt::{Interrupt, IrqSource},
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+mod eeprom;
+
+/// $x00D。LZ93D50 の EEPROM 制御 (153 では bit5 が PRG-RAM の有効化)。
+const EEPROM_SCL: u8 = 0x20;
+const EEPROM_SDA: u8 = 0x40;
+/// 立っている間は CPU が SDA を開放し、EEPROM の出力を読める。
+const EEPROM_READ_ENABLE: u8 = 0x80;
+const PRG_RAM_ENABLE: u8 = 0x20;
+/// $6000-$7FFF の読み出しで EEPROM の SDA が見えるビット。
+const EEPROM_DATA_BIT: u8 = 0x10;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BandaiFcgRuntimeState {
+    chr_banks: [u8; 8],
+    prg_bank: u8,
+    irq_enabled: bool,
+    irq_counter: u16,
+    irq_latch: u16,
+    control: u8,
+    eeprom: Option<SerialEeprom>,
+}
+
+/// Bandai FCG-1/2, LZ93D50 (Mapper 16, 153, 159)。16KiB 単位の PRG 切り替えと 1KiB 単位の CHR 切り替え、
+/// CPU サイクルで減る 16bit の IRQ カウンタを持つ。
+/// FCG-1/2 はレジスタが $6000-$7FFF にあり IRQ カウンタへ直接書き、LZ93D50 は $8000-$FFFF にあってラッチを経由する。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct BandaiFcg {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    /// FCG-1/2 のレジスタ ($6000-$7FFF) を持つ。
+    fcg_registers: bool,
+    /// LZ93D50 のレジスタ ($8000-$FFFF) を持つ。
+    lz93d50_registers: bool,
+    /// Mapper 153。8KiB の PRG-RAM と、CHR レジスタ bit0 による 256KiB 単位の PRG 切り替えを持つ。
+    has_prg_ram: bool,
+    chr_banks: [u8; 8],
+    prg_bank: u8,
+    irq_enabled: bool,
+    irq_counter: u16,
+    irq_latch: u16,
+    /// $x00D。
+    control: u8,
+    eeprom: Option<SerialEeprom>,
+}
+
+#[typetag::serde]
+impl Cartridge for BandaiFcg {
+    fn read_ram(&self, address: usize) -> OpenBusReadResult {
+        if let Some(eeprom) = &self.eeprom {
+            let sda = eeprom.output() && self.cpu_sda();
+            return OpenBusReadResult::new(if sda { EEPROM_DATA_BIT } else { 0 }, EEPROM_DATA_BIT);
+        }
+        Mapper::read_ram(self, address).map_or_else(
+            || OpenBusReadResult::new(0, 0),
+            |x| OpenBusReadResult::new(x, 0xFF),
+        )
+    }
+
+    // EEPROM を持つ基板は PRG-RAM の代わりに EEPROM の内容を保存する
+    fn persistent_mapper_save_lengths(&self) -> (usize, usize) {
+        if let Some(eeprom) = &self.eeprom {
+            (eeprom.data().len(), 0)
+        } else if self.has_prg_ram && self.state.has_battery {
+            (self.state.sram.len(), 0)
+        } else {
+            (0, 0)
+        }
+    }
+
+    fn export_mapper_save_state(&self) -> Result<(Vec<u8>, Vec<u8>), PersistenceError> {
+        let saved = match &self.eeprom {
+            Some(eeprom) => eeprom.data().to_vec(),
+            None => {
+                let (save_len, _) = self.persistent_mapper_save_lengths();
+                self.state.sram[..save_len].to_vec()
+            }
+        };
+        Ok((saved, Vec::new()))
+    }
+
+    fn import_mapper_save_state(
+        &mut self,
+        prg_ram: &[u8],
+        chr_ram: &[u8],
+    ) -> Result<(), PersistenceError> {
+        let (save_len, _) = self.persistent_mapper_save_lengths();
+        if save_len == 0 {
+            return Err(PersistenceError::Validation(
+                "cartridge does not expose persistent mapper save memory".into(),
+            ));
+        }
+        if prg_ram.len() != save_len || !chr_ram.is_empty() {
+            return Err(PersistenceError::Validation(
+                "persistent mapper memory length mismatch".into(),
+            ));
+        }
+        match &mut self.eeprom {
+            Some(eeprom) => eeprom.data_mut().copy_from_slice(prg_ram),
+            None => self.state.sram[..save_len].copy_from_slice(prg_ram),
+        }
+        Ok(())
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_BANDAI_FCG.into(),
+            extra_body: encode_payload(&BandaiFcgRuntimeState {
+                chr_banks: self.chr_banks,
+                prg_bank: self.prg_bank,
+                irq_enabled: self.irq_enabled,
+                irq_counter: self.irq_counter,
+                irq_latch: self.irq_latch,
+                control: self.control,
+                eeprom: self.eeprom.clone(),
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_BANDAI_FCG {
+            return Err(PersistenceError::Validation(
+                "unexpected Bandai FCG runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: BandaiFcgRuntimeState = decode_payload(&state.extra_body)?;
+        let eeprom_matches = match (&self.eeprom, &runtime.eeprom) {
+            (None, None) => true,
+            (Some(current), Some(saved)) => {
+                current.kind() == saved.kind() && saved.data().len() == saved.kind().len()
+            }
+            _ => false,
+        };
+        if !eeprom_matches {
+            return Err(PersistenceError::Validation(
+                "Bandai FCG EEPROM mismatch".into(),
+            ));
+        }
+        self.state = state.mapper_state;
+        self.chr_banks = runtime.chr_banks;
+        self.prg_bank = runtime.prg_bank;
+        self.irq_enabled = runtime.irq_enabled;
+        self.irq_counter = runtime.irq_counter;
+        self.irq_latch = runtime.irq_latch;
+        self.control = runtime.control;
+        self.eeprom = runtime.eeprom;
+        Ok(())
+    }
+}
+
+impl BandaiFcg {
+    pub(crate) fn new_mapper16(data: CartridgeData) -> Self {
+        let (fcg_registers, lz93d50_registers, eeprom) = match data.sub_mapper_type() {
+            4 => (true, false, None),
+            // NES 2.0 では EEPROM の容量が PRG-NVRAM の大きさとして入る
+            5 => (
+                false,
+                true,
+                Some(if data.save_pram_length() == EepromKind::X24C01.len() {
+                    EepromKind::X24C01
+                } else {
+                    EepromKind::C24C02
+                }),
+            ),
+            // サブマッパーが無い場合は両方の窓でレジスタを受け付け、電池付きなら 24C02 を載せる
+            _ => (true, true, data.has_battery().then_some(EepromKind::C24C02)),
+        };
+        Self::new(data, fcg_registers, lz93d50_registers, false, eeprom)
+    }
+
+    pub(crate) fn new_mapper153(data: CartridgeData) -> Self {
+        Self::new(data, false, true, true, None)
+    }
+
+    pub(crate) fn new_mapper159(data: CartridgeData) -> Self {
+        Self::new(data, false, true, false, Some(EepromKind::X24C01))
+    }
+
+    fn new(
+        data: CartridgeData,
+        fcg_registers: bool,
+        lz93d50_registers: bool,
+        has_prg_ram: bool,
+        eeprom: Option<EepromKind>,
+    ) -> Self {
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            fcg_registers,
+            lz93d50_registers,
+            has_prg_ram,
+            chr_banks: [0, 1, 2, 3, 4, 5, 6, 7],
+            prg_bank: 0,
+            irq_enabled: false,
+            irq_counter: 0,
+            irq_latch: 0,
+            control: 0,
+            eeprom: eeprom.map(SerialEeprom::new),
+        }
+    }
+
+    /// CPU が SDA に出している値。読み出し有効の間は開放する。
+    fn cpu_sda(&self) -> bool {
+        self.control & (EEPROM_SDA | EEPROM_READ_ENABLE) != 0
+    }
+
+    fn update_prg_banks(&mut self) {
+        // 153 は CHR レジスタの bit0 のどれかが立っていれば後半 256KiB を使う
+        let outer = if self.has_prg_ram && self.chr_banks.iter().any(|&bank| bank & 0x01 != 0) {
+            0x10
+        } else {
+            0
+        };
+        self.change_program_page(0, outer | usize::from(self.prg_bank & 0x0F));
+        self.change_program_page(1, outer | 0x0F);
+    }
+
+    fn write_irq_register(
+        &mut self,
+        register: usize,
+        value: u8,
+        fcg: bool,
+        interrupt: &mut Interrupt,
+    ) {
+        match register {
+            0x0A => {
+                self.irq_enabled = value & 0x01 != 0;
+                if !fcg {
+                    self.irq_counter = self.irq_latch;
+                }
+                interrupt.clear_irq(IrqSource::EXTERNAL);
+            }
+            _ => {
+                let target = if fcg {
+                    &mut self.irq_counter
+                } else {
+                    &mut self.irq_latch
+                };
+                *target = if register == 0x0B {
+                    (*target & 0xFF00) | u16::from(value)
+                } else {
+                    (*target & 0x00FF) | (u16::from(value) << 8)
+                };
+            }
+        }
+    }
+}
+
+impl CartridgeDataDao for BandaiFcg {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for BandaiFcg {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for BandaiFcg {
+    fn program_page_len(&self) -> usize {
+        0x4000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0400
+    }
+
+    fn initialize(&mut self) {
+        self.update_prg_banks();
+        for (page, bank) in self.chr_banks.into_iter().enumerate() {
+            self.change_character_page(page, usize::from(bank));
+        }
+        if self.has_prg_ram {
+            self.change_ram_page(0, 0);
+        }
+    }
+
+    fn name(&self) -> &str {
+        match (
+            self.has_prg_ram,
+            self.eeprom.as_ref().map(SerialEeprom::kind),
+        ) {
+            (true, _) => "Bandai LZ93D50 (Mapper153)",
+            (false, Some(EepromKind::X24C01)) => "Bandai LZ93D50 (Mapper159)",
+            _ => "Bandai FCG (Mapper16)",
+        }
+    }
+
+    fn ram_len_default(&self) -> usize {
+        if self.has_prg_ram { 0x2000 } else { 0 }
+    }
+
+    fn register_addr(&self, address: usize) -> bool {
+        if address >= 0x8000 {
+            self.lz93d50_registers
+        } else {
+            self.fcg_registers
+        }
+    }
+
+    fn read_ram(&self, index: usize) -> Option<u8> {
+        if !self.has_prg_ram || self.control & PRG_RAM_ENABLE == 0 {
+            return None;
+        }
+        self.ram_address(index)
+            .map(|x| self.mapper_state_ref().sram[x])
+    }
+
+    fn write_ram(&mut self, index: usize, data: u8) {
+        if self.has_prg_ram
+            && self.control & PRG_RAM_ENABLE != 0
+            && let Some(address) = self.ram_address(index)
+        {
+            self.mapper_state_mut().sram[address] = data;
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        let register = address & 0x0F;
+        match register {
+            0x00..=0x07 => {
+                self.chr_banks[register] = value;
+                if self.has_prg_ram {
+                    // 153 の CHR は 8KiB の CHR-RAM で固定
+                    self.update_prg_banks();
+                } else {
+                    self.change_character_page(register, usize::from(value));
+                }
+            }
+            0x08 => {
+                self.prg_bank = value;
+                self.update_prg_banks();
+            }
+            0x09 => self.set_mirror_mode(match value & 0x03 {
+                0 => MirrorMode::Vertical,
+                1 => MirrorMode::Horizontal,
+                2 => MirrorMode::Single0,
+                _ => MirrorMode::Single1,
+            }),
+            0x0A..=0x0C => self.write_irq_register(register, value, address < 0x8000, interrupt),
+            0x0D => {
+                self.control = value;
+                let scl = value & EEPROM_SCL != 0;
+                let sda = self.cpu_sda();
+                if let Some(eeprom) = &mut self.eeprom {
+                    eeprom.write_lines(scl, sda);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        self.step_cpu_cycles(1, interrupt);
+    }
+
+    fn step_cpu_cycles(&mut self, cycles: u64, interrupt: &mut Interrupt) {
+        if !self.irq_enabled {
+            return;
+        }
+        // カウンタが 0 の状態で 1 サイクル進むと IRQ を出し、$FFFF へ回り込む
+        let mut remaining = cycles;
+        while remaining > u64::from(self.irq_counter) {
+            remaining -= u64::from(self.irq_counter) + 1;
+            self.irq_counter = u16::MAX;
+            interrupt.set_irq(IrqSource::EXTERNAL);
+        }
+        self.irq_counter -= u16::try_from(remaining).expect("remaining cycles fit in the counter");
+    }
+
+    fn cycles_until_next_cpu_event(&self) -> u64 {
+        if self.irq_enabled {
+            u64::from(self.irq_counter) + 1
+        } else {
+            u64::MAX
+        }
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,317 @@
+/// Bandai の基板に載るシリアル EEPROM の型。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EepromKind {
+    /// Xicor X24C01 (128 バイト)。デバイスアドレスを持たず、アドレスとデータを LSB から送る。
+    X24C01,
+    /// 24C02 (256 バイト)。通常の I2C と同じくデバイスアドレスから始め、MSB から送る。
+    C24C02,
+}
+
+impl EepromKind {
+    pub(crate) fn len(self) -> usize {
+        match self {
+            Self::X24C01 => 0x80,
+            Self::C24C02 => 0x100,
+        }
+    }
+
+    /// ページ書き込みでアドレスが折り返す単位。
+    fn page_len(self) -> u8 {
+        match self {
+            Self::X24C01 => 4,
+            Self::C24C02 => 8,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Idle,
+    ChipAddress,
+    Address,
+    Read,
+    Write,
+    /// EEPROM が ACK を返す 9 ビット目。
+    SendAck,
+    /// ホストの ACK を待つ 9 ビット目。ACK があれば続けて読み出す。
+    WaitAck,
+}
+
+/// SCL / SDA の変化をビット単位で追う I2C EEPROM。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(crate) struct SerialEeprom {
+    kind: EepromKind,
+    data: Vec<u8>,
+    mode: Mode,
+    next_mode: Mode,
+    address: u8,
+    /// 送受信中のバイト。
+    shift: u8,
+    bit_count: u8,
+    /// EEPROM が SDA に出している値 (オープンドレインなので true で開放)。
+    output: bool,
+    scl: bool,
+    sda: bool,
+}
+
+impl SerialEeprom {
+    pub(crate) fn new(kind: EepromKind) -> Self {
+        Self {
+            kind,
+            data: vec![0xFF; kind.len()],
+            mode: Mode::Idle,
+            next_mode: Mode::Idle,
+            address: 0,
+            shift: 0,
+            bit_count: 0,
+            output: true,
+            scl: false,
+            sda: false,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> EepromKind {
+        self.kind
+    }
+
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub(crate) fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+
+    pub(crate) fn output(&self) -> bool {
+        self.output
+    }
+
+    pub(crate) fn write_lines(&mut self, scl: bool, sda: bool) {
+        let (prev_scl, prev_sda) = (self.scl, self.sda);
+        self.scl = scl;
+        self.sda = sda;
+        if prev_scl && scl && prev_sda != sda {
+            // SCL が High の間の SDA の立ち下がりが開始、立ち上がりが停止
+            if sda {
+                self.mode = Mode::Idle;
+                self.output = true;
+            } else {
+                self.begin(match self.kind {
+                    EepromKind::X24C01 => Mode::Address,
+                    EepromKind::C24C02 => Mode::ChipAddress,
+                });
+            }
+        } else if !prev_scl && scl {
+            self.clock_rising(sda);
+        } else if prev_scl && !scl {
+            self.clock_falling();
+        }
+    }
+
+    fn begin(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.bit_count = 0;
+        self.shift = if mode == Mode::Read {
+            self.data[usize::from(self.address)]
+        } else {
+            0
+        };
+        self.output = true;
+    }
+
+    fn bit_position(&self) -> u8 {
+        match self.kind {
+            EepromKind::X24C01 => self.bit_count,
+            EepromKind::C24C02 => 7 - self.bit_count,
+        }
+    }
+
+    fn clock_rising(&mut self, sda: bool) {
+        match self.mode {
+            Mode::ChipAddress | Mode::Address | Mode::Write if self.bit_count < 8 => {
+                self.shift |= u8::from(sda) << self.bit_position();
+                self.bit_count += 1;
+            }
+            Mode::Read if self.bit_count < 8 => {
+                self.output = self.shift & (1 << self.bit_position()) != 0;
+                self.bit_count += 1;
+            }
+            Mode::SendAck => self.output = false,
+            Mode::WaitAck => {
+                self.next_mode = if sda { Mode::Idle } else { Mode::Read };
+            }
+            _ => {}
+        }
+    }
+
+    fn clock_falling(&mut self) {
+        match self.mode {
+            Mode::SendAck | Mode::WaitAck => self.begin(self.next_mode),
+            _ if self.bit_count < 8 => {}
+            Mode::ChipAddress => {
+                // デバイス種別 1010 以外は応答しない
+                if self.shift & 0xF0 == 0xA0 {
+                    self.next_mode = if self.shift & 0x01 != 0 {
+                        Mode::Read
+                    } else {
+                        Mode::Address
+                    };
+                    self.mode = Mode::SendAck;
+                } else {
+                    self.mode = Mode::Idle;
+                }
+            }
+            Mode::Address => {
+                self.next_mode = match self.kind {
+                    EepromKind::X24C01 if self.shift & 0x80 != 0 => Mode::Read,
+                    _ => Mode::Write,
+                };
+                self.address = self.shift & (self.kind.len() - 1) as u8;
+                self.mode = Mode::SendAck;
+            }
+            Mode::Write => {
+                self.data[usize::from(self.address)] = self.shift;
+                let page_mask = self.kind.page_len() - 1;
+                self.address =
+                    (self.address & !page_mask) | (self.address.wrapping_add(1) & page_mask);
+                self.next_mode = Mode::Write;
+                self.mode = Mode::SendAck;
+            }
+            Mode::Read => {
+                self.address = self.address.wrapping_add(1) & (self.kind.len() - 1) as u8;
+                self.output = true;
+                self.mode = Mode::WaitAck;
+            }
+            Mode::Idle => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EepromKind, SerialEeprom};
+
+    struct Host<'a> {
+        eeprom: &'a mut SerialEeprom,
+        lsb_first: bool,
+    }
+
+    impl Host<'_> {
+        fn start(&mut self) {
+            self.eeprom.write_lines(false, true);
+            self.eeprom.write_lines(true, true);
+            self.eeprom.write_lines(true, false);
+            self.eeprom.write_lines(false, false);
+        }
+
+        fn stop(&mut self) {
+            self.eeprom.write_lines(false, false);
+            self.eeprom.write_lines(true, false);
+            self.eeprom.write_lines(true, true);
+        }
+
+        fn clock_bit(&mut self, sda: bool) -> bool {
+            self.eeprom.write_lines(false, sda);
+            self.eeprom.write_lines(true, sda);
+            let bit = self.eeprom.output();
+            self.eeprom.write_lines(false, sda);
+            bit
+        }
+
+        /// 1 バイト送り、EEPROM の ACK を返す。
+        fn send(&mut self, value: u8) -> bool {
+            for i in 0..8 {
+                let shift = if self.lsb_first { i } else { 7 - i };
+                self.clock_bit(value & (1 << shift) != 0);
+            }
+            !self.clock_bit(true)
+        }
+
+        fn receive(&mut self, ack: bool) -> u8 {
+            let mut value = 0;
+            for i in 0..8 {
+                let shift = if self.lsb_first { i } else { 7 - i };
+                value |= u8::from(self.clock_bit(true)) << shift;
+            }
+            self.clock_bit(!ack);
+            value
+        }
+    }
+
+    #[test]
+    fn c24c02_writes_and_reads_back_sequentially() {
+        let mut eeprom = SerialEeprom::new(EepromKind::C24C02);
+        let mut host = Host {
+            eeprom: &mut eeprom,
+            lsb_first: false,
+        };
+        host.start();
+        assert!(host.send(0xA0));
+        assert!(host.send(0x10));
+        assert!(host.send(0x12));
+        assert!(host.send(0x34));
+        host.stop();
+
+        // 書き込みでアドレスだけ送ってから読み出しで再開始する
+        host.start();
+        assert!(host.send(0xA0));
+        assert!(host.send(0x10));
+        host.start();
+        assert!(host.send(0xA1));
+        assert_eq!(host.receive(true), 0x12);
+        assert_eq!(host.receive(false), 0x34);
+        host.stop();
+
+        assert_eq!(&eeprom.data()[0x10..0x12], &[0x12, 0x34]);
+    }
+
+    #[test]
+    fn c24c02_ignores_other_device_addresses() {
+        let mut eeprom = SerialEeprom::new(EepromKind::C24C02);
+        let mut host = Host {
+            eeprom: &mut eeprom,
+            lsb_first: false,
+        };
+        host.start();
+        assert!(!host.send(0x50));
+        assert!(!host.send(0x00));
+        host.stop();
+    }
+
+    #[test]
+    fn x24c01_uses_lsb_first_address_with_read_bit() {
+        let mut eeprom = SerialEeprom::new(EepromKind::X24C01);
+        let mut host = Host {
+            eeprom: &mut eeprom,
+            lsb_first: true,
+        };
+        host.start();
+        assert!(host.send(0x05));
+        assert!(host.send(0xC3));
+        host.stop();
+
+        host.start();
+        assert!(host.send(0x80 | 0x05));
+        assert_eq!(host.receive(false), 0xC3);
+        host.stop();
+
+        assert_eq!(eeprom.data()[0x05], 0xC3);
+    }
+
+    #[test]
+    fn page_writes_wrap_within_the_page() {
+        let mut eeprom = SerialEeprom::new(EepromKind::X24C01);
+        let mut host = Host {
+            eeprom: &mut eeprom,
+            lsb_first: true,
+        };
+        host.start();
+        host.send(0x06);
+        for value in 1..=3 {
+            host.send(value);
+        }
+        host.stop();
+
+        assert_eq!(&eeprom.data()[0x04..0x08], &[0x03, 0xFF, 0x01, 0x02]);
+    }
+}
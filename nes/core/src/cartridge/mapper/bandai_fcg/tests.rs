@@ -0,0 +1,238 @@
+use super::{BandaiFcg, Cartridge};
+use crate::{
+    cartridge_data_parts::CartridgeDataParts,
+    cartridge_rom::CartridgeData,
+    interrupt::{Interrupt, IrqSource},
+    mapper::Mapper,
+    mirror::MirrorMode,
+    rom_format::RomFormat,
+};
+
+fn test_data(
+    mapper_type: u16,
+    sub_mapper_type: u8,
+    prog_rom_len: usize,
+    has_chr_rom: bool,
+    has_battery: bool,
+) -> CartridgeData {
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::INes,
+        prog_rom: (0..prog_rom_len).map(|i| (i / 0x4000) as u8).collect(),
+        char_rom: if has_chr_rom {
+            (0..0x40000).map(|i| (i / 0x400) as u8).collect()
+        } else {
+            Vec::new()
+        },
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0,
+        save_vram_length: 0,
+        mapper_type,
+        mirror_mode: MirrorMode::Vertical,
+        has_battery,
+        sub_mapper_type,
+        trainer: Vec::new(),
+        console_type: None,
+    })
+    .expect("test cartridge data should be valid")
+}
+
+fn initialized(mut mapper: BandaiFcg) -> BandaiFcg {
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+/// $800D を通して 24C01 へ 1 バイト書き込む。
+fn write_x24c01(mapper: &mut BandaiFcg, address: u8, value: u8) {
+    let mut interrupt = Interrupt::new();
+    let mut lines = |scl: bool, sda: bool| {
+        let value = (u8::from(scl) << 5) | (u8::from(sda) << 6);
+        Cartridge::write(mapper, 0x800D, value, &mut interrupt);
+    };
+    lines(false, true);
+    lines(true, true);
+    lines(true, false);
+    lines(false, false);
+    for byte in [address, value] {
+        // LSB から送り、9 ビット目は ACK のために開放する
+        for bit in (0..8).map(|i| byte & (1 << i) != 0).chain([true]) {
+            lines(false, bit);
+            lines(true, bit);
+            lines(false, bit);
+        }
+    }
+    lines(false, false);
+    lines(true, false);
+    lines(true, true);
+}
+
+#[test]
+fn prg_and_chr_banks_switch_with_fixed_last_bank() {
+    let mut mapper = initialized(BandaiFcg::new_mapper16(test_data(
+        16, 5, 0x40000, true, false,
+    )));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x8008, 0x03, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x8005, 0x21, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x8009, 0x01, &mut interrupt);
+
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x03);
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x0F);
+    assert_eq!(Cartridge::read(&mapper, 0x1400).data, 0x21);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+
+    // LZ93D50 は $6000 のレジスタを持たない
+    Cartridge::write(&mut mapper, 0x6008, 0x05, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x03);
+}
+
+#[test]
+fn lz93d50_reloads_counter_from_latch_when_enabled() {
+    let mut mapper = initialized(BandaiFcg::new_mapper16(test_data(
+        16, 5, 0x40000, true, false,
+    )));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x800B, 0x02, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x800C, 0x00, &mut interrupt);
+    assert_eq!(mapper.cycles_until_next_cpu_event(), u64::MAX);
+    Cartridge::write(&mut mapper, 0x800A, 0x01, &mut interrupt);
+    assert_eq!(mapper.cycles_until_next_cpu_event(), 3);
+
+    mapper.step_cpu_cycles(2, &mut interrupt);
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    mapper.step_cpu_cycles(1, &mut interrupt);
+    assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+    assert_eq!(mapper.cycles_until_next_cpu_event(), 0x10000);
+
+    Cartridge::write(&mut mapper, 0x800A, 0x00, &mut interrupt);
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+}
+
+#[test]
+fn steps_longer_than_the_counter_wrap_it_and_raise_irq() {
+    let mut mapper = initialized(BandaiFcg::new_mapper16(test_data(
+        16, 5, 0x40000, true, false,
+    )));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x800B, 0x02, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x800C, 0x00, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x800A, 0x01, &mut interrupt);
+
+    // ちょうど 1 周 (0x10000 サイクル) 進めると IRQ を出して元の値に戻る
+    mapper.step_cpu_cycles(0x10000, &mut interrupt);
+    assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+    assert_eq!(mapper.cycles_until_next_cpu_event(), 3);
+}
+
+#[test]
+fn fcg_writes_irq_counter_directly_through_6000() {
+    let mut mapper = initialized(BandaiFcg::new_mapper16(test_data(
+        16, 4, 0x40000, true, false,
+    )));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x600A, 0x01, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x600B, 0x04, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x600C, 0x00, &mut interrupt);
+    assert_eq!(mapper.cycles_until_next_cpu_event(), 5);
+
+    // FCG-1/2 は $8000 のレジスタを持たない
+    Cartridge::write(&mut mapper, 0x8008, 0x02, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x00);
+    Cartridge::write(&mut mapper, 0x6008, 0x02, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x02);
+}
+
+#[test]
+fn eeprom_contents_persist_through_mapper_save() {
+    let mut mapper = initialized(BandaiFcg::new_mapper159(test_data(
+        159, 0, 0x40000, true, false,
+    )));
+    assert_eq!(mapper.persistent_mapper_save_lengths(), (0x80, 0));
+    write_x24c01(&mut mapper, 0x12, 0x5A);
+
+    let (prg_ram, chr_ram) = mapper.export_mapper_save_state().unwrap();
+    assert_eq!(prg_ram[0x12], 0x5A);
+
+    let mut restored = initialized(BandaiFcg::new_mapper159(test_data(
+        159, 0, 0x40000, true, false,
+    )));
+    restored
+        .import_mapper_save_state(&prg_ram, &chr_ram)
+        .unwrap();
+    assert_eq!(restored.export_mapper_save_state().unwrap().0, prg_ram);
+    assert!(
+        restored
+            .import_mapper_save_state(&prg_ram[..0x40], &chr_ram)
+            .is_err()
+    );
+}
+
+#[test]
+fn eeprom_output_is_visible_at_6000_bit4() {
+    let mut mapper = initialized(BandaiFcg::new_mapper16(test_data(
+        16, 0, 0x40000, true, true,
+    )));
+    assert_eq!(mapper.persistent_mapper_save_lengths(), (0x100, 0));
+    let mut interrupt = Interrupt::new();
+    // 読み出し有効で SDA を開放すると EEPROM の出力 (待機中は High) が見える
+    Cartridge::write(&mut mapper, 0x800D, 0x80, &mut interrupt);
+    let read = Cartridge::read(&mapper, 0x6000);
+    assert_eq!((read.data, read.mask), (0x10, 0x10));
+    Cartridge::write(&mut mapper, 0x800D, 0x00, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x00);
+
+    assert!(
+        !initialized(BandaiFcg::new_mapper16(test_data(
+            16, 4, 0x40000, true, true
+        )))
+        .has_persistent_mapper_save()
+    );
+}
+
+#[test]
+fn mapper153_selects_outer_prg_bank_and_gates_prg_ram() {
+    let mut mapper = initialized(BandaiFcg::new_mapper153(test_data(
+        153, 0, 0x80000, false, true,
+    )));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x8008, 0x02, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x8001, 0x01, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x12);
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x1F);
+
+    Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).mask, 0x00);
+    Cartridge::write(&mut mapper, 0x800D, 0x20, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x55);
+    assert_eq!(mapper.persistent_mapper_save_lengths(), (0x2000, 0));
+}
+
+#[test]
+fn irq_and_eeprom_state_round_trip_through_runtime_state() {
+    let mut mapper = initialized(BandaiFcg::new_mapper159(test_data(
+        159, 0, 0x40000, true, false,
+    )));
+    let mut interrupt = Interrupt::new();
+    write_x24c01(&mut mapper, 0x01, 0xA5);
+    Cartridge::write(&mut mapper, 0x800B, 0x10, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x800A, 0x01, &mut interrupt);
+    mapper.step_cpu_cycles(4, &mut interrupt);
+
+    let state = mapper.export_runtime_state().unwrap();
+    let mut restored = initialized(BandaiFcg::new_mapper159(test_data(
+        159, 0, 0x40000, true, false,
+    )));
+    restored.import_runtime_state(state).unwrap();
+    assert_eq!(restored.cycles_until_next_cpu_event(), 0x0D);
+    assert_eq!(restored.export_mapper_save_state().unwrap().0[0x01], 0xA5);
+
+    let mut without_eeprom = initialized(BandaiFcg::new_mapper16(test_data(
+        16, 4, 0x40000, true, false,
+    )));
+    assert!(
+        without_eeprom
+            .import_runtime_state(mapper.export_runtime_state().unwrap())
+            .is_err()
+    );
+}
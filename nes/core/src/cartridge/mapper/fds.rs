@@ -0,0 +1,370 @@
+use nerust_input_traits::OpenBusReadResult;
+use serde_bytes::ByteBuf;
+
+pub(super) use self::audio::FdsAudio;
+use self::disk::{DiskDrive, add_gaps};
+use super::Cartridge;
+use crate::{
+    cartridge_rom::{CartridgeData, FDS_DISK_SIDE_LEN},
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_FDS},
+    interrupt::{Interrupt, IrqSource},
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+mod audio;
+mod disk;
+#[cfg(test)]
+mod tests;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct FdsRuntimeState {
+    disks: Vec<ByteBuf>,
+    drive: DiskDrive,
+    audio: FdsAudio,
+    irq_reload: u16,
+    irq_counter: u16,
+    irq_repeat: bool,
+    irq_enabled: bool,
+    timer_irq_occurred: bool,
+    disk_io_enabled: bool,
+    sound_io_enabled: bool,
+    external_connector: u8,
+}
+
+/// Famicom Disk System の RAM アダプタ。$6000-$DFFF の PRG-RAM、$E000-$FFFF の BIOS、
+/// タイマー IRQ、ディスクドライブ、拡張音源を持つ。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Fds {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    /// ギャップ付きに展開した各面。ゲームのセーブはここに書き込まれる。
+    disks: Vec<ByteBuf>,
+    drive: DiskDrive,
+    audio: FdsAudio,
+    irq_reload: u16,
+    irq_counter: u16,
+    irq_repeat: bool,
+    irq_enabled: bool,
+    timer_irq_occurred: bool,
+    disk_io_enabled: bool,
+    sound_io_enabled: bool,
+    external_connector: u8,
+}
+
+#[typetag::serde]
+impl Cartridge for Fds {
+    fn read(&self, address: usize) -> OpenBusReadResult {
+        match address {
+            0..=0x1FFF => self.read_character(address),
+            0x4020..=0x5FFF => Mapper::read_expansion(self, address),
+            0x6000..=0xDFFF => OpenBusReadResult::new(self.state.sram[address - 0x6000], 0xFF),
+            0xE000..=0xFFFF => self
+                .data_ref()
+                .bios()
+                .get(address - 0xE000)
+                .map_or(OpenBusReadResult::new(0, 0), |&value| {
+                    OpenBusReadResult::new(value, 0xFF)
+                }),
+            _ => {
+                log::error!("unhandled mapper read at address: 0x{:04X}", address);
+                OpenBusReadResult::new(0, 0)
+            }
+        }
+    }
+
+    fn write(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        match address {
+            0..=0x1FFF => self.write_character(address, value),
+            0x4020..=0x5FFF => Mapper::write_expansion(self, address, value, interrupt),
+            0x6000..=0xDFFF => self.state.sram[address - 0x6000] = value,
+            // BIOS ROM
+            0xE000..=0xFFFF => {}
+            _ => {
+                log::error!("unhandled mapper write at address: 0x{:04X}", address);
+            }
+        }
+    }
+
+    fn notify_cpu_read(&mut self, address: usize, _value: u8, interrupt: &mut Interrupt) {
+        if !self.disk_io_enabled {
+            return;
+        }
+        match address {
+            0x4030 => {
+                self.timer_irq_occurred = false;
+                self.drive.acknowledge_status();
+                interrupt.clear_irq(IrqSource::EXTERNAL | IrqSource::FDS_DISK);
+            }
+            0x4031 => self.drive.acknowledge_data(interrupt),
+            _ => {}
+        }
+    }
+
+    fn expansion_audio_output(&self) -> f32 {
+        self.audio.output()
+    }
+
+    fn has_persistent_mapper_save(&self) -> bool {
+        true
+    }
+
+    // ディスクへの書き込み (ゲームのセーブ) を面ごと永続化する
+    fn export_mapper_save_state(&self) -> Result<(Vec<u8>, Vec<u8>), PersistenceError> {
+        Ok((
+            self.disks
+                .iter()
+                .flat_map(|disk| disk.iter().copied())
+                .collect(),
+            Vec::new(),
+        ))
+    }
+
+    fn import_mapper_save_state(
+        &mut self,
+        prg_ram: &[u8],
+        chr_ram: &[u8],
+    ) -> Result<(), PersistenceError> {
+        let disks_len = self.disks.iter().map(|disk| disk.len()).sum::<usize>();
+        if prg_ram.len() != disks_len || !chr_ram.is_empty() {
+            return Err(PersistenceError::Validation(
+                "FDS disk image length mismatch".into(),
+            ));
+        }
+        let mut offset = 0;
+        for disk in &mut self.disks {
+            let end = offset + disk.len();
+            disk.copy_from_slice(&prg_ram[offset..end]);
+            offset = end;
+        }
+        Ok(())
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_FDS.into(),
+            extra_body: encode_payload(&FdsRuntimeState {
+                disks: self.disks.clone(),
+                drive: self.drive.clone(),
+                audio: self.audio.clone(),
+                irq_reload: self.irq_reload,
+                irq_counter: self.irq_counter,
+                irq_repeat: self.irq_repeat,
+                irq_enabled: self.irq_enabled,
+                timer_irq_occurred: self.timer_irq_occurred,
+                disk_io_enabled: self.disk_io_enabled,
+                sound_io_enabled: self.sound_io_enabled,
+                external_connector: self.external_connector,
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_FDS {
+            return Err(PersistenceError::Validation(
+                "unexpected FDS runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: FdsRuntimeState = decode_payload(&state.extra_body)?;
+        let same_layout = runtime.disks.len() == self.disks.len()
+            && runtime
+                .disks
+                .iter()
+                .zip(&self.disks)
+                .all(|(incoming, current)| incoming.len() == current.len());
+        if !same_layout {
+            return Err(PersistenceError::Validation(
+                "FDS disk image length mismatch".into(),
+            ));
+        }
+        runtime
+            .drive
+            .validate_runtime_state(self.disks.len())
+            .map_err(PersistenceError::Validation)?;
+        self.state = state.mapper_state;
+        self.disks = runtime.disks;
+        self.drive = runtime.drive;
+        self.audio = runtime.audio;
+        self.irq_reload = runtime.irq_reload;
+        self.irq_counter = runtime.irq_counter;
+        self.irq_repeat = runtime.irq_repeat;
+        self.irq_enabled = runtime.irq_enabled;
+        self.timer_irq_occurred = runtime.timer_irq_occurred;
+        self.disk_io_enabled = runtime.disk_io_enabled;
+        self.sound_io_enabled = runtime.sound_io_enabled;
+        self.external_connector = runtime.external_connector;
+        Ok(())
+    }
+
+    fn eject_disk(&mut self) {
+        self.drive.eject();
+    }
+
+    fn switch_disk_side(&mut self) {
+        self.drive.switch_side();
+    }
+}
+
+impl Fds {
+    pub(crate) fn new(data: CartridgeData) -> Self {
+        let disks = data
+            .prog_rom()
+            .chunks(FDS_DISK_SIDE_LEN)
+            .map(add_gaps)
+            .collect::<Vec<_>>();
+        Self {
+            drive: DiskDrive::new(disks.len()),
+            disks,
+            cartridge_data: data,
+            state: MapperState::new(),
+            audio: FdsAudio::new(),
+            irq_reload: 0,
+            irq_counter: 0,
+            irq_repeat: false,
+            irq_enabled: false,
+            timer_irq_occurred: false,
+            disk_io_enabled: true,
+            sound_io_enabled: true,
+            external_connector: 0xFF,
+        }
+    }
+
+    fn step_timer_irq(&mut self, interrupt: &mut Interrupt) {
+        if !self.irq_enabled {
+            return;
+        }
+        if self.irq_counter == 0 {
+            self.timer_irq_occurred = true;
+            interrupt.set_irq(IrqSource::EXTERNAL);
+            self.irq_counter = self.irq_reload;
+            if !self.irq_repeat {
+                self.irq_enabled = false;
+            }
+        } else {
+            self.irq_counter -= 1;
+        }
+    }
+}
+
+impl CartridgeDataDao for Fds {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Fds {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Fds {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn initialize(&mut self) {
+        self.change_character_page(0, 0);
+    }
+
+    fn name(&self) -> &str {
+        "Famicom Disk System"
+    }
+
+    fn ram_len_default(&self) -> usize {
+        0x8000
+    }
+
+    fn read_expansion(&self, address: usize) -> OpenBusReadResult {
+        match address {
+            0x4030..=0x4033 if self.disk_io_enabled => match address {
+                // 読み出しによるフラグのクリアは notify_cpu_read で行う
+                0x4030 => OpenBusReadResult::new(
+                    u8::from(self.timer_irq_occurred) | self.drive.status(),
+                    0x43,
+                ),
+                0x4031 => OpenBusReadResult::new(self.drive.data(), 0xFF),
+                0x4032 => OpenBusReadResult::new(self.drive.drive_status(), 0x07),
+                _ => OpenBusReadResult::new(0x80 | (self.external_connector & 0x7F), 0xFF),
+            },
+            0x4040..=0x4092 if self.sound_io_enabled => self
+                .audio
+                .read(address)
+                .map_or(OpenBusReadResult::new(0, 0), |value| {
+                    OpenBusReadResult::new(value, 0x3F)
+                }),
+            _ => OpenBusReadResult::new(0, 0),
+        }
+    }
+
+    fn write_expansion(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        match address {
+            0x4020 => self.irq_reload = (self.irq_reload & 0xFF00) | u16::from(value),
+            0x4021 => self.irq_reload = (self.irq_reload & 0x00FF) | (u16::from(value) << 8),
+            0x4022 => {
+                self.irq_repeat = value & 0x01 != 0;
+                self.irq_enabled = value & 0x02 != 0 && self.disk_io_enabled;
+                if self.irq_enabled {
+                    self.irq_counter = self.irq_reload;
+                } else {
+                    self.timer_irq_occurred = false;
+                    interrupt.clear_irq(IrqSource::EXTERNAL);
+                }
+            }
+            0x4023 => {
+                self.disk_io_enabled = value & 0x01 != 0;
+                self.sound_io_enabled = value & 0x02 != 0;
+                if !self.disk_io_enabled {
+                    self.irq_enabled = false;
+                    interrupt.clear_irq(IrqSource::EXTERNAL | IrqSource::FDS_DISK);
+                }
+            }
+            0x4024..=0x4026 if self.disk_io_enabled => match address {
+                0x4024 => self.drive.write_data(value, interrupt),
+                0x4025 => {
+                    self.drive.write_control(value, interrupt);
+                    self.set_mirror_mode(if value & 0x08 != 0 {
+                        MirrorMode::Horizontal
+                    } else {
+                        MirrorMode::Vertical
+                    });
+                }
+                _ => self.external_connector = value,
+            },
+            0x4040..=0x408A if self.sound_io_enabled => self.audio.write(address, value),
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        self.step_timer_irq(interrupt);
+        self.drive.step(&mut self.disks, interrupt);
+        self.audio.step();
+    }
+}
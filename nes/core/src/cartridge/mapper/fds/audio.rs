@@ -0,0 +1,256 @@
+/// $4089 のマスター音量 (2/2, 2/3, 2/4, 2/5) を 1152 分率で表したもの。
+const MASTER_VOLUME_TABLE: [u32; 4] = [36, 24, 17, 14];
+/// モジュレーションテーブルの各値によるカウンタの増分。4 はカウンタを 0 に戻す。
+const MOD_ADJUSTMENTS: [i8; 8] = [0, 1, 2, 4, 0, -4, -2, -1];
+const MOD_RESET: u8 = 4;
+/// 最大出力 (63) で 2A03 の矩形波 1ch の最大値の約 2.4 倍になるよう揃える。
+const OUTPUT_SCALE: f32 = 0.36 / 63.0;
+
+/// 音量 / モジュレーション共通のエンベロープと周波数レジスタ。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+struct Envelope {
+    speed: u8,
+    gain: u8,
+    increase: bool,
+    disabled: bool,
+    timer: u32,
+    frequency: u16,
+}
+
+impl Envelope {
+    fn write_control(&mut self, value: u8, master_speed: u8) {
+        self.speed = value & 0x3F;
+        self.increase = value & 0x40 != 0;
+        self.disabled = value & 0x80 != 0;
+        self.reset_timer(master_speed);
+        if self.disabled {
+            self.gain = self.speed;
+        }
+    }
+
+    fn write_frequency_low(&mut self, value: u8) {
+        self.frequency = (self.frequency & 0x0F00) | u16::from(value);
+    }
+
+    fn write_frequency_high(&mut self, value: u8) {
+        self.frequency = (self.frequency & 0x00FF) | (u16::from(value & 0x0F) << 8);
+    }
+
+    fn reset_timer(&mut self, master_speed: u8) {
+        self.timer = 8 * (u32::from(self.speed) + 1) * u32::from(master_speed);
+    }
+
+    /// ゲインが更新されたら `true`。
+    fn tick(&mut self, master_speed: u8) -> bool {
+        if self.disabled || master_speed == 0 {
+            return false;
+        }
+        if self.timer > 1 {
+            self.timer -= 1;
+            return false;
+        }
+        self.reset_timer(master_speed);
+        if self.increase && self.gain < 32 {
+            self.gain += 1;
+        } else if !self.increase && self.gain > 0 {
+            self.gain -= 1;
+        }
+        true
+    }
+}
+
+/// FDS 拡張音源 (64 段の波形メモリ + 周波数モジュレーション)。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(crate) struct FdsAudio {
+    #[serde(with = "nerust_serialize::array::BigArray")]
+    wave_table: [u8; 64],
+    wave_write_enabled: bool,
+    wave_position: u8,
+    wave_accumulator: u16,
+    wave_halted: bool,
+    envelopes_halted: bool,
+    master_volume: u8,
+    master_envelope_speed: u8,
+    volume: Envelope,
+
+    modulator: Envelope,
+    #[serde(with = "nerust_serialize::array::BigArray")]
+    mod_table: [u8; 64],
+    mod_position: u8,
+    mod_accumulator: u16,
+    mod_halted: bool,
+    mod_counter: i8,
+    mod_output: i32,
+
+    output: u8,
+}
+
+impl FdsAudio {
+    pub(crate) fn new() -> Self {
+        Self {
+            wave_table: [0; 64],
+            wave_write_enabled: false,
+            wave_position: 0,
+            wave_accumulator: 0,
+            wave_halted: true,
+            envelopes_halted: false,
+            master_volume: 0,
+            master_envelope_speed: 0xE8,
+            volume: Envelope::default(),
+            modulator: Envelope::default(),
+            mod_table: [0; 64],
+            mod_position: 0,
+            mod_accumulator: 0,
+            mod_halted: true,
+            mod_counter: 0,
+            mod_output: 0,
+            output: 0,
+        }
+    }
+
+    pub(crate) fn read(&self, address: usize) -> Option<u8> {
+        match address {
+            0x4040..=0x407F => Some(self.wave_table[address & 0x3F]),
+            0x4090 => Some(self.volume.gain),
+            0x4092 => Some(self.modulator.gain),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn write(&mut self, address: usize, value: u8) {
+        match address {
+            0x4040..=0x407F if self.wave_write_enabled => {
+                self.wave_table[address & 0x3F] = value & 0x3F;
+            }
+            0x4080 => self.volume.write_control(value, self.master_envelope_speed),
+            0x4082 => self.volume.write_frequency_low(value),
+            0x4083 => {
+                self.volume.write_frequency_high(value);
+                self.envelopes_halted = value & 0x40 != 0;
+                self.wave_halted = value & 0x80 != 0;
+                if self.envelopes_halted {
+                    self.volume.reset_timer(self.master_envelope_speed);
+                    self.modulator.reset_timer(self.master_envelope_speed);
+                }
+                if self.wave_halted {
+                    self.wave_position = 0;
+                    self.wave_accumulator = 0;
+                }
+            }
+            0x4084 => self
+                .modulator
+                .write_control(value, self.master_envelope_speed),
+            0x4085 => self.set_mod_counter(value & 0x7F),
+            0x4086 => self.modulator.write_frequency_low(value),
+            0x4087 => {
+                self.modulator.write_frequency_high(value);
+                self.mod_halted = value & 0x80 != 0;
+                if self.mod_halted {
+                    self.mod_accumulator = 0;
+                }
+            }
+            // 停止中のみ書き込め、1 回の書き込みで同じ値が 2 エントリに入る
+            0x4088 if self.mod_halted => {
+                for _ in 0..2 {
+                    self.mod_table[usize::from(self.mod_position)] = value & 0x07;
+                    self.mod_position = (self.mod_position + 1) & 0x3F;
+                }
+            }
+            0x4089 => {
+                self.master_volume = value & 0x03;
+                self.wave_write_enabled = value & 0x80 != 0;
+            }
+            0x408A => self.master_envelope_speed = value,
+            _ => {}
+        }
+    }
+
+    /// 下位 7bit を -64..=63 の符号付きカウンタとして格納する。
+    fn set_mod_counter(&mut self, value: u8) {
+        self.mod_counter = ((value << 1) as i8) >> 1;
+    }
+
+    /// CPU 1 サイクル分進める。
+    pub(crate) fn step(&mut self) {
+        let frequency = self.volume.frequency;
+        if !self.wave_halted && !self.envelopes_halted {
+            self.volume.tick(self.master_envelope_speed);
+            if self.modulator.tick(self.master_envelope_speed) {
+                self.update_mod_output(frequency);
+            }
+        }
+        if self.step_modulator() {
+            self.update_mod_output(frequency);
+        }
+
+        if self.wave_halted {
+            self.wave_position = 0;
+        } else {
+            let pitch = i32::from(frequency) + self.mod_output;
+            if pitch > 0 && !self.wave_write_enabled {
+                let (next, overflowed) = self.wave_accumulator.overflowing_add(pitch as u16);
+                self.wave_accumulator = next;
+                if overflowed {
+                    self.wave_position = (self.wave_position + 1) & 0x3F;
+                }
+            }
+        }
+        // 波形メモリへの書き込み中は直前の出力を保持する
+        if !self.wave_write_enabled {
+            let level = u32::from(self.volume.gain.min(32))
+                * MASTER_VOLUME_TABLE[usize::from(self.master_volume)];
+            self.output =
+                (u32::from(self.wave_table[usize::from(self.wave_position)]) * level / 1152) as u8;
+        }
+    }
+
+    fn step_modulator(&mut self) -> bool {
+        let frequency = self.modulator.frequency;
+        if self.mod_halted || frequency == 0 {
+            return false;
+        }
+        let (next, overflowed) = self.mod_accumulator.overflowing_add(frequency);
+        self.mod_accumulator = next;
+        if !overflowed {
+            return false;
+        }
+        let entry = self.mod_table[usize::from(self.mod_position)];
+        if entry == MOD_RESET {
+            self.mod_counter = 0;
+        } else {
+            let counter = self
+                .mod_counter
+                .wrapping_add(MOD_ADJUSTMENTS[usize::from(entry)]);
+            self.set_mod_counter(counter as u8 & 0x7F);
+        }
+        self.mod_position = (self.mod_position + 1) & 0x3F;
+        true
+    }
+
+    /// nesdev wiki の FDS audio にある整数演算をそのまま用いる。
+    fn update_mod_output(&mut self, pitch: u16) {
+        let counter = i32::from(self.mod_counter);
+        let mut temp = counter * i32::from(self.modulator.gain);
+        let remainder = temp & 0x0F;
+        temp >>= 4;
+        if remainder > 0 && temp & 0x80 == 0 {
+            temp += if counter < 0 { -1 } else { 2 };
+        }
+        if temp >= 192 {
+            temp -= 256;
+        } else if temp < -64 {
+            temp += 256;
+        }
+        temp *= i32::from(pitch);
+        let remainder = temp & 0x3F;
+        temp >>= 6;
+        if remainder >= 32 {
+            temp += 1;
+        }
+        self.mod_output = temp;
+    }
+
+    pub(crate) fn output(&self) -> f32 {
+        f32::from(self.output) * OUTPUT_SCALE
+    }
+}
@@ -0,0 +1,289 @@
+use serde_bytes::ByteBuf;
+
+use crate::interrupt::{Interrupt, IrqSource};
+
+/// 面の先頭にある 28300 bit のギャップ。
+const LEADING_GAP_LEN: usize = 28300 / 8;
+/// ブロック間の 976 bit のギャップ。
+const BLOCK_GAP_LEN: usize = 976 / 8;
+const BLOCK_START_MARK: u8 = 0x80;
+/// イメージには CRC が含まれないため、ドライブにはダミー値を読ませる。
+const DUMMY_CRC: [u8; 2] = [0x4D, 0x62];
+
+/// 1 byte 読み書きするのにかかる CPU サイクル数 (約 96.4kbit/s)。
+const BYTE_CYCLES: u32 = 149;
+/// モーター始動後、ヘッドが面の先頭に戻るまでの CPU サイクル数。
+const HEAD_RETURN_CYCLES: u32 = 50000;
+/// 面を切り替える際、BIOS が取り出しを検知できるよう空のドライブを見せておく期間。
+const DISK_SWAP_CYCLES: u32 = 1_000_000;
+
+/// `.fds` の 1 面分 (ギャップと CRC を省いた形式) を、ドライブが先頭から順に
+/// 読み書きするギャップ付きの列に展開する。長さは元の面より短くならない。
+pub(super) fn add_gaps(side: &[u8]) -> ByteBuf {
+    let mut disk = vec![0; LEADING_GAP_LEN];
+    let mut offset = 0;
+    while let Some(&block_type) = side.get(offset) {
+        let block_len = match block_type {
+            // ディスク情報
+            1 => 56,
+            // ファイル数
+            2 => 2,
+            // ファイルヘッダ
+            3 => 16,
+            // ファイル本体。長さは直前のファイルヘッダの 13-14 byte 目
+            4 => match offset
+                .checked_sub(3)
+                .and_then(|size| side.get(size..size + 2))
+            {
+                Some(&[low, high]) => 1 + usize::from(u16::from_le_bytes([low, high])),
+                _ => break,
+            },
+            _ => break,
+        };
+        let Some(block) = side.get(offset..offset + block_len) else {
+            break;
+        };
+        disk.push(BLOCK_START_MARK);
+        disk.extend_from_slice(block);
+        disk.extend_from_slice(&DUMMY_CRC);
+        disk.resize(disk.len() + BLOCK_GAP_LEN, 0);
+        offset += block_len;
+    }
+    disk.resize(disk.len().max(LEADING_GAP_LEN + side.len()), 0);
+    ByteBuf::from(disk)
+}
+
+/// RAM アダプタのディスク転送部とドライブ機構。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(super) struct DiskDrive {
+    side_count: usize,
+    inserted_side: Option<usize>,
+    next_side: usize,
+    swap_delay: u32,
+
+    write_data: u8,
+    read_data: u8,
+    motor_on: bool,
+    reset_transfer: bool,
+    read_mode: bool,
+    crc_control: bool,
+    previous_crc_control: bool,
+    disk_ready: bool,
+    irq_enabled: bool,
+
+    position: usize,
+    delay: u32,
+    end_of_head: bool,
+    scanning: bool,
+    gap_ended: bool,
+    transfer_complete: bool,
+    crc: u16,
+}
+
+impl DiskDrive {
+    /// 電源投入時は A 面が挿入されている。
+    pub(super) fn new(side_count: usize) -> Self {
+        Self {
+            side_count,
+            inserted_side: (side_count > 0).then_some(0),
+            next_side: 1 % side_count.max(1),
+            swap_delay: 0,
+            write_data: 0,
+            read_data: 0,
+            motor_on: false,
+            reset_transfer: false,
+            read_mode: true,
+            crc_control: false,
+            previous_crc_control: false,
+            disk_ready: false,
+            irq_enabled: false,
+            position: 0,
+            delay: 0,
+            end_of_head: true,
+            scanning: false,
+            gap_ended: false,
+            transfer_complete: false,
+            crc: 0,
+        }
+    }
+
+    pub(super) fn eject(&mut self) {
+        if let Some(side) = self.inserted_side.take() {
+            self.next_side = (side + 1) % self.side_count;
+        }
+        self.swap_delay = 0;
+    }
+
+    /// 現在の面を取り出し、しばらくしてから次の面 (最後の面の次は A 面) を挿入する。
+    pub(super) fn switch_side(&mut self) {
+        if self.side_count == 0 {
+            return;
+        }
+        let next = self
+            .inserted_side
+            .map_or(self.next_side, |side| (side + 1) % self.side_count);
+        self.inserted_side = None;
+        self.next_side = next;
+        self.swap_delay = DISK_SWAP_CYCLES;
+    }
+
+    /// $4024
+    pub(super) fn write_data(&mut self, value: u8, interrupt: &mut Interrupt) {
+        self.write_data = value;
+        self.transfer_complete = false;
+        interrupt.clear_irq(IrqSource::FDS_DISK);
+    }
+
+    /// $4025
+    pub(super) fn write_control(&mut self, value: u8, interrupt: &mut Interrupt) {
+        self.motor_on = value & 0x01 != 0;
+        self.reset_transfer = value & 0x02 != 0;
+        self.read_mode = value & 0x04 != 0;
+        self.crc_control = value & 0x10 != 0;
+        self.disk_ready = value & 0x40 != 0;
+        self.irq_enabled = value & 0x80 != 0;
+        interrupt.clear_irq(IrqSource::FDS_DISK);
+    }
+
+    /// $4030 の bit 1/6。
+    pub(super) fn status(&self) -> u8 {
+        (u8::from(self.transfer_complete) << 1) | (u8::from(self.end_of_head) << 6)
+    }
+
+    /// $4030 の読み出しで転送完了フラグを落とす。
+    pub(super) fn acknowledge_status(&mut self) {
+        self.transfer_complete = false;
+    }
+
+    /// $4031
+    pub(super) fn data(&self) -> u8 {
+        self.read_data
+    }
+
+    /// $4031 の読み出しで転送完了フラグとディスク IRQ を落とす。
+    pub(super) fn acknowledge_data(&mut self, interrupt: &mut Interrupt) {
+        self.transfer_complete = false;
+        interrupt.clear_irq(IrqSource::FDS_DISK);
+    }
+
+    /// $4032: bit 0 未挿入, bit 1 未準備, bit 2 書き込み禁止。
+    pub(super) fn drive_status(&self) -> u8 {
+        let inserted = self.inserted_side.is_some();
+        u8::from(!inserted)
+            | (u8::from(!inserted || !self.scanning) << 1)
+            | (u8::from(!inserted) << 2)
+    }
+
+    pub(super) fn step(&mut self, disks: &mut [ByteBuf], interrupt: &mut Interrupt) {
+        if self.swap_delay > 0 {
+            self.swap_delay -= 1;
+            if self.swap_delay == 0 {
+                self.inserted_side = Some(self.next_side);
+            }
+        }
+        let Some(disk) = self
+            .inserted_side
+            .filter(|_| self.motor_on)
+            .and_then(|side| disks.get_mut(side))
+        else {
+            self.end_of_head = true;
+            self.scanning = false;
+            return;
+        };
+        if self.reset_transfer && !self.scanning {
+            return;
+        }
+        if self.end_of_head {
+            self.delay = HEAD_RETURN_CYCLES;
+            self.end_of_head = false;
+            self.position = 0;
+            self.gap_ended = false;
+            return;
+        }
+        if self.delay > 0 {
+            self.delay -= 1;
+            return;
+        }
+
+        self.scanning = true;
+        let mut raise_irq = self.irq_enabled;
+        if self.read_mode {
+            let data = disk.get(self.position).copied().unwrap_or(0);
+            if !self.previous_crc_control {
+                self.update_crc(data);
+            }
+            if !self.disk_ready {
+                self.gap_ended = false;
+                self.crc = 0;
+            } else if data != 0 && !self.gap_ended {
+                // ギャップ末尾の開始マークは転送せず、同期だけ取る
+                self.gap_ended = true;
+                raise_irq = false;
+            }
+            if self.gap_ended {
+                self.transfer_complete = true;
+                self.read_data = data;
+                if raise_irq {
+                    interrupt.set_irq(IrqSource::FDS_DISK);
+                }
+            }
+        } else {
+            let mut data = 0;
+            if !self.crc_control {
+                self.transfer_complete = true;
+                data = self.write_data;
+                if raise_irq {
+                    interrupt.set_irq(IrqSource::FDS_DISK);
+                }
+            }
+            if !self.disk_ready {
+                data = 0;
+            }
+            if self.crc_control {
+                if !self.previous_crc_control {
+                    self.update_crc(0);
+                    self.update_crc(0);
+                }
+                data = self.crc as u8;
+                self.crc >>= 8;
+            } else {
+                self.update_crc(data);
+            }
+            if let Some(slot) = disk.get_mut(self.position) {
+                *slot = data;
+            }
+            self.gap_ended = false;
+        }
+        self.previous_crc_control = self.crc_control;
+
+        self.position += 1;
+        if self.position >= disk.len() {
+            self.motor_on = false;
+        } else {
+            self.delay = BYTE_CYCLES;
+        }
+    }
+
+    fn update_crc(&mut self, value: u8) {
+        for bit in 0..8 {
+            let carry = self.crc & 1 != 0;
+            self.crc >>= 1;
+            if carry {
+                self.crc ^= 0x8408;
+            }
+            if value >> bit & 1 != 0 {
+                self.crc ^= 0x8000;
+            }
+        }
+    }
+
+    pub(super) fn validate_runtime_state(&self, side_count: usize) -> Result<(), String> {
+        if self.side_count != side_count
+            || self.inserted_side.is_some_and(|side| side >= side_count)
+            || self.next_side >= side_count.max(1)
+        {
+            return Err("FDS disk side out of range".into());
+        }
+        Ok(())
+    }
+}
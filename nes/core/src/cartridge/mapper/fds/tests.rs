@@ -0,0 +1,160 @@
+use super::{Cartridge, Fds};
+use crate::{
+    cartridge_rom::{CartridgeData, FDS_DISK_SIDE_LEN},
+    interrupt::{Interrupt, IrqSource},
+    mapper::Mapper,
+    rom_parse::parse_rom,
+};
+
+/// ディスク情報、ファイル数、4 byte のファイル 1 つを持つ面
+fn test_side() -> Vec<u8> {
+    let mut side = b"\x01*NINTENDO-HVC*".to_vec();
+    side.resize(56, 0);
+    side.extend_from_slice(&[0x02, 0x01]);
+    let mut header = vec![0x03, 0x00, 0x00];
+    header.extend_from_slice(b"TESTFILE");
+    header.extend_from_slice(&[0x00, 0x60, 0x04, 0x00, 0x00]);
+    side.extend_from_slice(&header);
+    side.extend_from_slice(&[0x04, 0xDE, 0xAD, 0xBE, 0xEF]);
+    side.resize(FDS_DISK_SIDE_LEN, 0);
+    side
+}
+
+fn test_data() -> CartridgeData {
+    parse_rom(&[test_side(), test_side()].concat()).expect("test disk image should parse")
+}
+
+fn new_mapper() -> Fds {
+    let mut mapper = Fds::new(test_data());
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+fn read_register(mapper: &mut Fds, address: usize, interrupt: &mut Interrupt) -> u8 {
+    let value = Cartridge::read(mapper, address).data;
+    mapper.notify_cpu_read(address, value, interrupt);
+    value
+}
+
+#[test]
+fn timer_irq_fires_after_reload_value_and_is_acknowledged_by_4030() {
+    let mut mapper = new_mapper();
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x4023, 0x01, &mut interrupt);
+    mapper.write_expansion(0x4020, 0x03, &mut interrupt);
+    mapper.write_expansion(0x4021, 0x00, &mut interrupt);
+    mapper.write_expansion(0x4022, 0x02, &mut interrupt);
+
+    for _ in 0..3 {
+        mapper.step(&mut interrupt);
+    }
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    mapper.step(&mut interrupt);
+    assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+
+    assert_eq!(
+        read_register(&mut mapper, 0x4030, &mut interrupt) & 0x01,
+        0x01
+    );
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    assert_eq!(
+        read_register(&mut mapper, 0x4030, &mut interrupt) & 0x01,
+        0x00
+    );
+}
+
+#[test]
+fn disk_transfer_delivers_first_block_bytes_through_4031() {
+    let mut mapper = new_mapper();
+    let mut interrupt = Interrupt::new();
+    // モーター ON / 読み込み / ギャップ待ち / 転送 IRQ 有効
+    mapper.write_expansion(0x4025, 0xC5, &mut interrupt);
+
+    let mut received = Vec::new();
+    for _ in 0..1_000_000 {
+        mapper.step(&mut interrupt);
+        if interrupt.get_irq(IrqSource::FDS_DISK) {
+            received.push(read_register(&mut mapper, 0x4031, &mut interrupt));
+            if received.len() == 15 {
+                break;
+            }
+        }
+    }
+
+    assert_eq!(received, b"\x01*NINTENDO-HVC*");
+}
+
+#[test]
+fn eject_and_side_switch_are_reported_by_drive_status() {
+    let mut mapper = new_mapper();
+    let mut interrupt = Interrupt::new();
+    assert_eq!(
+        read_register(&mut mapper, 0x4032, &mut interrupt) & 0x01,
+        0x00
+    );
+
+    mapper.eject_disk();
+    assert_eq!(
+        read_register(&mut mapper, 0x4032, &mut interrupt) & 0x01,
+        0x01
+    );
+
+    mapper.switch_disk_side();
+    mapper.step(&mut interrupt);
+    assert_eq!(
+        read_register(&mut mapper, 0x4032, &mut interrupt) & 0x01,
+        0x01
+    );
+    for _ in 0..1_000_000 {
+        mapper.step(&mut interrupt);
+    }
+    assert_eq!(
+        read_register(&mut mapper, 0x4032, &mut interrupt) & 0x01,
+        0x00
+    );
+}
+
+#[test]
+fn wavetable_channel_produces_expansion_audio() {
+    let mut mapper = new_mapper();
+    let mut interrupt = Interrupt::new();
+    assert_eq!(mapper.expansion_audio_output(), 0.0);
+
+    mapper.write_expansion(0x4089, 0x80, &mut interrupt);
+    for address in 0x4040..=0x407F {
+        mapper.write_expansion(address, 0x3F, &mut interrupt);
+    }
+    mapper.write_expansion(0x4089, 0x00, &mut interrupt);
+    // エンベロープ無効でゲイン 32 固定
+    mapper.write_expansion(0x4080, 0xA0, &mut interrupt);
+    mapper.write_expansion(0x4082, 0xFF, &mut interrupt);
+    mapper.write_expansion(0x4083, 0x0F, &mut interrupt);
+    mapper.step(&mut interrupt);
+
+    assert!(mapper.expansion_audio_output() > 0.0);
+    assert_eq!(
+        read_register(&mut mapper, 0x4090, &mut interrupt) & 0x3F,
+        0x20
+    );
+}
+
+#[test]
+fn disk_writes_round_trip_through_mapper_save() {
+    let mut mapper = new_mapper();
+    let (prg_ram, chr_ram) = mapper
+        .export_mapper_save_state()
+        .expect("disk image should export");
+    assert!(chr_ram.is_empty());
+
+    let mut modified = prg_ram.clone();
+    modified[0] = 0x5A;
+    mapper
+        .import_mapper_save_state(&modified, &chr_ram)
+        .expect("same-sized disk image should import");
+    assert_eq!(mapper.disks[0][0], 0x5A);
+    assert!(
+        mapper
+            .import_mapper_save_state(&prg_ram[1..], &chr_ram)
+            .is_err()
+    );
+}
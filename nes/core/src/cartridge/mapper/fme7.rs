@@ -1,3 +1,4 @@
+pub(super) use self::audio::Sunsoft5bAudio;
 use super::Cartridge;
 use crate::{
     cartridge_rom::CartridgeData,
@@ -10,6 +11,8 @@ use crate::{
     persistence_error::PersistenceError,
 };
 
+mod audio;
+
 const IRQ_ENABLE: u8 = 0x01;
 const IRQ_COUNT: u8 = 0x80;
 
@@ -20,6 +23,8 @@ struct Fme7RuntimeState {
     prg_banks: [u8; 4],
     irq_control: u8,
     irq_counter: u16,
+    #[serde(default)]
+    audio: Sunsoft5bAudio,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -31,10 +36,20 @@ pub(crate) struct Fme7 {
     prg_banks: [u8; 4],
     irq_control: u8,
     irq_counter: u16,
+    audio: Sunsoft5bAudio,
 }
 
 #[typetag::serde]
 impl Cartridge for Fme7 {
+    fn expansion_audio_output(&self) -> f32 {
+        self.audio.output()
+    }
+
+    // 5B は CPU サイクルで進むため、APU のサンプル境界ごとにマッパーを進める
+    fn expansion_audio_cpu_step_synchronized(&self) -> bool {
+        true
+    }
+
     fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
         Ok(CartridgeRuntimeState {
             mapper_state: self.state.clone(),
@@ -45,6 +60,7 @@ impl Cartridge for Fme7 {
                 prg_banks: self.prg_banks,
                 irq_control: self.irq_control,
                 irq_counter: self.irq_counter,
+                audio: self.audio.clone(),
             })?,
         })
     }
@@ -72,6 +88,7 @@ impl Cartridge for Fme7 {
         self.prg_banks = runtime.prg_banks;
         self.irq_control = runtime.irq_control;
         self.irq_counter = runtime.irq_counter;
+        self.audio = runtime.audio;
         Ok(())
     }
 }
@@ -86,6 +103,7 @@ impl Fme7 {
             prg_banks: [3, 0, 1, 2],
             irq_control: 0,
             irq_counter: 0,
+            audio: Sunsoft5bAudio::new(),
         }
     }
 
@@ -242,15 +260,18 @@ impl Mapper for Fme7 {
         match address & 0xE000 {
             0x8000 => self.command = value & 0x0F,
             0xA000 => self.write_command_data(value, interrupt),
-            _ => {}
+            0xC000 => self.audio.write_register_select(value),
+            _ => self.audio.write_register_data(value),
         }
     }
 
     fn step(&mut self, interrupt: &mut Interrupt) {
         self.step_irq(interrupt);
+        self.audio.step();
     }
 
     fn step_cpu_cycles(&mut self, cycles: u64, interrupt: &mut Interrupt) {
+        self.audio.step_cycles(cycles);
         if (self.irq_control & IRQ_COUNT) == 0 {
             return;
         }
@@ -325,6 +346,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid")
     }
@@ -390,4 +412,24 @@ mod tests {
         mapper.write_register(0xA000, 0x81, &mut interrupt);
         assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
     }
+
+    #[test]
+    fn audio_registers_round_trip_through_runtime_state() {
+        let mut mapper = Fme7::new(test_data());
+        Cartridge::initialize(&mut mapper);
+        let mut interrupt = Interrupt::new();
+
+        mapper.write_register(0xC000, 0x07, &mut interrupt);
+        mapper.write_register(0xE000, 0x3F, &mut interrupt);
+        mapper.write_register(0xC000, 0x08, &mut interrupt);
+        mapper.write_register(0xE000, 0x0F, &mut interrupt);
+        let output = mapper.expansion_audio_output();
+        assert!(output > 0.0);
+
+        let state = mapper.export_runtime_state().unwrap();
+        let mut restored = Fme7::new(test_data());
+        Cartridge::initialize(&mut restored);
+        restored.import_runtime_state(state).unwrap();
+        assert_eq!(restored.expansion_audio_output(), output);
+    }
 }
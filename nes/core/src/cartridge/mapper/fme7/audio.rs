@@ -0,0 +1,277 @@
+/// 5bit レベルの出力値。1 段 1.5dB の対数カーブで、最大を 255 とする。
+const LEVEL_TABLE: [u8; 32] = [
+    0, 1, 2, 2, 2, 3, 3, 4, 5, 6, 7, 8, 10, 11, 14, 16, 19, 23, 27, 32, 38, 45, 54, 64, 76, 90,
+    108, 128, 152, 181, 215, 255,
+];
+/// 音量 12 (レベル 25) の 1ch が 2A03 の矩形波 1ch の最大値と同程度になるよう揃える
+/// (nes-audio-tests の db_5b)。
+const OUTPUT_SCALE: f32 = 0.15 / 90.0;
+/// 各ジェネレータの周期 1 単位あたりの CPU サイクル数 (内部クロックは CPU/2、さらに 1/8 分周)。
+const UNIT_CYCLES: u8 = 16;
+
+const ENVELOPE_HOLD: u8 = 0x01;
+const ENVELOPE_ALTERNATE: u8 = 0x02;
+const ENVELOPE_ATTACK: u8 = 0x04;
+const ENVELOPE_CONTINUE: u8 = 0x08;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+struct Tone {
+    period: u16,
+    counter: u16,
+    high: bool,
+    volume: u8,
+}
+
+impl Tone {
+    fn clock(&mut self) {
+        self.counter += 1;
+        if self.counter >= self.period.max(1) {
+            self.counter = 0;
+            self.high = !self.high;
+        }
+    }
+
+    fn uses_envelope(&self) -> bool {
+        self.volume & 0x10 != 0
+    }
+}
+
+/// Sunsoft 5B 拡張音源 (YM2149 互換の矩形波 3ch + ノイズ + エンベロープ)。
+/// $C000 でレジスタを選び、$E000 で値を書き込む。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(crate) struct Sunsoft5bAudio {
+    register: u8,
+    divider: u8,
+    tones: [Tone; 3],
+    /// R7。下位 3bit がトーン、次の 3bit がノイズの無効フラグ。
+    mixer: u8,
+    noise_period: u8,
+    noise_counter: u8,
+    noise_lfsr: u32,
+    envelope_period: u16,
+    envelope_counter: u16,
+    envelope_shape: u8,
+    envelope_step: u8,
+    envelope_attack: bool,
+    envelope_holding: bool,
+}
+
+impl Default for Sunsoft5bAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sunsoft5bAudio {
+    pub(crate) fn new() -> Self {
+        Self {
+            register: 0,
+            divider: 0,
+            tones: Default::default(),
+            mixer: 0,
+            noise_period: 0,
+            noise_counter: 0,
+            noise_lfsr: 1,
+            envelope_period: 0,
+            envelope_counter: 0,
+            envelope_shape: 0,
+            envelope_step: 0,
+            envelope_attack: false,
+            envelope_holding: true,
+        }
+    }
+
+    pub(crate) fn write_register_select(&mut self, value: u8) {
+        self.register = value;
+    }
+
+    pub(crate) fn write_register_data(&mut self, value: u8) {
+        // 上位 4bit が 0 でない選択はどのレジスタにも届かない
+        match self.register {
+            0x00..=0x05 => {
+                let tone = &mut self.tones[usize::from(self.register >> 1)];
+                tone.period = if self.register & 1 == 0 {
+                    (tone.period & 0x0F00) | u16::from(value)
+                } else {
+                    (tone.period & 0x00FF) | (u16::from(value & 0x0F) << 8)
+                };
+            }
+            0x06 => self.noise_period = value & 0x1F,
+            0x07 => self.mixer = value,
+            0x08..=0x0A => self.tones[usize::from(self.register - 0x08)].volume = value & 0x1F,
+            0x0B => self.envelope_period = (self.envelope_period & 0xFF00) | u16::from(value),
+            0x0C => {
+                self.envelope_period = (self.envelope_period & 0x00FF) | (u16::from(value) << 8)
+            }
+            0x0D => {
+                self.envelope_shape = value & 0x0F;
+                self.envelope_step = 0;
+                self.envelope_counter = 0;
+                self.envelope_attack = self.envelope_shape & ENVELOPE_ATTACK != 0;
+                self.envelope_holding = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// CPU 1 サイクル分進める。
+    pub(crate) fn step(&mut self) {
+        self.step_cycles(1);
+    }
+
+    pub(crate) fn step_cycles(&mut self, cycles: u64) {
+        let total = u64::from(self.divider) + cycles;
+        self.divider = (total % u64::from(UNIT_CYCLES)) as u8;
+        for _ in 0..total / u64::from(UNIT_CYCLES) {
+            self.clock_unit();
+        }
+    }
+
+    fn clock_unit(&mut self) {
+        for tone in &mut self.tones {
+            tone.clock();
+        }
+        // ノイズはトーンの半分の速度でシフトする
+        self.noise_counter += 1;
+        if self.noise_counter >= self.noise_period.max(1) * 2 {
+            self.noise_counter = 0;
+            let feedback = (self.noise_lfsr ^ (self.noise_lfsr >> 3)) & 1;
+            self.noise_lfsr = (self.noise_lfsr >> 1) | (feedback << 16);
+        }
+        self.envelope_counter += 1;
+        if self.envelope_counter >= self.envelope_period.max(1) {
+            self.envelope_counter = 0;
+            self.step_envelope();
+        }
+    }
+
+    fn step_envelope(&mut self) {
+        if self.envelope_holding {
+            return;
+        }
+        self.envelope_step += 1;
+        if self.envelope_step < 32 {
+            return;
+        }
+        let shape = self.envelope_shape;
+        if shape & ENVELOPE_CONTINUE == 0 {
+            // 1 周期で終わり、0 のまま止まる
+            self.envelope_attack = false;
+            self.envelope_step = 31;
+            self.envelope_holding = true;
+        } else if shape & ENVELOPE_HOLD != 0 {
+            if shape & ENVELOPE_ALTERNATE != 0 {
+                self.envelope_attack = !self.envelope_attack;
+            }
+            self.envelope_step = 31;
+            self.envelope_holding = true;
+        } else {
+            if shape & ENVELOPE_ALTERNATE != 0 {
+                self.envelope_attack = !self.envelope_attack;
+            }
+            self.envelope_step = 0;
+        }
+    }
+
+    fn envelope_level(&self) -> u8 {
+        if self.envelope_attack {
+            self.envelope_step
+        } else {
+            31 - self.envelope_step
+        }
+    }
+
+    pub(crate) fn output(&self) -> f32 {
+        let noise_high = self.noise_lfsr & 1 != 0;
+        let sum = self
+            .tones
+            .iter()
+            .enumerate()
+            .map(|(channel, tone)| {
+                let tone_disabled = self.mixer & (1 << channel) != 0;
+                let noise_disabled = self.mixer & (1 << (channel + 3)) != 0;
+                if !((tone.high || tone_disabled) && (noise_high || noise_disabled)) {
+                    return 0;
+                }
+                // 4bit の固定音量は 5bit レベルの奇数段に対応する (3dB 刻み)
+                let level = match tone.volume & 0x0F {
+                    _ if tone.uses_envelope() => self.envelope_level(),
+                    0 => 0,
+                    volume => volume * 2 + 1,
+                };
+                u32::from(LEVEL_TABLE[usize::from(level)])
+            })
+            .sum::<u32>();
+        sum as f32 * OUTPUT_SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sunsoft5bAudio;
+
+    fn write(audio: &mut Sunsoft5bAudio, register: u8, value: u8) {
+        audio.write_register_select(register);
+        audio.write_register_data(value);
+    }
+
+    #[test]
+    fn tone_period_sets_square_wave_length() {
+        let mut audio = Sunsoft5bAudio::new();
+        write(&mut audio, 0x00, 0x04);
+        write(&mut audio, 0x07, 0x3E);
+        write(&mut audio, 0x08, 0x0F);
+
+        // 周期 4 単位 (64 サイクル) ごとに反転する
+        let mut toggles = Vec::new();
+        let mut last = audio.output() > 0.0;
+        for cycle in 1..=256 {
+            audio.step();
+            let high = audio.output() > 0.0;
+            if high != last {
+                toggles.push(cycle);
+                last = high;
+            }
+        }
+        assert_eq!(toggles, [64, 128, 192, 256]);
+    }
+
+    #[test]
+    fn volume_curve_is_logarithmic() {
+        let mut audio = Sunsoft5bAudio::new();
+        write(&mut audio, 0x07, 0x3F);
+        write(&mut audio, 0x08, 0x0F);
+        let full = audio.output();
+        write(&mut audio, 0x08, 0x0D);
+        let minus_6db = audio.output();
+        write(&mut audio, 0x08, 0x00);
+        assert_eq!(audio.output(), 0.0);
+        assert!((minus_6db / full - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn envelope_shapes_hold_at_expected_level() {
+        let mut audio = Sunsoft5bAudio::new();
+        write(&mut audio, 0x07, 0x3F);
+        write(&mut audio, 0x08, 0x10);
+        write(&mut audio, 0x0B, 0x01);
+
+        // 減衰のみ (0x00) は 0 で止まる
+        write(&mut audio, 0x0D, 0x00);
+        assert!(audio.output() > 0.0);
+        audio.step_cycles(16 * 40);
+        assert_eq!(audio.output(), 0.0);
+
+        // 減衰 + 反転保持 (0x0B) は最大で止まる
+        write(&mut audio, 0x0D, 0x0B);
+        audio.step_cycles(16 * 40);
+        assert_eq!(audio.envelope_level(), 31);
+
+        // 三角波 (0x0E) は繰り返す
+        write(&mut audio, 0x0D, 0x0E);
+        audio.step_cycles(16 * 31);
+        assert_eq!(audio.envelope_level(), 31);
+        audio.step_cycles(16 * 32);
+        assert_eq!(audio.envelope_level(), 0);
+    }
+}
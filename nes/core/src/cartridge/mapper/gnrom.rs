@@ -94,6 +94,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid")
     }
@@ -117,6 +117,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid")
     }
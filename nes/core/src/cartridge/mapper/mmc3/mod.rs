@@ -4,6 +4,7 @@ mod shared;
 mod standard;
 mod txsrom;
 
+pub(super) use self::shared::{character_banks, program_banks};
 use self::{mmc3_nec::Mmc3Nec, mmc6::Mmc6, standard::Mmc3, txsrom::TxSrom};
 use crate::{
     cart_device::Cartridge, cartridge_error::CartridgeError, cartridge_rom::CartridgeData,
@@ -81,6 +82,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid")
     }
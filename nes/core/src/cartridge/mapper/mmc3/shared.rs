@@ -103,6 +103,38 @@ impl Mapper4Config {
     }
 }
 
+/// R6 / R7 と PRG モード ($8000 の bit6) から、$8000-$FFFF の 8KiB スロットごとのバンクを求める。
+/// Namco 108 系も PRG モード 0 固定でこの配置を使う。
+pub(crate) fn program_banks(bank_data: &[u8; 8], swap_low: bool, bank_count: usize) -> [usize; 4] {
+    let last_bank = bank_count.saturating_sub(1);
+    let second_last_bank = bank_count.saturating_sub(2);
+    let (r6, r7) = (usize::from(bank_data[6]), usize::from(bank_data[7]));
+    if swap_low {
+        [second_last_bank, r7, r6, last_bank]
+    } else {
+        [r6, r7, second_last_bank, last_bank]
+    }
+}
+
+/// R0-R5 と CHR A12 反転 ($8000 の bit7) から、1KiB スロットごとのバンクを求める。
+/// R0 / R1 は 2KiB 単位で、下位ビットは無視される。
+pub(crate) fn character_banks(bank_data: &[u8; 8], invert_a12: bool) -> [usize; 8] {
+    let register = |index: usize| usize::from(bank_data[index]);
+    let two_kib = [
+        register(0) & !0x01,
+        register(0) | 0x01,
+        register(1) & !0x01,
+        register(1) | 0x01,
+    ];
+    let one_kib = [register(2), register(3), register(4), register(5)];
+    let (low, high) = if invert_a12 {
+        (one_kib, two_kib)
+    } else {
+        (two_kib, one_kib)
+    };
+    std::array::from_fn(|slot| if slot < 4 { low[slot] } else { high[slot - 4] })
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 struct IrqUnit {
     variant: IrqVariant,
@@ -437,40 +469,17 @@ impl Mapper4Shared {
     }
 
     fn update_offsets(&mut self) {
-        let prg_bank_count = self.program_bank_count();
-        let last_bank = prg_bank_count.saturating_sub(1);
-        let second_last_bank = prg_bank_count.saturating_sub(2);
-
-        if (self.bank_select & 0x40) == 0 {
-            self.map_program_bank(0, usize::from(self.bank_data[6]));
-            self.map_program_bank(1, usize::from(self.bank_data[7]));
-            self.map_program_bank(2, second_last_bank);
-            self.map_program_bank(3, last_bank);
-        } else {
-            self.map_program_bank(0, second_last_bank);
-            self.map_program_bank(1, usize::from(self.bank_data[7]));
-            self.map_program_bank(2, usize::from(self.bank_data[6]));
-            self.map_program_bank(3, last_bank);
+        let program_banks = program_banks(
+            &self.bank_data,
+            self.bank_select & 0x40 != 0,
+            self.program_bank_count(),
+        );
+        for (slot, bank) in program_banks.into_iter().enumerate() {
+            self.map_program_bank(slot, bank);
         }
-
-        if (self.bank_select & 0x80) == 0 {
-            self.map_character_bank(0, usize::from(self.bank_data[0] & !0x01));
-            self.map_character_bank(1, usize::from(self.bank_data[0] | 0x01));
-            self.map_character_bank(2, usize::from(self.bank_data[1] & !0x01));
-            self.map_character_bank(3, usize::from(self.bank_data[1] | 0x01));
-            self.map_character_bank(4, usize::from(self.bank_data[2]));
-            self.map_character_bank(5, usize::from(self.bank_data[3]));
-            self.map_character_bank(6, usize::from(self.bank_data[4]));
-            self.map_character_bank(7, usize::from(self.bank_data[5]));
-        } else {
-            self.map_character_bank(0, usize::from(self.bank_data[2]));
-            self.map_character_bank(1, usize::from(self.bank_data[3]));
-            self.map_character_bank(2, usize::from(self.bank_data[4]));
-            self.map_character_bank(3, usize::from(self.bank_data[5]));
-            self.map_character_bank(4, usize::from(self.bank_data[0] & !0x01));
-            self.map_character_bank(5, usize::from(self.bank_data[0] | 0x01));
-            self.map_character_bank(6, usize::from(self.bank_data[1] & !0x01));
-            self.map_character_bank(7, usize::from(self.bank_data[1] | 0x01));
+        let character_banks = character_banks(&self.bank_data, self.bank_select & 0x80 != 0);
+        for (slot, bank) in character_banks.into_iter().enumerate() {
+            self.map_character_bank(slot, bank);
         }
 
         match self.config.prg_ram_model {
@@ -659,6 +659,14 @@ impl Mmc5 {
         }
     }
 
+    /// NSF の拡張音源として使う。ExRAM は常に CPU から読み書きできる。
+    pub(crate) fn new_for_nsf(data: CartridgeData) -> Self {
+        Self {
+            exram_mode: 0x02,
+            ..Self::new(data)
+        }
+    }
+
     fn sl3_pin_level(&self) -> bool {
         if self.mmc5a_sl3_write_strobe {
             !self.mmc5a_sl3_strobe_low
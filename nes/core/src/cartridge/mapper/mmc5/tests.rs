@@ -24,6 +24,7 @@ fn test_data() -> CartridgeData {
         has_battery: false,
         sub_mapper_type: 0,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("test cartridge data should be valid")
 }
@@ -42,6 +43,7 @@ fn split_test_data() -> CartridgeData {
         has_battery: false,
         sub_mapper_type: 0,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("split test cartridge data should be valid")
 }
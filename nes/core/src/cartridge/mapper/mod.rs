@@ -1,34 +1,56 @@
 mod action53;
 mod axrom;
+mod bandai_fcg;
 mod bnrom;
 mod cnrom;
 mod color_dreams;
 mod crazy_climber;
+mod fds;
 mod fme7;
 mod gnrom;
 mod mapper78;
 mod mmc2;
 mod mmc3;
 mod mmc5;
+mod multicart;
+mod namco108;
+mod namco163;
 mod nina001;
 mod nrom;
+mod nsf;
+mod rambo1;
+mod sunsoft4;
 mod sxrom;
+mod unrom512;
 mod uxrom;
+mod vrc4;
+mod vrc6;
+mod vrc7;
+mod vrc_irq;
 
 use self::{
-    action53::Action53, axrom::AxRom, bnrom::BNRom, cnrom::CNRom, color_dreams::ColorDreams,
-    crazy_climber::CrazyClimber, fme7::Fme7, gnrom::GnRom, mapper78::Mapper78, mmc2::Mmc2,
-    mmc5::Mmc5, nina001::Nina001, nrom::NRom, sxrom::SxRom, uxrom::UxRom,
+    action53::Action53, axrom::AxRom, bandai_fcg::BandaiFcg, bnrom::BNRom, cnrom::CNRom,
+    color_dreams::ColorDreams, crazy_climber::CrazyClimber, fds::Fds, fme7::Fme7, gnrom::GnRom,
+    mapper78::Mapper78, mmc2::Mmc2, mmc5::Mmc5, multicart::Multicart, namco108::Namco108,
+    namco163::Namco163, nina001::Nina001, nrom::NRom, nsf::Nsf, rambo1::Rambo1, sunsoft4::Sunsoft4,
+    sxrom::SxRom, unrom512::Unrom512, uxrom::UxRom, vrc4::Vrc4, vrc6::Vrc6, vrc7::Vrc7,
 };
 use crate::{
     cart_device::Cartridge, cartridge_error::CartridgeError, cartridge_rom::CartridgeData,
-    core_options::Mmc3IrqVariant,
+    core_options::Mmc3IrqVariant, rom_format::RomFormat,
 };
 
 pub(crate) fn try_from(
     data: CartridgeData,
     mmc3_irq_variant: Option<Mmc3IrqVariant>,
 ) -> Result<Box<dyn Cartridge>, CartridgeError> {
+    if data.format() == RomFormat::Fds {
+        return Ok(Box::new(Fds::new(data)));
+    }
+    if data.format() == RomFormat::Nsf {
+        let info = data.nsf().cloned().ok_or(CartridgeError::DataError)?;
+        return Ok(Box::new(Nsf::new(data, info)));
+    }
     match data.mapper_type() {
         0 => Ok(Box::new(NRom::new(data))),
         1 => Ok(Box::new(SxRom::new(data))),
@@ -40,12 +62,39 @@ pub(crate) fn try_from(
         9 => Ok(Box::new(Mmc2::new_mapper9(data))),
         10 => Ok(Box::new(Mmc2::new_mapper10(data))),
         11 => Ok(Box::new(ColorDreams::new(data))),
+        15 => Ok(Box::new(Multicart::new_mapper15(data))),
+        16 => Ok(Box::new(BandaiFcg::new_mapper16(data))),
+        19 => Ok(Box::new(Namco163::new_mapper19(data))),
+        21..=23 | 25 => Ok(Box::new(Vrc4::new(data))),
+        24 => Ok(Box::new(Vrc6::new_mapper24(data))),
+        26 => Ok(Box::new(Vrc6::new_mapper26(data))),
         28 => Ok(Box::new(Action53::new(data))),
+        30 => Ok(Box::new(Unrom512::new(data))),
+        41 => Ok(Box::new(Multicart::new_mapper41(data))),
+        57 => Ok(Box::new(Multicart::new_mapper57(data))),
+        58 => Ok(Box::new(Multicart::new_mapper58(data))),
+        64 => Ok(Box::new(Rambo1::new_mapper64(data))),
         66 => Ok(Box::new(GnRom::new(data))),
+        68 => Ok(Box::new(Sunsoft4::new(data))),
         69 => Ok(Box::new(Fme7::new(data))),
+        76 => Ok(Box::new(Namco108::new_mapper76(data))),
         78 => Ok(Box::new(Mapper78::new(data))),
+        85 => Ok(Box::new(Vrc7::new(data))),
+        88 => Ok(Box::new(Namco108::new_mapper88(data))),
+        95 => Ok(Box::new(Namco108::new_mapper95(data))),
         118 => mmc3::try_from_txsrom(data),
+        153 => Ok(Box::new(BandaiFcg::new_mapper153(data))),
+        154 => Ok(Box::new(Namco108::new_mapper154(data))),
+        158 => Ok(Box::new(Rambo1::new_mapper158(data))),
+        159 => Ok(Box::new(BandaiFcg::new_mapper159(data))),
         180 => Ok(Box::new(CrazyClimber::new(data))),
+        206 => Ok(Box::new(Namco108::new_mapper206(data))),
+        210 => Ok(Box::new(Namco163::new_mapper210(data))),
+        225 => Ok(Box::new(Multicart::new_mapper225(data))),
+        226 => Ok(Box::new(Multicart::new_mapper226(data))),
+        227 => Ok(Box::new(Multicart::new_mapper227(data))),
+        228 => Ok(Box::new(Multicart::new_mapper228(data))),
+        232 => Ok(Box::new(Multicart::new_mapper232(data))),
         34 => match data.sub_mapper_type() {
             0 => {
                 if data.char_rom_len() > 0 {
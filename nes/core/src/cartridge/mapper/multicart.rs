@@ -0,0 +1,459 @@
+use nerust_input_traits::OpenBusReadResult;
+
+use super::Cartridge;
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_MULTICART},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao, MappingMode},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum Board {
+    /// Mapper 15。データの下位 6bit が 16KiB バンク、A0-A1 が PRG モード。
+    K1029,
+    /// Mapper 41。$6000-$67FF のアドレスが外側、$8000-$FFFF のデータが内側の CHR バンク。
+    Caltron6In1,
+    /// Mapper 57。$8000 と $8800 の 2 本のレジスタ。リセットでメニューの DIP スイッチが進む。
+    Gk6In1,
+    /// Mapper 58。アドレスだけをラッチする。
+    Gk192,
+    /// Mapper 225。アドレスをラッチし、$5800-$5FFF に 4bit × 4 の RAM を持つ。
+    Et4310,
+    /// Mapper 226。$8000 と $8001 の 2 本のレジスタ。
+    Multi76In1,
+    /// Mapper 227。アドレスをラッチし、UNROM 風と NROM 風のモードを持つ。
+    Multi1200In1,
+    /// Mapper 228。アドレスとデータをラッチし、$4020-$5FFF に 4bit × 4 の RAM を持つ。
+    Action52,
+    /// Mapper 232。$8000-$BFFF が外側の 64KiB ブロック、$C000-$FFFF が内側の 16KiB バンク。
+    Bf9096,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct MulticartRuntimeState {
+    outer: usize,
+    inner: u8,
+    menu: u8,
+    nibble_ram: [u8; 4],
+}
+
+/// ディスクリート部品で組まれた多本入りカートリッジ (Mapper 15, 41, 57, 58, 225-228, 232)。
+/// 書き込みのアドレスやデータを外側 / 内側のラッチに覚え、そこからバンクを決める。
+/// 多くの基板はリセットでラッチが消えてメニューに戻る。
+/// メニューの切り替えは DIP スイッチ (57) か、リセットで消えない 4bit RAM (225, 228) を使う。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Multicart {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    board: Board,
+    /// 外側のラッチ。アドレスをラッチする基板では書き込みアドレスそのもの。
+    outer: usize,
+    /// 内側のラッチ。アドレスをラッチする基板では最後に書いたデータ。
+    inner: u8,
+    /// Mapper 57 のメニュー選択 DIP スイッチ。
+    menu: u8,
+    nibble_ram: [u8; 4],
+}
+
+#[typetag::serde]
+impl Cartridge for Multicart {
+    fn read_program(&self, address: usize) -> OpenBusReadResult {
+        if self.board == Board::Action52 && (self.outer >> 11) & 0x03 == 2 {
+            return OpenBusReadResult::new(0, 0);
+        }
+        OpenBusReadResult::new(
+            self.program_address(address)
+                .map(|x| self.data_ref().read_prog_rom(x))
+                .unwrap_or(0),
+            0xFF,
+        )
+    }
+
+    fn read_ram(&self, address: usize) -> OpenBusReadResult {
+        if self.board == Board::Gk6In1 {
+            return OpenBusReadResult::new(self.menu, 0x03);
+        }
+        Mapper::read_ram(self, address).map_or_else(
+            || OpenBusReadResult::new(0, 0),
+            |x| OpenBusReadResult::new(x, 0xFF),
+        )
+    }
+
+    fn write_ram(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        if self.board == Board::Caltron6In1 {
+            if address < 0x6800 {
+                self.outer = address & 0x3F;
+                self.update_banks();
+            }
+        } else {
+            Mapper::write_ram(self, address - 0x6000, value);
+        }
+    }
+
+    fn write_character(&mut self, address: usize, value: u8) {
+        // K-1029 の NROM モードでは CHR-RAM が書き込み禁止になる
+        if self.board == Board::K1029 && matches!(self.outer & 0x03, 0 | 3) {
+            return;
+        }
+        if self.mapper_state_ref().character_mapping_mode == MappingMode::Ram
+            && let Some(addr) = self.character_address(address)
+        {
+            self.mapper_state_mut().vram[addr] = value;
+        }
+    }
+
+    fn soft_reset(&mut self) {
+        match self.board {
+            // リセットはマッパーに配線されておらず、遊んでいるゲームの先頭に戻る
+            Board::Bf9096 => return,
+            Board::Gk6In1 => self.menu = (self.menu + 1) & 0x03,
+            _ => {}
+        }
+        self.outer = 0;
+        self.inner = 0;
+        self.update_banks();
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_MULTICART.into(),
+            extra_body: encode_payload(&MulticartRuntimeState {
+                outer: self.outer,
+                inner: self.inner,
+                menu: self.menu,
+                nibble_ram: self.nibble_ram,
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_MULTICART {
+            return Err(PersistenceError::Validation(
+                "unexpected multicart runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: MulticartRuntimeState = decode_payload(&state.extra_body)?;
+        self.state = state.mapper_state;
+        self.outer = runtime.outer;
+        self.inner = runtime.inner;
+        self.menu = runtime.menu;
+        self.nibble_ram = runtime.nibble_ram;
+        Ok(())
+    }
+}
+
+impl Multicart {
+    pub(crate) fn new_mapper15(data: CartridgeData) -> Self {
+        Self::new(data, Board::K1029)
+    }
+
+    pub(crate) fn new_mapper41(data: CartridgeData) -> Self {
+        Self::new(data, Board::Caltron6In1)
+    }
+
+    pub(crate) fn new_mapper57(data: CartridgeData) -> Self {
+        Self::new(data, Board::Gk6In1)
+    }
+
+    pub(crate) fn new_mapper58(data: CartridgeData) -> Self {
+        Self::new(data, Board::Gk192)
+    }
+
+    pub(crate) fn new_mapper225(data: CartridgeData) -> Self {
+        Self::new(data, Board::Et4310)
+    }
+
+    pub(crate) fn new_mapper226(data: CartridgeData) -> Self {
+        Self::new(data, Board::Multi76In1)
+    }
+
+    pub(crate) fn new_mapper227(data: CartridgeData) -> Self {
+        Self::new(data, Board::Multi1200In1)
+    }
+
+    pub(crate) fn new_mapper228(data: CartridgeData) -> Self {
+        Self::new(data, Board::Action52)
+    }
+
+    pub(crate) fn new_mapper232(data: CartridgeData) -> Self {
+        Self::new(data, Board::Bf9096)
+    }
+
+    fn new(data: CartridgeData, board: Board) -> Self {
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            board,
+            outer: 0,
+            inner: 0,
+            menu: 0,
+            nibble_ram: [0; 4],
+        }
+    }
+
+    fn nibble_ram_addr(&self, address: usize) -> bool {
+        match self.board {
+            Board::Et4310 => address >= 0x5800,
+            Board::Action52 => true,
+            _ => false,
+        }
+    }
+
+    fn select_prg_16k(&mut self, slot: usize, bank: usize) {
+        self.change_program_page(slot * 2, bank * 2);
+        self.change_program_page(slot * 2 + 1, bank * 2 + 1);
+    }
+
+    fn select_prg_32k(&mut self, bank: usize) {
+        self.select_prg_16k(0, bank * 2);
+        self.select_prg_16k(1, bank * 2 + 1);
+    }
+
+    /// 16KiB バンクを $8000 と $C000 の両方に置くか、偶数に揃えて 32KiB として置く。
+    fn select_prg_nrom(&mut self, bank: usize, nrom128: bool) {
+        if nrom128 {
+            self.select_prg_16k(0, bank);
+            self.select_prg_16k(1, bank);
+        } else {
+            self.select_prg_32k(bank >> 1);
+        }
+    }
+
+    fn set_mirroring(&mut self, horizontal: bool) {
+        self.set_mirror_mode(if horizontal {
+            MirrorMode::Horizontal
+        } else {
+            MirrorMode::Vertical
+        });
+    }
+
+    fn update_banks(&mut self) {
+        let outer = self.outer;
+        let inner = usize::from(self.inner);
+        match self.board {
+            Board::K1029 => {
+                let bank = inner & 0x3F;
+                match outer & 0x03 {
+                    0 => self.select_prg_32k(bank >> 1),
+                    1 => {
+                        self.select_prg_16k(0, bank);
+                        self.select_prg_16k(1, bank | 0x07);
+                    }
+                    2 => {
+                        // NROM-64。データの bit7 が PRG A13 になる
+                        let page = (bank << 1) | (inner >> 7);
+                        for slot in 0..4 {
+                            self.change_program_page(slot, page);
+                        }
+                    }
+                    _ => self.select_prg_nrom(bank, true),
+                }
+                self.change_character_page(0, 0);
+                self.set_mirroring(inner & 0x40 != 0);
+            }
+            Board::Caltron6In1 => {
+                self.select_prg_32k(outer & 0x07);
+                self.change_character_page(0, ((outer >> 1) & 0x0C) | (inner & 0x03));
+                self.set_mirroring(outer & 0x20 != 0);
+            }
+            Board::Gk6In1 => {
+                self.select_prg_nrom((outer >> 5) & 0x07, outer & 0x10 == 0);
+                self.change_character_page(0, ((inner & 0x40) >> 3) | ((inner | outer) & 0x07));
+                self.set_mirroring(outer & 0x08 != 0);
+            }
+            Board::Gk192 => {
+                self.select_prg_nrom(outer & 0x07, outer & 0x40 != 0);
+                self.change_character_page(0, (outer >> 3) & 0x07);
+                self.set_mirroring(outer & 0x80 != 0);
+            }
+            Board::Et4310 => {
+                // A14 が PRG / CHR 共通の最上位ビットになる
+                let high = (outer >> 8) & 0x40;
+                self.select_prg_nrom(((outer >> 6) & 0x3F) | high, outer & 0x1000 != 0);
+                self.change_character_page(0, (outer & 0x3F) | high);
+                self.set_mirroring(outer & 0x2000 != 0);
+            }
+            Board::Multi76In1 => {
+                let bank = (inner & 0x1F) | ((inner & 0x80) >> 2) | ((outer & 0x01) << 6);
+                self.select_prg_nrom(bank, inner & 0x20 != 0);
+                self.change_character_page(0, 0);
+                self.set_mirroring(inner & 0x40 == 0);
+            }
+            Board::Multi1200In1 => {
+                let bank = ((outer >> 2) & 0x1F) | ((outer & 0x100) >> 3);
+                let last = outer & 0x200 != 0;
+                if outer & 0x80 != 0 {
+                    self.select_prg_nrom(bank, outer & 0x01 == 0);
+                } else {
+                    // UNROM 風。$C000 は 128KiB ブロックの先頭か末尾に固定される
+                    let first = if outer & 0x01 != 0 { bank & 0x3E } else { bank };
+                    self.select_prg_16k(0, first);
+                    self.select_prg_16k(1, if last { bank | 0x07 } else { bank & 0x38 });
+                }
+                self.change_character_page(0, 0);
+                self.set_mirroring(outer & 0x02 != 0);
+            }
+            Board::Action52 => {
+                // チップ 2 は実装されていない (read_program でオープンバスにする)。
+                // ROM 上の 3 つ目の 512KiB はチップ 3 に載っている
+                let chip = match (outer >> 11) & 0x03 {
+                    3 => 2,
+                    chip => chip,
+                };
+                self.select_prg_nrom(((outer >> 6) & 0x1F) | (chip << 5), outer & 0x20 != 0);
+                self.change_character_page(0, ((outer & 0x0F) << 2) | (inner & 0x03));
+                self.set_mirroring(outer & 0x2000 != 0);
+            }
+            Board::Bf9096 => {
+                let block = if self.data_ref().sub_mapper_type() == 1 {
+                    // Aladdin Deck Enhancer は bit3 / bit4 が入れ替わっている
+                    ((outer >> 4) & 0x01) | ((outer >> 2) & 0x02)
+                } else {
+                    (outer >> 3) & 0x03
+                };
+                self.select_prg_16k(0, (block << 2) | (inner & 0x03));
+                self.select_prg_16k(1, (block << 2) | 0x03);
+                self.change_character_page(0, 0);
+            }
+        }
+    }
+}
+
+impl CartridgeDataDao for Multicart {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Multicart {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Multicart {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn initialize(&mut self) {
+        self.change_ram_page(0, 0);
+        self.update_banks();
+    }
+
+    fn name(&self) -> &str {
+        match self.board {
+            Board::K1029 => "K-1029 (Mapper15)",
+            Board::Caltron6In1 => "Caltron 6-in-1 (Mapper41)",
+            Board::Gk6In1 => "GK 6-in-1 (Mapper57)",
+            Board::Gk192 => "GK-192 (Mapper58)",
+            Board::Et4310 => "ET-4310 (Mapper225)",
+            Board::Multi76In1 => "76-in-1 (Mapper226)",
+            Board::Multi1200In1 => "1200-in-1 (Mapper227)",
+            Board::Action52 => "Action 52 (Mapper228)",
+            Board::Bf9096 => "Camerica BF9096 (Mapper232)",
+        }
+    }
+
+    fn ram_len_default(&self) -> usize {
+        if self.board == Board::K1029 {
+            0x2000
+        } else {
+            0
+        }
+    }
+
+    fn bus_conflicts(&self) -> bool {
+        self.board == Board::Caltron6In1
+    }
+
+    fn read_expansion(&self, address: usize) -> OpenBusReadResult {
+        if self.nibble_ram_addr(address) {
+            OpenBusReadResult::new(self.nibble_ram[address & 0x03], 0x0F)
+        } else {
+            OpenBusReadResult::new(0, 0)
+        }
+    }
+
+    fn write_expansion(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        if self.nibble_ram_addr(address) {
+            self.nibble_ram[address & 0x03] = value & 0x0F;
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        match self.board {
+            Board::Caltron6In1 => {
+                // 内側の CHR レジスタは外側の bit2 が立っている間だけ書ける
+                if self.outer & 0x04 != 0 {
+                    self.inner = value & 0x03;
+                }
+            }
+            Board::Gk6In1 => {
+                if address & 0x0800 == 0 {
+                    self.inner = value;
+                } else {
+                    self.outer = usize::from(value);
+                }
+            }
+            Board::Multi76In1 => {
+                if address & 0x01 == 0 {
+                    self.inner = value;
+                } else {
+                    self.outer = usize::from(value);
+                }
+            }
+            Board::Bf9096 => {
+                if address < 0xC000 {
+                    self.outer = usize::from(value);
+                } else {
+                    self.inner = value;
+                }
+            }
+            _ => {
+                self.outer = address;
+                self.inner = value;
+            }
+        }
+        self.update_banks();
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,225 @@
+use super::{Cartridge, Multicart};
+use crate::{
+    cartridge_data_parts::CartridgeDataParts, cartridge_rom::CartridgeData, interrupt::Interrupt,
+    mapper::Mapper, mirror::MirrorMode, rom_format::RomFormat,
+};
+
+/// PRG は 16KiB、CHR は 8KiB ごとにバンク番号で埋める。CHR の長さが 0 なら CHR-RAM。
+fn test_data(
+    mapper_type: u16,
+    sub_mapper_type: u8,
+    prg_len: usize,
+    chr_len: usize,
+) -> CartridgeData {
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::INes,
+        prog_rom: (0..prg_len).map(|i| (i / 0x4000) as u8).collect(),
+        char_rom: (0..chr_len).map(|i| (i / 0x2000) as u8).collect(),
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0,
+        save_vram_length: 0,
+        mapper_type,
+        mirror_mode: MirrorMode::Vertical,
+        has_battery: false,
+        sub_mapper_type,
+        trainer: Vec::new(),
+        console_type: None,
+    })
+    .expect("test cartridge data should be valid")
+}
+
+fn initialized(mut mapper: Multicart) -> Multicart {
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+fn prg_banks(mapper: &Multicart) -> [u8; 2] {
+    [0x8000, 0xC000].map(|address| Cartridge::read(mapper, address).data)
+}
+
+fn chr_bank(mapper: &Multicart) -> u8 {
+    Cartridge::read(mapper, 0x0000).data
+}
+
+#[test]
+fn k1029_modes_select_prg_layout_and_protect_chr_ram() {
+    let mut mapper = initialized(Multicart::new_mapper15(test_data(15, 0, 0x100000, 0)));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x8000, 0x05, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x04, 0x05]);
+    Cartridge::write(&mut mapper, 0x8001, 0x49, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x09, 0x0F]);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+    Cartridge::write(&mut mapper, 0x8003, 0x09, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x09, 0x09]);
+
+    // NROM-64 では bit7 が 8KiB の半分を選ぶ
+    Cartridge::write(&mut mapper, 0x8002, 0x83, &mut interrupt);
+    assert_eq!(
+        [0x8000, 0xA000, 0xC000, 0xE000].map(|address| mapper.program_address(address - 0x8000)),
+        [Some(0x3 * 0x4000 + 0x2000); 4]
+    );
+
+    Cartridge::write(&mut mapper, 0x0000, 0x12, &mut interrupt);
+    assert_eq!(chr_bank(&mapper), 0x12);
+    Cartridge::write(&mut mapper, 0x8003, 0x00, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x0000, 0x34, &mut interrupt);
+    assert_eq!(chr_bank(&mapper), 0x12);
+}
+
+#[test]
+fn caltron_inner_chr_register_needs_outer_bit2() {
+    let mut mapper = initialized(Multicart::new_mapper41(test_data(41, 0, 0x40000, 0x20000)));
+    let mut interrupt = Interrupt::new();
+    // 外側の bit2 が立っていない間は内側への書き込みを無視する
+    Cartridge::write(&mut mapper, 0x8000, 0x03, &mut interrupt);
+    assert_eq!(chr_bank(&mapper), 0x00);
+
+    Cartridge::write(&mut mapper, 0x603D, 0x00, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x0A, 0x0B]);
+    assert_eq!(chr_bank(&mapper), 0x0C);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+
+    // 内側の書き込みは ROM とのバス競合を受ける
+    Cartridge::write(&mut mapper, 0x8000, 0xFF, &mut interrupt);
+    assert_eq!(chr_bank(&mapper), 0x0E);
+}
+
+#[test]
+fn gk_6in1_menu_dip_switch_advances_on_soft_reset() {
+    let mut mapper = initialized(Multicart::new_mapper57(test_data(57, 0, 0x20000, 0x20000)));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x8800, 0xD9, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x8000, 0x42, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x06, 0x07]);
+    assert_eq!(chr_bank(&mapper), 0x0B);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x00);
+
+    mapper.soft_reset();
+    assert_eq!(prg_banks(&mapper), [0x00, 0x00]);
+    assert_eq!(chr_bank(&mapper), 0x00);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x01);
+}
+
+#[test]
+fn gk192_latches_the_write_address() {
+    let mut mapper = initialized(Multicart::new_mapper58(test_data(58, 0, 0x20000, 0x10000)));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x80DD, 0x00, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x05, 0x05]);
+    assert_eq!(chr_bank(&mapper), 0x03);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+
+    Cartridge::write(&mut mapper, 0x8005, 0x00, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x04, 0x05]);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Vertical);
+}
+
+#[test]
+fn et4310_high_bit_and_nibble_ram_survive_reset() {
+    let mut mapper = initialized(Multicart::new_mapper225(test_data(
+        225, 0, 0x200000, 0x100000,
+    )));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xF0C5, 0x00, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x43, 0x43]);
+    assert_eq!(chr_bank(&mapper), 0x45);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+
+    Cartridge::write(&mut mapper, 0x5801, 0xA7, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x5801).data, 0x07);
+    assert_eq!(Cartridge::read(&mapper, 0x5801).mask, 0x0F);
+    assert_eq!(Cartridge::read(&mapper, 0x5000).mask, 0x00);
+
+    mapper.soft_reset();
+    assert_eq!(prg_banks(&mapper), [0x00, 0x01]);
+    assert_eq!(Cartridge::read(&mapper, 0x5805).data, 0x07);
+}
+
+#[test]
+fn multi_76in1_combines_both_registers() {
+    let mut mapper = initialized(Multicart::new_mapper226(test_data(226, 0, 0x200000, 0)));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x8000, 0xA3, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x8001, 0x01, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x63, 0x63]);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+
+    Cartridge::write(&mut mapper, 0x8000, 0x43, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x42, 0x43]);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Vertical);
+}
+
+#[test]
+fn multi_1200in1_fixes_the_last_bank_in_unrom_mode() {
+    let mut mapper = initialized(Multicart::new_mapper227(test_data(227, 0, 0x80000, 0)));
+    let mut interrupt = Interrupt::new();
+    // L = 1: $C000 はブロックの末尾
+    Cartridge::write(&mut mapper, 0x8228, 0x00, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x0A, 0x0F]);
+    // L = 0: $C000 はブロックの先頭
+    Cartridge::write(&mut mapper, 0x8028, 0x00, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x0A, 0x08]);
+    // NROM-256
+    Cartridge::write(&mut mapper, 0x80AB, 0x00, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x0A, 0x0B]);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+}
+
+#[test]
+fn action52_maps_chip_three_to_the_third_rom_chip() {
+    let mut mapper = initialized(Multicart::new_mapper228(test_data(
+        228, 0, 0x180000, 0x80000,
+    )));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xB8E5, 0x02, &mut interrupt);
+    assert_eq!(prg_banks(&mapper), [0x43, 0x43]);
+    assert_eq!(chr_bank(&mapper), 0x16);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+
+    // チップ 2 は実装されておらずオープンバスになる
+    Cartridge::write(&mut mapper, 0x9000, 0x00, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).mask, 0);
+    assert_eq!(Cartridge::read(&mapper, 0xC000).mask, 0);
+
+    mapper.soft_reset();
+    assert_eq!(prg_banks(&mapper), [0x00, 0x01]);
+    assert_eq!(chr_bank(&mapper), 0x00);
+}
+
+#[test]
+fn bf9096_outer_block_and_aladdin_bit_swap() {
+    for (sub_mapper_type, outer, block) in [(0, 0x08, 1), (1, 0x08, 2), (1, 0x10, 1)] {
+        let mut mapper = initialized(Multicart::new_mapper232(test_data(
+            232,
+            sub_mapper_type,
+            0x40000,
+            0,
+        )));
+        let mut interrupt = Interrupt::new();
+        Cartridge::write(&mut mapper, 0x8000, outer, &mut interrupt);
+        Cartridge::write(&mut mapper, 0xC000, 0x02, &mut interrupt);
+        assert_eq!(prg_banks(&mapper), [block * 4 + 2, block * 4 + 3]);
+
+        // リセットしてもバンクは変わらない
+        mapper.soft_reset();
+        assert_eq!(prg_banks(&mapper), [block * 4 + 2, block * 4 + 3]);
+    }
+}
+
+#[test]
+fn latches_round_trip_through_runtime_state() {
+    let mut mapper = initialized(Multicart::new_mapper57(test_data(57, 0, 0x20000, 0x20000)));
+    let mut interrupt = Interrupt::new();
+    mapper.soft_reset();
+    Cartridge::write(&mut mapper, 0x8800, 0x5C, &mut interrupt);
+
+    let state = mapper.export_runtime_state().unwrap();
+    let mut restored = initialized(Multicart::new_mapper57(test_data(57, 0, 0x20000, 0x20000)));
+    restored.import_runtime_state(state).unwrap();
+    assert_eq!(prg_banks(&restored), [0x02, 0x03]);
+    assert_eq!(chr_bank(&restored), 0x04);
+    assert_eq!(Cartridge::read(&restored, 0x6000).data, 0x01);
+}
@@ -0,0 +1,250 @@
+use super::{
+    Cartridge,
+    mmc3::{character_banks, program_banks},
+};
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_NAMCO108},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+/// Mapper 88 / 154 で PPU $1000-$1FFF 側の 1KiB バンクに付く CHR A16。
+const CHR_HIGH_HALF: u8 = 0x40;
+/// Mapper 95 で CHR レジスタ 0 / 1 の bit5 が CIRAM A10 になる。
+const NAMETABLE_SELECT: u8 = 0x20;
+/// Mapper 154 で $8000-$FFFF への書き込みの bit6 が 1 画面ミラーリングを選ぶ。
+const ONE_SCREEN_SELECT: u8 = 0x40;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum Board {
+    /// Namco 108 そのまま (Mapper 206)。
+    Namco108,
+    /// NAMCOT-3446 (Mapper 76)。CHR レジスタ 2-5 が 2KiB 単位になる。
+    Namcot3446,
+    /// NAMCOT-3433 (Mapper 88)。CHR の前半 4KiB と後半 4KiB で CHR A16 が固定される。
+    Namcot3433,
+    /// NAMCOT-3425 (Mapper 95)。CHR レジスタ 0 / 1 の bit5 でネームテーブルを選ぶ。
+    Namcot3425,
+    /// NAMCOT-3453 (Mapper 154)。Mapper 88 に 1 画面ミラーリングの切り替えが付く。
+    Namcot3453,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Namco108RuntimeState {
+    bank_select: u8,
+    bank_data: [u8; 8],
+}
+
+/// Namco 108 / DxROM 系 (Mapper 76, 88, 95, 154, 206)。
+/// MMC3 の前身で、$8000 / $8001 のバンク選択とバンクデータの組だけを持つ。
+/// PRG と CHR の配置は MMC3 のモード 0 に固定で、IRQ と PRG-RAM は無い。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Namco108 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    board: Board,
+    bank_select: u8,
+    bank_data: [u8; 8],
+}
+
+#[typetag::serde]
+impl Cartridge for Namco108 {
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_NAMCO108.into(),
+            extra_body: encode_payload(&Namco108RuntimeState {
+                bank_select: self.bank_select,
+                bank_data: self.bank_data,
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_NAMCO108 {
+            return Err(PersistenceError::Validation(
+                "unexpected Namco 108 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Namco108RuntimeState = decode_payload(&state.extra_body)?;
+        self.state = state.mapper_state;
+        self.bank_select = runtime.bank_select;
+        self.bank_data = runtime.bank_data;
+        Ok(())
+    }
+}
+
+impl Namco108 {
+    pub(crate) fn new_mapper76(data: CartridgeData) -> Self {
+        Self::new(data, Board::Namcot3446)
+    }
+
+    pub(crate) fn new_mapper88(data: CartridgeData) -> Self {
+        Self::new(data, Board::Namcot3433)
+    }
+
+    pub(crate) fn new_mapper95(data: CartridgeData) -> Self {
+        Self::new(data, Board::Namcot3425)
+    }
+
+    pub(crate) fn new_mapper154(data: CartridgeData) -> Self {
+        Self::new(data, Board::Namcot3453)
+    }
+
+    pub(crate) fn new_mapper206(data: CartridgeData) -> Self {
+        Self::new(data, Board::Namco108)
+    }
+
+    fn new(data: CartridgeData, board: Board) -> Self {
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            board,
+            bank_select: 0,
+            bank_data: [0, 0, 0, 0, 0, 0, 0, 1],
+        }
+    }
+
+    /// CHR レジスタの値を基板ごとの配線に合わせて 1KiB バンク番号にする。
+    fn character_bank(&self, register: usize) -> u8 {
+        let value = self.bank_data[register];
+        match self.board {
+            Board::Namco108 | Board::Namcot3446 => value & 0x3F,
+            Board::Namcot3425 => value & 0x1F,
+            Board::Namcot3433 | Board::Namcot3453 if register <= 1 => value & 0x3F,
+            Board::Namcot3433 | Board::Namcot3453 => (value & 0x3F) | CHR_HIGH_HALF,
+        }
+    }
+
+    fn update_banks(&mut self) {
+        // MMC3 のモード 0 と同じ配置に、基板ごとの配線を通したレジスタ値を流す
+        let mut bank_data = self.bank_data;
+        for (register, value) in bank_data.iter_mut().enumerate().take(6) {
+            *value = self.character_bank(register);
+        }
+        bank_data[6] &= 0x0F;
+        bank_data[7] &= 0x0F;
+
+        let bank_count = self.data_ref().prog_rom_len() / self.program_page_len();
+        for (slot, bank) in program_banks(&bank_data, false, bank_count)
+            .into_iter()
+            .enumerate()
+        {
+            self.change_program_page(slot, bank);
+        }
+
+        if self.board == Board::Namcot3446 {
+            // CHR レジスタ 2-5 がそれぞれ 2KiB を受け持つ
+            for slot in 0..4 {
+                let bank = usize::from(bank_data[slot + 2]) * 2;
+                self.change_character_page(slot * 2, bank);
+                self.change_character_page(slot * 2 + 1, bank + 1);
+            }
+        } else {
+            for (slot, bank) in character_banks(&bank_data, false).into_iter().enumerate() {
+                self.change_character_page(slot, bank);
+            }
+        }
+
+        if self.board == Board::Namcot3425 {
+            // $2000-$27FF は CHR レジスタ 0、$2800-$2FFF は CHR レジスタ 1 の bit5 で決まる
+            let nametable = |register: usize| (self.bank_data[register] & NAMETABLE_SELECT) >> 5;
+            self.set_mirror_mode(MirrorMode::Custom([
+                nametable(0),
+                nametable(0),
+                nametable(1),
+                nametable(1),
+            ]));
+        }
+    }
+}
+
+impl CartridgeDataDao for Namco108 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Namco108 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Namco108 {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0400
+    }
+
+    fn initialize(&mut self) {
+        self.update_banks();
+    }
+
+    fn name(&self) -> &str {
+        match self.board {
+            Board::Namco108 => "Namco 108 (Mapper206)",
+            Board::Namcot3446 => "NAMCOT-3446 (Mapper76)",
+            Board::Namcot3433 => "NAMCOT-3433 (Mapper88)",
+            Board::Namcot3425 => "NAMCOT-3425 (Mapper95)",
+            Board::Namcot3453 => "NAMCOT-3453 (Mapper154)",
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        if self.board == Board::Namcot3453 {
+            // 1 画面ミラーリングは $8000-$FFFF のどこに書いても切り替わる
+            self.set_mirror_mode(if value & ONE_SCREEN_SELECT != 0 {
+                MirrorMode::Single1
+            } else {
+                MirrorMode::Single0
+            });
+        }
+        // MMC3 と違い $8000-$9FFF の 2 本だけで、モードビットや $A000 以降のレジスタは無い
+        match address & 0xE001 {
+            0x8000 => self.bank_select = value & 0x07,
+            0x8001 => {
+                self.bank_data[usize::from(self.bank_select)] = value;
+                self.update_banks();
+            }
+            _ => {}
+        }
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,146 @@
+use super::{Cartridge, Namco108};
+use crate::{
+    cartridge_data_parts::CartridgeDataParts, cartridge_rom::CartridgeData, interrupt::Interrupt,
+    mirror::MirrorMode, rom_format::RomFormat,
+};
+
+/// PRG は 8KiB、CHR は 1KiB ごとにバンク番号で埋める。
+fn test_data(mapper_type: u16, char_len: usize) -> CartridgeData {
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::INes,
+        prog_rom: (0..0x20000).map(|i| (i / 0x2000) as u8).collect(),
+        char_rom: (0..char_len).map(|i| (i / 0x0400) as u8).collect(),
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0,
+        save_vram_length: 0,
+        mapper_type,
+        mirror_mode: MirrorMode::Vertical,
+        has_battery: false,
+        sub_mapper_type: 0,
+        trainer: Vec::new(),
+        console_type: None,
+    })
+    .expect("test cartridge data should be valid")
+}
+
+fn initialized(mut mapper: Namco108) -> Namco108 {
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+fn write_bank(mapper: &mut Namco108, register: u8, value: u8) {
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(mapper, 0x8000, register, &mut interrupt);
+    Cartridge::write(mapper, 0x8001, value, &mut interrupt);
+}
+
+fn chr_banks(mapper: &Namco108) -> [u8; 8] {
+    std::array::from_fn(|slot| Cartridge::read(mapper, slot * 0x0400).data)
+}
+
+#[test]
+fn namco108_uses_fixed_mmc3_mode0_layout() {
+    let mut mapper = initialized(Namco108::new_mapper206(test_data(206, 0x10000)));
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x0E);
+    assert_eq!(Cartridge::read(&mapper, 0xE000).data, 0x0F);
+
+    write_bank(&mut mapper, 6, 0x03);
+    write_bank(&mut mapper, 7, 0x05);
+    write_bank(&mut mapper, 0, 0x09);
+    write_bank(&mut mapper, 1, 0x0C);
+    for (register, value) in (2..6).zip([0x20, 0x21, 0x22, 0x23]) {
+        write_bank(&mut mapper, register, value);
+    }
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x03);
+    assert_eq!(Cartridge::read(&mapper, 0xA000).data, 0x05);
+    assert_eq!(
+        chr_banks(&mapper),
+        [0x08, 0x09, 0x0C, 0x0D, 0x20, 0x21, 0x22, 0x23]
+    );
+
+    // MMC3 のモードビットと $A000 のミラーリングは無い
+    write_bank(&mut mapper, 0xC6, 0x01);
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xA000, 0x01, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x01);
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x0E);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Vertical);
+}
+
+#[test]
+fn namcot3446_maps_four_two_kib_chr_banks() {
+    let mut mapper = initialized(Namco108::new_mapper76(test_data(76, 0x20000)));
+    for (register, value) in (2..6).zip([0x01, 0x10, 0x3E, 0x07]) {
+        write_bank(&mut mapper, register, value);
+    }
+    write_bank(&mut mapper, 0, 0x30);
+    assert_eq!(
+        chr_banks(&mapper),
+        [0x02, 0x03, 0x20, 0x21, 0x7C, 0x7D, 0x0E, 0x0F]
+    );
+}
+
+#[test]
+fn namcot3433_splits_chr_a16_between_pattern_tables() {
+    let mut mapper = initialized(Namco108::new_mapper88(test_data(88, 0x20000)));
+    write_bank(&mut mapper, 0, 0x42);
+    write_bank(&mut mapper, 2, 0x05);
+    write_bank(&mut mapper, 5, 0x41);
+    let banks = chr_banks(&mapper);
+    assert_eq!(banks[0..2], [0x02, 0x03]);
+    assert_eq!(banks[4], 0x45);
+    assert_eq!(banks[7], 0x41);
+}
+
+#[test]
+fn namcot3433_ignores_chr_register_bits_above_bit5() {
+    let mut mapper = initialized(Namco108::new_mapper88(test_data(88, 0x40000)));
+    write_bank(&mut mapper, 0, 0x82);
+    write_bank(&mut mapper, 2, 0xC5);
+    let banks = chr_banks(&mapper);
+    assert_eq!(banks[0..2], [0x02, 0x03]);
+    assert_eq!(banks[4], 0x45);
+}
+
+#[test]
+fn namcot3425_selects_nametables_with_chr_bit5() {
+    let mut mapper = initialized(Namco108::new_mapper95(test_data(95, 0x8000)));
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Custom([0, 0, 0, 0]));
+
+    write_bank(&mut mapper, 0, 0x24);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Custom([1, 1, 0, 0]));
+    assert_eq!(chr_banks(&mapper)[0..2], [0x04, 0x05]);
+
+    write_bank(&mut mapper, 1, 0x20);
+    write_bank(&mut mapper, 0, 0x00);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Custom([0, 0, 1, 1]));
+}
+
+#[test]
+fn namcot3453_switches_one_screen_mirroring_on_any_write() {
+    let mut mapper = initialized(Namco108::new_mapper154(test_data(154, 0x20000)));
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xE000, 0x40, &mut interrupt);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Single1);
+
+    write_bank(&mut mapper, 2, 0x03);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Single0);
+    assert_eq!(chr_banks(&mapper)[4], 0x43);
+}
+
+#[test]
+fn bank_state_round_trips_through_runtime_state() {
+    let mut mapper = initialized(Namco108::new_mapper95(test_data(95, 0x8000)));
+    write_bank(&mut mapper, 6, 0x07);
+    write_bank(&mut mapper, 1, 0x22);
+    Cartridge::write(&mut mapper, 0x8000, 0x03, &mut Interrupt::new());
+
+    let state = mapper.export_runtime_state().unwrap();
+    let mut restored = initialized(Namco108::new_mapper95(test_data(95, 0x8000)));
+    restored.import_runtime_state(state).unwrap();
+    assert_eq!(Cartridge::read(&restored, 0x8000).data, 0x07);
+    assert_eq!(restored.mirror_mode(), MirrorMode::Custom([0, 0, 1, 1]));
+    Cartridge::write(&mut restored, 0x8001, 0x08, &mut Interrupt::new());
+    assert_eq!(chr_banks(&restored)[5], 0x08);
+}
@@ -0,0 +1,487 @@
+use nerust_input_traits::OpenBusReadResult;
+
+use self::audio::INTERNAL_RAM_LEN;
+pub(super) use self::audio::Namco163Audio;
+use super::Cartridge;
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_NAMCO163},
+    interrupt::{Interrupt, IrqSource},
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+    ppu_memory_access::PpuReadAccess,
+};
+
+mod audio;
+
+const IRQ_ENABLE: u16 = 0x8000;
+const IRQ_COUNTER_MAX: u16 = 0x7FFF;
+/// $E000 bit6。拡張音源を止める。
+const PRG_SOUND_DISABLE: u8 = 0x40;
+/// CHR/ネームテーブルのバンク値がこれ以上なら CIRAM を指す。
+const CIRAM_BANK_BASE: u8 = 0xE0;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq)]
+enum Model {
+    /// Mapper 19。内蔵 RAM、拡張音源、IRQ、ネームテーブルの切り替えを持つ。
+    Namco163,
+    /// Mapper 210 サブマッパー 1。ミラーリングは固定で、$C000 で PRG-RAM を有効にする。
+    Namco175,
+    /// Mapper 210 サブマッパー 2。$E000 の上位 2bit でミラーリングを選ぶ。
+    Namco340,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Namco163RuntimeState {
+    prg_banks: [u8; 3],
+    chr_banks: [u8; 8],
+    nametable_banks: [u8; 4],
+    irq_counter: u16,
+    write_protect: u8,
+    audio: Namco163Audio,
+}
+
+/// Namco 163 / 175 / 340 (Mapper 19, 210)。8KiB 単位の PRG 切り替え 3 つと 1KiB 単位の CHR 切り替え。
+/// 163 はネームテーブルに CHR-ROM を割り当てられ、15bit の CPU サイクル IRQ と波形メモリ音源を持つ。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Namco163 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    model: Model,
+    /// $E000 / $E800 / $F000。上位 2bit は音源の停止、CHR-RAM の無効化、ミラーリングに使う。
+    prg_banks: [u8; 3],
+    chr_banks: [u8; 8],
+    /// $C000-$D800。$E0 以上は CIRAM、それ未満は CHR-ROM のページ。
+    nametable_banks: [u8; 4],
+    /// 下位 15bit がカウンタ、bit15 が有効フラグ。
+    irq_counter: u16,
+    /// 163 の $F800 (上位 4bit が 0100 のとき PRG-RAM に書ける)、175 の $C000。
+    write_protect: u8,
+    audio: Namco163Audio,
+}
+
+#[typetag::serde]
+impl Cartridge for Namco163 {
+    fn expansion_audio_output(&self) -> f32 {
+        self.audio.output()
+    }
+
+    // 音源は CPU サイクルで進むため、APU のサンプル境界ごとにマッパーを進める
+    fn expansion_audio_cpu_step_synchronized(&self) -> bool {
+        self.model == Model::Namco163
+    }
+
+    fn notify_cpu_read(&mut self, address: usize, _value: u8, _interrupt: &mut Interrupt) {
+        if self.model == Model::Namco163 && (0x4800..=0x4FFF).contains(&address) {
+            self.audio.increment_address();
+        }
+    }
+
+    fn read_ppu_nametable(
+        &mut self,
+        address: usize,
+        _access: PpuReadAccess,
+        ciram: &mut [u8],
+    ) -> OpenBusReadResult {
+        OpenBusReadResult::new(self.nametable_byte(address, ciram), 0xFF)
+    }
+
+    fn write_ppu_nametable(
+        &mut self,
+        address: usize,
+        value: u8,
+        ciram: &mut [u8],
+        _interrupt: &mut Interrupt,
+    ) {
+        // CHR-ROM を割り当てたネームテーブルには書けない
+        if let Some(index) = self.ciram_index(address) {
+            ciram[index] = value;
+        }
+    }
+
+    fn peek_ppu_nametable(&self, address: usize, ciram: &[u8]) -> Option<u8> {
+        Some(self.nametable_byte(address, ciram))
+    }
+
+    // 電池がある場合は PRG-RAM に続けて内蔵 RAM も保存する
+    fn persistent_mapper_save_lengths(&self) -> (usize, usize) {
+        if !self.state.has_battery {
+            return (0, 0);
+        }
+        let internal_ram_len = if self.model == Model::Namco163 {
+            INTERNAL_RAM_LEN
+        } else {
+            0
+        };
+        (self.state.sram.len() + internal_ram_len, 0)
+    }
+
+    fn export_mapper_save_state(&self) -> Result<(Vec<u8>, Vec<u8>), PersistenceError> {
+        let (save_len, _) = self.persistent_mapper_save_lengths();
+        let saved = self
+            .state
+            .sram
+            .iter()
+            .chain(self.audio.ram())
+            .copied()
+            .take(save_len)
+            .collect();
+        Ok((saved, Vec::new()))
+    }
+
+    fn import_mapper_save_state(
+        &mut self,
+        prg_ram: &[u8],
+        chr_ram: &[u8],
+    ) -> Result<(), PersistenceError> {
+        let (save_len, _) = self.persistent_mapper_save_lengths();
+        if save_len == 0 {
+            return Err(PersistenceError::Validation(
+                "cartridge does not expose persistent mapper save memory".into(),
+            ));
+        }
+        if prg_ram.len() != save_len || !chr_ram.is_empty() {
+            return Err(PersistenceError::Validation(
+                "persistent mapper memory length mismatch".into(),
+            ));
+        }
+        let (sram, internal_ram) = prg_ram.split_at(self.state.sram.len());
+        self.state.sram.copy_from_slice(sram);
+        if !internal_ram.is_empty() {
+            self.audio.ram_mut().copy_from_slice(internal_ram);
+        }
+        Ok(())
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_NAMCO163.into(),
+            extra_body: encode_payload(&Namco163RuntimeState {
+                prg_banks: self.prg_banks,
+                chr_banks: self.chr_banks,
+                nametable_banks: self.nametable_banks,
+                irq_counter: self.irq_counter,
+                write_protect: self.write_protect,
+                audio: self.audio.clone(),
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_NAMCO163 {
+            return Err(PersistenceError::Validation(
+                "unexpected Namco 163 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Namco163RuntimeState = decode_payload(&state.extra_body)?;
+        if runtime.audio.ram().len() != INTERNAL_RAM_LEN {
+            return Err(PersistenceError::Validation(
+                "Namco 163 internal RAM length mismatch".into(),
+            ));
+        }
+        self.state = state.mapper_state;
+        self.prg_banks = runtime.prg_banks;
+        self.chr_banks = runtime.chr_banks;
+        self.nametable_banks = runtime.nametable_banks;
+        self.irq_counter = runtime.irq_counter;
+        self.write_protect = runtime.write_protect;
+        self.audio = runtime.audio;
+        Ok(())
+    }
+}
+
+impl Namco163 {
+    pub(crate) fn new_mapper19(data: CartridgeData) -> Self {
+        Self::new(data, Model::Namco163)
+    }
+
+    pub(crate) fn new_mapper210(data: CartridgeData) -> Self {
+        // サブマッパーが無い場合はミラーリング固定の 175 として扱う
+        let model = match data.sub_mapper_type() {
+            2 => Model::Namco340,
+            _ => Model::Namco175,
+        };
+        Self::new(data, model)
+    }
+
+    fn new(data: CartridgeData, model: Model) -> Self {
+        let nametable_banks = ciram_banks(data.mirror_mode());
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            model,
+            prg_banks: [0, 1, 2],
+            chr_banks: [0, 1, 2, 3, 4, 5, 6, 7],
+            nametable_banks,
+            irq_counter: 0,
+            write_protect: 0,
+            audio: Namco163Audio::new(),
+        }
+    }
+
+    fn update_prg_banks(&mut self) {
+        for (page, bank) in self.prg_banks.into_iter().enumerate() {
+            self.change_program_page(page, usize::from(bank & 0x3F));
+        }
+        let last_bank = (self.data_ref().prog_rom_len() / 0x2000).saturating_sub(1);
+        self.change_program_page(3, last_bank);
+    }
+
+    fn update_nametables(&mut self) {
+        // CIRAM だけで構成されていれば通常のミラーリングとしても見せておく
+        if self
+            .nametable_banks
+            .iter()
+            .all(|&bank| bank >= CIRAM_BANK_BASE)
+        {
+            let lut = self.nametable_banks.map(|bank| bank & 0x01);
+            self.set_mirror_mode(MirrorMode::Custom(lut));
+        }
+    }
+
+    /// CIRAM を割り当てたネームテーブルなら CIRAM 内の位置を返す。
+    fn ciram_index(&self, address: usize) -> Option<usize> {
+        let bank = self.nametable_banks[(address >> 10) & 0x03];
+        (bank >= CIRAM_BANK_BASE).then(|| (usize::from(bank & 0x01) << 10) | (address & 0x03FF))
+    }
+
+    fn nametable_byte(&self, address: usize, ciram: &[u8]) -> u8 {
+        if let Some(index) = self.ciram_index(address) {
+            return ciram[index];
+        }
+        let char_rom_len = self.data_ref().char_rom_len();
+        if char_rom_len == 0 {
+            return 0;
+        }
+        let bank = usize::from(self.nametable_banks[(address >> 10) & 0x03]);
+        self.data_ref()
+            .read_char_rom(((bank << 10) | (address & 0x03FF)) % char_rom_len)
+    }
+
+    fn prg_ram_writable(&self, index: usize) -> bool {
+        match self.model {
+            Model::Namco163 => {
+                self.write_protect & 0xF0 == 0x40 && self.write_protect & (1 << (index >> 11)) == 0
+            }
+            Model::Namco175 => self.write_protect & 0x01 != 0,
+            Model::Namco340 => false,
+        }
+    }
+
+    fn write_irq_counter(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        self.irq_counter = if address < 0x5800 {
+            (self.irq_counter & 0xFF00) | u16::from(value)
+        } else {
+            (self.irq_counter & 0x00FF) | (u16::from(value) << 8)
+        };
+        interrupt.clear_irq(IrqSource::EXTERNAL);
+    }
+
+    fn step_irq(&mut self, interrupt: &mut Interrupt) {
+        if self.irq_counter & IRQ_ENABLE == 0
+            || self.irq_counter & IRQ_COUNTER_MAX == IRQ_COUNTER_MAX
+        {
+            return;
+        }
+        self.irq_counter += 1;
+        if self.irq_counter & IRQ_COUNTER_MAX == IRQ_COUNTER_MAX {
+            interrupt.set_irq(IrqSource::EXTERNAL);
+        }
+    }
+}
+
+/// ミラーリングを CIRAM のネームテーブルバンク値に置き換える。
+fn ciram_banks(mode: MirrorMode) -> [u8; 4] {
+    let lut = match mode {
+        MirrorMode::Horizontal => [0, 0, 1, 1],
+        MirrorMode::Single0 => [0, 0, 0, 0],
+        MirrorMode::Single1 => [1, 1, 1, 1],
+        MirrorMode::Custom(lut) => lut,
+        MirrorMode::Vertical | MirrorMode::Four => [0, 1, 0, 1],
+    };
+    lut.map(|page| CIRAM_BANK_BASE | (page & 0x01))
+}
+
+impl CartridgeDataDao for Namco163 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Namco163 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Namco163 {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0400
+    }
+
+    fn initialize(&mut self) {
+        self.update_prg_banks();
+        for (page, bank) in self.chr_banks.into_iter().enumerate() {
+            self.change_character_page(page, usize::from(bank));
+        }
+        self.update_nametables();
+        self.change_ram_page(0, 0);
+    }
+
+    fn name(&self) -> &str {
+        match self.model {
+            Model::Namco163 => "Namco 163 (Mapper19)",
+            Model::Namco175 => "Namco 175 (Mapper210)",
+            Model::Namco340 => "Namco 340 (Mapper210)",
+        }
+    }
+
+    fn ram_len_default(&self) -> usize {
+        match self.model {
+            Model::Namco340 => 0,
+            _ => 0x2000,
+        }
+    }
+
+    fn write_ram(&mut self, index: usize, data: u8) {
+        if self.prg_ram_writable(index)
+            && let Some(address) = self.ram_address(index)
+        {
+            self.mapper_state_mut().sram[address] = data;
+        }
+    }
+
+    fn read_expansion(&self, address: usize) -> OpenBusReadResult {
+        if self.model != Model::Namco163 {
+            return OpenBusReadResult::new(0, 0);
+        }
+        match address {
+            0x4800..=0x4FFF => OpenBusReadResult::new(self.audio.read_data(), 0xFF),
+            0x5000..=0x57FF => OpenBusReadResult::new(self.irq_counter.to_le_bytes()[0], 0xFF),
+            0x5800..=0x5FFF => OpenBusReadResult::new(self.irq_counter.to_le_bytes()[1], 0xFF),
+            _ => OpenBusReadResult::new(0, 0),
+        }
+    }
+
+    fn write_expansion(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        if self.model != Model::Namco163 {
+            return;
+        }
+        match address {
+            0x4800..=0x4FFF => self.audio.write_data(value),
+            0x5000..=0x5FFF => self.write_irq_counter(address, value, interrupt),
+            _ => {}
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        match (self.model, address & 0xF800) {
+            // CIRAM を CHR 空間へ割り当てる設定 ($E800 bit6/7 が 0 で $E0 以上) は未対応で、CHR-ROM として扱う
+            (_, 0x8000..=0xB800) => {
+                let page = (address - 0x8000) >> 11;
+                self.chr_banks[page] = value;
+                self.change_character_page(page, usize::from(value));
+            }
+            (Model::Namco163, 0xC000..=0xD800) => {
+                self.nametable_banks[(address - 0xC000) >> 11] = value;
+                self.update_nametables();
+            }
+            (Model::Namco175, 0xC000..=0xD800) => self.write_protect = value,
+            (model, 0xE000) => {
+                self.prg_banks[0] = value;
+                self.update_prg_banks();
+                match model {
+                    Model::Namco163 => self.audio.set_disabled(value & PRG_SOUND_DISABLE != 0),
+                    Model::Namco340 => {
+                        self.nametable_banks = ciram_banks(match value >> 6 {
+                            0 => MirrorMode::Single0,
+                            1 => MirrorMode::Vertical,
+                            2 => MirrorMode::Horizontal,
+                            _ => MirrorMode::Single1,
+                        });
+                        self.update_nametables();
+                    }
+                    Model::Namco175 => {}
+                }
+            }
+            (_, 0xE800) => {
+                self.prg_banks[1] = value;
+                self.update_prg_banks();
+            }
+            (_, 0xF000) => {
+                self.prg_banks[2] = value;
+                self.update_prg_banks();
+            }
+            (Model::Namco163, 0xF800) => {
+                self.write_protect = value;
+                self.audio.write_address(value);
+            }
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        if self.model != Model::Namco163 {
+            return;
+        }
+        self.step_irq(interrupt);
+        self.audio.step();
+    }
+
+    fn step_cpu_cycles(&mut self, cycles: u64, interrupt: &mut Interrupt) {
+        if self.model != Model::Namco163 {
+            return;
+        }
+        for _ in 0..cycles {
+            self.step_irq(interrupt);
+        }
+        self.audio.step_cycles(cycles);
+    }
+
+    fn cycles_until_next_cpu_event(&self) -> u64 {
+        if self.irq_counter & IRQ_ENABLE == 0 {
+            return u64::MAX;
+        }
+        match IRQ_COUNTER_MAX - (self.irq_counter & IRQ_COUNTER_MAX) {
+            0 => u64::MAX,
+            remaining => u64::from(remaining),
+        }
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,199 @@
+/// 内蔵 RAM の大きさ。$40-$7F はチャンネルのレジスタを兼ねる。
+pub(crate) const INTERNAL_RAM_LEN: usize = 0x80;
+/// 1 チャンネルを更新する CPU サイクル数。
+const CHANNEL_CYCLES: u8 = 15;
+/// 音量 15 の 1ch が 2A03 の矩形波 1ch の最大値と同程度になるよう揃える。
+const OUTPUT_SCALE: f32 = 0.15 / 120.0;
+
+/// Namco 163 拡張音源。最大 8ch の 4bit 波形メモリ音源で、内蔵 RAM を波形とレジスタで共有する。
+/// チャンネルは 15 サイクルごとに 1 つずつ更新され、出力もその時点のチャンネルだけを出す。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(crate) struct Namco163Audio {
+    ram: Vec<u8>,
+    /// $F800。下位 7bit が RAM のアドレス、bit7 が自動インクリメント。
+    address: u8,
+    divider: u8,
+    /// 最後に更新したチャンネル (0 が $78-$7F)。
+    channel: u8,
+    output: i16,
+    /// $E000 bit6。立っている間は無音。
+    disabled: bool,
+}
+
+impl Default for Namco163Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Namco163Audio {
+    pub(crate) fn new() -> Self {
+        Self {
+            ram: vec![0; INTERNAL_RAM_LEN],
+            address: 0,
+            divider: 0,
+            channel: 0,
+            output: 0,
+            disabled: false,
+        }
+    }
+
+    pub(crate) fn ram(&self) -> &[u8] {
+        &self.ram
+    }
+
+    pub(crate) fn ram_mut(&mut self) -> &mut [u8] {
+        &mut self.ram
+    }
+
+    pub(crate) fn write_address(&mut self, value: u8) {
+        self.address = value;
+    }
+
+    pub(crate) fn read_data(&self) -> u8 {
+        self.ram[usize::from(self.address & 0x7F)]
+    }
+
+    pub(crate) fn write_data(&mut self, value: u8) {
+        self.ram[usize::from(self.address & 0x7F)] = value;
+        self.increment_address();
+    }
+
+    /// $4800 の読み出し後に呼ぶ。
+    pub(crate) fn increment_address(&mut self) {
+        if self.address & 0x80 != 0 {
+            self.address = 0x80 | (self.address.wrapping_add(1) & 0x7F);
+        }
+    }
+
+    pub(crate) fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+    }
+
+    /// $7F の bit4-6 で有効なチャンネル数を決める。
+    fn channel_count(&self) -> u8 {
+        ((self.ram[0x7F] >> 4) & 0x07) + 1
+    }
+
+    /// CPU 1 サイクル分進める。
+    pub(crate) fn step(&mut self) {
+        self.step_cycles(1);
+    }
+
+    pub(crate) fn step_cycles(&mut self, cycles: u64) {
+        let total = u64::from(self.divider) + cycles;
+        self.divider = (total % u64::from(CHANNEL_CYCLES)) as u8;
+        for _ in 0..total / u64::from(CHANNEL_CYCLES) {
+            self.clock_channel();
+        }
+    }
+
+    fn clock_channel(&mut self) {
+        self.channel = (self.channel + 1) % self.channel_count();
+        let base = 0x78 - usize::from(self.channel) * 8;
+        let register = |offset: usize| u32::from(self.ram[base + offset]);
+
+        let frequency = register(0) | (register(2) << 8) | ((register(4) & 0x03) << 16);
+        let length = (256 - (register(4) & 0xFC)) << 16;
+        let phase = register(1) | (register(3) << 8) | (register(5) << 16);
+        let phase = (phase + frequency) % length;
+        let sample_address = (register(6) + (phase >> 16)) & 0xFF;
+        let volume = register(7) & 0x0F;
+
+        let [low, middle, high, _] = phase.to_le_bytes();
+        self.ram[base + 1] = low;
+        self.ram[base + 3] = middle;
+        self.ram[base + 5] = high;
+
+        let sample =
+            (self.ram[(sample_address >> 1) as usize] >> ((sample_address & 1) * 4)) & 0x0F;
+        self.output = (i16::from(sample) - 8) * volume as i16;
+    }
+
+    pub(crate) fn output(&self) -> f32 {
+        if self.disabled {
+            return 0.0;
+        }
+        f32::from(self.output) * OUTPUT_SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Namco163Audio;
+
+    fn write(audio: &mut Namco163Audio, address: u8, values: &[u8]) {
+        audio.write_address(0x80 | address);
+        for &value in values {
+            audio.write_data(value);
+        }
+    }
+
+    #[test]
+    fn data_port_auto_increments_only_when_requested() {
+        let mut audio = Namco163Audio::new();
+        write(&mut audio, 0x7F, &[0x11, 0x22]);
+        // $7F の次は $00 に戻る
+        assert_eq!(audio.ram()[0x7F], 0x11);
+        assert_eq!(audio.ram()[0x00], 0x22);
+
+        audio.write_address(0x10);
+        audio.write_data(0x33);
+        audio.write_data(0x44);
+        assert_eq!(audio.read_data(), 0x44);
+        assert_eq!(audio.ram()[0x11], 0x00);
+    }
+
+    #[test]
+    fn single_channel_plays_wavetable_every_15_cycles() {
+        let mut audio = Namco163Audio::new();
+        // 波形: 0, F, 0, F ... (4 サンプル周期)
+        write(&mut audio, 0x00, &[0xF0, 0xF0]);
+        // 周波数 0x10000 (1 更新で 1 サンプル進む)、長さ 4、波形アドレス 0、音量 15
+        write(
+            &mut audio,
+            0x78,
+            &[0x00, 0x00, 0x00, 0x00, 0xFD, 0x00, 0x00, 0x0F],
+        );
+
+        let mut outputs = Vec::new();
+        for _ in 0..4 {
+            audio.step_cycles(14);
+            let before = audio.output();
+            audio.step();
+            assert_ne!(audio.output(), before);
+            outputs.push(audio.output() > 0.0);
+        }
+        assert_eq!(outputs, [true, false, true, false]);
+        // 位相は RAM に書き戻される
+        assert_eq!(audio.ram()[0x7D], 0x00);
+    }
+
+    #[test]
+    fn channels_are_time_multiplexed() {
+        let mut audio = Namco163Audio::new();
+        write(&mut audio, 0x00, &[0xFF]);
+        // 2ch 有効。ch8 は音量 15、ch7 は音量 0
+        write(
+            &mut audio,
+            0x78,
+            &[0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x1F],
+        );
+        write(
+            &mut audio,
+            0x70,
+            &[0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x00],
+        );
+
+        let outputs = (0..4)
+            .map(|_| {
+                audio.step_cycles(15);
+                audio.output() > 0.0
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(outputs, [false, true, false, true]);
+
+        audio.set_disabled(true);
+        assert_eq!(audio.output(), 0.0);
+    }
+}
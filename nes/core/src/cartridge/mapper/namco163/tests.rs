@@ -0,0 +1,226 @@
+use super::{Cartridge, Namco163};
+use crate::{
+    cartridge_data_parts::CartridgeDataParts,
+    cartridge_rom::CartridgeData,
+    interrupt::{Interrupt, IrqSource},
+    mapper::Mapper,
+    mirror::MirrorMode,
+    ppu_memory_access::PpuReadAccess,
+    rom_format::RomFormat,
+};
+
+fn test_data(mapper_type: u16, sub_mapper_type: u8, has_battery: bool) -> CartridgeData {
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::INes,
+        prog_rom: (0..0x40000).map(|i| (i / 0x2000) as u8).collect(),
+        char_rom: (0..0x40000).map(|i| (i / 0x400) as u8).collect(),
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0,
+        save_vram_length: 0,
+        mapper_type,
+        mirror_mode: MirrorMode::Vertical,
+        has_battery,
+        sub_mapper_type,
+        trainer: Vec::new(),
+        console_type: None,
+    })
+    .expect("test cartridge data should be valid")
+}
+
+fn new_mapper19(has_battery: bool) -> Namco163 {
+    let mut mapper = Namco163::new_mapper19(test_data(19, 0, has_battery));
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+fn write_internal_ram(mapper: &mut Namco163, address: u8, values: &[u8]) {
+    let mut interrupt = Interrupt::new();
+    mapper.write_register(0xF800, 0x80 | address, &mut interrupt);
+    for &value in values {
+        Cartridge::write(mapper, 0x4800, value, &mut interrupt);
+    }
+}
+
+#[test]
+fn prg_and_chr_banks_switch_with_fixed_last_bank() {
+    let mut mapper = new_mapper19(false);
+    let mut interrupt = Interrupt::new();
+    mapper.write_register(0xE000, 0x05, &mut interrupt);
+    mapper.write_register(0xE800, 0xC6, &mut interrupt);
+    mapper.write_register(0xF000, 0x07, &mut interrupt);
+    mapper.write_register(0x9800, 0x42, &mut interrupt);
+
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x05);
+    assert_eq!(Cartridge::read(&mapper, 0xA000).data, 0x06);
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x07);
+    assert_eq!(Cartridge::read(&mapper, 0xE000).data, 0x1F);
+    assert_eq!(Cartridge::read(&mapper, 0x0C00).data, 0x42);
+}
+
+#[test]
+fn nametables_can_map_chr_rom_or_ciram() {
+    let mut mapper = new_mapper19(false);
+    let mut interrupt = Interrupt::new();
+    let mut ciram = vec![0; 0x800];
+    ciram[0x400] = 0x99;
+    mapper.write_register(0xC000, 0x23, &mut interrupt);
+    mapper.write_register(0xC800, 0xE1, &mut interrupt);
+
+    let read = |mapper: &mut Namco163, address, ciram: &mut [u8]| {
+        mapper
+            .read_ppu_nametable(address, PpuReadAccess::CpuData, ciram)
+            .data
+    };
+    assert_eq!(read(&mut mapper, 0x2000, &mut ciram), 0x23);
+    assert_eq!(read(&mut mapper, 0x2400, &mut ciram), 0x99);
+
+    // CHR-ROM のネームテーブルへの書き込みは捨てられる
+    mapper.write_ppu_nametable(0x2000, 0x55, &mut ciram, &mut interrupt);
+    mapper.write_ppu_nametable(0x2401, 0x66, &mut ciram, &mut interrupt);
+    assert_eq!(read(&mut mapper, 0x2000, &mut ciram), 0x23);
+    assert_eq!(ciram[0x401], 0x66);
+}
+
+#[test]
+fn irq_counts_up_to_7fff_and_acknowledges_on_write() {
+    let mut mapper = new_mapper19(false);
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x5000, 0xFD, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x5800, 0xFF, &mut interrupt);
+    assert_eq!(mapper.cycles_until_next_cpu_event(), 2);
+
+    mapper.step_cpu_cycles(1, &mut interrupt);
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    mapper.step_cpu_cycles(1, &mut interrupt);
+    assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+    // 上限で止まる
+    mapper.step_cpu_cycles(10, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x5000).data, 0xFF);
+    assert_eq!(Cartridge::read(&mapper, 0x5800).data, 0xFF);
+
+    Cartridge::write(&mut mapper, 0x5800, 0x00, &mut interrupt);
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+}
+
+#[test]
+fn internal_ram_port_auto_increments_on_read() {
+    let mut mapper = new_mapper19(false);
+    let mut interrupt = Interrupt::new();
+    write_internal_ram(&mut mapper, 0x10, &[0x11, 0x22]);
+
+    mapper.write_register(0xF800, 0x90, &mut interrupt);
+    let first = Cartridge::read(&mapper, 0x4800).data;
+    mapper.notify_cpu_read(0x4800, first, &mut interrupt);
+    let second = Cartridge::read(&mapper, 0x4800).data;
+    assert_eq!((first, second), (0x11, 0x22));
+}
+
+#[test]
+fn prg_ram_honours_write_protect() {
+    let mut mapper = new_mapper19(false);
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x00);
+
+    // $6800-$6FFF だけ保護する
+    mapper.write_register(0xF800, 0x42, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x6800, 0x66, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x55);
+    assert_eq!(Cartridge::read(&mapper, 0x6800).data, 0x00);
+}
+
+#[test]
+fn memory_editor_pokes_report_write_protect() {
+    let mut mapper = new_mapper19(false);
+    assert!(!Cartridge::poke_ram(&mut mapper, 0x6000, 0x55));
+
+    let mut interrupt = Interrupt::new();
+    mapper.write_register(0xF800, 0x42, &mut interrupt);
+    assert!(Cartridge::poke_ram(&mut mapper, 0x6000, 0x55));
+    assert!(!Cartridge::poke_ram(&mut mapper, 0x6800, 0x66));
+    assert_eq!(Cartridge::read(&mapper, 0x6800).data, 0x00);
+}
+
+#[test]
+fn battery_save_includes_internal_ram() {
+    assert!(!new_mapper19(false).has_persistent_mapper_save());
+
+    let mut mapper = new_mapper19(true);
+    let mut interrupt = Interrupt::new();
+    mapper.write_register(0xF800, 0x40, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+    write_internal_ram(&mut mapper, 0x7F, &[0x77]);
+    assert_eq!(mapper.persistent_mapper_save_lengths(), (0x2080, 0));
+
+    let (prg_ram, chr_ram) = mapper.export_mapper_save_state().unwrap();
+    assert_eq!(prg_ram[0], 0x55);
+    assert_eq!(prg_ram[0x207F], 0x77);
+
+    let mut restored = new_mapper19(true);
+    restored
+        .import_mapper_save_state(&prg_ram, &chr_ram)
+        .unwrap();
+    restored.write_register(0xF800, 0x7F, &mut interrupt);
+    assert_eq!(Cartridge::read(&restored, 0x4800).data, 0x77);
+    assert_eq!(Cartridge::read(&restored, 0x6000).data, 0x55);
+    assert!(
+        restored
+            .import_mapper_save_state(&prg_ram[..0x2000], &chr_ram)
+            .is_err()
+    );
+}
+
+#[test]
+fn audio_and_irq_state_round_trip_through_runtime_state() {
+    let mut mapper = new_mapper19(false);
+    let mut interrupt = Interrupt::new();
+    write_internal_ram(&mut mapper, 0x00, &[0xFF]);
+    write_internal_ram(
+        &mut mapper,
+        0x78,
+        &[0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x0F],
+    );
+    Cartridge::write(&mut mapper, 0x5800, 0xFF, &mut interrupt);
+    mapper.step_cpu_cycles(15, &mut interrupt);
+    assert!(mapper.expansion_audio_output() > 0.0);
+
+    let state = mapper.export_runtime_state().unwrap();
+    let mut restored = new_mapper19(false);
+    restored.import_runtime_state(state).unwrap();
+    assert_eq!(
+        restored.expansion_audio_output(),
+        mapper.expansion_audio_output()
+    );
+    assert_eq!(
+        restored.cycles_until_next_cpu_event(),
+        mapper.cycles_until_next_cpu_event()
+    );
+
+    // $E000 bit6 で音源を止める
+    restored.write_register(0xE000, 0x40, &mut interrupt);
+    assert_eq!(restored.expansion_audio_output(), 0.0);
+}
+
+#[test]
+fn namco340_selects_mirroring_from_prg_register() {
+    let mut mapper = Namco163::new_mapper210(test_data(210, 2, false));
+    Cartridge::initialize(&mut mapper);
+    let mut interrupt = Interrupt::new();
+    let mut ciram = vec![0; 0x800];
+    ciram[0x400] = 0x99;
+    mapper.write_register(0xE000, 0x81, &mut interrupt);
+
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x01);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Custom([0, 0, 1, 1]));
+    assert_eq!(
+        mapper
+            .read_ppu_nametable(0x2800, PpuReadAccess::CpuData, &mut ciram)
+            .data,
+        0x99
+    );
+    // 340 には音源も IRQ も無い
+    Cartridge::write(&mut mapper, 0x5800, 0xFF, &mut interrupt);
+    assert_eq!(mapper.cycles_until_next_cpu_event(), u64::MAX);
+}
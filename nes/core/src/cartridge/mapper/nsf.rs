@@ -0,0 +1,552 @@
+use nerust_input_traits::OpenBusReadResult;
+
+use self::driver::{
+    IRQ_ENTRY, NMI_ENTRY, REG_PLAY_ACK, REG_PLAY_START, REG_REGION, REG_TRACK, REG_TRACK_START,
+    RESET_ENTRY,
+};
+use super::{
+    Cartridge, fds::FdsAudio, fme7::Sunsoft5bAudio, mmc5::Mmc5, namco163::Namco163Audio,
+    vrc6::Vrc6Audio, vrc7::Vrc7Audio,
+};
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_NSF},
+    interrupt::{Interrupt, IrqSource},
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    nsf_info::{NsfChips, NsfInfo},
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+    status::console_type::ConsoleType,
+};
+
+mod driver;
+#[cfg(test)]
+mod tests;
+
+const BANK_LEN: usize = 0x1000;
+/// FDS 音源付きの場合は $6000-$FFFF の 40KiB 全体が RAM になる。
+const FDS_RAM_LEN: usize = 0xA000;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct NsfRuntimeState {
+    track: u8,
+    play_counter: u32,
+    play_running: bool,
+    play_pending: bool,
+    fds_audio: Option<FdsAudio>,
+    mmc5: Option<CartridgeRuntimeState>,
+    #[serde(default)]
+    sunsoft5b: Option<Sunsoft5bAudio>,
+    #[serde(default)]
+    vrc6: Option<Vrc6Audio>,
+    #[serde(default)]
+    vrc7: Option<Vrc7Audio>,
+    #[serde(default)]
+    n163: Option<Namco163Audio>,
+}
+
+/// NSF 再生用の仮想カートリッジ。$5FF8-$5FFF の 4KiB バンク切り替えと拡張音源、
+/// $4100 の再生ドライバを持つ。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Nsf {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    info: NsfInfo,
+    console_type: ConsoleType,
+    track: u8,
+    play_counter: u32,
+    play_running: bool,
+    play_pending: bool,
+    fds_audio: Option<FdsAudio>,
+    mmc5: Option<Box<Mmc5>>,
+    sunsoft5b: Option<Sunsoft5bAudio>,
+    vrc6: Option<Vrc6Audio>,
+    vrc7: Option<Vrc7Audio>,
+    n163: Option<Namco163Audio>,
+}
+
+#[typetag::serde]
+impl Cartridge for Nsf {
+    fn read(&self, address: usize) -> OpenBusReadResult {
+        match address {
+            0..=0x1FFF => self.read_character(address),
+            0x4020..=0x5FFF => Mapper::read_expansion(self, address),
+            0xFFFA..=0xFFFF => {
+                let vector = match address & !1 {
+                    0xFFFA => NMI_ENTRY,
+                    0xFFFC => RESET_ENTRY,
+                    _ => IRQ_ENTRY,
+                };
+                OpenBusReadResult::new(vector.to_le_bytes()[address & 1], 0xFF)
+            }
+            0x6000..=0xFFFF if self.has_fds_ram() => {
+                OpenBusReadResult::new(self.state.sram[address - 0x6000], 0xFF)
+            }
+            0x6000..=0x7FFF => OpenBusReadResult::new(self.state.sram[address - 0x6000], 0xFF),
+            0x8000..=0xFFFF => self.read_program(address - 0x8000),
+            _ => {
+                log::error!("unhandled mapper read at address: 0x{:04X}", address);
+                OpenBusReadResult::new(0, 0)
+            }
+        }
+    }
+
+    fn write(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        match address {
+            0..=0x1FFF => self.write_character(address, value),
+            0x4020..=0x5FFF => Mapper::write_expansion(self, address, value, interrupt),
+            0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002 if self.vrc6.is_some() => {
+                if let Some(audio) = self.vrc6.as_mut() {
+                    audio.write(address, value);
+                }
+                if self.has_fds_ram() {
+                    self.state.sram[address - 0x6000] = value;
+                }
+            }
+            0x9010 | 0x9030 if self.vrc7.is_some() => {
+                if let Some(audio) = self.vrc7.as_mut() {
+                    if address == 0x9010 {
+                        audio.write_register_select(value);
+                    } else {
+                        audio.write_register_data(value);
+                    }
+                }
+                if self.has_fds_ram() {
+                    self.state.sram[address - 0x6000] = value;
+                }
+            }
+            0xF800..=0xFFFF if self.n163.is_some() => {
+                if let Some(audio) = self.n163.as_mut() {
+                    audio.write_address(value);
+                }
+                if self.has_fds_ram() {
+                    self.state.sram[address - 0x6000] = value;
+                }
+            }
+            0xC000..=0xFFFF if self.sunsoft5b.is_some() => {
+                if let Some(audio) = self.sunsoft5b.as_mut() {
+                    if address < 0xE000 {
+                        audio.write_register_select(value);
+                    } else {
+                        audio.write_register_data(value);
+                    }
+                }
+                if self.has_fds_ram() {
+                    self.state.sram[address - 0x6000] = value;
+                }
+            }
+            0x6000..=0xFFFF if self.has_fds_ram() => self.state.sram[address - 0x6000] = value,
+            0x6000..=0x7FFF => self.state.sram[address - 0x6000] = value,
+            0x8000..=0xFFFF => {}
+            _ => {
+                log::error!("unhandled mapper write at address: 0x{:04X}", address);
+            }
+        }
+    }
+
+    fn notify_cpu_read(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        if address == REG_PLAY_ACK {
+            self.play_pending = false;
+            interrupt.clear_irq(IrqSource::NSF_PLAY);
+        }
+        if let Some(mmc5) = self.mmc5.as_mut() {
+            mmc5.notify_cpu_read(address, value, interrupt);
+        }
+        if (0x4800..=0x4FFF).contains(&address)
+            && let Some(audio) = self.n163.as_mut()
+        {
+            audio.increment_address();
+        }
+    }
+
+    fn expansion_audio_output(&self) -> f32 {
+        let direct = self.fds_audio.as_ref().map_or(0.0, FdsAudio::output)
+            + self.sunsoft5b.as_ref().map_or(0.0, Sunsoft5bAudio::output)
+            + self.vrc6.as_ref().map_or(0.0, Vrc6Audio::output)
+            + self.vrc7.as_ref().map_or(0.0, Vrc7Audio::output)
+            + self.n163.as_ref().map_or(0.0, Namco163Audio::output);
+        // MMC5 は APU と逆相で出力されるため、他の音源の符号を反転して合わせる
+        match self.mmc5.as_ref() {
+            Some(mmc5) => mmc5.expansion_audio_output() - direct,
+            None => direct,
+        }
+    }
+
+    fn expansion_audio_inverted(&self) -> bool {
+        self.mmc5.is_some()
+    }
+
+    fn expansion_audio_cpu_step_synchronized(&self) -> bool {
+        self.mmc5.is_some()
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_NSF.into(),
+            extra_body: encode_payload(&NsfRuntimeState {
+                track: self.track,
+                play_counter: self.play_counter,
+                play_running: self.play_running,
+                play_pending: self.play_pending,
+                fds_audio: self.fds_audio.clone(),
+                mmc5: self
+                    .mmc5
+                    .as_ref()
+                    .map(|mmc5| mmc5.export_runtime_state())
+                    .transpose()?,
+                sunsoft5b: self.sunsoft5b.clone(),
+                vrc6: self.vrc6.clone(),
+                vrc7: self.vrc7.clone(),
+                n163: self.n163.clone(),
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_NSF {
+            return Err(PersistenceError::Validation(
+                "unexpected NSF runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: NsfRuntimeState = decode_payload(&state.extra_body)?;
+        if runtime.track >= self.info.song_count
+            || runtime.fds_audio.is_some() != self.fds_audio.is_some()
+            || runtime.mmc5.is_some() != self.mmc5.is_some()
+            || runtime.sunsoft5b.is_some() != self.sunsoft5b.is_some()
+            || runtime.vrc6.is_some() != self.vrc6.is_some()
+            || runtime.vrc7.is_some() != self.vrc7.is_some()
+            || runtime.n163.is_some() != self.n163.is_some()
+        {
+            return Err(PersistenceError::Validation(
+                "NSF runtime state does not match the loaded file".into(),
+            ));
+        }
+        if let (Some(mmc5), Some(mmc5_state)) = (self.mmc5.as_mut(), runtime.mmc5) {
+            mmc5.import_runtime_state(mmc5_state)?;
+        }
+        self.state = state.mapper_state;
+        self.track = runtime.track;
+        self.play_counter = runtime.play_counter;
+        self.play_running = runtime.play_running;
+        self.play_pending = runtime.play_pending;
+        self.fds_audio = runtime.fds_audio;
+        self.sunsoft5b = runtime.sunsoft5b;
+        self.vrc6 = runtime.vrc6;
+        self.vrc7 = runtime.vrc7;
+        self.n163 = runtime.n163;
+        Ok(())
+    }
+
+    fn set_console_type(&mut self, console_type: ConsoleType) {
+        self.console_type = console_type;
+    }
+
+    fn next_track(&mut self) -> bool {
+        self.track = (self.track + 1) % self.info.song_count;
+        self.log_track();
+        true
+    }
+
+    fn previous_track(&mut self) -> bool {
+        self.track = self
+            .track
+            .checked_sub(1)
+            .unwrap_or(self.info.song_count - 1);
+        self.log_track();
+        true
+    }
+}
+
+impl Nsf {
+    pub(crate) fn new(data: CartridgeData, info: NsfInfo) -> Self {
+        let unsupported = info.chips
+            - (NsfChips::FDS
+                | NsfChips::MMC5
+                | NsfChips::SUNSOFT_5B
+                | NsfChips::VRC6
+                | NsfChips::VRC7
+                | NsfChips::N163);
+        if !unsupported.is_empty() {
+            log::warn!("NSF expansion audio is not supported: {unsupported:?}");
+        }
+        Self {
+            fds_audio: info.chips.contains(NsfChips::FDS).then(FdsAudio::new),
+            mmc5: info
+                .chips
+                .contains(NsfChips::MMC5)
+                .then(|| Box::new(Mmc5::new_for_nsf(data.clone()))),
+            sunsoft5b: info
+                .chips
+                .contains(NsfChips::SUNSOFT_5B)
+                .then(Sunsoft5bAudio::new),
+            vrc6: info.chips.contains(NsfChips::VRC6).then(Vrc6Audio::new),
+            vrc7: info.chips.contains(NsfChips::VRC7).then(Vrc7Audio::new),
+            n163: info.chips.contains(NsfChips::N163).then(Namco163Audio::new),
+            console_type: data.console_type().unwrap_or_default(),
+            track: info.first_song.min(info.song_count - 1),
+            info,
+            cartridge_data: data,
+            state: MapperState::new(),
+            play_counter: 0,
+            play_running: false,
+            play_pending: false,
+        }
+    }
+
+    fn has_fds_ram(&self) -> bool {
+        self.fds_audio.is_some()
+    }
+
+    fn log_track(&self) {
+        let label = self
+            .info
+            .track_labels
+            .get(usize::from(self.track))
+            .map_or("", String::as_str);
+        log::info!(
+            "NSF track {}/{} {}",
+            self.track + 1,
+            self.info.song_count,
+            label
+        );
+    }
+
+    /// PLAY を呼び出す間隔 (CPU サイクル)。Dendy も 50Hz のため PAL の値を使う。
+    fn play_period(&self) -> u32 {
+        let speed = match self.console_type {
+            ConsoleType::Ntsc => self.info.ntsc_speed,
+            ConsoleType::Pal | ConsoleType::Dendy => self.info.pal_speed,
+        };
+        (u64::from(speed) * self.console_type.cpu_clock_rate() / 1_000_000).max(1) as u32
+    }
+
+    /// $6000 から 4KiB 単位で見た各スロットの初期バンク。
+    fn initial_banks(&self) -> [Option<usize>; 10] {
+        let mut banks = [None; 10];
+        match self.info.bank_init {
+            Some(bank_init) => {
+                // $5FF6/$5FF7 は FDS のみ。ヘッダの $6000-$7FFF 相当の値を使う
+                if self.has_fds_ram() {
+                    banks[0] = Some(usize::from(bank_init[6]));
+                    banks[1] = Some(usize::from(bank_init[7]));
+                }
+                for (slot, &bank) in banks[2..].iter_mut().zip(&bank_init) {
+                    *slot = Some(usize::from(bank));
+                }
+            }
+            None => {
+                let first_slot = usize::from(self.info.load_address >> 12) - 6;
+                for (slot, bank) in banks.iter_mut().enumerate() {
+                    *bank = slot.checked_sub(first_slot);
+                }
+            }
+        }
+        banks
+    }
+
+    fn switch_bank(&mut self, slot: usize, bank: Option<usize>) {
+        if self.has_fds_ram() {
+            let Some(bank) = bank else {
+                return;
+            };
+            let rom_banks = self.cartridge_data.prog_rom_len() / BANK_LEN;
+            let source = (bank % rom_banks) * BANK_LEN;
+            let target = slot * BANK_LEN;
+            self.state.sram[target..target + BANK_LEN]
+                .copy_from_slice(&self.cartridge_data.prog_rom()[source..source + BANK_LEN]);
+        } else if let Some(offset) = slot.checked_sub(2) {
+            match bank {
+                Some(bank) => self.change_program_page(offset, bank),
+                None => {
+                    let pages = BANK_LEN >> 8;
+                    self.state.program_page_table[offset * pages..(offset + 1) * pages].fill(None);
+                }
+            }
+        }
+    }
+
+    /// ドライバがリセット直後に呼ぶ。メモリと拡張音源を曲の開始状態に戻す。
+    fn start_track(&mut self, interrupt: &mut Interrupt) {
+        self.state.sram.fill(0);
+        for (slot, bank) in self.initial_banks().into_iter().enumerate() {
+            self.switch_bank(slot, bank);
+        }
+        self.play_running = false;
+        self.play_pending = false;
+        interrupt.clear_irq(IrqSource::NSF_PLAY);
+        if self.fds_audio.is_some() {
+            self.fds_audio = Some(FdsAudio::new());
+        }
+        if self.mmc5.is_some() {
+            self.mmc5 = Some(Box::new(Mmc5::new_for_nsf(self.cartridge_data.clone())));
+        }
+        if self.sunsoft5b.is_some() {
+            self.sunsoft5b = Some(Sunsoft5bAudio::new());
+        }
+        if self.vrc6.is_some() {
+            self.vrc6 = Some(Vrc6Audio::new());
+        }
+        if self.vrc7.is_some() {
+            self.vrc7 = Some(Vrc7Audio::new());
+        }
+        if self.n163.is_some() {
+            self.n163 = Some(Namco163Audio::new());
+        }
+    }
+
+    fn step_play_timer(&mut self, interrupt: &mut Interrupt) {
+        if !self.play_running {
+            return;
+        }
+        if self.play_counter <= 1 {
+            self.play_counter = self.play_period();
+            self.play_pending = true;
+            interrupt.set_irq(IrqSource::NSF_PLAY);
+        } else {
+            self.play_counter -= 1;
+        }
+    }
+}
+
+impl CartridgeDataDao for Nsf {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Nsf {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Nsf {
+    fn program_page_len(&self) -> usize {
+        BANK_LEN
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn initialize(&mut self) {
+        self.change_character_page(0, 0);
+    }
+
+    fn name(&self) -> &str {
+        "NSF"
+    }
+
+    fn ram_len_default(&self) -> usize {
+        if self.has_fds_ram() {
+            FDS_RAM_LEN
+        } else {
+            0x2000
+        }
+    }
+
+    fn read_expansion(&self, address: usize) -> OpenBusReadResult {
+        match address {
+            0x4040..=0x4092 => self
+                .fds_audio
+                .as_ref()
+                .and_then(|audio| audio.read(address))
+                .map_or(OpenBusReadResult::new(0, 0), |value| {
+                    OpenBusReadResult::new(value, 0x3F)
+                }),
+            REG_TRACK => OpenBusReadResult::new(self.track, 0xFF),
+            REG_REGION => {
+                OpenBusReadResult::new(u8::from(self.console_type == ConsoleType::Pal), 0xFF)
+            }
+            REG_PLAY_ACK => OpenBusReadResult::new(if self.play_pending { 0x80 } else { 0 }, 0xFF),
+            0x4100..=0x41FF => {
+                driver::read(address, self.info.init_address, self.info.play_address)
+                    .map_or(OpenBusReadResult::new(0, 0), |value| {
+                        OpenBusReadResult::new(value, 0xFF)
+                    })
+            }
+            0x4800..=0x4FFF => self
+                .n163
+                .as_ref()
+                .map_or(OpenBusReadResult::new(0, 0), |audio| {
+                    OpenBusReadResult::new(audio.read_data(), 0xFF)
+                }),
+            0x5000..=0x5FF5 => self
+                .mmc5
+                .as_ref()
+                .map_or(OpenBusReadResult::new(0, 0), |mmc5| {
+                    mmc5.read_expansion(address)
+                }),
+            _ => OpenBusReadResult::new(0, 0),
+        }
+    }
+
+    fn write_expansion(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        match address {
+            0x4040..=0x408A => {
+                if let Some(audio) = self.fds_audio.as_mut() {
+                    audio.write(address, value);
+                }
+            }
+            0x4800..=0x4FFF => {
+                if let Some(audio) = self.n163.as_mut() {
+                    audio.write_data(value);
+                }
+            }
+            REG_TRACK_START => self.start_track(interrupt),
+            REG_PLAY_START => {
+                self.play_counter = self.play_period();
+                self.play_running = self.info.play_address != 0;
+            }
+            0x5000..=0x5FF5 => {
+                if let Some(mmc5) = self.mmc5.as_mut() {
+                    mmc5.write_expansion(address, value, interrupt);
+                }
+            }
+            0x5FF6..=0x5FFF => self.switch_bank(address - 0x5FF6, Some(usize::from(value))),
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        self.step_play_timer(interrupt);
+        if let Some(audio) = self.fds_audio.as_mut() {
+            audio.step();
+        }
+        if let Some(mmc5) = self.mmc5.as_mut() {
+            mmc5.step(interrupt);
+        }
+        if let Some(audio) = self.sunsoft5b.as_mut() {
+            audio.step();
+        }
+        if let Some(audio) = self.vrc6.as_mut() {
+            audio.step();
+        }
+        if let Some(audio) = self.vrc7.as_mut() {
+            audio.step();
+        }
+        if let Some(audio) = self.n163.as_mut() {
+            audio.step();
+        }
+    }
+}
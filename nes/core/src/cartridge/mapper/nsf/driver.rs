@@ -0,0 +1,95 @@
+//! $4100 に配置する NSF 再生ドライバ。
+//!
+//! リセットで RAM と APU を初期化して INIT を呼び、その後はマッパーのタイマー IRQ ごとに
+//! PLAY を呼ぶ。
+
+pub(super) const DRIVER_BASE: usize = 0x4100;
+/// 書き込みで曲の再生開始を通知する。バンクと PRG-RAM を初期状態に戻す。
+pub(super) const REG_TRACK_START: usize = 0x41F0;
+/// 再生する曲番号 (0 始まり)。
+pub(super) const REG_TRACK: usize = 0x41F1;
+/// 0 = NTSC, 1 = PAL。
+pub(super) const REG_REGION: usize = 0x41F2;
+/// 書き込みで PLAY タイマーを開始する。
+pub(super) const REG_PLAY_START: usize = 0x41F3;
+/// bit7 が PLAY タイマーの IRQ。読み出しでクリアされる。
+pub(super) const REG_PLAY_ACK: usize = 0x41F4;
+
+pub(super) const NMI_ENTRY: u16 = 0x415F;
+pub(super) const RESET_ENTRY: u16 = 0x4100;
+pub(super) const IRQ_ENTRY: u16 = 0x414A;
+
+const INIT_OPERAND: usize = 0x41;
+const PLAY_OPERAND: usize = 0x55;
+
+#[rustfmt::skip]
+const DRIVER: [u8; 0x60] = [
+    // reset:
+    0x78,             // SEI
+    0xD8,             // CLD
+    0xA2, 0xFF,       // LDX #$FF
+    0x9A,             // TXS
+    0x8D, 0xF0, 0x41, // STA $41F0
+    0xA9, 0x00,       // LDA #$00
+    0xAA,             // TAX
+    // clear_ram:
+    0x95, 0x00,       // STA $00,X
+    0x9D, 0x00, 0x01, // STA $0100,X
+    0x9D, 0x00, 0x02, // STA $0200,X
+    0x9D, 0x00, 0x03, // STA $0300,X
+    0x9D, 0x00, 0x04, // STA $0400,X
+    0x9D, 0x00, 0x05, // STA $0500,X
+    0x9D, 0x00, 0x06, // STA $0600,X
+    0x9D, 0x00, 0x07, // STA $0700,X
+    0xE8,             // INX
+    0xD0, 0xE6,       // BNE clear_ram
+    0xA2, 0x13,       // LDX #$13
+    // clear_apu:
+    0x9D, 0x00, 0x40, // STA $4000,X
+    0xCA,             // DEX
+    0x10, 0xFA,       // BPL clear_apu
+    0x8D, 0x15, 0x40, // STA $4015
+    0xA9, 0x0F,       // LDA #$0F
+    0x8D, 0x15, 0x40, // STA $4015
+    0xA9, 0x40,       // LDA #$40
+    0x8D, 0x17, 0x40, // STA $4017
+    0xAD, 0xF1, 0x41, // LDA $41F1
+    0xAE, 0xF2, 0x41, // LDX $41F2
+    0x20, 0x00, 0x00, // JSR INIT
+    0x8D, 0xF3, 0x41, // STA $41F3
+    0x58,             // CLI
+    // idle:
+    0x4C, 0x47, 0x41, // JMP idle
+    // irq:
+    0x48,             // PHA
+    0x8A,             // TXA
+    0x48,             // PHA
+    0x98,             // TYA
+    0x48,             // PHA
+    0xAD, 0xF4, 0x41, // LDA $41F4
+    0x10, 0x03,       // BPL skip_play
+    0x20, 0x00, 0x00, // JSR PLAY
+    // skip_play:
+    0xAD, 0x15, 0x40, // LDA $4015 (フレーム IRQ の解除)
+    0x68,             // PLA
+    0xA8,             // TAY
+    0x68,             // PLA
+    0xAA,             // TAX
+    0x68,             // PLA
+    // nmi:
+    0x40,             // RTI
+];
+
+/// ドライバの `address` の内容。INIT / PLAY の呼び出し先は曲ごとに埋め込む。
+pub(super) fn read(address: usize, init: u16, play: u16) -> Option<u8> {
+    let offset = address.checked_sub(DRIVER_BASE)?;
+    let [init_low, init_high] = init.to_le_bytes();
+    let [play_low, play_high] = play.to_le_bytes();
+    match offset {
+        INIT_OPERAND => Some(init_low),
+        o if o == INIT_OPERAND + 1 => Some(init_high),
+        PLAY_OPERAND => Some(play_low),
+        o if o == PLAY_OPERAND + 1 => Some(play_high),
+        o => DRIVER.get(o).copied(),
+    }
+}
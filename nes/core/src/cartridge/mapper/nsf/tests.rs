@@ -0,0 +1,163 @@
+use super::{Cartridge, Nsf};
+use crate::{
+    interrupt::{Interrupt, IrqSource},
+    mapper::Mapper,
+    rom_parse::parse_rom,
+};
+
+/// 各 4KiB バンクの先頭にバンク番号を置いた、バンク切り替え対応の NSF
+fn bankswitched_nsf(chips: u8) -> Vec<u8> {
+    let mut nsf = b"NESM\x1A\x01\x02\x01".to_vec();
+    nsf.resize(0x80, 0);
+    nsf[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x03, 0x80]);
+    nsf[0x70..0x78].copy_from_slice(&[0, 1, 2, 3, 0, 1, 2, 3]);
+    nsf[0x7B] = chips;
+    for bank in 0..4 {
+        let mut data = vec![0; 0x1000];
+        data[0] = 0xB0 | bank;
+        nsf.extend_from_slice(&data);
+    }
+    nsf
+}
+
+fn new_mapper(nsf: &[u8]) -> Nsf {
+    let data = parse_rom(nsf).expect("test NSF should parse");
+    let info = data.nsf().cloned().unwrap();
+    let mut mapper = Nsf::new(data, info);
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+fn read_register(mapper: &mut Nsf, address: usize, interrupt: &mut Interrupt) -> u8 {
+    let value = Cartridge::read(mapper, address).data;
+    mapper.notify_cpu_read(address, value, interrupt);
+    value
+}
+
+#[test]
+fn track_start_applies_header_banks_and_5ff8_switches_windows() {
+    let mut mapper = new_mapper(&bankswitched_nsf(0));
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x41F0, 0, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0xB0);
+    assert_eq!(Cartridge::read(&mapper, 0xB000).data, 0xB3);
+
+    mapper.write_expansion(0x5FF9, 0x02, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x9000).data, 0xB2);
+
+    // ベクタはドライバを指す
+    assert_eq!(Cartridge::read(&mapper, 0xFFFC).data, 0x00);
+    assert_eq!(Cartridge::read(&mapper, 0xFFFD).data, 0x41);
+    assert_eq!(read_register(&mut mapper, 0x41F1, &mut interrupt), 0);
+}
+
+#[test]
+fn play_timer_raises_irq_until_acknowledged() {
+    let mut mapper = new_mapper(&bankswitched_nsf(0));
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x41F0, 0, &mut interrupt);
+    mapper.write_expansion(0x41F3, 0, &mut interrupt);
+
+    let period = mapper.play_period();
+    for _ in 1..period {
+        mapper.step(&mut interrupt);
+    }
+    assert!(!interrupt.get_irq(IrqSource::NSF_PLAY));
+    mapper.step(&mut interrupt);
+    assert!(interrupt.get_irq(IrqSource::NSF_PLAY));
+
+    assert_eq!(read_register(&mut mapper, 0x41F4, &mut interrupt), 0x80);
+    assert!(!interrupt.get_irq(IrqSource::NSF_PLAY));
+    assert_eq!(read_register(&mut mapper, 0x41F4, &mut interrupt), 0x00);
+}
+
+#[test]
+fn fds_tunes_load_banks_into_writable_ram() {
+    let mut mapper = new_mapper(&bankswitched_nsf(0x04));
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x41F0, 0, &mut interrupt);
+    // $5FF6/$5FF7 にはヘッダの 7, 8 バイト目が入る
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0xB2);
+
+    mapper.write_expansion(0x5FF6, 0x01, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0xB1);
+    Cartridge::write(&mut mapper, 0x9000, 0x42, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x9000).data, 0x42);
+
+    mapper.write_expansion(0x4040, 0x3F, &mut interrupt);
+    mapper.write_expansion(0x4089, 0x80, &mut interrupt);
+    mapper.write_expansion(0x4040, 0x3F, &mut interrupt);
+    assert_eq!(read_register(&mut mapper, 0x4040, &mut interrupt), 0x3F);
+}
+
+#[test]
+fn sunsoft_5b_tunes_route_c000_e000_writes_to_audio() {
+    let mut mapper = new_mapper(&bankswitched_nsf(0x20));
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x41F0, 0, &mut interrupt);
+    assert_eq!(mapper.expansion_audio_output(), 0.0);
+
+    // 全チャンネルのトーンを無効化して A の音量だけを出す
+    for (register, value) in [(0x07, 0x3F), (0x08, 0x0F)] {
+        Cartridge::write(&mut mapper, 0xC000, register, &mut interrupt);
+        Cartridge::write(&mut mapper, 0xE000, value, &mut interrupt);
+    }
+    assert!(mapper.expansion_audio_output() > 0.0);
+    // ROM 領域はそのまま
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0xB0);
+}
+
+#[test]
+fn vrc6_tunes_route_pulse_registers_to_audio() {
+    let mut mapper = new_mapper(&bankswitched_nsf(0x01));
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x41F0, 0, &mut interrupt);
+
+    Cartridge::write(&mut mapper, 0x9000, 0x8F, &mut interrupt);
+    assert_eq!(mapper.expansion_audio_output(), 0.0);
+    Cartridge::write(&mut mapper, 0x9002, 0x80, &mut interrupt);
+    assert!(mapper.expansion_audio_output() > 0.0);
+}
+
+#[test]
+fn vrc7_tunes_route_fm_registers_to_audio() {
+    let mut mapper = new_mapper(&bankswitched_nsf(0x02));
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x41F0, 0, &mut interrupt);
+
+    for (register, value) in [(0x30, 0x10), (0x10, 0xAC), (0x20, 0x18)] {
+        Cartridge::write(&mut mapper, 0x9010, register, &mut interrupt);
+        Cartridge::write(&mut mapper, 0x9030, value, &mut interrupt);
+    }
+    assert_eq!(mapper.expansion_audio_output(), 0.0);
+    let output = (0..0x2000)
+        .map(|_| {
+            Mapper::step(&mut mapper, &mut interrupt);
+            mapper.expansion_audio_output().abs()
+        })
+        .fold(0.0, f32::max);
+    assert!(output > 0.0);
+}
+
+#[test]
+fn n163_tunes_route_ram_ports_to_audio() {
+    let mut mapper = new_mapper(&bankswitched_nsf(0x10));
+    let mut interrupt = Interrupt::new();
+    mapper.write_expansion(0x41F0, 0, &mut interrupt);
+
+    // 波形と ch8 のレジスタを自動インクリメントで書き込む
+    Cartridge::write(&mut mapper, 0xF800, 0x80, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x4800, 0xFF, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xF800, 0xF8, &mut interrupt);
+    for value in [0x00, 0x00, 0x00, 0x00, 0xFC, 0x00, 0x00, 0x0F] {
+        Cartridge::write(&mut mapper, 0x4800, value, &mut interrupt);
+    }
+    Cartridge::write(&mut mapper, 0xF800, 0xFF, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x4800).data, 0x0F);
+
+    assert_eq!(mapper.expansion_audio_output(), 0.0);
+    for _ in 0..15 {
+        Mapper::step(&mut mapper, &mut interrupt);
+    }
+    assert!(mapper.expansion_audio_output() > 0.0);
+}
@@ -0,0 +1,265 @@
+use self::irq::Rambo1Irq;
+use super::Cartridge;
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_RAMBO1},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+    ppu_memory_access::PpuBusEvent,
+};
+
+mod irq;
+
+/// $8000 のバンク選択。bit5 で CHR レジスタ 0 / 1 を 1KiB 単位にし、8 / 9 を使う。
+const CHR_1K_MODE: u8 = 0x20;
+const PRG_MODE: u8 = 0x40;
+const CHR_INVERSION: u8 = 0x80;
+/// Mapper 158 では CHR レジスタの bit7 がそのまま CIRAM A10 になる。
+const NAMETABLE_SELECT: u8 = 0x80;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Rambo1RuntimeState {
+    bank_select: u8,
+    registers: [u8; 16],
+    irq: Rambo1Irq,
+}
+
+/// Tengen RAMBO-1 (Mapper 64, 158)。MMC3 と同じ $8000 / $8001 の組で 16 本のレジスタを書き、
+/// 1KiB 単位の CHR モードと 3 つ目の切り替え PRG バンク ($F) を持つ。
+/// IRQ は A12 によるスキャンライン数と CPU サイクル数のどちらでも数えられる。
+/// Mapper 158 (TENGEN 800037) は $A000 の代わりに CHR レジスタの bit7 でネームテーブルを選ぶ。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Rambo1 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    chr_mirroring: bool,
+    bank_select: u8,
+    /// 0-5, 8, 9 が CHR、6, 7, $F が PRG。
+    registers: [u8; 16],
+    irq: Rambo1Irq,
+}
+
+#[typetag::serde]
+impl Cartridge for Rambo1 {
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_RAMBO1.into(),
+            extra_body: encode_payload(&Rambo1RuntimeState {
+                bank_select: self.bank_select,
+                registers: self.registers,
+                irq: self.irq.clone(),
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_RAMBO1 {
+            return Err(PersistenceError::Validation(
+                "unexpected RAMBO-1 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Rambo1RuntimeState = decode_payload(&state.extra_body)?;
+        self.state = state.mapper_state;
+        self.bank_select = runtime.bank_select;
+        self.registers = runtime.registers;
+        self.irq = runtime.irq;
+        Ok(())
+    }
+}
+
+impl Rambo1 {
+    pub(crate) fn new_mapper64(data: CartridgeData) -> Self {
+        Self::new(data, false)
+    }
+
+    pub(crate) fn new_mapper158(data: CartridgeData) -> Self {
+        Self::new(data, true)
+    }
+
+    fn new(data: CartridgeData, chr_mirroring: bool) -> Self {
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            chr_mirroring,
+            bank_select: 0,
+            registers: [0; 16],
+            irq: Rambo1Irq::new(),
+        }
+    }
+
+    /// PPU $0000-$1FFF の 1KiB ごとに、使う CHR レジスタとバンク番号を返す。
+    fn character_slots(&self) -> [(usize, usize); 8] {
+        let register = |index: usize| {
+            let value = self.registers[index];
+            usize::from(if self.chr_mirroring {
+                value & !NAMETABLE_SELECT
+            } else {
+                value
+            })
+        };
+        let low_half = if self.bank_select & CHR_1K_MODE != 0 {
+            [
+                (0, register(0)),
+                (8, register(8)),
+                (1, register(1)),
+                (9, register(9)),
+            ]
+        } else {
+            [
+                (0, register(0) & !0x01),
+                (0, register(0) | 0x01),
+                (1, register(1) & !0x01),
+                (1, register(1) | 0x01),
+            ]
+        };
+        let high_half = [2, 3, 4, 5].map(|index| (index, register(index)));
+        let (first, second) = if self.bank_select & CHR_INVERSION != 0 {
+            (high_half, low_half)
+        } else {
+            (low_half, high_half)
+        };
+        std::array::from_fn(|slot| {
+            if slot < 4 {
+                first[slot]
+            } else {
+                second[slot - 4]
+            }
+        })
+    }
+
+    fn update_banks(&mut self) {
+        let last_bank =
+            (self.data_ref().prog_rom_len() / self.program_page_len()).saturating_sub(1);
+        let [r6, r7, rf] = [6, 7, 15].map(|index| usize::from(self.registers[index]));
+        let program_banks = if self.bank_select & PRG_MODE != 0 {
+            [rf, r6, r7, last_bank]
+        } else {
+            [r6, r7, rf, last_bank]
+        };
+        for (slot, bank) in program_banks.into_iter().enumerate() {
+            self.change_program_page(slot, bank);
+        }
+
+        let slots = self.character_slots();
+        for (slot, (_, bank)) in slots.into_iter().enumerate() {
+            self.change_character_page(slot, bank);
+        }
+
+        if self.chr_mirroring {
+            // ネームテーブルは PPU $0000-$0FFF と同じ CHR レジスタを引く
+            self.set_mirror_mode(MirrorMode::Custom(std::array::from_fn(|table| {
+                (self.registers[slots[table].0] & NAMETABLE_SELECT) >> 7
+            })));
+        }
+    }
+}
+
+impl CartridgeDataDao for Rambo1 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Rambo1 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Rambo1 {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0400
+    }
+
+    fn initialize(&mut self) {
+        self.update_banks();
+    }
+
+    fn name(&self) -> &str {
+        if self.chr_mirroring {
+            "TENGEN 800037 (Mapper158)"
+        } else {
+            "Tengen RAMBO-1 (Mapper64)"
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        match address & 0xE001 {
+            0x8000 => {
+                self.bank_select = value;
+                self.update_banks();
+            }
+            0x8001 => {
+                self.registers[usize::from(self.bank_select & 0x0F)] = value;
+                self.update_banks();
+            }
+            0xA000
+                if !self.chr_mirroring && !matches!(self.get_mirror_mode(), MirrorMode::Four) =>
+            {
+                self.set_mirror_mode(if value & 0x01 == 0 {
+                    MirrorMode::Vertical
+                } else {
+                    MirrorMode::Horizontal
+                });
+            }
+            0xC000 => self.irq.write_latch(value),
+            0xC001 => self.irq.write_mode(value),
+            0xE000 => self.irq.write_disable(interrupt),
+            0xE001 => self.irq.write_enable(),
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        self.irq.step(interrupt);
+    }
+
+    fn cycles_until_next_cpu_event(&self) -> u64 {
+        self.irq.cycles_until_irq()
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+
+    fn notify_ppu_bus_event(&mut self, event: PpuBusEvent, interrupt: &mut Interrupt) {
+        let PpuBusEvent::AddressBusUpdate {
+            address, ppu_tick, ..
+        } = event;
+        self.irq.on_address_bus_update(address, ppu_tick, interrupt);
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,162 @@
+use crate::interrupt::{Interrupt, IrqSource};
+
+/// MMC3 と同じく、A12 が Low の期間がこれより短い立ち上がりは無視する。
+const A12_LOW_FILTER_TICKS: u64 = 9;
+/// CPU サイクルモードでは 4 サイクルごとにカウンタを進める。
+const CYCLE_PRESCALER: u8 = 4;
+
+/// RAMBO-1 の IRQ カウンタ。A12 の立ち上がり (スキャンラインモード) か
+/// CPU 4 サイクル (CPU サイクルモード) で減り、0 になったときに IRQ を出す。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub(super) struct Rambo1Irq {
+    latch: u8,
+    /// $C001 の書き込み後、次のクロックでラッチから読み込み直す。
+    reload: bool,
+    /// ラッチ + 2 まで入るので 8bit に収まらない。
+    counter: u16,
+    enabled: bool,
+    cycle_mode: bool,
+    prescaler: u8,
+    last_a12_high: bool,
+    last_a12_low_tick: u64,
+}
+
+impl Rambo1Irq {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn write_latch(&mut self, value: u8) {
+        self.latch = value;
+    }
+
+    /// $C001。bit0 でモードを選び、カウンタの再読み込みを予約する。
+    pub(super) fn write_mode(&mut self, value: u8) {
+        self.cycle_mode = value & 0x01 != 0;
+        if self.cycle_mode {
+            self.prescaler = 0;
+        }
+        self.reload = true;
+    }
+
+    pub(super) fn write_disable(&mut self, interrupt: &mut Interrupt) {
+        self.enabled = false;
+        interrupt.clear_irq(IrqSource::EXTERNAL);
+    }
+
+    pub(super) fn write_enable(&mut self) {
+        self.enabled = true;
+    }
+
+    /// CPU 1 サイクル分進める。
+    pub(super) fn step(&mut self, interrupt: &mut Interrupt) {
+        if !self.cycle_mode {
+            return;
+        }
+        self.prescaler = (self.prescaler + 1) % CYCLE_PRESCALER;
+        if self.prescaler == 0 {
+            self.clock(interrupt);
+        }
+    }
+
+    /// 次に IRQ が立つまでの CPU サイクル数の下限。CPU サイクルモード以外では `u64::MAX`。
+    pub(super) fn cycles_until_irq(&self) -> u64 {
+        if !self.cycle_mode || !self.enabled {
+            return u64::MAX;
+        }
+        let clocks = if self.reload || self.counter == 0 {
+            self.reload_value()
+        } else {
+            self.counter
+        };
+        let first = u64::from(CYCLE_PRESCALER - self.prescaler);
+        first + (u64::from(clocks) - 1) * u64::from(CYCLE_PRESCALER)
+    }
+
+    pub(super) fn on_address_bus_update(
+        &mut self,
+        address: usize,
+        ppu_tick: u64,
+        interrupt: &mut Interrupt,
+    ) {
+        let a12_high = (address & 0x1000) != 0;
+        if a12_high {
+            if !self.last_a12_high
+                && !self.cycle_mode
+                && ppu_tick.saturating_sub(self.last_a12_low_tick) >= A12_LOW_FILTER_TICKS
+            {
+                self.clock(interrupt);
+            }
+        } else if self.last_a12_high {
+            self.last_a12_low_tick = ppu_tick;
+        }
+        self.last_a12_high = a12_high;
+    }
+
+    /// 再読み込みした直後のクロックで減る分を含めたカウンタの初期値。
+    /// $C001 直後はラッチが 2 以上なら 1 多く数える (Hard Drivin' などが頼る挙動)。
+    fn reload_value(&self) -> u16 {
+        let latch = u16::from(self.latch);
+        if self.reload && latch > 1 {
+            latch + 2
+        } else {
+            latch + 1
+        }
+    }
+
+    fn clock(&mut self, interrupt: &mut Interrupt) {
+        if self.reload || self.counter == 0 {
+            self.counter = self.reload_value();
+            self.reload = false;
+        }
+        self.counter -= 1;
+        if self.counter == 0 && self.enabled {
+            interrupt.set_irq(IrqSource::EXTERNAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rambo1Irq;
+    use crate::interrupt::{Interrupt, IrqSource};
+
+    #[test]
+    fn cycle_mode_counts_every_four_cpu_cycles() {
+        let mut irq = Rambo1Irq::new();
+        let mut interrupt = Interrupt::new();
+        irq.write_latch(0x02);
+        irq.write_mode(0x01);
+        irq.write_enable();
+        // 再読み込み直後はラッチ + 1 回分のクロックで 0 になる
+        assert_eq!(irq.cycles_until_irq(), 16);
+
+        for _ in 0..15 {
+            irq.step(&mut interrupt);
+        }
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+        irq.step(&mut interrupt);
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+
+        irq.write_disable(&mut interrupt);
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+        assert_eq!(irq.cycles_until_irq(), u64::MAX);
+    }
+
+    #[test]
+    fn small_latch_reloads_without_the_extra_clock() {
+        let mut irq = Rambo1Irq::new();
+        let mut interrupt = Interrupt::new();
+        irq.write_latch(0x01);
+        irq.write_mode(0x01);
+        irq.write_enable();
+        assert_eq!(irq.cycles_until_irq(), 8);
+
+        for _ in 0..8 {
+            irq.step(&mut interrupt);
+        }
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+        // 以降はラッチ + 1 クロックごと
+        assert_eq!(irq.cycles_until_irq(), 8);
+    }
+}
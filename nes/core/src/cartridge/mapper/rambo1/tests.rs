@@ -0,0 +1,182 @@
+use super::{CHR_1K_MODE, Cartridge, Rambo1};
+use crate::{
+    cartridge_data_parts::CartridgeDataParts,
+    cartridge_rom::CartridgeData,
+    interrupt::{Interrupt, IrqSource},
+    mapper::Mapper,
+    mirror::MirrorMode,
+    ppu_memory_access::{PpuBusAccess, PpuBusEvent},
+    rom_format::RomFormat,
+};
+
+/// PRG は 8KiB、CHR は 1KiB ごとにバンク番号で埋める。
+fn test_data(mapper_type: u16) -> CartridgeData {
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::INes,
+        prog_rom: (0..0x20000).map(|i| (i / 0x2000) as u8).collect(),
+        char_rom: (0..0x20000).map(|i| (i / 0x0400) as u8).collect(),
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0,
+        save_vram_length: 0,
+        mapper_type,
+        mirror_mode: MirrorMode::Vertical,
+        has_battery: false,
+        sub_mapper_type: 0,
+        trainer: Vec::new(),
+        console_type: None,
+    })
+    .expect("test cartridge data should be valid")
+}
+
+fn new_mapper64() -> Rambo1 {
+    let mut mapper = Rambo1::new_mapper64(test_data(64));
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+fn write_bank(mapper: &mut Rambo1, select: u8, value: u8) {
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(mapper, 0x8000, select, &mut interrupt);
+    Cartridge::write(mapper, 0x8001, value, &mut interrupt);
+}
+
+fn chr_banks(mapper: &Rambo1) -> [u8; 8] {
+    std::array::from_fn(|slot| Cartridge::read(mapper, slot * 0x0400).data)
+}
+
+fn prg_banks(mapper: &Rambo1) -> [u8; 4] {
+    std::array::from_fn(|slot| Cartridge::read(mapper, 0x8000 + slot * 0x2000).data)
+}
+
+fn a12_rise(mapper: &mut Rambo1, tick: u64, interrupt: &mut Interrupt) {
+    for (address, ppu_tick) in [(0x0000, tick), (0x1000, tick + 10)] {
+        mapper.notify_ppu_bus_event(
+            PpuBusEvent::AddressBusUpdate {
+                address,
+                ppu_tick,
+                from_cpu_register: false,
+                access: PpuBusAccess::Read,
+            },
+            interrupt,
+        );
+    }
+}
+
+#[test]
+fn third_prg_bank_follows_prg_mode() {
+    let mut mapper = new_mapper64();
+    write_bank(&mut mapper, 0x06, 0x03);
+    write_bank(&mut mapper, 0x07, 0x05);
+    write_bank(&mut mapper, 0x0F, 0x09);
+    assert_eq!(prg_banks(&mapper), [0x03, 0x05, 0x09, 0x0F]);
+
+    write_bank(&mut mapper, 0x40, 0x00);
+    assert_eq!(prg_banks(&mapper), [0x09, 0x03, 0x05, 0x0F]);
+}
+
+#[test]
+fn one_kib_chr_mode_uses_registers_8_and_9() {
+    let mut mapper = new_mapper64();
+    for (register, value) in [(0, 0x10), (1, 0x21), (2, 2), (3, 3), (4, 4), (5, 5)] {
+        write_bank(&mut mapper, register, value);
+    }
+    write_bank(&mut mapper, 0x08, 0x30);
+    write_bank(&mut mapper, 0x09, 0x31);
+    assert_eq!(chr_banks(&mapper), [0x10, 0x11, 0x20, 0x21, 2, 3, 4, 5]);
+
+    write_bank(&mut mapper, CHR_1K_MODE, 0x10);
+    assert_eq!(chr_banks(&mapper), [0x10, 0x30, 0x21, 0x31, 2, 3, 4, 5]);
+
+    write_bank(&mut mapper, CHR_1K_MODE | 0x80, 0x10);
+    assert_eq!(chr_banks(&mapper), [2, 3, 4, 5, 0x10, 0x30, 0x21, 0x31]);
+}
+
+#[test]
+fn a000_selects_mirroring_on_mapper64() {
+    let mut mapper = new_mapper64();
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xA000, 0x01, &mut interrupt);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+    Cartridge::write(&mut mapper, 0xA000, 0x00, &mut interrupt);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Vertical);
+}
+
+#[test]
+fn mapper158_selects_nametables_with_chr_bit7() {
+    let mut mapper = Rambo1::new_mapper158(test_data(158));
+    Cartridge::initialize(&mut mapper);
+    write_bank(&mut mapper, 0x00, 0x80);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Custom([1, 1, 0, 0]));
+    assert_eq!(chr_banks(&mapper)[0..2], [0x00, 0x01]);
+
+    // $A000 は無視され、1KiB モードでは R8 / R9 も個別に効く
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xA000, 0x01, &mut interrupt);
+    write_bank(&mut mapper, CHR_1K_MODE | 0x09, 0x85);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Custom([1, 0, 0, 1]));
+
+    // CHR 反転時は R2-R5 が PPU $0000-$0FFF 側になる
+    write_bank(&mut mapper, 0x80 | 0x04, 0x80);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Custom([0, 0, 1, 0]));
+}
+
+#[test]
+fn scanline_mode_clocks_on_filtered_a12_rises() {
+    let mut mapper = new_mapper64();
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xC000, 0x01, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xC001, 0x00, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xE001, 0x00, &mut interrupt);
+    assert_eq!(mapper.cycles_until_next_cpu_event(), u64::MAX);
+
+    a12_rise(&mut mapper, 0, &mut interrupt);
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    a12_rise(&mut mapper, 100, &mut interrupt);
+    assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+
+    Cartridge::write(&mut mapper, 0xE000, 0x00, &mut interrupt);
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+}
+
+#[test]
+fn cycle_mode_ignores_a12_and_counts_cpu_cycles() {
+    let mut mapper = new_mapper64();
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xC000, 0x03, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xC001, 0x01, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xE001, 0x00, &mut interrupt);
+    for tick in 0..8 {
+        a12_rise(&mut mapper, tick * 100, &mut interrupt);
+    }
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+
+    let cycles = mapper.cycles_until_next_cpu_event();
+    assert_eq!(cycles, 20);
+    mapper.step_cpu_cycles(cycles - 1, &mut interrupt);
+    assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    mapper.step_cpu_cycles(1, &mut interrupt);
+    assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+}
+
+#[test]
+fn banks_and_irq_round_trip_through_runtime_state() {
+    let mut mapper = new_mapper64();
+    let mut interrupt = Interrupt::new();
+    write_bank(&mut mapper, 0x0F, 0x07);
+    write_bank(&mut mapper, CHR_1K_MODE | 0x08, 0x44);
+    Cartridge::write(&mut mapper, 0xC000, 0x10, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xC001, 0x01, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xE001, 0x00, &mut interrupt);
+    mapper.step_cpu_cycles(6, &mut interrupt);
+
+    let state = mapper.export_runtime_state().unwrap();
+    let mut restored = new_mapper64();
+    restored.import_runtime_state(state).unwrap();
+    assert_eq!(prg_banks(&restored)[2], 0x07);
+    assert_eq!(chr_banks(&restored)[1], 0x44);
+    assert_eq!(
+        restored.cycles_until_next_cpu_event(),
+        mapper.cycles_until_next_cpu_event()
+    );
+}
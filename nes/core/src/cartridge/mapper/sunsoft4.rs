@@ -0,0 +1,325 @@
+use nerust_input_traits::OpenBusReadResult;
+
+use super::Cartridge;
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_SUNSOFT4},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+    ppu_memory_access::PpuReadAccess,
+};
+
+/// $E000 の bit4。ネームテーブルを CIRAM の代わりに CHR-ROM から読む。
+const CHR_NAMETABLES: u8 = 0x10;
+/// $C000 / $D000 の値は CHR-ROM の後半 128KiB の 1KiB バンクを指す。
+const NAMETABLE_BANK_BASE: u8 = 0x80;
+/// $F000 の bit3 が 0 で外部 ROM (Nantettatte!! Baseball のオプション ROM) を選ぶ。
+const INTERNAL_PRG: u8 = 0x08;
+const PRG_RAM_ENABLE: u8 = 0x10;
+/// 内蔵 ROM は 16KiB × 8 バンクまで。
+const INTERNAL_PRG_BANKS: usize = 8;
+/// $6000-$7FFF への最後の書き込みから外部 ROM が読める CPU サイクル数 (1024 × 105)。
+const LICENSING_TIMER_CYCLES: u32 = 107_520;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Sunsoft4RuntimeState {
+    chr_banks: [u8; 4],
+    nametable_banks: [u8; 2],
+    control: u8,
+    prg_bank: u8,
+    licensing_timer: u32,
+}
+
+/// Sunsoft-4 (Mapper 68)。16KiB の PRG 切り替えと 2KiB 単位の CHR 切り替えを持ち、
+/// ネームテーブルに CHR-ROM の 1KiB バンクを割り当てられる。
+/// PRG-RAM は $F000 の bit4 で有効になる。外部 ROM を持つ基板では、$6000-$7FFF への書き込みで
+/// ライセンスタイマーが動き出し、動いている間だけ $8000-$BFFF に外部 ROM が見える。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Sunsoft4 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    chr_banks: [u8; 4],
+    nametable_banks: [u8; 2],
+    /// $E000。bit0-1 がミラーリング、bit4 が CHR-ROM ネームテーブル。
+    control: u8,
+    /// $F000。
+    prg_bank: u8,
+    licensing_timer: u32,
+}
+
+#[typetag::serde]
+impl Cartridge for Sunsoft4 {
+    fn read_program(&self, address: usize) -> OpenBusReadResult {
+        if address < 0x4000 && self.using_external_rom() && self.licensing_timer == 0 {
+            return OpenBusReadResult::new(0, 0);
+        }
+        OpenBusReadResult::new(
+            self.program_address(address)
+                .map(|x| self.data_ref().read_prog_rom(x))
+                .unwrap_or(0),
+            0xFF,
+        )
+    }
+
+    fn write_ram(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        self.licensing_timer = LICENSING_TIMER_CYCLES;
+        Mapper::write_ram(self, address - 0x6000, value);
+    }
+
+    fn read_ppu_nametable(
+        &mut self,
+        address: usize,
+        _access: PpuReadAccess,
+        ciram: &mut [u8],
+    ) -> OpenBusReadResult {
+        OpenBusReadResult::new(self.nametable_byte(address, ciram), 0xFF)
+    }
+
+    fn write_ppu_nametable(
+        &mut self,
+        address: usize,
+        value: u8,
+        ciram: &mut [u8],
+        _interrupt: &mut Interrupt,
+    ) {
+        // CHR-ROM を割り当てたネームテーブルには書けない
+        if let Some(index) = self.ciram_index(address) {
+            ciram[index] = value;
+        }
+    }
+
+    fn peek_ppu_nametable(&self, address: usize, ciram: &[u8]) -> Option<u8> {
+        Some(self.nametable_byte(address, ciram))
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_SUNSOFT4.into(),
+            extra_body: encode_payload(&Sunsoft4RuntimeState {
+                chr_banks: self.chr_banks,
+                nametable_banks: self.nametable_banks,
+                control: self.control,
+                prg_bank: self.prg_bank,
+                licensing_timer: self.licensing_timer,
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_SUNSOFT4 {
+            return Err(PersistenceError::Validation(
+                "unexpected Sunsoft-4 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Sunsoft4RuntimeState = decode_payload(&state.extra_body)?;
+        self.state = state.mapper_state;
+        self.chr_banks = runtime.chr_banks;
+        self.nametable_banks = runtime.nametable_banks;
+        self.control = runtime.control;
+        self.prg_bank = runtime.prg_bank;
+        self.licensing_timer = runtime.licensing_timer;
+        Ok(())
+    }
+}
+
+impl Sunsoft4 {
+    pub(crate) fn new(data: CartridgeData) -> Self {
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            chr_banks: [0; 4],
+            nametable_banks: [0; 2],
+            control: 0,
+            prg_bank: INTERNAL_PRG,
+            licensing_timer: 0,
+        }
+    }
+
+    fn has_external_rom(&self) -> bool {
+        self.data_ref().prog_rom_len() / self.program_page_len() > INTERNAL_PRG_BANKS
+    }
+
+    fn using_external_rom(&self) -> bool {
+        self.has_external_rom() && self.prg_bank & INTERNAL_PRG == 0
+    }
+
+    fn update_prg_banks(&mut self) {
+        let bank = usize::from(self.prg_bank & 0x07);
+        let bank = if self.using_external_rom() {
+            INTERNAL_PRG_BANKS | bank
+        } else {
+            bank
+        };
+        self.change_program_page(0, bank);
+    }
+
+    fn update_mirroring(&mut self) {
+        self.set_mirror_mode(match self.control & 0x03 {
+            0 => MirrorMode::Vertical,
+            1 => MirrorMode::Horizontal,
+            2 => MirrorMode::Single0,
+            _ => MirrorMode::Single1,
+        });
+    }
+
+    /// ネームテーブル $2000 / $2400 / $2800 / $2C00 が使う $C000 / $D000 の番号。
+    /// CIRAM を使う場合も同じ番号が CIRAM A10 になる。
+    fn nametable_register(&self, address: usize) -> usize {
+        let table = (address >> 10) & 0x03;
+        match self.control & 0x03 {
+            0 => table & 0x01,
+            1 => table >> 1,
+            2 => 0,
+            _ => 1,
+        }
+    }
+
+    fn ciram_index(&self, address: usize) -> Option<usize> {
+        (self.control & CHR_NAMETABLES == 0)
+            .then(|| (self.nametable_register(address) << 10) | (address & 0x03FF))
+    }
+
+    fn nametable_byte(&self, address: usize, ciram: &[u8]) -> u8 {
+        if let Some(index) = self.ciram_index(address) {
+            return ciram[index];
+        }
+        let char_rom_len = self.data_ref().char_rom_len();
+        if char_rom_len == 0 {
+            return 0;
+        }
+        let bank = usize::from(
+            self.nametable_banks[self.nametable_register(address)] | NAMETABLE_BANK_BASE,
+        );
+        self.data_ref()
+            .read_char_rom(((bank << 10) | (address & 0x03FF)) % char_rom_len)
+    }
+}
+
+impl CartridgeDataDao for Sunsoft4 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Sunsoft4 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Sunsoft4 {
+    fn program_page_len(&self) -> usize {
+        0x4000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0800
+    }
+
+    fn initialize(&mut self) {
+        let internal_banks =
+            (self.data_ref().prog_rom_len() / self.program_page_len()).min(INTERNAL_PRG_BANKS);
+        self.change_program_page(1, internal_banks.saturating_sub(1));
+        self.update_prg_banks();
+        for (slot, bank) in self.chr_banks.into_iter().enumerate() {
+            self.change_character_page(slot, usize::from(bank));
+        }
+        self.change_ram_page(0, 0);
+        // $E000 を書くまではヘッダのミラーリングに合わせる
+        self.control = match self.data_ref().mirror_mode() {
+            MirrorMode::Horizontal => 1,
+            MirrorMode::Single0 => 2,
+            MirrorMode::Single1 => 3,
+            _ => 0,
+        };
+        self.update_mirroring();
+    }
+
+    fn name(&self) -> &str {
+        "Sunsoft-4 (Mapper68)"
+    }
+
+    fn ram_len_default(&self) -> usize {
+        0x2000
+    }
+
+    fn read_ram(&self, index: usize) -> Option<u8> {
+        if self.prg_bank & PRG_RAM_ENABLE == 0 {
+            return None;
+        }
+        self.ram_address(index)
+            .map(|x| self.mapper_state_ref().sram[x])
+    }
+
+    fn write_ram(&mut self, index: usize, data: u8) {
+        if self.prg_bank & PRG_RAM_ENABLE != 0
+            && let Some(address) = self.ram_address(index)
+        {
+            self.mapper_state_mut().sram[address] = data;
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        match address & 0xF000 {
+            0x8000..=0xB000 => {
+                let slot = (address >> 12) & 0x03;
+                self.chr_banks[slot] = value;
+                self.change_character_page(slot, usize::from(value));
+            }
+            0xC000 => self.nametable_banks[0] = value,
+            0xD000 => self.nametable_banks[1] = value,
+            0xE000 => {
+                self.control = value;
+                self.update_mirroring();
+            }
+            _ => {
+                self.prg_bank = value;
+                self.update_prg_banks();
+            }
+        }
+    }
+
+    fn step(&mut self, _interrupt: &mut Interrupt) {
+        self.licensing_timer = self.licensing_timer.saturating_sub(1);
+    }
+
+    fn step_cpu_cycles(&mut self, cycles: u64, _interrupt: &mut Interrupt) {
+        self.licensing_timer = self
+            .licensing_timer
+            .saturating_sub(u32::try_from(cycles).unwrap_or(u32::MAX));
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
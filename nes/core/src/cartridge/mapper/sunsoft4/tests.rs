@@ -0,0 +1,135 @@
+use super::{Cartridge, LICENSING_TIMER_CYCLES, Sunsoft4};
+use crate::{
+    cartridge_data_parts::CartridgeDataParts, cartridge_rom::CartridgeData, interrupt::Interrupt,
+    mapper::Mapper, mirror::MirrorMode, ppu_memory_access::PpuReadAccess, rom_format::RomFormat,
+};
+
+/// PRG は 16KiB、CHR は 1KiB ごとにバンク番号で埋める。
+fn test_data(prg_len: usize) -> CartridgeData {
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::INes,
+        prog_rom: (0..prg_len).map(|i| (i / 0x4000) as u8).collect(),
+        char_rom: (0..0x40000).map(|i| (i / 0x0400) as u8).collect(),
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0,
+        save_vram_length: 0,
+        mapper_type: 68,
+        mirror_mode: MirrorMode::Horizontal,
+        has_battery: false,
+        sub_mapper_type: 0,
+        trainer: Vec::new(),
+        console_type: None,
+    })
+    .expect("test cartridge data should be valid")
+}
+
+fn new_mapper(prg_len: usize) -> Sunsoft4 {
+    let mut mapper = Sunsoft4::new(test_data(prg_len));
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+#[test]
+fn switches_prg_and_two_kib_chr_banks() {
+    let mut mapper = new_mapper(0x20000);
+    let mut interrupt = Interrupt::new();
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x07);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+
+    Cartridge::write(&mut mapper, 0xF000, 0x0B, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x03);
+    for (address, bank) in [
+        (0x8000, 0x01),
+        (0x9000, 0x02),
+        (0xA000, 0x10),
+        (0xB000, 0x7F),
+    ] {
+        Cartridge::write(&mut mapper, address, bank, &mut interrupt);
+    }
+    let chr: Vec<u8> = (0..8)
+        .map(|slot| Cartridge::read(&mapper, slot * 0x0400).data)
+        .collect();
+    assert_eq!(chr, [0x02, 0x03, 0x04, 0x05, 0x20, 0x21, 0xFE, 0xFF]);
+}
+
+#[test]
+fn prg_ram_is_gated_by_f000_bit4() {
+    let mut mapper = new_mapper(0x20000);
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x6000, 0x12, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).mask, 0);
+
+    Cartridge::write(&mut mapper, 0xF000, 0x18, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x6000, 0x34, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x34);
+    Cartridge::write(&mut mapper, 0xF000, 0x08, &mut interrupt);
+    assert_eq!(Mapper::read_ram(&mapper, 0x0000), None);
+}
+
+#[test]
+fn chr_rom_nametables_follow_mirroring_and_ignore_writes() {
+    let mut mapper = new_mapper(0x20000);
+    let mut interrupt = Interrupt::new();
+    let mut ciram = vec![0x55; 0x800];
+    Cartridge::write(&mut mapper, 0xC000, 0x01, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xD000, 0x02, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xE000, 0x10, &mut interrupt);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Vertical);
+
+    let read = |mapper: &mut Sunsoft4, address: usize, ciram: &mut [u8]| {
+        mapper
+            .read_ppu_nametable(address, PpuReadAccess::BackgroundNameTable, ciram)
+            .data
+    };
+    assert_eq!(read(&mut mapper, 0x2000, &mut ciram), 0x81);
+    assert_eq!(read(&mut mapper, 0x2400, &mut ciram), 0x82);
+    assert_eq!(read(&mut mapper, 0x2800, &mut ciram), 0x81);
+    mapper.write_ppu_nametable(0x2000, 0x00, &mut ciram, &mut interrupt);
+    assert!(ciram.iter().all(|&byte| byte == 0x55));
+
+    // CIRAM に戻すと同じミラーリングで内部 VRAM を使う
+    Cartridge::write(&mut mapper, 0xE000, 0x01, &mut interrupt);
+    mapper.write_ppu_nametable(0x2C00, 0x66, &mut ciram, &mut interrupt);
+    assert_eq!(ciram[0x400], 0x66);
+    assert_eq!(mapper.peek_ppu_nametable(0x2800, &ciram), Some(0x66));
+}
+
+#[test]
+fn licensing_timer_enables_the_external_rom() {
+    let mut mapper = new_mapper(0x40000);
+    let mut interrupt = Interrupt::new();
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x07);
+
+    Cartridge::write(&mut mapper, 0xF000, 0x02, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).mask, 0);
+
+    Cartridge::write(&mut mapper, 0x6000, 0x00, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x0A);
+    mapper.step_cpu_cycles(u64::from(LICENSING_TIMER_CYCLES) - 1, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x0A);
+    mapper.step_cpu_cycles(1, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).mask, 0);
+
+    // 内蔵 ROM はタイマーに関係なく読める
+    Cartridge::write(&mut mapper, 0xF000, 0x0A, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x02);
+}
+
+#[test]
+fn registers_round_trip_through_runtime_state() {
+    let mut mapper = new_mapper(0x40000);
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xF000, 0x15, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xC000, 0x03, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xE000, 0x12, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x6000, 0x9A, &mut interrupt);
+
+    let state = mapper.export_runtime_state().unwrap();
+    let mut restored = new_mapper(0x40000);
+    restored.import_runtime_state(state).unwrap();
+    assert_eq!(Cartridge::read(&restored, 0x8000).data, 0x0D);
+    assert_eq!(Cartridge::read(&restored, 0x6000).data, 0x9A);
+    assert_eq!(restored.mirror_mode(), MirrorMode::Single0);
+    assert_eq!(restored.peek_ppu_nametable(0x2C00, &[0; 0x800]), Some(0x83));
+}
@@ -352,6 +352,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
         let mut mapper = SxRom::new(data);
@@ -414,6 +415,7 @@ mod tests {
             has_battery: true,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
         let mut source = SxRom::new(data.clone());
@@ -456,6 +458,7 @@ mod tests {
             has_battery: true,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
         let mut source = SxRom::new(data.clone());
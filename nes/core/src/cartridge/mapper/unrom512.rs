@@ -0,0 +1,263 @@
+use nerust_input_traits::OpenBusReadResult;
+
+use self::flash::{Command, FlashRom};
+use super::Cartridge;
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_UNROM512},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+mod flash;
+
+/// ヘッダのミラーリングが 1 画面のとき、bit7 で画面を選ぶ。
+const ONE_SCREEN_SELECT: u8 = 0x80;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Unrom512RuntimeState {
+    bank: u8,
+    flash_command: Command,
+    flash_id_mode: bool,
+    /// 書き換えたセクタ (`FlashRom::export_modified_sectors` の形式)。
+    flash_sectors: Vec<u8>,
+}
+
+/// UNROM 512 (Mapper 30)。$C000-$FFFF は最終バンク固定で、$8000-$BFFF の 16KiB と
+/// 32KiB の CHR-RAM のうち 8KiB を切り替える。
+/// 電池フラグの立った基板は PRG が SST39SF040 で、$8000-$BFFF への書き込みでフラッシュを書き換えられる。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Unrom512 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    /// PRG (bit0-4)、CHR-RAM (bit5-6)、1 画面ミラーリング (bit7)。
+    bank: u8,
+    flash: Option<FlashRom>,
+}
+
+#[typetag::serde]
+impl Cartridge for Unrom512 {
+    fn read_program(&self, address: usize) -> OpenBusReadResult {
+        OpenBusReadResult::new(
+            self.program_address(address)
+                .map(|x| match &self.flash {
+                    Some(flash) => flash.read(x),
+                    None => self.data_ref().read_prog_rom(x),
+                })
+                .unwrap_or(0),
+            0xFF,
+        )
+    }
+
+    fn write_program(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        if self.flash.is_none() {
+            // フラッシュの無い基板はバスコンフリクトを起こす
+            let value = self.read_program(address - 0x8000).data & value;
+            self.write_register(address, value, interrupt);
+        } else if address >= 0xC000 {
+            self.write_register(address, value, interrupt);
+        } else if let Some(flash_address) = self.program_address(address - 0x8000)
+            && let Some(flash) = &mut self.flash
+        {
+            flash.write(flash_address, value);
+        }
+    }
+
+    fn poke_program_rom(&mut self, index: usize, value: u8) -> bool {
+        self.data_mut().write_prog_rom(index, value);
+        if let Some(flash) = &mut self.flash {
+            flash.poke(index, value);
+        }
+        true
+    }
+
+    // 書き換えたセクタだけを保存する
+    fn persistent_mapper_save_lengths(&self) -> (usize, usize) {
+        match &self.flash {
+            Some(flash) => (flash.modified_sectors_len(), 0),
+            None => (0, 0),
+        }
+    }
+
+    fn export_mapper_save_state(&self) -> Result<(Vec<u8>, Vec<u8>), PersistenceError> {
+        Ok((
+            self.flash
+                .as_ref()
+                .map(FlashRom::export_modified_sectors)
+                .unwrap_or_default(),
+            Vec::new(),
+        ))
+    }
+
+    fn import_mapper_save_state(
+        &mut self,
+        prg_ram: &[u8],
+        chr_ram: &[u8],
+    ) -> Result<(), PersistenceError> {
+        let Some(flash) = &mut self.flash else {
+            return Err(PersistenceError::Validation(
+                "cartridge does not expose persistent mapper save memory".into(),
+            ));
+        };
+        if !chr_ram.is_empty() {
+            return Err(PersistenceError::Validation(
+                "persistent mapper memory length mismatch".into(),
+            ));
+        }
+        flash
+            .import_modified_sectors(self.cartridge_data.prog_rom(), prg_ram)
+            .map_err(PersistenceError::Validation)
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_UNROM512.into(),
+            extra_body: encode_payload(&Unrom512RuntimeState {
+                bank: self.bank,
+                flash_command: self
+                    .flash
+                    .as_ref()
+                    .map_or(Command::Ready, FlashRom::command),
+                flash_id_mode: self.flash.as_ref().is_some_and(FlashRom::id_mode),
+                flash_sectors: self
+                    .flash
+                    .as_ref()
+                    .map(FlashRom::export_modified_sectors)
+                    .unwrap_or_default(),
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_UNROM512 {
+            return Err(PersistenceError::Validation(
+                "unexpected UNROM 512 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Unrom512RuntimeState = decode_payload(&state.extra_body)?;
+        match &mut self.flash {
+            Some(flash) => {
+                let mut restored = flash.clone();
+                restored
+                    .import_modified_sectors(self.cartridge_data.prog_rom(), &runtime.flash_sectors)
+                    .map_err(PersistenceError::Validation)?;
+                restored.set_command_state(runtime.flash_command, runtime.flash_id_mode);
+                *flash = restored;
+            }
+            None if !runtime.flash_sectors.is_empty() => {
+                return Err(PersistenceError::Validation(
+                    "UNROM 512 flash state mismatch".into(),
+                ));
+            }
+            None => {}
+        }
+        self.state = state.mapper_state;
+        self.bank = runtime.bank;
+        Ok(())
+    }
+}
+
+impl Unrom512 {
+    pub(crate) fn new(data: CartridgeData) -> Self {
+        let flash = data.has_battery().then(|| FlashRom::new(data.prog_rom()));
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            bank: 0,
+            flash,
+        }
+    }
+
+    fn update_banks(&mut self) {
+        self.change_program_page(0, usize::from(self.bank & 0x1F));
+        self.change_character_page(0, usize::from((self.bank >> 5) & 0x03));
+        // ヘッダの 1 画面ミラーリングは切り替え可能な 1 画面を表す
+        if self.data_ref().mirror_mode() == MirrorMode::Single0 {
+            self.set_mirror_mode(if self.bank & ONE_SCREEN_SELECT != 0 {
+                MirrorMode::Single1
+            } else {
+                MirrorMode::Single0
+            });
+        }
+    }
+}
+
+impl CartridgeDataDao for Unrom512 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Unrom512 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Unrom512 {
+    fn program_page_len(&self) -> usize {
+        0x4000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_ram_page_len_default(&self) -> usize {
+        0x8000
+    }
+
+    fn initialize(&mut self) {
+        let last_bank =
+            (self.data_ref().prog_rom_len() / self.program_page_len()).saturating_sub(1);
+        self.change_program_page(1, last_bank);
+        // ヘッダの Single1 (4 画面ビットと垂直ビット) は基板上の 4 画面 RAM を表す
+        if self.data_ref().mirror_mode() == MirrorMode::Single1 {
+            self.set_mirror_mode(MirrorMode::Four);
+        }
+        self.update_banks();
+    }
+
+    fn name(&self) -> &str {
+        "UNROM 512 (Mapper30)"
+    }
+
+    fn write_register(&mut self, _address: usize, value: u8, _interrupt: &mut Interrupt) {
+        self.bank = value;
+        self.update_banks();
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests;
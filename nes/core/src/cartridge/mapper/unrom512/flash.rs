@@ -0,0 +1,261 @@
+/// 消去の最小単位。
+pub(crate) const SECTOR_LEN: usize = 0x1000;
+const MANUFACTURER_ID: u8 = 0xBF;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Command {
+    Ready,
+    /// $5555 に $AA を受け取った。
+    Unlock1,
+    /// 続けて $2AAA に $55 を受け取った。
+    Unlock2,
+    /// $A0 の後、次の書き込みで 1 バイト書き込む。
+    Program,
+    /// $80 の後の 2 回目のアンロックを待つ。
+    Erase,
+    EraseUnlock1,
+    EraseUnlock2,
+}
+
+/// SST39SF010/020/040 互換のフラッシュ ROM。書き込みと消去は即座に完了する。
+/// 保存用に 4KiB セクタ単位で ROM から書き換わった場所を覚えておく。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(crate) struct FlashRom {
+    data: Vec<u8>,
+    dirty_sectors: Vec<bool>,
+    command: Command,
+    id_mode: bool,
+}
+
+impl FlashRom {
+    pub(crate) fn new(rom: &[u8]) -> Self {
+        Self {
+            data: rom.to_vec(),
+            dirty_sectors: vec![false; rom.len().div_ceil(SECTOR_LEN)],
+            command: Command::Ready,
+            id_mode: false,
+        }
+    }
+
+    pub(crate) fn command(&self) -> Command {
+        self.command
+    }
+
+    pub(crate) fn id_mode(&self) -> bool {
+        self.id_mode
+    }
+
+    pub(crate) fn set_command_state(&mut self, command: Command, id_mode: bool) {
+        self.command = command;
+        self.id_mode = id_mode;
+    }
+
+    pub(crate) fn read(&self, address: usize) -> u8 {
+        if self.id_mode {
+            return match address & 0x01 {
+                0 => MANUFACTURER_ID,
+                _ => self.device_id(),
+            };
+        }
+        self.data[address % self.data.len()]
+    }
+
+    /// 容量から SST39SF010 / 020 / 040 のデバイス ID を選ぶ。
+    fn device_id(&self) -> u8 {
+        match self.data.len() {
+            0..=0x20000 => 0xB5,
+            0x20001..=0x40000 => 0xB6,
+            _ => 0xB7,
+        }
+    }
+
+    /// `address` はフラッシュ上のアドレス。コマンドは下位 15bit で判定する。
+    pub(crate) fn write(&mut self, address: usize, value: u8) {
+        if self.command == Command::Program {
+            self.program(address, value);
+            self.command = Command::Ready;
+            return;
+        }
+        if value == 0xF0 {
+            // ID モードの終了は 1 バイトのコマンドでも受け付ける
+            self.id_mode = false;
+            self.command = Command::Ready;
+            return;
+        }
+        self.command = match (self.command, address & 0x7FFF, value) {
+            (Command::Ready, 0x5555, 0xAA) => Command::Unlock1,
+            (Command::Unlock1, 0x2AAA, 0x55) => Command::Unlock2,
+            (Command::Unlock2, 0x5555, 0xA0) => Command::Program,
+            (Command::Unlock2, 0x5555, 0x80) => Command::Erase,
+            (Command::Unlock2, 0x5555, 0x90) => {
+                self.id_mode = true;
+                Command::Ready
+            }
+            (Command::Erase, 0x5555, 0xAA) => Command::EraseUnlock1,
+            (Command::EraseUnlock1, 0x2AAA, 0x55) => Command::EraseUnlock2,
+            (Command::EraseUnlock2, 0x5555, 0x10) => {
+                for sector in 0..self.dirty_sectors.len() {
+                    self.erase_sector(sector);
+                }
+                Command::Ready
+            }
+            (Command::EraseUnlock2, _, 0x30) => {
+                self.erase_sector((address % self.data.len()) / SECTOR_LEN);
+                Command::Ready
+            }
+            _ => Command::Ready,
+        };
+    }
+
+    /// メモリビューアで元の ROM を書き換えたとき、同じ場所を合わせる。
+    /// 元の ROM ごと変わるので、保存するセクタには数えない。
+    pub(crate) fn poke(&mut self, address: usize, value: u8) {
+        let len = self.data.len();
+        self.data[address % len] = value;
+    }
+
+    fn program(&mut self, address: usize, value: u8) {
+        let address = address % self.data.len();
+        // 書き込みではビットを 0 にすることしかできない
+        self.data[address] &= value;
+        self.dirty_sectors[address / SECTOR_LEN] = true;
+    }
+
+    fn erase_sector(&mut self, sector: usize) {
+        let start = sector * SECTOR_LEN;
+        let end = (start + SECTOR_LEN).min(self.data.len());
+        self.data[start..end].fill(0xFF);
+        self.dirty_sectors[sector] = true;
+    }
+
+    fn bitmap_len(&self) -> usize {
+        self.dirty_sectors.len().div_ceil(8)
+    }
+
+    /// 書き換えたセクタのビットマップと、その中身を順に並べたもの。
+    pub(crate) fn modified_sectors_len(&self) -> usize {
+        self.bitmap_len() + self.dirty_sectors.iter().filter(|&&dirty| dirty).count() * SECTOR_LEN
+    }
+
+    pub(crate) fn export_modified_sectors(&self) -> Vec<u8> {
+        let mut bytes = vec![0; self.bitmap_len()];
+        for (sector, _) in self
+            .dirty_sectors
+            .iter()
+            .enumerate()
+            .filter(|(_, dirty)| **dirty)
+        {
+            bytes[sector / 8] |= 1 << (sector % 8);
+            bytes.extend_from_slice(self.sector(sector));
+        }
+        bytes
+    }
+
+    /// ROM の内容に戻してから保存されたセクタを書き戻す。
+    pub(crate) fn import_modified_sectors(
+        &mut self,
+        rom: &[u8],
+        bytes: &[u8],
+    ) -> Result<(), String> {
+        let bitmap_len = self.bitmap_len();
+        if rom.len() != self.data.len() || bytes.len() < bitmap_len {
+            return Err("flash save length mismatch".into());
+        }
+        let (bitmap, mut sectors) = bytes.split_at(bitmap_len);
+        let dirty_sectors = (0..self.dirty_sectors.len())
+            .map(|sector| bitmap[sector / 8] & (1 << (sector % 8)) != 0)
+            .collect::<Vec<_>>();
+        let dirty_count = dirty_sectors.iter().filter(|&&dirty| dirty).count();
+        if sectors.len() != dirty_count * SECTOR_LEN {
+            return Err("flash save length mismatch".into());
+        }
+        self.data.copy_from_slice(rom);
+        for sector in (0..dirty_sectors.len()).filter(|&sector| dirty_sectors[sector]) {
+            let (saved, rest) = sectors.split_at(SECTOR_LEN);
+            let start = sector * SECTOR_LEN;
+            let end = (start + SECTOR_LEN).min(self.data.len());
+            self.data[start..end].copy_from_slice(&saved[..end - start]);
+            sectors = rest;
+        }
+        self.dirty_sectors = dirty_sectors;
+        Ok(())
+    }
+
+    fn sector(&self, sector: usize) -> &[u8] {
+        let start = sector * SECTOR_LEN;
+        &self.data[start..(start + SECTOR_LEN).min(self.data.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FlashRom, SECTOR_LEN};
+
+    fn unlock(flash: &mut FlashRom, command: u8) {
+        flash.write(0x5555, 0xAA);
+        flash.write(0x2AAA, 0x55);
+        flash.write(0x5555, command);
+    }
+
+    #[test]
+    fn program_only_clears_bits() {
+        let mut flash = FlashRom::new(&[0xF0; 0x80000]);
+        unlock(&mut flash, 0xA0);
+        flash.write(0x1234, 0x3C);
+        assert_eq!(flash.read(0x1234), 0x30);
+
+        // アンロック無しの書き込みは無視される
+        flash.write(0x1235, 0x00);
+        assert_eq!(flash.read(0x1235), 0xF0);
+    }
+
+    #[test]
+    fn sector_erase_fills_only_the_addressed_sector() {
+        let mut flash = FlashRom::new(&[0x00; 0x80000]);
+        unlock(&mut flash, 0x80);
+        flash.write(0x5555, 0xAA);
+        flash.write(0x2AAA, 0x55);
+        flash.write(0x3456, 0x30);
+
+        assert_eq!(flash.read(0x2FFF), 0x00);
+        assert_eq!(flash.read(0x3000), 0xFF);
+        assert_eq!(flash.read(0x3FFF), 0xFF);
+        assert_eq!(flash.read(0x4000), 0x00);
+    }
+
+    #[test]
+    fn id_mode_reports_manufacturer_and_device_until_exit() {
+        let mut flash = FlashRom::new(&[0x00; 0x80000]);
+        unlock(&mut flash, 0x90);
+        assert_eq!((flash.read(0x0000), flash.read(0x0001)), (0xBF, 0xB7));
+        flash.write(0x0000, 0xF0);
+        assert_eq!(flash.read(0x0000), 0x00);
+
+        let mut small = FlashRom::new(&[0x00; 0x20000]);
+        unlock(&mut small, 0x90);
+        assert_eq!(small.read(0x0001), 0xB5);
+    }
+
+    #[test]
+    fn modified_sectors_round_trip() {
+        let rom = vec![0x11; 0x80000];
+        let mut flash = FlashRom::new(&rom);
+        assert_eq!(flash.modified_sectors_len(), 16);
+        unlock(&mut flash, 0xA0);
+        flash.write(0x7_F001, 0x01);
+
+        let saved = flash.export_modified_sectors();
+        assert_eq!(saved.len(), 16 + SECTOR_LEN);
+        assert_eq!(saved[15], 0x80);
+
+        let mut restored = FlashRom::new(&rom);
+        restored.import_modified_sectors(&rom, &saved).unwrap();
+        assert_eq!(restored.read(0x7_F001), 0x01);
+        assert_eq!(restored.read(0x7_F000), 0x11);
+        assert!(
+            restored
+                .import_modified_sectors(&rom, &saved[..saved.len() - 1])
+                .is_err()
+        );
+    }
+}
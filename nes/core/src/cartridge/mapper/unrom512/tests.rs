@@ -0,0 +1,178 @@
+use super::{Cartridge, Unrom512};
+use crate::{
+    cartridge_data_parts::CartridgeDataParts, cartridge_rom::CartridgeData, interrupt::Interrupt,
+    mirror::MirrorMode, rom_format::RomFormat,
+};
+
+fn test_data(mirror_mode: MirrorMode, has_battery: bool) -> CartridgeData {
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::INes,
+        prog_rom: (0..0x80000).map(|i| (i / 0x4000) as u8).collect(),
+        char_rom: Vec::new(),
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0,
+        save_vram_length: 0,
+        mapper_type: 30,
+        mirror_mode,
+        has_battery,
+        sub_mapper_type: 0,
+        trainer: Vec::new(),
+        console_type: None,
+    })
+    .expect("test cartridge data should be valid")
+}
+
+fn new_mapper(mirror_mode: MirrorMode, has_battery: bool) -> Unrom512 {
+    let mut mapper = Unrom512::new(test_data(mirror_mode, has_battery));
+    Cartridge::initialize(&mut mapper);
+    mapper
+}
+
+/// 実機のソフトと同じく、バンク切り替えでフラッシュのコマンドアドレスを合わせて書く。
+fn flash_command(mapper: &mut Unrom512, command: u8) {
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(mapper, 0xC000, 0x01, &mut interrupt);
+    Cartridge::write(mapper, 0x9555, 0xAA, &mut interrupt);
+    Cartridge::write(mapper, 0xC000, 0x00, &mut interrupt);
+    Cartridge::write(mapper, 0xAAAA, 0x55, &mut interrupt);
+    Cartridge::write(mapper, 0xC000, 0x01, &mut interrupt);
+    Cartridge::write(mapper, 0x9555, command, &mut interrupt);
+}
+
+fn program_byte(mapper: &mut Unrom512, bank: u8, address: usize, value: u8) {
+    let mut interrupt = Interrupt::new();
+    flash_command(mapper, 0xA0);
+    Cartridge::write(mapper, 0xC000, bank, &mut interrupt);
+    Cartridge::write(mapper, address, value, &mut interrupt);
+}
+
+#[test]
+fn register_switches_prg_chr_and_one_screen_mirroring() {
+    let mut mapper = new_mapper(MirrorMode::Single0, true);
+    let mut interrupt = Interrupt::new();
+    assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x1F);
+
+    Cartridge::write(&mut mapper, 0xC000, 0x80 | 0x40 | 0x05, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x05);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Single1);
+
+    Cartridge::write(&mut mapper, 0x0000, 0x77, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xC000, 0x00, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x0000).data, 0x00);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Single0);
+    Cartridge::write(&mut mapper, 0xC000, 0x40, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x0000).data, 0x77);
+}
+
+#[test]
+fn boards_without_flash_have_bus_conflicts() {
+    let mut mapper = new_mapper(MirrorMode::Vertical, false);
+    let mut interrupt = Interrupt::new();
+    assert!(!mapper.has_persistent_mapper_save());
+
+    // $FFFF は最終バンク ($1F) なので書いた値との AND になる
+    Cartridge::write(&mut mapper, 0xFFFF, 0x23, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x03);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Vertical);
+}
+
+#[test]
+fn flash_program_is_visible_through_prg_window() {
+    let mut mapper = new_mapper(MirrorMode::Vertical, true);
+    program_byte(&mut mapper, 0x03, 0x8010, 0x01);
+    assert_eq!(Cartridge::read(&mapper, 0x8010).data, 0x01);
+
+    // アンロック無しの $8000-$BFFF への書き込みはバンクもフラッシュも変えない
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0x8011, 0x00, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8011).data, 0x03);
+}
+
+#[test]
+fn program_rom_pokes_reach_the_flash_copy() {
+    let mut mapper = new_mapper(MirrorMode::Vertical, true);
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xC000, 0x02, &mut interrupt);
+    assert!(Cartridge::poke_program_rom(&mut mapper, 0x8010, 0xEA));
+    assert_eq!(Cartridge::read(&mapper, 0x8010).data, 0xEA);
+    // ROM ごと変えたので、保存するセクタには含めない
+    assert_eq!(mapper.persistent_mapper_save_lengths(), (16, 0));
+}
+
+#[test]
+fn software_id_mode_reads_sst39sf040_ids() {
+    let mut mapper = new_mapper(MirrorMode::Vertical, true);
+    let mut interrupt = Interrupt::new();
+    flash_command(&mut mapper, 0x90);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0xBF);
+    assert_eq!(Cartridge::read(&mapper, 0x8001).data, 0xB7);
+
+    Cartridge::write(&mut mapper, 0x8000, 0xF0, &mut interrupt);
+    assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x01);
+}
+
+#[test]
+fn modified_sectors_persist_through_mapper_save() {
+    let mut mapper = new_mapper(MirrorMode::Vertical, true);
+    let mut interrupt = Interrupt::new();
+    assert_eq!(mapper.persistent_mapper_save_lengths(), (16, 0));
+
+    flash_command(&mut mapper, 0x80);
+    Cartridge::write(&mut mapper, 0xC000, 0x01, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x9555, 0xAA, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xC000, 0x00, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xAAAA, 0x55, &mut interrupt);
+    Cartridge::write(&mut mapper, 0xC000, 0x02, &mut interrupt);
+    Cartridge::write(&mut mapper, 0x9000, 0x30, &mut interrupt);
+    program_byte(&mut mapper, 0x02, 0x9000, 0x5A);
+    assert_eq!(mapper.persistent_mapper_save_lengths(), (16 + 0x1000, 0));
+
+    let (prg_ram, chr_ram) = mapper.export_mapper_save_state().unwrap();
+    let mut restored = new_mapper(MirrorMode::Vertical, true);
+    restored
+        .import_mapper_save_state(&prg_ram, &chr_ram)
+        .unwrap();
+    Cartridge::write(&mut restored, 0xC000, 0x02, &mut interrupt);
+    assert_eq!(Cartridge::read(&restored, 0x9000).data, 0x5A);
+    assert_eq!(Cartridge::read(&restored, 0x9001).data, 0xFF);
+    assert_eq!(Cartridge::read(&restored, 0x8FFF).data, 0x02);
+    assert!(
+        restored
+            .import_mapper_save_state(&prg_ram[..16], &chr_ram)
+            .is_err()
+    );
+}
+
+#[test]
+fn flash_and_bank_state_round_trip_through_runtime_state() {
+    let mut mapper = new_mapper(MirrorMode::Vertical, true);
+    program_byte(&mut mapper, 0x04, 0x8000, 0x00);
+    flash_command(&mut mapper, 0x90);
+    let mut interrupt = Interrupt::new();
+    Cartridge::write(&mut mapper, 0xC000, 0x04, &mut interrupt);
+
+    let state = mapper.export_runtime_state().unwrap();
+    let mut restored = new_mapper(MirrorMode::Vertical, true);
+    restored.import_runtime_state(state).unwrap();
+    assert_eq!(Cartridge::read(&restored, 0x8001).data, 0xB7);
+    Cartridge::write(&mut restored, 0x8000, 0xF0, &mut interrupt);
+    assert_eq!(Cartridge::read(&restored, 0x8000).data, 0x00);
+    assert_eq!(Cartridge::read(&restored, 0x8001).data, 0x04);
+
+    let mut without_flash = new_mapper(MirrorMode::Vertical, false);
+    assert!(
+        without_flash
+            .import_runtime_state(mapper.export_runtime_state().unwrap())
+            .is_err()
+    );
+}
+
+#[test]
+fn four_screen_header_keeps_four_screen_mirroring() {
+    let mut mapper = new_mapper(MirrorMode::Single1, true);
+    let mut interrupt = Interrupt::new();
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Four);
+    Cartridge::write(&mut mapper, 0xC000, 0x80, &mut interrupt);
+    assert_eq!(mapper.mirror_mode(), MirrorMode::Four);
+}
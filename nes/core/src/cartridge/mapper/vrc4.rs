@@ -0,0 +1,427 @@
+use nerust_input_traits::OpenBusReadResult;
+
+use super::{Cartridge, vrc_irq::VrcIrq};
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_VRC4},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+const PRG_SWAP_MODE: u8 = 0x02;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq)]
+enum Model {
+    /// PRG 配置の切り替えと IRQ が無く、CHR バンクの上位は 4bit。
+    Vrc2,
+    Vrc4,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Vrc4RuntimeState {
+    prg_banks: [u8; 2],
+    chr_banks: [u16; 8],
+    prg_mode: u8,
+    irq: VrcIrq,
+    microwire_latch: u8,
+}
+
+/// Konami VRC2 / VRC4 (Mapper 21, 22, 23, 25)。
+/// 型番ごとにレジスタ番号へつながるアドレス線が異なるため、`register_lines` で吸収する。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Vrc4 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    model: Model,
+    /// レジスタ番号の bit0 / bit1 につながるアドレス線。サブマッパーが無い場合は 2 系統を OR する。
+    register_lines: [usize; 2],
+    /// VRC2a (Mapper 22) は CHR バンクの最下位ビットを捨てる。
+    chr_shift: u8,
+    prg_banks: [u8; 2],
+    chr_banks: [u16; 8],
+    /// $9002。bit1 で $8000 と $C000 の PRG を入れ替える。
+    prg_mode: u8,
+    irq: VrcIrq,
+    /// PRG-RAM の無い VRC2 で $6000-$6FFF に見える 1bit のラッチ。
+    microwire_latch: u8,
+}
+
+#[typetag::serde]
+impl Cartridge for Vrc4 {
+    fn read_ram(&self, address: usize) -> OpenBusReadResult {
+        if self.has_microwire_latch() {
+            return if address < 0x1000 {
+                OpenBusReadResult::new(self.microwire_latch, 0x01)
+            } else {
+                OpenBusReadResult::new(0, 0)
+            };
+        }
+        Mapper::read_ram(self, address).map_or_else(
+            || OpenBusReadResult::new(0, 0),
+            |x| OpenBusReadResult::new(x, 0xFF),
+        )
+    }
+
+    fn write_ram(&mut self, address: usize, value: u8, _interrupt: &mut Interrupt) {
+        if self.has_microwire_latch() {
+            if address < 0x7000 {
+                self.microwire_latch = value & 0x01;
+            }
+        } else {
+            Mapper::write_ram(self, address - 0x6000, value);
+        }
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_VRC4.into(),
+            extra_body: encode_payload(&Vrc4RuntimeState {
+                prg_banks: self.prg_banks,
+                chr_banks: self.chr_banks,
+                prg_mode: self.prg_mode,
+                irq: self.irq.clone(),
+                microwire_latch: self.microwire_latch,
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_VRC4 {
+            return Err(PersistenceError::Validation(
+                "unexpected VRC2/VRC4 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Vrc4RuntimeState = decode_payload(&state.extra_body)?;
+        self.state = state.mapper_state;
+        self.prg_banks = runtime.prg_banks;
+        self.chr_banks = runtime.chr_banks;
+        self.prg_mode = runtime.prg_mode;
+        self.irq = runtime.irq;
+        self.microwire_latch = runtime.microwire_latch & 0x01;
+        Ok(())
+    }
+}
+
+impl Vrc4 {
+    pub(crate) fn new(data: CartridgeData) -> Self {
+        let (model, register_lines, chr_shift) = match (data.mapper_type(), data.sub_mapper_type())
+        {
+            (21, 1) => (Model::Vrc4, [0x02, 0x04], 0),
+            (21, 2) => (Model::Vrc4, [0x40, 0x80], 0),
+            (21, _) => (Model::Vrc4, [0x42, 0x84], 0),
+            (22, _) => (Model::Vrc2, [0x02, 0x01], 1),
+            (23, 1) => (Model::Vrc4, [0x01, 0x02], 0),
+            (23, 2) => (Model::Vrc4, [0x04, 0x08], 0),
+            (23, 3) => (Model::Vrc2, [0x01, 0x02], 0),
+            (23, _) => (Model::Vrc4, [0x05, 0x0A], 0),
+            (25, 1) => (Model::Vrc4, [0x02, 0x01], 0),
+            (25, 2) => (Model::Vrc4, [0x08, 0x04], 0),
+            (25, 3) => (Model::Vrc2, [0x02, 0x01], 0),
+            (_, _) => (Model::Vrc4, [0x0A, 0x05], 0),
+        };
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            model,
+            register_lines,
+            chr_shift,
+            prg_banks: [0, 1],
+            chr_banks: [0, 1, 2, 3, 4, 5, 6, 7],
+            prg_mode: 0,
+            irq: VrcIrq::new(),
+            microwire_latch: 0,
+        }
+    }
+
+    /// アドレス線の配線を VRC4 の $x000-$x003 にそろえる。
+    fn register_address(&self, address: usize) -> usize {
+        let [low, high] = self.register_lines;
+        (address & 0xF000)
+            | usize::from(address & low != 0)
+            | (usize::from(address & high != 0) << 1)
+    }
+
+    fn has_microwire_latch(&self) -> bool {
+        self.model == Model::Vrc2 && self.mapper_state_ref().sram.is_empty()
+    }
+
+    fn update_prg_banks(&mut self) {
+        let second_last = (self.data_ref().prog_rom_len() / 0x2000).saturating_sub(2);
+        let first = usize::from(self.prg_banks[0] & 0x1F);
+        let (bank_8000, bank_c000) = if self.prg_mode & PRG_SWAP_MODE != 0 {
+            (second_last, first)
+        } else {
+            (first, second_last)
+        };
+        self.change_program_page(0, bank_8000);
+        self.change_program_page(1, usize::from(self.prg_banks[1] & 0x1F));
+        self.change_program_page(2, bank_c000);
+        self.change_program_page(3, second_last + 1);
+    }
+
+    fn write_chr_bank(&mut self, address: usize, value: u8) {
+        let index = (((address & 0xF000) - 0xB000) >> 11) | ((address >> 1) & 0x01);
+        let bank = self.chr_banks[index];
+        self.chr_banks[index] = if address & 0x01 == 0 {
+            (bank & 0x1F0) | u16::from(value & 0x0F)
+        } else {
+            let high_mask = if self.model == Model::Vrc4 {
+                0x1F
+            } else {
+                0x0F
+            };
+            (bank & 0x0F) | (u16::from(value & high_mask) << 4)
+        };
+        self.change_character_page(index, usize::from(self.chr_banks[index] >> self.chr_shift));
+    }
+
+    fn write_mirroring(&mut self, value: u8) {
+        self.set_mirror_mode(match (self.model, value & 0x03) {
+            (Model::Vrc2, mode) if mode & 0x01 == 0 => MirrorMode::Vertical,
+            (Model::Vrc2, _) => MirrorMode::Horizontal,
+            (Model::Vrc4, 0) => MirrorMode::Vertical,
+            (Model::Vrc4, 1) => MirrorMode::Horizontal,
+            (Model::Vrc4, 2) => MirrorMode::Single0,
+            (Model::Vrc4, _) => MirrorMode::Single1,
+        });
+    }
+}
+
+impl CartridgeDataDao for Vrc4 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Vrc4 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Vrc4 {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0400
+    }
+
+    fn initialize(&mut self) {
+        self.update_prg_banks();
+        for (page, bank) in self.chr_banks.into_iter().enumerate() {
+            self.change_character_page(page, usize::from(bank >> self.chr_shift));
+        }
+        self.change_ram_page(0, 0);
+    }
+
+    fn name(&self) -> &str {
+        match (self.model, self.data_ref().mapper_type()) {
+            (Model::Vrc4, 21) => "VRC4 (Mapper21)",
+            (Model::Vrc4, 23) => "VRC4 (Mapper23)",
+            (Model::Vrc4, _) => "VRC4 (Mapper25)",
+            (Model::Vrc2, 22) => "VRC2 (Mapper22)",
+            (Model::Vrc2, 23) => "VRC2 (Mapper23)",
+            (Model::Vrc2, _) => "VRC2 (Mapper25)",
+        }
+    }
+
+    fn ram_len_default(&self) -> usize {
+        match self.model {
+            Model::Vrc2 => 0,
+            Model::Vrc4 => 0x2000,
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        let address = self.register_address(address);
+        match (self.model, address) {
+            (_, 0x8000..=0x8003) => {
+                self.prg_banks[0] = value;
+                self.update_prg_banks();
+            }
+            (Model::Vrc2, 0x9000..=0x9003) | (Model::Vrc4, 0x9000..=0x9001) => {
+                self.write_mirroring(value);
+            }
+            (Model::Vrc4, 0x9002..=0x9003) => {
+                self.prg_mode = value;
+                self.update_prg_banks();
+            }
+            (_, 0xA000..=0xA003) => {
+                self.prg_banks[1] = value;
+                self.update_prg_banks();
+            }
+            (_, 0xB000..=0xEFFF) => self.write_chr_bank(address, value),
+            (Model::Vrc4, 0xF000) => self.irq.write_latch_low(value),
+            (Model::Vrc4, 0xF001) => self.irq.write_latch_high(value),
+            (Model::Vrc4, 0xF002) => self.irq.write_control(value, interrupt),
+            (Model::Vrc4, 0xF003) => self.irq.acknowledge(interrupt),
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        self.irq.step(interrupt);
+    }
+
+    fn cycles_until_next_cpu_event(&self) -> u64 {
+        self.irq.cycles_until_irq()
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cartridge, Vrc4};
+    use crate::{
+        cartridge_data_parts::CartridgeDataParts,
+        cartridge_rom::CartridgeData,
+        interrupt::{Interrupt, IrqSource},
+        mapper::Mapper,
+        mirror::MirrorMode,
+        rom_format::RomFormat,
+    };
+
+    fn new_mapper(mapper_type: u16, sub_mapper_type: u8) -> Vrc4 {
+        let data = CartridgeData::new(CartridgeDataParts {
+            format: RomFormat::INes,
+            prog_rom: (0..0x40000).map(|i| (i / 0x2000) as u8).collect(),
+            char_rom: (0..0x80000).map(|i| (i / 0x400) as u8).collect(),
+            pram_length: 0,
+            save_pram_length: 0,
+            vram_length: 0,
+            save_vram_length: 0,
+            mapper_type,
+            mirror_mode: MirrorMode::Horizontal,
+            has_battery: false,
+            sub_mapper_type,
+            trainer: Vec::new(),
+            console_type: None,
+        })
+        .expect("test cartridge data should be valid");
+        let mut mapper = Vrc4::new(data);
+        Cartridge::initialize(&mut mapper);
+        mapper
+    }
+
+    #[test]
+    fn prg_swap_mode_moves_first_bank_to_c000() {
+        let mut mapper = new_mapper(21, 1);
+        let mut interrupt = Interrupt::new();
+        mapper.write_register(0x8000, 0x03, &mut interrupt);
+        mapper.write_register(0xA000, 0x04, &mut interrupt);
+
+        assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x03);
+        assert_eq!(Cartridge::read(&mapper, 0xA000).data, 0x04);
+        assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x1E);
+        assert_eq!(Cartridge::read(&mapper, 0xE000).data, 0x1F);
+
+        // VRC4a の $9004 は $9002 に相当する
+        mapper.write_register(0x9004, 0x02, &mut interrupt);
+        assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x1E);
+        assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x03);
+    }
+
+    #[test]
+    fn submappers_pick_address_lines_and_heuristic_accepts_both() {
+        let mut interrupt = Interrupt::new();
+        // CHR 0 の上位 4bit を書くレジスタ ($B001)
+        for (mapper_type, sub_mapper_type, address) in [
+            (21, 1, 0xB002),
+            (21, 2, 0xB040),
+            (21, 0, 0xB002),
+            (21, 0, 0xB040),
+            (23, 1, 0xB001),
+            (23, 2, 0xB004),
+            (23, 0, 0xB001),
+            (23, 0, 0xB004),
+            (25, 1, 0xB002),
+            (25, 2, 0xB008),
+            (25, 0, 0xB002),
+            (25, 0, 0xB008),
+        ] {
+            let mut mapper = new_mapper(mapper_type, sub_mapper_type);
+            mapper.write_register(0xB000, 0x02, &mut interrupt);
+            mapper.write_register(address, 0x01, &mut interrupt);
+            assert_eq!(
+                Cartridge::read(&mapper, 0x0000).data,
+                0x12,
+                "mapper {mapper_type}.{sub_mapper_type} at {address:04X}"
+            );
+        }
+    }
+
+    #[test]
+    fn vrc2a_drops_low_chr_bit_and_uses_one_bit_mirroring() {
+        let mut mapper = new_mapper(22, 0);
+        let mut interrupt = Interrupt::new();
+        mapper.write_register(0xB000, 0x05, &mut interrupt);
+        mapper.write_register(0x9000, 0x03, &mut interrupt);
+
+        assert_eq!(Cartridge::read(&mapper, 0x0000).data, 0x02);
+        assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+    }
+
+    #[test]
+    fn vrc2_without_prg_ram_exposes_microwire_latch() {
+        let mut mapper = new_mapper(23, 3);
+        let mut interrupt = Interrupt::new();
+        Cartridge::write(&mut mapper, 0x6000, 0xFF, &mut interrupt);
+        let result = Cartridge::read(&mapper, 0x6000);
+        assert_eq!((result.data, result.mask), (0x01, 0x01));
+        assert_eq!(Cartridge::read(&mapper, 0x7000).mask, 0);
+
+        Cartridge::write(&mut mapper, 0x6000, 0xFE, &mut interrupt);
+        assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x00);
+    }
+
+    #[test]
+    fn irq_latch_nibbles_and_state_round_trip() {
+        let mut mapper = new_mapper(25, 1);
+        let mut interrupt = Interrupt::new();
+        // VRC4b は A1 が bit0、A0 が bit1 なので $F002 が latch 上位、$F001 が control
+        mapper.write_register(0xF000, 0x0E, &mut interrupt);
+        mapper.write_register(0xF002, 0x0F, &mut interrupt);
+        mapper.write_register(0xF001, 0x06, &mut interrupt);
+        assert_eq!(mapper.cycles_until_next_cpu_event(), 2);
+
+        let state = mapper.export_runtime_state().unwrap();
+        let mut restored = new_mapper(25, 1);
+        restored.import_runtime_state(state).unwrap();
+        restored.step_cpu_cycles(2, &mut interrupt);
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+        restored.write_register(0xF003, 0x00, &mut interrupt);
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    }
+}
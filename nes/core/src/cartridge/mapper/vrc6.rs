@@ -0,0 +1,405 @@
+pub(super) use self::audio::Vrc6Audio;
+use super::{Cartridge, vrc_irq::VrcIrq};
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_VRC6},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+mod audio;
+
+const BANKING_PRG_RAM_ENABLE: u8 = 0x80;
+const BANKING_CHR_A10: u8 = 0x20;
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Eq)]
+enum Model {
+    /// Mapper 24 (悪魔城伝説)
+    Vrc6a,
+    /// Mapper 26 (魍魎戦記 MADARA / エスパードリーム 2)。A0 と A1 が入れ替わっている。
+    Vrc6b,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Vrc6RuntimeState {
+    prg_bank_16k: u8,
+    prg_bank_8k: u8,
+    chr_banks: [u8; 8],
+    banking: u8,
+    irq: VrcIrq,
+    audio: Vrc6Audio,
+}
+
+/// Konami VRC6。16KiB + 8KiB の PRG 切り替え、1KiB 単位の CHR 切り替え、
+/// CPU サイクル IRQ と拡張音源を持つ。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Vrc6 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    model: Model,
+    prg_bank_16k: u8,
+    prg_bank_8k: u8,
+    chr_banks: [u8; 8],
+    /// $B003。CHR の配置、ミラーリング、PRG-RAM の有効化。
+    banking: u8,
+    irq: VrcIrq,
+    audio: Vrc6Audio,
+}
+
+#[typetag::serde]
+impl Cartridge for Vrc6 {
+    fn expansion_audio_output(&self) -> f32 {
+        self.audio.output()
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_VRC6.into(),
+            extra_body: encode_payload(&Vrc6RuntimeState {
+                prg_bank_16k: self.prg_bank_16k,
+                prg_bank_8k: self.prg_bank_8k,
+                chr_banks: self.chr_banks,
+                banking: self.banking,
+                irq: self.irq.clone(),
+                audio: self.audio.clone(),
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_VRC6 {
+            return Err(PersistenceError::Validation(
+                "unexpected VRC6 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Vrc6RuntimeState = decode_payload(&state.extra_body)?;
+        self.state = state.mapper_state;
+        self.prg_bank_16k = runtime.prg_bank_16k;
+        self.prg_bank_8k = runtime.prg_bank_8k;
+        self.chr_banks = runtime.chr_banks;
+        self.banking = runtime.banking;
+        self.irq = runtime.irq;
+        self.audio = runtime.audio;
+        Ok(())
+    }
+}
+
+impl Vrc6 {
+    pub(crate) fn new_mapper24(data: CartridgeData) -> Self {
+        Self::new(data, Model::Vrc6a)
+    }
+
+    pub(crate) fn new_mapper26(data: CartridgeData) -> Self {
+        Self::new(data, Model::Vrc6b)
+    }
+
+    fn new(data: CartridgeData, model: Model) -> Self {
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            model,
+            prg_bank_16k: 0,
+            prg_bank_8k: 0,
+            chr_banks: [0, 1, 2, 3, 4, 5, 6, 7],
+            banking: 0,
+            irq: VrcIrq::new(),
+            audio: Vrc6Audio::new(),
+        }
+    }
+
+    /// VRC6b の A0/A1 を入れ替え、VRC6a と同じレジスタ番号にそろえる。
+    fn register_address(&self, address: usize) -> usize {
+        let address = address & 0xF003;
+        match self.model {
+            Model::Vrc6a => address,
+            Model::Vrc6b => (address & 0xF000) | ((address & 1) << 1) | ((address >> 1) & 1),
+        }
+    }
+
+    fn update_prg_banks(&mut self) {
+        let bank = usize::from(self.prg_bank_16k & 0x0F) << 1;
+        self.change_program_page(0, bank);
+        self.change_program_page(1, bank + 1);
+        self.change_program_page(2, usize::from(self.prg_bank_8k & 0x1F));
+        let last_bank = (self.data_ref().prog_rom_len() / 0x2000).saturating_sub(1);
+        self.change_program_page(3, last_bank);
+    }
+
+    fn update_chr_banks(&mut self) {
+        // 2KiB 単位の配置では、$B003 bit5 が立っていると下位ビットを PPU A10 で置き換える
+        let pair = |bank: u8, a10: bool| {
+            if a10 {
+                [bank & !1, bank | 1]
+            } else {
+                [bank, bank]
+            }
+        };
+        let a10 = self.banking & BANKING_CHR_A10 != 0;
+        let r = self.chr_banks;
+        let pages: [u8; 8] = match self.banking & 0x03 {
+            0 => r,
+            1 => {
+                let [a, b] = pair(r[0], a10);
+                let [c, d] = pair(r[1], a10);
+                let [e, f] = pair(r[2], a10);
+                let [g, h] = pair(r[3], a10);
+                [a, b, c, d, e, f, g, h]
+            }
+            _ => {
+                let [e, f] = pair(r[4], a10);
+                let [g, h] = pair(r[5], a10);
+                [r[0], r[1], r[2], r[3], e, f, g, h]
+            }
+        };
+        for (index, page) in pages.into_iter().enumerate() {
+            self.change_character_page(index, usize::from(page));
+        }
+    }
+
+    fn write_banking(&mut self, value: u8) {
+        self.banking = value;
+        if value & 0x10 != 0 {
+            log::warn!("VRC6 CHR-ROM nametables are not supported");
+        }
+        self.set_mirror_mode(match (value >> 2) & 0x03 {
+            0 => MirrorMode::Vertical,
+            1 => MirrorMode::Horizontal,
+            2 => MirrorMode::Single0,
+            _ => MirrorMode::Single1,
+        });
+        self.update_chr_banks();
+    }
+
+    fn prg_ram_enabled(&self) -> bool {
+        self.banking & BANKING_PRG_RAM_ENABLE != 0
+    }
+}
+
+impl CartridgeDataDao for Vrc6 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Vrc6 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Vrc6 {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0400
+    }
+
+    fn initialize(&mut self) {
+        self.update_prg_banks();
+        self.update_chr_banks();
+        self.change_ram_page(0, 0);
+    }
+
+    fn name(&self) -> &str {
+        match self.model {
+            Model::Vrc6a => "VRC6a (Mapper24)",
+            Model::Vrc6b => "VRC6b (Mapper26)",
+        }
+    }
+
+    fn ram_len_default(&self) -> usize {
+        0x2000
+    }
+
+    fn read_ram(&self, index: usize) -> Option<u8> {
+        if !self.prg_ram_enabled() {
+            return None;
+        }
+        self.ram_address(index)
+            .map(|address| self.mapper_state_ref().sram[address])
+    }
+
+    fn write_ram(&mut self, index: usize, data: u8) {
+        if self.prg_ram_enabled()
+            && let Some(address) = self.ram_address(index)
+        {
+            self.mapper_state_mut().sram[address] = data;
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        let address = self.register_address(address);
+        match address {
+            0x8000..=0x8003 => {
+                self.prg_bank_16k = value;
+                self.update_prg_banks();
+            }
+            0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002 => self.audio.write(address, value),
+            0xB003 => self.write_banking(value),
+            0xC000..=0xC003 => {
+                self.prg_bank_8k = value;
+                self.update_prg_banks();
+            }
+            0xD000..=0xD003 | 0xE000..=0xE003 => {
+                let index = ((address - 0xD000) >> 10) | (address & 0x03);
+                self.chr_banks[index] = value;
+                self.update_chr_banks();
+            }
+            0xF000 => self.irq.write_latch(value),
+            0xF001 => self.irq.write_control(value, interrupt),
+            0xF002 => self.irq.acknowledge(interrupt),
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        self.irq.step(interrupt);
+        self.audio.step();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cartridge, Vrc6};
+    use crate::{
+        cartridge_data_parts::CartridgeDataParts,
+        cartridge_rom::CartridgeData,
+        interrupt::{Interrupt, IrqSource},
+        mapper::Mapper,
+        mirror::MirrorMode,
+        rom_format::RomFormat,
+    };
+
+    fn test_data(mapper_type: u16) -> CartridgeData {
+        CartridgeData::new(CartridgeDataParts {
+            format: RomFormat::INes,
+            prog_rom: (0..0x40000).map(|i| (i / 0x2000) as u8).collect(),
+            char_rom: (0..0x20000).map(|i| (i / 0x400) as u8).collect(),
+            pram_length: 0,
+            save_pram_length: 0,
+            vram_length: 0,
+            save_vram_length: 0,
+            mapper_type,
+            mirror_mode: MirrorMode::Horizontal,
+            has_battery: false,
+            sub_mapper_type: 0,
+            trainer: Vec::new(),
+            console_type: None,
+        })
+        .expect("test cartridge data should be valid")
+    }
+
+    fn new_mapper(mapper_type: u16) -> Vrc6 {
+        let mut mapper = if mapper_type == 24 {
+            Vrc6::new_mapper24(test_data(mapper_type))
+        } else {
+            Vrc6::new_mapper26(test_data(mapper_type))
+        };
+        Cartridge::initialize(&mut mapper);
+        mapper
+    }
+
+    #[test]
+    fn prg_banks_switch_16k_and_8k_windows_with_fixed_last_bank() {
+        let mut mapper = new_mapper(24);
+        let mut interrupt = Interrupt::new();
+        mapper.write_register(0x8000, 0x03, &mut interrupt);
+        mapper.write_register(0xC000, 0x11, &mut interrupt);
+
+        assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x06);
+        assert_eq!(Cartridge::read(&mapper, 0xA000).data, 0x07);
+        assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x11);
+        assert_eq!(Cartridge::read(&mapper, 0xE000).data, 0x1F);
+    }
+
+    #[test]
+    fn vrc6b_swaps_address_lines() {
+        let mut vrc6a = new_mapper(24);
+        let mut vrc6b = new_mapper(26);
+        let mut interrupt = Interrupt::new();
+        // VRC6a の $D001 (CHR 1) は VRC6b では $D002
+        vrc6a.write_register(0xD001, 0x42, &mut interrupt);
+        vrc6b.write_register(0xD002, 0x42, &mut interrupt);
+        // $B003 は入れ替えても同じ
+        vrc6a.write_register(0xB003, 0x04, &mut interrupt);
+        vrc6b.write_register(0xB003, 0x04, &mut interrupt);
+
+        for mapper in [&vrc6a, &vrc6b] {
+            assert_eq!(Cartridge::read(mapper, 0x0400).data, 0x42);
+            assert_eq!(Cartridge::read(mapper, 0x0800).data, 0x02);
+            assert_eq!(mapper.mirror_mode(), MirrorMode::Horizontal);
+        }
+    }
+
+    #[test]
+    fn chr_2k_mode_replaces_low_bit_with_ppu_a10() {
+        let mut mapper = new_mapper(24);
+        let mut interrupt = Interrupt::new();
+        mapper.write_register(0xD000, 0x11, &mut interrupt);
+        mapper.write_register(0xE000, 0x20, &mut interrupt);
+        mapper.write_register(0xE001, 0x31, &mut interrupt);
+        mapper.write_register(0xB003, 0x22, &mut interrupt);
+
+        assert_eq!(Cartridge::read(&mapper, 0x0000).data, 0x11);
+        assert_eq!(Cartridge::read(&mapper, 0x1000).data, 0x20);
+        assert_eq!(Cartridge::read(&mapper, 0x1400).data, 0x21);
+        assert_eq!(Cartridge::read(&mapper, 0x1800).data, 0x30);
+        assert_eq!(Cartridge::read(&mapper, 0x1C00).data, 0x31);
+    }
+
+    #[test]
+    fn prg_ram_requires_banking_enable_bit() {
+        let mut mapper = new_mapper(24);
+        let mut interrupt = Interrupt::new();
+        Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+        assert_eq!(Cartridge::read(&mapper, 0x6000).mask, 0);
+
+        mapper.write_register(0xB003, 0x80, &mut interrupt);
+        Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+        assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x55);
+    }
+
+    #[test]
+    fn irq_and_audio_state_round_trip_through_runtime_state() {
+        let mut mapper = new_mapper(26);
+        let mut interrupt = Interrupt::new();
+        // VRC6b なので IRQ 制御は $F002、矩形波の有効化は $9001
+        mapper.write_register(0xF000, 0xFF, &mut interrupt);
+        mapper.write_register(0xF002, 0x06, &mut interrupt);
+        mapper.write_register(0x9000, 0x8F, &mut interrupt);
+        mapper.write_register(0x9001, 0x80, &mut interrupt);
+
+        let state = mapper.export_runtime_state().unwrap();
+        let mut restored = new_mapper(26);
+        restored.import_runtime_state(state).unwrap();
+        assert!(restored.expansion_audio_output() > 0.0);
+
+        Mapper::step(&mut restored, &mut interrupt);
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+    }
+}
@@ -0,0 +1,241 @@
+/// 音量 15 の矩形波 1ch が 2A03 の矩形波 1ch の最大値と同程度になるよう揃える。
+const OUTPUT_SCALE: f32 = 0.15 / 15.0;
+
+const FREQUENCY_HALT: u8 = 0x01;
+const FREQUENCY_SHIFT_4: u8 = 0x02;
+const FREQUENCY_SHIFT_8: u8 = 0x04;
+
+/// 12bit の分周タイマ。$9003 の指定に応じて周期を 1/16, 1/256 に縮める。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+struct Divider {
+    period: u16,
+    counter: u16,
+    enabled: bool,
+}
+
+impl Divider {
+    fn write_low(&mut self, value: u8) {
+        self.period = (self.period & 0x0F00) | u16::from(value);
+    }
+
+    /// bit7 が有効フラグ。無効化された場合は `false` を返す。
+    fn write_high(&mut self, value: u8) -> bool {
+        self.period = (self.period & 0x00FF) | (u16::from(value & 0x0F) << 8);
+        self.enabled = value & 0x80 != 0;
+        self.enabled
+    }
+
+    fn clock(&mut self, frequency_control: u8) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        if self.counter == 0 {
+            self.counter = if frequency_control & FREQUENCY_SHIFT_8 != 0 {
+                self.period >> 8
+            } else if frequency_control & FREQUENCY_SHIFT_4 != 0 {
+                self.period >> 4
+            } else {
+                self.period
+            };
+            true
+        } else {
+            self.counter -= 1;
+            false
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct Pulse {
+    divider: Divider,
+    volume: u8,
+    duty: u8,
+    /// bit7。デューティを無視して常に音量を出す。
+    digitized: bool,
+    step: u8,
+}
+
+impl Pulse {
+    fn new() -> Self {
+        Self {
+            divider: Divider::default(),
+            volume: 0,
+            duty: 0,
+            digitized: false,
+            step: 15,
+        }
+    }
+
+    fn write(&mut self, register: usize, value: u8) {
+        match register {
+            0 => {
+                self.volume = value & 0x0F;
+                self.duty = (value >> 4) & 0x07;
+                self.digitized = value & 0x80 != 0;
+            }
+            1 => self.divider.write_low(value),
+            _ => {
+                if !self.divider.write_high(value) {
+                    self.step = 15;
+                }
+            }
+        }
+    }
+
+    fn clock(&mut self, frequency_control: u8) {
+        if self.divider.clock(frequency_control) {
+            self.step = self.step.wrapping_sub(1) & 0x0F;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if self.divider.enabled && (self.digitized || self.step <= self.duty) {
+            self.volume
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+struct Sawtooth {
+    divider: Divider,
+    rate: u8,
+    accumulator: u8,
+    step: u8,
+}
+
+impl Sawtooth {
+    fn write(&mut self, register: usize, value: u8) {
+        match register {
+            0 => self.rate = value & 0x3F,
+            1 => self.divider.write_low(value),
+            _ => {
+                if !self.divider.write_high(value) {
+                    self.accumulator = 0;
+                    self.step = 0;
+                }
+            }
+        }
+    }
+
+    /// 2 クロックごとに加算し、14 クロックで 0 に戻る。
+    fn clock(&mut self, frequency_control: u8) {
+        if !self.divider.clock(frequency_control) {
+            return;
+        }
+        self.step += 1;
+        if self.step == 14 {
+            self.step = 0;
+            self.accumulator = 0;
+        } else if self.step.is_multiple_of(2) {
+            self.accumulator = self.accumulator.wrapping_add(self.rate);
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if self.divider.enabled {
+            self.accumulator >> 3
+        } else {
+            0
+        }
+    }
+}
+
+/// VRC6 拡張音源 (矩形波 2ch + のこぎり波)。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(crate) struct Vrc6Audio {
+    pulses: [Pulse; 2],
+    sawtooth: Sawtooth,
+    frequency_control: u8,
+}
+
+impl Vrc6Audio {
+    pub(crate) fn new() -> Self {
+        Self {
+            pulses: [Pulse::new(), Pulse::new()],
+            sawtooth: Sawtooth::default(),
+            frequency_control: 0,
+        }
+    }
+
+    /// $9000-$9003, $A000-$A002, $B000-$B002。A0/A1 の入れ替えは呼び出し側で済ませておく。
+    pub(crate) fn write(&mut self, address: usize, value: u8) {
+        let register = address & 0x03;
+        match (address & 0xF000, register) {
+            (0x9000, 3) => self.frequency_control = value & 0x07,
+            (0x9000, _) => self.pulses[0].write(register, value),
+            (0xA000, 0..=2) => self.pulses[1].write(register, value),
+            (0xB000, 0..=2) => self.sawtooth.write(register, value),
+            _ => {}
+        }
+    }
+
+    /// CPU 1 サイクル分進める。
+    pub(crate) fn step(&mut self) {
+        if self.frequency_control & FREQUENCY_HALT != 0 {
+            return;
+        }
+        for pulse in &mut self.pulses {
+            pulse.clock(self.frequency_control);
+        }
+        self.sawtooth.clock(self.frequency_control);
+    }
+
+    pub(crate) fn output(&self) -> f32 {
+        let sum = self.pulses[0].output() + self.pulses[1].output() + self.sawtooth.output();
+        f32::from(sum) * OUTPUT_SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Vrc6Audio;
+
+    #[test]
+    fn pulse_duty_sets_high_steps_of_sixteen() {
+        let mut audio = Vrc6Audio::new();
+        // デューティ 3 (4/16)、音量 15、周期 0 (毎サイクルで 1 ステップ)
+        audio.write(0x9000, 0x3F);
+        audio.write(0x9001, 0x00);
+        audio.write(0x9002, 0x80);
+
+        let high = (0..16)
+            .filter(|_| {
+                audio.step();
+                audio.output() > 0.0
+            })
+            .count();
+        assert_eq!(high, 4);
+    }
+
+    #[test]
+    fn sawtooth_accumulates_every_other_clock_and_resets() {
+        let mut audio = Vrc6Audio::new();
+        audio.write(0xB000, 0x2A);
+        audio.write(0xB001, 0x00);
+        audio.write(0xB002, 0x80);
+
+        let levels = (0..14)
+            .map(|_| {
+                audio.step();
+                audio.sawtooth.output()
+            })
+            .collect::<Vec<_>>();
+        // 42 ずつ 6 回加算して上位 5bit を出力する
+        assert_eq!(levels, [0, 5, 5, 10, 10, 15, 15, 21, 21, 26, 26, 31, 31, 0]);
+    }
+
+    #[test]
+    fn halt_and_disable_silence_channels() {
+        let mut audio = Vrc6Audio::new();
+        audio.write(0x9000, 0x8F);
+        audio.write(0x9002, 0x80);
+        assert!(audio.output() > 0.0);
+        audio.write(0x9003, 0x01);
+        audio.step();
+        assert!(audio.output() > 0.0, "halt freezes the current output");
+        audio.write(0x9002, 0x00);
+        assert_eq!(audio.output(), 0.0);
+    }
+}
@@ -0,0 +1,375 @@
+pub(super) use self::audio::Vrc7Audio;
+use super::{Cartridge, vrc_irq::VrcIrq};
+use crate::{
+    cartridge_rom::CartridgeData,
+    cartridge_runtime_state::{CartridgeRuntimeState, MAPPER_KIND_VRC7},
+    interrupt::Interrupt,
+    mapper::{CartridgeDataDao, Mapper},
+    mapper_state::{MapperState, MapperStateDao},
+    mirror::MirrorMode,
+    persistence_codec::{decode_payload, encode_payload},
+    persistence_error::PersistenceError,
+};
+
+mod audio;
+
+const CONTROL_PRG_RAM_ENABLE: u8 = 0x80;
+const CONTROL_SILENCE: u8 = 0x40;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Vrc7RuntimeState {
+    prg_banks: [u8; 3],
+    chr_banks: [u8; 8],
+    control: u8,
+    irq: VrcIrq,
+    audio: Vrc7Audio,
+}
+
+/// Konami VRC7 (Mapper 85)。8KiB 単位の PRG 切り替え 3 つ、1KiB 単位の CHR 切り替え、
+/// CPU サイクル IRQ と FM 拡張音源を持つ。
+#[derive(serde::Serialize, serde::Deserialize)]
+pub(crate) struct Vrc7 {
+    cartridge_data: CartridgeData,
+    state: MapperState,
+    /// 各レジスタ対の 2 つ目を選ぶアドレス線。VRC7a は A4、VRC7b は A3。
+    second_register_mask: usize,
+    prg_banks: [u8; 3],
+    chr_banks: [u8; 8],
+    /// $E000。ミラーリング、拡張音源の消音、PRG-RAM の有効化。
+    control: u8,
+    irq: VrcIrq,
+    audio: Vrc7Audio,
+}
+
+#[typetag::serde]
+impl Cartridge for Vrc7 {
+    fn expansion_audio_output(&self) -> f32 {
+        self.audio.output()
+    }
+
+    // FM 音源は CPU サイクルで進むため、APU のサンプル境界ごとにマッパーを進める
+    fn expansion_audio_cpu_step_synchronized(&self) -> bool {
+        true
+    }
+
+    fn export_runtime_state(&self) -> Result<CartridgeRuntimeState, PersistenceError> {
+        Ok(CartridgeRuntimeState {
+            mapper_state: self.state.clone(),
+            extra_kind: MAPPER_KIND_VRC7.into(),
+            extra_body: encode_payload(&Vrc7RuntimeState {
+                prg_banks: self.prg_banks,
+                chr_banks: self.chr_banks,
+                control: self.control,
+                irq: self.irq.clone(),
+                audio: self.audio.clone(),
+            })?,
+        })
+    }
+
+    fn import_runtime_state(
+        &mut self,
+        state: CartridgeRuntimeState,
+    ) -> Result<(), PersistenceError> {
+        if state.extra_kind != MAPPER_KIND_VRC7 {
+            return Err(PersistenceError::Validation(
+                "unexpected VRC7 runtime kind".into(),
+            ));
+        }
+        self.state
+            .validate_for_import(
+                &state.mapper_state,
+                self.data_ref().prog_rom_len(),
+                self.data_ref().char_rom_len(),
+            )
+            .map_err(PersistenceError::Validation)?;
+        let runtime: Vrc7RuntimeState = decode_payload(&state.extra_body)?;
+        self.state = state.mapper_state;
+        self.prg_banks = runtime.prg_banks;
+        self.chr_banks = runtime.chr_banks;
+        self.control = runtime.control;
+        self.irq = runtime.irq;
+        self.audio = runtime.audio;
+        Ok(())
+    }
+}
+
+impl Vrc7 {
+    pub(crate) fn new(data: CartridgeData) -> Self {
+        // サブマッパーが無い場合は A3 と A4 の両方を見る
+        let second_register_mask = match data.sub_mapper_type() {
+            1 => 0x08,
+            2 => 0x10,
+            _ => 0x18,
+        };
+        Self {
+            cartridge_data: data,
+            state: MapperState::new(),
+            second_register_mask,
+            prg_banks: [0, 1, 2],
+            chr_banks: [0, 1, 2, 3, 4, 5, 6, 7],
+            control: 0,
+            irq: VrcIrq::new(),
+            audio: Vrc7Audio::new(),
+        }
+    }
+
+    fn update_prg_banks(&mut self) {
+        for (page, bank) in self.prg_banks.into_iter().enumerate() {
+            self.change_program_page(page, usize::from(bank & 0x3F));
+        }
+        let last_bank = (self.data_ref().prog_rom_len() / 0x2000).saturating_sub(1);
+        self.change_program_page(3, last_bank);
+    }
+
+    fn write_control(&mut self, value: u8) {
+        self.control = value;
+        self.set_mirror_mode(match value & 0x03 {
+            0 => MirrorMode::Vertical,
+            1 => MirrorMode::Horizontal,
+            2 => MirrorMode::Single0,
+            _ => MirrorMode::Single1,
+        });
+        self.audio.set_silenced(value & CONTROL_SILENCE != 0);
+    }
+
+    fn prg_ram_enabled(&self) -> bool {
+        self.control & CONTROL_PRG_RAM_ENABLE != 0
+    }
+}
+
+impl CartridgeDataDao for Vrc7 {
+    fn data_mut(&mut self) -> &mut CartridgeData {
+        &mut self.cartridge_data
+    }
+
+    fn data_ref(&self) -> &CartridgeData {
+        &self.cartridge_data
+    }
+}
+
+impl MapperStateDao for Vrc7 {
+    fn mapper_state_mut(&mut self) -> &mut MapperState {
+        &mut self.state
+    }
+
+    fn mapper_state_ref(&self) -> &MapperState {
+        &self.state
+    }
+}
+
+impl Mapper for Vrc7 {
+    fn program_page_len(&self) -> usize {
+        0x2000
+    }
+
+    fn character_page_len(&self) -> usize {
+        0x0400
+    }
+
+    fn initialize(&mut self) {
+        self.update_prg_banks();
+        for (page, bank) in self.chr_banks.into_iter().enumerate() {
+            self.change_character_page(page, usize::from(bank));
+        }
+        self.change_ram_page(0, 0);
+    }
+
+    fn name(&self) -> &str {
+        "VRC7 (Mapper85)"
+    }
+
+    fn ram_len_default(&self) -> usize {
+        0x2000
+    }
+
+    fn read_ram(&self, index: usize) -> Option<u8> {
+        if !self.prg_ram_enabled() {
+            return None;
+        }
+        self.ram_address(index)
+            .map(|address| self.mapper_state_ref().sram[address])
+    }
+
+    fn write_ram(&mut self, index: usize, data: u8) {
+        if self.prg_ram_enabled()
+            && let Some(address) = self.ram_address(index)
+        {
+            self.mapper_state_mut().sram[address] = data;
+        }
+    }
+
+    fn write_register(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        // 音源は VRC7a の配線 ($9010 / $9030) でのみ届く
+        match address & 0xF030 {
+            0x9010 => return self.audio.write_register_select(value),
+            0x9030 => return self.audio.write_register_data(value),
+            _ => {}
+        }
+        let second = usize::from(address & self.second_register_mask != 0);
+        match (address & 0xF000, second) {
+            (0x8000, index) => {
+                self.prg_banks[index] = value;
+                self.update_prg_banks();
+            }
+            (0x9000, 0) => {
+                self.prg_banks[2] = value;
+                self.update_prg_banks();
+            }
+            (0xA000..=0xD000, index) => {
+                let page = (((address & 0xF000) - 0xA000) >> 11) | index;
+                self.chr_banks[page] = value;
+                self.change_character_page(page, usize::from(value));
+            }
+            (0xE000, 0) => self.write_control(value),
+            (0xE000, _) => self.irq.write_latch(value),
+            (0xF000, 0) => self.irq.write_control(value, interrupt),
+            (0xF000, _) => self.irq.acknowledge(interrupt),
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, interrupt: &mut Interrupt) {
+        self.irq.step(interrupt);
+        self.audio.step();
+    }
+
+    fn step_cpu_cycles(&mut self, cycles: u64, interrupt: &mut Interrupt) {
+        for _ in 0..cycles {
+            self.irq.step(interrupt);
+        }
+        self.audio.step_cycles(cycles);
+    }
+
+    fn cycles_until_next_cpu_event(&self) -> u64 {
+        self.irq.cycles_until_irq()
+    }
+
+    fn cpu_read_has_side_effect(&self, _address: usize) -> bool {
+        false
+    }
+
+    fn allow_instruction_fast_path(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cartridge, Vrc7};
+    use crate::{
+        cartridge_data_parts::CartridgeDataParts,
+        cartridge_rom::CartridgeData,
+        interrupt::{Interrupt, IrqSource},
+        mapper::Mapper,
+        mirror::MirrorMode,
+        rom_format::RomFormat,
+    };
+
+    fn new_mapper(sub_mapper_type: u8) -> Vrc7 {
+        let data = CartridgeData::new(CartridgeDataParts {
+            format: RomFormat::INes,
+            prog_rom: (0..0x80000).map(|i| (i / 0x2000) as u8).collect(),
+            char_rom: (0..0x40000).map(|i| (i / 0x400) as u8).collect(),
+            pram_length: 0,
+            save_pram_length: 0,
+            vram_length: 0,
+            save_vram_length: 0,
+            mapper_type: 85,
+            mirror_mode: MirrorMode::Horizontal,
+            has_battery: false,
+            sub_mapper_type,
+            trainer: Vec::new(),
+            console_type: None,
+        })
+        .expect("test cartridge data should be valid");
+        let mut mapper = Vrc7::new(data);
+        Cartridge::initialize(&mut mapper);
+        mapper
+    }
+
+    #[test]
+    fn prg_banks_switch_three_8k_windows_with_fixed_last_bank() {
+        let mut mapper = new_mapper(0);
+        let mut interrupt = Interrupt::new();
+        mapper.write_register(0x8000, 0x05, &mut interrupt);
+        mapper.write_register(0x8010, 0x06, &mut interrupt);
+        mapper.write_register(0x9000, 0x3F, &mut interrupt);
+
+        assert_eq!(Cartridge::read(&mapper, 0x8000).data, 0x05);
+        assert_eq!(Cartridge::read(&mapper, 0xA000).data, 0x06);
+        assert_eq!(Cartridge::read(&mapper, 0xC000).data, 0x3F);
+        assert_eq!(Cartridge::read(&mapper, 0xE000).data, 0x3F);
+    }
+
+    #[test]
+    fn submapper_selects_a3_or_a4_for_second_registers() {
+        let mut interrupt = Interrupt::new();
+        for (sub_mapper_type, address) in [(0, 0xA008), (0, 0xA010), (1, 0xA008), (2, 0xA010)] {
+            let mut mapper = new_mapper(sub_mapper_type);
+            mapper.write_register(address, 0x42, &mut interrupt);
+            assert_eq!(Cartridge::read(&mapper, 0x0000).data, 0x00);
+            assert_eq!(Cartridge::read(&mapper, 0x0400).data, 0x42);
+        }
+
+        // VRC7b では A4 は無視される
+        let mut mapper = new_mapper(1);
+        mapper.write_register(0xA010, 0x42, &mut interrupt);
+        assert_eq!(Cartridge::read(&mapper, 0x0000).data, 0x42);
+    }
+
+    #[test]
+    fn control_register_sets_mirroring_and_prg_ram() {
+        let mut mapper = new_mapper(0);
+        let mut interrupt = Interrupt::new();
+        Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+        assert_eq!(Cartridge::read(&mapper, 0x6000).mask, 0);
+
+        mapper.write_register(0xE000, 0x83, &mut interrupt);
+        assert_eq!(mapper.mirror_mode(), MirrorMode::Single1);
+        Cartridge::write(&mut mapper, 0x6000, 0x55, &mut interrupt);
+        assert_eq!(Cartridge::read(&mapper, 0x6000).data, 0x55);
+    }
+
+    #[test]
+    fn batched_steps_stop_at_irq() {
+        let mut mapper = new_mapper(2);
+        let mut interrupt = Interrupt::new();
+        mapper.write_register(0xE010, 0xF0, &mut interrupt);
+        mapper.write_register(0xF000, 0x06, &mut interrupt);
+        assert_eq!(mapper.cycles_until_next_cpu_event(), 0x10);
+
+        mapper.step_cpu_cycles(0x0F, &mut interrupt);
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+        mapper.step_cpu_cycles(1, &mut interrupt);
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+        mapper.write_register(0xF010, 0x00, &mut interrupt);
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    }
+
+    #[test]
+    fn irq_and_audio_state_round_trip_through_runtime_state() {
+        let mut mapper = new_mapper(0);
+        let mut interrupt = Interrupt::new();
+        mapper.write_register(0xE008, 0xFF, &mut interrupt);
+        mapper.write_register(0xF000, 0x06, &mut interrupt);
+        // 音色 1 (バイオリン) で発音
+        for (register, value) in [(0x30, 0x10), (0x10, 0xAC), (0x20, 0x18)] {
+            mapper.write_register(0x9010, register, &mut interrupt);
+            mapper.write_register(0x9030, value, &mut interrupt);
+        }
+        mapper.step_cpu_cycles(0x2000, &mut interrupt);
+        interrupt.clear_irq(IrqSource::EXTERNAL);
+
+        let state = mapper.export_runtime_state().unwrap();
+        let mut restored = new_mapper(0);
+        restored.import_runtime_state(state).unwrap();
+        assert_ne!(restored.expansion_audio_output(), 0.0);
+        assert_eq!(
+            restored.expansion_audio_output(),
+            mapper.expansion_audio_output()
+        );
+
+        Mapper::step(&mut restored, &mut interrupt);
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+    }
+}
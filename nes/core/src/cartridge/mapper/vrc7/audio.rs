@@ -0,0 +1,507 @@
+use std::f32::consts::TAU;
+
+/// 内蔵音色 1-15 (VRC7 のダイ解析による値)。音色 0 は $00-$07 のカスタム音色。
+const INSTRUMENT_ROM: [[u8; 8]; 15] = [
+    [0x03, 0x21, 0x05, 0x06, 0xE8, 0x81, 0x42, 0x27],
+    [0x13, 0x41, 0x14, 0x0D, 0xD8, 0xF6, 0x23, 0x12],
+    [0x11, 0x11, 0x08, 0x08, 0xFA, 0xB2, 0x20, 0x12],
+    [0x31, 0x61, 0x0C, 0x07, 0xA8, 0x64, 0x61, 0x27],
+    [0x32, 0x21, 0x1E, 0x06, 0xE1, 0x76, 0x01, 0x28],
+    [0x02, 0x01, 0x06, 0x00, 0xA3, 0xE2, 0xF4, 0xF4],
+    [0x21, 0x61, 0x1D, 0x07, 0x82, 0x81, 0x11, 0x07],
+    [0x23, 0x21, 0x22, 0x17, 0xA2, 0x72, 0x01, 0x17],
+    [0x35, 0x11, 0x25, 0x00, 0x40, 0x73, 0x72, 0x01],
+    [0xB5, 0x01, 0x0F, 0x0F, 0xA8, 0xA5, 0x51, 0x02],
+    [0x17, 0xC1, 0x24, 0x07, 0xF8, 0xF8, 0x22, 0x12],
+    [0x71, 0x23, 0x11, 0x06, 0x65, 0x74, 0x18, 0x16],
+    [0x01, 0x02, 0xD3, 0x05, 0xC9, 0x95, 0x03, 0x02],
+    [0x61, 0x63, 0x0C, 0x00, 0x94, 0xC0, 0x33, 0xF6],
+    [0x21, 0x72, 0x0D, 0x00, 0xC1, 0xD5, 0x56, 0x06],
+];
+/// MULT の倍率 (1/2 単位)。
+const MULTIPLE_X2: [u32; 16] = [1, 2, 4, 6, 8, 10, 12, 14, 16, 18, 20, 20, 24, 24, 30, 30];
+/// F-Number 上位 4bit ごとのキースケール基準値 (0.75dB 単位)。
+const KSL_ROM: [u8; 16] = [
+    0, 32, 40, 45, 48, 51, 53, 55, 56, 58, 59, 60, 61, 62, 63, 64,
+];
+/// ビブラートの 8 段階 (F-Number の 1/2 単位)。
+const VIBRATO_STEPS: [i32; 8] = [0, 1, 2, 1, 0, -1, -2, -1];
+
+/// 1 サンプルあたりの CPU サイクル数 (OPLL は CPU の 2 倍で動き、72 クロックで 1 サンプル)。
+const SAMPLE_CYCLES: u8 = 36;
+/// 位相カウンタの 1 周期。
+const PHASE_PERIOD: u32 = 1 << 19;
+/// エンベロープカウンタの全域 (7bit のレベル × 2^15)。
+const ENVELOPE_RANGE: u32 = 1 << 22;
+const ENVELOPE_LEVEL_SHIFT: u32 = 15;
+const ENVELOPE_STEP_DB: f32 = 0.375;
+/// 約 3.7Hz の AM (トレモロ) 周期 (サンプル数)。
+const TREMOLO_PERIOD: u16 = 13_432;
+const TREMOLO_DEPTH_DB: f32 = 4.8;
+/// 音量 0 の 1ch が 2A03 の矩形波 1ch の最大値と同程度になるよう揃える。
+const OUTPUT_SCALE: f32 = 0.15;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvelopeState {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+    Off,
+}
+
+/// 音色データから取り出したオペレータ 1 つ分のパラメータ。
+struct OperatorPatch {
+    tremolo: bool,
+    vibrato: bool,
+    sustained: bool,
+    key_scale_rate: bool,
+    multiple: u8,
+    key_scale_level: u8,
+    rectified: bool,
+    attack: u8,
+    decay: u8,
+    sustain_level: u8,
+    release: u8,
+}
+
+impl OperatorPatch {
+    /// `slot` は 0 がモジュレータ、1 がキャリア。
+    fn new(patch: &[u8; 8], slot: usize) -> Self {
+        let flags = patch[slot];
+        Self {
+            tremolo: flags & 0x80 != 0,
+            vibrato: flags & 0x40 != 0,
+            sustained: flags & 0x20 != 0,
+            key_scale_rate: flags & 0x10 != 0,
+            multiple: flags & 0x0F,
+            key_scale_level: patch[2 + slot] >> 6,
+            rectified: patch[3] & (0x08 << slot) != 0,
+            attack: patch[4 + slot] >> 4,
+            decay: patch[4 + slot] & 0x0F,
+            sustain_level: patch[6 + slot] >> 4,
+            release: patch[6 + slot] & 0x0F,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+struct Operator {
+    phase: u32,
+    envelope_state: EnvelopeState,
+    envelope_counter: u32,
+    output: f32,
+    previous_output: f32,
+}
+
+impl Default for Operator {
+    fn default() -> Self {
+        Self {
+            phase: 0,
+            envelope_state: EnvelopeState::Off,
+            envelope_counter: ENVELOPE_RANGE,
+            output: 0.0,
+            previous_output: 0.0,
+        }
+    }
+}
+
+impl Operator {
+    fn key_on(&mut self, patch: &OperatorPatch) {
+        self.phase = 0;
+        self.envelope_counter = 0;
+        // AR=15 はアタックを飛ばして最大音量から始める
+        self.envelope_state = if patch.attack == 15 {
+            EnvelopeState::Decay
+        } else {
+            EnvelopeState::Attack
+        };
+    }
+
+    fn key_off(&mut self) {
+        if self.envelope_state == EnvelopeState::Off {
+            return;
+        }
+        if self.envelope_state == EnvelopeState::Attack {
+            // アタック途中の音量からリリースを始める
+            self.envelope_counter = (self.envelope_level() / 127.0 * ENVELOPE_RANGE as f32) as u32;
+        }
+        self.envelope_state = EnvelopeState::Release;
+    }
+
+    fn step_envelope(&mut self, patch: &OperatorPatch, key_scale: u8, channel_sustain: bool) {
+        let rate = match self.envelope_state {
+            EnvelopeState::Attack => patch.attack,
+            EnvelopeState::Decay => patch.decay,
+            EnvelopeState::Sustain if patch.sustained => 0,
+            EnvelopeState::Sustain => patch.release,
+            EnvelopeState::Release if channel_sustain => 5,
+            EnvelopeState::Release if patch.sustained => patch.release,
+            EnvelopeState::Release => 7,
+            EnvelopeState::Off => return,
+        };
+        let key_scale = if patch.key_scale_rate {
+            key_scale
+        } else {
+            key_scale >> 2
+        };
+        let increment = envelope_increment(
+            rate,
+            key_scale,
+            self.envelope_state == EnvelopeState::Attack,
+        );
+        self.envelope_counter = self.envelope_counter.saturating_add(increment);
+        match self.envelope_state {
+            EnvelopeState::Attack if self.envelope_counter >= ENVELOPE_RANGE => {
+                self.envelope_counter = 0;
+                self.envelope_state = EnvelopeState::Decay;
+            }
+            EnvelopeState::Decay
+                if self.envelope_counter >> ENVELOPE_LEVEL_SHIFT
+                    >= u32::from(patch.sustain_level) * 8 =>
+            {
+                self.envelope_state = EnvelopeState::Sustain;
+            }
+            EnvelopeState::Sustain | EnvelopeState::Release
+                if self.envelope_counter >= ENVELOPE_RANGE =>
+            {
+                self.envelope_counter = ENVELOPE_RANGE;
+                self.envelope_state = EnvelopeState::Off;
+            }
+            _ => {}
+        }
+    }
+
+    /// 0 (最大音量) から 127 (無音) までのエンベロープ減衰量。
+    fn envelope_level(&self) -> f32 {
+        match self.envelope_state {
+            EnvelopeState::Off => 127.0,
+            // アタックは指数カーブで立ち上がる
+            EnvelopeState::Attack => {
+                let progress = (self.envelope_counter >> ENVELOPE_LEVEL_SHIFT) as f32;
+                if progress < 1.0 {
+                    127.0
+                } else {
+                    127.0 * (1.0 - progress.ln() / 128f32.ln())
+                }
+            }
+            _ => (self.envelope_counter >> ENVELOPE_LEVEL_SHIFT).min(127) as f32,
+        }
+    }
+
+    /// `modulation` は位相へ加える周期数。減衰量は dB で受け取る。
+    fn compute(&mut self, modulation: f32, attenuation_db: f32, rectified: bool) -> f32 {
+        self.previous_output = self.output;
+        if self.envelope_state == EnvelopeState::Off {
+            self.output = 0.0;
+            return 0.0;
+        }
+        let angle = (self.phase as f32 / PHASE_PERIOD as f32 + modulation) * TAU;
+        let wave = angle.sin();
+        let wave = if rectified && wave < 0.0 { 0.0 } else { wave };
+        let total_db = self.envelope_level() * ENVELOPE_STEP_DB + attenuation_db;
+        self.output = wave * 10f32.powf(-total_db / 20.0);
+        self.output
+    }
+}
+
+/// `rate` と `key_scale` から 1 サンプルあたりのエンベロープカウンタ増分を求める。
+fn envelope_increment(rate: u8, key_scale: u8, attack: bool) -> u32 {
+    if rate == 0 {
+        return 0;
+    }
+    let rate_high = u32::from((rate + (key_scale >> 2)).min(15));
+    let rate_low = u32::from(key_scale & 0x03);
+    if attack {
+        (3 * (rate_low + 4)) << (rate_high + 1)
+    } else {
+        (rate_low + 4) << (rate_high - 1)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+struct Channel {
+    /// 9bit の F-Number。
+    frequency: u16,
+    block: u8,
+    sustain: bool,
+    key: bool,
+    instrument: u8,
+    volume: u8,
+    operators: [Operator; 2],
+}
+
+impl Channel {
+    /// KSR と KSL の基準になる (ブロック, F-Number 最上位) の 4bit 値。
+    fn key_scale(&self) -> u8 {
+        (self.block << 1) | (self.frequency >> 8) as u8
+    }
+
+    fn key_scale_level_db(&self, key_scale_level: u8) -> f32 {
+        if key_scale_level == 0 {
+            return 0.0;
+        }
+        let base = i32::from(KSL_ROM[usize::from(self.frequency >> 5)]) * 4
+            - (i32::from(8 - self.block) << 5);
+        (base.max(0) >> (3 - key_scale_level)) as f32 * 0.1875
+    }
+}
+
+/// VRC7 拡張音源 (YM2413 互換の 2 オペレータ FM 6ch)。
+/// $9010 でレジスタを選び、$9030 で値を書き込む。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+pub(crate) struct Vrc7Audio {
+    register: u8,
+    divider: u8,
+    custom_patch: [u8; 8],
+    channels: [Channel; 6],
+    tremolo_counter: u16,
+    vibrato_counter: u16,
+    /// マッパーの $E000 bit6。立っている間は無音。
+    silenced: bool,
+}
+
+impl Default for Vrc7Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vrc7Audio {
+    pub(crate) fn new() -> Self {
+        Self {
+            register: 0,
+            divider: 0,
+            custom_patch: [0; 8],
+            channels: Default::default(),
+            tremolo_counter: 0,
+            vibrato_counter: 0,
+            silenced: false,
+        }
+    }
+
+    pub(crate) fn write_register_select(&mut self, value: u8) {
+        self.register = value;
+    }
+
+    pub(crate) fn write_register_data(&mut self, value: u8) {
+        let register = self.register;
+        match register {
+            0x00..=0x07 => self.custom_patch[usize::from(register)] = value,
+            0x10..=0x15 => {
+                let channel = &mut self.channels[usize::from(register & 0x0F)];
+                channel.frequency = (channel.frequency & 0x100) | u16::from(value);
+            }
+            0x20..=0x25 => {
+                let index = usize::from(register & 0x0F);
+                let patch = self.patch(self.channels[index].instrument);
+                let channel = &mut self.channels[index];
+                channel.frequency = (channel.frequency & 0xFF) | (u16::from(value & 0x01) << 8);
+                channel.block = (value >> 1) & 0x07;
+                channel.sustain = value & 0x20 != 0;
+                let key = value & 0x10 != 0;
+                if key && !channel.key {
+                    for (slot, operator) in channel.operators.iter_mut().enumerate() {
+                        operator.key_on(&OperatorPatch::new(&patch, slot));
+                    }
+                } else if !key && channel.key {
+                    for operator in &mut channel.operators {
+                        operator.key_off();
+                    }
+                }
+                channel.key = key;
+            }
+            0x30..=0x35 => {
+                let channel = &mut self.channels[usize::from(register & 0x0F)];
+                channel.instrument = value >> 4;
+                channel.volume = value & 0x0F;
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn set_silenced(&mut self, silenced: bool) {
+        self.silenced = silenced;
+    }
+
+    fn patch(&self, instrument: u8) -> [u8; 8] {
+        match instrument {
+            0 => self.custom_patch,
+            _ => INSTRUMENT_ROM[usize::from(instrument - 1)],
+        }
+    }
+
+    /// CPU 1 サイクル分進める。
+    pub(crate) fn step(&mut self) {
+        self.step_cycles(1);
+    }
+
+    pub(crate) fn step_cycles(&mut self, cycles: u64) {
+        let total = u64::from(self.divider) + cycles;
+        self.divider = (total % u64::from(SAMPLE_CYCLES)) as u8;
+        for _ in 0..total / u64::from(SAMPLE_CYCLES) {
+            self.clock_sample();
+        }
+    }
+
+    fn clock_sample(&mut self) {
+        self.tremolo_counter = (self.tremolo_counter + 1) % TREMOLO_PERIOD;
+        self.vibrato_counter = self.vibrato_counter.wrapping_add(1);
+        let half = f32::from(TREMOLO_PERIOD / 2);
+        let tremolo_position = f32::from(self.tremolo_counter);
+        let tremolo_db = if tremolo_position < half {
+            tremolo_position / half
+        } else {
+            (f32::from(TREMOLO_PERIOD) - tremolo_position) / half
+        } * TREMOLO_DEPTH_DB;
+        let vibrato = VIBRATO_STEPS[usize::from((self.vibrato_counter >> 10) & 0x07)];
+
+        for index in 0..self.channels.len() {
+            let patch = self.patch(self.channels[index].instrument);
+            let channel = &mut self.channels[index];
+            let key_scale = channel.key_scale();
+            let slots = [OperatorPatch::new(&patch, 0), OperatorPatch::new(&patch, 1)];
+            for (operator, slot) in channel.operators.iter_mut().zip(&slots) {
+                operator.step_envelope(slot, key_scale, channel.sustain);
+                let frequency = 2 * i32::from(channel.frequency)
+                    + if slot.vibrato {
+                        i32::from(channel.frequency >> 6) * vibrato
+                    } else {
+                        0
+                    };
+                let increment = (((frequency.max(0) as u32) << channel.block)
+                    * MULTIPLE_X2[usize::from(slot.multiple)])
+                    >> 2;
+                operator.phase = (operator.phase + increment) & (PHASE_PERIOD - 1);
+            }
+
+            let attenuation = |slot: &OperatorPatch, level_db: f32| {
+                level_db
+                    + channel.key_scale_level_db(slot.key_scale_level)
+                    + if slot.tremolo { tremolo_db } else { 0.0 }
+            };
+            let modulator_db = attenuation(&slots[0], f32::from(patch[2] & 0x3F) * 0.75);
+            let carrier_db = attenuation(&slots[1], f32::from(channel.volume) * 3.0);
+
+            // フィードバックは直前 2 サンプルの平均。FB=7 で ±4π
+            let feedback = patch[3] & 0x07;
+            let [modulator, carrier] = &mut channel.operators;
+            let self_modulation = if feedback == 0 {
+                0.0
+            } else {
+                (modulator.output + modulator.previous_output) / f32::from(1u8 << (7 - feedback))
+            };
+            let modulation = modulator.compute(self_modulation, modulator_db, slots[0].rectified);
+            // キャリアへの変調は最大 ±8π
+            carrier.compute(modulation * 4.0, carrier_db, slots[1].rectified);
+        }
+    }
+
+    pub(crate) fn output(&self) -> f32 {
+        if self.silenced {
+            return 0.0;
+        }
+        let sum = self
+            .channels
+            .iter()
+            .map(|channel| channel.operators[1].output)
+            .sum::<f32>();
+        sum * OUTPUT_SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SAMPLE_CYCLES, Vrc7Audio};
+
+    fn write(audio: &mut Vrc7Audio, register: u8, value: u8) {
+        audio.write_register_select(register);
+        audio.write_register_data(value);
+    }
+
+    /// モジュレータを無音にした、持続するサイン波のカスタム音色。
+    fn write_sine_patch(audio: &mut Vrc7Audio) {
+        for (register, value) in [0x20, 0x21, 0x3F, 0x00, 0xF0, 0xF0, 0x00, 0x0F]
+            .into_iter()
+            .enumerate()
+        {
+            write(audio, register as u8, value);
+        }
+    }
+
+    fn peak(audio: &mut Vrc7Audio, samples: usize) -> f32 {
+        (0..samples)
+            .map(|_| {
+                audio.step_cycles(u64::from(SAMPLE_CYCLES));
+                audio.output().abs()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn frequency_number_and_block_set_pitch() {
+        let mut audio = Vrc7Audio::new();
+        write_sine_patch(&mut audio);
+        write(&mut audio, 0x30, 0x00);
+        write(&mut audio, 0x10, 0x00);
+        // F-Number 0x100、ブロック 4 で約 388Hz
+        write(&mut audio, 0x20, 0x19);
+
+        let samples = 49_716 / 4;
+        let mut last = audio.output() >= 0.0;
+        let mut crossings = 0;
+        for _ in 0..samples {
+            audio.step_cycles(u64::from(SAMPLE_CYCLES));
+            let positive = audio.output() >= 0.0;
+            if positive != last {
+                crossings += 1;
+                last = positive;
+            }
+        }
+        let frequency = crossings as f32 / 2.0 * 4.0;
+        assert!((frequency - 388.4).abs() < 4.0, "frequency = {frequency}");
+    }
+
+    #[test]
+    fn volume_register_attenuates_in_3db_steps() {
+        let mut audio = Vrc7Audio::new();
+        write_sine_patch(&mut audio);
+        write(&mut audio, 0x10, 0x00);
+        write(&mut audio, 0x20, 0x19);
+
+        write(&mut audio, 0x30, 0x00);
+        let full = peak(&mut audio, 1024);
+        write(&mut audio, 0x30, 0x02);
+        let minus_6db = peak(&mut audio, 1024);
+        assert!(full > 0.1);
+        assert!(
+            (minus_6db / full - 0.5).abs() < 0.03,
+            "{minus_6db} / {full}"
+        );
+    }
+
+    #[test]
+    fn built_in_instrument_sounds_until_released() {
+        let mut audio = Vrc7Audio::new();
+        assert_eq!(audio.output(), 0.0);
+        // 音色 3 (ピアノ)
+        write(&mut audio, 0x30, 0x30);
+        write(&mut audio, 0x10, 0xAC);
+        write(&mut audio, 0x20, 0x18);
+        assert!(peak(&mut audio, 2048) > 0.0);
+
+        write(&mut audio, 0x20, 0x08);
+        peak(&mut audio, 49_716);
+        assert_eq!(peak(&mut audio, 256), 0.0);
+    }
+
+    #[test]
+    fn silence_bit_mutes_output() {
+        let mut audio = Vrc7Audio::new();
+        write_sine_patch(&mut audio);
+        write(&mut audio, 0x20, 0x19);
+        assert!(peak(&mut audio, 256) > 0.0);
+        audio.set_silenced(true);
+        assert_eq!(peak(&mut audio, 256), 0.0);
+    }
+}
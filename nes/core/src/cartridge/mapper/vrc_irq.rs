@@ -0,0 +1,138 @@
+use crate::interrupt::{Interrupt, IrqSource};
+
+const CONTROL_ENABLE_AFTER_ACK: u8 = 0x01;
+const CONTROL_ENABLE: u8 = 0x02;
+const CONTROL_CYCLE_MODE: u8 = 0x04;
+/// スキャンラインモードのプリスケーラ。CPU 1 サイクルごとに 3 減らし、341 PPU ドット相当で 1 回カウントする。
+const PRESCALER_RELOAD: i16 = 341;
+
+/// VRC4 / VRC6 / VRC7 共通の CPU サイクル IRQ カウンタ。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub(super) struct VrcIrq {
+    latch: u8,
+    control: u8,
+    counter: u8,
+    prescaler: i16,
+}
+
+impl VrcIrq {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(super) fn write_latch(&mut self, value: u8) {
+        self.latch = value;
+    }
+
+    /// VRC4 はラッチを 4bit ずつ 2 つのレジスタで書く。
+    pub(super) fn write_latch_low(&mut self, value: u8) {
+        self.latch = (self.latch & 0xF0) | (value & 0x0F);
+    }
+
+    pub(super) fn write_latch_high(&mut self, value: u8) {
+        self.latch = (self.latch & 0x0F) | (value << 4);
+    }
+
+    pub(super) fn write_control(&mut self, value: u8, interrupt: &mut Interrupt) {
+        self.control = value & 0x07;
+        if self.control & CONTROL_ENABLE != 0 {
+            self.counter = self.latch;
+            self.prescaler = PRESCALER_RELOAD;
+        }
+        interrupt.clear_irq(IrqSource::EXTERNAL);
+    }
+
+    /// A ビットを E ビットへ写して IRQ を取り下げる。
+    pub(super) fn acknowledge(&mut self, interrupt: &mut Interrupt) {
+        if self.control & CONTROL_ENABLE_AFTER_ACK != 0 {
+            self.control |= CONTROL_ENABLE;
+        } else {
+            self.control &= !CONTROL_ENABLE;
+        }
+        interrupt.clear_irq(IrqSource::EXTERNAL);
+    }
+
+    /// CPU 1 サイクル分進める。
+    pub(super) fn step(&mut self, interrupt: &mut Interrupt) {
+        if self.control & CONTROL_ENABLE == 0 {
+            return;
+        }
+        if self.control & CONTROL_CYCLE_MODE != 0 {
+            self.clock_counter(interrupt);
+            return;
+        }
+        self.prescaler -= 3;
+        if self.prescaler <= 0 {
+            self.prescaler += PRESCALER_RELOAD;
+            self.clock_counter(interrupt);
+        }
+    }
+
+    /// 次に IRQ が立つまでの CPU サイクル数の下限。止まっている場合は `u64::MAX`。
+    pub(super) fn cycles_until_irq(&self) -> u64 {
+        if self.control & CONTROL_ENABLE == 0 {
+            return u64::MAX;
+        }
+        let clocks = 0x100 - u64::from(self.counter);
+        if self.control & CONTROL_CYCLE_MODE != 0 {
+            return clocks;
+        }
+        // 最初のカウントまでは残りのプリスケーラ、以降は 1 ラインあたり 113 サイクル以上
+        let first = (self.prescaler.max(1) as u64).div_ceil(3);
+        first + (clocks - 1) * u64::from(PRESCALER_RELOAD as u16 / 3)
+    }
+
+    fn clock_counter(&mut self, interrupt: &mut Interrupt) {
+        if self.counter == 0xFF {
+            self.counter = self.latch;
+            interrupt.set_irq(IrqSource::EXTERNAL);
+        } else {
+            self.counter += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VrcIrq;
+    use crate::interrupt::{Interrupt, IrqSource};
+
+    #[test]
+    fn cycle_mode_fires_after_latch_to_overflow() {
+        let mut irq = VrcIrq::new();
+        let mut interrupt = Interrupt::new();
+        irq.write_latch(0xFC);
+        irq.write_control(0x06, &mut interrupt);
+
+        for _ in 0..3 {
+            irq.step(&mut interrupt);
+        }
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+        irq.step(&mut interrupt);
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+
+        // A ビットが 0 なので応答後は止まる
+        irq.acknowledge(&mut interrupt);
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+        for _ in 0..0x200 {
+            irq.step(&mut interrupt);
+        }
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+    }
+
+    #[test]
+    fn scanline_mode_counts_every_341_ppu_dots() {
+        let mut irq = VrcIrq::new();
+        let mut interrupt = Interrupt::new();
+        irq.write_latch(0xFE);
+        irq.write_control(0x02, &mut interrupt);
+
+        // 2 ライン分 (682 ドット) は 227.33 CPU サイクル
+        for _ in 0..227 {
+            irq.step(&mut interrupt);
+        }
+        assert!(!interrupt.get_irq(IrqSource::EXTERNAL));
+        irq.step(&mut interrupt);
+        assert!(interrupt.get_irq(IrqSource::EXTERNAL));
+    }
+}
@@ -1,7 +1,10 @@
+use nerust_core_traits::debug::BreakpointKind;
 use nerust_input_traits::OpenBusReadResult;
 
 use crate::{
     cart_device::Cartridge as MapperCartridge,
+    cheat::CheatEngine,
+    debugger::Debugger,
     interrupt::Interrupt,
     mapper::Mapper,
     ppu_memory_access::{PpuBusEvent, PpuReadAccess},
@@ -94,6 +97,8 @@ pub(crate) trait CpuCartridgeBus: PpuCartridgeBus {
     fn read(&self, address: usize) -> OpenBusReadResult;
     fn write(&mut self, address: usize, value: u8, interrupt: &mut Interrupt);
     fn notify_cpu_read(&mut self, address: usize, value: u8, interrupt: &mut Interrupt);
+    /// CPU の全ての書き込みで、書き込み先に関係なく呼ばれる。
+    fn notify_cpu_write(&mut self, _address: usize, _value: u8) {}
     fn notify_oam_dma(&mut self, interrupt: &mut Interrupt);
 }
 
@@ -252,3 +257,198 @@ impl PpuCartridgeBus for CpuPpuCartridgeBus<'_> {
 pub(crate) fn cpu_ppu_cartridge_bus(cartridge: &mut dyn CpuCartridgeBus) -> CpuPpuCartridgeBus<'_> {
     CpuPpuCartridgeBus(cartridge)
 }
+
+/// Game Genie のチートで $8000-$FFFF の読み出しを置き換える CPU バス。
+pub(crate) struct CheatCartridgeBus<'a> {
+    inner: &'a mut dyn CpuCartridgeBus,
+    cheats: &'a CheatEngine,
+}
+
+impl PpuCartridgeBus for CheatCartridgeBus<'_> {
+    fn read_ppu_pattern(
+        &mut self,
+        address: usize,
+        access: PpuReadAccess,
+        interrupt: &mut Interrupt,
+    ) -> OpenBusReadResult {
+        PpuCartridgeBus::read_ppu_pattern(self.inner, address, access, interrupt)
+    }
+
+    fn write_ppu_pattern(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        PpuCartridgeBus::write_ppu_pattern(self.inner, address, value, interrupt);
+    }
+
+    fn read_ppu_nametable(
+        &mut self,
+        address: usize,
+        access: PpuReadAccess,
+        ciram: &mut [u8],
+    ) -> OpenBusReadResult {
+        PpuCartridgeBus::read_ppu_nametable(self.inner, address, access, ciram)
+    }
+
+    fn write_ppu_nametable(
+        &mut self,
+        address: usize,
+        value: u8,
+        ciram: &mut [u8],
+        interrupt: &mut Interrupt,
+    ) {
+        PpuCartridgeBus::write_ppu_nametable(self.inner, address, value, ciram, interrupt);
+    }
+
+    fn peek_ppu_nametable(&self, address: usize, ciram: &[u8]) -> Option<u8> {
+        PpuCartridgeBus::peek_ppu_nametable(self.inner, address, ciram)
+    }
+
+    fn notify_ppu_status_read(&mut self, value: u8, interrupt: &mut Interrupt) {
+        PpuCartridgeBus::notify_ppu_status_read(self.inner, value, interrupt);
+    }
+
+    fn notify_ppu_ctrl(&mut self, value: u8) {
+        PpuCartridgeBus::notify_ppu_ctrl(self.inner, value);
+    }
+
+    fn notify_ppu_mask(&mut self, value: u8) {
+        PpuCartridgeBus::notify_ppu_mask(self.inner, value);
+    }
+
+    fn notify_ppu_bus_event(&mut self, event: PpuBusEvent, interrupt: &mut Interrupt) {
+        PpuCartridgeBus::notify_ppu_bus_event(self.inner, event, interrupt);
+    }
+}
+
+impl CpuCartridgeBus for CheatCartridgeBus<'_> {
+    fn read(&self, address: usize) -> OpenBusReadResult {
+        let result = CpuCartridgeBus::read(self.inner, address);
+        if address < 0x8000 {
+            return result;
+        }
+        match self.cheats.patch_read(address, result.data) {
+            Some(value) => OpenBusReadResult::new(value, 0xFF),
+            None => result,
+        }
+    }
+
+    fn write(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        CpuCartridgeBus::write(self.inner, address, value, interrupt);
+    }
+
+    fn notify_cpu_read(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        CpuCartridgeBus::notify_cpu_read(self.inner, address, value, interrupt);
+    }
+
+    fn notify_cpu_write(&mut self, address: usize, value: u8) {
+        CpuCartridgeBus::notify_cpu_write(self.inner, address, value);
+    }
+
+    fn notify_oam_dma(&mut self, interrupt: &mut Interrupt) {
+        CpuCartridgeBus::notify_oam_dma(self.inner, interrupt);
+    }
+}
+
+pub(crate) fn cheat_cartridge_bus<'a>(
+    cartridge: &'a mut dyn CpuCartridgeBus,
+    cheats: &'a CheatEngine,
+) -> CheatCartridgeBus<'a> {
+    CheatCartridgeBus {
+        inner: cartridge,
+        cheats,
+    }
+}
+
+/// デバッガの読み書きブレークポイントのために CPU バスのアクセスを記録する。
+pub(crate) struct DebugCartridgeBus<'a> {
+    inner: &'a mut dyn CpuCartridgeBus,
+    debugger: &'a mut Debugger,
+}
+
+impl PpuCartridgeBus for DebugCartridgeBus<'_> {
+    fn read_ppu_pattern(
+        &mut self,
+        address: usize,
+        access: PpuReadAccess,
+        interrupt: &mut Interrupt,
+    ) -> OpenBusReadResult {
+        PpuCartridgeBus::read_ppu_pattern(self.inner, address, access, interrupt)
+    }
+
+    fn write_ppu_pattern(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        PpuCartridgeBus::write_ppu_pattern(self.inner, address, value, interrupt);
+    }
+
+    fn read_ppu_nametable(
+        &mut self,
+        address: usize,
+        access: PpuReadAccess,
+        ciram: &mut [u8],
+    ) -> OpenBusReadResult {
+        PpuCartridgeBus::read_ppu_nametable(self.inner, address, access, ciram)
+    }
+
+    fn write_ppu_nametable(
+        &mut self,
+        address: usize,
+        value: u8,
+        ciram: &mut [u8],
+        interrupt: &mut Interrupt,
+    ) {
+        PpuCartridgeBus::write_ppu_nametable(self.inner, address, value, ciram, interrupt);
+    }
+
+    fn peek_ppu_nametable(&self, address: usize, ciram: &[u8]) -> Option<u8> {
+        PpuCartridgeBus::peek_ppu_nametable(self.inner, address, ciram)
+    }
+
+    fn notify_ppu_status_read(&mut self, value: u8, interrupt: &mut Interrupt) {
+        PpuCartridgeBus::notify_ppu_status_read(self.inner, value, interrupt);
+    }
+
+    fn notify_ppu_ctrl(&mut self, value: u8) {
+        PpuCartridgeBus::notify_ppu_ctrl(self.inner, value);
+    }
+
+    fn notify_ppu_mask(&mut self, value: u8) {
+        PpuCartridgeBus::notify_ppu_mask(self.inner, value);
+    }
+
+    fn notify_ppu_bus_event(&mut self, event: PpuBusEvent, interrupt: &mut Interrupt) {
+        PpuCartridgeBus::notify_ppu_bus_event(self.inner, event, interrupt);
+    }
+}
+
+impl CpuCartridgeBus for DebugCartridgeBus<'_> {
+    fn read(&self, address: usize) -> OpenBusReadResult {
+        CpuCartridgeBus::read(self.inner, address)
+    }
+
+    fn write(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        CpuCartridgeBus::write(self.inner, address, value, interrupt);
+    }
+
+    fn notify_cpu_read(&mut self, address: usize, value: u8, interrupt: &mut Interrupt) {
+        self.debugger
+            .record_access(BreakpointKind::Read, address, value);
+        CpuCartridgeBus::notify_cpu_read(self.inner, address, value, interrupt);
+    }
+
+    fn notify_cpu_write(&mut self, address: usize, value: u8) {
+        self.debugger
+            .record_access(BreakpointKind::Write, address, value);
+        CpuCartridgeBus::notify_cpu_write(self.inner, address, value);
+    }
+
+    fn notify_oam_dma(&mut self, interrupt: &mut Interrupt) {
+        CpuCartridgeBus::notify_oam_dma(self.inner, interrupt);
+    }
+}
+
+pub(crate) fn debug_cartridge_bus<'a>(
+    cartridge: &'a mut dyn CpuCartridgeBus,
+    debugger: &'a mut Debugger,
+) -> DebugCartridgeBus<'a> {
+    DebugCartridgeBus {
+        inner: cartridge,
+        debugger,
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{mirror::MirrorMode, rom_format::RomFormat};
+use crate::{mirror::MirrorMode, rom_format::RomFormat, status::console_type::ConsoleType};
 
 #[derive(Debug, Clone)]
 pub struct CartridgeDataParts {
@@ -14,4 +14,5 @@ pub struct CartridgeDataParts {
     pub has_battery: bool,
     pub sub_mapper_type: u8,
     pub trainer: Vec<u8>,
+    pub console_type: Option<ConsoleType>,
 }
@@ -4,4 +4,8 @@ pub enum CartridgeError {
     DataError,
     #[error("file ends unexpectedly")]
     UnexpectedEof,
+    #[error("FDS BIOS image is required")]
+    MissingBios,
+    #[error("FDS BIOS image must be 8 KiB")]
+    InvalidBios,
 }
@@ -1,8 +1,13 @@
 use crate::{
     cartridge_data_parts::CartridgeDataParts, cartridge_error::CartridgeError, mirror::MirrorMode,
-    rom_format::RomFormat,
+    nsf_info::NsfInfo, rom_format::RomFormat, status::console_type::ConsoleType,
 };
 
+/// fwNES ヘッダを除いた FDS ディスク 1 面分のバイト数。
+pub const FDS_DISK_SIDE_LEN: usize = 65500;
+/// FDS BIOS (DISKSYS.ROM) のバイト数。
+pub const FDS_BIOS_LEN: usize = 0x2000;
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 pub struct CartridgeData {
     format: RomFormat,
@@ -22,6 +27,16 @@ pub struct CartridgeData {
     #[serde(default)]
     #[serde(with = "serde_bytes")]
     trainer: Vec<u8>,
+    #[serde(default)]
+    console_type: Option<ConsoleType>,
+    /// FDS の RAM アダプタが $E000-$FFFF に見せる BIOS。ディスクイメージには含まれないため
+    /// ロード時に別途設定する。
+    #[serde(default)]
+    #[serde(with = "serde_bytes")]
+    bios: Vec<u8>,
+    /// NSF / NSFe の場合のみ存在する再生情報。
+    #[serde(default)]
+    nsf: Option<NsfInfo>,
 }
 
 impl CartridgeData {
@@ -39,6 +54,9 @@ impl CartridgeData {
             has_battery: parts.has_battery,
             sub_mapper_type: parts.sub_mapper_type,
             trainer: parts.trainer,
+            console_type: parts.console_type,
+            bios: Vec::new(),
+            nsf: None,
         };
         data.validate()?;
         Ok(data)
@@ -66,6 +84,14 @@ impl CartridgeData {
             return Err(CartridgeError::DataError);
         }
 
+        if self.format == RomFormat::Fds && !self.prog_rom.len().is_multiple_of(FDS_DISK_SIDE_LEN) {
+            return Err(CartridgeError::DataError);
+        }
+
+        if !self.bios.is_empty() && self.bios.len() != FDS_BIOS_LEN {
+            return Err(CartridgeError::InvalidBios);
+        }
+
         Ok(())
     }
 
@@ -109,6 +135,10 @@ impl CartridgeData {
         self.prog_rom[index] = data;
     }
 
+    pub fn write_char_rom(&mut self, index: usize, data: u8) {
+        self.char_rom[index] = data;
+    }
+
     pub fn mirror_mode(&self) -> MirrorMode {
         self.mirror_mode
     }
@@ -136,6 +166,40 @@ impl CartridgeData {
     pub fn trainer(&self) -> &[u8] {
         &self.trainer
     }
+
+    /// ヘッダで指定された CPU/PPU タイミング。iNES や multiple-region の場合は `None`。
+    pub fn console_type(&self) -> Option<ConsoleType> {
+        self.console_type
+    }
+
+    /// FDS ディスクイメージの面数。カートリッジの場合は 0。
+    pub fn disk_side_count(&self) -> usize {
+        if self.format == RomFormat::Fds {
+            self.prog_rom.len() / FDS_DISK_SIDE_LEN
+        } else {
+            0
+        }
+    }
+
+    pub fn bios(&self) -> &[u8] {
+        &self.bios
+    }
+
+    pub fn set_bios(&mut self, bios: Vec<u8>) -> Result<(), CartridgeError> {
+        if bios.len() != FDS_BIOS_LEN {
+            return Err(CartridgeError::InvalidBios);
+        }
+        self.bios = bios;
+        Ok(())
+    }
+
+    pub fn nsf(&self) -> Option<&NsfInfo> {
+        self.nsf.as_ref()
+    }
+
+    pub(crate) fn set_nsf(&mut self, nsf: NsfInfo) {
+        self.nsf = Some(nsf);
+    }
 }
 
 mod mirror_mode_serde {
@@ -211,6 +275,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         });
 
         assert!(result.is_err());
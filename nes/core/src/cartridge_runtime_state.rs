@@ -13,8 +13,20 @@ pub(crate) struct CartridgeRuntimeState {
 }
 
 pub(crate) const MAPPER_KIND_ACTION53: &str = "action53";
+pub(crate) const MAPPER_KIND_BANDAI_FCG: &str = "bandai_fcg";
+pub(crate) const MAPPER_KIND_FDS: &str = "fds";
 pub(crate) const MAPPER_KIND_FME7: &str = "fme7";
 pub(crate) const MAPPER_KIND_MMC2: &str = "mmc2";
 pub(crate) const MAPPER_KIND_MMC3: &str = "mmc3";
 pub(crate) const MAPPER_KIND_MMC5: &str = "mmc5";
+pub(crate) const MAPPER_KIND_MULTICART: &str = "multicart";
+pub(crate) const MAPPER_KIND_NAMCO108: &str = "namco108";
+pub(crate) const MAPPER_KIND_NAMCO163: &str = "namco163";
+pub(crate) const MAPPER_KIND_NSF: &str = "nsf";
+pub(crate) const MAPPER_KIND_RAMBO1: &str = "rambo1";
+pub(crate) const MAPPER_KIND_SUNSOFT4: &str = "sunsoft4";
 pub(crate) const MAPPER_KIND_SXROM: &str = "sxrom";
+pub(crate) const MAPPER_KIND_UNROM512: &str = "unrom512";
+pub(crate) const MAPPER_KIND_VRC4: &str = "vrc4";
+pub(crate) const MAPPER_KIND_VRC6: &str = "vrc6";
+pub(crate) const MAPPER_KIND_VRC7: &str = "vrc7";
@@ -0,0 +1,209 @@
+//! Game Genie / Pro Action Replay 形式のチートコード
+
+use std::str::FromStr;
+
+// Game Genie の 1 文字は 4bit。この並びの位置がそのまま値になる
+const GAME_GENIE_LETTERS: &[u8; 16] = b"APZLGITYEOXUKSVN";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheatCode {
+    /// $8000-$FFFF の CPU 読み出しを `value` に置き換える。
+    /// `compare` があれば、元の値が一致した時だけ置き換える (8 文字コード)。
+    GameGenie {
+        address: u16,
+        value: u8,
+        compare: Option<u8>,
+    },
+    /// 毎フレーム RAM に `value` を書き込む (`AAAA:VV` または `AAAAVV`)。
+    RamWrite { address: u16, value: u8 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CheatError {
+    #[error("unrecognized cheat code: {0}")]
+    Format(String),
+    #[error("cheat address ${0:04X} is not RAM")]
+    NotRam(u16),
+}
+
+impl FromStr for CheatCode {
+    type Err = CheatError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let normalized: String = code
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        if let Some(nibbles) = game_genie_nibbles(&normalized) {
+            return Ok(decode_game_genie(&nibbles));
+        }
+        let hex = match normalized.split_once(':') {
+            Some((address, value)) if address.len() == 4 && value.len() == 2 => {
+                format!("{address}{value}")
+            }
+            Some(_) => return Err(CheatError::Format(code.to_string())),
+            None if normalized.len() == 6 => normalized,
+            None => return Err(CheatError::Format(code.to_string())),
+        };
+        let raw =
+            u32::from_str_radix(&hex, 16).map_err(|_| CheatError::Format(code.to_string()))?;
+        let address = (raw >> 8) as u16;
+        if !matches!(address, 0x0000..=0x1FFF | 0x6000..=0x7FFF) {
+            return Err(CheatError::NotRam(address));
+        }
+        Ok(Self::RamWrite {
+            address,
+            value: raw as u8,
+        })
+    }
+}
+
+fn game_genie_nibbles(code: &str) -> Option<Vec<u8>> {
+    if code.len() != 6 && code.len() != 8 {
+        return None;
+    }
+    code.bytes()
+        .map(|c| {
+            GAME_GENIE_LETTERS
+                .iter()
+                .position(|&letter| letter == c)
+                .map(|n| n as u8)
+        })
+        .collect()
+}
+
+fn decode_game_genie(n: &[u8]) -> CheatCode {
+    let address = 0x8000
+        | (u16::from(n[3] & 7) << 12)
+        | (u16::from(n[5] & 7) << 8)
+        | (u16::from(n[4] & 8) << 8)
+        | (u16::from(n[2] & 7) << 4)
+        | (u16::from(n[1] & 8) << 4)
+        | u16::from(n[4] & 7)
+        | u16::from(n[3] & 8);
+    let value_low = (n[0] & 7) | ((n[0] & 8) << 4) | ((n[1] & 7) << 4);
+    if n.len() == 6 {
+        CheatCode::GameGenie {
+            address,
+            value: value_low | (n[5] & 8),
+            compare: None,
+        }
+    } else {
+        CheatCode::GameGenie {
+            address,
+            value: value_low | (n[7] & 8),
+            compare: Some((n[6] & 7) | ((n[6] & 8) << 4) | ((n[7] & 7) << 4) | (n[5] & 8)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ReadPatch {
+    address: usize,
+    value: u8,
+    compare: Option<u8>,
+}
+
+/// 有効なチートをバスから引きやすい形にまとめたもの。
+#[derive(Debug, Default, Clone)]
+pub(crate) struct CheatEngine {
+    reads: Vec<ReadPatch>,
+    writes: Vec<(usize, u8)>,
+}
+
+impl CheatEngine {
+    pub(crate) fn new(codes: &[CheatCode]) -> Self {
+        let mut engine = Self::default();
+        for code in codes {
+            match *code {
+                CheatCode::GameGenie {
+                    address,
+                    value,
+                    compare,
+                } => engine.reads.push(ReadPatch {
+                    address: usize::from(address),
+                    value,
+                    compare,
+                }),
+                CheatCode::RamWrite { address, value } => {
+                    engine.writes.push((usize::from(address), value));
+                }
+            }
+        }
+        engine
+    }
+
+    pub(crate) fn patches_reads(&self) -> bool {
+        !self.reads.is_empty()
+    }
+
+    /// `original` を読み出した `address` に対する置き換え値を返す。
+    pub(crate) fn patch_read(&self, address: usize, original: u8) -> Option<u8> {
+        self.reads
+            .iter()
+            .find(|patch| {
+                patch.address == address && patch.compare.is_none_or(|compare| compare == original)
+            })
+            .map(|patch| patch.value)
+    }
+
+    pub(crate) fn ram_writes(&self) -> &[(usize, u8)] {
+        &self.writes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheatCode, CheatEngine, CheatError};
+
+    #[test]
+    fn game_genie_codes_decode_address_value_and_compare() {
+        assert_eq!(
+            "SXIOPO".parse(),
+            Ok(CheatCode::GameGenie {
+                address: 0x91D9,
+                value: 0xAD,
+                compare: None,
+            })
+        );
+        assert_eq!(
+            "yeuzugaa".parse(),
+            Ok(CheatCode::GameGenie {
+                address: 0xACB3,
+                value: 0x07,
+                compare: Some(0x00),
+            })
+        );
+    }
+
+    #[test]
+    fn raw_ram_codes_accept_colon_and_packed_forms() {
+        let expected = Ok(CheatCode::RamWrite {
+            address: 0x0075,
+            value: 0x09,
+        });
+        assert_eq!("0075:09".parse(), expected);
+        assert_eq!("007509".parse(), expected);
+        assert_eq!(
+            "8000:01".parse::<CheatCode>(),
+            Err(CheatError::NotRam(0x8000))
+        );
+        assert!(matches!(
+            "HELLO".parse::<CheatCode>(),
+            Err(CheatError::Format(_))
+        ));
+    }
+
+    #[test]
+    fn compare_value_gates_read_patch() {
+        let engine = CheatEngine::new(&[CheatCode::GameGenie {
+            address: 0x8000,
+            value: 0x42,
+            compare: Some(0x10),
+        }]);
+        assert_eq!(engine.patch_read(0x8000, 0x10), Some(0x42));
+        assert_eq!(engine.patch_read(0x8000, 0x11), None);
+        assert_eq!(engine.patch_read(0x8001, 0x10), None);
+    }
+}
@@ -1,14 +1,24 @@
 use nerust_core_traits::{
-    ConsoleCore, CoreCapabilities, CoreConfig, CoreError, VideoSignalKind, audio::AudioBackend,
+    ConsoleCore, CoreCapabilities, CoreConfig, CoreError, Region, VideoSignalKind,
+    audio::AudioBackend,
+    cheat::Cheat,
+    debug::{
+        BreakReason, Breakpoint, DebugState, MemoryBlock, MemorySpace, StepMode, VideoMemoryView,
+    },
     identity::SystemIdentity,
 };
 use nerust_input_traits::{ControllerCollection, ControllerHub as _, EmuInput};
 use nerust_render_traits::{FrameBuffer, PixelFormat};
 
 use crate::{
-    Core, cartridge_rom::CartridgeData, core_options::CoreOptions, input_types::NesInputBuffer,
+    Core, cartridge_error::CartridgeError, cartridge_rom::CartridgeData, cheat::CheatCode,
+    core_options::CoreOptions, input_types::NesInputBuffer, rom_format::RomFormat,
+    status::console_type::ConsoleType,
 };
 
+/// `CoreConfig::bios_paths` で Famicom Disk System の BIOS を指すキー。
+pub const FDS_BIOS_KEY: &str = "fds";
+
 /// `Core` は `pub(crate)` な `Cartridge` trait (`Box<dyn Cartridge>`) を含む。
 /// 全ての具象 mapper は同一 crate 内 (`nes/core/src/cartridge/mapper/`) にあり、
 /// かつ全て Send であることが確認されているため、`unsafe impl Send` は安全。
@@ -24,6 +34,12 @@ pub struct NesConsoleCore {
     audio: Box<dyn AudioBackend>,
     emu_input: EmuInput,
     paused: bool,
+    audio_muted: bool,
+    rewind_state_size: Option<usize>,
+    // `cheat_codes` は `cheats` と同じ順に並ぶ解析済みのコード
+    cheats: Vec<Cheat>,
+    cheat_codes: Vec<CheatCode>,
+    breakpoints: Vec<Breakpoint>,
 }
 
 impl NesConsoleCore {
@@ -34,12 +50,18 @@ impl NesConsoleCore {
         emu_input: EmuInput,
     ) -> Result<Self, CoreError> {
         let core = Core::new(cartridge_data).map_err(CoreError::Core)?;
+        let rewind_state_size = rewind_state_size(&core);
         Ok(Self {
             core: SendCore(Some(core)),
             controller,
             audio,
             emu_input,
             paused: false,
+            audio_muted: false,
+            rewind_state_size,
+            cheats: Vec::new(),
+            cheat_codes: Vec::new(),
+            breakpoints: Vec::new(),
         })
     }
 
@@ -56,6 +78,11 @@ impl NesConsoleCore {
             audio,
             emu_input,
             paused: false,
+            audio_muted: false,
+            rewind_state_size: None,
+            cheats: Vec::new(),
+            cheat_codes: Vec::new(),
+            breakpoints: Vec::new(),
         }
     }
 }
@@ -68,13 +95,36 @@ impl NesConsoleCore {
     fn core_mut(&mut self) -> Result<&mut Core, CoreError> {
         self.core.0.as_mut().ok_or(CoreError::NoRomLoaded)
     }
+
+    fn apply_cheats(&mut self) {
+        let enabled: Vec<CheatCode> = self
+            .cheats
+            .iter()
+            .zip(&self.cheat_codes)
+            .filter(|(cheat, _)| cheat.enabled)
+            .map(|(_, code)| *code)
+            .collect();
+        if let Some(core) = self.core.0.as_mut() {
+            core.set_cheats(&enabled);
+        }
+    }
+
+    fn check_cheat_index(&self, index: usize) -> Result<(), CoreError> {
+        if index < self.cheats.len() {
+            Ok(())
+        } else {
+            Err(CoreError::Core(
+                format!("cheat index out of range: {index}").into(),
+            ))
+        }
+    }
 }
 
 impl ConsoleCore for NesConsoleCore {
     fn capabilities(&self) -> CoreCapabilities {
         CoreCapabilities {
             output_formats: vec![PixelFormat::PaletteIndex {
-                palette: Box::new([0u32; 256]),
+                palette: Box::new([0u32; 512]),
             }],
             video_signal: VideoSignalKind::Ntsc,
         }
@@ -89,16 +139,35 @@ impl ConsoleCore for NesConsoleCore {
             self.controller.sync_input(&state.0);
         }
 
-        core.run_frame(frame_slot, &mut self.controller, self.audio.as_mut());
+        if self.audio_muted {
+            let mut discard = DiscardAudio {
+                sample_rate: self.audio.sample_rate(),
+            };
+            core.run_frame(frame_slot, &mut self.controller, &mut discard);
+        } else {
+            core.run_frame(frame_slot, &mut self.controller, self.audio.as_mut());
+        }
 
         Ok(())
     }
 
-    // `region` フィールドは NES PAL 対応時に使用する。
+    // `region` が指定されていれば ROM ヘッダや CoreOptions の地域設定より優先する。
     fn load(&mut self, rom: &[u8], config: &CoreConfig) -> Result<(), CoreError> {
-        let cartridge_data =
+        let mut cartridge_data =
             crate::rom_parse::parse_rom(rom).map_err(|e| CoreError::RomParse(Box::new(e)))?;
-        let options = if let Some(core_options) = &config.core_options {
+        if cartridge_data.format() == RomFormat::Fds {
+            let path = config
+                .bios_paths
+                .get(FDS_BIOS_KEY)
+                .ok_or_else(|| CoreError::RomParse(Box::new(CartridgeError::MissingBios)))?;
+            let bios = std::fs::read(path).map_err(|e| {
+                CoreError::Core(format!("failed to read FDS BIOS {}: {e}", path.display()).into())
+            })?;
+            cartridge_data
+                .set_bios(bios)
+                .map_err(|e| CoreError::RomParse(Box::new(e)))?;
+        }
+        let mut options = if let Some(core_options) = &config.core_options {
             *core_options
                 .clone()
                 .downcast::<CoreOptions>()
@@ -106,15 +175,24 @@ impl ConsoleCore for NesConsoleCore {
         } else {
             CoreOptions::default()
         };
+        if let Some(region) = config.region {
+            options.region = Some(console_type_from_region(region));
+        }
         let core = Core::new_with_options(cartridge_data, options).map_err(CoreError::Core)?;
+        self.rewind_state_size = rewind_state_size(&core);
         self.core = SendCore(Some(core));
         self.paused = false;
+        self.clear_cheats();
+        self.breakpoints.clear();
         Ok(())
     }
 
     fn unload(&mut self) {
         self.core = SendCore(None);
         self.paused = false;
+        self.rewind_state_size = None;
+        self.clear_cheats();
+        self.breakpoints.clear();
     }
 
     fn reset(&mut self) {
@@ -145,6 +223,147 @@ impl ConsoleCore for NesConsoleCore {
         self.audio.set_volume(volume);
     }
 
+    fn set_audio_muted(&mut self, muted: bool) {
+        self.audio_muted = muted;
+    }
+
+    fn eject_disk(&mut self) {
+        if let Some(core) = self.core.0.as_mut() {
+            core.eject_disk();
+        }
+    }
+
+    fn switch_disk_side(&mut self) {
+        if let Some(core) = self.core.0.as_mut() {
+            core.switch_disk_side();
+        }
+    }
+
+    fn next_track(&mut self) {
+        if let Some(core) = self.core.0.as_mut() {
+            core.next_track();
+        }
+    }
+
+    fn previous_track(&mut self) {
+        if let Some(core) = self.core.0.as_mut() {
+            core.previous_track();
+        }
+    }
+
+    fn add_cheat(&mut self, cheat: Cheat) -> Result<(), CoreError> {
+        let code = cheat
+            .code
+            .parse::<CheatCode>()
+            .map_err(|e| CoreError::Core(Box::new(e)))?;
+        self.cheats.push(cheat);
+        self.cheat_codes.push(code);
+        self.apply_cheats();
+        Ok(())
+    }
+
+    fn set_cheat_enabled(&mut self, index: usize, enabled: bool) -> Result<(), CoreError> {
+        self.check_cheat_index(index)?;
+        self.cheats[index].enabled = enabled;
+        self.apply_cheats();
+        Ok(())
+    }
+
+    fn remove_cheat(&mut self, index: usize) -> Result<(), CoreError> {
+        self.check_cheat_index(index)?;
+        self.cheats.remove(index);
+        self.cheat_codes.remove(index);
+        self.apply_cheats();
+        Ok(())
+    }
+
+    fn clear_cheats(&mut self) {
+        self.cheats.clear();
+        self.cheat_codes.clear();
+        self.apply_cheats();
+    }
+
+    fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    fn set_breakpoints(&mut self, breakpoints: Vec<Breakpoint>) -> Result<(), CoreError> {
+        let core = self.core_mut()?;
+        core.set_breakpoints(&breakpoints)
+            .map_err(|e| CoreError::Core(Box::new(e)))?;
+        self.breakpoints = breakpoints;
+        Ok(())
+    }
+
+    fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    fn debug_step(&mut self, mode: StepMode) -> Result<(), CoreError> {
+        self.core_mut()?.debug_step(mode);
+        Ok(())
+    }
+
+    fn take_debug_break(&mut self) -> Option<BreakReason> {
+        self.core.0.as_mut()?.take_debug_break()
+    }
+
+    fn debug_state(&self) -> Result<DebugState, CoreError> {
+        Ok(self.core_ref()?.debug_state())
+    }
+
+    fn set_register(&mut self, name: &str, value: u32) -> Result<(), CoreError> {
+        self.core_mut()?
+            .set_cpu_register(name, value)
+            .map_err(|e| CoreError::Core(Box::new(e)))
+    }
+
+    fn set_video_capture(&mut self, scanline: Option<u16>) -> Result<(), CoreError> {
+        self.core_mut()?.set_ppu_capture_scanline(scanline);
+        Ok(())
+    }
+
+    fn video_memory_view(&self, pattern_palette: u8) -> Result<VideoMemoryView, CoreError> {
+        Ok(self
+            .core_ref()?
+            .ppu_snapshot()
+            .video_memory_view(pattern_palette))
+    }
+
+    fn peek_memory(
+        &self,
+        space: MemorySpace,
+        start: u32,
+        len: u32,
+    ) -> Result<MemoryBlock, CoreError> {
+        let core = self.core_ref()?;
+        let space_len = core.memory_len(space);
+        let start = start as usize;
+        let end = start.saturating_add(len as usize).min(space_len);
+        Ok(MemoryBlock {
+            space,
+            start: start as u32,
+            bytes: (start..end)
+                .map(|address| core.peek_memory(space, address))
+                .collect(),
+            space_len: space_len as u32,
+        })
+    }
+
+    fn poke_memory(
+        &mut self,
+        space: MemorySpace,
+        start: u32,
+        bytes: &[u8],
+    ) -> Result<(), CoreError> {
+        let core = self.core_mut()?;
+        for (address, &value) in (start as usize..).zip(bytes) {
+            core.poke_memory(space, address, value)
+                .map_err(|e| CoreError::Core(Box::new(e)))?;
+        }
+        Ok(())
+    }
+
     fn mapper_save(&self) -> Result<Option<Vec<u8>>, CoreError> {
         let core = self.core_ref()?;
         core.export_mapper_save().map_err(CoreError::Core)
@@ -156,18 +375,70 @@ impl ConsoleCore for NesConsoleCore {
     }
 
     fn identity(&self) -> Result<SystemIdentity, CoreError> {
-        self.core_ref()?
-            .rom_identity()
+        let core = self.core_ref()?;
+        core.rom_identity()
             .into_system_identity()
+            .map(|identity| identity.with_metadata(core.media_metadata()))
             .map_err(|e| CoreError::Core(Box::new(e)))
     }
+
+    fn frame_rate(&self) -> Option<f64> {
+        self.core
+            .0
+            .as_ref()
+            .map(|core| core.console_type().frame_rate())
+    }
+
+    fn rewind_state_size(&self) -> Option<usize> {
+        self.rewind_state_size
+    }
+
+    fn rewind_save(&self, buf: &mut [u8]) -> Result<(), CoreError> {
+        crate::rewind::save(self.core_ref()?, buf).map_err(CoreError::Core)
+    }
+
+    fn rewind_restore(&mut self, buf: &[u8]) -> Result<(), CoreError> {
+        crate::rewind::restore(self.core_mut()?, buf).map_err(CoreError::Core)
+    }
+}
+
+/// 早送り中の音声を捨てる。APU のリサンプル比を変えないよう sample rate は実機側に合わせる。
+struct DiscardAudio {
+    sample_rate: u32,
+}
+
+impl AudioBackend for DiscardAudio {
+    fn start(&mut self) {}
+    fn pause(&mut self) {}
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+    fn push(&mut self, _sample: f32) {}
+}
+
+/// 状態を書き出せない場合は巻き戻し非対応として扱う。
+fn rewind_state_size(core: &Core) -> Option<usize> {
+    crate::rewind::slot_size(core)
+        .inspect_err(|e| log::warn!("rewind disabled: {e}"))
+        .ok()
+}
+
+fn console_type_from_region(region: Region) -> ConsoleType {
+    match region {
+        Region::Ntsc => ConsoleType::Ntsc,
+        Region::Pal => ConsoleType::Pal,
+        Region::Dendy => ConsoleType::Dendy,
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::{
         collections::HashMap,
-        sync::{Arc, Mutex, atomic::AtomicBool},
+        sync::{
+            Arc, Mutex,
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+        },
     };
 
     use nerust_core_traits::CoreConfig;
@@ -228,7 +499,7 @@ mod tests {
             256,
             240,
             PixelFormat::PaletteIndex {
-                palette: Box::new([0u32; 256]),
+                palette: Box::new([0u32; 512]),
             },
         );
         let result = core.render_frame(&mut fb);
@@ -259,7 +530,7 @@ mod tests {
             256,
             240,
             PixelFormat::PaletteIndex {
-                palette: Box::new([0u32; 256]),
+                palette: Box::new([0u32; 512]),
             },
         );
         let result = core.render_frame(&mut fb);
@@ -269,4 +540,342 @@ mod tests {
             result
         );
     }
+
+    #[test]
+    fn load_selects_region_from_header_and_config_override() {
+        let mut rom = test_rom();
+        // NES 2.0, CPU/PPU timing = PAL
+        rom[7] = 0x08;
+        rom[12] = 0x01;
+        let mut core = NesConsoleCore::new_empty(
+            ControllerCollection::new(vec![Box::new(MockController)]),
+            Box::new(nerust_core_traits::audio::NullAudio),
+            test_emu_input(),
+        );
+        let mut config = CoreConfig {
+            region: None,
+            bios_paths: HashMap::new(),
+            controllers: HashMap::new(),
+            core_options: None,
+        };
+
+        ConsoleCore::load(&mut core, &rom, &config).expect("PAL ROM should load");
+        assert_eq!(core.core_ref().unwrap().console_type(), ConsoleType::Pal);
+        assert_eq!(core.frame_rate(), Some(ConsoleType::Pal.frame_rate()));
+
+        config.region = Some(Region::Dendy);
+        ConsoleCore::load(&mut core, &rom, &config).expect("override should load");
+        assert_eq!(core.core_ref().unwrap().console_type(), ConsoleType::Dendy);
+
+        config.region = None;
+        config.core_options = Some(Box::new(CoreOptions {
+            region: Some(ConsoleType::Ntsc),
+            ..CoreOptions::default()
+        }));
+        ConsoleCore::load(&mut core, &rom, &config).expect("options override should load");
+        assert_eq!(core.core_ref().unwrap().console_type(), ConsoleType::Ntsc);
+    }
+
+    #[test]
+    fn rewind_snapshot_restores_earlier_state() {
+        let rom = test_rom();
+        let mut core = NesConsoleCore::new_empty(
+            ControllerCollection::new(vec![Box::new(MockController)]),
+            Box::new(nerust_core_traits::audio::NullAudio),
+            test_emu_input(),
+        );
+        assert_eq!(core.rewind_state_size(), None);
+        let config = CoreConfig {
+            region: None,
+            bios_paths: HashMap::new(),
+            controllers: HashMap::new(),
+            core_options: None,
+        };
+        ConsoleCore::load(&mut core, &rom, &config).expect("load should succeed");
+
+        let size = core.rewind_state_size().expect("NES core supports rewind");
+        let mut fb = FrameBuffer::with_capacity(
+            256,
+            240,
+            PixelFormat::PaletteIndex {
+                palette: Box::new([0u32; 512]),
+            },
+        );
+        core.render_frame(&mut fb).unwrap();
+        let mut snapshot = vec![0xA5; size];
+        core.rewind_save(&mut snapshot).unwrap();
+        let expected = core.save_state().unwrap();
+
+        core.render_frame(&mut fb).unwrap();
+        core.render_frame(&mut fb).unwrap();
+        assert_ne!(core.save_state().unwrap(), expected);
+
+        core.rewind_restore(&snapshot).unwrap();
+        assert_eq!(core.save_state().unwrap(), expected);
+        // 枠に収まらない状態は書き込まずにエラーにする
+        let mut short = vec![0xA5; 64];
+        assert!(core.rewind_save(&mut short).is_err());
+        assert!(short.iter().all(|&byte| byte == 0xA5));
+
+        core.unload();
+        assert_eq!(core.rewind_state_size(), None);
+        assert!(matches!(
+            core.rewind_save(&mut snapshot),
+            Err(CoreError::NoRomLoaded)
+        ));
+        assert!(matches!(
+            core.rewind_restore(&snapshot),
+            Err(CoreError::NoRomLoaded)
+        ));
+    }
+
+    #[test]
+    fn muted_audio_is_dropped() {
+        struct CountingAudio(Arc<AtomicUsize>);
+        impl AudioBackend for CountingAudio {
+            fn start(&mut self) {}
+            fn pause(&mut self) {}
+            fn push(&mut self, _sample: f32) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let pushed = Arc::new(AtomicUsize::new(0));
+        let cartridge = crate::rom_parse::parse_rom(&test_rom()).unwrap();
+        let mut core = NesConsoleCore::new(
+            cartridge,
+            ControllerCollection::new(vec![Box::new(MockController)]),
+            Box::new(CountingAudio(Arc::clone(&pushed))),
+            test_emu_input(),
+        )
+        .unwrap();
+        let mut fb = FrameBuffer::with_capacity(
+            256,
+            240,
+            PixelFormat::PaletteIndex {
+                palette: Box::new([0u32; 512]),
+            },
+        );
+
+        core.render_frame(&mut fb).unwrap();
+        let unmuted = pushed.load(Ordering::Relaxed);
+        assert!(unmuted > 0);
+
+        core.set_audio_muted(true);
+        core.render_frame(&mut fb).unwrap();
+        assert_eq!(pushed.load(Ordering::Relaxed), unmuted);
+
+        core.set_audio_muted(false);
+        core.render_frame(&mut fb).unwrap();
+        assert!(pushed.load(Ordering::Relaxed) > unmuted);
+    }
+
+    #[test]
+    fn fds_load_reads_bios_from_bios_paths() {
+        let mut disk = b"\x01*NINTENDO-HVC*".to_vec();
+        disk.resize(crate::cartridge_rom::FDS_DISK_SIDE_LEN, 0);
+        // JMP $E000 で止まり、リセットベクタは $E000
+        let mut bios = vec![0; crate::cartridge_rom::FDS_BIOS_LEN];
+        bios[..3].copy_from_slice(&[0x4C, 0x00, 0xE0]);
+        bios[0x1FFC..0x1FFE].copy_from_slice(&[0x00, 0xE0]);
+        let bios_path =
+            std::env::temp_dir().join(format!("nerust-fds-bios-{}.rom", std::process::id()));
+        std::fs::write(&bios_path, &bios).unwrap();
+
+        let mut core = NesConsoleCore::new_empty(
+            ControllerCollection::new(vec![Box::new(MockController)]),
+            Box::new(nerust_core_traits::audio::NullAudio),
+            test_emu_input(),
+        );
+        let mut config = CoreConfig {
+            region: None,
+            bios_paths: HashMap::new(),
+            controllers: HashMap::new(),
+            core_options: None,
+        };
+        assert!(ConsoleCore::load(&mut core, &disk, &config).is_err());
+
+        config
+            .bios_paths
+            .insert(FDS_BIOS_KEY.to_string(), bios_path.clone());
+        let result = ConsoleCore::load(&mut core, &disk, &config);
+        let _ = std::fs::remove_file(&bios_path);
+        result.expect("FDS image should load with BIOS");
+        let mut fb = FrameBuffer::with_capacity(
+            256,
+            240,
+            PixelFormat::PaletteIndex {
+                palette: Box::new([0u32; 512]),
+            },
+        );
+        core.render_frame(&mut fb).unwrap();
+        core.switch_disk_side();
+        core.eject_disk();
+        core.render_frame(&mut fb).unwrap();
+    }
+
+    #[test]
+    fn cheats_patch_rom_reads_and_pin_ram_each_frame() {
+        let mut rom = test_rom();
+        // LDA $8010 / STA $00 / JMP $8000
+        rom[16..24].copy_from_slice(&[0xAD, 0x10, 0x80, 0x85, 0x00, 0x4C, 0x00, 0x80]);
+        rom[16 + 0x10] = 0x11;
+        rom[16 + 0x7FFD] = 0x80;
+        let mut core = NesConsoleCore::new(
+            crate::rom_parse::parse_rom(&rom).unwrap(),
+            ControllerCollection::new(vec![Box::new(MockController)]),
+            Box::new(nerust_core_traits::audio::NullAudio),
+            test_emu_input(),
+        )
+        .unwrap();
+        let mut fb = FrameBuffer::with_capacity(
+            256,
+            240,
+            PixelFormat::PaletteIndex {
+                palette: Box::new([0u32; 512]),
+            },
+        );
+        let ram = |core: &NesConsoleCore, address| {
+            core.core_ref().unwrap().cpu.peek_work_ram(address).unwrap()
+        };
+
+        core.add_cheat(Cheat::new("OPPAAE", "$8010 = $99")).unwrap();
+        core.add_cheat(Cheat::new("0020:42", "")).unwrap();
+        assert!(core.add_cheat(Cheat::new("not a code", "")).is_err());
+        core.render_frame(&mut fb).unwrap();
+        assert_eq!(ram(&core, 0x00), 0x99);
+        assert_eq!(ram(&core, 0x20), 0x42);
+
+        core.set_cheat_enabled(0, false).unwrap();
+        core.render_frame(&mut fb).unwrap();
+        assert_eq!(ram(&core, 0x00), 0x11);
+
+        core.remove_cheat(1).unwrap();
+        assert_eq!(
+            core.cheats(),
+            [Cheat {
+                enabled: false,
+                ..Cheat::new("OPPAAE", "$8010 = $99")
+            }]
+        );
+        assert!(core.remove_cheat(1).is_err());
+    }
+
+    #[test]
+    fn breakpoints_stop_render_frame_and_report_state() {
+        use nerust_core_traits::debug::BreakpointKind;
+
+        let mut rom = test_rom();
+        // INX / JMP $8000
+        rom[16..20].copy_from_slice(&[0xE8, 0x4C, 0x00, 0x80]);
+        rom[16 + 0x7FFD] = 0x80;
+        let mut core = NesConsoleCore::new(
+            crate::rom_parse::parse_rom(&rom).unwrap(),
+            ControllerCollection::new(vec![Box::new(MockController)]),
+            Box::new(nerust_core_traits::audio::NullAudio),
+            test_emu_input(),
+        )
+        .unwrap();
+        let mut fb = FrameBuffer::with_capacity(
+            256,
+            240,
+            PixelFormat::PaletteIndex {
+                palette: Box::new([0u32; 512]),
+            },
+        );
+
+        let breakpoint = Breakpoint::new(BreakpointKind::Execute, 0x8001).with_condition("x == 5");
+        core.set_breakpoints(vec![breakpoint.clone()]).unwrap();
+        assert!(
+            core.set_breakpoints(vec![breakpoint.clone().with_condition("x ==")])
+                .is_err()
+        );
+        assert_eq!(core.breakpoints(), [breakpoint]);
+
+        core.render_frame(&mut fb).unwrap();
+        assert_eq!(
+            core.take_debug_break(),
+            Some(BreakReason::Breakpoint {
+                index: 0,
+                address: 0x8001
+            })
+        );
+        assert_eq!(core.take_debug_break(), None);
+        let state = core.debug_state().unwrap();
+        assert_eq!(state.pc, 0x8001);
+        assert!(
+            state
+                .registers
+                .iter()
+                .any(|register| register.name == "X" && register.value == 5)
+        );
+
+        core.set_register("X", 0x10).unwrap();
+        assert!(core.set_register("X", 0x100).is_err());
+        core.debug_step(StepMode::Instruction).unwrap();
+        core.render_frame(&mut fb).unwrap();
+        assert_eq!(core.take_debug_break(), Some(BreakReason::Step));
+        assert_eq!(core.debug_state().unwrap().pc, 0x8000);
+    }
+
+    #[test]
+    fn nsf_plays_selected_track_and_exposes_metadata() {
+        let mut nsf = b"NESM\x1A\x01\x03\x02".to_vec();
+        nsf.resize(0x80, 0);
+        // load $8000, init $8000, play $8004
+        nsf[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x04, 0x80]);
+        nsf[0x0E..0x17].copy_from_slice(b"Test Tune");
+        nsf[0x6E..0x70].copy_from_slice(&16639u16.to_le_bytes());
+        // INIT: STA $6000 / RTS, PLAY: INC $6001 / RTS
+        nsf.extend_from_slice(&[0x8D, 0x00, 0x60, 0x60, 0xEE, 0x01, 0x60, 0x60]);
+
+        let mut core = NesConsoleCore::new_empty(
+            ControllerCollection::new(vec![Box::new(MockController)]),
+            Box::new(nerust_core_traits::audio::NullAudio),
+            test_emu_input(),
+        );
+        let config = CoreConfig {
+            region: None,
+            bios_paths: HashMap::new(),
+            controllers: HashMap::new(),
+            core_options: None,
+        };
+        ConsoleCore::load(&mut core, &nsf, &config).expect("NSF should load");
+        let metadata = core.identity().unwrap().metadata.expect("NSF has metadata");
+        assert_eq!(metadata.title.as_deref(), Some("Test Tune"));
+        assert_eq!(metadata.tracks.len(), 3);
+        assert_eq!(metadata.first_track, 1);
+
+        let mut fb = FrameBuffer::with_capacity(
+            256,
+            240,
+            PixelFormat::PaletteIndex {
+                palette: Box::new([0u32; 512]),
+            },
+        );
+        let mut run_and_peek = |core: &mut NesConsoleCore| {
+            for _ in 0..5 {
+                core.render_frame(&mut fb).unwrap();
+            }
+            let core = core.core_ref().unwrap();
+            (
+                core.peek_cartridge_ram(0x6000).unwrap().data,
+                core.peek_cartridge_ram(0x6001).unwrap().data,
+            )
+        };
+
+        let (track, plays) = run_and_peek(&mut core);
+        assert_eq!(track, 1);
+        assert!((4..=6).contains(&plays), "PLAY ran {plays} times");
+
+        core.next_track();
+        let (track, plays) = run_and_peek(&mut core);
+        assert_eq!(track, 2);
+        assert!((4..=6).contains(&plays), "PLAY ran {plays} times");
+
+        core.next_track();
+        assert_eq!(run_and_peek(&mut core).0, 0);
+        core.previous_track();
+        assert_eq!(run_and_peek(&mut core).0, 2);
+    }
 }
@@ -1,7 +1,61 @@
-use nerust_input_traits::SimplePort;
+use nerust_input_traits::{ControllerHub, LightSense, OpenBusReadResult, Port, SimplePort};
+use nerust_render_traits::FrameBuffer;
 
 /// NES port constants indexed by CPU address ($4016 → index 0, $4017 → index 1).
 pub const NES_PORTS: [SimplePort; 2] = [
     SimplePort::new(0, "nes.attachment.player1"),
     SimplePort::new(1, "nes.attachment.player2"),
 ];
+
+/// 描画途中のフレームを光線銃へ見せる。
+#[derive(Debug)]
+pub(crate) struct FrameLight<'a> {
+    pub(crate) screen: &'a FrameBuffer,
+    pub(crate) beam: Option<(usize, usize)>,
+}
+
+impl LightSense for FrameLight<'_> {
+    fn beam(&self) -> Option<(usize, usize)> {
+        self.beam
+    }
+
+    fn brightness(&self, x: usize, y: usize) -> Option<f32> {
+        let entry = self.screen.pixel(x, y)?;
+        Some(palette_brightness((entry & 0x3F) as u8))
+    }
+}
+
+/// 2C02 パレット index のおおよその輝度。行 (上位 2bit) ごとに明るくなり、
+/// $xD〜$xF 列は黒 ($2D/$3D のみ灰色)。
+fn palette_brightness(index: u8) -> f32 {
+    const GREY: [f32; 4] = [0.33, 0.62, 1.0, 1.0];
+    const HUE: [f32; 4] = [0.15, 0.38, 0.72, 0.90];
+    const DARK_GREY: [f32; 4] = [0.0, 0.0, 0.30, 0.63];
+    let row = usize::from((index >> 4) & 0x03);
+    match index & 0x0F {
+        0x00 => GREY[row],
+        0x01..=0x0C => HUE[row],
+        0x0D => DARK_GREY[row],
+        _ => 0.0,
+    }
+}
+
+/// `$4016`/`$4017` の読み出しに描画途中のフレームを添えるハブ。
+/// 光線銃が接続されている間だけ CPU に渡す。
+#[derive(Debug)]
+pub(crate) struct LightSensingHub<'a> {
+    pub(crate) hub: &'a mut dyn ControllerHub,
+    pub(crate) light: FrameLight<'a>,
+}
+
+impl ControllerHub for LightSensingHub<'_> {
+    fn read_port(&mut self, port: &dyn Port) -> OpenBusReadResult {
+        self.hub.read_port_with_light(port, &self.light)
+    }
+    fn write_strobe(&mut self, value: u8) {
+        self.hub.write_strobe(value);
+    }
+    fn sync_input(&mut self, state: &[u8]) {
+        self.hub.sync_input(state);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::status::console_type::ConsoleType;
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum Mmc3IrqVariant {
@@ -9,6 +11,9 @@ pub enum Mmc3IrqVariant {
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct CoreOptions {
     pub mmc3_irq_variant: Option<Mmc3IrqVariant>,
+    /// `None` の場合は ROM ヘッダのタイミング指定に従う (未指定なら NTSC)。
+    #[serde(default)]
+    pub region: Option<ConsoleType>,
 }
 
 impl nerust_core_traits::CoreOptions for CoreOptions {}
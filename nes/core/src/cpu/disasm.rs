@@ -0,0 +1,317 @@
+//! トレースログ用の逆アセンブラ。表記は nestest.log (Nintendulator) に合わせる。
+
+use std::fmt::{self, Write as _};
+
+use super::{Core, internal_stat::CpuStatesEnum};
+use crate::cart_device::Cartridge as MapperCartridge;
+
+/// レジスタ欄が始まる列。
+const OPERAND_COLUMN_END: usize = 48;
+/// 非公式命令の `*` を置く列。
+const MNEMONIC_COLUMN: usize = 15;
+
+/// 読めなかった値は `??` で表示する。
+struct Byte(Option<u8>);
+
+impl fmt::Display for Byte {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(value) => write!(f, "{value:02X}"),
+            None => f.write_str("??"),
+        }
+    }
+}
+
+struct Word(Option<u16>);
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(value) => write!(f, "{value:04X}"),
+            None => f.write_str("????"),
+        }
+    }
+}
+
+impl Core {
+    /// 命令境界で、次に実行する命令を `C000  4C F5 C5  JMP $C5F5` の形でレジスタ欄の手前まで書く。
+    /// 実効アドレスの値は副作用なしに読めるものだけを表示し、I/O などは `??` になる。
+    pub(crate) fn write_disassembly(&self, cartridge: &dyn MapperCartridge, out: &mut String) {
+        let start = out.len();
+        let registers = self.registers();
+        let pc = registers.pc;
+        let opcode = self.internal_stat.get_opcode();
+        let mode = self.addressing_tables.get(opcode);
+        let operation = self.opcode_tables.get(opcode);
+        let peek = |address: u16| self.peek_cpu_read(usize::from(address), cartridge);
+        let peek_word = |low: u16, high: u16| Some(u16::from_le_bytes([peek(low)?, peek(high)?]));
+        let peek_zero_page_word =
+            |pointer: u8| peek_word(u16::from(pointer), u16::from(pointer.wrapping_add(1)));
+
+        let low = peek(pc.wrapping_add(1));
+        let high = peek(pc.wrapping_add(2));
+        let _ = write!(out, "{pc:04X}  {opcode:02X}");
+        for byte in [low, high].into_iter().take(operand_len(mode)) {
+            let _ = write!(out, " {}", Byte(byte));
+        }
+        pad_to(out, start + MNEMONIC_COLUMN);
+        out.push(if is_unofficial(operation, opcode) {
+            '*'
+        } else {
+            ' '
+        });
+        out.push_str(mnemonic(operation));
+
+        let x = registers.x;
+        let y = registers.y;
+        let absolute = low
+            .zip(high)
+            .map(|(low, high)| u16::from_le_bytes([low, high]));
+        let value = |address: Option<u16>| Byte(address.and_then(peek));
+        let _ = match mode {
+            CpuStatesEnum::Accumulator => write!(out, " A"),
+            CpuStatesEnum::Immediate => write!(out, " #${}", Byte(low)),
+            CpuStatesEnum::ZeroPage => {
+                let address = low.map(u16::from);
+                write!(out, " ${} = {}", Byte(low), value(address))
+            }
+            CpuStatesEnum::ZeroPageX | CpuStatesEnum::ZeroPageY => {
+                let (name, index) = if mode == CpuStatesEnum::ZeroPageX {
+                    ('X', x)
+                } else {
+                    ('Y', y)
+                };
+                let address = low.map(|low| low.wrapping_add(index));
+                write!(
+                    out,
+                    " ${},{name} @ {} = {}",
+                    Byte(low),
+                    Byte(address),
+                    value(address.map(u16::from))
+                )
+            }
+            CpuStatesEnum::Absolute
+                if matches!(operation, CpuStatesEnum::Jmp | CpuStatesEnum::Jsr) =>
+            {
+                write!(out, " ${}", Word(absolute))
+            }
+            CpuStatesEnum::Absolute => {
+                write!(out, " ${} = {}", Word(absolute), value(absolute))
+            }
+            CpuStatesEnum::AbsoluteX
+            | CpuStatesEnum::AbsoluteXRMW
+            | CpuStatesEnum::AbsoluteY
+            | CpuStatesEnum::AbsoluteYRMW => {
+                let (name, index) =
+                    if matches!(mode, CpuStatesEnum::AbsoluteX | CpuStatesEnum::AbsoluteXRMW) {
+                        ('X', x)
+                    } else {
+                        ('Y', y)
+                    };
+                let address = absolute.map(|base| base.wrapping_add(u16::from(index)));
+                write!(
+                    out,
+                    " ${},{name} @ {} = {}",
+                    Word(absolute),
+                    Word(address),
+                    value(address)
+                )
+            }
+            CpuStatesEnum::AbsoluteIndirect => {
+                // 6502 のバグどおり、上位バイトはページをまたがずに読む
+                let target = absolute.and_then(|pointer| {
+                    peek_word(
+                        pointer,
+                        (pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF),
+                    )
+                });
+                write!(out, " (${}) = {}", Word(absolute), Word(target))
+            }
+            CpuStatesEnum::IndexedIndirect => {
+                let pointer = low.map(|low| low.wrapping_add(x));
+                let address = pointer.and_then(peek_zero_page_word);
+                write!(
+                    out,
+                    " (${},X) @ {} = {} = {}",
+                    Byte(low),
+                    Byte(pointer),
+                    Word(address),
+                    value(address)
+                )
+            }
+            CpuStatesEnum::IndirectIndexed | CpuStatesEnum::IndirectIndexedRMW => {
+                let base = low.and_then(peek_zero_page_word);
+                let address = base.map(|base| base.wrapping_add(u16::from(y)));
+                write!(
+                    out,
+                    " (${}),Y = {} @ {} = {}",
+                    Byte(low),
+                    Word(base),
+                    Word(address),
+                    value(address)
+                )
+            }
+            CpuStatesEnum::Relative => {
+                let target = low.map(|offset| {
+                    pc.wrapping_add(2)
+                        .wrapping_add_signed(i16::from(offset as i8))
+                });
+                write!(out, " ${}", Word(target))
+            }
+            _ => Ok(()),
+        };
+        pad_to(out, start + OPERAND_COLUMN_END);
+    }
+}
+
+fn pad_to(out: &mut String, column: usize) {
+    let padding = column.saturating_sub(out.len()).max(1);
+    out.extend(std::iter::repeat_n(' ', padding));
+}
+
+fn operand_len(mode: CpuStatesEnum) -> usize {
+    match mode {
+        CpuStatesEnum::Immediate
+        | CpuStatesEnum::ZeroPage
+        | CpuStatesEnum::ZeroPageX
+        | CpuStatesEnum::ZeroPageY
+        | CpuStatesEnum::IndexedIndirect
+        | CpuStatesEnum::IndirectIndexed
+        | CpuStatesEnum::IndirectIndexedRMW
+        | CpuStatesEnum::Relative => 1,
+        CpuStatesEnum::Absolute
+        | CpuStatesEnum::AbsoluteIndirect
+        | CpuStatesEnum::AbsoluteX
+        | CpuStatesEnum::AbsoluteXRMW
+        | CpuStatesEnum::AbsoluteY
+        | CpuStatesEnum::AbsoluteYRMW => 2,
+        _ => 0,
+    }
+}
+
+fn is_unofficial(operation: CpuStatesEnum, opcode: usize) -> bool {
+    match operation {
+        CpuStatesEnum::Nop => opcode != 0xEA,
+        CpuStatesEnum::Sbc => opcode == 0xEB,
+        CpuStatesEnum::Lax
+        | CpuStatesEnum::Anc
+        | CpuStatesEnum::Alr
+        | CpuStatesEnum::Arr
+        | CpuStatesEnum::Xaa
+        | CpuStatesEnum::Las
+        | CpuStatesEnum::Axs
+        | CpuStatesEnum::Sax
+        | CpuStatesEnum::Tas
+        | CpuStatesEnum::Ahx
+        | CpuStatesEnum::Shx
+        | CpuStatesEnum::Shy
+        | CpuStatesEnum::Kil
+        | CpuStatesEnum::Isc
+        | CpuStatesEnum::Dcp
+        | CpuStatesEnum::Slo
+        | CpuStatesEnum::Rla
+        | CpuStatesEnum::Sre
+        | CpuStatesEnum::Rra => true,
+        _ => false,
+    }
+}
+
+// 非公式命令の名前は nestest.log に合わせる (ISC は ISB)
+fn mnemonic(operation: CpuStatesEnum) -> &'static str {
+    match operation {
+        CpuStatesEnum::And => "AND",
+        CpuStatesEnum::Eor => "EOR",
+        CpuStatesEnum::Ora => "ORA",
+        CpuStatesEnum::Adc => "ADC",
+        CpuStatesEnum::Sbc => "SBC",
+        CpuStatesEnum::Bit => "BIT",
+        CpuStatesEnum::Lax => "LAX",
+        CpuStatesEnum::Anc => "ANC",
+        CpuStatesEnum::Alr => "ALR",
+        CpuStatesEnum::Arr => "ARR",
+        CpuStatesEnum::Xaa => "XAA",
+        CpuStatesEnum::Las => "LAS",
+        CpuStatesEnum::Axs => "AXS",
+        CpuStatesEnum::Sax => "SAX",
+        CpuStatesEnum::Tas => "TAS",
+        CpuStatesEnum::Ahx => "AHX",
+        CpuStatesEnum::Shx => "SHX",
+        CpuStatesEnum::Shy => "SHY",
+        CpuStatesEnum::Cmp => "CMP",
+        CpuStatesEnum::Cpx => "CPX",
+        CpuStatesEnum::Cpy => "CPY",
+        CpuStatesEnum::Bcc => "BCC",
+        CpuStatesEnum::Bcs => "BCS",
+        CpuStatesEnum::Beq => "BEQ",
+        CpuStatesEnum::Bmi => "BMI",
+        CpuStatesEnum::Bne => "BNE",
+        CpuStatesEnum::Bpl => "BPL",
+        CpuStatesEnum::Bvc => "BVC",
+        CpuStatesEnum::Bvs => "BVS",
+        CpuStatesEnum::Dex => "DEX",
+        CpuStatesEnum::Dey => "DEY",
+        CpuStatesEnum::Dec => "DEC",
+        CpuStatesEnum::Clc => "CLC",
+        CpuStatesEnum::Cld => "CLD",
+        CpuStatesEnum::Cli => "CLI",
+        CpuStatesEnum::Clv => "CLV",
+        CpuStatesEnum::Sec => "SEC",
+        CpuStatesEnum::Sed => "SED",
+        CpuStatesEnum::Sei => "SEI",
+        CpuStatesEnum::Inx => "INX",
+        CpuStatesEnum::Iny => "INY",
+        CpuStatesEnum::Inc => "INC",
+        CpuStatesEnum::Brk => "BRK",
+        CpuStatesEnum::Rti => "RTI",
+        CpuStatesEnum::Rts => "RTS",
+        CpuStatesEnum::Jmp => "JMP",
+        CpuStatesEnum::Jsr => "JSR",
+        CpuStatesEnum::Lda => "LDA",
+        CpuStatesEnum::Ldx => "LDX",
+        CpuStatesEnum::Ldy => "LDY",
+        CpuStatesEnum::Nop => "NOP",
+        CpuStatesEnum::Kil => "KIL",
+        CpuStatesEnum::Isc => "ISB",
+        CpuStatesEnum::Dcp => "DCP",
+        CpuStatesEnum::Slo => "SLO",
+        CpuStatesEnum::Rla => "RLA",
+        CpuStatesEnum::Sre => "SRE",
+        CpuStatesEnum::Rra => "RRA",
+        CpuStatesEnum::AslAcc | CpuStatesEnum::AslMem => "ASL",
+        CpuStatesEnum::LsrAcc | CpuStatesEnum::LsrMem => "LSR",
+        CpuStatesEnum::RolAcc | CpuStatesEnum::RolMem => "ROL",
+        CpuStatesEnum::RorAcc | CpuStatesEnum::RorMem => "ROR",
+        CpuStatesEnum::Pla => "PLA",
+        CpuStatesEnum::Plp => "PLP",
+        CpuStatesEnum::Pha => "PHA",
+        CpuStatesEnum::Php => "PHP",
+        CpuStatesEnum::Sta => "STA",
+        CpuStatesEnum::Stx => "STX",
+        CpuStatesEnum::Sty => "STY",
+        CpuStatesEnum::Tax => "TAX",
+        CpuStatesEnum::Tay => "TAY",
+        CpuStatesEnum::Tsx => "TSX",
+        CpuStatesEnum::Txa => "TXA",
+        CpuStatesEnum::Tya => "TYA",
+        CpuStatesEnum::Txs => "TXS",
+        CpuStatesEnum::FetchOpCode
+        | CpuStatesEnum::Reset
+        | CpuStatesEnum::Irq
+        | CpuStatesEnum::AbsoluteIndirect
+        | CpuStatesEnum::AbsoluteXRMW
+        | CpuStatesEnum::AbsoluteX
+        | CpuStatesEnum::AbsoluteYRMW
+        | CpuStatesEnum::AbsoluteY
+        | CpuStatesEnum::Absolute
+        | CpuStatesEnum::Accumulator
+        | CpuStatesEnum::Immediate
+        | CpuStatesEnum::Implied
+        | CpuStatesEnum::IndexedIndirect
+        | CpuStatesEnum::IndirectIndexedRMW
+        | CpuStatesEnum::IndirectIndexed
+        | CpuStatesEnum::Relative
+        | CpuStatesEnum::ZeroPageX
+        | CpuStatesEnum::ZeroPageY
+        | CpuStatesEnum::ZeroPage => "???",
+    }
+}
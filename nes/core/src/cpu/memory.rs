@@ -69,6 +69,12 @@ impl Memory {
         }
     }
 
+    pub(crate) fn poke_work_ram(&mut self, address: usize, value: u8) {
+        if let 0..=0x1FFF = address {
+            self.wram[address & 0x07FF] = value;
+        }
+    }
+
     #[expect(
         clippy::too_many_arguments,
         reason = "CPU bus reads need access to every attached device"
@@ -107,6 +113,7 @@ impl Memory {
         apu: &mut Apu,
         interrupt: &mut Interrupt,
     ) {
+        cartridge.notify_cpu_write(address, value);
         match address {
             0..=0x1FFF => self.wram[address & 0x07FF] = value,
             0x2000..=0x3FFF => {
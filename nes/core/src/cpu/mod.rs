@@ -1,4 +1,5 @@
 mod addressing_mode;
+mod disasm;
 mod internal_stat;
 mod memory;
 mod oamdma;
@@ -47,6 +48,7 @@ use crate::{
     Apu, Ppu,
     cart_device::Cartridge as MapperCartridge,
     cartridge_bus::{CpuCartridgeBus, CpuCartridgeBus as Cartridge, mapper_cartridge_bus},
+    debugger::CpuRegisters,
     interrupt::{DmcDmaKind, Interrupt, IrqSource},
     persistence_error::PersistenceError,
 };
@@ -309,6 +311,10 @@ impl Core {
         self.memory.peek_work_ram(address)
     }
 
+    pub(crate) fn poke_work_ram(&mut self, address: usize, value: u8) {
+        self.memory.poke_work_ram(address, value);
+    }
+
     fn set_cpu_state(&mut self, state: CpuStatesEnum) {
         self.internal_stat.state = state;
         self.cpu_stepfunc = cpu_stepfunc(state);
@@ -322,6 +328,17 @@ impl Core {
         apu: &mut Apu,
     ) {
         let mut cartridge = mapper_cartridge_bus(cartridge);
+        self.step_bus(ppu, &mut cartridge, hub, apu);
+    }
+
+    /// `step` と同じだが、チートなどで差し替えた CPU バスを通して 1 サイクル進める。
+    pub(crate) fn step_bus(
+        &mut self,
+        ppu: &mut Ppu,
+        cartridge: &mut dyn Cartridge,
+        hub: &mut dyn ControllerHub,
+        apu: &mut Apu,
+    ) {
         self.cycles = self.cycles.wrapping_add(1);
 
         if let Some(offset) = self.interrupt.oam_dma.take() {
@@ -335,13 +352,13 @@ impl Core {
             self.dmc_dma = Some(DmcDmaState::from_kind(kind));
         }
 
-        if self.process_dma_cycle_bus(ppu, &mut cartridge, hub, apu) {
+        if self.process_dma_cycle_bus(ppu, cartridge, hub, apu) {
             return;
         }
 
         let mut machine = self.cpu_stepfunc;
         self.internal_stat.step += 1;
-        while let CpuStepStateEnum::Exit(s) = machine(self, ppu, &mut cartridge, hub, apu) {
+        while let CpuStepStateEnum::Exit(s) = machine(self, ppu, cartridge, hub, apu) {
             self.set_cpu_state(s);
             self.internal_stat.step = 1;
             machine = self.cpu_stepfunc;
@@ -1068,6 +1085,70 @@ impl Core {
         &self.interrupt
     }
 
+    pub(crate) fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// 命令境界では PC がオペコードの次を指しているため、命令の先頭に戻して見せる。
+    pub(crate) fn registers(&self) -> CpuRegisters {
+        let pc = self.register.get_pc();
+        CpuRegisters {
+            pc: if self.is_instruction_boundary() {
+                pc.wrapping_sub(1)
+            } else {
+                pc
+            },
+            sp: self.register.get_sp(),
+            a: self.register.get_a(),
+            x: self.register.get_x(),
+            y: self.register.get_y(),
+            p: self.register.get_p(),
+        }
+    }
+
+    /// 命令境界で PC を変えた場合は、新しい PC のオペコードを読み直す。
+    pub(crate) fn set_registers(
+        &mut self,
+        registers: CpuRegisters,
+        cartridge: &dyn MapperCartridge,
+    ) {
+        let current = self.registers();
+        self.register.set_sp(registers.sp);
+        self.register.set_a(registers.a);
+        self.register.set_x(registers.x);
+        self.register.set_y(registers.y);
+        self.register.set_p(registers.p);
+        if !self.is_instruction_boundary() {
+            self.register.set_pc(registers.pc);
+        } else if registers.pc != current.pc {
+            let address = usize::from(registers.pc);
+            let opcode = self
+                .peek_cpu_read(address, cartridge)
+                .unwrap_or_else(|| cartridge.read(address).data);
+            self.internal_stat.set_opcode(usize::from(opcode));
+            self.register.set_pc(registers.pc.wrapping_add(1));
+        }
+    }
+
+    /// 実行中、または命令境界でこれから実行する命令のオペコード。
+    pub(crate) fn current_opcode(&self) -> u8 {
+        self.internal_stat.get_opcode() as u8
+    }
+
+    pub(crate) fn boundary_opcode(&self) -> Option<u8> {
+        self.is_instruction_boundary()
+            .then(|| self.current_opcode())
+    }
+
+    pub(crate) fn in_interrupt_sequence(&self) -> bool {
+        self.internal_stat.get_state() == CpuStatesEnum::Irq
+    }
+
+    /// 直前の割り込みシーケンスが NMI のベクタを読んだか。
+    pub(crate) fn interrupt_sequence_was_nmi(&self) -> bool {
+        self.internal_stat.get_interrupt()
+    }
+
     pub(crate) fn validate_runtime_state(&self) -> Result<(), PersistenceError> {
         self.internal_stat.validate()
     }
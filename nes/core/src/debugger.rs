@@ -0,0 +1,716 @@
+//! CPU デバッガ: ブレークポイントの条件式とステップ実行の停止判定
+//!
+//! 条件式は次の文法を持つ。空の条件は常に成立する。
+//!
+//! ```text
+//! A == $10 && (X >= 3 || value & $80 != 0)
+//! ```
+//!
+//! - 値: `A` `X` `Y` `SP` `P` `PC`、アクセスした `value` と `address`、`scanline` `dot`
+//! - 数値: `$FF` / `0xFF` (16 進) または 10 進
+//! - 演算子 (優先順位の低い順): `||`、`&&`、`==` `!=` `<` `<=` `>` `>=`、`&`
+
+use std::{mem, str::FromStr};
+
+use nerust_core_traits::debug::{BreakReason, Breakpoint, BreakpointKind, StepMode};
+
+const OPCODE_JSR: u8 = 0x20;
+const OPCODE_RTI: u8 = 0x40;
+const OPCODE_RTS: u8 = 0x60;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DebuggerError {
+    #[error("invalid breakpoint condition `{condition}`: {reason}")]
+    Condition { condition: String, reason: String },
+    #[error("unknown register: {0}")]
+    UnknownRegister(String),
+    #[error("value ${value:X} is out of range for {name}")]
+    OutOfRange { name: &'static str, value: u32 },
+}
+
+/// デバッガに見せる CPU レジスタ。`pc` は次に実行する命令のアドレス。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CpuRegisters {
+    pub(crate) pc: u16,
+    pub(crate) sp: u8,
+    pub(crate) a: u8,
+    pub(crate) x: u8,
+    pub(crate) y: u8,
+    pub(crate) p: u8,
+}
+
+impl CpuRegisters {
+    /// 表示順に並べたステータスフラグの名前とビット。
+    pub(crate) const FLAGS: [(&'static str, u8); 8] = [
+        ("N", 0x80),
+        ("V", 0x40),
+        ("U", 0x20),
+        ("B", 0x10),
+        ("D", 0x08),
+        ("I", 0x04),
+        ("Z", 0x02),
+        ("C", 0x01),
+    ];
+
+    /// 名前でレジスタかフラグを書き換える。名前は大文字小文字を区別しない。
+    pub(crate) fn set(&mut self, name: &str, value: u32) -> Result<(), DebuggerError> {
+        let name = name.to_ascii_uppercase();
+        if let Some(&(flag, bit)) = Self::FLAGS.iter().find(|(flag, _)| *flag == name) {
+            match value {
+                0 => self.p &= !bit,
+                1 => self.p |= bit,
+                _ => return Err(DebuggerError::OutOfRange { name: flag, value }),
+            }
+            return Ok(());
+        }
+        let (register, name): (&mut u8, &'static str) = match name.as_str() {
+            "PC" => {
+                self.pc = u16::try_from(value)
+                    .map_err(|_| DebuggerError::OutOfRange { name: "PC", value })?;
+                return Ok(());
+            }
+            "SP" => (&mut self.sp, "SP"),
+            "A" => (&mut self.a, "A"),
+            "X" => (&mut self.x, "X"),
+            "Y" => (&mut self.y, "Y"),
+            "P" => (&mut self.p, "P"),
+            _ => return Err(DebuggerError::UnknownRegister(name)),
+        };
+        *register = u8::try_from(value).map_err(|_| DebuggerError::OutOfRange { name, value })?;
+        Ok(())
+    }
+}
+
+/// 1 CPU サイクル進めた直後の状態。`Core` が組み立てて `Debugger::after_cycle` に渡す。
+pub(crate) struct DebugCycle {
+    pub(crate) registers: CpuRegisters,
+    pub(crate) scanline: u16,
+    pub(crate) dot: u16,
+    /// 命令境界にいれば、これから実行する命令のオペコード
+    pub(crate) boundary_opcode: Option<u8>,
+    /// このサイクルで割り込みハンドラの先頭に入った (true なら NMI)
+    pub(crate) entered_interrupt: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Var {
+    A,
+    X,
+    Y,
+    Sp,
+    P,
+    Pc,
+    Value,
+    Address,
+    Scanline,
+    Dot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    BitAnd,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Number(u32),
+    Var(Var),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+/// ブレークポイントの条件式。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition(Expr);
+
+struct ConditionContext<'a> {
+    cycle: &'a DebugCycle,
+    address: u16,
+    value: u8,
+}
+
+impl Condition {
+    fn eval(&self, context: &ConditionContext<'_>) -> bool {
+        Self::eval_expr(&self.0, context) != 0
+    }
+
+    fn eval_expr(expr: &Expr, context: &ConditionContext<'_>) -> u32 {
+        match expr {
+            Expr::Number(value) => *value,
+            Expr::Var(var) => {
+                let registers = &context.cycle.registers;
+                match var {
+                    Var::A => u32::from(registers.a),
+                    Var::X => u32::from(registers.x),
+                    Var::Y => u32::from(registers.y),
+                    Var::Sp => u32::from(registers.sp),
+                    Var::P => u32::from(registers.p),
+                    Var::Pc => u32::from(registers.pc),
+                    Var::Value => u32::from(context.value),
+                    Var::Address => u32::from(context.address),
+                    Var::Scanline => u32::from(context.cycle.scanline),
+                    Var::Dot => u32::from(context.cycle.dot),
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let left = Self::eval_expr(left, context);
+                // || と && は短絡評価する
+                match op {
+                    BinaryOp::Or if left != 0 => return 1,
+                    BinaryOp::And if left == 0 => return 0,
+                    _ => {}
+                }
+                let right = Self::eval_expr(right, context);
+                match op {
+                    BinaryOp::Or | BinaryOp::And => u32::from(right != 0),
+                    BinaryOp::Eq => u32::from(left == right),
+                    BinaryOp::Ne => u32::from(left != right),
+                    BinaryOp::Lt => u32::from(left < right),
+                    BinaryOp::Le => u32::from(left <= right),
+                    BinaryOp::Gt => u32::from(left > right),
+                    BinaryOp::Ge => u32::from(left >= right),
+                    BinaryOp::BitAnd => left & right,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Number(u32),
+    Ident(String),
+    Op(&'static str),
+    Open,
+    Close,
+}
+
+// 2 文字の演算子を先に照合する
+const OPERATORS: [&str; 9] = ["||", "&&", "==", "!=", "<=", ">=", "<", ">", "&"];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (token, len) = if c == '(' {
+            (Token::Open, 1)
+        } else if c == ')' {
+            (Token::Close, 1)
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            (Token::Op(op), op.len())
+        } else if c == '$' || c.is_ascii_digit() {
+            let (digits, radix, prefix) = if let Some(hex) = rest.strip_prefix('$') {
+                (hex, 16, 1)
+            } else if let Some(hex) = rest.strip_prefix("0x").or(rest.strip_prefix("0X")) {
+                (hex, 16, 2)
+            } else {
+                (rest, 10, 0)
+            };
+            let len = digits
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(digits.len());
+            let value = u32::from_str_radix(&digits[..len], radix)
+                .map_err(|_| format!("invalid number `{}`", &rest[..prefix + len]))?;
+            (Token::Number(value), prefix + len)
+        } else if c.is_ascii_alphabetic() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            (Token::Ident(rest[..len].to_ascii_lowercase()), len)
+        } else {
+            return Err(format!("unexpected `{c}`"));
+        };
+        tokens.push(token);
+        rest = rest[len..].trim_start();
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek_op(&self, ops: &[&str]) -> Option<&'static str> {
+        match self.tokens.get(self.position) {
+            Some(Token::Op(op)) if ops.contains(op) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn binary(
+        &mut self,
+        ops: &[&str],
+        next: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        let mut left = next(self)?;
+        while let Some(op) = self.peek_op(ops) {
+            self.position += 1;
+            let right = next(self)?;
+            let op = match op {
+                "||" => BinaryOp::Or,
+                "&&" => BinaryOp::And,
+                "==" => BinaryOp::Eq,
+                "!=" => BinaryOp::Ne,
+                "<" => BinaryOp::Lt,
+                "<=" => BinaryOp::Le,
+                ">" => BinaryOp::Gt,
+                ">=" => BinaryOp::Ge,
+                _ => BinaryOp::BitAnd,
+            };
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.binary(&["||"], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.binary(&["&&"], Self::compare)
+    }
+
+    fn compare(&mut self) -> Result<Expr, String> {
+        self.binary(&["==", "!=", "<", "<=", ">", ">="], Self::bits)
+    }
+
+    fn bits(&mut self) -> Result<Expr, String> {
+        self.binary(&["&"], Self::atom)
+    }
+
+    fn atom(&mut self) -> Result<Expr, String> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| "unexpected end of condition".to_string())?;
+        self.position += 1;
+        match token {
+            Token::Number(value) => Ok(Expr::Number(value)),
+            Token::Ident(name) => {
+                let var = match name.as_str() {
+                    "a" => Var::A,
+                    "x" => Var::X,
+                    "y" => Var::Y,
+                    "sp" => Var::Sp,
+                    "p" => Var::P,
+                    "pc" => Var::Pc,
+                    "value" => Var::Value,
+                    "address" => Var::Address,
+                    "scanline" => Var::Scanline,
+                    "dot" => Var::Dot,
+                    _ => return Err(format!("unknown name `{name}`")),
+                };
+                Ok(Expr::Var(var))
+            }
+            Token::Open => {
+                let expr = self.or()?;
+                match self.tokens.get(self.position) {
+                    Some(Token::Close) => {
+                        self.position += 1;
+                        Ok(expr)
+                    }
+                    _ => Err("missing `)`".to_string()),
+                }
+            }
+            Token::Op(op) => Err(format!("unexpected `{op}`")),
+            Token::Close => Err("unexpected `)`".to_string()),
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = DebuggerError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let error = |reason| DebuggerError::Condition {
+            condition: source.to_string(),
+            reason,
+        };
+        let mut parser = Parser {
+            tokens: tokenize(source).map_err(error)?,
+            position: 0,
+        };
+        let expr = parser.or().map_err(error)?;
+        if parser.position != parser.tokens.len() {
+            return Err(error("trailing input".to_string()));
+        }
+        Ok(Self(expr))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ArmedBreakpoint {
+    index: usize,
+    kind: BreakpointKind,
+    start: u32,
+    end: u32,
+    condition: Option<Condition>,
+}
+
+impl ArmedBreakpoint {
+    fn matches(&self, kind: BreakpointKind, address: u16) -> bool {
+        self.kind == kind && (self.start..=self.end).contains(&u32::from(address))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum StepState {
+    Instruction,
+    Over { return_pc: u16, sp: u8 },
+    Out { sp: u8 },
+    Scanline { line: u16, last: u16, reached: bool },
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Access {
+    kind: BreakpointKind,
+    address: u16,
+    value: u8,
+}
+
+/// ブレークポイントとステップ実行の状態。ホスト側の設定なのでステートには含めない。
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Debugger {
+    breakpoints: Vec<ArmedBreakpoint>,
+    step: Option<StepState>,
+    // このサイクルに起きた読み書きのうち、範囲が一致したもの
+    accesses: Vec<Access>,
+    // 読み書きのブレークはアクセスした命令が終わってから止める
+    pending: Option<BreakReason>,
+    last_opcode: u8,
+    break_reason: Option<BreakReason>,
+}
+
+impl Debugger {
+    /// 有効なブレークポイントを差し替える。`index` は渡したリスト上の位置。
+    pub(crate) fn set_breakpoints(
+        &mut self,
+        breakpoints: &[Breakpoint],
+    ) -> Result<(), DebuggerError> {
+        self.breakpoints = breakpoints
+            .iter()
+            .enumerate()
+            .filter(|(_, breakpoint)| breakpoint.enabled)
+            .map(|(index, breakpoint)| {
+                let condition = breakpoint.condition.trim();
+                Ok(ArmedBreakpoint {
+                    index,
+                    kind: breakpoint.kind,
+                    start: breakpoint.start,
+                    end: breakpoint.end,
+                    condition: (!condition.is_empty())
+                        .then(|| condition.parse())
+                        .transpose()?,
+                })
+            })
+            .collect::<Result<_, DebuggerError>>()?;
+        self.pending = None;
+        Ok(())
+    }
+
+    /// サイクルごとの判定が必要か。必要な間は命令単位の高速パスを使わない。
+    pub(crate) fn is_armed(&self) -> bool {
+        !self.breakpoints.is_empty() || self.step.is_some()
+    }
+
+    /// CPU バスの読み書きを監視する必要があるか。
+    pub(crate) fn watches_memory(&self) -> bool {
+        self.breakpoints.iter().any(|breakpoint| {
+            matches!(
+                breakpoint.kind,
+                BreakpointKind::Read | BreakpointKind::Write
+            )
+        })
+    }
+
+    pub(crate) fn record_access(&mut self, kind: BreakpointKind, address: usize, value: u8) {
+        let address = address as u16;
+        if self
+            .breakpoints
+            .iter()
+            .any(|breakpoint| breakpoint.matches(kind, address))
+        {
+            self.accesses.push(Access {
+                kind,
+                address,
+                value,
+            });
+        }
+    }
+
+    /// ステップ実行を始める。`current_opcode` は実行中か、これから実行する命令。
+    pub(crate) fn step(&mut self, mode: StepMode, cycle: &DebugCycle, current_opcode: u8) {
+        let registers = cycle.registers;
+        self.last_opcode = current_opcode;
+        self.step = Some(match mode {
+            StepMode::Instruction => StepState::Instruction,
+            // JSR の直前で止まっている時だけ、戻り先まで進める
+            StepMode::Over if cycle.boundary_opcode == Some(OPCODE_JSR) => StepState::Over {
+                return_pc: registers.pc.wrapping_add(3),
+                sp: registers.sp,
+            },
+            StepMode::Over => StepState::Instruction,
+            StepMode::Out => StepState::Out { sp: registers.sp },
+            StepMode::Scanline(line) => StepState::Scanline {
+                line,
+                last: cycle.scanline,
+                reached: false,
+            },
+        });
+        self.break_reason = None;
+    }
+
+    pub(crate) fn take_break(&mut self) -> Option<BreakReason> {
+        self.break_reason.take()
+    }
+
+    /// 1 サイクル進めた後に呼ぶ。止めるなら `true` を返し、理由を `take_break` で取り出せる。
+    pub(crate) fn after_cycle(&mut self, cycle: &DebugCycle) -> bool {
+        for access in mem::take(&mut self.accesses) {
+            if self.pending.is_none() {
+                self.pending = self
+                    .first_match(access.kind, access.address, access.value, cycle)
+                    .map(|index| BreakReason::Breakpoint {
+                        index,
+                        address: u32::from(access.address),
+                    });
+            }
+        }
+        if let Some(StepState::Scanline {
+            line,
+            last,
+            reached,
+        }) = &mut self.step
+        {
+            *reached |= cycle.scanline == *line && *last != *line;
+            *last = cycle.scanline;
+        }
+
+        let Some(opcode) = cycle.boundary_opcode else {
+            return false;
+        };
+        let previous_opcode = mem::replace(&mut self.last_opcode, opcode);
+        let pc = cycle.registers.pc;
+        let reason = self
+            .pending
+            .take()
+            .or_else(|| {
+                let kind = match cycle.entered_interrupt? {
+                    true => BreakpointKind::Nmi,
+                    false => BreakpointKind::Irq,
+                };
+                self.first_match(kind, pc, 0, cycle)
+                    .map(|index| BreakReason::Breakpoint {
+                        index,
+                        address: u32::from(pc),
+                    })
+            })
+            .or_else(|| {
+                self.first_match(BreakpointKind::Execute, pc, opcode, cycle)
+                    .map(|index| BreakReason::Breakpoint {
+                        index,
+                        address: u32::from(pc),
+                    })
+            })
+            .or_else(|| {
+                let done = match self.step? {
+                    StepState::Instruction => true,
+                    StepState::Over { return_pc, sp } => {
+                        pc == return_pc && cycle.registers.sp == sp
+                    }
+                    StepState::Out { sp } => {
+                        matches!(previous_opcode, OPCODE_RTS | OPCODE_RTI)
+                            && cycle.registers.sp > sp
+                    }
+                    StepState::Scanline { reached, .. } => reached,
+                };
+                done.then_some(BreakReason::Step)
+            });
+        match reason {
+            Some(reason) => {
+                self.step = None;
+                self.break_reason = Some(reason);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn first_match(
+        &self,
+        kind: BreakpointKind,
+        address: u16,
+        value: u8,
+        cycle: &DebugCycle,
+    ) -> Option<usize> {
+        let context = ConditionContext {
+            cycle,
+            address,
+            value,
+        };
+        self.breakpoints
+            .iter()
+            .filter(|breakpoint| match kind {
+                // 割り込みのブレークはアドレス範囲を見ない
+                BreakpointKind::Irq | BreakpointKind::Nmi => breakpoint.kind == kind,
+                _ => breakpoint.matches(kind, address),
+            })
+            .find(|breakpoint| {
+                breakpoint
+                    .condition
+                    .as_ref()
+                    .is_none_or(|condition| condition.eval(&context))
+            })
+            .map(|breakpoint| breakpoint.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(pc: u16, opcode: Option<u8>) -> DebugCycle {
+        DebugCycle {
+            registers: CpuRegisters {
+                pc,
+                sp: 0xFD,
+                a: 0x10,
+                x: 0x03,
+                y: 0x00,
+                p: 0x24,
+            },
+            scanline: 0,
+            dot: 0,
+            boundary_opcode: opcode,
+            entered_interrupt: None,
+        }
+    }
+
+    #[test]
+    fn conditions_follow_precedence_and_reject_bad_input() {
+        let cycle = cycle(0x8000, None);
+        let context = |value| ConditionContext {
+            cycle: &cycle,
+            address: 0x0300,
+            value,
+        };
+        let eval = |source: &str, value| source.parse::<Condition>().unwrap().eval(&context(value));
+        assert!(eval("A == $10 && (X >= 3 || value != 0)", 0));
+        assert!(eval("value & $80 != 0 || address == 0x301", 0x80));
+        assert!(!eval("value & $80 != 0 || address == 0x301", 0x7F));
+        assert!(eval("pc > 32767 && p & 4", 0));
+
+        for bad in ["A ==", "(A == 1", "B == 1", "A == $G", "A # 1", "A == 1 2"] {
+            assert!(
+                matches!(
+                    bad.parse::<Condition>(),
+                    Err(DebuggerError::Condition { .. })
+                ),
+                "{bad}"
+            );
+        }
+    }
+
+    #[test]
+    fn disabled_breakpoints_keep_their_list_index() {
+        let mut debugger = Debugger::default();
+        let mut disabled = Breakpoint::new(BreakpointKind::Execute, 0x8000);
+        disabled.enabled = false;
+        debugger
+            .set_breakpoints(&[
+                disabled,
+                Breakpoint::new(BreakpointKind::Execute, 0x8000).with_condition("x == 4"),
+                Breakpoint::range(BreakpointKind::Execute, 0x8000, 0x80FF),
+            ])
+            .unwrap();
+
+        assert!(debugger.after_cycle(&cycle(0x8010, Some(0xEA))));
+        assert_eq!(
+            debugger.take_break(),
+            Some(BreakReason::Breakpoint {
+                index: 2,
+                address: 0x8010
+            })
+        );
+        assert!(!debugger.after_cycle(&cycle(0x8100, Some(0xEA))));
+        assert!(
+            debugger
+                .set_breakpoints(&[
+                    Breakpoint::new(BreakpointKind::Read, 0).with_condition("value ==")
+                ])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn access_breaks_wait_for_the_instruction_boundary() {
+        let mut debugger = Debugger::default();
+        debugger
+            .set_breakpoints(&[
+                Breakpoint::new(BreakpointKind::Write, 0x0300).with_condition("value == $42")
+            ])
+            .unwrap();
+        assert!(debugger.watches_memory());
+
+        debugger.record_access(BreakpointKind::Write, 0x0300, 0x41);
+        debugger.record_access(BreakpointKind::Read, 0x0300, 0x42);
+        assert!(!debugger.after_cycle(&cycle(0x8000, None)));
+        debugger.record_access(BreakpointKind::Write, 0x0300, 0x42);
+        assert!(!debugger.after_cycle(&cycle(0x8000, None)));
+        assert!(debugger.after_cycle(&cycle(0x8003, Some(0xEA))));
+        assert_eq!(
+            debugger.take_break(),
+            Some(BreakReason::Breakpoint {
+                index: 0,
+                address: 0x0300
+            })
+        );
+    }
+
+    #[test]
+    fn step_over_runs_to_the_return_address_and_step_out_waits_for_rts() {
+        let mut debugger = Debugger::default();
+        let at_jsr = cycle(0x8000, Some(OPCODE_JSR));
+        debugger.step(StepMode::Over, &at_jsr, OPCODE_JSR);
+        let mut inside = cycle(0x9000, Some(OPCODE_RTS));
+        inside.registers.sp = 0xFB;
+        assert!(!debugger.after_cycle(&inside));
+        assert!(debugger.after_cycle(&cycle(0x8003, Some(0xEA))));
+        assert_eq!(debugger.take_break(), Some(BreakReason::Step));
+        assert!(!debugger.is_armed());
+
+        debugger.step(StepMode::Out, &inside, OPCODE_RTS);
+        assert!(debugger.after_cycle(&cycle(0x8003, Some(0xEA))));
+        assert_eq!(debugger.take_break(), Some(BreakReason::Step));
+    }
+
+    #[test]
+    fn registers_and_flags_are_set_by_name() {
+        let mut registers = cycle(0x8000, None).registers;
+        registers.set("pc", 0xC000).unwrap();
+        registers.set("X", 0x7F).unwrap();
+        registers.set("c", 1).unwrap();
+        registers.set("I", 0).unwrap();
+        assert_eq!(registers.pc, 0xC000);
+        assert_eq!(registers.x, 0x7F);
+        assert_eq!(registers.p, 0x21);
+        assert!(matches!(
+            registers.set("A", 0x100),
+            Err(DebuggerError::OutOfRange { name: "A", .. })
+        ));
+        assert!(matches!(
+            registers.set("Q", 0),
+            Err(DebuggerError::UnknownRegister(_))
+        ));
+    }
+}
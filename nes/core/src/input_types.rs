@@ -1,13 +1,47 @@
 use nerust_input_traits::{BufferError, InputStateBuffer, InputValue};
 
-/// NES 入力バッファ。P1(1byte) + P2(1byte) + mic(1byte) の 3 bytes。
+/// NES 入力バッファ。P1(1byte) + P2(1byte) + mic(1byte) + Zapper 1(3bytes) + P3(1byte) + P4(1byte)
+/// + Zapper 2(3bytes) の 11 bytes。
 ///
 /// Field layout:
 ///   0-7:   P1 (A, B, Select, Start, Up, Down, Left, Right)
 ///   8-15:  P2 (A, B, Select, Start, Up, Down, Left, Right)
 ///   16:    Microphone
+///   17:    $4016 Zapper trigger
+///   18:    $4016 Zapper aim (`InputValue::Position`, 0.0〜1.0 に正規化した画面座標)
+///   19-26: P3 (4 人用アダプタ, byte `[6]`)
+///   27-34: P4 (4 人用アダプタ, byte `[7]`)
+///   35:    $4017 Zapper trigger
+///   36:    $4017 Zapper aim
+///
+/// Byte layout of each Zapper part (`[3..6]` for $4016, `[8..11]` for $4017):
+/// flags (bit0 = trigger, bit1 = aim on screen), aim x (0-255), aim y (0-239).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct NesInputBuffer(pub [u8; 3]);
+pub struct NesInputBuffer(pub [u8; 11]);
+
+pub const ZAPPER_TRIGGER: u8 = 0x01;
+pub const ZAPPER_ON_SCREEN: u8 = 0x02;
+
+/// Zapper の (trigger field, aim field, state offset)。
+const ZAPPERS: [(usize, usize, usize); 2] = [(17, 18, 3), (35, 36, 8)];
+
+impl NesInputBuffer {
+    /// trigger field に対応する Zapper state の位置。
+    fn zapper_trigger_offset(field: usize) -> Option<usize> {
+        ZAPPERS
+            .iter()
+            .find(|&&(trigger, _, _)| trigger == field)
+            .map(|&(_, _, offset)| offset)
+    }
+
+    /// aim field に対応する Zapper state の位置。
+    fn zapper_aim_offset(field: usize) -> Option<usize> {
+        ZAPPERS
+            .iter()
+            .find(|&&(_, aim, _)| aim == field)
+            .map(|&(_, _, offset)| offset)
+    }
+}
 
 impl InputStateBuffer for NesInputBuffer {
     fn set(&mut self, field: usize, value: InputValue) -> Result<(), BufferError> {
@@ -35,8 +69,50 @@ impl InputStateBuffer for NesInputBuffer {
                     self.0[2] = if pressed { 1 } else { 0 };
                     Ok(())
                 }
-                _ => Err(BufferError::FieldNotFound { field }),
+                19..=34 => {
+                    let byte = 6 + (field - 19) / 8;
+                    let mask = 1 << ((field - 19) % 8);
+                    if pressed {
+                        self.0[byte] |= mask;
+                    } else {
+                        self.0[byte] &= !mask;
+                    }
+                    Ok(())
+                }
+                _ => {
+                    if let Some(offset) = Self::zapper_trigger_offset(field) {
+                        if pressed {
+                            self.0[offset] |= ZAPPER_TRIGGER;
+                        } else {
+                            self.0[offset] &= !ZAPPER_TRIGGER;
+                        }
+                        Ok(())
+                    } else if Self::zapper_aim_offset(field).is_some() {
+                        Err(BufferError::UnsupportedFieldType {
+                            field,
+                            expected: "position",
+                        })
+                    } else {
+                        Err(BufferError::FieldNotFound { field })
+                    }
+                }
             },
+            InputValue::Position { x, y } => {
+                let Some(offset) = Self::zapper_aim_offset(field) else {
+                    return Err(BufferError::UnsupportedFieldType {
+                        field,
+                        expected: "digital",
+                    });
+                };
+                if (0.0..1.0).contains(&x) && (0.0..1.0).contains(&y) {
+                    self.0[offset] |= ZAPPER_ON_SCREEN;
+                    self.0[offset + 1] = (x * 256.0) as u8;
+                    self.0[offset + 2] = (y * 240.0) as u8;
+                } else {
+                    self.0[offset] &= !ZAPPER_ON_SCREEN;
+                }
+                Ok(())
+            }
             _ => Err(BufferError::UnsupportedFieldType {
                 field,
                 expected: "digital",
@@ -45,7 +121,13 @@ impl InputStateBuffer for NesInputBuffer {
     }
 
     fn clear(&mut self) {
-        self.0 = [0; 3];
+        // 照準位置は押下状態ではないため保持する
+        let previous = self.0;
+        self.0 = [0; 11];
+        for (_, _, offset) in ZAPPERS {
+            self.0[offset] = previous[offset] & ZAPPER_ON_SCREEN;
+            self.0[offset + 1..offset + 3].copy_from_slice(&previous[offset + 1..offset + 3]);
+        }
     }
 
     fn copy_state(&mut self, other: &dyn nerust_input_traits::InputStateBuffer) {
@@ -54,3 +136,30 @@ impl InputStateBuffer for NesInputBuffer {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nerust_input_traits::{InputStateBuffer, InputValue};
+
+    use super::NesInputBuffer;
+
+    #[test]
+    fn zappers_on_both_ports_keep_separate_state() {
+        let mut buffer = NesInputBuffer::default();
+        buffer.set(17, InputValue::Digital(true)).unwrap();
+        buffer
+            .set(18, InputValue::Position { x: 0.5, y: 0.5 })
+            .unwrap();
+        buffer
+            .set(36, InputValue::Position { x: 0.25, y: 0.0 })
+            .unwrap();
+
+        assert_eq!(buffer.0[3..6], [0x03, 128, 120]);
+        assert_eq!(buffer.0[8..11], [0x02, 64, 0]);
+
+        // 押下状態だけ消え、照準は残る
+        buffer.clear();
+        assert_eq!(buffer.0[3..6], [0x02, 128, 120]);
+        assert_eq!(buffer.0[8..11], [0x02, 64, 0]);
+    }
+}
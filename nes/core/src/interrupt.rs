@@ -11,6 +11,7 @@ bitflags::bitflags! {
         const FRAME_COUNTER = 0b0000_0010;
         const DMC = 0b0000_0100;
         const FDS_DISK = 0b0000_1000;
+        const NSF_PLAY = 0b0001_0000;
         const ALL = 0xFF;
     }
 }
@@ -6,25 +6,37 @@ pub mod cartridge_data_parts;
 pub mod cartridge_error;
 pub mod cartridge_rom;
 mod cartridge_runtime_state;
+pub mod cheat;
 pub mod console_core;
 pub mod controller;
 pub mod core_options;
 mod cpu;
+pub mod debugger;
 pub mod input_types;
 mod interrupt;
 mod mapper;
 mod mapper_state;
+pub mod memory_viewer;
 pub(crate) mod mirror;
+pub mod nsf_info;
 mod persistence_codec;
 mod persistence_error;
 mod ppu;
 mod ppu_memory_access;
+pub mod ppu_viewer;
+mod rewind;
 pub(crate) mod rom_format;
 pub mod rom_identity;
 pub mod rom_parse;
+pub mod status;
+pub mod trace;
 
 use crc::{CRC_64_XZ, Crc, Digest};
-use nerust_core_traits::audio::AudioBackend;
+use nerust_core_traits::{
+    audio::AudioBackend,
+    debug::{BreakReason, Breakpoint, DebugState, FlagValue, MemorySpace, RegisterValue, StepMode},
+    identity::MediaMetadata,
+};
 use nerust_input_traits::{ControllerHub, OpenBusReadResult};
 use nerust_render_traits::FrameBuffer;
 use nerust_sound_filter::{
@@ -37,23 +49,33 @@ use self::cartridge_data_parts::CartridgeDataParts;
 use self::{
     apu::Core as Apu,
     cart_device::Cartridge,
+    cartridge_bus::CpuCartridgeBus,
     cartridge_rom::CartridgeData,
     cartridge_runtime_state::CartridgeRuntimeState,
+    cheat::{CheatCode, CheatEngine},
     cpu::Core as Cpu,
+    debugger::{CpuRegisters, DebugCycle, Debugger, DebuggerError},
+    memory_viewer::MemoryAccessError,
     persistence_codec::{
         PERSISTENCE_SCHEMA_VERSION, decode_payload, encode_payload, validate_schema_version,
     },
     persistence_error::PersistenceError,
     ppu::Core as Ppu,
+    ppu_viewer::{PpuCapture, PpuSnapshot},
+    trace::TraceSink,
 };
 #[cfg(test)]
 use crate::core_options::Mmc3IrqVariant;
 use crate::{
-    core_options::CoreOptions, mirror::MirrorMode, rom_format::RomFormat, rom_identity::RomIdentity,
+    controller::{FrameLight, LightSensingHub},
+    core_options::CoreOptions,
+    mirror::MirrorMode,
+    nsf_info::NsfInfo,
+    rom_format::RomFormat,
+    rom_identity::RomIdentity,
+    status::console_type::ConsoleType,
 };
 
-pub(crate) const CLOCK_RATE: u64 = 1_789_773;
-
 const CRC64_LEGACY_ECMA: Crc<u64> = Crc::<u64>::new(&CRC_64_XZ);
 
 struct Crc64Hasher(Digest<'static, u64>);
@@ -97,8 +119,25 @@ pub struct Core {
     apu: Apu,
     cartridge: Box<dyn Cartridge>,
     options: CoreOptions,
+    #[serde(default)]
+    console_type: ConsoleType,
+    // PAL では CPU 5 サイクルで PPU が 16 ドット進むため、端数の位相を保持する
+    #[serde(default)]
+    ppu_clock_phase: u8,
     #[serde(skip)]
     apu_state: Option<Box<ApuState>>,
+    // 光線銃が接続されているフレームだけ、コントローラ読み出しに描画中の画面を渡す
+    #[serde(skip)]
+    light_sensing: bool,
+    // チートはホスト側の設定なのでステートには含めない
+    #[serde(skip)]
+    cheats: CheatEngine,
+    #[serde(skip)]
+    debugger: Debugger,
+    #[serde(skip)]
+    trace: Option<TraceSink>,
+    #[serde(skip)]
+    ppu_capture: Option<PpuCapture>,
 }
 
 // NES 固有のフィルタ構成: LPF 14kHz + HPF 90Hz + HPF 442Hz (3段 IIR)
@@ -201,6 +240,8 @@ struct MachineStatePayload {
     ppu: Ppu,
     apu: Apu,
     cartridge: CartridgeRuntimeState,
+    #[serde(default)]
+    ppu_clock_phase: u8,
 }
 
 impl Core {
@@ -216,16 +257,28 @@ impl Core {
         options: CoreOptions,
     ) -> Result<Core, Error> {
         cartridge_data.validate()?;
+        let console_type = options
+            .region
+            .or(cartridge_data.console_type())
+            .unwrap_or_default();
         let mut cpu = Cpu::new();
-        let cartridge = cartridge::try_from_with_options(cartridge_data, options)?;
-        let apu = Apu::new(cpu.interrupt_mut());
+        let mut cartridge = cartridge::try_from_with_options(cartridge_data, options)?;
+        cartridge.set_console_type(console_type);
+        let apu = Apu::new_with_console_type(console_type, cpu.interrupt_mut());
         Ok(Self {
             cpu,
-            ppu: Ppu::new(),
+            ppu: Ppu::with_console_type(console_type),
             apu,
             cartridge,
             options,
+            console_type,
+            ppu_clock_phase: 0,
             apu_state: None,
+            light_sensing: false,
+            cheats: CheatEngine::default(),
+            debugger: Debugger::default(),
+            trace: None,
+            ppu_capture: None,
         })
     }
 
@@ -233,7 +286,126 @@ impl Core {
         self.cpu.reset();
         self.ppu.reset();
         self.apu.reset(self.cpu.interrupt_mut());
+        self.ppu_clock_phase = 0;
         self.apu_state = None;
+        self.cartridge.soft_reset();
+    }
+
+    /// 有効なチートを差し替える。Game Genie のコードがある間は命令単位の高速パスを使わない。
+    pub fn set_cheats(&mut self, codes: &[CheatCode]) {
+        self.cheats = CheatEngine::new(codes);
+    }
+
+    /// ブレークポイントを差し替える。条件式が不正なら何も変えずにエラーを返す。
+    /// 有効なブレークポイントやステップ実行がある間は命令単位の高速パスを使わない。
+    pub fn set_breakpoints(&mut self, breakpoints: &[Breakpoint]) -> Result<(), DebuggerError> {
+        let mut debugger = self.debugger.clone();
+        debugger.set_breakpoints(breakpoints)?;
+        self.debugger = debugger;
+        Ok(())
+    }
+
+    /// CPU トレースの出力先を差し替え、それまでの出力先を返す。
+    /// トレース中は命令単位の高速パスを使わない。
+    pub fn set_trace_sink(&mut self, sink: Option<TraceSink>) -> Option<TraceSink> {
+        std::mem::replace(&mut self.trace, sink)
+    }
+
+    /// `scanline` に入るたびに PPU のスナップショットを取る。`None` で止める。
+    /// 取っている間は命令単位の高速パスを使わない。
+    pub fn set_ppu_capture_scanline(&mut self, scanline: Option<u16>) {
+        if self.ppu_capture.as_ref().map(PpuCapture::scanline) != scanline {
+            self.ppu_capture = scanline.map(PpuCapture::new);
+        }
+    }
+
+    /// 最後に取ったスナップショット。まだ取れていなければ今の PPU を写す。
+    pub fn ppu_snapshot(&self) -> PpuSnapshot {
+        self.ppu_capture
+            .as_ref()
+            .and_then(PpuCapture::snapshot)
+            .cloned()
+            .unwrap_or_else(|| self.ppu.snapshot(self.cartridge.as_ref()))
+    }
+
+    /// 次の `run_frame` からステップ実行する。終わるとフレームの途中でも `run_frame` が戻る。
+    pub fn debug_step(&mut self, mode: StepMode) {
+        let cycle = self.debug_cycle(false);
+        self.debugger.step(mode, &cycle, self.cpu.current_opcode());
+    }
+
+    /// 直前の `run_frame` がブレークで止まった理由を取り出す。
+    pub fn take_debug_break(&mut self) -> Option<BreakReason> {
+        self.debugger.take_break()
+    }
+
+    pub fn debug_state(&self) -> DebugState {
+        let registers = self.cpu.registers();
+        let (scanline, dot) = self.ppu.scanline_and_dot();
+        let register = |name, value: u16, bits| RegisterValue {
+            name,
+            value: u32::from(value),
+            bits,
+        };
+        DebugState {
+            pc: u32::from(registers.pc),
+            registers: vec![
+                register("PC", registers.pc, 16),
+                register("A", registers.a.into(), 8),
+                register("X", registers.x.into(), 8),
+                register("Y", registers.y.into(), 8),
+                register("SP", registers.sp.into(), 8),
+                register("P", registers.p.into(), 8),
+            ],
+            flags: CpuRegisters::FLAGS
+                .iter()
+                .map(|&(name, bit)| FlagValue {
+                    name,
+                    set: registers.p & bit != 0,
+                })
+                .collect(),
+            scanline,
+            dot,
+            frame: self.ppu.frame_count(),
+            cycle: self.cpu.cycles(),
+        }
+    }
+
+    /// `debug_state` が返す名前でレジスタかフラグ (0 か 1) を書き換える。
+    pub fn set_cpu_register(&mut self, name: &str, value: u32) -> Result<(), DebuggerError> {
+        let mut registers = self.cpu.registers();
+        registers.set(name, value)?;
+        self.cpu.set_registers(registers, self.cartridge.as_ref());
+        Ok(())
+    }
+
+    /// Famicom Disk System のディスクを取り出す。それ以外のカートリッジでは何もしない。
+    pub fn eject_disk(&mut self) {
+        self.cartridge.eject_disk();
+    }
+
+    /// Famicom Disk System のディスクを次の面に入れ替える。それ以外のカートリッジでは何もしない。
+    pub fn switch_disk_side(&mut self) {
+        self.cartridge.switch_disk_side();
+    }
+
+    /// NSF の次の曲を最初から再生する。それ以外のカートリッジでは何もしない。
+    pub fn next_track(&mut self) {
+        if self.cartridge.next_track() {
+            self.reset();
+        }
+    }
+
+    /// NSF の前の曲を最初から再生する。それ以外のカートリッジでは何もしない。
+    pub fn previous_track(&mut self) {
+        if self.cartridge.previous_track() {
+            self.reset();
+        }
+    }
+
+    /// NSF などが持つタイトルと曲目。
+    pub fn media_metadata(&self) -> Option<MediaMetadata> {
+        self.cartridge.data_ref().nsf().map(NsfInfo::metadata)
     }
 
     pub fn peek_work_ram(&self, address: usize) -> Option<u8> {
@@ -286,6 +458,88 @@ impl Core {
         self.ppu.peek_vram(address, self.cartridge.as_ref())
     }
 
+    /// メモリビューアが扱う空間の大きさ。
+    pub fn memory_len(&self, space: MemorySpace) -> usize {
+        match space {
+            MemorySpace::CpuBus => 0x10000,
+            MemorySpace::VideoBus => 0x4000,
+            MemorySpace::SpriteRam => 0x100,
+            MemorySpace::ProgramRom => self.cartridge.data_ref().prog_rom_len(),
+            MemorySpace::CharacterMemory => self.cartridge.character_memory().len(),
+        }
+    }
+
+    /// 副作用なしで 1 バイト読む。I/O レジスタやオープンバスの番地は None。
+    pub fn peek_memory(&self, space: MemorySpace, address: usize) -> Option<u8> {
+        if address >= self.memory_len(space) {
+            return None;
+        }
+        match space {
+            MemorySpace::CpuBus => match address {
+                0x0000..=0x1FFF => self.cpu.peek_work_ram(address),
+                0x4020..=0xFFFF => {
+                    let result = self.cartridge.read(address);
+                    (result.mask == 0xFF).then_some(result.data)
+                }
+                _ => None,
+            },
+            MemorySpace::VideoBus => match address {
+                0x0000..=0x1FFF => {
+                    let result = self.cartridge.read(address);
+                    (result.mask == 0xFF).then_some(result.data)
+                }
+                _ => self.peek_ppu_vram(address),
+            },
+            MemorySpace::SpriteRam => Some(self.ppu.peek_oam(address)),
+            MemorySpace::ProgramRom => Some(self.cartridge.data_ref().read_prog_rom(address)),
+            MemorySpace::CharacterMemory => self.cartridge.character_memory().get(address).copied(),
+        }
+    }
+
+    /// 1 バイト書き換える。マッパーのレジスタには触れず、割り込みも起こさない。
+    /// CPU バスの ROM は書けないので PRG-ROM 側を書き換える。ROM を書き換えると
+    /// ステートセーブの照合に使う ROM の同一性も変わる。
+    pub fn poke_memory(
+        &mut self,
+        space: MemorySpace,
+        address: usize,
+        value: u8,
+    ) -> Result<(), MemoryAccessError> {
+        let len = self.memory_len(space);
+        if address >= len {
+            return Err(MemoryAccessError::OutOfRange {
+                space,
+                address,
+                len,
+            });
+        }
+        let written = match space {
+            MemorySpace::CpuBus => match address {
+                0x0000..=0x1FFF => {
+                    self.cpu.poke_work_ram(address, value);
+                    true
+                }
+                0x6000..=0x7FFF => self.cartridge.poke_ram(address, value),
+                _ => false,
+            },
+            MemorySpace::VideoBus => match address {
+                0x0000..=0x1FFF => self.cartridge.poke_character(address, value),
+                _ => self.ppu.poke_vram(address, value, self.cartridge.as_mut()),
+            },
+            MemorySpace::SpriteRam => {
+                self.ppu.poke_oam(address, value);
+                true
+            }
+            MemorySpace::ProgramRom => self.cartridge.poke_program_rom(address, value),
+            MemorySpace::CharacterMemory => self.cartridge.poke_character_memory(address, value),
+        };
+        if written {
+            Ok(())
+        } else {
+            Err(MemoryAccessError::NotWritable { space, address })
+        }
+    }
+
     pub fn rom_identity(&self) -> RomIdentity {
         let data = self.cartridge.data_ref();
         RomIdentity {
@@ -311,6 +565,11 @@ impl Core {
         self.options
     }
 
+    /// ヘッダとオプションから決定した実行中の地域タイミング。
+    pub fn console_type(&self) -> ConsoleType {
+        self.console_type
+    }
+
     pub fn has_persistent_mapper_save(&self) -> bool {
         self.cartridge.has_persistent_mapper_save()
     }
@@ -350,6 +609,7 @@ impl Core {
             ppu: self.ppu.clone(),
             apu: self.apu.clone(),
             cartridge: self.cartridge.export_runtime_state()?,
+            ppu_clock_phase: self.ppu_clock_phase,
         };
         Ok(encode_payload(&payload)?)
     }
@@ -366,10 +626,19 @@ impl Core {
         ppu.validate_runtime_state()?;
         let apu = payload.apu;
         apu.validate_runtime_state()?;
+        if ppu.console_type() != self.console_type
+            || apu.console_type() != self.console_type
+            || !self
+                .console_type
+                .validate_ppu_clock_phase(payload.ppu_clock_phase)
+        {
+            return Err(PersistenceError::Validation("console type mismatch".into()).into());
+        }
         self.cartridge.import_runtime_state(payload.cartridge)?;
         self.cpu = cpu;
         self.ppu = ppu;
         self.apu = apu;
+        self.ppu_clock_phase = payload.ppu_clock_phase;
         Ok(())
     }
 
@@ -379,10 +648,12 @@ impl Core {
         hub: &mut dyn ControllerHub,
         audio: &mut dyn AudioBackend,
     ) -> u64 {
-        let mut state = self
-            .apu_state
-            .take()
-            .unwrap_or_else(|| Box::new(ApuState::new(CLOCK_RATE as u32, audio.sample_rate())));
+        let mut state = self.apu_state.take().unwrap_or_else(|| {
+            Box::new(ApuState::new(
+                self.console_type.cpu_clock_rate() as u32,
+                audio.sample_rate(),
+            ))
+        });
         let mut adapter = ApuAdapter {
             inner: audio,
             state: &mut state,
@@ -399,6 +670,8 @@ impl Core {
         mixer: &mut M,
     ) -> u64 {
         let mut cycles = 0;
+        self.light_sensing = hub.senses_light();
+        self.apply_ram_cheats();
         let mixer_sample_rate = mixer.sample_rate();
         let apu_batch_mode = self.apu_batch_mode(mixer_sample_rate);
         let mut scheduler_enabled = true;
@@ -431,12 +704,44 @@ impl Core {
             }
 
             cycles += 1;
-            if self.step_cycle(screen, hub, mixer, mixer_sample_rate) {
+            let debugging = self.debugger.is_armed();
+            let in_interrupt = debugging && self.cpu.in_interrupt_sequence();
+            let beam = self.trace.is_some().then(|| self.ppu.scanline_and_dot());
+            let frame_done = self.step_cycle(screen, hub, mixer, mixer_sample_rate);
+            if let (Some((scanline, dot)), Some(trace)) = (beam, self.trace.as_mut()) {
+                trace.record(&self.cpu, self.cartridge.as_ref(), scanline, dot);
+            }
+            if let Some(capture) = self.ppu_capture.as_mut()
+                && capture.entered(self.ppu.scanline_and_dot().0)
+            {
+                capture.store(self.ppu.snapshot(self.cartridge.as_ref()));
+            }
+            if debugging {
+                let cycle = self.debug_cycle(in_interrupt);
+                if self.debugger.after_cycle(&cycle) {
+                    return cycles;
+                }
+            }
+            if frame_done {
                 return cycles;
             }
         }
     }
 
+    /// `in_interrupt` はこのサイクルを進める前に割り込みシーケンス中だったか。
+    fn debug_cycle(&self, in_interrupt: bool) -> DebugCycle {
+        let boundary_opcode = self.cpu.boundary_opcode();
+        let (scanline, dot) = self.ppu.scanline_and_dot();
+        DebugCycle {
+            registers: self.cpu.registers(),
+            scanline,
+            dot,
+            boundary_opcode,
+            entered_interrupt: (in_interrupt && boundary_opcode.is_some())
+                .then(|| self.cpu.interrupt_sequence_was_nmi()),
+        }
+    }
+
     #[inline(always)]
     fn step_cycle<M: AudioBackend>(
         &mut self,
@@ -447,13 +752,52 @@ impl Core {
     ) -> bool {
         // 1CPUサイクルにつき、APUは1、PPUはNTSC=>3,PAL=>3.2となる
         let mut result = false;
-        self.cpu
-            .step(&mut self.ppu, self.cartridge.as_mut(), hub, &mut self.apu);
+        let mut light_hub;
+        let hub: &mut dyn ControllerHub = if self.light_sensing {
+            light_hub = LightSensingHub {
+                hub: &mut *hub,
+                light: FrameLight {
+                    screen: &*screen,
+                    beam: self.ppu.beam_position(),
+                },
+            };
+            &mut light_hub
+        } else {
+            &mut *hub
+        };
+        let watches_memory = self.debugger.watches_memory();
+        if self.cheats.patches_reads() || watches_memory {
+            let mut mapper_bus =
+                crate::cartridge_bus::mapper_cartridge_bus(self.cartridge.as_mut());
+            let mut cheat_bus;
+            let cartridge: &mut dyn CpuCartridgeBus = if self.cheats.patches_reads() {
+                cheat_bus =
+                    crate::cartridge_bus::cheat_cartridge_bus(&mut mapper_bus, &self.cheats);
+                &mut cheat_bus
+            } else {
+                &mut mapper_bus
+            };
+            if watches_memory {
+                let mut cartridge =
+                    crate::cartridge_bus::debug_cartridge_bus(cartridge, &mut self.debugger);
+                self.cpu
+                    .step_bus(&mut self.ppu, &mut cartridge, hub, &mut self.apu);
+            } else {
+                self.cpu
+                    .step_bus(&mut self.ppu, cartridge, hub, &mut self.apu);
+            }
+        } else {
+            self.cpu
+                .step(&mut self.ppu, self.cartridge.as_mut(), hub, &mut self.apu);
+        }
+        let ppu_cycles = self.advance_ppu_clock(1);
         let mut ppu_cartridge = crate::cartridge_bus::mapper_cartridge_bus(self.cartridge.as_mut());
-        if self
-            .ppu
-            .step_exact_many(screen, &mut ppu_cartridge, self.cpu.interrupt_mut(), 3)
-        {
+        if self.ppu.step_exact_many(
+            screen,
+            &mut ppu_cartridge,
+            self.cpu.interrupt_mut(),
+            ppu_cycles,
+        ) {
             result = true;
         }
         self.cartridge.step(self.cpu.interrupt_mut());
@@ -468,11 +812,22 @@ impl Core {
         result
     }
 
+    fn apply_ram_cheats(&mut self) {
+        for &(address, value) in self.cheats.ram_writes() {
+            if address < 0x2000 {
+                self.cpu.poke_work_ram(address, value);
+            } else {
+                // マッパーのレジスタには書かず、PRG-RAM だけを書き換える
+                self.cartridge.poke_ram(address, value);
+            }
+        }
+    }
+
     #[inline]
     fn apu_batch_mode(&self, mixer_sample_rate: u32) -> ApuBatchMode {
         if self.cartridge.expansion_audio_cpu_step_synchronized() {
             ApuBatchMode::SynchronizedExpansionAudio
-        } else if Apu::should_step_many_exact(mixer_sample_rate) {
+        } else if self.apu.should_step_many_exact(mixer_sample_rate) {
             ApuBatchMode::Exact
         } else {
             ApuBatchMode::Batched
@@ -487,7 +842,15 @@ impl Core {
         mixer_sample_rate: u32,
         apu_batch_mode: ApuBatchMode,
     ) -> Option<(u64, bool)> {
-        if !self.cartridge.allow_instruction_fast_path() {
+        // 高速パスは ROM を直接読むため、Game Genie の置き換えを通らない。
+        // デバッガはサイクルごとに止める位置を判定し、トレースは命令ごとに PPU の位置を記録する。
+        // PPU のスナップショットはスキャンラインに入ったサイクルで取る
+        if !self.cartridge.allow_instruction_fast_path()
+            || self.cheats.patches_reads()
+            || self.debugger.is_armed()
+            || self.trace.is_some()
+            || self.ppu_capture.is_some()
+        {
             return None;
         }
 
@@ -527,7 +890,7 @@ impl Core {
             return None;
         }
 
-        let ppu_cycles = cpu_cycles * 3;
+        let ppu_cycles = self.advance_ppu_clock(cpu_cycles);
         let screen_updated = {
             let mut ppu_cartridge =
                 crate::cartridge_bus::mapper_cartridge_bus(self.cartridge.as_mut());
@@ -597,12 +960,24 @@ impl Core {
         }
     }
 
+    #[inline]
+    fn advance_ppu_clock(&mut self, cpu_cycles: u64) -> u64 {
+        let ppu_cycles = self.console_type.ppu_dots(self.ppu_clock_phase, cpu_cycles);
+        self.ppu_clock_phase = self
+            .console_type
+            .next_ppu_clock_phase(self.ppu_clock_phase, cpu_cycles);
+        ppu_cycles
+    }
+
     fn scheduler_fast_path_window(&self) -> u64 {
         let max_cpu_cycles = Self::INSTRUCTION_SCHEDULER_MAX_BATCH_CYCLES;
-        let ppu_event_cycles = self
-            .ppu
-            .cycles_until_next_scheduler_event(max_cpu_cycles * 3);
-        let ppu_safe_cpu_cycles = ppu_event_cycles.saturating_sub(1) / 3;
+        let ppu_event_cycles = self.ppu.cycles_until_next_scheduler_event(
+            self.console_type
+                .ppu_dots(self.ppu_clock_phase, max_cpu_cycles),
+        );
+        let ppu_safe_cpu_cycles = self
+            .console_type
+            .cpu_cycles_within_ppu_dots(self.ppu_clock_phase, ppu_event_cycles.saturating_sub(1));
         let apu_safe_cpu_cycles = self
             .apu
             .cycles_until_next_scheduler_event(self.cpu.interrupt_ref(), max_cpu_cycles)
@@ -668,6 +1043,7 @@ fn nrom_test_cartridge() -> Box<dyn Cartridge> {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid"),
     )
@@ -689,6 +1065,7 @@ fn nrom_test_data() -> CartridgeData {
         has_battery: false,
         sub_mapper_type: 0,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("test cartridge data should be valid")
 }
@@ -708,6 +1085,7 @@ fn mmc6_test_data() -> CartridgeData {
         has_battery: true,
         sub_mapper_type: 1,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("test cartridge data should be valid")
 }
@@ -731,6 +1109,7 @@ fn nrom_program_test_data(program: &[u8]) -> CartridgeData {
         has_battery: false,
         sub_mapper_type: 0,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("test cartridge data should be valid")
 }
@@ -762,6 +1141,7 @@ fn nrom_repeating_fast_path_program_test_data() -> CartridgeData {
         has_battery: false,
         sub_mapper_type: 0,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("test cartridge data should be valid")
 }
@@ -802,6 +1182,7 @@ fn mapper_program_with_prefix_test_data(
         has_battery: false,
         sub_mapper_type: 0,
         trainer: Vec::new(),
+        console_type: None,
     })
     .expect("test cartridge data should be valid")
 }
@@ -810,7 +1191,7 @@ fn mapper_program_with_prefix_test_data(
 mod cartridge_tests {
     use crate::{
         Core, cartridge_data_parts::CartridgeDataParts, cartridge_rom::CartridgeData,
-        mirror::MirrorMode, rom_format::RomFormat,
+        mapper_program_test_data, mirror::MirrorMode, rom_format::RomFormat,
     };
 
     #[test]
@@ -828,6 +1209,7 @@ mod cartridge_tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
 
@@ -865,6 +1247,7 @@ mod cartridge_tests {
             has_battery: true,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
 
@@ -874,6 +1257,20 @@ mod cartridge_tests {
         assert_eq!(info.prg_ram_len, 0x2000);
         assert_eq!(info.save_prg_ram_len, 0x2000);
     }
+
+    #[test]
+    fn reset_notifies_cartridge_but_power_on_does_not() {
+        let data = || mapper_program_test_data(57, RomFormat::INes, 0x8000, 0x2000);
+        let mut core = Core::new(data()).expect("core should construct");
+        // Mapper 57 はリセットのたびにメニューの DIP スイッチを進める
+        assert_eq!(core.cartridge.read(0x6000).data, 0x00);
+        core.reset();
+        core.reset();
+        assert_eq!(core.cartridge.read(0x6000).data, 0x02);
+
+        let core = Core::new(data()).expect("core should construct");
+        assert_eq!(core.cartridge.read(0x6000).data, 0x00);
+    }
 }
 
 #[cfg(test)]
@@ -1187,6 +1584,7 @@ mod scheduler_tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
         let mut scheduled = Core::new(data.clone()).expect("scheduled core should construct");
@@ -1484,6 +1882,301 @@ mod scheduler_tests {
             .is_none()
         );
     }
+
+    // 8000: LDA #$01 / JSR $8010 / STA $0300 / JMP $8000, 8010: INX / RTS
+    fn debugger_program_test_data() -> CartridgeData {
+        let mut program = vec![
+            0xA9, 0x01, 0x20, 0x10, 0x80, 0x8D, 0x00, 0x03, 0x4C, 0x00, 0x80,
+        ];
+        program.resize(0x10, 0xEA);
+        program.extend_from_slice(&[0xE8, 0x60]);
+        nrom_program_test_data(&program)
+    }
+
+    fn run_until_break(core: &mut Core) -> (u64, Option<BreakReason>) {
+        let mut screen = null_fb();
+        let mut hub = NullController;
+        let mut mixer = CountingMixer::default();
+        let cycles = core.run_frame_inner(&mut screen, &mut hub, &mut mixer);
+        (cycles, core.take_debug_break())
+    }
+
+    fn register(core: &Core, name: &str) -> u32 {
+        core.debug_state()
+            .registers
+            .iter()
+            .find(|register| register.name == name)
+            .map(|register| register.value)
+            .expect("register should exist")
+    }
+
+    #[test]
+    fn execute_breakpoint_stops_run_frame_before_the_instruction() {
+        use nerust_core_traits::debug::BreakpointKind;
+
+        let mut core = Core::new(debugger_program_test_data()).expect("core should construct");
+        core.set_breakpoints(&[
+            Breakpoint::new(BreakpointKind::Execute, 0x9000),
+            Breakpoint::new(BreakpointKind::Execute, 0x8005).with_condition("x == 3"),
+        ])
+        .expect("breakpoints should be valid");
+
+        let (cycles, reason) = run_until_break(&mut core);
+        assert!(cycles < 1000);
+        assert_eq!(
+            reason,
+            Some(BreakReason::Breakpoint {
+                index: 1,
+                address: 0x8005
+            })
+        );
+        assert_eq!(core.debug_state().pc, 0x8005);
+        assert_eq!(register(&core, "X"), 3);
+    }
+
+    #[test]
+    fn write_breakpoint_stops_after_the_storing_instruction() {
+        use nerust_core_traits::debug::BreakpointKind;
+
+        let mut core = Core::new(debugger_program_test_data()).expect("core should construct");
+        core.set_breakpoints(&[
+            Breakpoint::new(BreakpointKind::Write, 0x0300).with_condition("value == 1")
+        ])
+        .expect("breakpoint should be valid");
+
+        let (_, reason) = run_until_break(&mut core);
+        assert_eq!(
+            reason,
+            Some(BreakReason::Breakpoint {
+                index: 0,
+                address: 0x0300
+            })
+        );
+        assert_eq!(core.debug_state().pc, 0x8008);
+        assert_eq!(core.peek_work_ram(0x0300), Some(0x01));
+    }
+
+    #[test]
+    fn step_modes_stop_at_the_expected_instruction() {
+        use nerust_core_traits::debug::BreakpointKind;
+
+        let mut core = Core::new(debugger_program_test_data()).expect("core should construct");
+        core.set_breakpoints(&[Breakpoint::new(BreakpointKind::Execute, 0x8002)])
+            .expect("breakpoint should be valid");
+        run_until_break(&mut core);
+        core.set_breakpoints(&[]).expect("clearing should succeed");
+
+        core.debug_step(StepMode::Over);
+        assert_eq!(run_until_break(&mut core).1, Some(BreakReason::Step));
+        assert_eq!(core.debug_state().pc, 0x8005);
+        assert_eq!(register(&core, "X"), 1);
+
+        core.debug_step(StepMode::Instruction);
+        run_until_break(&mut core);
+        assert_eq!(core.debug_state().pc, 0x8008);
+
+        // JSR で $8010 に入ってから、RTS で戻るまで進める
+        core.debug_step(StepMode::Instruction);
+        run_until_break(&mut core);
+        core.debug_step(StepMode::Instruction);
+        run_until_break(&mut core);
+        core.debug_step(StepMode::Instruction);
+        run_until_break(&mut core);
+        assert_eq!(core.debug_state().pc, 0x8010);
+        core.debug_step(StepMode::Out);
+        assert_eq!(run_until_break(&mut core).1, Some(BreakReason::Step));
+        assert_eq!(core.debug_state().pc, 0x8005);
+
+        core.debug_step(StepMode::Scanline(100));
+        assert_eq!(run_until_break(&mut core).1, Some(BreakReason::Step));
+        assert_eq!(core.debug_state().scanline, 100);
+
+        // 何も仕掛けていなければフレームの終わりまで走る
+        let (cycles, reason) = run_until_break(&mut core);
+        assert!(cycles > 1000);
+        assert_eq!(reason, None);
+    }
+
+    #[test]
+    fn set_cpu_register_redirects_execution() {
+        let mut core = Core::new(debugger_program_test_data()).expect("core should construct");
+        // 電源投入直後はリセットシーケンス中なので、最初のステップは $8000 で止まる
+        core.debug_step(StepMode::Instruction);
+        run_until_break(&mut core);
+        assert_eq!(core.debug_state().pc, 0x8000);
+        core.debug_step(StepMode::Instruction);
+        run_until_break(&mut core);
+        assert_eq!(core.debug_state().pc, 0x8002);
+
+        core.set_cpu_register("PC", 0x8010)
+            .expect("PC should be writable");
+        core.set_cpu_register("c", 1)
+            .expect("flags should be writable");
+        assert_eq!(core.debug_state().pc, 0x8010);
+        assert!(
+            core.debug_state()
+                .flags
+                .iter()
+                .any(|flag| flag.name == "C" && flag.set)
+        );
+        core.debug_step(StepMode::Instruction);
+        run_until_break(&mut core);
+        assert_eq!(register(&core, "X"), 1);
+        assert!(matches!(
+            core.set_cpu_register("Q", 0),
+            Err(DebuggerError::UnknownRegister(_))
+        ));
+    }
+
+    #[test]
+    fn armed_debugger_disables_instruction_scheduler() {
+        use nerust_core_traits::debug::BreakpointKind;
+
+        let mut core =
+            Core::new(nrom_repeating_fast_path_program_test_data()).expect("core should construct");
+        core.set_breakpoints(&[Breakpoint::new(BreakpointKind::Execute, 0x9000)])
+            .expect("breakpoint should be valid");
+        let mut screen = null_fb();
+        let mut hub = NullController;
+        let mut mixer = CountingMixer::default();
+        let sample_rate = mixer.sample_rate();
+
+        assert!(
+            step_instruction_event_for_test(
+                &mut core,
+                &mut screen,
+                &mut hub,
+                &mut mixer,
+                sample_rate
+            )
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn trace_ring_keeps_the_latest_instructions() {
+        let mut core = Core::new(debugger_program_test_data()).expect("core should construct");
+        core.set_trace_sink(Some(TraceSink::ring(4)));
+        let mut screen = null_fb();
+        let mut hub = NullController;
+        let mut mixer = CountingMixer::default();
+        let sample_rate = mixer.sample_rate();
+        assert!(
+            step_instruction_event_for_test(
+                &mut core,
+                &mut screen,
+                &mut hub,
+                &mut mixer,
+                sample_rate
+            )
+            .is_none()
+        );
+
+        core.set_breakpoints(&[Breakpoint::new(
+            nerust_core_traits::debug::BreakpointKind::Execute,
+            0x8005,
+        )])
+        .expect("breakpoint should be valid");
+        run_until_break(&mut core);
+        let sink = core.set_trace_sink(None).expect("sink should be installed");
+
+        assert_eq!(sink.lines_written(), 5);
+        assert_eq!(
+            sink.lines().collect::<Vec<_>>(),
+            [
+                "8002  20 10 80  JSR $8010                       A:01 X:00 Y:00 P:24 SP:FD PPU:261, 27 CYC:9",
+                "8010  E8        INX                             A:01 X:00 Y:00 P:24 SP:FB PPU:261, 45 CYC:15",
+                "8011  60        RTS                             A:01 X:01 Y:00 P:24 SP:FB PPU:261, 51 CYC:17",
+                "8005  8D 00 03  STA $0300 = 00                  A:01 X:01 Y:00 P:24 SP:FD PPU:261, 69 CYC:23",
+            ]
+        );
+    }
+
+    #[test]
+    fn memory_viewer_pokes_storage_and_skips_registers() {
+        let mut core =
+            Core::new(nrom_program_test_data(&[0xA9, 0x42])).expect("core should construct");
+
+        core.poke_memory(MemorySpace::CpuBus, 0x0001, 0x55)
+            .expect("work RAM should be writable");
+        assert_eq!(core.peek_memory(MemorySpace::CpuBus, 0x0801), Some(0x55));
+        // $2002 を読むと VBlank フラグが落ちるので読まない
+        assert_eq!(core.peek_memory(MemorySpace::CpuBus, 0x2002), None);
+        assert!(matches!(
+            core.poke_memory(MemorySpace::CpuBus, 0x2000, 0x80),
+            Err(MemoryAccessError::NotWritable { .. })
+        ));
+
+        // CPU バスの ROM は書けず、PRG-ROM 側で書き換える
+        assert_eq!(core.peek_memory(MemorySpace::CpuBus, 0x8000), Some(0xA9));
+        assert!(core.poke_memory(MemorySpace::CpuBus, 0x8000, 0xEA).is_err());
+        core.poke_memory(MemorySpace::ProgramRom, 0x0000, 0xEA)
+            .expect("PRG-ROM should be writable");
+        assert_eq!(core.peek_memory(MemorySpace::CpuBus, 0x8000), Some(0xEA));
+        assert!(matches!(
+            core.poke_memory(MemorySpace::ProgramRom, 0x8000, 0),
+            Err(MemoryAccessError::OutOfRange { len: 0x8000, .. })
+        ));
+
+        // 水平ミラーでは $2400 が $2000 と同じ面、$3F10 は $3F00 のミラー
+        core.poke_memory(MemorySpace::VideoBus, 0x2005, 0x21)
+            .expect("nametable should be writable");
+        core.poke_memory(MemorySpace::VideoBus, 0x3F10, 0x16)
+            .expect("palette should be writable");
+        assert_eq!(core.peek_memory(MemorySpace::VideoBus, 0x2405), Some(0x21));
+        assert_eq!(core.peek_memory(MemorySpace::VideoBus, 0x2805), Some(0));
+        assert_eq!(core.peek_memory(MemorySpace::VideoBus, 0x3F00), Some(0x16));
+
+        assert!(
+            core.poke_memory(MemorySpace::VideoBus, 0x0010, 0xFF)
+                .is_err()
+        );
+        core.poke_memory(MemorySpace::CharacterMemory, 0x0010, 0xFF)
+            .expect("CHR-ROM should be writable");
+        assert_eq!(core.peek_memory(MemorySpace::VideoBus, 0x0010), Some(0xFF));
+
+        core.poke_memory(MemorySpace::SpriteRam, 0xFF, 0x7F)
+            .expect("OAM should be writable");
+        assert_eq!(core.peek_memory(MemorySpace::SpriteRam, 0xFF), Some(0x7F));
+        assert_eq!(core.peek_memory(MemorySpace::SpriteRam, 0x100), None);
+    }
+
+    #[test]
+    fn ppu_capture_keeps_the_snapshot_from_the_requested_scanline() {
+        let mut core = Core::new(debugger_program_test_data()).expect("core should construct");
+        assert_eq!(core.ppu_snapshot().scanline(), 261);
+
+        core.set_ppu_capture_scanline(Some(100));
+        let mut screen = null_fb();
+        let mut hub = NullController;
+        let mut mixer = CountingMixer::default();
+        let sample_rate = mixer.sample_rate();
+        assert!(
+            step_instruction_event_for_test(
+                &mut core,
+                &mut screen,
+                &mut hub,
+                &mut mixer,
+                sample_rate
+            )
+            .is_none()
+        );
+        core.run_frame(&mut screen, &mut hub, &mut mixer);
+        core.run_frame(&mut screen, &mut hub, &mut mixer);
+
+        let snapshot = core.ppu_snapshot();
+        assert_eq!(snapshot.scanline(), 100);
+        // フレーム番号は描画の終わりで進むので、2 フレーム目の途中のスナップショットは 1
+        assert_eq!(snapshot.frame(), 1);
+        assert_eq!(core.ppu.frame_count(), 2);
+        assert_ne!(core.ppu.scanline_and_dot().0, 100);
+
+        core.set_ppu_capture_scanline(None);
+        assert_eq!(
+            core.ppu_snapshot().scanline(),
+            core.ppu.scanline_and_dot().0
+        );
+    }
 }
 
 #[cfg(test)]
@@ -1662,6 +2355,7 @@ mod persistence_tests {
             nrom_test_data(),
             CoreOptions {
                 mmc3_irq_variant: Some(Mmc3IrqVariant::Sharp),
+                region: None,
             },
         )
         .expect("source core should construct");
@@ -1673,6 +2367,7 @@ mod persistence_tests {
             nrom_test_data(),
             CoreOptions {
                 mmc3_irq_variant: Some(Mmc3IrqVariant::Nec),
+                region: None,
             },
         )
         .expect("target core should construct");
@@ -1892,6 +2587,7 @@ mod persistence_tests {
                 has_battery: true,
                 sub_mapper_type: 0,
                 trainer: Vec::new(),
+                console_type: None,
             })
             .expect("test cartridge data should be valid"),
         )
@@ -1922,6 +2618,7 @@ mod persistence_tests {
                 has_battery: true,
                 sub_mapper_type: 0,
                 trainer: Vec::new(),
+                console_type: None,
             })
             .expect("test cartridge data should be valid"),
         )
@@ -1936,4 +2633,82 @@ mod persistence_tests {
         assert_eq!(decoded.prg_ram.len(), 0x2000);
         assert!(decoded.chr_ram.is_empty());
     }
+
+    #[test]
+    fn mapper_save_exposes_bandai_eeprom_without_prg_ram() {
+        let core = Core::new(
+            CartridgeData::new(CartridgeDataParts {
+                format: RomFormat::INes,
+                prog_rom: vec![0; 0x40000],
+                char_rom: vec![0; 0x40000],
+                pram_length: 0,
+                save_pram_length: 0,
+                vram_length: 0,
+                save_vram_length: 0,
+                mapper_type: 159,
+                mirror_mode: MirrorMode::Horizontal,
+                has_battery: false,
+                sub_mapper_type: 0,
+                trainer: Vec::new(),
+                console_type: None,
+            })
+            .expect("test cartridge data should be valid"),
+        )
+        .expect("core should construct");
+
+        let decoded = export_mapper_save_payload(&core);
+        assert_eq!(decoded.prg_ram.len(), 0x80);
+        assert!(decoded.chr_ram.is_empty());
+    }
+
+    #[test]
+    fn mapper_save_exposes_unrom512_flash_sectors() {
+        let data = || {
+            CartridgeData::new(CartridgeDataParts {
+                format: RomFormat::INes,
+                prog_rom: vec![0; 0x80000],
+                char_rom: Vec::new(),
+                pram_length: 0,
+                save_pram_length: 0,
+                vram_length: 0,
+                save_vram_length: 0,
+                mapper_type: 30,
+                mirror_mode: MirrorMode::Vertical,
+                has_battery: true,
+                sub_mapper_type: 0,
+                trainer: Vec::new(),
+                console_type: None,
+            })
+            .expect("test cartridge data should be valid")
+        };
+        let mut core = Core::new(data()).expect("core should construct");
+        let mut interrupt = crate::interrupt::Interrupt::new();
+        // セクタ 0 を消去する
+        for (bank, address, value) in [
+            (1, 0x9555, 0xAA),
+            (0, 0xAAAA, 0x55),
+            (1, 0x9555, 0x80),
+            (1, 0x9555, 0xAA),
+            (0, 0xAAAA, 0x55),
+            (0, 0x8000, 0x30),
+        ] {
+            core.cartridge.write(0xC000, bank, &mut interrupt);
+            core.cartridge.write(address, value, &mut interrupt);
+        }
+
+        let bytes = core
+            .export_mapper_save()
+            .expect("mapper save should export")
+            .expect("flash board should expose persistent save");
+        let decoded =
+            decode_payload::<MapperSavePayload>(&bytes).expect("mapper save payload should decode");
+        assert_eq!(decoded.prg_ram.len(), 16 + 0x1000);
+
+        let mut restored = Core::new(data()).expect("core should construct");
+        restored
+            .import_mapper_save(&bytes)
+            .expect("flash save should import");
+        restored.cartridge.write(0xC000, 0, &mut interrupt);
+        assert_eq!(restored.cartridge.read(0x8000).data, 0xFF);
+    }
 }
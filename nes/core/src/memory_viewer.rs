@@ -0,0 +1,18 @@
+//! メモリビューア: CPU と PPU のアドレス空間、OAM、PRG-ROM、CHR を副作用なしで読み書きする。
+//!
+//! 読むと状態が変わるレジスタは読まず、書き込みはマッパーのレジスタを通さずに
+//! 記憶素子だけを書き換える。
+
+use nerust_core_traits::debug::MemorySpace;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MemoryAccessError {
+    #[error("{space:?} address ${address:X} is past the end (${len:X} bytes)")]
+    OutOfRange {
+        space: MemorySpace,
+        address: usize,
+        len: usize,
+    },
+    #[error("{space:?} address ${address:04X} is not writable memory")]
+    NotWritable { space: MemorySpace, address: usize },
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use nerust_core_traits::identity::{MediaMetadata, TrackMetadata};
+
+/// NTSC 機での既定の再生間隔 (約 60.1Hz)。
+pub const NSF_DEFAULT_NTSC_SPEED: u16 = 16639;
+/// PAL 機での既定の再生間隔 (約 50.0Hz)。
+pub const NSF_DEFAULT_PAL_SPEED: u16 = 19997;
+
+bitflags::bitflags! {
+    /// NSF ヘッダの拡張音源フラグ。
+    #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct NsfChips: u8 {
+        const VRC6 = 0b0000_0001;
+        const VRC7 = 0b0000_0010;
+        const FDS = 0b0000_0100;
+        const MMC5 = 0b0000_1000;
+        const N163 = 0b0001_0000;
+        const SUNSOFT_5B = 0b0010_0000;
+    }
+}
+
+/// NSF / NSFe から読み取った再生情報と曲目。
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NsfInfo {
+    pub load_address: u16,
+    pub init_address: u16,
+    pub play_address: u16,
+    /// $5FF8-$5FFF の初期値。`None` ならバンク切り替えを使わず、ロードアドレスに直接配置する。
+    pub bank_init: Option<[u8; 8]>,
+    pub song_count: u8,
+    /// 最初に再生する曲 (0 始まり)。
+    pub first_song: u8,
+    /// PLAY を呼び出す間隔 (マイクロ秒)。
+    pub ntsc_speed: u16,
+    pub pal_speed: u16,
+    pub chips: NsfChips,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub copyright: Option<String>,
+    pub track_labels: Vec<String>,
+    /// 曲ごとの演奏時間 (ミリ秒)。NSFe の `time` チャンクにのみ存在する。
+    pub track_durations: Vec<Option<u32>>,
+}
+
+impl NsfInfo {
+    pub fn metadata(&self) -> MediaMetadata {
+        MediaMetadata {
+            title: self.title.clone(),
+            artist: self.artist.clone(),
+            copyright: self.copyright.clone(),
+            tracks: (0..usize::from(self.song_count))
+                .map(|index| TrackMetadata {
+                    label: self
+                        .track_labels
+                        .get(index)
+                        .filter(|label| !label.is_empty())
+                        .cloned(),
+                    duration: self
+                        .track_durations
+                        .get(index)
+                        .copied()
+                        .flatten()
+                        .map(|ms| Duration::from_millis(u64::from(ms))),
+                })
+                .collect(),
+            first_track: usize::from(self.first_song),
+        }
+    }
+}
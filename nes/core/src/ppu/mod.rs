@@ -13,10 +13,10 @@ use crate::{
     interrupt::Interrupt,
     persistence_error::PersistenceError,
     ppu_memory_access::{PpuBusAccess, PpuBusEvent, PpuReadAccess},
+    ppu_viewer::PpuSnapshot,
+    status::console_type::ConsoleType,
 };
 
-const NMI_SCAN_LINE: u16 = 242;
-const TOTAL_SCAN_LINE: u16 = 262;
 const PALETTE_ADDRESS: [usize; 32] = [
     0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E, 0x0F,
     0x00, 0x11, 0x12, 0x13, 0x04, 0x15, 0x16, 0x17, 0x08, 0x19, 0x1A, 0x1B, 0x0C, 0x1D, 0x1E, 0x1F,
@@ -37,13 +37,15 @@ struct DecayableOpenBus {
 mod tests {
     use nerust_render_traits::FrameBuffer;
 
-    use super::{Core, Mask, NMI_SCAN_LINE};
+    use super::{Core, Mask};
     use crate::{
         cart_device::Cartridge, cartridge, cartridge_data_parts::CartridgeDataParts,
         cartridge_rom::CartridgeData, interrupt::Interrupt, mirror::MirrorMode,
-        rom_format::RomFormat,
+        rom_format::RomFormat, status::console_type::ConsoleType,
     };
 
+    const NMI_SCAN_LINE: u16 = ConsoleType::Ntsc.nmi_scan_line();
+
     fn null_fb() -> FrameBuffer {
         let mut fb = FrameBuffer::with_capacity(256, 240, nerust_render_traits::PixelFormat::Rgba);
         fb.resize(256, 240);
@@ -64,6 +66,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
         cartridge::try_from(cartridge_data).expect("cartridge should construct")
@@ -86,6 +89,7 @@ mod tests {
             has_battery: false,
             sub_mapper_type: 0,
             trainer: Vec::new(),
+            console_type: None,
         })
         .expect("test cartridge data should be valid");
         cartridge::try_from(cartridge_data).expect("cartridge should construct")
@@ -308,7 +312,7 @@ mod tests {
         let batched = run_exact_many_recording(ppu, 5);
 
         assert_visible_advance_matches(&repeated, &batched);
-        assert_eq!(&batched.3.as_ref()[..5], &[0x2A; 5]);
+        assert_eq!(batched.3.pixels().take(5).collect::<Vec<_>>(), [0x2A; 5]);
         assert_eq!(batched.0.vram_read_delay, 0);
     }
 
@@ -324,7 +328,27 @@ mod tests {
         let batched = run_exact_many_recording(ppu, 12);
 
         assert_visible_advance_matches(&repeated, &batched);
-        assert_eq!(&batched.3.as_ref()[..12], &[0x31; 12]);
+        assert_eq!(batched.3.pixels().take(12).collect::<Vec<_>>(), [0x31; 12]);
+    }
+
+    #[test]
+    fn emphasis_bits_are_written_above_the_palette_index() {
+        let mut ppu = Core::new();
+        ppu.scan_line = 12;
+        ppu.cycle = 20;
+        ppu.state.vram_addr = 0;
+        ppu.palette[0] = 0x2A;
+        // 赤と青を強調
+        ppu.mask = Mask::from(0xA0);
+
+        let repeated = run_repeated_step_recording(ppu.clone(), 4);
+        let batched = run_exact_many_recording(ppu, 4);
+
+        assert_visible_advance_matches(&repeated, &batched);
+        assert_eq!(
+            batched.3.pixels().take(4).collect::<Vec<_>>(),
+            [0x2A | 0b101 << 6; 4]
+        );
     }
 
     #[test]
@@ -340,16 +364,7 @@ mod tests {
 
         assert_visible_advance_matches(&repeated, &batched);
         assert_eq!(batched.0.cycle, 260);
-        assert_eq!(
-            batched
-                .3
-                .as_ref()
-                .iter()
-                .take(6)
-                .filter(|&&b| b != 0)
-                .count(),
-            6
-        );
+        assert_eq!(batched.3.pixels().take(6).filter(|&p| p != 0).count(), 6);
     }
 
     #[test]
@@ -563,6 +578,14 @@ impl Mask {
         self.green_tint = false;
         self.blue_tint = false;
     }
+
+    /// フレームバッファの画素でパレット番号の上に載せる強調ビット
+    fn emphasis(&self) -> u16 {
+        (u16::from(self.red_tint)
+            | u16::from(self.green_tint) << 1
+            | u16::from(self.blue_tint) << 2)
+            << 6
+    }
 }
 
 impl From<u8> for Mask {
@@ -654,6 +677,8 @@ pub(crate) struct Core {
     openbus_vram: OpenBus,
     openbus_io: DecayableOpenBus,
     has_next_sprite: bool,
+    #[serde(default)]
+    console_type: ConsoleType,
     // screen_buffer: [u8; 256 * 240],
 }
 
@@ -700,9 +725,21 @@ impl Core {
             openbus_vram: OpenBus::new(),
             openbus_io: DecayableOpenBus::new(),
             has_next_sprite: false,
+            console_type: ConsoleType::Ntsc,
             // screen_buffer: [0; 256 * 240],
         }
     }
+
+    pub(crate) fn with_console_type(console_type: ConsoleType) -> Self {
+        Self {
+            console_type,
+            ..Self::new()
+        }
+    }
+
+    pub(crate) fn console_type(&self) -> ConsoleType {
+        self.console_type
+    }
     pub(crate) fn reset(&mut self) {
         self.vram = [0; 2048];
         self.palette = [
@@ -733,6 +770,11 @@ impl Core {
     }
 
     pub(crate) fn validate_runtime_state(&self) -> Result<(), PersistenceError> {
+        if self.scan_line >= self.console_type.total_scan_line() {
+            return Err(PersistenceError::Validation(
+                "PPU scan line overflow".into(),
+            ));
+        }
         if usize::from(self.sprite_index) > 8 {
             return Err(PersistenceError::Validation(
                 "PPU sprite index overflow".into(),
@@ -787,6 +829,70 @@ impl Core {
         }
     }
 
+    /// メモリビューアから $2000-$3FFF を書き換える。パターンテーブルは扱わない。
+    pub(crate) fn poke_vram(
+        &mut self,
+        mut address: usize,
+        value: u8,
+        cartridge: &mut dyn MapperCartridge,
+    ) -> bool {
+        address &= 0x3FFF;
+        match address {
+            0x2000..=0x3EFF => {
+                // ネームテーブルへの書き込みで割り込みを起こすマッパーはないので捨てる
+                let mut interrupt = Interrupt::default();
+                MapperCartridge::write_ppu_nametable(
+                    cartridge,
+                    address,
+                    value,
+                    &mut self.vram,
+                    &mut interrupt,
+                );
+                true
+            }
+            0x3F00..=0x3FFF => {
+                self.write_palette(address, value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub(crate) fn peek_oam(&self, address: usize) -> u8 {
+        self.primary_oam[address & 0xFF]
+    }
+
+    pub(crate) fn poke_oam(&mut self, address: usize, value: u8) {
+        self.primary_oam[address & 0xFF] = value;
+    }
+
+    /// ビューア用に VRAM・パターンテーブル・パレット・OAM を副作用なしで写す。
+    /// パターンテーブルは CPU から見える今の CHR バンクで読む。
+    pub(crate) fn snapshot(&self, cartridge: &dyn MapperCartridge) -> PpuSnapshot {
+        let mut nametables = Box::new([0; 0x1000]);
+        for (offset, value) in nametables.iter_mut().enumerate() {
+            *value = self.peek_vram(0x2000 + offset, cartridge).unwrap_or(0);
+        }
+        let mut pattern_tables = Box::new([0; 0x2000]);
+        for (address, value) in pattern_tables.iter_mut().enumerate() {
+            *value = MapperCartridge::read(cartridge, address).data;
+        }
+        let (scanline, _) = self.scanline_and_dot();
+        PpuSnapshot {
+            scanline,
+            frame: self.frame_count(),
+            nametables,
+            pattern_tables,
+            palette: std::array::from_fn(|index| self.read_palette(index)),
+            oam: self.primary_oam,
+            background_table: self.control.background_table,
+            sprite_table: self.control.sprite_table,
+            tall_sprites: self.control.sprite_size,
+            scroll_address: self.state.temp_vram_addr,
+            fine_x: self.state.x_scroll,
+        }
+    }
+
     #[inline]
     pub(crate) fn read_register(
         &mut self,
@@ -817,14 +923,16 @@ impl Core {
 
         let result = if self.status.sprite_overflow { 0x20 } else { 0 }
             | if self.status.sprite_zero_hit { 0x40 } else { 0 }
-            | if self.status.nmi_occurred && (self.scan_line != 242 || self.cycle != 0) {
+            | if self.status.nmi_occurred
+                && (self.scan_line != self.console_type.nmi_scan_line() || self.cycle != 0)
+            {
                 0x80
             } else {
                 0
             };
         self.status.nmi_occurred = false;
 
-        if self.scan_line == 242 && self.cycle < 3 {
+        if self.scan_line == self.console_type.nmi_scan_line() && self.cycle < 3 {
             interrupt.nmi = false;
         }
         result
@@ -1035,7 +1143,10 @@ impl Core {
         {
             interrupt.nmi = true;
         }
-        if self.scan_line == 242 && self.cycle < 3 && !self.control.nmi_output {
+        if self.scan_line == self.console_type.nmi_scan_line()
+            && self.cycle < 3
+            && !self.control.nmi_output
+        {
             interrupt.nmi = false;
         }
     }
@@ -1349,7 +1460,7 @@ impl Core {
         } else {
             self.state.vram_addr as usize
         };
-        screen.push(self.read_palette(color) & 0x3F);
+        screen.push(u16::from(self.read_palette(color) & 0x3F) | self.mask.emphasis());
         // self.screen_buffer[(self.cycle as usize - 1) + (self.scan_line as usize - 1) * 256]
     }
 
@@ -1478,6 +1589,8 @@ impl Core {
         if self.cycle > 339 {
             self.cycle = 0;
             self.scan_line += 1;
+            let nmi_scan_line = self.console_type.nmi_scan_line();
+            let total_scan_line = self.console_type.total_scan_line();
             match self.scan_line {
                 241 => {
                     self.frames += 1;
@@ -1485,14 +1598,13 @@ impl Core {
                     self.openbus_io.next();
                     self.render_screen(screen);
                 }
-                NMI_SCAN_LINE => self.set_vertical_blank(interrupt),
-                TOTAL_SCAN_LINE => {
+                x if x == nmi_scan_line => self.set_vertical_blank(interrupt),
+                x if x == total_scan_line => {
                     self.status.sprite_overflow = false;
                     self.status.sprite_zero_hit = false;
                     self.scan_line = 0;
                 }
-                x @ 1..=TOTAL_SCAN_LINE
-                    if x != 241 && x != NMI_SCAN_LINE && x != TOTAL_SCAN_LINE => {}
+                x if (1..total_scan_line).contains(&x) => {}
                 _ => unreachable!(),
             }
         } else {
@@ -1600,7 +1712,10 @@ impl Core {
                                 interrupt,
                                 PpuReadAccess::BackgroundNameTable,
                             );
-                            if self.scan_line == 0 && (self.frames & 1) != 0 {
+                            if self.scan_line == 0
+                                && (self.frames & 1) != 0
+                                && self.console_type.skips_odd_frame_dot()
+                            {
                                 self.cycle += 1;
                             }
                         }
@@ -1730,7 +1845,7 @@ impl Core {
             self.read_palette(self.state.vram_addr as usize)
         } & 0x3F;
         screen.push_many(
-            color,
+            u16::from(color) | self.mask.emphasis(),
             cycles
                 .try_into()
                 .expect("visible pixel batching never exceeds one scanline"),
@@ -1764,6 +1879,30 @@ impl Core {
         };
     }
 
+    /// 出力済みピクセルの境界 (x, y)。scan_line 0 は pre-render ラインのため None。
+    /// 可視ラインではサイクル 1..=256 で 1 ピクセルずつ出力される。
+    pub(crate) fn beam_position(&self) -> Option<(usize, usize)> {
+        (self.scan_line > 0).then(|| {
+            (
+                usize::from(self.cycle.min(256)),
+                usize::from(self.scan_line - 1),
+            )
+        })
+    }
+
+    /// 一般的な番号付けでの (scanline, dot)。可視ラインが 0..=239 で、pre-render は最後のライン。
+    pub(crate) fn scanline_and_dot(&self) -> (u16, u16) {
+        let total_scan_line = self.console_type.total_scan_line();
+        (
+            (self.scan_line + total_scan_line - 1) % total_scan_line,
+            self.cycle,
+        )
+    }
+
+    pub(crate) fn frame_count(&self) -> u64 {
+        self.frames as u64
+    }
+
     pub(crate) fn cycles_until_next_scheduler_event(&self, max_cycles: u64) -> u64 {
         if self.render_executing
             || self.post_render_executing
@@ -1774,18 +1913,16 @@ impl Core {
             return 1;
         }
 
+        let nmi_scan_line = self.console_type.nmi_scan_line();
+        let total_scan_line = self.console_type.total_scan_line();
         let mut cycle = self.cycle;
         let mut scan_line = self.scan_line;
         for elapsed in 1..=max_cycles {
             if cycle > 339 {
                 cycle = 0;
                 scan_line += 1;
-                match scan_line {
-                    241 | NMI_SCAN_LINE | TOTAL_SCAN_LINE => return elapsed,
-                    _ => {}
-                }
-                if scan_line == TOTAL_SCAN_LINE {
-                    scan_line = 0;
+                if scan_line == 241 || scan_line == nmi_scan_line || scan_line == total_scan_line {
+                    return elapsed;
                 }
             } else {
                 cycle += 1;
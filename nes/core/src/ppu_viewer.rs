@@ -0,0 +1,313 @@
+//! PPU ビューア: ネームテーブル・パターンテーブル・OAM・パレットを副作用なしで写し、
+//! マスターパレットの番号で描いた画像にする。
+//!
+//! スナップショットは指定したスキャンラインに入った時点で取る。スクロール位置は
+//! その時点の t レジスタと fine X から求める。
+
+use nerust_core_traits::debug::{IndexedImage, SpriteView, VideoMemoryView, ViewRect};
+
+const TILE_MAPS_WIDTH: usize = 512;
+const TILE_MAPS_HEIGHT: usize = 480;
+const PATTERN_TABLE_SIZE: usize = 128;
+const SCREEN_WIDTH: usize = 256;
+const SCREEN_HEIGHT: usize = 240;
+
+/// ある時点の PPU のメモリとスクロール。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PpuSnapshot {
+    pub(crate) scanline: u16,
+    pub(crate) frame: u64,
+    /// $2000-$2FFF をマッパーのミラーリング込みで読んだもの。
+    pub(crate) nametables: Box<[u8; 0x1000]>,
+    /// $0000-$1FFF を今の CHR バンクで読んだもの。
+    pub(crate) pattern_tables: Box<[u8; 0x2000]>,
+    /// $3F00-$3F1F。$3F10/$3F14/$3F18/$3F1C は背景側のミラー。
+    pub(crate) palette: [u8; 32],
+    pub(crate) oam: [u8; 256],
+    pub(crate) background_table: bool,
+    pub(crate) sprite_table: bool,
+    pub(crate) tall_sprites: bool,
+    /// t レジスタ (yyy NN YYYYY XXXXX) と fine X。
+    pub(crate) scroll_address: u16,
+    pub(crate) fine_x: u8,
+}
+
+impl PpuSnapshot {
+    pub fn scanline(&self) -> u16 {
+        self.scanline
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// ビューア用の画像に描く。パターンテーブルはパレット `pattern_palette` (0-7) で塗る。
+    pub fn video_memory_view(&self, pattern_palette: u8) -> VideoMemoryView {
+        let pattern_palette = pattern_palette & 7;
+        VideoMemoryView {
+            scanline: self.scanline,
+            frame: self.frame,
+            tile_maps: self.render_nametables(),
+            scroll: self.scroll(),
+            pattern_tables: (0..2)
+                .map(|table| self.render_pattern_table(table, pattern_palette))
+                .collect(),
+            sprites: (0..64).map(|index| self.sprite(index)).collect(),
+            palette: self.palette.iter().map(|color| color & 0x3F).collect(),
+        }
+    }
+
+    /// 4 面を並べた画像の上での、描画が始まる位置。
+    fn scroll(&self) -> ViewRect {
+        let t = usize::from(self.scroll_address);
+        let coarse_x = t & 0x1F;
+        let coarse_y = (t >> 5) & 0x1F;
+        let nametable = (t >> 10) & 3;
+        let fine_y = (t >> 12) & 7;
+        ViewRect {
+            x: (nametable & 1) * SCREEN_WIDTH + coarse_x * 8 + usize::from(self.fine_x),
+            // coarse Y が 30 以上 (属性テーブルの位置) でも、そのまま先へ進めた位置を示す
+            y: ((nametable >> 1) * SCREEN_HEIGHT + coarse_y * 8 + fine_y) % TILE_MAPS_HEIGHT,
+            width: SCREEN_WIDTH,
+            height: SCREEN_HEIGHT,
+        }
+    }
+
+    /// `palette` 番 (0-3 が背景、4-7 がスプライト) のパレットで `pixel` (0-3) の色を引く。
+    /// 色 0 はどのパレットでも $3F00 の背景色になる。
+    fn color(&self, palette: u8, pixel: u8) -> u8 {
+        let address = if pixel == 0 {
+            0
+        } else {
+            usize::from(palette) * 4 + usize::from(pixel)
+        };
+        self.palette[address] & 0x3F
+    }
+
+    fn tile_pixel(&self, table_base: usize, tile: usize, x: usize, y: usize) -> u8 {
+        let address = table_base + tile * 16 + y;
+        let low = self.pattern_tables[address];
+        let high = self.pattern_tables[address + 8];
+        let bit = 7 - x;
+        ((low >> bit) & 1) | (((high >> bit) & 1) << 1)
+    }
+
+    fn render_nametables(&self) -> IndexedImage {
+        let mut image = IndexedImage::new(TILE_MAPS_WIDTH, TILE_MAPS_HEIGHT);
+        let table_base = if self.background_table { 0x1000 } else { 0 };
+        for (table, nametable) in self.nametables.chunks_exact(0x400).enumerate() {
+            let origin_x = (table & 1) * SCREEN_WIDTH;
+            let origin_y = (table >> 1) * SCREEN_HEIGHT;
+            for tile_y in 0..30 {
+                for tile_x in 0..32 {
+                    let tile = usize::from(nametable[tile_y * 32 + tile_x]);
+                    let attribute = nametable[0x3C0 + (tile_y / 4) * 8 + tile_x / 4];
+                    let shift = ((tile_y & 2) << 1) | (tile_x & 2);
+                    let palette = (attribute >> shift) & 3;
+                    for y in 0..8 {
+                        for x in 0..8 {
+                            let pixel = self.tile_pixel(table_base, tile, x, y);
+                            image.set_pixel(
+                                origin_x + tile_x * 8 + x,
+                                origin_y + tile_y * 8 + y,
+                                self.color(palette, pixel),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        image
+    }
+
+    fn render_pattern_table(&self, table: usize, palette: u8) -> IndexedImage {
+        let mut image = IndexedImage::new(PATTERN_TABLE_SIZE, PATTERN_TABLE_SIZE);
+        for tile in 0..256 {
+            let origin_x = (tile % 16) * 8;
+            let origin_y = (tile / 16) * 8;
+            for y in 0..8 {
+                for x in 0..8 {
+                    let pixel = self.tile_pixel(table * 0x1000, tile, x, y);
+                    image.set_pixel(origin_x + x, origin_y + y, self.color(palette, pixel));
+                }
+            }
+        }
+        image
+    }
+
+    fn sprite(&self, index: usize) -> SpriteView {
+        let entry = &self.oam[index * 4..][..4];
+        let (y, tile, attributes, x) = (entry[0], entry[1], entry[2], entry[3]);
+        let flip_horizontal = attributes & 0x40 != 0;
+        let flip_vertical = attributes & 0x80 != 0;
+        let palette = attributes & 3;
+        let height = if self.tall_sprites { 16 } else { 8 };
+
+        let mut image = IndexedImage::new(8, height);
+        for row in 0..height {
+            let source_row = if flip_vertical { height - 1 - row } else { row };
+            // 8x16 では tile の bit 0 がテーブルを選び、上下で隣り合うタイルを使う
+            let (table_base, source_tile) = if self.tall_sprites {
+                (
+                    usize::from(tile & 1) * 0x1000,
+                    usize::from(tile & 0xFE) + source_row / 8,
+                )
+            } else {
+                (
+                    if self.sprite_table { 0x1000 } else { 0 },
+                    usize::from(tile),
+                )
+            };
+            for column in 0..8 {
+                let source_column = if flip_horizontal { 7 - column } else { column };
+                let pixel = self.tile_pixel(table_base, source_tile, source_column, source_row % 8);
+                image.set_pixel(column, row, self.color(4 + palette, pixel));
+            }
+        }
+
+        SpriteView {
+            index: index as u8,
+            x,
+            y,
+            tile,
+            palette,
+            flip_horizontal,
+            flip_vertical,
+            behind_background: attributes & 0x20 != 0,
+            image,
+        }
+    }
+}
+
+/// 指定したスキャンラインに入るたびにスナップショットを取り直す。
+#[derive(Debug, Clone)]
+pub(crate) struct PpuCapture {
+    scanline: u16,
+    last_scanline: Option<u16>,
+    snapshot: Option<PpuSnapshot>,
+}
+
+impl PpuCapture {
+    pub(crate) fn new(scanline: u16) -> Self {
+        Self {
+            scanline,
+            last_scanline: None,
+            snapshot: None,
+        }
+    }
+
+    pub(crate) fn scanline(&self) -> u16 {
+        self.scanline
+    }
+
+    /// 1 サイクル進めた後に今のスキャンラインを渡す。指定したラインに入ったサイクルなら true。
+    pub(crate) fn entered(&mut self, scanline: u16) -> bool {
+        let entered = scanline == self.scanline && self.last_scanline != Some(scanline);
+        self.last_scanline = Some(scanline);
+        entered
+    }
+
+    pub(crate) fn store(&mut self, snapshot: PpuSnapshot) {
+        self.snapshot = Some(snapshot);
+    }
+
+    pub(crate) fn snapshot(&self) -> Option<&PpuSnapshot> {
+        self.snapshot.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank_snapshot() -> PpuSnapshot {
+        PpuSnapshot {
+            scanline: 241,
+            frame: 0,
+            nametables: Box::new([0; 0x1000]),
+            pattern_tables: Box::new([0; 0x2000]),
+            palette: [0; 32],
+            oam: [0; 256],
+            background_table: false,
+            sprite_table: false,
+            tall_sprites: false,
+            scroll_address: 0,
+            fine_x: 0,
+        }
+    }
+
+    #[test]
+    fn nametable_tiles_use_attribute_palettes() {
+        let mut snapshot = blank_snapshot();
+        // タイル 1 は全面が色 3
+        snapshot.pattern_tables[16..32].fill(0xFF);
+        snapshot.palette[3] = 0x16;
+        snapshot.palette[11] = 0x2A;
+        // 2 面目 ($2400) の (2, 0) にタイル 1、その 16x16 区画は右上なのでパレット 2
+        snapshot.nametables[0x400 + 2] = 1;
+        snapshot.nametables[0x400 + 0x3C0] = 0b0000_1000;
+
+        let view = snapshot.video_memory_view(0);
+
+        assert_eq!(view.tile_maps.pixel(256 + 16, 0), 0x2A);
+        assert_eq!(view.tile_maps.pixel(256 + 23, 7), 0x2A);
+        assert_eq!(view.tile_maps.pixel(256 + 24, 0), 0);
+        assert_eq!(view.tile_maps.pixel(16, 0), 0);
+    }
+
+    #[test]
+    fn scroll_follows_the_t_register_and_fine_x() {
+        let mut snapshot = blank_snapshot();
+        // fine Y 5, ネームテーブル 3, coarse Y 4, coarse X 10
+        snapshot.scroll_address = (5 << 12) | (3 << 10) | (4 << 5) | 10;
+        snapshot.fine_x = 3;
+
+        let scroll = snapshot.video_memory_view(0).scroll;
+
+        assert_eq!(
+            scroll,
+            ViewRect {
+                x: 256 + 83,
+                y: 240 + 37,
+                width: 256,
+                height: 240,
+            }
+        );
+    }
+
+    #[test]
+    fn sprites_apply_flips_and_tall_tile_pairs() {
+        let mut snapshot = blank_snapshot();
+        snapshot.tall_sprites = true;
+        // $1000 側のタイル 4 の 1 行目の左端だけ色 1、タイル 5 は全面が色 2
+        snapshot.pattern_tables[0x1000 + 4 * 16] = 0x80;
+        snapshot.pattern_tables[0x1000 + 5 * 16 + 8..0x1000 + 6 * 16].fill(0xFF);
+        snapshot.palette[0x15] = 0x21;
+        snapshot.palette[0x16] = 0x22;
+        snapshot.oam[4..8].copy_from_slice(&[0x40, 0x05, 0b1110_0001, 0x80]);
+
+        let sprite = &snapshot.video_memory_view(0).sprites[1];
+
+        assert_eq!(
+            (sprite.x, sprite.y, sprite.tile, sprite.palette),
+            (0x80, 0x40, 5, 1)
+        );
+        assert!(sprite.flip_horizontal && sprite.flip_vertical && sprite.behind_background);
+        assert_eq!((sprite.image.width, sprite.image.height), (8, 16));
+        // 上下反転で下半分のタイルが上に来て、元の左上の画素は右下に移る
+        assert_eq!(sprite.image.pixel(0, 0), 0x22);
+        assert_eq!(sprite.image.pixel(7, 15), 0x21);
+        assert_eq!(sprite.image.pixel(0, 15), 0);
+    }
+
+    #[test]
+    fn capture_fires_once_per_entry_into_the_scanline() {
+        let mut capture = PpuCapture::new(241);
+
+        assert!(!capture.entered(240));
+        assert!(capture.entered(241));
+        assert!(!capture.entered(241));
+        assert!(!capture.entered(242));
+        assert!(capture.entered(241));
+    }
+}
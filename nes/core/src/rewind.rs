@@ -0,0 +1,46 @@
+//! 巻き戻し用の固定長スナップショット。
+//!
+//! レイアウトは `[payload 長 (u32 LE)][MachineStatePayload][0 埋め]`。
+//! MessagePack の整数は値によって符号長が変わるため、ロード直後の状態長に
+//! 余裕を持たせた長さを枠として確保する。
+
+use crate::{Core, Error, persistence_error::PersistenceError};
+
+const LEN_BYTES: usize = 4;
+const HEADROOM_BYTES: usize = 4096;
+
+/// ロード直後の状態から 1 スナップショットあたりの固定長を求める。
+pub(crate) fn slot_size(core: &Core) -> Result<usize, Error> {
+    let payload_len = core.export_machine_state()?.len();
+    Ok(LEN_BYTES + payload_len + payload_len / 4 + HEADROOM_BYTES)
+}
+
+/// `buf` に現在の状態を書き込む。枠に収まらない場合は `buf` に触れずにエラーを返す。
+pub(crate) fn save(core: &Core, buf: &mut [u8]) -> Result<(), Error> {
+    let (len, body) = buf
+        .split_at_mut_checked(LEN_BYTES)
+        .ok_or_else(|| PersistenceError::Validation("rewind snapshot truncated".into()))?;
+    let payload = core.export_machine_state()?;
+    if payload.len() > body.len() {
+        return Err(PersistenceError::Validation(format!(
+            "rewind snapshot overflow: {} > {}",
+            payload.len(),
+            body.len()
+        ))
+        .into());
+    }
+    body[..payload.len()].copy_from_slice(&payload);
+    len.copy_from_slice(&(payload.len() as u32).to_le_bytes());
+    Ok(())
+}
+
+pub(crate) fn restore(core: &mut Core, buf: &[u8]) -> Result<(), Error> {
+    let (len, body) = buf
+        .split_at_checked(LEN_BYTES)
+        .ok_or_else(|| PersistenceError::Validation("rewind snapshot truncated".into()))?;
+    let len = u32::from_le_bytes(len.try_into().expect("length prefix is 4 bytes")) as usize;
+    if len == 0 || len > body.len() {
+        return Err(PersistenceError::Validation("rewind snapshot is empty".into()).into());
+    }
+    core.import_machine_state(&body[..len])
+}
@@ -2,6 +2,10 @@
 pub enum RomFormat {
     INes,
     Nes20,
+    /// Famicom Disk System disk image (`.fds`, with or without the fwNES header).
+    Fds,
+    /// NES Sound Format music file (`.nsf` / `.nsfe`).
+    Nsf,
 }
 
 impl RomFormat {
@@ -9,6 +13,8 @@ impl RomFormat {
         match self {
             Self::INes => "iNES",
             Self::Nes20 => "NES 2.0",
+            Self::Fds => "FDS",
+            Self::Nsf => "NSF",
         }
     }
 }
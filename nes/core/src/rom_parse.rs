@@ -1,12 +1,26 @@
 use std::cmp;
 
 use crate::{
-    cartridge_data_parts::CartridgeDataParts, cartridge_error::CartridgeError,
-    cartridge_rom::CartridgeData, mirror::MirrorMode, rom_format::RomFormat,
+    cartridge_data_parts::CartridgeDataParts,
+    cartridge_error::CartridgeError,
+    cartridge_rom::{CartridgeData, FDS_DISK_SIDE_LEN},
+    mirror::MirrorMode,
+    nsf_info::{NSF_DEFAULT_NTSC_SPEED, NSF_DEFAULT_PAL_SPEED, NsfChips, NsfInfo},
+    rom_format::RomFormat,
+    status::console_type::ConsoleType,
 };
 
-/// Raw ROM バイト列をパースして CartridgeData を生成する。
-/// iNES または NES 2.0 を自動判別する。
+/// NES 2.0 で FDS に予約されているマッパー番号。
+const FDS_MAPPER_TYPE: u16 = 20;
+const FDS_HEADER_MAGIC: &[u8] = b"FDS\x1A";
+/// 各面の先頭にあるディスク情報ブロック。ヘッダなしイメージの判別にも使う。
+const FDS_DISK_INFO_MAGIC: &[u8] = b"\x01*NINTENDO-HVC*";
+const NSF_MAGIC: &[u8] = b"NESM\x1A";
+const NSFE_MAGIC: &[u8] = b"NSFE";
+const NSF_HEADER_LEN: usize = 0x80;
+/// NSF の 4KiB バンク。
+const NSF_BANK_LEN: usize = 0x1000;
+
 type RomChunks = (Vec<u8>, Vec<u8>, Vec<u8>);
 
 fn extract_chunks(
@@ -47,10 +61,21 @@ fn extract_chunks(
     Ok((trainer, prog_rom, char_rom))
 }
 
+/// Raw ROM バイト列をパースして CartridgeData を生成する。
+/// iNES / NES 2.0 / FDS (fwNES ヘッダの有無は問わない) / NSF / NSFe を自動判別する。
 pub fn parse_rom(data: &[u8]) -> Result<CartridgeData, CartridgeError> {
     if data.len() < 16 {
         return Err(CartridgeError::UnexpectedEof);
     }
+    if data.starts_with(FDS_HEADER_MAGIC) || data.starts_with(FDS_DISK_INFO_MAGIC) {
+        return parse_fds(data);
+    }
+    if data.starts_with(NSF_MAGIC) {
+        return parse_nsf(data);
+    }
+    if data.starts_with(NSFE_MAGIC) {
+        return parse_nsfe(data);
+    }
     if data[0] != 0x4E || data[1] != 0x45 || data[2] != 0x53 || data[3] != 0x1A {
         return Err(CartridgeError::DataError);
     }
@@ -92,6 +117,7 @@ fn parse_ines(data: &[u8]) -> Result<CartridgeData, CartridgeError> {
         has_battery,
         sub_mapper_type: 0,
         trainer,
+        console_type: None,
     })
 }
 
@@ -141,6 +167,7 @@ fn parse_nes20(data: &[u8]) -> Result<CartridgeData, CartridgeError> {
     let mirror_mode = MirrorMode::try_from(mirror_bits).map_err(|_| CartridgeError::DataError)?;
     let has_battery = (flags1 & 2) == 2;
     let has_trainer = (flags1 & 4) == 4;
+    let console_type = ConsoleType::from_nes20_timing(data[12]);
 
     let (trainer, prog_rom, char_rom) =
         extract_chunks(data, prom_length, crom_length, has_trainer)?;
@@ -158,5 +185,378 @@ fn parse_nes20(data: &[u8]) -> Result<CartridgeData, CartridgeError> {
         has_battery,
         sub_mapper_type,
         trainer,
+        console_type,
+    })
+}
+
+fn parse_fds(data: &[u8]) -> Result<CartridgeData, CartridgeError> {
+    let (side_count, body) = if data.starts_with(FDS_HEADER_MAGIC) {
+        let body = data.get(16..).ok_or(CartridgeError::UnexpectedEof)?;
+        (usize::from(data[4]), body)
+    } else {
+        (data.len() / FDS_DISK_SIDE_LEN, data)
+    };
+    if side_count == 0 {
+        return Err(CartridgeError::DataError);
+    }
+    let disks = body
+        .get(..side_count * FDS_DISK_SIDE_LEN)
+        .ok_or(CartridgeError::UnexpectedEof)?;
+    if disks
+        .chunks(FDS_DISK_SIDE_LEN)
+        .any(|side| !side.starts_with(FDS_DISK_INFO_MAGIC))
+    {
+        return Err(CartridgeError::DataError);
+    }
+
+    CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::Fds,
+        prog_rom: disks.to_vec(),
+        char_rom: Vec::new(),
+        // RAM アダプタの 32KiB PRG-RAM と 8KiB CHR-RAM
+        pram_length: 0x8000,
+        save_pram_length: 0,
+        vram_length: 0x2000,
+        save_vram_length: 0,
+        mapper_type: FDS_MAPPER_TYPE,
+        mirror_mode: MirrorMode::Horizontal,
+        has_battery: false,
+        sub_mapper_type: 0,
+        trainer: Vec::new(),
+        console_type: None,
     })
 }
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, CartridgeError> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .ok_or(CartridgeError::UnexpectedEof)
+}
+
+/// NUL 終端の文字列を読む。空文字列と NSF の "<?>" は未設定として扱う。
+fn nsf_string(bytes: &[u8]) -> Option<String> {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let value = String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+    (!value.is_empty() && value != "<?>").then_some(value)
+}
+
+fn nsf_strings(bytes: &[u8]) -> Vec<String> {
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    bytes
+        .split(|&b| b == 0)
+        .map(|s| String::from_utf8_lossy(s).trim().to_string())
+        .collect()
+}
+
+fn nsf_console_type(region: u8) -> Option<ConsoleType> {
+    // bit1 が立っていれば両対応。PAL 専用の場合のみ地域を固定する
+    (region & 0x03 == 0x01).then_some(ConsoleType::Pal)
+}
+
+fn nsf_speed(value: u16, default: u16) -> u16 {
+    if value == 0 { default } else { value }
+}
+
+fn parse_nsf(data: &[u8]) -> Result<CartridgeData, CartridgeError> {
+    let header = data
+        .get(..NSF_HEADER_LEN)
+        .ok_or(CartridgeError::UnexpectedEof)?;
+    // NSF2 はヘッダ末尾にプログラム長を持ち、その後ろにメタデータが続く
+    let program_len = usize::from(header[0x7D])
+        | (usize::from(header[0x7E]) << 8)
+        | (usize::from(header[0x7F]) << 16);
+    let body = &data[NSF_HEADER_LEN..];
+    let program = if header[5] >= 2 && program_len > 0 {
+        body.get(..program_len)
+            .ok_or(CartridgeError::UnexpectedEof)?
+    } else {
+        body
+    };
+    let mut bank_init = [0; 8];
+    bank_init.copy_from_slice(&header[0x70..0x78]);
+    let info = NsfInfo {
+        load_address: read_u16(header, 0x08)?,
+        init_address: read_u16(header, 0x0A)?,
+        play_address: read_u16(header, 0x0C)?,
+        bank_init: bank_init.iter().any(|&b| b != 0).then_some(bank_init),
+        song_count: header[6],
+        first_song: header[7].saturating_sub(1),
+        ntsc_speed: nsf_speed(read_u16(header, 0x6E)?, NSF_DEFAULT_NTSC_SPEED),
+        pal_speed: nsf_speed(read_u16(header, 0x78)?, NSF_DEFAULT_PAL_SPEED),
+        chips: NsfChips::from_bits_truncate(header[0x7B]),
+        title: nsf_string(&header[0x0E..0x2E]),
+        artist: nsf_string(&header[0x2E..0x4E]),
+        copyright: nsf_string(&header[0x4E..0x6E]),
+        track_labels: Vec::new(),
+        track_durations: Vec::new(),
+    };
+    nsf_cartridge_data(program, info, nsf_console_type(header[0x7A]))
+}
+
+fn parse_nsfe(data: &[u8]) -> Result<CartridgeData, CartridgeError> {
+    let mut info = None;
+    let mut program = None;
+    let mut bank_init = None;
+    let mut speeds = None;
+    let mut strings = Vec::new();
+    let mut track_labels = Vec::new();
+    let mut track_durations = Vec::new();
+    let mut region = 0;
+    let mut offset = NSFE_MAGIC.len();
+    loop {
+        let len = data
+            .get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .ok_or(CartridgeError::UnexpectedEof)?;
+        let id = data
+            .get(offset + 4..offset + 8)
+            .ok_or(CartridgeError::UnexpectedEof)?;
+        let chunk = data
+            .get(offset + 8..offset + 8 + len)
+            .ok_or(CartridgeError::UnexpectedEof)?;
+        offset += 8 + len;
+        match id {
+            b"INFO" => {
+                if chunk.len() < 8 {
+                    return Err(CartridgeError::DataError);
+                }
+                region = chunk[6];
+                info = Some((
+                    read_u16(chunk, 0)?,
+                    read_u16(chunk, 2)?,
+                    read_u16(chunk, 4)?,
+                    NsfChips::from_bits_truncate(chunk[7]),
+                    chunk.get(8).copied().unwrap_or(1),
+                    chunk.get(9).copied().unwrap_or(0),
+                ));
+            }
+            b"DATA" => program = Some(chunk),
+            b"BANK" => {
+                let mut banks = [0; 8];
+                let n = chunk.len().min(8);
+                banks[..n].copy_from_slice(&chunk[..n]);
+                bank_init = Some(banks);
+            }
+            b"RATE" => speeds = Some((read_u16(chunk, 0)?, read_u16(chunk, 2).ok())),
+            b"auth" => strings = nsf_strings(chunk),
+            b"tlbl" => track_labels = nsf_strings(chunk),
+            b"time" => {
+                track_durations = chunk
+                    .chunks_exact(4)
+                    .map(|b| {
+                        let ms = i32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                        u32::try_from(ms).ok()
+                    })
+                    .collect();
+            }
+            b"NEND" => break,
+            // 先頭が大文字のチャンクは解釈必須
+            [first, ..] if first.is_ascii_uppercase() => {
+                log::error!("unsupported NSFe chunk: {}", String::from_utf8_lossy(id));
+                return Err(CartridgeError::DataError);
+            }
+            _ => {}
+        }
+    }
+    let (load_address, init_address, play_address, chips, song_count, first_song) =
+        info.ok_or(CartridgeError::DataError)?;
+    let program = program.ok_or(CartridgeError::DataError)?;
+    let (ntsc_speed, pal_speed) = speeds.unwrap_or((0, None));
+    let mut strings = strings.into_iter().map(|s| (!s.is_empty()).then_some(s));
+    let info = NsfInfo {
+        load_address,
+        init_address,
+        play_address,
+        bank_init,
+        song_count,
+        first_song,
+        ntsc_speed: nsf_speed(ntsc_speed, NSF_DEFAULT_NTSC_SPEED),
+        pal_speed: nsf_speed(pal_speed.unwrap_or(0), NSF_DEFAULT_PAL_SPEED),
+        chips,
+        title: strings.next().flatten(),
+        artist: strings.next().flatten(),
+        copyright: strings.next().flatten(),
+        track_labels,
+        track_durations,
+    };
+    nsf_cartridge_data(program, info, nsf_console_type(region))
+}
+
+fn nsf_cartridge_data(
+    program: &[u8],
+    info: NsfInfo,
+    console_type: Option<ConsoleType>,
+) -> Result<CartridgeData, CartridgeError> {
+    let lowest_address = if info.chips.contains(NsfChips::FDS) {
+        0x6000
+    } else {
+        0x8000
+    };
+    if info.song_count == 0
+        || program.is_empty()
+        || (info.bank_init.is_none() && usize::from(info.load_address) < lowest_address)
+    {
+        return Err(CartridgeError::DataError);
+    }
+    // ロードアドレスの 4KiB 境界からの端数だけ前を埋め、全体を 4KiB 単位に揃える
+    let padding = usize::from(info.load_address) & (NSF_BANK_LEN - 1);
+    let mut prog_rom = vec![0; padding];
+    prog_rom.extend_from_slice(program);
+    prog_rom.resize(prog_rom.len().next_multiple_of(NSF_BANK_LEN).max(0x4000), 0);
+
+    let mut data = CartridgeData::new(CartridgeDataParts {
+        format: RomFormat::Nsf,
+        prog_rom,
+        char_rom: Vec::new(),
+        pram_length: 0,
+        save_pram_length: 0,
+        vram_length: 0x2000,
+        save_vram_length: 0,
+        mapper_type: 0,
+        mirror_mode: MirrorMode::Vertical,
+        has_battery: false,
+        sub_mapper_type: 0,
+        trainer: Vec::new(),
+        console_type,
+    })?;
+    data.set_nsf(info);
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_rom;
+    use crate::{
+        cartridge_rom::FDS_DISK_SIDE_LEN, nsf_info::NsfChips, rom_format::RomFormat,
+        status::console_type::ConsoleType,
+    };
+
+    fn rom_with_header(flags2: u8, timing: u8) -> Vec<u8> {
+        let mut rom = vec![
+            0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01, 0x00, flags2, 0x00, 0x00, 0x00, 0x00, timing, 0x00,
+            0x00, 0x00,
+        ];
+        rom.resize(16 + 0x4000 + 0x2000, 0);
+        rom
+    }
+
+    #[test]
+    fn nes20_timing_byte_selects_console_type() {
+        let ntsc = parse_rom(&rom_with_header(0x08, 0x00)).expect("NTSC ROM should parse");
+        let pal = parse_rom(&rom_with_header(0x08, 0x01)).expect("PAL ROM should parse");
+        let multi = parse_rom(&rom_with_header(0x08, 0x02)).expect("multi-region should parse");
+        let dendy = parse_rom(&rom_with_header(0x08, 0x03)).expect("Dendy ROM should parse");
+
+        assert_eq!(ntsc.console_type(), Some(ConsoleType::Ntsc));
+        assert_eq!(pal.console_type(), Some(ConsoleType::Pal));
+        assert_eq!(multi.console_type(), None);
+        assert_eq!(dendy.console_type(), Some(ConsoleType::Dendy));
+    }
+
+    #[test]
+    fn ines_header_leaves_console_type_unspecified() {
+        let data = parse_rom(&rom_with_header(0x00, 0x01)).expect("iNES ROM should parse");
+
+        assert_eq!(data.console_type(), None);
+    }
+
+    fn fds_side() -> Vec<u8> {
+        let mut side = b"\x01*NINTENDO-HVC*".to_vec();
+        side.resize(FDS_DISK_SIDE_LEN, 0);
+        side
+    }
+
+    #[test]
+    fn fds_image_parses_with_and_without_fwnes_header() {
+        let raw = [fds_side(), fds_side()].concat();
+        let mut headered = b"FDS\x1A\x02".to_vec();
+        headered.resize(16, 0);
+        headered.extend_from_slice(&raw);
+
+        for image in [raw, headered] {
+            let data = parse_rom(&image).expect("FDS image should parse");
+            assert_eq!(data.format(), RomFormat::Fds);
+            assert_eq!(data.disk_side_count(), 2);
+        }
+    }
+
+    #[test]
+    fn fds_image_rejects_truncated_or_foreign_sides() {
+        let mut headered = b"FDS\x1A\x02".to_vec();
+        headered.resize(16, 0);
+        headered.extend_from_slice(&fds_side());
+        assert!(parse_rom(&headered).is_err());
+
+        let mut side = fds_side();
+        side[1] = b'?';
+        let mut headered = b"FDS\x1A\x01".to_vec();
+        headered.resize(16, 0);
+        headered.extend_from_slice(&side);
+        assert!(parse_rom(&headered).is_err());
+    }
+
+    #[test]
+    fn nsf_header_places_unbanked_data_at_load_address() {
+        let mut nsf = b"NESM\x1A\x01\x05\x02".to_vec();
+        nsf.resize(0x80, 0);
+        nsf[0x08..0x0E].copy_from_slice(&[0x34, 0x92, 0x00, 0x93, 0x03, 0x93]);
+        nsf[0x0E..0x13].copy_from_slice(b"Title");
+        nsf[0x2E..0x31].copy_from_slice(b"<?>");
+        nsf[0x7A] = 0x01;
+        nsf[0x7B] = 0x0C;
+        nsf.extend_from_slice(&[0xEA; 0x100]);
+
+        let data = parse_rom(&nsf).expect("NSF should parse");
+        let info = data.nsf().expect("NSF info should be set");
+        assert_eq!(data.format(), RomFormat::Nsf);
+        assert_eq!(data.console_type(), Some(ConsoleType::Pal));
+        assert_eq!(info.bank_init, None);
+        assert_eq!(info.song_count, 5);
+        assert_eq!(info.first_song, 1);
+        assert_eq!(info.chips, NsfChips::FDS | NsfChips::MMC5);
+        assert_eq!(info.title.as_deref(), Some("Title"));
+        assert_eq!(info.artist, None);
+        assert_eq!(data.prog_rom()[0x234], 0xEA);
+        assert_eq!(data.prog_rom()[0x233], 0x00);
+    }
+
+    #[test]
+    fn nsfe_chunks_provide_banks_labels_and_durations() {
+        fn chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+            let mut chunk = (body.len() as u32).to_le_bytes().to_vec();
+            chunk.extend_from_slice(id);
+            chunk.extend_from_slice(body);
+            chunk
+        }
+        let nsfe = [
+            b"NSFE".to_vec(),
+            chunk(
+                b"INFO",
+                &[0x00, 0x80, 0x00, 0x80, 0x03, 0x80, 0x02, 0x00, 0x02, 0x00],
+            ),
+            chunk(b"BANK", &[0, 1]),
+            chunk(b"DATA", &[0x60; 0x2000]),
+            chunk(b"auth", b"Song\0Composer\0\0Ripper\0"),
+            chunk(b"tlbl", b"Intro\0Ending\0"),
+            chunk(b"time", &[0x10, 0x27, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]),
+            chunk(b"xtra", &[1, 2, 3]),
+            chunk(b"NEND", &[]),
+        ]
+        .concat();
+
+        let data = parse_rom(&nsfe).expect("NSFe should parse");
+        let info = data.nsf().unwrap();
+        assert_eq!(data.console_type(), None);
+        assert_eq!(info.bank_init, Some([0, 1, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(info.title.as_deref(), Some("Song"));
+        assert_eq!(info.artist.as_deref(), Some("Composer"));
+        assert_eq!(info.copyright, None);
+        assert_eq!(info.track_labels, ["Intro", "Ending"]);
+        assert_eq!(info.track_durations, [Some(10_000), None]);
+
+        let mut required = nsfe.clone();
+        let end = required.len() - 8;
+        required.splice(end..end, chunk(b"ZZZZ", &[]));
+        assert!(parse_rom(&required).is_err());
+    }
+}
@@ -1,27 +1,162 @@
-pub(crate) enum ConsoleType {
-    Pal,
+// https://www.nesdev.org/wiki/Cycle_reference_chart
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleType {
+    #[default]
     Ntsc,
+    Pal,
+    Dendy,
 }
 
 impl ConsoleType {
-    pub fn nmi_timing_at_scan_line(&self) -> usize {
+    /// NES 2.0 ヘッダ byte 12 (CPU/PPU timing) から判別する。
+    /// multiple-region (2) は地域を特定できないため `None` を返す。
+    pub fn from_nes20_timing(timing: u8) -> Option<Self> {
+        match timing & 0x03 {
+            0 => Some(ConsoleType::Ntsc),
+            1 => Some(ConsoleType::Pal),
+            3 => Some(ConsoleType::Dendy),
+            _ => None,
+        }
+    }
+
+    pub const fn label(self) -> &'static str {
         match self {
-            ConsoleType::Ntsc => 241,
-            ConsoleType::Pal => 241,
+            ConsoleType::Ntsc => "NTSC",
+            ConsoleType::Pal => "PAL",
+            ConsoleType::Dendy => "Dendy",
         }
     }
 
-    pub fn scan_line_size(&self) -> usize {
+    /// VBlank (NMI) を開始する PPU 内部のスキャンライン番号。
+    /// 内部番号は 0 がプリレンダー、1..=240 が可視領域、241 がポストレンダー。
+    pub(crate) const fn nmi_scan_line(self) -> u16 {
         match self {
-            ConsoleType::Ntsc => 260,
-            ConsoleType::Pal => 310,
+            ConsoleType::Ntsc | ConsoleType::Pal => 242,
+            // Dendy はポストレンダー後 51 ライン待ってから VBlank に入る
+            ConsoleType::Dendy => 292,
         }
     }
 
-    pub fn cpu_clock_rate(&self) -> usize {
+    pub(crate) const fn total_scan_line(self) -> u16 {
+        match self {
+            ConsoleType::Ntsc => 262,
+            ConsoleType::Pal | ConsoleType::Dendy => 312,
+        }
+    }
+
+    /// 奇数フレームでプリレンダーラインの 1 ドットを省略するのは NTSC のみ。
+    pub(crate) const fn skips_odd_frame_dot(self) -> bool {
+        matches!(self, ConsoleType::Ntsc)
+    }
+
+    pub fn cpu_clock_rate(self) -> u64 {
         match self {
             ConsoleType::Ntsc => 1_789_773,
             ConsoleType::Pal => 1_662_607,
+            ConsoleType::Dendy => 1_773_448,
         }
     }
+
+    /// APU のノイズ/DMC 周期表とフレームカウンタに PAL 版を使うか。
+    /// Dendy の APU は NTSC と同じテーブルで動作する。
+    pub(crate) fn uses_pal_apu(self) -> bool {
+        matches!(self, ConsoleType::Pal)
+    }
+
+    /// (PPU ドット数, CPU サイクル数) の比。NTSC/Dendy => 3:1, PAL => 16:5 (3.2)
+    fn ppu_clock_ratio(self) -> (u64, u64) {
+        match self {
+            ConsoleType::Ntsc | ConsoleType::Dendy => (3, 1),
+            ConsoleType::Pal => (16, 5),
+        }
+    }
+
+    /// 端数位相 `phase` から `cpu_cycles` 進めたときに進む PPU ドット数。
+    pub(crate) fn ppu_dots(self, phase: u8, cpu_cycles: u64) -> u64 {
+        let (dots, cpu) = self.ppu_clock_ratio();
+        if cpu == 1 {
+            return cpu_cycles * dots;
+        }
+        let phase = u64::from(phase);
+        (phase + cpu_cycles) * dots / cpu - phase * dots / cpu
+    }
+
+    pub(crate) fn next_ppu_clock_phase(self, phase: u8, cpu_cycles: u64) -> u8 {
+        let (_, cpu) = self.ppu_clock_ratio();
+        ((u64::from(phase) + cpu_cycles) % cpu) as u8
+    }
+
+    /// `ppu_dots(phase, n) <= ppu_dots` を満たす最大の CPU サイクル数 `n`。
+    pub(crate) fn cpu_cycles_within_ppu_dots(self, phase: u8, ppu_dots: u64) -> u64 {
+        let (dots, cpu) = self.ppu_clock_ratio();
+        let phase = u64::from(phase);
+        let base = phase * dots / cpu;
+        ((base + ppu_dots + 1) * cpu - 1) / dots - phase
+    }
+
+    pub(crate) fn validate_ppu_clock_phase(self, phase: u8) -> bool {
+        u64::from(phase) < self.ppu_clock_ratio().1
+    }
+
+    /// 1 秒あたりのフレーム数。NTSC は奇数フレームのドット省略を平均して計算する。
+    pub fn frame_rate(self) -> f64 {
+        let (dots, cpu) = self.ppu_clock_ratio();
+        let frame_dots = f64::from(self.total_scan_line()) * 341.0
+            - if self.skips_odd_frame_dot() { 0.5 } else { 0.0 };
+        self.cpu_clock_rate() as f64 * dots as f64 / cpu as f64 / frame_dots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ConsoleType;
+
+    #[test]
+    fn nes20_timing_byte_maps_to_console_type() {
+        assert_eq!(ConsoleType::from_nes20_timing(0), Some(ConsoleType::Ntsc));
+        assert_eq!(ConsoleType::from_nes20_timing(1), Some(ConsoleType::Pal));
+        assert_eq!(ConsoleType::from_nes20_timing(2), None);
+        assert_eq!(ConsoleType::from_nes20_timing(3), Some(ConsoleType::Dendy));
+        assert_eq!(ConsoleType::from_nes20_timing(0xFD), Some(ConsoleType::Pal));
+    }
+
+    #[test]
+    fn pal_ppu_clock_advances_sixteen_dots_per_five_cpu_cycles() {
+        let console_type = ConsoleType::Pal;
+        let mut phase = 0;
+        let mut dots = Vec::new();
+        for _ in 0..10 {
+            dots.push(console_type.ppu_dots(phase, 1));
+            phase = console_type.next_ppu_clock_phase(phase, 1);
+        }
+
+        assert_eq!(dots, [3, 3, 3, 3, 4, 3, 3, 3, 3, 4]);
+        assert_eq!(phase, 0);
+        assert_eq!(console_type.ppu_dots(0, 5), 16);
+        assert_eq!(console_type.ppu_dots(3, 7), 23);
+    }
+
+    #[test]
+    fn cpu_cycles_within_ppu_dots_is_inverse_of_ppu_dots() {
+        for console_type in [ConsoleType::Ntsc, ConsoleType::Pal, ConsoleType::Dendy] {
+            for phase in 0..5 {
+                if !console_type.validate_ppu_clock_phase(phase) {
+                    continue;
+                }
+                for budget in 0..80 {
+                    let cycles = console_type.cpu_cycles_within_ppu_dots(phase, budget);
+                    assert!(console_type.ppu_dots(phase, cycles) <= budget);
+                    assert!(console_type.ppu_dots(phase, cycles + 1) > budget);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn frame_rate_matches_region_refresh() {
+        assert!((ConsoleType::Ntsc.frame_rate() - 60.0988).abs() < 0.001);
+        assert!((ConsoleType::Pal.frame_rate() - 50.0070).abs() < 0.001);
+        assert!((ConsoleType::Dendy.frame_rate() - 50.0070).abs() < 0.001);
+    }
 }
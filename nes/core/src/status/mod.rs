@@ -0,0 +1 @@
+pub mod console_type;
@@ -0,0 +1,135 @@
+//! CPU 実行トレース。1 命令 1 行を nestest.log (Nintendulator) の形式で出力する。
+//!
+//! ```text
+//! C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7
+//! ```
+//!
+//! PPU と CYC は命令の最初のサイクル (オペコードの読み出し) を始める時点の値。
+
+use std::{
+    collections::VecDeque,
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+use crate::{cart_device::Cartridge as MapperCartridge, cpu::Core as Cpu};
+
+enum TraceOutput {
+    /// 直近 `capacity` 行だけを保持する。
+    Ring {
+        lines: VecDeque<String>,
+        capacity: usize,
+    },
+    Writer(Box<dyn Write + Send>),
+}
+
+/// トレースの出力先。`Core::set_trace_sink` で CPU のステップループに差し込む。
+pub struct TraceSink {
+    output: TraceOutput,
+    line: String,
+    lines_written: u64,
+    // DMA でオペコード読み出しの後に止まっている間は、同じ命令を何度も書かない
+    at_boundary: bool,
+    error: Option<io::Error>,
+}
+
+impl TraceSink {
+    /// 直近 `capacity` 行をメモリに保持する。
+    pub fn ring(capacity: usize) -> Self {
+        Self::new(TraceOutput::Ring {
+            lines: VecDeque::with_capacity(capacity.min(4096)),
+            capacity,
+        })
+    }
+
+    /// 1 行ごとに `writer` へ書く。ファイルなら `BufWriter` で包んで渡す。
+    pub fn writer(writer: impl Write + Send + 'static) -> Self {
+        Self::new(TraceOutput::Writer(Box::new(writer)))
+    }
+
+    fn new(output: TraceOutput) -> Self {
+        Self {
+            output,
+            line: String::with_capacity(96),
+            lines_written: 0,
+            at_boundary: false,
+            error: None,
+        }
+    }
+
+    /// リングバッファに残っている行を古い順に返す。書き出し先が `writer` なら空。
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        let lines = match &self.output {
+            TraceOutput::Ring { lines, .. } => Some(lines.iter().map(String::as_str)),
+            TraceOutput::Writer(_) => None,
+        };
+        lines.into_iter().flatten()
+    }
+
+    /// 捨てた行も含めた、これまでにトレースした命令の数。
+    pub fn lines_written(&self) -> u64 {
+        self.lines_written
+    }
+
+    /// 書き出し先をフラッシュし、途中で起きた最初の書き込みエラーを返す。
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        match &mut self.output {
+            TraceOutput::Ring { .. } => Ok(()),
+            TraceOutput::Writer(writer) => writer.flush(),
+        }
+    }
+
+    /// 1 サイクル進めた後に呼ぶ。命令境界に入ったサイクルなら次の命令を 1 行書く。
+    /// `scanline` と `dot` はそのサイクルを進める前の PPU の位置。
+    pub(crate) fn record(
+        &mut self,
+        cpu: &Cpu,
+        cartridge: &dyn MapperCartridge,
+        scanline: u16,
+        dot: u16,
+    ) {
+        let at_boundary = cpu.boundary_opcode().is_some();
+        let entered = at_boundary && !self.at_boundary;
+        self.at_boundary = at_boundary;
+        if !entered {
+            return;
+        }
+
+        self.line.clear();
+        cpu.write_disassembly(cartridge, &mut self.line);
+        let registers = cpu.registers();
+        let _ = write!(
+            self.line,
+            "A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{scanline:>3},{dot:>3} CYC:{}",
+            registers.a,
+            registers.x,
+            registers.y,
+            registers.p,
+            registers.sp,
+            cpu.cycles().wrapping_sub(1)
+        );
+        self.lines_written += 1;
+
+        match &mut self.output {
+            TraceOutput::Ring { lines, capacity } => {
+                if *capacity == 0 {
+                    return;
+                }
+                if lines.len() == *capacity {
+                    lines.pop_front();
+                }
+                lines.push_back(self.line.clone());
+            }
+            TraceOutput::Writer(writer) => {
+                if self.error.is_none()
+                    && let Err(error) = writeln!(writer, "{}", self.line)
+                {
+                    self.error = Some(error);
+                }
+            }
+        }
+    }
+}
@@ -184,6 +184,103 @@ impl Controller for FamicomPadP2 {
     }
 }
 
+/// Controls of the Famicom's built-in controller I.
+pub(crate) const FAMICOM_P1_CONTROLS: &[ControlInfo] = &[
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.a"),
+        label: "A",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Button1),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.b"),
+        label: "B",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Button2),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.select"),
+        label: "Select",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Select),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.start"),
+        label: "Start",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Start),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.up"),
+        label: "Up",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadUp),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.down"),
+        label: "Down",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadDown),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.left"),
+        label: "Left",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadLeft),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.right"),
+        label: "Right",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadRight),
+    },
+];
+/// Controls of the built-in controller II; its microphone is reported on $4016.
+pub(crate) const FAMICOM_P2_CONTROLS: &[ControlInfo] = &[
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.a"),
+        label: "A",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Button1),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.b"),
+        label: "B",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Button2),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("famicom.microphone"),
+        label: "Microphone",
+        kind: ControlKind::Digital,
+        abstract_key: None,
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.up"),
+        label: "Up",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadUp),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.down"),
+        label: "Down",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadDown),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.left"),
+        label: "Left",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadLeft),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.right"),
+        label: "Right",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadRight),
+    },
+];
+
 #[derive(Debug)]
 pub struct FamicomSetProfile;
 
@@ -203,102 +300,7 @@ impl ControllerProfile for FamicomSetProfile {
         SETS
     }
     fn port_groups(&self) -> &[&[ControlInfo]] {
-        use ControlKind::*;
-        const P1: &[ControlInfo] = &[
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.a"),
-                label: "A",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Button1),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.b"),
-                label: "B",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Button2),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.select"),
-                label: "Select",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Select),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.start"),
-                label: "Start",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Start),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.up"),
-                label: "Up",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadUp),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.down"),
-                label: "Down",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadDown),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.left"),
-                label: "Left",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadLeft),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.right"),
-                label: "Right",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadRight),
-            },
-        ];
-        const P2: &[ControlInfo] = &[
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.a"),
-                label: "A",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Button1),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.b"),
-                label: "B",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Button2),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("famicom.microphone"),
-                label: "Microphone",
-                kind: Digital,
-                abstract_key: None,
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.up"),
-                label: "Up",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadUp),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.down"),
-                label: "Down",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadDown),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.left"),
-                label: "Left",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadLeft),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.right"),
-                label: "Right",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadRight),
-            },
-        ];
-        const G: &[&[ControlInfo]] = &[P1, P2];
+        const G: &[&[ControlInfo]] = &[FAMICOM_P1_CONTROLS, FAMICOM_P2_CONTROLS];
         G
     }
 
@@ -0,0 +1,315 @@
+use nerust_input_traits::{
+    AttachmentId, ControlInfo, Controller, ControllerProfile, DigitalControlId, OpenBusReadResult,
+    Port, PortSet, ProfileId,
+};
+
+use crate::{
+    famicom_set::{FAMICOM_P1_CONTROLS, FAMICOM_P2_CONTROLS, FamicomPadP1, FamicomPadP2},
+    standard_pad::STANDARD_PAD_CONTROLS,
+};
+
+const PLAYER1: AttachmentId = AttachmentId::new("nes.attachment.player1");
+const PLAYER2: AttachmentId = AttachmentId::new("nes.attachment.player2");
+const PLAYER3: AttachmentId = AttachmentId::new("nes.attachment.player3");
+const PLAYER4: AttachmentId = AttachmentId::new("nes.attachment.player4");
+
+/// Players 3/4 の入力バッファ上の位置 (field index, state byte)
+const PLAYER3_FIELDS: (usize, usize) = (19, 6);
+const PLAYER4_FIELDS: (usize, usize) = (27, 7);
+
+/// Serial stream of the pad behind a port: 8 buttons, 8 filler bits and an
+/// 8-bit adapter signature. Reads past the 24th bit return 1.
+///
+/// The signature is stored in shift order, first read in bit 0. Games collect
+/// it first read into bit 7, so `0x08` and `0x04` show up as `$10` and `$20`.
+#[derive(Debug, Clone)]
+struct FourPlayerStream {
+    state_byte: usize,
+    signature: u8,
+    cached: u32,
+    result: u32,
+    strobe: bool,
+}
+
+impl FourPlayerStream {
+    fn new(state_byte: usize, signature: u8) -> Self {
+        Self {
+            state_byte,
+            signature,
+            cached: u32::from(signature) << 16,
+            result: 0,
+            strobe: false,
+        }
+    }
+
+    /// `bits` are the first 16 bits shifted out before the signature.
+    fn sync_input(&mut self, bits: u16) {
+        self.cached = u32::from(bits) | u32::from(self.signature) << 16;
+    }
+
+    fn read(&mut self) -> u8 {
+        if self.strobe {
+            (self.cached & 1) as u8
+        } else {
+            let b = (self.result & 1) as u8;
+            self.result = self.result >> 1 | 0x80_0000;
+            b
+        }
+    }
+
+    fn write(&mut self, value: u8) {
+        let new_strobe = value & 1 == 1;
+        if self.strobe && !new_strobe {
+            self.result = self.cached;
+        }
+        self.strobe = new_strobe;
+    }
+}
+
+fn pad_field_map(
+    attachment: AttachmentId,
+    base: usize,
+) -> Vec<(AttachmentId, DigitalControlId, usize)> {
+    STANDARD_PAD_CONTROLS
+        .iter()
+        .enumerate()
+        .map(|(bit, control)| (attachment, control.id, base + bit))
+        .collect()
+}
+
+/// NES Four Score: $4016 shifts out P1 then P3, $4017 shifts out P2 then P4,
+/// each followed by the adapter signature on D0.
+#[derive(Debug, Clone)]
+pub struct FourScore {
+    primary_byte: usize,
+    expansion: AttachmentId,
+    expansion_base: usize,
+    stream: FourPlayerStream,
+}
+
+impl FourScore {
+    /// Half of the adapter wired to $4016 (P1 and P3).
+    pub fn port1() -> Self {
+        Self {
+            primary_byte: 0,
+            expansion: PLAYER3,
+            expansion_base: PLAYER3_FIELDS.0,
+            stream: FourPlayerStream::new(PLAYER3_FIELDS.1, 0x08),
+        }
+    }
+
+    /// Half of the adapter wired to $4017 (P2 and P4).
+    pub fn port2() -> Self {
+        Self {
+            primary_byte: 1,
+            expansion: PLAYER4,
+            expansion_base: PLAYER4_FIELDS.0,
+            stream: FourPlayerStream::new(PLAYER4_FIELDS.1, 0x04),
+        }
+    }
+
+    pub fn reset_runtime(&mut self) {
+        self.stream.result = 0;
+        self.stream.strobe = false;
+    }
+}
+
+impl Controller for FourScore {
+    fn sync_input(&mut self, state: &[u8]) {
+        if let (Some(&primary), Some(&expansion)) = (
+            state.get(self.primary_byte),
+            state.get(self.stream.state_byte),
+        ) {
+            // 1 本のシフトレジスタに 2 人分を連結し、その後に署名が続く
+            self.stream
+                .sync_input(u16::from(primary) | u16::from(expansion) << 8);
+        }
+    }
+    fn read(&mut self, _port: &dyn Port) -> OpenBusReadResult {
+        OpenBusReadResult::new(self.stream.read(), 0x1F)
+    }
+    fn write(&mut self, _port: &dyn Port, value: u8) {
+        self.stream.write(value);
+    }
+    fn field_map(&self, port: &dyn Port) -> Vec<(AttachmentId, DigitalControlId, usize)> {
+        let mut map = pad_field_map(port.as_attachment_id(), port.index() * 8);
+        map.extend(pad_field_map(self.expansion, self.expansion_base));
+        map
+    }
+}
+
+#[derive(Debug)]
+pub struct FourScoreProfile;
+
+impl ControllerProfile for FourScoreProfile {
+    fn profile_id(&self) -> ProfileId {
+        ProfileId::new("nes.four_score")
+    }
+    fn label(&self) -> &'static str {
+        "NES Four Score"
+    }
+    fn port_sets(&self) -> &[PortSet] {
+        const PORTS: &[AttachmentId] = &[PLAYER1, PLAYER2, PLAYER3, PLAYER4];
+        const SETS: &[PortSet] = &[PortSet { ports: PORTS }];
+        SETS
+    }
+    fn port_groups(&self) -> &[&[ControlInfo]] {
+        const G: &[&[ControlInfo]] = &[
+            STANDARD_PAD_CONTROLS,
+            STANDARD_PAD_CONTROLS,
+            STANDARD_PAD_CONTROLS,
+            STANDARD_PAD_CONTROLS,
+        ];
+        G
+    }
+}
+
+/// Hori-style Famicom 4 players adapter in 4-player mode: the built-in pads
+/// stay on D0 and the pads plugged into the adapter are shifted out on D1,
+/// followed by the adapter signature.
+#[derive(Debug)]
+pub struct HoriAdapter {
+    pad: Box<dyn Controller + Send>,
+    expansion: AttachmentId,
+    expansion_base: usize,
+    stream: FourPlayerStream,
+}
+
+impl HoriAdapter {
+    /// $4016: built-in controller I (with its D2 microphone) and P3.
+    pub fn port1() -> Self {
+        Self {
+            pad: Box::new(FamicomPadP1::new()),
+            expansion: PLAYER3,
+            expansion_base: PLAYER3_FIELDS.0,
+            stream: FourPlayerStream::new(PLAYER3_FIELDS.1, 0x04),
+        }
+    }
+
+    /// $4017: built-in controller II and P4.
+    pub fn port2() -> Self {
+        Self {
+            pad: Box::new(FamicomPadP2::new()),
+            expansion: PLAYER4,
+            expansion_base: PLAYER4_FIELDS.0,
+            stream: FourPlayerStream::new(PLAYER4_FIELDS.1, 0x08),
+        }
+    }
+}
+
+impl Controller for HoriAdapter {
+    fn sync_input(&mut self, state: &[u8]) {
+        self.pad.sync_input(state);
+        if let Some(&buttons) = state.get(self.stream.state_byte) {
+            self.stream.sync_input(u16::from(buttons));
+        }
+    }
+    fn read(&mut self, port: &dyn Port) -> OpenBusReadResult {
+        let pad = self.pad.read(port);
+        OpenBusReadResult::new(pad.data | self.stream.read() << 1, pad.mask | 0x02)
+    }
+    fn write(&mut self, port: &dyn Port, value: u8) {
+        self.pad.write(port, value);
+        self.stream.write(value);
+    }
+    fn field_map(&self, port: &dyn Port) -> Vec<(AttachmentId, DigitalControlId, usize)> {
+        let mut map = self.pad.field_map(port);
+        map.extend(pad_field_map(self.expansion, self.expansion_base));
+        map
+    }
+}
+
+#[derive(Debug)]
+pub struct HoriAdapterProfile;
+
+impl ControllerProfile for HoriAdapterProfile {
+    fn profile_id(&self) -> ProfileId {
+        ProfileId::new("nes.famicom_four_player")
+    }
+    fn label(&self) -> &'static str {
+        "Famicom 4 Players Adapter (Hori)"
+    }
+    fn port_sets(&self) -> &[PortSet] {
+        const PORTS: &[AttachmentId] = &[PLAYER1, PLAYER2, PLAYER3, PLAYER4];
+        const SETS: &[PortSet] = &[PortSet { ports: PORTS }];
+        SETS
+    }
+    fn port_groups(&self) -> &[&[ControlInfo]] {
+        const G: &[&[ControlInfo]] = &[
+            FAMICOM_P1_CONTROLS,
+            FAMICOM_P2_CONTROLS,
+            STANDARD_PAD_CONTROLS,
+            STANDARD_PAD_CONTROLS,
+        ];
+        G
+    }
+
+    fn device_kind_for_group(&self, group_index: usize) -> &'static str {
+        match group_index {
+            0 => "nes.famicom",
+            1 => "nes.famicom_p2",
+            _ => "nes.famicom_expansion_pad",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_input_traits::{Controller, SimplePort};
+
+    use super::{FourScore, HoriAdapter};
+
+    const PORT1: SimplePort = SimplePort::new(0, "nes.attachment.player1");
+    const PORT2: SimplePort = SimplePort::new(1, "nes.attachment.player2");
+
+    fn read_bits(device: &mut dyn Controller, port: &SimplePort, bit: u8) -> u32 {
+        device.write(port, 1);
+        device.write(port, 0);
+        (0..24).fold(0, |acc, i| {
+            acc | u32::from(device.read(port).data >> bit & 1) << i
+        })
+    }
+
+    /// 1 始まりで何回目の読み出しに署名の 1 が来るか。
+    fn signature_read(bits: u32) -> u32 {
+        assert_eq!((bits >> 16).count_ones(), 1, "{bits:#08X}");
+        (bits >> 16).trailing_zeros() + 17
+    }
+
+    #[test]
+    fn four_score_shifts_both_pads_then_signature() {
+        let state = [0x81, 0x42, 0, 0, 0, 0, 0x11, 0x24];
+        let mut port1 = FourScore::port1();
+        let mut port2 = FourScore::port2();
+        port1.sync_input(&state);
+        port2.sync_input(&state);
+
+        let bits1 = read_bits(&mut port1, &PORT1, 0);
+        let bits2 = read_bits(&mut port2, &PORT2, 0);
+        assert_eq!(bits1 & 0xFFFF, 0x1181);
+        assert_eq!(bits2 & 0xFFFF, 0x2442);
+        // $4016 は 0,0,0,1,0,0,0,0、$4017 は 0,0,1,0,0,0,0,0
+        assert_eq!(signature_read(bits1), 20);
+        assert_eq!(signature_read(bits2), 19);
+        // 24 bit 以降は 1 を返す
+        assert_eq!(port1.read(&PORT1).data, 1);
+    }
+
+    #[test]
+    fn hori_adapter_reports_expansion_pads_on_d1() {
+        let state = [0x01, 0x02, 0, 0, 0, 0, 0x11, 0x24];
+        let mut port1 = HoriAdapter::port1();
+        let mut port2 = HoriAdapter::port2();
+        port1.sync_input(&state);
+        port2.sync_input(&state);
+
+        let bits1 = read_bits(&mut port1, &PORT1, 1);
+        let bits2 = read_bits(&mut port2, &PORT2, 1);
+        assert_eq!(bits1 & 0xFFFF, 0x0011);
+        assert_eq!(bits2 & 0xFFFF, 0x0024);
+        // Four Score と逆で、$4016 が 19 回目、$4017 が 20 回目
+        assert_eq!(signature_read(bits1), 19);
+        assert_eq!(signature_read(bits2), 20);
+        assert_eq!(read_bits(&mut port1, &PORT1, 0) & 0xFF, 0x01);
+    }
+}
@@ -1,5 +1,7 @@
 pub mod famicom_set;
+pub mod four_score;
 pub mod standard_pad;
+pub mod zapper;
 
 use std::rc::Rc;
 
@@ -9,5 +11,8 @@ pub fn nes_device_controller_profiles() -> Vec<Rc<dyn ControllerProfile>> {
     vec![
         Rc::new(famicom_set::FamicomSetProfile) as Rc<dyn ControllerProfile>,
         Rc::new(standard_pad::StandardPadProfile) as Rc<dyn ControllerProfile>,
+        Rc::new(zapper::ZapperProfile) as Rc<dyn ControllerProfile>,
+        Rc::new(four_score::FourScoreProfile) as Rc<dyn ControllerProfile>,
+        Rc::new(four_score::HoriAdapterProfile) as Rc<dyn ControllerProfile>,
     ]
 }
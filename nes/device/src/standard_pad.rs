@@ -101,6 +101,58 @@ impl Controller for StandardPad {
     }
 }
 
+/// Controls of an 8-button NES pad, shared by every profile that exposes one.
+pub(crate) const STANDARD_PAD_CONTROLS: &[ControlInfo] = &[
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.a"),
+        label: "A",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Button1),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.b"),
+        label: "B",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Button2),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.select"),
+        label: "Select",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Select),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.start"),
+        label: "Start",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::Start),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.up"),
+        label: "Up",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadUp),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.down"),
+        label: "Down",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadDown),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.left"),
+        label: "Left",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadLeft),
+    },
+    ControlInfo {
+        id: DigitalControlId::new("nes.control.right"),
+        label: "Right",
+        kind: ControlKind::Digital,
+        abstract_key: Some(AbstractKey::DpadRight),
+    },
+];
+
 #[derive(Debug)]
 pub struct StandardPadProfile;
 
@@ -118,58 +170,7 @@ impl ControllerProfile for StandardPadProfile {
         SETS
     }
     fn port_groups(&self) -> &[&[ControlInfo]] {
-        use ControlKind::*;
-        const C: &[ControlInfo] = &[
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.a"),
-                label: "A",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Button1),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.b"),
-                label: "B",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Button2),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.select"),
-                label: "Select",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Select),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.start"),
-                label: "Start",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::Start),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.up"),
-                label: "Up",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadUp),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.down"),
-                label: "Down",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadDown),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.left"),
-                label: "Left",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadLeft),
-            },
-            ControlInfo {
-                id: DigitalControlId::new("nes.control.right"),
-                label: "Right",
-                kind: Digital,
-                abstract_key: Some(AbstractKey::DpadRight),
-            },
-        ];
-        const G: &[&[ControlInfo]] = &[C];
+        const G: &[&[ControlInfo]] = &[STANDARD_PAD_CONTROLS];
         G
     }
 }
@@ -0,0 +1,209 @@
+use nerust_input_traits::{
+    AbstractKey, AttachmentId, ControlInfo, ControlKind, Controller, ControllerProfile,
+    DigitalControlId, LightSense, OpenBusReadResult, Port, PortSet, ProfileId,
+};
+
+/// 入力バッファ上の位置 (trigger field, aim field, state offset)。
+/// state は [flags, x, y] の 3 bytes
+const PORT1_FIELDS: (usize, usize, usize) = (17, 18, 3);
+const PORT2_FIELDS: (usize, usize, usize) = (35, 36, 8);
+
+const STATE_TRIGGER: u8 = 0x01;
+const STATE_ON_SCREEN: u8 = 0x02;
+
+/// D3: light sense (0 = light detected), D4: trigger (1 = pulled).
+const LIGHT_NOT_DETECTED: u8 = 0x08;
+const TRIGGER_PULLED: u8 = 0x10;
+const DATA_MASK: u8 = 0x18;
+
+/// Pixels around the aim point seen by the photodiode.
+const SENSE_RADIUS: usize = 2;
+/// The photodiode keeps reporting light for roughly this many scanlines
+/// after the beam has passed the aim point.
+const SENSE_LINES: usize = 24;
+const SENSE_THRESHOLD: f32 = 0.5;
+
+/// NES Zapper light gun. Light is only reported while the beam has just
+/// drawn a bright pixel near the aim point, so it needs a `LightSense` view.
+#[derive(Debug, Clone)]
+pub struct Zapper {
+    fields: (usize, usize, usize),
+    trigger: bool,
+    aim: Option<(usize, usize)>,
+}
+
+impl Zapper {
+    /// Zapper plugged into $4016.
+    pub fn port1() -> Self {
+        Self::with_fields(PORT1_FIELDS)
+    }
+
+    /// Zapper plugged into $4017.
+    pub fn port2() -> Self {
+        Self::with_fields(PORT2_FIELDS)
+    }
+
+    fn with_fields(fields: (usize, usize, usize)) -> Self {
+        Self {
+            fields,
+            trigger: false,
+            aim: None,
+        }
+    }
+
+    fn trigger_bit(&self) -> u8 {
+        if self.trigger { TRIGGER_PULLED } else { 0 }
+    }
+
+    fn detects_light(&self, light: &dyn LightSense) -> bool {
+        let (Some((aim_x, aim_y)), Some((beam_x, beam_y))) = (self.aim, light.beam()) else {
+            return false;
+        };
+        let rows = aim_y.saturating_sub(SENSE_RADIUS)..=aim_y + SENSE_RADIUS;
+        let cols = aim_x.saturating_sub(SENSE_RADIUS)..=aim_x + SENSE_RADIUS;
+        rows.into_iter().any(|y| {
+            let drawn_recently = y <= beam_y && beam_y - y <= SENSE_LINES;
+            drawn_recently
+                && cols.clone().any(|x| {
+                    (y < beam_y || x < beam_x)
+                        && light
+                            .brightness(x, y)
+                            .is_some_and(|brightness| brightness >= SENSE_THRESHOLD)
+                })
+        })
+    }
+}
+
+impl Controller for Zapper {
+    fn sync_input(&mut self, state: &[u8]) {
+        let offset = self.fields.2;
+        if let Some(&[flags, x, y]) = state.get(offset..offset + 3) {
+            self.trigger = flags & STATE_TRIGGER != 0;
+            self.aim = (flags & STATE_ON_SCREEN != 0).then_some((usize::from(x), usize::from(y)));
+        }
+    }
+    fn senses_light(&self) -> bool {
+        true
+    }
+    fn read(&mut self, _port: &dyn Port) -> OpenBusReadResult {
+        OpenBusReadResult::new(LIGHT_NOT_DETECTED | self.trigger_bit(), DATA_MASK)
+    }
+    fn read_with_light(&mut self, _port: &dyn Port, light: &dyn LightSense) -> OpenBusReadResult {
+        let light_bit = if self.detects_light(light) {
+            0
+        } else {
+            LIGHT_NOT_DETECTED
+        };
+        OpenBusReadResult::new(light_bit | self.trigger_bit(), DATA_MASK)
+    }
+    fn write(&mut self, _port: &dyn Port, _value: u8) {}
+    fn field_map(&self, port: &dyn Port) -> Vec<(AttachmentId, DigitalControlId, usize)> {
+        let attachment = port.as_attachment_id();
+        vec![
+            (
+                attachment,
+                DigitalControlId::new("nes.control.zapper.trigger"),
+                self.fields.0,
+            ),
+            (
+                attachment,
+                DigitalControlId::new("nes.control.zapper.aim"),
+                self.fields.1,
+            ),
+        ]
+    }
+}
+
+#[derive(Debug)]
+pub struct ZapperProfile;
+
+impl ControllerProfile for ZapperProfile {
+    fn profile_id(&self) -> ProfileId {
+        ProfileId::new("nes.zapper")
+    }
+    fn label(&self) -> &'static str {
+        "Zapper"
+    }
+    fn port_sets(&self) -> &[PortSet] {
+        const P1: &[AttachmentId] = &[AttachmentId::new("nes.attachment.player1")];
+        const P2: &[AttachmentId] = &[AttachmentId::new("nes.attachment.player2")];
+        const SETS: &[PortSet] = &[PortSet { ports: P1 }, PortSet { ports: P2 }];
+        SETS
+    }
+    fn port_groups(&self) -> &[&[ControlInfo]] {
+        const C: &[ControlInfo] = &[
+            ControlInfo {
+                id: DigitalControlId::new("nes.control.zapper.trigger"),
+                label: "Trigger",
+                kind: ControlKind::Digital,
+                abstract_key: Some(AbstractKey::Button1),
+            },
+            ControlInfo {
+                id: DigitalControlId::new("nes.control.zapper.aim"),
+                label: "Aim",
+                kind: ControlKind::Mouse,
+                abstract_key: None,
+            },
+        ];
+        const G: &[&[ControlInfo]] = &[C];
+        G
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_input_traits::{Controller, LightSense, SimplePort};
+
+    use super::Zapper;
+
+    /// 白い 8x8 の的を (100, 100) に描いた画面。
+    struct Target {
+        beam: Option<(usize, usize)>,
+    }
+
+    impl LightSense for Target {
+        fn beam(&self) -> Option<(usize, usize)> {
+            self.beam
+        }
+        fn brightness(&self, x: usize, y: usize) -> Option<f32> {
+            let lit = (100..108).contains(&x) && (100..108).contains(&y);
+            Some(if lit { 1.0 } else { 0.0 })
+        }
+    }
+
+    fn read(zapper: &mut Zapper, beam: Option<(usize, usize)>) -> u8 {
+        let port = SimplePort::new(1, "nes.attachment.player2");
+        zapper.read_with_light(&port, &Target { beam }).data
+    }
+
+    #[test]
+    fn light_is_sensed_only_after_the_beam_draws_the_target() {
+        let mut zapper = Zapper::port1();
+        zapper.sync_input(&[0, 0, 0, 0x03, 104, 104]);
+
+        assert_eq!(read(&mut zapper, None), 0x18);
+        assert_eq!(read(&mut zapper, Some((0, 90))), 0x18);
+        assert_eq!(read(&mut zapper, Some((0, 105))), 0x10);
+        assert_eq!(read(&mut zapper, Some((0, 200))), 0x18);
+    }
+
+    #[test]
+    fn off_screen_aim_never_senses_light() {
+        let mut zapper = Zapper::port1();
+        zapper.sync_input(&[0, 0, 0, 0x00, 104, 104]);
+
+        assert_eq!(read(&mut zapper, Some((0, 105))), 0x08);
+    }
+
+    #[test]
+    fn each_port_reads_its_own_state() {
+        let state = [0, 0, 0, 0x03, 104, 104, 0, 0, 0x00, 0, 0];
+        let mut port1 = Zapper::port1();
+        let mut port2 = Zapper::port2();
+        port1.sync_input(&state);
+        port2.sync_input(&state);
+
+        assert_eq!(read(&mut port1, Some((0, 105))), 0x10);
+        assert_eq!(read(&mut port2, Some((0, 105))), 0x08);
+    }
+}
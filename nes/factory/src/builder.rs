@@ -8,8 +8,12 @@ use nerust_input_traits::{
     AttachmentId, ControllerCollection, DigitalControlId, EmuInput, GuiInput,
 };
 use nerust_nes_core::console_core::NesConsoleCore;
-use nerust_render_filters::FilterTypeExt;
-use nerust_render_traits::{VideoRenderProfile, filter::FilterType, logical::LogicalSize};
+use nerust_render_filters::{FilterTypeExt, palette::NesPalette};
+use nerust_render_traits::{
+    VideoRenderProfile,
+    filter::{FilterType, PALETTE_TEXTURE_WIDTH},
+    logical::LogicalSize,
+};
 
 pub(crate) fn create_core_and_adapter(
     view: &FactorySettingsView,
@@ -20,8 +24,9 @@ pub(crate) fn create_core_and_adapter(
     controller_collection: ControllerCollection,
 ) -> Result<CoreParts, FactoryError> {
     let filter = crate::settings::filter_type_from_bytes(view.system_config.as_deref());
+    let filter_palette = crate::settings::palette_from_bytes(view.system_config.as_deref());
 
-    let (render_profile, palette) = compute_render_profile(filter);
+    let (render_profile, palette) = compute_render_profile(filter, filter_palette.as_ref());
     let mut speaker = speaker;
     speaker.start();
     let core = NesConsoleCore::new_empty(controller_collection, speaker, emu_input);
@@ -34,13 +39,19 @@ pub(crate) fn create_core_and_adapter(
     })
 }
 
-fn compute_render_profile(filter_type: FilterType) -> (VideoRenderProfile, Box<[u32; 256]>) {
+fn compute_render_profile(
+    filter_type: FilterType,
+    filter_palette: Option<&NesPalette>,
+) -> (
+    VideoRenderProfile,
+    Box<[u32; PALETTE_TEXTURE_WIDTH as usize]>,
+) {
     let source_logical_size = LogicalSize {
         width: 256,
         height: 240,
     };
     let layout = filter_type.layout(source_logical_size);
-    let assets = filter_type.palette_console_video_assets();
+    let assets = filter_type.palette_console_video_assets_with_palette(filter_palette);
     let ntsc_packed_rgba8 = assets
         .packed_ntsc_rgba8()
         .map(|data| data.to_vec().into_boxed_slice());
@@ -51,9 +62,9 @@ fn compute_render_profile(filter_type: FilterType) -> (VideoRenderProfile, Box<[
         frame_format: nerust_render_traits::VideoFrameFormat::Palette,
         ntsc_packed_rgba8,
     };
-    let mut palette = [0u32; 256];
+    let mut palette = [0u32; PALETTE_TEXTURE_WIDTH as usize];
     let rgba8 = assets.palette_rgba8();
-    for (i, entry) in palette.iter_mut().enumerate().take(64) {
+    for (i, entry) in palette.iter_mut().enumerate() {
         let pos = i * 4;
         *entry = u32::from(rgba8[pos]) << 24
             | u32::from(rgba8[pos + 1]) << 16
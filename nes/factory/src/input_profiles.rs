@@ -17,6 +17,15 @@ impl InputPorts for crate::NesFactory {
                 id: AttachmentId::new("nes.attachment.player2"),
                 label: "Player 2",
             },
+            // 4 人用アダプタ経由でのみ接続される
+            SlotInfo {
+                id: AttachmentId::new("nes.attachment.player3"),
+                label: "Player 3",
+            },
+            SlotInfo {
+                id: AttachmentId::new("nes.attachment.player4"),
+                label: "Player 4",
+            },
         ];
         SLOTS
     }
@@ -36,6 +45,8 @@ impl InputSystemFactory for crate::NesFactory {
             slots: vec![
                 (AttachmentId::new("nes.attachment.player1"), famicom),
                 (AttachmentId::new("nes.attachment.player2"), None),
+                (AttachmentId::new("nes.attachment.player3"), None),
+                (AttachmentId::new("nes.attachment.player4"), None),
             ],
         }
     }
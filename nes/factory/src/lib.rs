@@ -2,7 +2,10 @@ mod builder;
 pub mod input_profiles;
 mod settings;
 
-use std::rc::Rc;
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
 use nerust_core_traits::{
     audio::AudioBackend,
@@ -18,13 +21,25 @@ use nerust_core_traits::{
     identity::SystemId,
 };
 use nerust_input_traits::{
-    Controller, ControllerCollection, ControllerProfile, EmuInput, GuiInput, ProfileId,
+    Controller, ControllerCollection, ControllerProfile, EmuInput, GuiInput, OpenBusReadResult,
+    Port, ProfileId,
 };
 use nerust_nes_settings::NesSettings;
 
 #[derive(Debug)]
 pub struct NesFactory;
 
+/// Placeholder for an empty port in front of an occupied one; reads are open bus.
+#[derive(Debug)]
+struct Unplugged;
+
+impl Controller for Unplugged {
+    fn read(&mut self, _port: &dyn Port) -> OpenBusReadResult {
+        OpenBusReadResult::new(0, 0)
+    }
+    fn write(&mut self, _port: &dyn Port, _value: u8) {}
+}
+
 impl CoreFactory for NesFactory {
     fn system_id(&self) -> Box<dyn SystemId> {
         Box::new(nerust_nes_core::rom_identity::NesSystemId)
@@ -41,13 +56,21 @@ impl CoreFactory for NesFactory {
         assignments: &nerust_input_traits::InputAssignments,
     ) -> Result<CoreParts, FactoryError> {
         let input_factory: &dyn nerust_input_traits::InputSystemFactory = self;
-        // Build controller devices per occupied port.
+        // Build controller devices per occupied port. Device index must match the
+        // port index, so empty ports before an occupied one are padded.
         let mut devices: Vec<Box<dyn Controller + Send>> = Vec::new();
-        for (_, ctrl_opt) in &assignments.slots {
+        for (slot, (_, ctrl_opt)) in assignments.slots.iter().enumerate() {
             let profile = match ctrl_opt {
                 Some(p) => p,
                 None => continue,
             };
+            // Famicom セットや 4 人用アダプタは後続のポートまで占有する
+            if devices.len() > slot {
+                continue;
+            }
+            while devices.len() < slot {
+                devices.push(Box::new(Unplugged));
+            }
             let pid = profile.profile_id();
             if pid == ProfileId::new("nes.famicom") {
                 devices.push(Box::new(nerust_nes_device::famicom_set::FamicomPadP1::new()));
@@ -56,6 +79,18 @@ impl CoreFactory for NesFactory {
                 devices.push(Box::new(nerust_nes_device::standard_pad::StandardPad::new(
                     0x1F,
                 )));
+            } else if pid == ProfileId::new("nes.four_score") {
+                devices.push(Box::new(nerust_nes_device::four_score::FourScore::port1()));
+                devices.push(Box::new(nerust_nes_device::four_score::FourScore::port2()));
+            } else if pid == ProfileId::new("nes.famicom_four_player") {
+                devices.push(Box::new(nerust_nes_device::four_score::HoriAdapter::port1()));
+                devices.push(Box::new(nerust_nes_device::four_score::HoriAdapter::port2()));
+            } else if pid == ProfileId::new("nes.zapper") {
+                devices.push(Box::new(if slot == 0 {
+                    nerust_nes_device::zapper::Zapper::port1()
+                } else {
+                    nerust_nes_device::zapper::Zapper::port2()
+                }));
             }
         }
         let controller_collection = ControllerCollection::new(devices);
@@ -75,7 +110,15 @@ impl CoreFactory for NesFactory {
     }
 
     fn probe_media(&self, media: &MediaObject) -> bool {
-        media.bytes.len() >= 4 && media.bytes[..4] == [0x4E, 0x45, 0x53, 0x1A]
+        // iNES / NES 2.0, fwNES ヘッダ付き FDS, ヘッダなし FDS, NSF, NSFe
+        const MAGICS: [&[u8]; 5] = [
+            b"NES\x1A",
+            b"FDS\x1A",
+            b"\x01*NINTENDO-HVC*",
+            b"NESM\x1A",
+            b"NSFE",
+        ];
+        MAGICS.iter().any(|magic| media.bytes.starts_with(magic))
     }
 
     fn settings_page(&self, view: &FactorySettingsView) -> SystemSettingsPageModel {
@@ -99,6 +142,20 @@ impl CoreFactory for NesFactory {
         Ok(())
     }
 
+    fn apply_settings_path(
+        &self,
+        view: &mut FactorySettingsView,
+        field: &SystemSettingsFieldId,
+        path: Option<&Path>,
+    ) -> Result<(), FactoryError> {
+        let nes = view
+            .system_config
+            .as_deref_mut()
+            .and_then(|settings| settings.downcast_mut::<NesSettings>())
+            .ok_or(FactoryError::InvalidSettings)?;
+        settings::apply_nes_settings_path_inner(nes, field, path)
+    }
+
     fn resolve_load_request(
         &self,
         view: &FactorySettingsView,
@@ -122,6 +179,12 @@ impl CoreFactory for NesFactory {
         self
     }
 
+    fn validate_cheat_code(&self, code: &str) -> Result<(), FactoryError> {
+        code.parse::<nerust_nes_core::cheat::CheatCode>()
+            .map(|_| ())
+            .map_err(|e| FactoryError::InvalidCheat(e.to_string()))
+    }
+
     fn load_options_schema(&self) -> Box<dyn DynSystemLoadOptionsSchema> {
         NesLoadOptionsSchema.into()
     }
@@ -149,12 +212,24 @@ impl SystemDefaults for NesFactory {
             "nes.filter.ntsc_composite" => Some(localized("NTSC Composite", "NTSC コンポジット")),
             "nes.filter.ntsc_svideo" => Some(localized("NTSC S-Video", "NTSC S-ビデオ")),
             "nes.filter.ntsc_rgb" => Some(localized("NTSC RGB", "NTSC RGB")),
+            "nes.video.palette" => Some(localized("Palette", "パレット")),
+            "nes.palette.default" => Some(localized("Default", "標準")),
+            "nes.palette.measured_2c02" => Some(localized("2C02 (Measured)", "2C02 (実測)")),
+            "nes.palette.rgb_2c03" => Some(localized("2C03 RGB", "2C03 RGB")),
+            "nes.palette.pvm" => Some(localized("PVM Style", "PVM 風")),
+            "nes.palette.file" => Some(localized(".pal File", ".pal ファイル")),
+            "nes.video.palette_file" => Some(localized("Palette File", "パレットファイル")),
             "nes.core.mmc3_irq_variant" => {
                 Some(localized("MMC3 IRQ Variant", "MMC3 IRQ バリアント"))
             }
             "nes.mmc3.auto" => Some(localized("Auto", "自動")),
             "nes.mmc3.sharp" => Some(localized("Sharp", "Sharp")),
             "nes.mmc3.nec" => Some(localized("Nec", "Nec")),
+            "nes.core.region" => Some(localized("Region", "地域")),
+            "nes.region.auto" => Some(localized("Auto", "自動")),
+            "nes.region.ntsc" => Some(localized("NTSC", "NTSC")),
+            "nes.region.pal" => Some(localized("PAL", "PAL")),
+            "nes.region.dendy" => Some(localized("Dendy", "Dendy")),
             _ => None,
         }
     }
@@ -166,6 +241,10 @@ impl SystemDefaults for NesFactory {
     fn default_input_control_prefix(&self) -> Option<&'static str> {
         Some("nes.control")
     }
+
+    fn default_input_extra_attachment_ids(&self) -> &'static [&'static str] {
+        &["nes.attachment.player3", "nes.attachment.player4"]
+    }
 }
 
 #[derive(Default, clap::Args, Eq, PartialEq, Clone, Debug)]
@@ -173,6 +252,12 @@ struct CommandLineOptions {
     /// Override mapper 4 MMC3 IRQ behavior
     #[clap(long, value_enum)]
     mmc3_irq_variant: Option<Mmc3IrqVariant>,
+    /// Override console region timing (default: from ROM header)
+    #[clap(long, value_enum)]
+    region: Option<Region>,
+    /// Famicom Disk System BIOS image (default: from settings)
+    #[clap(long, value_name = "PATH")]
+    fds_bios: Option<PathBuf>,
 }
 
 impl SystemLoadOptions for CommandLineOptions {}
@@ -198,6 +283,23 @@ impl From<Mmc3IrqVariant> for nerust_nes_core::core_options::Mmc3IrqVariant {
     }
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+enum Region {
+    Ntsc,
+    Pal,
+    Dendy,
+}
+
+impl From<Region> for nerust_nes_core::status::console_type::ConsoleType {
+    fn from(value: Region) -> Self {
+        match value {
+            Region::Ntsc => Self::Ntsc,
+            Region::Pal => Self::Pal,
+            Region::Dendy => Self::Dendy,
+        }
+    }
+}
+
 pub fn nes_device_controller_profiles() -> Vec<Rc<dyn ControllerProfile>> {
     nerust_nes_device::nes_device_controller_profiles()
 }
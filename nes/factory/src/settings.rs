@@ -1,4 +1,4 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, path::Path, sync::Arc};
 
 use nerust_core_traits::factory::{
     FactoryError,
@@ -9,8 +9,13 @@ use nerust_core_traits::factory::{
     load::{DynSystemLoadOptions, DynSystemLoadOptionsExt, ResolvedLoadRequest},
     settings::{FactorySettingsView, Language},
 };
-use nerust_nes_core::core_options::{CoreOptions, Mmc3IrqVariant};
-use nerust_nes_settings::{NesSettings, NesVideoFilter};
+use nerust_nes_core::{
+    console_core::FDS_BIOS_KEY,
+    core_options::{CoreOptions, Mmc3IrqVariant},
+    status::console_type::ConsoleType,
+};
+use nerust_nes_settings::{NesSettings, NesVideoFilter, NesVideoPalette, Region};
+use nerust_render_filters::palette::{BuiltinPalette, NesPalette};
 use nerust_render_traits::filter::FilterType;
 use nerust_settings_traits::SystemSettings;
 
@@ -29,6 +34,38 @@ pub(crate) fn filter_type_from_bytes(settings: Option<&dyn SystemSettings>) -> F
     }
 }
 
+/// The selected palette; `None` keeps the filter's own colors.
+///
+/// A `.pal` file that is unset or unreadable falls back to the filter's colors
+/// with a warning, so a moved file never keeps the core from starting.
+pub(crate) fn palette_from_bytes(settings: Option<&dyn SystemSettings>) -> Option<NesPalette> {
+    let default_settings = NesSettings::default();
+    let nes_settings = settings
+        .and_then(|s| s.downcast_ref())
+        .unwrap_or(&default_settings);
+    let builtin = match nes_settings.video.palette {
+        NesVideoPalette::Default => return None,
+        NesVideoPalette::Measured2c02 => BuiltinPalette::Measured2c02,
+        NesVideoPalette::Rgb2c03 => BuiltinPalette::Rgb2c03,
+        NesVideoPalette::Pvm => BuiltinPalette::Pvm,
+        NesVideoPalette::File => {
+            let Some(path) = nes_settings.video.palette_file.as_deref() else {
+                log::warn!("no palette file is set; using the default palette");
+                return None;
+            };
+            return NesPalette::load(path)
+                .inspect_err(|error| {
+                    log::warn!(
+                        "palette {}: {error}; using the default palette",
+                        path.display()
+                    );
+                })
+                .ok();
+        }
+    };
+    Some(NesPalette::builtin(builtin))
+}
+
 pub(crate) fn nes_settings_page(view: &FactorySettingsView) -> SystemSettingsPageModel {
     nes_settings_page_inner(
         view.system_config
@@ -71,6 +108,49 @@ fn nes_settings_page_inner(current: &NesSettings) -> SystemSettingsPageModel {
                     ]),
                 },
             },
+            SystemSettingsFieldModel {
+                id: SystemSettingsFieldId(Cow::Borrowed(PALETTE_FIELD)),
+                label_id: "nes.video.palette",
+                kind: SystemSettingsFieldKind::Choice {
+                    selected: SystemSettingsChoiceId(Cow::Borrowed(match current.video.palette {
+                        NesVideoPalette::Default => "default",
+                        NesVideoPalette::Measured2c02 => "measured_2c02",
+                        NesVideoPalette::Rgb2c03 => "rgb_2c03",
+                        NesVideoPalette::Pvm => "pvm",
+                        NesVideoPalette::File => "file",
+                    })),
+                    options: Arc::from([
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("default")),
+                            label_id: "nes.palette.default",
+                        },
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("measured_2c02")),
+                            label_id: "nes.palette.measured_2c02",
+                        },
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("rgb_2c03")),
+                            label_id: "nes.palette.rgb_2c03",
+                        },
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("pvm")),
+                            label_id: "nes.palette.pvm",
+                        },
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("file")),
+                            label_id: "nes.palette.file",
+                        },
+                    ]),
+                },
+            },
+            SystemSettingsFieldModel {
+                id: SystemSettingsFieldId(Cow::Borrowed(PALETTE_FILE_FIELD)),
+                label_id: "nes.video.palette_file",
+                kind: SystemSettingsFieldKind::Path {
+                    selected: current.video.palette_file.clone(),
+                    extensions: &["pal"],
+                },
+            },
             SystemSettingsFieldModel {
                 id: SystemSettingsFieldId(Cow::Borrowed(MMC3_FIELD)),
                 label_id: "nes.core.mmc3_irq_variant",
@@ -98,12 +178,45 @@ fn nes_settings_page_inner(current: &NesSettings) -> SystemSettingsPageModel {
                     ]),
                 },
             },
+            SystemSettingsFieldModel {
+                id: SystemSettingsFieldId(Cow::Borrowed(REGION_FIELD)),
+                label_id: "nes.core.region",
+                kind: SystemSettingsFieldKind::Choice {
+                    selected: SystemSettingsChoiceId(Cow::Borrowed(match current.core.region {
+                        Some(Region::Ntsc) => "ntsc",
+                        Some(Region::Pal) => "pal",
+                        Some(Region::Dendy) => "dendy",
+                        None => "auto",
+                    })),
+                    options: Arc::from([
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("auto")),
+                            label_id: "nes.region.auto",
+                        },
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("ntsc")),
+                            label_id: "nes.region.ntsc",
+                        },
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("pal")),
+                            label_id: "nes.region.pal",
+                        },
+                        SystemSettingsChoiceOption {
+                            id: SystemSettingsChoiceId(Cow::Borrowed("dendy")),
+                            label_id: "nes.region.dendy",
+                        },
+                    ]),
+                },
+            },
         ]),
     }
 }
 
 const FILTER_FIELD: &str = "video.filter";
+const PALETTE_FIELD: &str = "video.palette";
+const PALETTE_FILE_FIELD: &str = "video.palette_file";
 const MMC3_FIELD: &str = "core.mmc3_irq_variant";
+const REGION_FIELD: &str = "core.region";
 
 fn convert_mmc3(v: nerust_nes_settings::Mmc3IrqVariant) -> Mmc3IrqVariant {
     match v {
@@ -112,6 +225,14 @@ fn convert_mmc3(v: nerust_nes_settings::Mmc3IrqVariant) -> Mmc3IrqVariant {
     }
 }
 
+fn convert_region(v: Region) -> ConsoleType {
+    match v {
+        Region::Ntsc => ConsoleType::Ntsc,
+        Region::Pal => ConsoleType::Pal,
+        Region::Dendy => ConsoleType::Dendy,
+    }
+}
+
 pub(crate) fn resolve_nes_load_request_inner(
     nes: &NesSettings,
     _language: &Language,
@@ -122,11 +243,22 @@ pub(crate) fn resolve_nes_load_request_inner(
         .into_inner::<CommandLineOptions>()
         .map_err(|_| FactoryError::Resolve("failed to downcast load options".to_string()))?;
     let explicit_val = options.mmc3_irq_variant.map(Mmc3IrqVariant::from);
+    let saved_region = nes.core.region.map(convert_region);
+    let explicit_region = options.region.map(ConsoleType::from);
     let core_opts = CoreOptions {
         mmc3_irq_variant: explicit_val.or(saved),
+        region: explicit_region.or(saved_region),
     };
+    let bios_paths = options
+        .fds_bios
+        .or_else(|| nes.bios.fds.clone())
+        .map(|path| (FDS_BIOS_KEY.to_string(), path))
+        .into_iter()
+        .collect();
     Ok(ResolvedLoadRequest {
         options: core_opts.into(),
+        bios_paths,
+        patch_paths: Vec::new(),
     })
 }
 
@@ -146,6 +278,17 @@ pub(crate) fn apply_nes_settings_choice_inner(
             };
             Ok(())
         }
+        PALETTE_FIELD => {
+            s.video.palette = match choice.as_str() {
+                "default" => NesVideoPalette::Default,
+                "measured_2c02" => NesVideoPalette::Measured2c02,
+                "rgb_2c03" => NesVideoPalette::Rgb2c03,
+                "pvm" => NesVideoPalette::Pvm,
+                "file" => NesVideoPalette::File,
+                other => return Err(FactoryError::InvalidChoice(other.to_string())),
+            };
+            Ok(())
+        }
         MMC3_FIELD => {
             s.core.mmc3_irq_variant = match choice.as_str() {
                 "sharp" => Some(nerust_nes_settings::Mmc3IrqVariant::Sharp),
@@ -155,28 +298,59 @@ pub(crate) fn apply_nes_settings_choice_inner(
             };
             Ok(())
         }
+        REGION_FIELD => {
+            s.core.region = match choice.as_str() {
+                "ntsc" => Some(Region::Ntsc),
+                "pal" => Some(Region::Pal),
+                "dendy" => Some(Region::Dendy),
+                "auto" => None,
+                other => return Err(FactoryError::InvalidChoice(other.to_string())),
+            };
+            Ok(())
+        }
+        _ => Err(FactoryError::InvalidChoice(field.as_str().to_string())),
+    }
+}
+
+pub(crate) fn apply_nes_settings_path_inner(
+    s: &mut NesSettings,
+    field: &SystemSettingsFieldId,
+    path: Option<&Path>,
+) -> Result<(), FactoryError> {
+    match field.as_str() {
+        PALETTE_FILE_FIELD => {
+            s.video.palette_file = path.map(Path::to_path_buf);
+            Ok(())
+        }
         _ => Err(FactoryError::InvalidChoice(field.as_str().to_string())),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::borrow::Cow;
+    use std::{
+        borrow::Cow,
+        path::{Path, PathBuf},
+    };
 
     use nerust_core_traits::factory::{
         descriptor::{SystemSettingsChoiceId, SystemSettingsFieldId},
         load::DynSystemLoadOptions,
         settings::{FactorySettingsView, Language},
     };
-    use nerust_nes_core::core_options::{CoreOptions, Mmc3IrqVariant};
-    use nerust_nes_settings::{NesSettings, NesVideoFilter};
+    use nerust_nes_core::{
+        console_core::FDS_BIOS_KEY,
+        core_options::{CoreOptions, Mmc3IrqVariant},
+        status::console_type::ConsoleType,
+    };
+    use nerust_nes_settings::{NesSettings, NesVideoFilter, NesVideoPalette};
     use nerust_render_traits::filter::FilterType;
 
     use crate::CommandLineOptions;
 
     use super::{
-        apply_nes_settings_choice_inner, filter_type_from_bytes, nes_settings_page,
-        resolve_nes_load_request_inner,
+        apply_nes_settings_choice_inner, apply_nes_settings_path_inner, filter_type_from_bytes,
+        nes_settings_page, palette_from_bytes, resolve_nes_load_request_inner,
     };
 
     fn test_view() -> FactorySettingsView {
@@ -189,6 +363,8 @@ mod tests {
     fn nec_options() -> Box<dyn DynSystemLoadOptions> {
         CommandLineOptions {
             mmc3_irq_variant: Some(crate::Mmc3IrqVariant::Nec),
+            region: None,
+            fds_bios: None,
         }
         .into()
     }
@@ -227,7 +403,7 @@ mod tests {
             system_config: Some(Box::new(nes)),
         };
         let page = nes_settings_page(&view);
-        assert_eq!(page.fields.len(), 2);
+        assert_eq!(page.fields.len(), 5);
     }
 
     #[test]
@@ -256,6 +432,36 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn saved_palette_choice_resolves_to_filter_palette() {
+        let mut nes = NesSettings::default();
+        assert!(palette_from_bytes(Some(&nes)).is_none());
+
+        apply_nes_settings_choice_inner(
+            &mut nes,
+            &SystemSettingsFieldId(Cow::Borrowed("video.palette")),
+            &SystemSettingsChoiceId(Cow::Borrowed("rgb_2c03")),
+        )
+        .unwrap();
+        assert_eq!(nes.video.palette, NesVideoPalette::Rgb2c03);
+        assert!(palette_from_bytes(Some(&nes)).is_some());
+
+        // An unset or unreadable .pal file falls back to the default colors
+        nes.video.palette = NesVideoPalette::File;
+        assert!(palette_from_bytes(Some(&nes)).is_none());
+        apply_nes_settings_path_inner(
+            &mut nes,
+            &SystemSettingsFieldId(Cow::Borrowed("video.palette_file")),
+            Some(Path::new("missing/palette.pal")),
+        )
+        .unwrap();
+        assert_eq!(
+            nes.video.palette_file.as_deref(),
+            Some(Path::new("missing/palette.pal"))
+        );
+        assert!(palette_from_bytes(Some(&nes)).is_none());
+    }
+
     #[test]
     fn mmc3_irq_variant_conversion_covers_all_variants() {
         use nerust_nes_core::core_options::Mmc3IrqVariant as CoreVariant;
@@ -276,6 +482,8 @@ mod tests {
             &Language::SystemDefault,
             CommandLineOptions {
                 mmc3_irq_variant: Some(crate::Mmc3IrqVariant::Sharp),
+                region: None,
+                fds_bios: None,
             }
             .into(),
         )
@@ -289,6 +497,8 @@ mod tests {
             &Language::SystemDefault,
             CommandLineOptions {
                 mmc3_irq_variant: Some(crate::Mmc3IrqVariant::Nec),
+                region: None,
+                fds_bios: None,
             }
             .into(),
         )
@@ -296,4 +506,77 @@ mod tests {
         let core_opts = &resolved.options.downcast::<CoreOptions>().unwrap();
         assert_eq!(core_opts.mmc3_irq_variant, Some(Mmc3IrqVariant::Nec));
     }
+
+    #[test]
+    fn region_resolves_from_saved_settings_and_command_line() {
+        let mut nes = NesSettings::default();
+        apply_nes_settings_choice_inner(
+            &mut nes,
+            &SystemSettingsFieldId(Cow::Borrowed("core.region")),
+            &SystemSettingsChoiceId(Cow::Borrowed("pal")),
+        )
+        .unwrap();
+
+        let resolved = resolve_nes_load_request_inner(
+            &nes,
+            &Language::SystemDefault,
+            CommandLineOptions::default().into(),
+        )
+        .unwrap();
+        let core_opts = &resolved.options.downcast::<CoreOptions>().unwrap();
+        assert_eq!(core_opts.region, Some(ConsoleType::Pal));
+
+        let resolved = resolve_nes_load_request_inner(
+            &nes,
+            &Language::SystemDefault,
+            CommandLineOptions {
+                mmc3_irq_variant: None,
+                region: Some(crate::Region::Dendy),
+                fds_bios: None,
+            }
+            .into(),
+        )
+        .unwrap();
+        let core_opts = &resolved.options.downcast::<CoreOptions>().unwrap();
+        assert_eq!(core_opts.region, Some(ConsoleType::Dendy));
+    }
+
+    #[test]
+    fn fds_bios_path_resolves_from_saved_settings_and_command_line() {
+        let mut nes = NesSettings::default();
+        let resolved = resolve_nes_load_request_inner(
+            &nes,
+            &Language::SystemDefault,
+            CommandLineOptions::default().into(),
+        )
+        .unwrap();
+        assert!(resolved.bios_paths.is_empty());
+
+        nes.bios.fds = Some(PathBuf::from("saved/disksys.rom"));
+        let resolved = resolve_nes_load_request_inner(
+            &nes,
+            &Language::SystemDefault,
+            CommandLineOptions::default().into(),
+        )
+        .unwrap();
+        assert_eq!(
+            resolved.bios_paths.get(FDS_BIOS_KEY),
+            Some(&PathBuf::from("saved/disksys.rom"))
+        );
+
+        let resolved = resolve_nes_load_request_inner(
+            &nes,
+            &Language::SystemDefault,
+            CommandLineOptions {
+                fds_bios: Some(PathBuf::from("cli/disksys.rom")),
+                ..CommandLineOptions::default()
+            }
+            .into(),
+        )
+        .unwrap();
+        assert_eq!(
+            resolved.bios_paths.get(FDS_BIOS_KEY),
+            Some(&PathBuf::from("cli/disksys.rom"))
+        );
+    }
 }
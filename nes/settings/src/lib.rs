@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use nerust_settings_traits::SystemSettings;
 use serde::{Deserialize, Serialize};
 
@@ -9,16 +11,36 @@ pub enum Mmc3IrqVariant {
     Nec,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Region {
+    Ntsc,
+    Pal,
+    Dendy,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct NesVideoSettings {
     pub filter: NesVideoFilter,
+    pub palette: NesVideoPalette,
+    /// `.pal` file (64 or 512 colors) used when `palette` is `File`. Picked in the settings UI.
+    pub palette_file: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 #[serde(default)]
 pub struct NesCoreSettings {
     pub mmc3_irq_variant: Option<Mmc3IrqVariant>,
+    pub region: Option<Region>,
+}
+
+/// BIOS images required by some media. Edited in the settings file.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NesBiosSettings {
+    /// Famicom Disk System BIOS (`disksys.rom`, 8 KiB).
+    pub fds: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -26,6 +48,7 @@ pub struct NesCoreSettings {
 pub struct NesSettings {
     pub video: NesVideoSettings,
     pub core: NesCoreSettings,
+    pub bios: NesBiosSettings,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
@@ -38,11 +61,24 @@ pub enum NesVideoFilter {
     NtscRgb,
 }
 
+/// Colors the video filter starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NesVideoPalette {
+    /// The filter's own colors: the built-in table, or the NTSC signal decode.
+    #[default]
+    Default,
+    Measured2c02,
+    Rgb2c03,
+    Pvm,
+    File,
+}
+
 #[typetag::serde]
 impl SystemSettings for NesSettings {
     fn requires_live_session_rebuild(&self, next: &dyn SystemSettings) -> bool {
         if let Some(other) = next.downcast_ref::<NesSettings>() {
-            self.video.filter != other.video.filter
+            self.video != other.video
         } else {
             false
         }
@@ -59,9 +95,15 @@ mod tests {
         NesSettings {
             video: NesVideoSettings {
                 filter: NesVideoFilter::NtscRgb,
+                palette: NesVideoPalette::File,
+                palette_file: Some(PathBuf::from("smooth.pal")),
             },
             core: NesCoreSettings {
                 mmc3_irq_variant: Some(Mmc3IrqVariant::Sharp),
+                region: Some(Region::Pal),
+            },
+            bios: NesBiosSettings {
+                fds: Some(PathBuf::from("disksys.rom")),
             },
         }
     }
@@ -88,6 +130,7 @@ mod tests {
             cloned_nes.core.mmc3_irq_variant,
             Some(Mmc3IrqVariant::Sharp)
         );
+        assert_eq!(cloned_nes.core.region, Some(Region::Pal));
     }
 
     #[test]
@@ -99,11 +142,24 @@ mod tests {
         assert!(a.requires_live_session_rebuild(&b));
     }
 
+    #[test]
+    fn requires_live_session_rebuild_detects_palette_change() {
+        let a: NesSettings = test_settings();
+        let mut b = a.clone();
+        b.video.palette_file = Some(PathBuf::from("other.pal"));
+        assert!(a.requires_live_session_rebuild(&b));
+
+        b.video.palette_file = a.video.palette_file.clone();
+        b.video.palette = NesVideoPalette::Rgb2c03;
+        assert!(a.requires_live_session_rebuild(&b));
+    }
+
     #[test]
     fn requires_live_session_rebuild_ignores_core_change() {
         let a: NesSettings = test_settings();
         let mut b = a.clone();
         b.core.mmc3_irq_variant = Some(Mmc3IrqVariant::Nec);
+        b.core.region = Some(Region::Dendy);
 
         assert!(!a.requires_live_session_rebuild(&b));
     }
@@ -0,0 +1,104 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+};
+
+use nerust_core_traits::{
+    cheat::Cheat,
+    identity::{SystemId, SystemIdentity},
+};
+
+use crate::{
+    error::PersistenceError,
+    fs_ops::{read_limited, write_atomic},
+};
+
+const CHEATS_ENTRY: &str = "cheats.msgpack";
+const CHEATS_SCHEMA_VERSION: u32 = 1;
+const MAX_CHEATS_BYTES: usize = 4 * 1024 * 1024;
+
+/// One file per states directory. A patched and an unpatched ROM can share the
+/// directory, so each cheat list is keyed by the loaded content's identity.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CheatFile {
+    schema_version: u32,
+    entries: Vec<CheatFileEntry>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CheatFileEntry {
+    system_id: Box<dyn SystemId>,
+    #[serde(with = "serde_bytes")]
+    identity_bytes: Vec<u8>,
+    cheats: Vec<Cheat>,
+}
+
+impl CheatFileEntry {
+    fn matches(&self, identity: &SystemIdentity) -> bool {
+        self.system_id == identity.system_id && self.identity_bytes == identity.identity_bytes
+    }
+}
+
+pub fn cheats_path(states_dir: &Path) -> PathBuf {
+    states_dir.join(CHEATS_ENTRY)
+}
+
+pub fn load_cheats_for_identity(
+    states_dir: &Path,
+    identity: &SystemIdentity,
+) -> Result<Vec<Cheat>, PersistenceError> {
+    Ok(read_cheat_file(states_dir)?
+        .entries
+        .into_iter()
+        .find(|entry| entry.matches(identity))
+        .map(|entry| entry.cheats)
+        .unwrap_or_default())
+}
+
+/// Replaces the cheat list stored for `identity`. An empty list removes the entry.
+pub fn write_cheats_for_identity(
+    states_dir: &Path,
+    identity: &SystemIdentity,
+    cheats: &[Cheat],
+) -> Result<(), PersistenceError> {
+    let mut file = read_cheat_file(states_dir)?;
+    file.entries.retain(|entry| !entry.matches(identity));
+    if !cheats.is_empty() {
+        file.entries.push(CheatFileEntry {
+            system_id: identity.system_id.clone(),
+            identity_bytes: identity.identity_bytes.clone(),
+            cheats: cheats.to_vec(),
+        });
+    }
+    let path = cheats_path(states_dir);
+    if file.entries.is_empty() {
+        return match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => Err(error.into()),
+        };
+    }
+    fs::create_dir_all(states_dir)?;
+    write_atomic(&path, &rmp_serde::to_vec_named(&file)?)
+}
+
+fn read_cheat_file(states_dir: &Path) -> Result<CheatFile, PersistenceError> {
+    let bytes = match File::open(cheats_path(states_dir)) {
+        Ok(mut file) => read_limited(&mut file, MAX_CHEATS_BYTES, "cheat list")?,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(CheatFile {
+                schema_version: CHEATS_SCHEMA_VERSION,
+                entries: Vec::new(),
+            });
+        }
+        Err(error) => return Err(error.into()),
+    };
+    let file: CheatFile = rmp_serde::from_slice(&bytes)?;
+    if file.schema_version != CHEATS_SCHEMA_VERSION {
+        return Err(PersistenceError::Validation(format!(
+            "unsupported cheat list schema version: {}",
+            file.schema_version
+        )));
+    }
+    Ok(file)
+}
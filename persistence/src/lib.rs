@@ -12,6 +12,7 @@
 //! names, metadata fields, or this crate's validation/interpretation rules change.
 
 mod archive;
+pub mod cheats;
 pub mod error;
 mod fs_ops;
 mod metadata;
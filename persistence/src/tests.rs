@@ -1,4 +1,5 @@
 mod archive;
+mod cheats;
 mod sidecar;
 mod slots;
 mod time;
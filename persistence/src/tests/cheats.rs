@@ -0,0 +1,34 @@
+use nerust_core_traits::cheat::Cheat;
+
+use super::{prepare_test_dir, test_identity, test_identity_with_bytes};
+use crate::cheats::{cheats_path, load_cheats_for_identity, write_cheats_for_identity};
+
+#[test]
+fn cheat_lists_are_kept_per_identity_next_to_slots() {
+    let dir = prepare_test_dir("cheats-per-identity");
+    let original = test_identity();
+    let patched = test_identity_with_bytes(vec![9, 9, 9, 9]);
+    let mut disabled = Cheat::new("0075:09", "lives");
+    disabled.enabled = false;
+
+    assert!(
+        load_cheats_for_identity(&dir, &original)
+            .unwrap()
+            .is_empty()
+    );
+    write_cheats_for_identity(&dir, &original, &[Cheat::new("SXIOPO", "")]).unwrap();
+    write_cheats_for_identity(&dir, &patched, std::slice::from_ref(&disabled)).unwrap();
+
+    assert_eq!(
+        load_cheats_for_identity(&dir, &original).unwrap(),
+        [Cheat::new("SXIOPO", "")]
+    );
+    assert_eq!(
+        load_cheats_for_identity(&dir, &patched).unwrap(),
+        [disabled]
+    );
+
+    write_cheats_for_identity(&dir, &original, &[]).unwrap();
+    write_cheats_for_identity(&dir, &patched, &[]).unwrap();
+    assert!(!cheats_path(&dir).exists());
+}
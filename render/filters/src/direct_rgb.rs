@@ -2,22 +2,25 @@ use nerust_render_traits::{
     filter::FilterUnit, logical::LogicalSize, physical::PhysicalSize, rgb::RGB,
 };
 
+use crate::palette::EMPHASIS_COLOR_COUNT;
+
 pub(crate) struct DirectRgb {
     source: LogicalSize,
+    palette: [RGB; EMPHASIS_COLOR_COUNT],
 }
 
 impl DirectRgb {
-    pub(crate) fn new(source: LogicalSize) -> Self {
-        Self { source }
+    pub(crate) fn new(source: LogicalSize, palette: [RGB; EMPHASIS_COLOR_COUNT]) -> Self {
+        Self { source, palette }
     }
 }
 
 impl FilterUnit for DirectRgb {
-    type Input = u8;
+    type Input = u16;
     type Output = RGB;
 
     fn push<F: FnMut(Self::Output)>(&mut self, value: Self::Input, next_func: &mut F) {
-        next_func(RGB::from(PALETTE[usize::from(value)]))
+        next_func(self.palette[usize::from(value)])
     }
 
     fn source_logical_size(&self) -> LogicalSize {
@@ -1,5 +1,6 @@
 mod direct_rgb;
 mod ntsc_simulator;
+pub mod palette;
 pub mod presentation;
 
 pub use presentation::FilterTypeExt;
@@ -2,6 +2,8 @@ use nerust_render_traits::{
     filter::FilterUnit, logical::LogicalSize, physical::PhysicalSize, rgb::RGB,
 };
 
+use crate::palette::EMPHASIS_COLOR_COUNT;
+
 #[derive(Debug)]
 pub(crate) struct NtscSimulator {
     ntsc: nerust_render_ntsc::Engine,
@@ -9,30 +11,42 @@ pub(crate) struct NtscSimulator {
 }
 
 impl NtscSimulator {
-    pub(crate) fn composite(source: LogicalSize) -> Self {
+    pub(crate) fn composite(
+        source: LogicalSize,
+        base_palette: Option<&[RGB; EMPHASIS_COLOR_COUNT]>,
+    ) -> Self {
         Self {
             ntsc: nerust_render_ntsc::Engine::new(
                 &nerust_render_ntsc::setup::Setup::Composite,
+                base_palette,
                 source.width,
             ),
             source,
         }
     }
 
-    pub(crate) fn svideo(source: LogicalSize) -> Self {
+    pub(crate) fn svideo(
+        source: LogicalSize,
+        base_palette: Option<&[RGB; EMPHASIS_COLOR_COUNT]>,
+    ) -> Self {
         Self {
             ntsc: nerust_render_ntsc::Engine::new(
                 &nerust_render_ntsc::setup::Setup::SVideo,
+                base_palette,
                 source.width,
             ),
             source,
         }
     }
 
-    pub(crate) fn rgb(source: LogicalSize) -> Self {
+    pub(crate) fn rgb(
+        source: LogicalSize,
+        base_palette: Option<&[RGB; EMPHASIS_COLOR_COUNT]>,
+    ) -> Self {
         Self {
             ntsc: nerust_render_ntsc::Engine::new(
                 &nerust_render_ntsc::setup::Setup::RGB,
+                base_palette,
                 source.width,
             ),
             source,
@@ -41,7 +55,7 @@ impl NtscSimulator {
 }
 
 impl FilterUnit for NtscSimulator {
-    type Input = u8;
+    type Input = u16;
     type Output = RGB;
 
     fn push<F: FnMut(Self::Output)>(&mut self, value: Self::Input, next_func: &mut F) {
@@ -0,0 +1,258 @@
+use std::path::Path;
+
+use nerust_render_ntsc::setup::Setup;
+use nerust_render_traits::rgb::RGB;
+
+/// Colors addressed by a 6-bit palette index.
+pub const BASE_COLOR_COUNT: usize = 64;
+/// Base colors times the eight combinations of the `$2001` emphasis bits.
+pub const EMPHASIS_COLOR_COUNT: usize = BASE_COLOR_COUNT * 8;
+
+/// White point of a 9300 K broadcast monitor relative to sRGB's D65 white.
+const D93_WHITE: [f32; 3] = [0.89, 0.94, 1.0];
+
+/// How much an emphasis bit darkens the two channels it does not select.
+const EMPHASIS_ATTENUATION: f32 = 0.816;
+
+/// Output levels of the 2C03 RGB PPU, three bits per channel (`0o7` = full).
+#[rustfmt::skip]
+const RGB_2C03_LEVELS: [u16; BASE_COLOR_COUNT] = [
+    0o333, 0o014, 0o006, 0o326, 0o403, 0o503, 0o510, 0o420,
+    0o320, 0o120, 0o031, 0o040, 0o022, 0o000, 0o000, 0o000,
+    0o555, 0o036, 0o027, 0o407, 0o507, 0o704, 0o700, 0o630,
+    0o430, 0o140, 0o040, 0o053, 0o044, 0o000, 0o000, 0o000,
+    0o777, 0o357, 0o447, 0o637, 0o707, 0o737, 0o740, 0o750,
+    0o660, 0o360, 0o070, 0o276, 0o077, 0o000, 0o000, 0o000,
+    0o777, 0o567, 0o657, 0o757, 0o747, 0o755, 0o764, 0o772,
+    0o773, 0o572, 0o473, 0o276, 0o467, 0o000, 0o000, 0o000,
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum PaletteError {
+    #[error("palette must hold 64 or 512 RGB entries (192 or 1536 bytes), got {0} bytes")]
+    InvalidLength(usize),
+    #[error("failed to read palette file: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Palettes shipped with the emulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinPalette {
+    /// Decoded from the composite levels measured on a 2C02.
+    Measured2c02,
+    /// The 2C03 RGB PPU used in PlayChoice-10 and Famicom Titler units.
+    Rgb2c03,
+    /// The measured 2C02 on a 9300 K broadcast monitor like Sony's PVM series.
+    Pvm,
+}
+
+/// The 512 colors a frame's palette entries address: the 6-bit palette index with the
+/// three `$2001` emphasis bits above it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NesPalette {
+    colors: [RGB; EMPHASIS_COLOR_COUNT],
+}
+
+impl NesPalette {
+    pub fn new(colors: [RGB; EMPHASIS_COLOR_COUNT]) -> Self {
+        Self { colors }
+    }
+
+    /// Derives the emphasis rows by darkening the channels each emphasis bit leaves out.
+    pub fn from_base(base: [RGB; BASE_COLOR_COUNT]) -> Self {
+        Self::new(std::array::from_fn(|entry| {
+            let color = base[entry % BASE_COLOR_COUNT];
+            let emphasis = entry / BASE_COLOR_COUNT;
+            let attenuate = |value: u8, bit: usize| {
+                // A bit selecting this channel, or no bit at all, leaves it alone
+                let others = emphasis & !(1 << bit);
+                (f32::from(value) * EMPHASIS_ATTENUATION.powi(others.count_ones() as i32)).round()
+                    as u8
+            };
+            RGB {
+                red: attenuate(color.red, 0),
+                green: attenuate(color.green, 1),
+                blue: attenuate(color.blue, 2),
+            }
+        }))
+    }
+
+    /// Parses a `.pal` file: packed RGB triplets for 64 colors, or 512 colors with the
+    /// emphasis bits above the color index. A 64-color file gets derived emphasis rows.
+    pub fn from_pal_bytes(bytes: &[u8]) -> Result<Self, PaletteError> {
+        let to_rgb = |chunk: &[u8]| RGB {
+            red: chunk[0],
+            green: chunk[1],
+            blue: chunk[2],
+        };
+        if bytes.len() == BASE_COLOR_COUNT * 3 {
+            let mut chunks = bytes.chunks_exact(3);
+            Ok(Self::from_base(std::array::from_fn(|_| {
+                to_rgb(chunks.next().expect("length checked"))
+            })))
+        } else if bytes.len() == EMPHASIS_COLOR_COUNT * 3 {
+            let mut chunks = bytes.chunks_exact(3);
+            Ok(Self::new(std::array::from_fn(|_| {
+                to_rgb(chunks.next().expect("length checked"))
+            })))
+        } else {
+            Err(PaletteError::InvalidLength(bytes.len()))
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, PaletteError> {
+        Self::from_pal_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn builtin(palette: BuiltinPalette) -> Self {
+        match palette {
+            BuiltinPalette::Measured2c02 => Self::new(measured_2c02()),
+            // The RGB PPU drives the emphasized channels at full level instead of dimming the rest
+            BuiltinPalette::Rgb2c03 => Self::new(std::array::from_fn(|entry| {
+                let levels = RGB_2C03_LEVELS[entry % BASE_COLOR_COUNT];
+                let emphasis = entry / BASE_COLOR_COUNT;
+                let level = |shift: u16, bit: usize| {
+                    let level = if emphasis & (1 << bit) != 0 {
+                        7
+                    } else {
+                        u32::from((levels >> shift) & 7)
+                    };
+                    ((level * 255 + 3) / 7) as u8
+                };
+                RGB {
+                    red: level(6, 0),
+                    green: level(3, 1),
+                    blue: level(0, 2),
+                }
+            })),
+            BuiltinPalette::Pvm => Self::new(measured_2c02().map(|color| {
+                let scale = |value: u8, white: f32| (f32::from(value) * white).round() as u8;
+                RGB {
+                    red: scale(color.red, D93_WHITE[0]),
+                    green: scale(color.green, D93_WHITE[1]),
+                    blue: scale(color.blue, D93_WHITE[2]),
+                }
+            })),
+        }
+    }
+
+    pub fn colors(&self) -> &[RGB; EMPHASIS_COLOR_COUNT] {
+        &self.colors
+    }
+}
+
+fn measured_2c02() -> [RGB; EMPHASIS_COLOR_COUNT] {
+    nerust_render_ntsc::Engine::rgb_palette(&Setup::Composite)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base_palette_derives_emphasis_rows() {
+        let bytes: Vec<u8> = (0..BASE_COLOR_COUNT).flat_map(|_| [200, 100, 50]).collect();
+        let palette = NesPalette::from_pal_bytes(&bytes).unwrap();
+
+        let plain = RGB {
+            red: 200,
+            green: 100,
+            blue: 50,
+        };
+        assert_eq!(
+            palette.colors()[..BASE_COLOR_COUNT],
+            [plain; BASE_COLOR_COUNT]
+        );
+        // Red emphasis keeps red and darkens green and blue
+        assert_eq!(
+            palette.colors()[BASE_COLOR_COUNT],
+            RGB {
+                red: 200,
+                green: 82,
+                blue: 41,
+            }
+        );
+    }
+
+    #[test]
+    fn full_palette_keeps_its_emphasis_rows() {
+        let bytes: Vec<u8> = (0..EMPHASIS_COLOR_COUNT)
+            .flat_map(|entry| {
+                [
+                    (entry / BASE_COLOR_COUNT) as u8,
+                    (entry % BASE_COLOR_COUNT) as u8,
+                    0,
+                ]
+            })
+            .collect();
+        let palette = NesPalette::from_pal_bytes(&bytes).unwrap();
+
+        assert_eq!(
+            palette.colors()[5 * BASE_COLOR_COUNT + 0x2A],
+            RGB {
+                red: 5,
+                green: 0x2A,
+                blue: 0,
+            }
+        );
+        assert!(matches!(
+            NesPalette::from_pal_bytes(&bytes[..100]),
+            Err(PaletteError::InvalidLength(100))
+        ));
+    }
+
+    #[test]
+    fn builtin_palettes_have_black_and_white() {
+        for builtin in [
+            BuiltinPalette::Measured2c02,
+            BuiltinPalette::Rgb2c03,
+            BuiltinPalette::Pvm,
+        ] {
+            let colors = NesPalette::builtin(builtin).colors().to_owned();
+            let black = colors[0x0F];
+            let white = colors[0x30];
+            assert_eq!(
+                (black.red, black.green, black.blue),
+                (0, 0, 0),
+                "{builtin:?}"
+            );
+            assert!(
+                white.red > 220 && white.green > 220 && white.blue > 220,
+                "{builtin:?}"
+            );
+        }
+        let rgb = NesPalette::builtin(BuiltinPalette::Rgb2c03)
+            .colors()
+            .to_owned();
+        assert_eq!(
+            rgb[0x16],
+            RGB {
+                red: 255,
+                green: 0,
+                blue: 0,
+            }
+        );
+        // Blue emphasis turns the 2C03's red fully magenta
+        assert_eq!(
+            rgb[4 * BASE_COLOR_COUNT + 0x16],
+            RGB {
+                red: 255,
+                green: 0,
+                blue: 255,
+            }
+        );
+    }
+
+    #[test]
+    fn measured_emphasis_darkens_the_other_channels() {
+        let colors = NesPalette::builtin(BuiltinPalette::Measured2c02)
+            .colors()
+            .to_owned();
+        let (white, red_emphasis) = (colors[0x30], colors[BASE_COLOR_COUNT + 0x30]);
+        assert!(red_emphasis.green < white.green && red_emphasis.blue < white.blue);
+        assert!(red_emphasis.red > red_emphasis.blue);
+        // All three bits darken every channel
+        let dark = colors[7 * BASE_COLOR_COUNT + 0x30];
+        assert!(dark.red < white.red && dark.green < white.green && dark.blue < white.blue);
+    }
+}
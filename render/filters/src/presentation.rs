@@ -1,13 +1,16 @@
 use nerust_render_ntsc::{NTSC_TEXTURE_HEIGHT, ShaderKernelEntry, setup::Setup};
 use nerust_render_traits::{
-    VideoFrameFormat, VideoFrameSpec, VideoPresentation,
+    PALETTE_RGBA8_LEN, VideoFrameFormat, VideoFrameSpec, VideoPresentation,
     filter::{FilterType, PALETTE_TEXTURE_WIDTH},
     logical::LogicalSize,
     physical::PhysicalSize,
     rgb::RGB,
 };
 
-use crate::direct_rgb;
+use crate::{
+    direct_rgb,
+    palette::{EMPHASIS_COLOR_COUNT, NesPalette},
+};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum VideoPresentationPipelineKind {
@@ -149,6 +152,12 @@ impl PaletteAssets {
 
 pub trait FilterTypeExt {
     fn generate(self, size: LogicalSize) -> Box<dyn nerust_render_traits::filter::VideoFilter>;
+    /// `palette` replaces the filter's own colors; `None` keeps them.
+    fn generate_with_palette(
+        self,
+        size: LogicalSize,
+        palette: Option<&NesPalette>,
+    ) -> Box<dyn nerust_render_traits::filter::VideoFilter>;
     fn layout(self, source_logical_size: LogicalSize) -> FilterLayout;
     fn presentation(
         self,
@@ -158,13 +167,18 @@ pub trait FilterTypeExt {
     fn rgba_presentation(self, source_logical_size: LogicalSize) -> VideoPresentation;
     fn palette_presentation(self, source_logical_size: LogicalSize) -> VideoPresentation;
     fn palette_assets(self) -> PaletteAssets;
+    fn palette_assets_with_palette(self, palette: Option<&NesPalette>) -> PaletteAssets;
     fn palette_console_video_assets(self) -> ConsoleVideoAssets;
-    fn palette(self) -> [RGB; 64];
+    fn palette_console_video_assets_with_palette(
+        self,
+        palette: Option<&NesPalette>,
+    ) -> ConsoleVideoAssets;
+    fn palette(self) -> [RGB; EMPHASIS_COLOR_COUNT];
 }
 
-fn encoded_palette_rgba8(filter: FilterType) -> Box<[u8]> {
-    let palette = filter.palette();
-    let mut rgba = vec![0u8; 256];
+fn encoded_palette_rgba8(filter: FilterType, palette: Option<&NesPalette>) -> Box<[u8]> {
+    let palette = palette.map_or_else(|| filter.palette(), |palette| *palette.colors());
+    let mut rgba = vec![0u8; PALETTE_RGBA8_LEN];
     for (i, color) in palette.iter().enumerate() {
         let pos = i * 4;
         rgba[pos] = color.red;
@@ -184,12 +198,19 @@ fn ntsc_setup(filter: FilterType) -> Option<Setup> {
     }
 }
 
-fn shader_kernel_entries(filter: FilterType) -> Option<Box<[ShaderKernelEntry]>> {
-    ntsc_setup(filter).map(|setup| nerust_render_ntsc::Engine::shader_kernel_entries(&setup))
+fn shader_kernel_entries(
+    filter: FilterType,
+    palette: Option<&NesPalette>,
+) -> Option<Box<[ShaderKernelEntry]>> {
+    ntsc_setup(filter).map(|setup| {
+        nerust_render_ntsc::Engine::shader_kernel_entries(&setup, palette.map(NesPalette::colors))
+    })
 }
 
-fn packed_kernel_entries(filter: FilterType) -> Option<Box<[u32]>> {
-    ntsc_setup(filter).map(|setup| nerust_render_ntsc::Engine::packed_kernel_entries(&setup))
+fn packed_kernel_entries(filter: FilterType, palette: Option<&NesPalette>) -> Option<Box<[u32]>> {
+    ntsc_setup(filter).map(|setup| {
+        nerust_render_ntsc::Engine::packed_kernel_entries(&setup, palette.map(NesPalette::colors))
+    })
 }
 
 fn encode_ntsc_packed_entries_rgba8(entries: &[u32]) -> Box<[u8]> {
@@ -207,14 +228,20 @@ fn encode_ntsc_packed_entries_rgba8(entries: &[u32]) -> Box<[u8]> {
     encoded.into_boxed_slice()
 }
 
-fn encoded_packed_ntsc_texture_rgba8(filter: FilterType) -> Option<EncodedPackedNtscTexture> {
-    packed_kernel_entries(filter).map(|entries| EncodedPackedNtscTexture {
+fn encoded_packed_ntsc_texture_rgba8(
+    filter: FilterType,
+    palette: Option<&NesPalette>,
+) -> Option<EncodedPackedNtscTexture> {
+    packed_kernel_entries(filter, palette).map(|entries| EncodedPackedNtscTexture {
         rgba8: encode_ntsc_packed_entries_rgba8(entries.as_ref()),
     })
 }
 
-fn encoded_ntsc_textures_rgba8(filter: FilterType) -> Option<EncodedNtscTextures> {
-    shader_kernel_entries(filter).map(|entries| {
+fn encoded_ntsc_textures_rgba8(
+    filter: FilterType,
+    palette: Option<&NesPalette>,
+) -> Option<EncodedNtscTextures> {
+    shader_kernel_entries(filter, palette).map(|entries| {
         let color_count = PALETTE_TEXTURE_WIDTH as usize;
         let texture_height = NTSC_TEXTURE_HEIGHT as usize;
         let entry_stride = entries.len() / color_count;
@@ -246,13 +273,32 @@ fn encoded_ntsc_textures_rgba8(filter: FilterType) -> Option<EncodedNtscTextures
 
 impl FilterTypeExt for FilterType {
     fn generate(self, size: LogicalSize) -> Box<dyn nerust_render_traits::filter::VideoFilter> {
+        self.generate_with_palette(size, None)
+    }
+
+    fn generate_with_palette(
+        self,
+        size: LogicalSize,
+        palette: Option<&NesPalette>,
+    ) -> Box<dyn nerust_render_traits::filter::VideoFilter> {
+        let base_palette = palette.map(NesPalette::colors);
         match self {
-            FilterType::None => Box::new(direct_rgb::DirectRgb::new(size)),
-            FilterType::NtscRGB => Box::new(crate::ntsc_simulator::NtscSimulator::rgb(size)),
-            FilterType::NtscComposite => {
-                Box::new(crate::ntsc_simulator::NtscSimulator::composite(size))
-            }
-            FilterType::NtscSVideo => Box::new(crate::ntsc_simulator::NtscSimulator::svideo(size)),
+            FilterType::None => Box::new(direct_rgb::DirectRgb::new(
+                size,
+                base_palette.copied().unwrap_or_else(|| self.palette()),
+            )),
+            FilterType::NtscRGB => Box::new(crate::ntsc_simulator::NtscSimulator::rgb(
+                size,
+                base_palette,
+            )),
+            FilterType::NtscComposite => Box::new(crate::ntsc_simulator::NtscSimulator::composite(
+                size,
+                base_palette,
+            )),
+            FilterType::NtscSVideo => Box::new(crate::ntsc_simulator::NtscSimulator::svideo(
+                size,
+                base_palette,
+            )),
         }
     }
 
@@ -310,16 +356,20 @@ impl FilterTypeExt for FilterType {
     }
 
     fn palette_assets(self) -> PaletteAssets {
+        self.palette_assets_with_palette(None)
+    }
+
+    fn palette_assets_with_palette(self, palette: Option<&NesPalette>) -> PaletteAssets {
         let pipeline = match self {
             FilterType::None => VideoFilterPipeline::Palette {
-                palette_rgba8: encoded_palette_rgba8(self),
+                palette_rgba8: encoded_palette_rgba8(self, palette),
             },
             FilterType::NtscRGB | FilterType::NtscComposite | FilterType::NtscSVideo => {
                 VideoFilterPipeline::Ntsc {
-                    palette_rgba8: encoded_palette_rgba8(self),
-                    packed_ntsc_rgba8: encoded_packed_ntsc_texture_rgba8(self)
+                    palette_rgba8: encoded_palette_rgba8(self, palette),
+                    packed_ntsc_rgba8: encoded_packed_ntsc_texture_rgba8(self, palette)
                         .expect("NTSC filters should expose packed textures"),
-                    split_ntsc_textures: encoded_ntsc_textures_rgba8(self)
+                    split_ntsc_textures: encoded_ntsc_textures_rgba8(self, palette)
                         .expect("NTSC filters should expose split textures"),
                 }
             }
@@ -329,11 +379,18 @@ impl FilterTypeExt for FilterType {
     }
 
     fn palette_console_video_assets(self) -> ConsoleVideoAssets {
-        ConsoleVideoAssets::Nes(self.palette_assets())
+        self.palette_console_video_assets_with_palette(None)
     }
 
-    fn palette(self) -> [RGB; 64] {
-        direct_rgb::PALETTE.map(RGB::from)
+    fn palette_console_video_assets_with_palette(
+        self,
+        palette: Option<&NesPalette>,
+    ) -> ConsoleVideoAssets {
+        ConsoleVideoAssets::Nes(self.palette_assets_with_palette(palette))
+    }
+
+    fn palette(self) -> [RGB; EMPHASIS_COLOR_COUNT] {
+        *NesPalette::from_base(direct_rgb::PALETTE.map(RGB::from)).colors()
     }
 }
 
@@ -341,14 +398,17 @@ impl FilterTypeExt for FilterType {
 mod tests {
     use nerust_render_ntsc::{self, NTSC_TEXTURE_HEIGHT};
     use nerust_render_traits::{
-        VideoFrameFormat,
+        PALETTE_RGBA8_LEN, VideoFrameFormat,
         filter::{BLACK_PALETTE_INDEX, FilterFunc, FilterType, PALETTE_TEXTURE_WIDTH},
         logical::LogicalSize,
         rgb::RGB,
     };
 
     use super::{ConsoleVideoAssets, VideoPresentationPipelineKind};
-    use crate::FilterTypeExt;
+    use crate::{
+        FilterTypeExt,
+        palette::{BASE_COLOR_COUNT, BuiltinPalette, NesPalette},
+    };
 
     #[test]
     fn black_palette_index_matches_ntsc_black() {
@@ -414,8 +474,13 @@ mod tests {
         ]
     }
 
-    fn collect_cpu_rgba(filter: FilterType, source: LogicalSize, source_frame: &[u8]) -> Vec<u8> {
-        let mut filter_impl = filter.generate(source);
+    fn collect_cpu_rgba(
+        filter: FilterType,
+        palette: Option<&NesPalette>,
+        source: LogicalSize,
+        source_frame: &[u16],
+    ) -> Vec<u8> {
+        let mut filter_impl = filter.generate_with_palette(source, palette);
         let logical_size = filter_impl.logical_size();
         let mut collector = RgbaCollector {
             bytes: Vec::with_capacity(logical_size.width * logical_size.height * 4),
@@ -432,7 +497,7 @@ mod tests {
         collector.bytes
     }
 
-    fn palette_index(source_frame: &[u8], source: LogicalSize, x: i32, y: usize) -> u8 {
+    fn palette_index(source_frame: &[u16], source: LogicalSize, x: i32, y: usize) -> u16 {
         if x < 0 || x >= source.width as i32 {
             return BLACK_PALETTE_INDEX;
         }
@@ -441,10 +506,11 @@ mod tests {
 
     fn simulate_gpu_ntsc_rgba(
         filter: FilterType,
+        palette: Option<&NesPalette>,
         source: LogicalSize,
-        source_frame: &[u8],
+        source_frame: &[u16],
     ) -> Vec<u8> {
-        let packed_entries = super::packed_kernel_entries(filter)
+        let packed_entries = super::packed_kernel_entries(filter, palette)
             .expect("NTSC filters should expose packed entries");
         let logical_size = filter.layout(source).logical_size;
         let entry_stride = packed_entries.len() / PALETTE_TEXTURE_WIDTH as usize;
@@ -477,9 +543,9 @@ mod tests {
 
     #[test]
     fn encoded_ntsc_textures_are_row_major_and_complete() {
-        let entries = super::shader_kernel_entries(FilterType::NtscComposite)
+        let entries = super::shader_kernel_entries(FilterType::NtscComposite, None)
             .expect("NTSC filters should expose shader entries");
-        let textures = super::encoded_ntsc_textures_rgba8(FilterType::NtscComposite)
+        let textures = super::encoded_ntsc_textures_rgba8(FilterType::NtscComposite, None)
             .expect("NTSC filters should expose encoded textures");
         let color_count = PALETTE_TEXTURE_WIDTH as usize;
         let texture_height = NTSC_TEXTURE_HEIGHT as usize;
@@ -528,9 +594,9 @@ mod tests {
 
     #[test]
     fn encoded_packed_ntsc_texture_is_row_major_big_endian_and_complete() {
-        let entries = super::packed_kernel_entries(FilterType::NtscComposite)
+        let entries = super::packed_kernel_entries(FilterType::NtscComposite, None)
             .expect("NTSC filters should expose packed entries");
-        let texture = super::encoded_packed_ntsc_texture_rgba8(FilterType::NtscComposite)
+        let texture = super::encoded_packed_ntsc_texture_rgba8(FilterType::NtscComposite, None)
             .expect("NTSC filters should expose packed textures");
         let color_count = PALETTE_TEXTURE_WIDTH as usize;
         let texture_height = NTSC_TEXTURE_HEIGHT as usize;
@@ -625,21 +691,69 @@ mod tests {
         };
         let source_frame = (0..source.height)
             .flat_map(|y| {
-                (0..source.width).map(move |x| ((x * 11 + y * 17 + x * y * 5) % 64) as u8)
+                (0..source.width).map(move |x| ((x * 11 + y * 17 + x * y * 5) % 512) as u16)
             })
             .collect::<Vec<_>>();
 
+        let measured = NesPalette::builtin(BuiltinPalette::Measured2c02);
+
         for filter in [
             FilterType::NtscRGB,
             FilterType::NtscComposite,
             FilterType::NtscSVideo,
         ] {
-            let cpu_output = collect_cpu_rgba(filter, source, &source_frame);
-            let gpu_output = simulate_gpu_ntsc_rgba(filter, source, &source_frame);
-            assert_eq!(
-                gpu_output, cpu_output,
-                "{filter:?} GPU reference diverged from CPU filter output"
-            );
+            for palette in [None, Some(&measured)] {
+                let cpu_output = collect_cpu_rgba(filter, palette, source, &source_frame);
+                let gpu_output = simulate_gpu_ntsc_rgba(filter, palette, source, &source_frame);
+                assert_eq!(
+                    gpu_output,
+                    cpu_output,
+                    "{filter:?} GPU reference diverged from CPU filter output (palette: {})",
+                    palette.is_some()
+                );
+            }
         }
     }
+
+    #[test]
+    fn selected_palette_replaces_filter_colors() {
+        let palette = NesPalette::builtin(BuiltinPalette::Rgb2c03);
+        let source = LogicalSize {
+            width: 3,
+            height: 1,
+        };
+        let output = collect_cpu_rgba(
+            FilterType::None,
+            Some(&palette),
+            source,
+            &[0x16, 0x2A, 0x0F],
+        );
+        assert_eq!(output, [255, 0, 0, 255, 0, 255, 0, 255, 0, 0, 0, 255]);
+
+        // The emphasis bits above the index pick the palette's emphasis rows
+        let output = collect_cpu_rgba(
+            FilterType::None,
+            Some(&palette),
+            source,
+            &[0x16 | 4 << 6, 0x2A | 1 << 6, 0x0F],
+        );
+        assert_eq!(output, [255, 0, 255, 255, 255, 255, 0, 255, 0, 0, 0, 255]);
+
+        let assets = FilterType::None.palette_assets_with_palette(Some(&palette));
+        assert_eq!(assets.palette_rgba8().len(), PALETTE_RGBA8_LEN);
+        assert_eq!(
+            &assets.palette_rgba8()[0x16 * 4..0x16 * 4 + 4],
+            [255, 0, 0, 255]
+        );
+        let red_emphasis = (BASE_COLOR_COUNT + 0x16) * 4;
+        assert_eq!(
+            &assets.palette_rgba8()[red_emphasis..red_emphasis + 4],
+            [255, 0, 0, 255]
+        );
+
+        // The NTSC kernels are built from the palette instead of the generated colors
+        let generated = super::packed_kernel_entries(FilterType::NtscRGB, None).unwrap();
+        let replaced = super::packed_kernel_entries(FilterType::NtscRGB, Some(&palette)).unwrap();
+        assert_ne!(generated, replaced);
+    }
 }
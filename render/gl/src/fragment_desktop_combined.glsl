@@ -11,7 +11,9 @@ uint palette_index(ivec2 pos) {
     if (pos.x < 0 || pos.y < 0 || pos.x >= int(source_size.x) || pos.y >= int(source_size.y)) {
         return 15u;
     }
-    return uint(round(texelFetch(frame_texture, pos, 0).r * 255.0));
+    // RG8 の 2 バイトから u16 LE の palette entry (強調ビット込み) を復元
+    vec2 entry = round(texelFetch(frame_texture, pos, 0).rg * 255.0);
+    return uint(entry.r) + uint(entry.g) * 256u;
 }
 
 vec3 palette_color(uint index) {
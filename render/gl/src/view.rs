@@ -2,8 +2,10 @@ use std::{os::raw::c_void, ptr, rc::Rc};
 
 use gl::types::GLint;
 use nerust_glwrap::{Shader, raw::*, vertex::*};
-use nerust_render_ntsc::NTSC_TEXTURE_HEIGHT;
-use nerust_render_traits::{VideoFrameFormat, VideoRenderProfile};
+use nerust_render_ntsc::{NTSC_TEXTURE_HEIGHT, NTSC_TEXTURE_WIDTH};
+use nerust_render_traits::{
+    PALETTE_RGBA8_LEN, VideoFrameFormat, VideoRenderProfile, filter::PALETTE_TEXTURE_WIDTH,
+};
 
 use crate::{mat4::Mat4, vec2d::Vec2D, vertex_data::VertexData};
 
@@ -87,16 +89,16 @@ impl GlView {
         self.logical_width = frame_size.width as i32;
         self.logical_height = frame_size.height as i32;
 
-        let bpp: usize = if self.is_palette_format { 1 } else { 4 };
+        let bpp: usize = if self.is_palette_format { 2 } else { 4 };
         let shader = compile_shader_program(self.is_palette_format);
         shader.use_program();
         clear_color(0.0, 0.0, 0.0, 1.0).unwrap();
 
         pixel_storei(gl::UNPACK_ALIGNMENT, 1).unwrap();
 
-        // frame texture (palette 時は R8、RGBA 時は RGBA)
+        // frame texture (palette 時は u16 LE の palette entry を RG8、RGBA 時は RGBA)
         let (internal_fmt, data_fmt) = if self.is_palette_format {
-            (gl::R8 as GLint, gl::RED)
+            (gl::RG8 as GLint, gl::RG)
         } else {
             (gl::RGBA as GLint, gl::RGBA)
         };
@@ -113,10 +115,10 @@ impl GlView {
             allocate(frame_size.width * frame_size.height * bpp).as_ref(),
         );
 
-        // palette texture: 常に 64x1 RGBA8、ゼロ初期化。
+        // palette texture: 常に 512x1 RGBA8 (強調ビット込み)、ゼロ初期化。
         // 実データは render 時に FrameBuffer.palette_as_rgba8() から同期される。
         if self.is_palette_format {
-            self.palette_width = 64;
+            self.palette_width = PALETTE_TEXTURE_WIDTH as i32;
             self.palette_height = 1;
             let palette_data =
                 vec![0u8; self.palette_width as usize * self.palette_height as usize * 4];
@@ -144,7 +146,7 @@ impl GlView {
                 configure_ntsc_texture(
                     2,
                     self.ntsc_texture,
-                    64,
+                    NTSC_TEXTURE_WIDTH as usize,
                     NTSC_TEXTURE_HEIGHT as usize,
                     ntsc_data,
                 );
@@ -155,11 +157,11 @@ impl GlView {
                 gen_textures(1, ntsc_names.as_mut_ptr()).unwrap();
                 self.ntsc_texture = ntsc_names[0];
                 let ntsc_height = NTSC_TEXTURE_HEIGHT as usize;
-                let dummy = vec![0u8; 64 * ntsc_height * 4];
+                let dummy = vec![0u8; NTSC_TEXTURE_WIDTH as usize * ntsc_height * 4];
                 configure_frame_texture(
                     2,
                     self.ntsc_texture,
-                    64,
+                    NTSC_TEXTURE_WIDTH as usize,
                     ntsc_height,
                     gl::RGBA as GLint,
                     gl::RGBA,
@@ -256,7 +258,7 @@ impl GlView {
 
     /// PaletteIndex 形式のパレットデータを palette texture にアップロードする。
     /// `on_update()` の前に呼ばれることを想定。
-    pub fn update_palette_texture(&self, rgba8: &[u8; 256]) {
+    pub fn update_palette_texture(&self, rgba8: &[u8; PALETTE_RGBA8_LEN]) {
         if !self.is_palette_format {
             return;
         }
@@ -289,7 +291,7 @@ impl GlView {
             bind_texture(gl::TEXTURE_2D, self.palette_texture).unwrap();
             active_texture(gl::TEXTURE2).unwrap();
             bind_texture(gl::TEXTURE_2D, self.ntsc_texture).unwrap();
-            // frame texture (palette index を R8 → GL_RED で upload)
+            // frame texture (palette entry を RG8 → GL_RG で upload)
             active_texture(gl::TEXTURE0).unwrap();
             tex_sub_image_2d(
                 gl::TEXTURE_2D,
@@ -298,7 +300,7 @@ impl GlView {
                 0,
                 self.logical_width,
                 self.logical_height,
-                gl::RED,
+                gl::RG,
                 gl::UNSIGNED_BYTE,
                 screen_ptr,
             )
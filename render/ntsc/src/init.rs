@@ -1,8 +1,9 @@
 use std::f32;
 
 use crate::{
-    ARTIFACTS_MAX, ARTIFACTS_MID, BURST_COUNT, DEFAULT_DECODER, FRINGING_MAX, FRINGING_MID,
-    KERNEL_HALF, KERNEL_SIZE, LUMA_CUTOFF, RESCALE_IN, RESCALE_OUT, STD_DECODER_HUE, rotate_iq,
+    ARTIFACTS_MAX, ARTIFACTS_MID, BURST_COUNT, DEFAULT_DECODER, EXT_DECODER_HUE, FRINGING_MAX,
+    FRINGING_MID, KERNEL_HALF, KERNEL_SIZE, LUMA_CUTOFF, RESCALE_IN, RESCALE_OUT, STD_DECODER_HUE,
+    rotate_iq,
     setup::{Setup, SetupValues as _},
 };
 
@@ -17,7 +18,7 @@ pub(crate) struct Init {
 }
 
 impl Init {
-    pub(crate) fn new(setup: &Setup) -> Self {
+    pub(crate) fn new(setup: &Setup, external_palette: bool) -> Self {
         // let brightness = setup.brightness as f32 * (RGB_UNIT >> 1) as f32 + RGB_OFFSET;
         // let contrast = (setup.contrast as f32 + 1.0) * (RGB_UNIT >> 1) as f32;
 
@@ -51,7 +52,14 @@ impl Init {
 
         // setup decoder matricies
         let to_rgb = {
-            let hue = (setup.hue() * f32::consts::PI) + (f32::consts::PI / 180.0 * STD_DECODER_HUE);
+            // The standard hue offset only suits the generated colors; an RGB palette is
+            // already decoded.
+            let decoder_hue = if external_palette {
+                EXT_DECODER_HUE
+            } else {
+                STD_DECODER_HUE
+            };
+            let hue = (setup.hue() * f32::consts::PI) + (f32::consts::PI / 180.0 * decoder_hue);
             let sat = setup.saturation() + 1.0;
 
             let s = hue.sin() * sat;
@@ -6,7 +6,8 @@ use self::{
     setup::{Setup, SetupValues},
 };
 
-const NES_NTSC_PALETTE_SIZE: usize = 64;
+/// 64 colors times the eight combinations of the `$2001` emphasis bits (nes_ntsc_emph).
+const NES_NTSC_PALETTE_SIZE: usize = 512;
 const NES_NTSC_ENTRY_SIZE: usize = 128;
 
 const ALIGNMENT_COUNT: usize = 3;
@@ -21,7 +22,7 @@ const FRINGING_MID: f32 = 1.0;
 const FRINGING_MAX: f32 = FRINGING_MID * 2.0;
 
 const STD_DECODER_HUE: f32 = -15.0;
-// const EXT_DECODER_HUE: f32 = STD_DECODER_HUE + 15.0;
+const EXT_DECODER_HUE: f32 = STD_DECODER_HUE + 15.0;
 const LUMA_CUTOFF: f32 = 0.20;
 
 const KERNEL_HALF: usize = 16;
@@ -46,7 +47,7 @@ const RGB_BIAS: u32 = RGB_UNIT as u32 * 2 * NES_NTSC_RGB_BUILDER;
 const NES_NTSC_IN_CHUNK: usize = 3;
 const NES_NTSC_OUT_CHUNK: usize = 7;
 const NES_NTSC_BURST_COUNT: usize = 3;
-const NES_NTSC_BLACK: u8 = 15;
+const NES_NTSC_BLACK: u16 = 15;
 const NES_NTSC_BURST_SIZE: usize = NES_NTSC_ENTRY_SIZE / NES_NTSC_BURST_COUNT;
 // const NES_NTSC_OUT_DEPTH: usize = 24;
 
@@ -92,7 +93,7 @@ fn rotate_iq(iq: (f32, f32), sin_b: f32, cos_b: f32) -> (f32, f32) {
 
 pub use nerust_render_traits::rgb::RGB;
 
-pub const BLACK: u8 = NES_NTSC_BLACK;
+pub const BLACK: u16 = NES_NTSC_BLACK;
 pub const SHADER_COLOR_COUNT: usize = NES_NTSC_PALETTE_SIZE;
 pub const SHADER_PHASE_COUNT: usize = NES_NTSC_BURST_COUNT;
 pub const SHADER_PHASE_ENTRY_COUNT: usize = NES_NTSC_BURST_SIZE;
@@ -179,11 +180,20 @@ impl Engine {
         };
 
         let sat = (high - low) * 0.5;
+        let (y, i, q) = Self::emphasize(
+            entry >> 6,
+            color,
+            high,
+            (
+                (high + low) * 0.5,
+                Color(color).to_angle_sin() * sat,
+                Color(color).to_angle_cos() * sat,
+            ),
+        );
         let yiq = (
-            ((high + low) * 0.5) * (setup.contrast() * 0.5 + 1.0) + setup.brightness() * 0.5
-                - 0.5 / 256.0,
-            Color(color).to_angle_sin() * sat,
-            Color(color).to_angle_cos() * sat,
+            y * (setup.contrast() * 0.5 + 1.0) + setup.brightness() * 0.5 - 0.5 / 256.0,
+            i,
+            q,
         );
         let (r, g, b) = Self::yiq_to_rgb_f32(yiq, &DEFAULT_DECODER);
         let yiq = Self::rgb_to_yiq_f32(
@@ -198,6 +208,59 @@ impl Engine {
         )
     }
 
+    // The `$2001` emphasis bits attenuate the signal during the hues they do not select.
+    fn emphasize(tint: usize, color: usize, high: f32, yiq: (f32, f32, f32)) -> (f32, f32, f32) {
+        const ATTEN_MUL: f32 = 0.79399;
+        const ATTEN_SUB: f32 = 0.078_283_8;
+        const TINTS: [usize; 8] = [0, 6, 10, 8, 2, 4, 0, 0];
+
+        let (mut y, mut i, mut q) = yiq;
+        if tint == 0 || color > 0x0D {
+            return yiq;
+        }
+        if tint == 7 {
+            y = y * (ATTEN_MUL * 1.13) - ATTEN_SUB * 1.13;
+        } else {
+            let mut sat = high * (0.5 - ATTEN_MUL * 0.5) + ATTEN_SUB * 0.5;
+            y -= sat * 0.5;
+            if tint >= 3 && tint != 4 {
+                // Two bits at once
+                sat *= 0.6;
+                y -= sat;
+            }
+            i += Color(TINTS[tint]).to_angle_sin() * sat;
+            q += Color(TINTS[tint]).to_angle_cos() * sat;
+        }
+        (y, i, q)
+    }
+
+    // An RGB palette replaces the color generation. The colors are taken as-is, so only the
+    // signal simulation (hue, saturation, artifacts, fringing and blur) applies to them.
+    fn palette_yiq(color: RGB) -> (f32, f32, f32) {
+        let yiq = Self::rgb_to_yiq_f32(
+            f32::from(color.red) / 255.0,
+            f32::from(color.green) / 255.0,
+            f32::from(color.blue) / 255.0,
+        );
+        (
+            yiq.0 * RGB_UNIT as f32 + RGB_OFFSET,
+            yiq.1 * RGB_UNIT as f32,
+            yiq.2 * RGB_UNIT as f32,
+        )
+    }
+
+    fn color_yiq(
+        setup: &Setup,
+        gamma_factor: f32,
+        base_palette: Option<&[RGB; NES_NTSC_PALETTE_SIZE]>,
+        entry: usize,
+    ) -> (f32, f32, f32) {
+        match base_palette {
+            Some(palette) => Self::palette_yiq(palette[entry]),
+            None => Self::entry_yiq(setup, gamma_factor, entry),
+        }
+    }
+
     fn yiq_to_rgb_f32(yiq: (f32, f32, f32), to_rgb: &[f32]) -> (f32, f32, f32) {
         (
             yiq.0 + to_rgb[0] * yiq.1 + to_rgb[1] * yiq.2,
@@ -481,14 +544,18 @@ impl Engine {
         }
     }
 
-    pub fn shader_kernel_entries(setup: &Setup) -> Box<[ShaderKernelEntry]> {
-        let filter_impl = Init::new(setup);
+    /// Kernel table for the shader. `base_palette` replaces the generated 512 colors.
+    pub fn shader_kernel_entries(
+        setup: &Setup,
+        base_palette: Option<&[RGB; NES_NTSC_PALETTE_SIZE]>,
+    ) -> Box<[ShaderKernelEntry]> {
+        let filter_impl = Init::new(setup, base_palette.is_some());
         let gamma_factor = Self::gamma_factor(setup);
         let merge_fields = Self::merge_fields(setup);
 
         (0..NES_NTSC_PALETTE_SIZE)
             .flat_map(|entry| {
-                let yiq = Self::entry_yiq(setup, gamma_factor, entry);
+                let yiq = Self::color_yiq(setup, gamma_factor, base_palette, entry);
 
                 let (r, g, b) = Self::yiq_to_rgb_f32(yiq, &filter_impl.to_rgb[0]);
                 let color = ShaderKernelEntryI32 {
@@ -515,14 +582,18 @@ impl Engine {
             .into_boxed_slice()
     }
 
-    pub fn packed_kernel_entries(setup: &Setup) -> Box<[u32]> {
-        let filter_impl = Init::new(setup);
+    /// Kernel table for the CPU filter. `base_palette` replaces the generated 512 colors.
+    pub fn packed_kernel_entries(
+        setup: &Setup,
+        base_palette: Option<&[RGB; NES_NTSC_PALETTE_SIZE]>,
+    ) -> Box<[u32]> {
+        let filter_impl = Init::new(setup, base_palette.is_some());
         let gamma_factor = Self::gamma_factor(setup);
         let merge_fields = Self::merge_fields(setup);
 
         (0..NES_NTSC_PALETTE_SIZE)
             .flat_map(|entry| {
-                let yiq = Self::entry_yiq(setup, gamma_factor, entry);
+                let yiq = Self::color_yiq(setup, gamma_factor, base_palette, entry);
                 let (r, g, b) = Self::yiq_to_rgb_f32(yiq, &filter_impl.to_rgb[0]);
                 let rgb = Self::pack_rgb(
                     r as u32,
@@ -541,8 +612,28 @@ impl Engine {
             .into_boxed_slice()
     }
 
-    pub fn new(setup: &Setup, width: usize) -> Self {
-        let ktable = Self::packed_kernel_entries(setup);
+    /// The 512 colors the signal decodes to without artifacts (nes_ntsc's `palette_out`).
+    pub fn rgb_palette(setup: &Setup) -> [RGB; NES_NTSC_PALETTE_SIZE] {
+        let filter_impl = Init::new(setup, false);
+        let gamma_factor = Self::gamma_factor(setup);
+        let to_u8 = |value: f32| (value - (RGB_OFFSET - 0.5)).clamp(0.0, 255.0) as u8;
+        std::array::from_fn(|entry| {
+            let yiq = Self::entry_yiq(setup, gamma_factor, entry);
+            let (r, g, b) = Self::yiq_to_rgb_f32(yiq, &filter_impl.to_rgb[0]);
+            RGB {
+                red: to_u8(r),
+                green: to_u8(g),
+                blue: to_u8(b),
+            }
+        })
+    }
+
+    pub fn new(
+        setup: &Setup,
+        base_palette: Option<&[RGB; NES_NTSC_PALETTE_SIZE]>,
+        width: usize,
+    ) -> Self {
+        let ktable = Self::packed_kernel_entries(setup, base_palette);
         Self {
             width,
             burst: 0,
@@ -557,7 +648,7 @@ impl Engine {
     }
 
     #[inline]
-    fn rgb_out<F: FnMut(RGB)>(&mut self, in_chunk: usize, value: u8, next_func: &mut F) {
+    fn rgb_out<F: FnMut(RGB)>(&mut self, in_chunk: usize, value: u16, next_func: &mut F) {
         match in_chunk {
             0 => {
                 self.row.color_in(0, value);
@@ -580,7 +671,7 @@ impl Engine {
     }
 
     #[inline]
-    pub fn push<F: FnMut(RGB)>(&mut self, value: u8, next_func: &mut F) {
+    pub fn push<F: FnMut(RGB)>(&mut self, value: u16, next_func: &mut F) {
         if self.row_pos == 0 {
             self.row
                 .update(self.burst, NES_NTSC_BLACK, NES_NTSC_BLACK, value);
@@ -628,9 +719,9 @@ impl NtscRow {
     // NES_NTSC_BEGIN_ROW
     pub(crate) fn new(
         burst: usize,
-        pixel0: u8,
-        pixel1: u8,
-        pixel2: u8,
+        pixel0: u16,
+        pixel1: u16,
+        pixel2: u16,
         ktable: Box<[u32]>,
     ) -> Self {
         let ktable_offset = burst * NES_NTSC_BURST_SIZE;
@@ -650,7 +741,7 @@ impl NtscRow {
     }
 
     #[inline]
-    pub(crate) fn update(&mut self, burst: usize, pixel0: u8, pixel1: u8, pixel2: u8) {
+    pub(crate) fn update(&mut self, burst: usize, pixel0: u16, pixel1: u16, pixel2: u16) {
         let ktable_offset = burst * NES_NTSC_BURST_SIZE;
         let kernel0 = Self::entry_impl(ktable_offset, pixel0);
         self.kernelx = [0, kernel0, kernel0];
@@ -664,7 +755,7 @@ impl NtscRow {
 
     // NES_NTSC_ENTRY_
     #[inline]
-    fn entry_impl(ktable_offset: usize, n: u8) -> usize {
+    fn entry_impl(ktable_offset: usize, n: u16) -> usize {
         ktable_offset + usize::from(n) * NES_NTSC_ENTRY_SIZE
     }
 
@@ -719,7 +810,7 @@ impl NtscRow {
 
     // NES_NTSC_COLOR_IN
     #[inline]
-    pub(crate) fn color_in(&mut self, in_index: usize, color_in: u8) {
+    pub(crate) fn color_in(&mut self, in_index: usize, color_in: u16) {
         self.kernelx[in_index] = self.kernel[in_index];
         self.kernel[in_index] = Self::entry_impl(self.ktable_offset, color_in);
     }
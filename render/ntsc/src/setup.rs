@@ -39,13 +39,9 @@ pub(crate) trait SetupValues {
 
     // decoder_matrix: &[f32; 6], // optional RGB decoder matrix, 6 elements
 
-    // palette_out: &mut [u8], // optional RGB palette out, 3 bytes per color
-
-    // // You can replace the standard NES color generation with an RGB palette. The
-    // // first replaces all color generation, while the second replaces only the core
-    // // 64-color generation and does standard color emphasis calculations on it.
-    // palette: &[u8; 512 * 3], // optional 512-entry RGB palette in, 3 bytes per color
-    // base_palette: &[u8; 64 * 3], // optional 64-entry RGB palette in, 3 bytes per color
+    // The RGB palette in/out of nes_ntsc are `Engine::rgb_palette` and the `base_palette`
+    // argument of the kernel builders. Frames carry no emphasis bits, so only the core
+    // 64 colors are replaced.
 }
 
 impl SetupValues for Setup {
@@ -3,7 +3,7 @@ use std::num::NonZeroU32;
 use log::{error, warn};
 use nerust_render_traits::{
     FrameBuffer, PixelFormat, SurfaceSize, VideoRenderProfile,
-    filter::BLACK_PALETTE_INDEX,
+    filter::{BLACK_PALETTE_INDEX, PALETTE_TEXTURE_WIDTH},
     renderer::{GpuFactory, GpuRenderer, OpaqueError, RenderResult, RendererConfig, RendererError},
 };
 use raw_window_handle::{
@@ -390,11 +390,15 @@ impl SoftbufferRenderer {
         }
     }
 
-    fn palette_index(source_frame: &[u8], width: usize, x: i32, y: usize) -> u8 {
+    fn palette_entry(source_frame: &[u8], index: usize) -> u16 {
+        u16::from_le_bytes([source_frame[index * 2], source_frame[index * 2 + 1]])
+    }
+
+    fn palette_index(source_frame: &[u8], width: usize, x: i32, y: usize) -> u16 {
         if x < 0 || x >= width as i32 {
             return BLACK_PALETTE_INDEX;
         }
-        source_frame[y * width + x as usize]
+        Self::palette_entry(source_frame, y * width + x as usize)
     }
 
     fn clamp_impl(io: u32) -> u32 {
@@ -411,9 +415,8 @@ impl SoftbufferRenderer {
         (rgb << 8) | 0xff
     }
 
-    fn read_entry(buf: &[u8], color: u8, row: usize) -> u32 {
-        const PALETTE_WIDTH: usize = 64;
-        let offset = (row * PALETTE_WIDTH + color as usize) * 4;
+    fn read_entry(buf: &[u8], color: u16, row: usize) -> u32 {
+        let offset = (row * PALETTE_TEXTURE_WIDTH as usize + usize::from(color)) * 4;
         u32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap())
     }
 
@@ -577,11 +580,11 @@ impl GpuRenderer for SoftbufferRenderer {
                 } else {
                     Self::rendering(
                         dst,
-                        src_stride,
+                        src_stride / frame.format().bytes_per_pixel(),
                         src_h,
                         dst_w,
                         dst_h,
-                        move |i| palette[src[i] as usize].to_le_bytes(),
+                        move |i| palette[usize::from(Self::palette_entry(src, i))].to_le_bytes(),
                         &self.lut,
                         &mut self.resize_buffer,
                     );
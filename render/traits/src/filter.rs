@@ -1,10 +1,11 @@
 use crate::{LogicalSize, PhysicalSize, RGB};
 
-pub const BLACK_PALETTE_INDEX: u8 = 0x0F;
-pub const PALETTE_TEXTURE_WIDTH: u32 = 64;
+pub const BLACK_PALETTE_INDEX: u16 = 0x0F;
+/// Palette entries: 64 colors times the eight combinations of the `$2001` emphasis bits.
+pub const PALETTE_TEXTURE_WIDTH: u32 = 512;
 
 pub trait VideoFilter: Send {
-    fn push(&mut self, value: u8, filter_func: &mut dyn FilterFunc);
+    fn push(&mut self, value: u16, filter_func: &mut dyn FilterFunc);
 
     fn logical_size(&self) -> LogicalSize;
     fn physical_size(&self) -> PhysicalSize;
@@ -77,8 +78,8 @@ impl<T: FilterUnit, U: FilterUnit<Input = T::Output>> FilterUnit for Combine<T,
     }
 }
 
-impl<F: FilterUnit<Input = u8, Output = RGB>> VideoFilter for F {
-    fn push(&mut self, value: u8, filter_func: &mut dyn FilterFunc) {
+impl<F: FilterUnit<Input = u16, Output = RGB>> VideoFilter for F {
+    fn push(&mut self, value: u16, filter_func: &mut dyn FilterFunc) {
         FilterUnit::push(self, value, &mut |x| filter_func.filter_func(x))
     }
 
@@ -4,7 +4,14 @@ pub mod physical;
 pub mod renderer;
 pub mod rgb;
 
-use crate::{logical::LogicalSize, physical::PhysicalSize, rgb::RGB};
+use crate::{
+    filter::PALETTE_TEXTURE_WIDTH, logical::LogicalSize, physical::PhysicalSize, rgb::RGB,
+};
+
+/// PaletteIndex 形式の 1 ピクセルのバイト数。
+const PIXEL_BYTES: usize = 2;
+/// [`FrameBuffer::palette_as_rgba8`] のバイト数。
+pub const PALETTE_RGBA8_LEN: usize = PALETTE_TEXTURE_WIDTH as usize * 4;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub struct SurfaceSize {
@@ -28,7 +35,7 @@ impl VideoFrameFormat {
     pub fn bytes_per_pixel(&self) -> usize {
         match self {
             VideoFrameFormat::Rgba => 4,
-            VideoFrameFormat::Palette => 1,
+            VideoFrameFormat::Palette => 2,
         }
     }
 }
@@ -133,9 +140,10 @@ pub enum PixelFormat {
     /// 4 bytes/pixel, RGBA各8bit. GPUにそのまま転送.
     Rgba,
 
-    /// 1 byte/pixel, palette index + palette LUT.
+    /// 2 bytes/pixel (little endian), palette entry + palette LUT.
+    /// 下位 6bit が palette index、その上の 3bit が `$2001` の強調ビット。
     PaletteIndex {
-        /// RGBA palette entries (u32 = 0xRRGGBBAA)
+        /// RGBA palette entries (u32 = 0xRRGGBBAA), `PALETTE_TEXTURE_WIDTH` 個
         palette: Box<[u32]>,
     },
 }
@@ -144,7 +152,7 @@ impl PixelFormat {
     pub fn bytes_per_pixel(&self) -> usize {
         match self {
             PixelFormat::Rgba => 4,
-            PixelFormat::PaletteIndex { .. } => 1,
+            PixelFormat::PaletteIndex { .. } => 2,
         }
     }
 }
@@ -192,10 +200,10 @@ impl FrameBuffer {
         self.data.resize(self.stride * height, 0);
     }
 
-    /// PPU が palette index を 1 ピクセル書き込む。
+    /// PPU が palette entry を 1 ピクセル書き込む。
     /// バッファ不足時は警告ログを出力して無視する。
-    pub fn push(&mut self, value: u8) {
-        if self.cursor >= self.data.len() {
+    pub fn push(&mut self, value: u16) {
+        if self.cursor + PIXEL_BYTES > self.data.len() {
             log::warn!(
                 "FrameBuffer::push: cursor {} out of bounds (len {})",
                 self.cursor,
@@ -203,14 +211,14 @@ impl FrameBuffer {
             );
             return;
         }
-        self.data[self.cursor] = value;
-        self.cursor += 1;
+        self.data[self.cursor..self.cursor + PIXEL_BYTES].copy_from_slice(&value.to_le_bytes());
+        self.cursor += PIXEL_BYTES;
     }
 
-    /// PPU が同一 palette index を連続書き込みする。
+    /// PPU が同一 palette entry を連続書き込みする。
     /// バッファ不足時は警告ログを出力して無視する。
-    pub fn push_many(&mut self, value: u8, count: u16) {
-        let end = self.cursor + count as usize;
+    pub fn push_many(&mut self, value: u16, count: u16) {
+        let end = self.cursor + count as usize * PIXEL_BYTES;
         if end > self.data.len() {
             log::warn!(
                 "FrameBuffer::push_many: cursor {} + count {} out of bounds (len {})",
@@ -220,10 +228,32 @@ impl FrameBuffer {
             );
             return;
         }
-        self.data[self.cursor..end].fill(value);
+        let bytes = value.to_le_bytes();
+        for pixel in self.data[self.cursor..end].chunks_exact_mut(PIXEL_BYTES) {
+            pixel.copy_from_slice(&bytes);
+        }
         self.cursor = end;
     }
 
+    /// (x, y) の palette entry。範囲外なら None。
+    pub fn pixel(&self, x: usize, y: usize) -> Option<u16> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let offset = y * self.stride + x * PIXEL_BYTES;
+        let bytes = self.data.get(offset..offset + PIXEL_BYTES)?;
+        Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// 全ピクセルの palette entry を行順に返す。
+    pub fn pixels(&self) -> impl Iterator<Item = u16> + '_ {
+        self.data
+            .chunks(self.stride.max(1))
+            .take(self.height)
+            .flat_map(move |row| row.chunks_exact(PIXEL_BYTES).take(self.width))
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
     /// フレーム完了を通知する（cursor を先頭に戻す）。
     pub fn render(&mut self) {
         self.cursor = 0;
@@ -273,12 +303,16 @@ impl FrameBuffer {
         }
     }
 
-    /// 先頭64エントリを RGBA8 バイト列 (256B) に変換。GPU upload 用。
+    /// 先頭 `PALETTE_TEXTURE_WIDTH` エントリを RGBA8 バイト列に変換。GPU upload 用。
     /// PaletteIndex 形式以外の場合は None。
-    pub fn palette_as_rgba8(&self) -> Option<[u8; 256]> {
+    pub fn palette_as_rgba8(&self) -> Option<[u8; PALETTE_RGBA8_LEN]> {
         let palette = self.palette()?;
-        let mut rgba8 = [0u8; 256];
-        for (i, &color) in palette.iter().enumerate().take(64) {
+        let mut rgba8 = [0u8; PALETTE_RGBA8_LEN];
+        for (i, &color) in palette
+            .iter()
+            .enumerate()
+            .take(PALETTE_TEXTURE_WIDTH as usize)
+        {
             let pos = i * 4;
             rgba8[pos] = (color >> 24) as u8; // R
             rgba8[pos + 1] = (color >> 16) as u8; // G
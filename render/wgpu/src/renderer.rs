@@ -1,7 +1,9 @@
 mod draw;
 mod setup;
 
-use nerust_render_traits::{SurfaceSize, logical::LogicalSize, physical::PhysicalSize};
+use nerust_render_traits::{
+    PALETTE_RGBA8_LEN, SurfaceSize, logical::LogicalSize, physical::PhysicalSize,
+};
 use wgpu::{BindGroup, Buffer, Device, Limits, Queue, SurfaceConfiguration, Texture};
 
 use crate::upload::FrameUploadLayout;
@@ -94,7 +96,7 @@ impl RenderPipeline {
     /// PaletteIndex 形式の FrameBuffer からパレットデータを palette texture に書き込む。
     /// `render()` の前に呼ばれることを想定。
     /// palette の width/height は texture 作成時の値から自動的に決まる。
-    pub fn update_palette_texture(&self, rgba8: &[u8; 256]) {
+    pub fn update_palette_texture(&self, rgba8: &[u8; PALETTE_RGBA8_LEN]) {
         self.queue.write_texture(
             wgpu::TexelCopyTextureInfo {
                 texture: &self.palette_texture,
@@ -110,7 +110,7 @@ impl RenderPipeline {
             usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
             view_formats: &[],
         });
-        // Palette texture: 常に 64x1 RGBA8、ゼロ初期化。
+        // Palette texture: 常に 512x1 RGBA8 (強調ビット込み)、ゼロ初期化。
         // 実データは render 時に FrameBuffer.palette_as_rgba8() から同期される。
         let palette_size = Extent3d {
             width: PALETTE_TEXTURE_WIDTH,
@@ -468,14 +468,14 @@ fn frame_pipeline_kind(
 fn frame_bytes_per_pixel(kind: FramePipelineKind) -> u32 {
     match kind {
         FramePipelineKind::DirectColor => 4,
-        FramePipelineKind::Palette | FramePipelineKind::Ntsc => 1,
+        FramePipelineKind::Palette | FramePipelineKind::Ntsc => 2,
     }
 }
 
 fn frame_texture_format(kind: FramePipelineKind) -> TextureFormat {
     match kind {
         FramePipelineKind::DirectColor => TextureFormat::Rgba8Uint,
-        FramePipelineKind::Palette | FramePipelineKind::Ntsc => TextureFormat::R8Uint,
+        FramePipelineKind::Palette | FramePipelineKind::Ntsc => TextureFormat::R16Uint,
     }
 }
 
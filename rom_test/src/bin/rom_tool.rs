@@ -2,10 +2,12 @@ use std::path::PathBuf;
 
 use clap::{Arg, ArgAction, ArgMatches, Command};
 use nerust_rom_test::{
-    manifest::{RomManifest, load_default_manifest, load_manifest},
+    manifest::{RomCase, RomManifest, load_default_manifest, load_manifest},
+    ppu::dump_ppu,
     report::{default_output_root, write_html_report},
     results::{CaseOutcome, ValidationOptions},
     runner::validate_case,
+    trace::trace_case,
 };
 
 pub fn main() {
@@ -46,6 +48,42 @@ fn run() -> Result<(), String> {
                 .about("Capture actual hashes and screenshots without asserting"),
         )
         .subcommand(Command::new("list").about("List configured ROM cases"))
+        .subcommand(
+            Command::new("trace")
+                .about("Write a nestest.log-style CPU trace for one ROM case")
+                .arg(
+                    Arg::new("frames")
+                        .long("frames")
+                        .value_name("N")
+                        .required(true),
+                )
+                .arg(Arg::new("output").long("output").value_name("PATH")),
+        )
+        .subcommand(
+            Command::new("ppu")
+                .about(
+                    "Dump nametables, pattern tables, sprites and palette of one ROM case as PNG",
+                )
+                .arg(
+                    Arg::new("frames")
+                        .long("frames")
+                        .value_name("N")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("scanline")
+                        .long("scanline")
+                        .value_name("LINE")
+                        .default_value("241"),
+                )
+                .arg(
+                    Arg::new("palette")
+                        .long("palette")
+                        .value_name("0-7")
+                        .default_value("0"),
+                )
+                .arg(Arg::new("output").long("output").value_name("DIR")),
+        )
         .get_matches();
 
     let manifest = matches
@@ -95,7 +133,13 @@ fn run() -> Result<(), String> {
             }
             Ok(())
         }
-        _ => Err("subcommand required: validate, capture, or list".to_string()),
+        Some(("trace", subcommand_matches)) => {
+            run_trace(&manifest, &case_ids, perf_only, subcommand_matches)
+        }
+        Some(("ppu", subcommand_matches)) => {
+            run_ppu_dump(&manifest, &case_ids, perf_only, subcommand_matches)
+        }
+        _ => Err("subcommand required: validate, capture, list, trace, or ppu".to_string()),
     }
 }
 
@@ -180,6 +224,95 @@ fn run_command(
     Ok(())
 }
 
+fn run_trace(
+    manifest: &RomManifest,
+    case_ids: &[String],
+    perf_only: bool,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let frames = parse_arg::<u64>(matches, "frames")?;
+    let case = select_one(manifest, case_ids, perf_only, "trace")?;
+    let output = matches.get_one::<String>("output").map_or_else(
+        || output_dir_for(matches, "trace").join(format!("{}.log", case.id)),
+        PathBuf::from,
+    );
+
+    let summary = trace_case(case, frames, &output).map_err(|error| error.to_string())?;
+    println!(
+        "case={} frames={} steps={} instructions={} trace={}",
+        case.id,
+        summary.frames,
+        summary.steps,
+        summary.instructions,
+        output.display()
+    );
+    Ok(())
+}
+
+fn run_ppu_dump(
+    manifest: &RomManifest,
+    case_ids: &[String],
+    perf_only: bool,
+    matches: &ArgMatches,
+) -> Result<(), String> {
+    let frames = parse_arg::<u64>(matches, "frames")?;
+    let scanline = parse_arg::<u16>(matches, "scanline")?;
+    let palette = parse_arg::<u8>(matches, "palette")?;
+    if palette > 7 {
+        return Err(format!("invalid --palette value: {palette} (expected 0-7)"));
+    }
+    let case = select_one(manifest, case_ids, perf_only, "ppu")?;
+    let output_dir = matches.get_one::<String>("output").map_or_else(
+        || output_dir_for(matches, "ppu").join(&case.id),
+        PathBuf::from,
+    );
+
+    let summary = dump_ppu(case, frames, scanline, palette, &output_dir)
+        .map_err(|error| error.to_string())?;
+    println!(
+        "case={} frames={} captured_frame={} scanline={} output_dir={}",
+        case.id,
+        summary.frames,
+        summary.frame,
+        summary.scanline,
+        output_dir.display()
+    );
+    for file in &summary.files {
+        println!("  file={}", file.display());
+    }
+    Ok(())
+}
+
+fn parse_arg<T: std::str::FromStr>(matches: &ArgMatches, name: &str) -> Result<T, String>
+where
+    T::Err: std::fmt::Display,
+{
+    matches
+        .get_one::<String>(name)
+        .map(String::as_str)
+        .unwrap_or_default()
+        .parse::<T>()
+        .map_err(|error| format!("invalid --{name} value: {error}"))
+}
+
+fn select_one<'a>(
+    manifest: &'a RomManifest,
+    case_ids: &[String],
+    perf_only: bool,
+    subcommand: &str,
+) -> Result<&'a RomCase, String> {
+    let cases = manifest
+        .select(case_ids, perf_only)
+        .map_err(|error| error.to_string())?;
+    let [case] = cases.as_slice() else {
+        return Err(format!(
+            "{subcommand} needs exactly one case; {} selected (use --case ID)",
+            cases.len()
+        ));
+    };
+    Ok(*case)
+}
+
 fn print_outcome(outcome: &CaseOutcome) {
     match outcome {
         CaseOutcome::Completed(validation) => {
@@ -24,7 +24,15 @@ pub fn drive_case<H: CaseHarness>(
     case: &RomCase,
     harness: &mut H,
 ) -> Result<ExecutionTotals, RomTestError> {
-    let final_frame = case.final_frame();
+    drive_case_until(case, harness, case.final_frame())
+}
+
+/// Runs the case's events like `drive_case`, but stops after `final_frame` frames.
+pub fn drive_case_until<H: CaseHarness>(
+    case: &RomCase,
+    harness: &mut H,
+    final_frame: u64,
+) -> Result<ExecutionTotals, RomTestError> {
     let mut total_steps = 0_u64;
     let mut next_event = 0_usize;
 
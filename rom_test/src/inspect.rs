@@ -0,0 +1,123 @@
+use nerust_input_traits::{ControllerCollection, ControllerHub as _};
+use nerust_nes_core::{Core, rom_parse};
+use nerust_nes_device::famicom_set::{FamicomPadP1, FamicomPadP2};
+use nerust_render_traits::FrameBuffer;
+
+use crate::{
+    error::RomTestError,
+    events::{ButtonCode, Buttons, ControllerPad, PadState, RomAssertion},
+    harness::{CaseHarness, apply_button_state, drive_case_until},
+    manifest::RomCase,
+    media::{HashingMixer, validation_screen_buffer},
+    results::ExecutionTotals,
+};
+
+/// Plays a case's controller and reset events on a bare core for tools that look
+/// inside the core. Screen and memory checks are skipped.
+pub struct InspectRunner {
+    core: Core,
+    screen: FrameBuffer,
+    controller: ControllerCollection,
+    mixer: HashingMixer,
+    frame_counter: u64,
+    pad1: Buttons,
+    pad2: Buttons,
+    mic: bool,
+}
+
+impl InspectRunner {
+    pub fn new(case: &RomCase, rom_bytes: &[u8]) -> Result<Self, RomTestError> {
+        let cartridge_data =
+            rom_parse::parse_rom(rom_bytes).map_err(|error| RomTestError::CoreConstruction {
+                case_id: case.id.clone(),
+                message: error.to_string(),
+            })?;
+        let core =
+            Core::new_with_options(cartridge_data, case.core_options()).map_err(|error| {
+                RomTestError::CoreConstruction {
+                    case_id: case.id.clone(),
+                    message: error.to_string(),
+                }
+            })?;
+
+        Ok(Self {
+            core,
+            screen: validation_screen_buffer(),
+            controller: ControllerCollection::new(vec![
+                Box::new(FamicomPadP1::new()),
+                Box::new(FamicomPadP2::new()),
+            ]),
+            mixer: HashingMixer::new(case.audio_sample_rate()),
+            frame_counter: 0,
+            pad1: Buttons::empty(),
+            pad2: Buttons::empty(),
+            mic: false,
+        })
+    }
+
+    pub fn core(&self) -> &Core {
+        &self.core
+    }
+
+    pub fn core_mut(&mut self) -> &mut Core {
+        &mut self.core
+    }
+
+    /// Runs until `frames` frames have been emulated in total.
+    pub fn run(&mut self, case: &RomCase, frames: u64) -> Result<ExecutionTotals, RomTestError> {
+        drive_case_until(case, self, frames)
+    }
+
+    fn sync_input(&mut self) {
+        self.controller
+            .sync_input(&[self.pad1.bits(), self.pad2.bits(), self.mic as u8]);
+    }
+}
+
+impl CaseHarness for InspectRunner {
+    fn run_frame(&mut self) -> u64 {
+        let steps = self
+            .core
+            .run_frame(&mut self.screen, &mut self.controller, &mut self.mixer);
+        self.frame_counter += 1;
+        steps
+    }
+
+    fn frame_counter(&self) -> u64 {
+        self.frame_counter
+    }
+
+    fn on_assert(&mut self, _frame: u64, _assertion: &RomAssertion) -> Result<(), RomTestError> {
+        Ok(())
+    }
+
+    fn on_reset(&mut self) -> Result<(), RomTestError> {
+        self.core.reset();
+        Ok(())
+    }
+
+    fn on_standard_controller(
+        &mut self,
+        pad: ControllerPad,
+        button: ButtonCode,
+        state: PadState,
+    ) -> Result<(), RomTestError> {
+        let buttons = Buttons::from(button);
+        match pad {
+            ControllerPad::Pad1 => {
+                self.pad1 = apply_button_state(self.pad1, buttons, state);
+            }
+            ControllerPad::Pad2 => {
+                self.pad2 = apply_button_state(self.pad2, buttons, state);
+            }
+        }
+        self.sync_input();
+        Ok(())
+    }
+
+    fn on_microphone(&mut self, state: PadState) -> Result<(), RomTestError> {
+        self.mic = matches!(state, PadState::Pressed);
+        self.sync_input();
+        Ok(())
+    }
+}
@@ -1,12 +1,15 @@
 pub mod error;
 pub mod events;
 pub mod harness;
+pub mod inspect;
 pub mod manifest;
 mod media;
 pub mod perf;
+pub mod ppu;
 pub mod report;
 pub mod results;
 pub mod runner;
 mod serde_helpers;
 #[cfg(test)]
 mod tests;
+pub mod trace;
@@ -4,7 +4,10 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use nerust_nes_core::core_options::{CoreOptions, Mmc3IrqVariant};
+use nerust_nes_core::{
+    core_options::{CoreOptions, Mmc3IrqVariant},
+    status::console_type::ConsoleType,
+};
 use serde::{Deserialize, Serialize};
 
 use super::{error::RomTestError, events::RomEvent};
@@ -101,6 +104,8 @@ pub struct RomCase {
     pub sub_mapper_type: Option<u8>,
     #[serde(default)]
     pub mmc3_irq_variant: Option<Mmc3IrqVariant>,
+    #[serde(default)]
+    pub region: Option<ConsoleType>,
     pub events: Vec<RomEvent>,
     #[serde(default)]
     pub expected_audio: Option<AudioExpectation>,
@@ -182,6 +187,7 @@ impl RomCase {
     pub fn core_options(&self) -> CoreOptions {
         CoreOptions {
             mmc3_irq_variant: self.mmc3_irq_variant,
+            region: self.region,
         }
     }
 
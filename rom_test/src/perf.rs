@@ -6,7 +6,10 @@ use nerust_input_traits::{ControllerCollection, ControllerHub as _};
 use nerust_nes_core::{Core, rom_parse};
 use nerust_nes_device::famicom_set::{FamicomPadP1, FamicomPadP2};
 use nerust_render_filters::FilterTypeExt;
-use nerust_render_traits::{FrameBuffer, PixelFormat, filter::FilterType};
+use nerust_render_traits::{
+    FrameBuffer, PixelFormat,
+    filter::{FilterType, PALETTE_TEXTURE_WIDTH},
+};
 
 use crate::{
     error::RomTestError,
@@ -262,10 +265,10 @@ impl PerfRunner {
                     message: error.to_string(),
                 }
             })?;
-        let mut palette = [0u32; 256];
+        let mut palette = [0u32; PALETTE_TEXTURE_WIDTH as usize];
         let assets = FilterType::NtscComposite.palette_console_video_assets();
         let rgba8 = assets.palette_rgba8();
-        for (i, entry) in palette.iter_mut().enumerate().take(64) {
+        for (i, entry) in palette.iter_mut().enumerate() {
             let pos = i * 4;
             *entry = u32::from(rgba8[pos]) << 24
                 | u32::from(rgba8[pos + 1]) << 16
@@ -312,8 +315,11 @@ impl CaseHarness for PerfRunner {
             .core
             .run_frame(&mut self.screen, &mut self.controller, &mut self.mixer);
         // Per-frame checksum: PPU が FrameBuffer に書き込んだ全ピクセルから計算
-        for &b in self.screen.as_ref() {
-            self.checksum = self.checksum.wrapping_mul(31).wrapping_add(u64::from(b));
+        for entry in self.screen.pixels() {
+            self.checksum = self
+                .checksum
+                .wrapping_mul(31)
+                .wrapping_add(u64::from(entry));
         }
         self.frame_counter += 1;
         steps
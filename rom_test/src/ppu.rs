@@ -0,0 +1,223 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use nerust_core_traits::debug::{IndexedImage, VideoMemoryView, ViewRect};
+use nerust_nes_core::ppu_viewer::PpuSnapshot;
+use nerust_render_filters::FilterTypeExt;
+use nerust_render_traits::filter::FilterType;
+
+use crate::{
+    error::RomTestError,
+    inspect::InspectRunner,
+    manifest::{RomCase, read_rom},
+    media::encode_rgba_png,
+};
+
+/// Outline color of the scroll rectangle drawn on `nametables.png`.
+const SCROLL_OUTLINE: [u8; 4] = [0xFF, 0x30, 0x30, 0xFF];
+const PALETTE_SWATCH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PpuDumpSummary {
+    pub frames: u64,
+    /// Scanline and frame of the snapshot that was written.
+    pub scanline: u16,
+    pub frame: u64,
+    pub files: Vec<PathBuf>,
+}
+
+/// Runs `case` for `frames` frames and returns the PPU state captured the last
+/// time the beam entered `scanline`.
+pub fn capture_ppu(
+    case: &RomCase,
+    frames: u64,
+    scanline: u16,
+) -> Result<(u64, PpuSnapshot), RomTestError> {
+    let rom_bytes = read_rom(case)?;
+    let mut runner = InspectRunner::new(case, &rom_bytes)?;
+    runner.core_mut().set_ppu_capture_scanline(Some(scanline));
+    let totals = runner.run(case, frames)?;
+    Ok((totals.frames, runner.core().ppu_snapshot()))
+}
+
+/// Writes nametables, pattern tables, sprites and palette as PNG files plus an
+/// OAM listing to `output_dir`. Pattern tables use palette `pattern_palette` (0-7).
+pub fn dump_ppu(
+    case: &RomCase,
+    frames: u64,
+    scanline: u16,
+    pattern_palette: u8,
+    output_dir: &Path,
+) -> Result<PpuDumpSummary, RomTestError> {
+    let (frames, snapshot) = capture_ppu(case, frames, scanline)?;
+    let view = snapshot.video_memory_view(pattern_palette);
+    fs::create_dir_all(output_dir).map_err(|source| RomTestError::CreateDirectory {
+        path: output_dir.to_path_buf(),
+        source,
+    })?;
+
+    let colors = master_palette_rgba8();
+    let pattern_tables = side_by_side(&view.pattern_tables);
+    let sprites = sprite_sheet(&view);
+    let palette = palette_swatches(&view.palette);
+    let mut nametables = to_rgba(&view.tile_maps, &colors);
+    outline(&mut nametables, view.tile_maps.width, view.scroll);
+    let images = [
+        ("nametables.png", &view.tile_maps, nametables),
+        (
+            "pattern_tables.png",
+            &pattern_tables,
+            to_rgba(&pattern_tables, &colors),
+        ),
+        ("sprites.png", &sprites, to_rgba(&sprites, &colors)),
+        ("palette.png", &palette, to_rgba(&palette, &colors)),
+    ];
+
+    let mut files = Vec::with_capacity(images.len() + 1);
+    for (name, image, rgba) in images {
+        let png = encode_rgba_png(image.width as u32, image.height as u32, &rgba)?;
+        files.push(write_file(output_dir, name, &png)?);
+    }
+    files.push(write_file(
+        output_dir,
+        "oam.txt",
+        oam_listing(&view).as_bytes(),
+    )?);
+
+    Ok(PpuDumpSummary {
+        frames,
+        scanline: view.scanline,
+        frame: view.frame,
+        files,
+    })
+}
+
+fn write_file(output_dir: &Path, name: &str, contents: &[u8]) -> Result<PathBuf, RomTestError> {
+    let path = output_dir.join(name);
+    fs::write(&path, contents).map_err(|source| RomTestError::WriteFile {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+fn master_palette_rgba8() -> Vec<u8> {
+    FilterType::None
+        .palette_console_video_assets()
+        .palette_rgba8()
+        .to_vec()
+}
+
+fn to_rgba(image: &IndexedImage, colors: &[u8]) -> Vec<u8> {
+    image
+        .pixels
+        .iter()
+        .flat_map(|&index| {
+            let offset = usize::from(index & 0x3F) * 4;
+            [
+                colors[offset],
+                colors[offset + 1],
+                colors[offset + 2],
+                colors[offset + 3],
+            ]
+        })
+        .collect()
+}
+
+/// Draws the edge of `rect`, wrapping around the image like the scroll does.
+fn outline(rgba: &mut [u8], width: usize, rect: ViewRect) {
+    let height = rgba.len() / 4 / width;
+    let mut plot = |x: usize, y: usize| {
+        let offset = ((y % height) * width + x % width) * 4;
+        rgba[offset..offset + 4].copy_from_slice(&SCROLL_OUTLINE);
+    };
+    for dx in 0..rect.width {
+        plot(rect.x + dx, rect.y);
+        plot(rect.x + dx, rect.y + rect.height - 1);
+    }
+    for dy in 0..rect.height {
+        plot(rect.x, rect.y + dy);
+        plot(rect.x + rect.width - 1, rect.y + dy);
+    }
+}
+
+fn side_by_side(images: &[IndexedImage]) -> IndexedImage {
+    let width = images.iter().map(|image| image.width).sum();
+    let height = images.iter().map(|image| image.height).max().unwrap_or(0);
+    let mut combined = IndexedImage::new(width, height);
+    let mut origin_x = 0;
+    for image in images {
+        blit(&mut combined, image, origin_x, 0);
+        origin_x += image.width;
+    }
+    combined
+}
+
+/// All sprites in OAM order, 8 per row.
+fn sprite_sheet(view: &VideoMemoryView) -> IndexedImage {
+    let cell_width = view.sprites.first().map_or(8, |sprite| sprite.image.width);
+    let cell_height = view.sprites.first().map_or(8, |sprite| sprite.image.height);
+    let rows = view.sprites.len().div_ceil(8);
+    let mut sheet = IndexedImage::new(cell_width * 8, cell_height * rows);
+    for (index, sprite) in view.sprites.iter().enumerate() {
+        blit(
+            &mut sheet,
+            &sprite.image,
+            (index % 8) * cell_width,
+            (index / 8) * cell_height,
+        );
+    }
+    sheet
+}
+
+/// Background palettes on the top row, sprite palettes on the bottom row.
+fn palette_swatches(palette: &[u8]) -> IndexedImage {
+    let mut image = IndexedImage::new(PALETTE_SWATCH * 16, PALETTE_SWATCH * 2);
+    for (entry, &color) in palette.iter().enumerate() {
+        let origin_x = (entry % 16) * PALETTE_SWATCH;
+        let origin_y = (entry / 16) * PALETTE_SWATCH;
+        for y in 0..PALETTE_SWATCH {
+            for x in 0..PALETTE_SWATCH {
+                image.set_pixel(origin_x + x, origin_y + y, color);
+            }
+        }
+    }
+    image
+}
+
+fn blit(target: &mut IndexedImage, source: &IndexedImage, origin_x: usize, origin_y: usize) {
+    for y in 0..source.height {
+        for x in 0..source.width {
+            target.set_pixel(origin_x + x, origin_y + y, source.pixel(x, y));
+        }
+    }
+}
+
+fn oam_listing(view: &VideoMemoryView) -> String {
+    let mut listing = format!(
+        "frame={} scanline={} scroll={},{}\n",
+        view.frame, view.scanline, view.scroll.x, view.scroll.y
+    );
+    for sprite in &view.sprites {
+        let _ = writeln!(
+            listing,
+            "#{:02} x={:3} y={:3} tile=${:02X} palette={} flip={}{} priority={}",
+            sprite.index,
+            sprite.x,
+            sprite.y,
+            sprite.tile,
+            sprite.palette,
+            if sprite.flip_horizontal { 'H' } else { '-' },
+            if sprite.flip_vertical { 'V' } else { '-' },
+            if sprite.behind_background {
+                "back"
+            } else {
+                "front"
+            },
+        );
+    }
+    listing
+}
@@ -1,6 +1,6 @@
 use std::path::{Path, PathBuf};
 
-use nerust_nes_core::core_options::Mmc3IrqVariant;
+use nerust_nes_core::{core_options::Mmc3IrqVariant, trace::TraceSink};
 
 use super::{
     error::RomTestError,
@@ -11,8 +11,10 @@ use super::{
     harness::{CaseHarness, drive_case},
     manifest::{
         RomCase, RomCategory, RomManifest, apply_case_rom_overrides, default_manifest_path,
-        load_default_manifest,
+        load_default_manifest, read_rom,
     },
+    ppu::capture_ppu,
+    trace::TraceRunner,
 };
 
 #[test]
@@ -186,6 +188,7 @@ fn drive_case_dispatches_frame_zero_events() {
         perf: false,
         sub_mapper_type: None,
         mmc3_irq_variant: None,
+        region: None,
         events: vec![
             RomEvent {
                 frame: 0,
@@ -341,6 +344,7 @@ fn rom_case_builds_core_options() {
         perf: false,
         sub_mapper_type: Some(4),
         mmc3_irq_variant: Some(Mmc3IrqVariant::Nec),
+        region: None,
         events: vec![RomEvent {
             frame: 1,
             kind: RomEventKind::CheckScreen { hash: 1 },
@@ -390,6 +394,7 @@ fn submapper_override_promotes_rom_header_in_memory() {
         perf: false,
         sub_mapper_type: Some(1),
         mmc3_irq_variant: None,
+        region: None,
         events: vec![RomEvent {
             frame: 1,
             kind: RomEventKind::CheckScreen { hash: 1 },
@@ -418,6 +423,7 @@ fn submapper_override_clears_ines_prg_ram_bits_when_promoting_to_nes20() {
         perf: false,
         sub_mapper_type: Some(1),
         mmc3_irq_variant: None,
+        region: None,
         events: vec![RomEvent {
             frame: 1,
             kind: RomEventKind::CheckScreen { hash: 1 },
@@ -435,3 +441,83 @@ fn submapper_override_clears_ines_prg_ram_bits_when_promoting_to_nes20() {
     assert_eq!(overridden[7], 0x08);
     assert_eq!(overridden[8], 0x10);
 }
+
+#[test]
+fn nestest_trace_matches_nintendulator_log() {
+    let manifest = load_default_manifest().expect("default manifest should load");
+    let case = manifest
+        .case("cpu.nestest")
+        .expect("nestest case should exist");
+    let mut rom_bytes = read_rom(case).expect("nestest ROM should load");
+    // Automation mode starts at $C000 instead of the reset vector's $C004.
+    let prg_len = usize::from(rom_bytes[4]) * 0x4000;
+    rom_bytes[16 + prg_len - 4..16 + prg_len - 2].copy_from_slice(&[0x00, 0xC0]);
+    let expected = std::fs::read_to_string(
+        case.resolved_rom_path()
+            .expect("ROM path should resolve")
+            .with_extension("log"),
+    )
+    .expect("nestest.log should load");
+
+    let (summary, sink) = TraceRunner::new(case, &rom_bytes, TraceSink::ring(16_384))
+        .expect("trace runner should construct")
+        .run(case, 1)
+        .expect("trace should run");
+
+    assert_eq!(summary.frames, 1);
+    assert!(summary.instructions > 8_991);
+    for (line, (actual, expected)) in sink.lines().zip(expected.lines()).enumerate() {
+        assert_eq!(
+            without_scanline(actual),
+            without_scanline(&mask_unreadable(actual, expected.trim_end())),
+            "line {}",
+            line + 1
+        );
+    }
+}
+
+#[test]
+fn nestest_menu_is_visible_in_the_ppu_capture() {
+    let manifest = load_default_manifest().expect("default manifest should load");
+    let case = manifest
+        .case("cpu.nestest")
+        .expect("nestest case should exist");
+
+    let (frames, snapshot) = capture_ppu(case, 15, 241).expect("capture should run");
+    let view = snapshot.video_memory_view(0);
+
+    assert_eq!(frames, 15);
+    assert_eq!(view.scanline, 241);
+    assert_eq!(view.frame, 14);
+    assert_eq!((view.tile_maps.width, view.tile_maps.height), (512, 480));
+    assert_eq!((view.scroll.x, view.scroll.y), (0, 0));
+    assert_eq!(view.sprites.len(), 64);
+    assert_eq!(view.palette.len(), 32);
+    // The menu text is drawn with the background color and one other color.
+    let mut colors = view.tile_maps.pixels.clone();
+    colors.sort_unstable();
+    colors.dedup();
+    assert_eq!(colors.len(), 2);
+}
+
+// Values behind I/O registers such as $4015 are traced as `??` instead of being read.
+fn mask_unreadable(actual: &str, expected: &str) -> String {
+    if actual.len() != expected.len() {
+        return expected.to_string();
+    }
+    actual
+        .chars()
+        .zip(expected.chars())
+        .map(|(actual, expected)| if actual == '?' { '?' } else { expected })
+        .collect()
+}
+
+// Nintendulator powers on at scanline 0 while this core starts on the pre-render line,
+// so only the dot of the PPU column is comparable.
+fn without_scanline(line: &str) -> String {
+    let (head, tail) = line
+        .split_once("PPU:")
+        .expect("trace line should have a PPU column");
+    let (_, tail) = tail.split_once(',').expect("PPU column should have a dot");
+    format!("{head}PPU:{tail}")
+}
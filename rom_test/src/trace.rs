@@ -0,0 +1,86 @@
+use std::{
+    fs::{self, File},
+    io::BufWriter,
+    path::Path,
+};
+
+use nerust_nes_core::trace::TraceSink;
+
+use crate::{
+    error::RomTestError,
+    inspect::InspectRunner,
+    manifest::{RomCase, read_rom},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceSummary {
+    pub frames: u64,
+    pub steps: u64,
+    pub instructions: u64,
+}
+
+/// Runs `case` for `frames` frames and writes a nestest.log-style CPU trace to `output`.
+/// Controller and reset events still fire; screen and memory checks are skipped.
+pub fn trace_case(
+    case: &RomCase,
+    frames: u64,
+    output: &Path,
+) -> Result<TraceSummary, RomTestError> {
+    let rom_bytes = read_rom(case)?;
+    if let Some(parent) = output
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(|source| RomTestError::CreateDirectory {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
+    let file = File::create(output).map_err(|source| RomTestError::WriteFile {
+        path: output.to_path_buf(),
+        source,
+    })?;
+
+    let runner = TraceRunner::new(case, &rom_bytes, TraceSink::writer(BufWriter::new(file)))?;
+    let (summary, sink) = runner.run(case, frames)?;
+    sink.finish().map_err(|source| RomTestError::WriteFile {
+        path: output.to_path_buf(),
+        source,
+    })?;
+    Ok(summary)
+}
+
+/// [`InspectRunner`] with a trace sink installed on its core.
+pub struct TraceRunner {
+    runner: InspectRunner,
+}
+
+impl TraceRunner {
+    pub fn new(case: &RomCase, rom_bytes: &[u8], sink: TraceSink) -> Result<Self, RomTestError> {
+        let mut runner = InspectRunner::new(case, rom_bytes)?;
+        runner.core_mut().set_trace_sink(Some(sink));
+        Ok(Self { runner })
+    }
+
+    /// Returns the sink so the caller can flush it or read the ring buffer.
+    pub fn run(
+        mut self,
+        case: &RomCase,
+        frames: u64,
+    ) -> Result<(TraceSummary, TraceSink), RomTestError> {
+        let totals = self.runner.run(case, frames)?;
+        let sink = self
+            .runner
+            .core_mut()
+            .set_trace_sink(None)
+            .expect("trace sink is installed in TraceRunner::new");
+        Ok((
+            TraceSummary {
+                frames: totals.frames,
+                steps: totals.steps,
+                instructions: sink.lines_written(),
+            },
+            sink,
+        ))
+    }
+}
@@ -28,7 +28,7 @@ pub struct ShortcutDescriptor {
     pub label: &'static str,
 }
 
-const SHORTCUT_DESCRIPTORS: [ShortcutDescriptor; 7] = [
+const SHORTCUT_DESCRIPTORS: [ShortcutDescriptor; 15] = [
     ShortcutDescriptor {
         action: ShortcutAction::TogglePause,
         label: "Toggle Pause",
@@ -57,6 +57,38 @@ const SHORTCUT_DESCRIPTORS: [ShortcutDescriptor; 7] = [
         action: ShortcutAction::Reset,
         label: "Reset",
     },
+    ShortcutDescriptor {
+        action: ShortcutAction::Rewind,
+        label: "Rewind (Hold)",
+    },
+    ShortcutDescriptor {
+        action: ShortcutAction::FastForward,
+        label: "Fast Forward (Hold)",
+    },
+    ShortcutDescriptor {
+        action: ShortcutAction::ToggleFastForward,
+        label: "Toggle Fast Forward",
+    },
+    ShortcutDescriptor {
+        action: ShortcutAction::ToggleSlowMotion,
+        label: "Toggle Slow Motion",
+    },
+    ShortcutDescriptor {
+        action: ShortcutAction::EjectDisk,
+        label: "Eject Disk",
+    },
+    ShortcutDescriptor {
+        action: ShortcutAction::SwitchDiskSide,
+        label: "Switch Disk Side",
+    },
+    ShortcutDescriptor {
+        action: ShortcutAction::NextTrack,
+        label: "Next Track",
+    },
+    ShortcutDescriptor {
+        action: ShortcutAction::PreviousTrack,
+        label: "Previous Track",
+    },
 ];
 
 pub fn keyboard_binding_descriptors(
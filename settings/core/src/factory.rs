@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use nerust_core_traits::{
     factory::{
         CoreFactory, FactoryError,
@@ -36,6 +38,27 @@ pub fn apply_settings_choice(
     snapshot: &mut SettingsSnapshot,
     field: &SystemSettingsFieldId,
     choice: &SystemSettingsChoiceId,
+) -> Result<(), FactoryError> {
+    update_system_settings(factory, snapshot, |view| {
+        factory.apply_settings_choice(view, field, choice)
+    })
+}
+
+pub fn apply_settings_path(
+    factory: &dyn CoreFactory,
+    snapshot: &mut SettingsSnapshot,
+    field: &SystemSettingsFieldId,
+    path: Option<&Path>,
+) -> Result<(), FactoryError> {
+    update_system_settings(factory, snapshot, |view| {
+        factory.apply_settings_path(view, field, path)
+    })
+}
+
+fn update_system_settings(
+    factory: &dyn CoreFactory,
+    snapshot: &mut SettingsSnapshot,
+    update: impl FnOnce(&mut FactorySettingsView) -> Result<(), FactoryError>,
 ) -> Result<(), FactoryError> {
     let system_id = factory.system_id();
     let mut view = settings_view(snapshot, system_id.as_ref());
@@ -44,7 +67,7 @@ pub fn apply_settings_choice(
             .as_system_defaults()
             .and_then(|defaults| defaults.default_system_settings());
     }
-    factory.apply_settings_choice(&mut view, field, choice)?;
+    update(&mut view)?;
     if let Some(settings) = view.system_config {
         snapshot.shared.systems.insert(system_id, settings);
     }
@@ -65,6 +65,14 @@ pub enum UiText {
     ConflictDetected,
     CapturePrompt,
     InvalidCustomStorageDirectory,
+    ChooseRomInArchive,
+    Debugger,
+    PpuViewer,
+    MemoryViewer,
+    RewindInterval,
+    FastForwardSpeed,
+    SlowMotionSpeed,
+    Unlimited,
 }
 
 pub fn resolve_language(language: AppLanguage) -> AppLanguage {
@@ -155,6 +163,14 @@ fn english_text(key: UiText) -> &'static str {
         UiText::InvalidCustomStorageDirectory => {
             "The custom storage directory must exist or be creatable."
         }
+        UiText::ChooseRomInArchive => "Load this ROM from the archive?",
+        UiText::Debugger => "Debugger",
+        UiText::PpuViewer => "PPU Viewer",
+        UiText::MemoryViewer => "Memory Viewer",
+        UiText::RewindInterval => "Rewind interval (frames)",
+        UiText::FastForwardSpeed => "Fast-forward speed",
+        UiText::SlowMotionSpeed => "Slow-motion speed",
+        UiText::Unlimited => "Unlimited",
     }
 }
 
@@ -225,5 +241,13 @@ fn japanese_text(key: UiText) -> &'static str {
         UiText::InvalidCustomStorageDirectory => {
             "任意の保存先フォルダは存在するか作成可能である必要があります。"
         }
+        UiText::ChooseRomInArchive => "アーカイブ内のこの ROM を読み込みますか?",
+        UiText::Debugger => "デバッガ",
+        UiText::PpuViewer => "PPU ビューア",
+        UiText::MemoryViewer => "メモリビューア",
+        UiText::RewindInterval => "巻き戻しの記録間隔 (フレーム)",
+        UiText::FastForwardSpeed => "早送りの速さ",
+        UiText::SlowMotionSpeed => "スローモーションの速さ",
+        UiText::Unlimited => "無制限",
     }
 }
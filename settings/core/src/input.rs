@@ -1,9 +1,9 @@
 use std::{collections::HashSet, rc::Rc};
 
 use nerust_input_traits::{
-    AttachmentId, AttachmentSlotDescriptor, ControlDescriptor, ControllerProfile, DeviceDescriptor,
-    DeviceKindId, DigitalControlDescriptor, InputTopologyDescriptor, PortDescriptor, PortId,
-    ProfileId, SlotInfo,
+    AnalogControlDescriptor, AnalogControlId, AttachmentId, AttachmentSlotDescriptor,
+    ControlDescriptor, ControlKind, ControllerProfile, DeviceDescriptor, DeviceKindId,
+    DigitalControlDescriptor, InputTopologyDescriptor, PortDescriptor, PortId, SlotInfo,
 };
 
 /// Map a controller profile + port group index to a device kind string.
@@ -13,7 +13,9 @@ pub fn device_kind(profile: &dyn ControllerProfile, group_index: usize) -> &'sta
 
 struct TopologyContext {
     ports: Vec<PortDescriptor>,
-    seen_devices: HashSet<(ProfileId, usize)>,
+    /// Device kinds already described. Groups sharing a kind (e.g. the four
+    /// pads of a multitap) are described once.
+    seen_devices: HashSet<&'static str>,
     devices: Vec<DeviceDescriptor>,
 }
 
@@ -40,23 +42,29 @@ impl TopologyContext {
         }
     }
 
-    fn register_device(&mut self, profile: &dyn ControllerProfile, ctrl_id: ProfileId, gi: usize) {
-        if !self.seen_devices.insert((ctrl_id, gi)) {
+    fn register_device(&mut self, profile: &dyn ControllerProfile, gi: usize) {
+        let dk = device_kind(profile, gi);
+        if !self.seen_devices.insert(dk) {
             return;
         }
-        let dk = device_kind(profile, gi);
         let controls = profile.port_groups()[gi];
         self.devices.push(DeviceDescriptor {
             kind: DeviceKindId::new(dk),
             label: profile.label(),
             controls: controls
                 .iter()
-                .map(|ci| {
-                    ControlDescriptor::Digital(DigitalControlDescriptor {
+                .map(|ci| match ci.kind {
+                    ControlKind::Digital => ControlDescriptor::Digital(DigitalControlDescriptor {
                         id: ci.id,
                         label: ci.label,
                         description: ci.label,
-                    })
+                    }),
+                    // ポインタやスティックはキー割り当ての対象外
+                    _ => ControlDescriptor::Analog(AnalogControlDescriptor {
+                        id: AnalogControlId::new(ci.id.as_str()),
+                        label: ci.label,
+                        description: ci.label,
+                    }),
                 })
                 .collect(),
         });
@@ -111,7 +119,7 @@ pub fn build_topology(
                 continue;
             }
             for (gi, &port) in ps.ports.iter().enumerate() {
-                ctx.register_device(profile, profile.profile_id(), gi);
+                ctx.register_device(profile, gi);
                 ctx.register_port(port, profile, gi, &slot_label);
             }
         }
@@ -143,7 +151,10 @@ pub fn clear_multi_port_conflicts(
 mod tests {
     use std::rc::Rc;
 
-    use nerust_input_traits::{AttachmentId, ControllerProfile, PortSet, ProfileId};
+    use nerust_input_traits::{
+        AnalogControlId, AttachmentId, ControlId, ControlInfo, ControlKind, ControllerProfile,
+        DigitalControlId, PortSet, ProfileId, SlotInfo,
+    };
 
     use super::{build_topology, clear_multi_port_conflicts, device_kind};
 
@@ -187,6 +198,65 @@ mod tests {
         }
     }
 
+    #[derive(Debug)]
+    struct PointerProfile;
+    impl ControllerProfile for PointerProfile {
+        fn profile_id(&self) -> ProfileId {
+            ProfileId::new("test.pointer")
+        }
+        fn label(&self) -> &'static str {
+            "Pointer"
+        }
+        fn port_sets(&self) -> &[PortSet] {
+            static PORTS: [PortSet; 1] = [PortSet { ports: &[P1] }];
+            &PORTS
+        }
+        fn port_groups(&self) -> &[&[ControlInfo]] {
+            static CONTROLS: [ControlInfo; 2] = [
+                ControlInfo {
+                    id: DigitalControlId::new("test.trigger"),
+                    label: "Trigger",
+                    kind: ControlKind::Digital,
+                    abstract_key: None,
+                },
+                ControlInfo {
+                    id: DigitalControlId::new("test.aim"),
+                    label: "Aim",
+                    kind: ControlKind::Mouse,
+                    abstract_key: None,
+                },
+            ];
+            static GROUPS: [&[ControlInfo]; 1] = [&CONTROLS];
+            &GROUPS
+        }
+    }
+
+    /// Two identical pads behind one adapter, sharing a device kind.
+    #[derive(Debug)]
+    struct MultitapProfile;
+    impl ControllerProfile for MultitapProfile {
+        fn profile_id(&self) -> ProfileId {
+            ProfileId::new("test.multitap")
+        }
+        fn label(&self) -> &'static str {
+            "Multitap"
+        }
+        fn port_sets(&self) -> &[PortSet] {
+            static PORTS: [PortSet; 1] = [PortSet { ports: &[P1, P2] }];
+            &PORTS
+        }
+        fn port_groups(&self) -> &[&[ControlInfo]] {
+            static CONTROLS: [ControlInfo; 1] = [ControlInfo {
+                id: DigitalControlId::new("test.a"),
+                label: "A",
+                kind: ControlKind::Digital,
+                abstract_key: None,
+            }];
+            static GROUPS: [&[ControlInfo]; 2] = [&CONTROLS, &CONTROLS];
+            &GROUPS
+        }
+    }
+
     const P1: AttachmentId = AttachmentId::new("p1");
     const P2: AttachmentId = AttachmentId::new("p2");
     const OTHER: AttachmentId = AttachmentId::new("other");
@@ -237,4 +307,46 @@ mod tests {
         assert!(topology.ports.is_empty());
         assert!(topology.devices.is_empty());
     }
+
+    #[test]
+    fn build_topology_maps_pointer_controls_to_analog() {
+        let slots = [SlotInfo {
+            id: P1,
+            label: "Player 1",
+        }];
+        let topology = build_topology(
+            &[(
+                P1,
+                Some(Rc::new(PointerProfile) as Rc<dyn ControllerProfile>),
+            )],
+            &slots,
+        );
+        let ids: Vec<_> = topology.devices[0]
+            .controls
+            .iter()
+            .map(|control| control.id())
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                ControlId::Digital(DigitalControlId::new("test.trigger")),
+                ControlId::Analog(AnalogControlId::new("test.aim")),
+            ]
+        );
+    }
+
+    #[test]
+    fn build_topology_describes_shared_device_kind_once() {
+        let multitap = Rc::new(MultitapProfile) as Rc<dyn ControllerProfile>;
+        let topology = build_topology(&[(P1, Some(Rc::clone(&multitap))), (P2, None)], &[]);
+
+        assert_eq!(topology.ports.len(), 2);
+        assert_eq!(topology.devices.len(), 1);
+        assert!(
+            topology
+                .ports
+                .iter()
+                .all(|port| port.attachments[0].device.as_str() == "test.multitap")
+        );
+    }
 }
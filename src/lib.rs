@@ -59,7 +59,14 @@ fn parse_cli_args_from(
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about(env!("CARGO_PKG_DESCRIPTION"))
-        .arg(clap::Arg::new("filename").help("Rom file name"));
+        .arg(clap::Arg::new("filename").help("Rom file name"))
+        .arg(
+            clap::Arg::new("patch")
+                .long("patch")
+                .value_name("FILE")
+                .action(clap::ArgAction::Append)
+                .help("IPS/BPS/UPS patch applied to the ROM (repeatable)"),
+        );
     for opt in &defaults {
         app = opt.augment_args(app);
     }
@@ -67,6 +74,12 @@ fn parse_cli_args_from(
     let matches = app.try_get_matches_from(args)?;
     let options = RunOptions {
         rom_path: matches.get_one::<String>("filename").map(PathBuf::from),
+        patch_paths: matches
+            .get_many::<String>("patch")
+            .into_iter()
+            .flatten()
+            .map(PathBuf::from)
+            .collect(),
     };
     let parsed = factories
         .iter()
@@ -121,7 +134,7 @@ pub fn run() {
     let (options, core_options) = parse_cli_args(registry.all()).unwrap_or_else(|e| e.exit());
 
     let rom_loader = registry
-        .create_loader(core_options)
+        .create_loader(core_options, options.patch_paths.clone())
         .expect("CLI options must belong to registered systems");
 
     let ctx = FrontendContext {
@@ -200,7 +213,7 @@ mod tests {
         let shared = default_shared_settings(std::slice::from_ref(&factory));
         let registry = Arc::new(SystemRegistry::new(vec![factory]));
         let mut loader = registry
-            .create_loader(HashMap::from([(system_id, pending)]))
+            .create_loader(HashMap::from([(system_id, pending)]), Vec::new())
             .unwrap();
 
         let mut target = LoadRecorder {
@@ -217,7 +230,7 @@ mod tests {
         let nes_bytes = vec![0x4E, 0x45, 0x53, 0x1A, 1u8, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
         std::fs::write(&rom_path, &nes_bytes).expect("write rom");
 
-        let result = loader.load_rom(&rom_path, &mut target);
+        let result = loader.load_rom(&rom_path, None, &mut target);
         let _ = std::fs::remove_file(&rom_path);
         assert!(result.is_ok());
         assert!(target.resumed);
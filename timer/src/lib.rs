@@ -18,8 +18,16 @@ pub struct Timer {
 
 impl Timer {
     pub fn new() -> Self {
+        Self::with_frame_interval(Duration::from_nanos(Self::FRAME_WAIT_NANOS))
+    }
+
+    /// Creates a timer pacing at `frame_rate` frames per second (e.g. 50.007 for PAL).
+    pub fn with_frame_rate(frame_rate: f64) -> Self {
+        Self::with_frame_interval(frame_interval_from_rate(frame_rate))
+    }
+
+    fn with_frame_interval(frame_interval: Duration) -> Self {
         let instants = VecDeque::with_capacity(Self::CALC_FRAMES);
-        let frame_interval = Duration::from_nanos(Self::FRAME_WAIT_NANOS);
         let now = Instant::now();
         Self {
             instants,
@@ -59,6 +67,15 @@ impl Timer {
             advance_deadline(self.next_deadline, waited_until, self.frame_interval);
     }
 
+    /// Records a frame without sleeping, for uncapped (turbo) emulation.
+    /// The deadline follows the current instant so returning to `wait` does
+    /// not burst through a stale backlog.
+    pub fn skip_wait(&mut self) {
+        let now = Instant::now();
+        self.record_instant(now);
+        self.next_deadline = now + self.frame_interval;
+    }
+
     fn record_instant(&mut self, now: Instant) {
         let len = self.instants.len();
         if len == 0 {
@@ -78,6 +95,30 @@ impl Timer {
     pub fn as_fps(&self) -> f32 {
         self.fps
     }
+
+    /// Changes the pacing rate. The next deadline is rescheduled from now so a
+    /// switch between 60 Hz and 50 Hz content takes effect on the next frame.
+    pub fn set_frame_rate(&mut self, frame_rate: f64) {
+        let frame_interval = frame_interval_from_rate(frame_rate);
+        if frame_interval == self.frame_interval {
+            return;
+        }
+        self.frame_interval = frame_interval;
+        self.next_deadline = Instant::now() + frame_interval;
+        self.instants.clear();
+    }
+
+    pub fn frame_interval(&self) -> Duration {
+        self.frame_interval
+    }
+}
+
+fn frame_interval_from_rate(frame_rate: f64) -> Duration {
+    assert!(
+        frame_rate.is_finite() && frame_rate > 0.0,
+        "frame rate must be positive: {frame_rate}"
+    );
+    Duration::from_nanos((1_000_000_000.0 / frame_rate) as u64)
 }
 
 fn advance_deadline(next_deadline: Instant, now: Instant, frame_interval: Duration) -> Instant {
@@ -168,4 +209,27 @@ mod tests {
         assert_eq!(advanced, scheduled + frame_interval);
         assert!(advanced < now);
     }
+
+    #[test]
+    fn frame_rate_sets_pacing_interval() {
+        let mut timer = Timer::with_frame_rate(50.0);
+        assert_eq!(timer.frame_interval(), Duration::from_millis(20));
+
+        timer.set_frame_rate(100.0);
+        assert_eq!(timer.frame_interval(), Duration::from_millis(10));
+        assert_eq!(
+            Timer::new().frame_interval(),
+            Duration::from_nanos(Timer::FRAME_WAIT_NANOS)
+        );
+    }
+
+    #[test]
+    fn skip_wait_rebases_deadline_on_now() {
+        let mut timer = Timer::with_frame_rate(50.0);
+        let before = Instant::now();
+        timer.skip_wait();
+
+        assert!(timer.next_deadline >= before + timer.frame_interval());
+        assert!(timer.next_deadline <= Instant::now() + timer.frame_interval());
+    }
 }
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// A user-entered cheat code. The code syntax is owned by each core.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cheat {
+    pub code: String,
+    #[serde(default)]
+    pub description: String,
+    pub enabled: bool,
+}
+
+impl Cheat {
+    /// Creates an enabled cheat.
+    pub fn new(code: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            description: description.into(),
+            enabled: true,
+        }
+    }
+}
+
+/// Edit applied to the core's cheat list by `EmuCommand::Cheat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheatOp {
+    Add(Cheat),
+    SetEnabled { index: usize, enabled: bool },
+    Remove(usize),
+    Clear,
+}
@@ -0,0 +1,244 @@
+use serde::{Deserialize, Serialize};
+
+/// What a breakpoint watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BreakpointKind {
+    /// Stops before an instruction in the address range runs.
+    Execute,
+    /// Stops after the instruction that read an address in the range.
+    Read,
+    /// Stops after the instruction that wrote an address in the range.
+    Write,
+    /// Stops at the first instruction of an IRQ handler. The address range is ignored.
+    Irq,
+    /// Stops at the first instruction of an NMI handler. The address range is ignored.
+    Nmi,
+}
+
+/// A debugger breakpoint. The condition syntax is owned by each core; an empty
+/// condition always matches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    pub kind: BreakpointKind,
+    pub start: u32,
+    pub end: u32,
+    #[serde(default)]
+    pub condition: String,
+    pub enabled: bool,
+}
+
+impl Breakpoint {
+    /// Creates an enabled, unconditional breakpoint on a single address.
+    pub fn new(kind: BreakpointKind, address: u32) -> Self {
+        Self::range(kind, address, address)
+    }
+
+    /// Creates an enabled, unconditional breakpoint on `start..=end`.
+    pub fn range(kind: BreakpointKind, start: u32, end: u32) -> Self {
+        Self {
+            kind,
+            start,
+            end,
+            condition: String::new(),
+            enabled: true,
+        }
+    }
+
+    pub fn with_condition(self, condition: impl Into<String>) -> Self {
+        Self {
+            condition: condition.into(),
+            ..self
+        }
+    }
+
+    pub fn contains(&self, address: u32) -> bool {
+        (self.start..=self.end).contains(&address)
+    }
+}
+
+/// How far `ConsoleCore::debug_step` runs before the core stops again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepMode {
+    /// Runs one instruction.
+    Instruction,
+    /// Runs one instruction, treating a subroutine call as a single instruction.
+    Over,
+    /// Runs until the current subroutine or interrupt handler returns.
+    Out,
+    /// Runs until the video beam next enters the scanline.
+    Scanline(u16),
+}
+
+/// Why the core stopped in the middle of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakReason {
+    /// The breakpoint at this index matched. `address` is the instruction or the
+    /// accessed address.
+    Breakpoint { index: usize, address: u32 },
+    /// A `debug_step` finished.
+    Step,
+}
+
+/// One CPU register as shown by a debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterValue {
+    pub name: &'static str,
+    pub value: u32,
+    /// Width of the register in bits.
+    pub bits: u8,
+}
+
+/// One CPU status flag as shown by a debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlagValue {
+    pub name: &'static str,
+    pub set: bool,
+}
+
+/// CPU and video position of a stopped core.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugState {
+    /// Address of the next instruction to run.
+    pub pc: u32,
+    pub registers: Vec<RegisterValue>,
+    pub flags: Vec<FlagValue>,
+    pub scanline: u16,
+    pub dot: u16,
+    pub frame: u64,
+    pub cycle: u64,
+}
+
+/// Debugger request applied by `EmuCommand::Debug`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DebugOp {
+    /// Only reads the state.
+    Inspect,
+    SetBreakpoints(Vec<Breakpoint>),
+    /// Resumes the core until the step finishes.
+    Step(StepMode),
+    /// Writes a register or flag by the name reported in `DebugState`.
+    SetRegister {
+        name: String,
+        value: u32,
+    },
+}
+
+/// An image whose pixels are indices into the core's master palette, like a
+/// `PixelFormat::PaletteIndex` frame.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct IndexedImage {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major, `width * height` entries.
+    pub pixels: Vec<u8>,
+}
+
+impl IndexedImage {
+    /// Creates an image filled with index 0.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, value: u8) {
+        self.pixels[y * self.width + x] = value;
+    }
+}
+
+/// A rectangle on a tile map image. It wraps around the right and bottom edges
+/// the same way the hardware scrolls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// One sprite attribute entry and its tile drawn with its own palette.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpriteView {
+    pub index: u8,
+    pub x: u8,
+    pub y: u8,
+    pub tile: u8,
+    pub palette: u8,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    pub behind_background: bool,
+    pub image: IndexedImage,
+}
+
+/// Video memory as seen when the beam entered `scanline`, drawn for a viewer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoMemoryView {
+    pub scanline: u16,
+    pub frame: u64,
+    /// Every background tile map, laid out as the address space arranges them.
+    pub tile_maps: IndexedImage,
+    /// The area of `tile_maps` the scroll registers point at.
+    pub scroll: ViewRect,
+    /// Each pattern table drawn with the requested palette.
+    pub pattern_tables: Vec<IndexedImage>,
+    pub sprites: Vec<SpriteView>,
+    /// Palette RAM as master palette indices.
+    pub palette: Vec<u8>,
+}
+
+/// An address space a memory viewer can read and write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MemorySpace {
+    /// Everything the CPU can address, as currently mapped.
+    CpuBus,
+    /// Everything the video chip can address, as currently mapped.
+    VideoBus,
+    /// Sprite attribute memory.
+    SpriteRam,
+    /// The whole program ROM, by file offset.
+    ProgramRom,
+    /// The whole character ROM, or character RAM when there is no ROM.
+    CharacterMemory,
+}
+
+/// Memory viewer request applied by `EmuCommand::Memory`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MemoryOp {
+    /// Reads `len` bytes from `start`, clipped to the end of the space.
+    Read {
+        space: MemorySpace,
+        start: u32,
+        len: u32,
+    },
+    /// Writes `bytes` from `start`, then reads them back.
+    Write {
+        space: MemorySpace,
+        start: u32,
+        bytes: Vec<u8>,
+    },
+}
+
+/// Bytes read from one memory space without side effects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryBlock {
+    pub space: MemorySpace,
+    pub start: u32,
+    /// `None` where reading would disturb the hardware or nothing answers.
+    pub bytes: Vec<Option<u8>>,
+    /// Size of the whole space.
+    pub space_len: u32,
+}
+
+impl MemoryBlock {
+    /// The byte at `address`, if it is in this block and readable.
+    pub fn get(&self, address: u32) -> Option<u8> {
+        let offset = address.checked_sub(self.start)?;
+        self.bytes.get(offset as usize).copied().flatten()
+    }
+}
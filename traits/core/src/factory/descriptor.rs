@@ -1,4 +1,4 @@
-use std::{borrow::Cow, sync::Arc};
+use std::{borrow::Cow, path::PathBuf, sync::Arc};
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct SystemSettingsFieldId(pub Cow<'static, str>);
@@ -36,6 +36,11 @@ pub enum SystemSettingsFieldKind {
         selected: SystemSettingsChoiceId,
         options: Arc<[SystemSettingsChoiceOption]>,
     },
+    /// A file picked by the user, applied through `CoreFactory::apply_settings_path`.
+    Path {
+        selected: Option<PathBuf>,
+        extensions: &'static [&'static str],
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -1,4 +1,4 @@
-use std::{fmt::Debug, path::PathBuf, sync::Arc};
+use std::{collections::HashMap, fmt::Debug, path::PathBuf, sync::Arc};
 
 use clap::{ArgMatches, Args, Command, FromArgMatches};
 use downcast_rs::Downcast;
@@ -110,4 +110,9 @@ impl DynSystemLoadOptionsExt for Box<dyn DynSystemLoadOptions> {
 #[derive(Clone, Debug)]
 pub struct ResolvedLoadRequest {
     pub options: Box<dyn CoreOptions>,
+    /// Forwarded to `CoreConfig::bios_paths`.
+    pub bios_paths: HashMap<String, PathBuf>,
+    /// IPS / BPS / UPS patches the session applies to the media, in order,
+    /// before handing it to the core.
+    pub patch_paths: Vec<PathBuf>,
 }
@@ -2,7 +2,7 @@ pub mod descriptor;
 pub mod load;
 pub mod settings;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
 use nerust_input_traits::{
     AttachmentId, DigitalControlId, GuiInput, InputAssignments, InputSystemFactory,
@@ -31,6 +31,8 @@ pub enum FactoryError {
     Resolve(String),
     #[error("invalid settings snapshot")]
     InvalidSettings,
+    #[error("invalid cheat code: {0}")]
+    InvalidCheat(String),
 }
 
 /// Raw parts produced by a system factory before EmuCore wrapping.
@@ -66,6 +68,16 @@ pub trait CoreFactory: Send + Sync {
         choice: &SystemSettingsChoiceId,
     ) -> Result<(), FactoryError>;
 
+    /// Sets or clears (`None`) a `SystemSettingsFieldKind::Path` field.
+    fn apply_settings_path(
+        &self,
+        _view: &mut FactorySettingsView,
+        field: &SystemSettingsFieldId,
+        _path: Option<&Path>,
+    ) -> Result<(), FactoryError> {
+        Err(FactoryError::InvalidChoice(field.as_str().to_string()))
+    }
+
     fn resolve_load_request(
         &self,
         view: &FactorySettingsView,
@@ -99,6 +111,13 @@ pub trait CoreFactory: Send + Sync {
     /// Returns this factory's input system factory for negotiation.
     fn input_system_factory(&self) -> &dyn InputSystemFactory;
 
+    /// Checks a cheat code without a loaded core, e.g. while editing the cheat list.
+    fn validate_cheat_code(&self, _code: &str) -> Result<(), FactoryError> {
+        Err(FactoryError::InvalidCheat(
+            "cheats are not supported".into(),
+        ))
+    }
+
     /// Access the [`SystemDefaults`] facet of this factory.
     ///
     /// Returns `None` for factories that only provide core creation
@@ -134,4 +153,10 @@ pub trait SystemDefaults: Send + Sync {
     fn default_input_control_prefix(&self) -> Option<&'static str> {
         None
     }
+
+    /// Further attachments that get the alternate keyboard layouts, in
+    /// layout order (e.g. players 3 and 4 behind a multitap).
+    fn default_input_extra_attachment_ids(&self) -> &'static [&'static str] {
+        &[]
+    }
 }
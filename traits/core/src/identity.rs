@@ -20,6 +20,8 @@ dyn_hash::hash_trait_object!(SystemId);
 pub struct SystemIdentity {
     pub system_id: Box<dyn SystemId>,
     pub identity_bytes: Vec<u8>,
+    /// 楽曲ファイルなどが持つ表示用の情報。永続化キーには `identity_bytes` のみを使う。
+    pub metadata: Option<MediaMetadata>,
 }
 
 impl SystemIdentity {
@@ -27,8 +29,31 @@ impl SystemIdentity {
         Self {
             system_id,
             identity_bytes,
+            metadata: None,
         }
     }
+
+    pub fn with_metadata(mut self, metadata: Option<MediaMetadata>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+}
+
+/// メディアに埋め込まれたタイトルや曲目。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaMetadata {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub copyright: Option<String>,
+    pub tracks: Vec<TrackMetadata>,
+    /// 読み込み直後に再生する曲 (0 始まり)。
+    pub first_track: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackMetadata {
+    pub label: Option<String>,
+    pub duration: Option<std::time::Duration>,
 }
 
 impl Display for dyn SystemId {
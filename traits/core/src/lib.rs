@@ -1,4 +1,6 @@
 pub mod audio;
+pub mod cheat;
+pub mod debug;
 pub mod factory;
 pub mod identity;
 pub mod save_state;
@@ -56,6 +58,7 @@ pub struct CoreCapabilities {
 pub enum Region {
     Ntsc,
     Pal,
+    Dendy,
 }
 
 // ---------------------------------------------------------------------------
@@ -102,6 +105,84 @@ pub struct StateDataCommand {
     pub reply: Sender<Result<(), CoreError>>,
 }
 
+/// Boxed payload for `EmuCommand::Cheat`. Replies with the cheat list after the edit.
+#[derive(Debug)]
+pub struct CheatCommand {
+    pub op: cheat::CheatOp,
+    pub reply: Sender<Result<Vec<cheat::Cheat>, CoreError>>,
+}
+
+/// Boxed payload for `EmuCommand::Debug`. Replies with the CPU state after the request.
+#[derive(Debug)]
+pub struct DebugCommand {
+    pub op: debug::DebugOp,
+    pub reply: Sender<Result<debug::DebugState, CoreError>>,
+}
+
+/// Boxed payload for `EmuCommand::VideoView`. Replies with the latest capture.
+#[derive(Debug)]
+pub struct VideoViewCommand {
+    /// Scanline to capture at from now on; `None` stops capturing.
+    pub capture_scanline: Option<u16>,
+    pub pattern_palette: u8,
+    pub reply: Sender<Result<debug::VideoMemoryView, CoreError>>,
+}
+
+/// Boxed payload for `EmuCommand::Memory`. Replies with the bytes read, or the
+/// bytes written as read back.
+#[derive(Debug)]
+pub struct MemoryCommand {
+    pub op: debug::MemoryOp,
+    pub reply: Sender<Result<debug::MemoryBlock, CoreError>>,
+}
+
+/// Rewind ring buffer tuning for the emu thread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewindConfig {
+    /// Frames emulated between two captured snapshots.
+    pub interval_frames: u32,
+    /// Number of snapshots kept. The oldest one is overwritten when full.
+    pub capacity: usize,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        // 2 フレームごとに 600 個 => 60fps で約 20 秒分
+        Self {
+            interval_frames: 2,
+            capacity: 600,
+        }
+    }
+}
+
+/// Emulation speed relative to the loaded content's native frame rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmuSpeed {
+    /// Paces at the native rate times the multiplier
+    /// (e.g. `0.5` for slow motion, `2.0` for turbo).
+    Multiplier(f32),
+    /// Runs as fast as the host allows.
+    Uncapped,
+}
+
+impl EmuSpeed {
+    pub const NORMAL: Self = Self::Multiplier(1.0);
+
+    /// Faster than native speed. Audio is dropped while turbo is active.
+    pub fn is_turbo(self) -> bool {
+        match self {
+            Self::Multiplier(multiplier) => multiplier > 1.0,
+            Self::Uncapped => true,
+        }
+    }
+}
+
+impl Default for EmuSpeed {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
 #[derive(Debug)]
 pub enum EmuCommand {
     Pause,
@@ -122,6 +203,18 @@ pub enum EmuCommand {
     Identity {
         reply: Sender<Result<identity::SystemIdentity, CoreError>>,
     },
+    /// Starts (`true`) or stops (`false`) stepping back through rewind snapshots.
+    SetRewinding(bool),
+    ConfigureRewind(RewindConfig),
+    SetSpeed(EmuSpeed),
+    EjectDisk,
+    SwitchDiskSide,
+    NextTrack,
+    PreviousTrack,
+    Cheat(Box<CheatCommand>),
+    Debug(Box<DebugCommand>),
+    VideoView(Box<VideoViewCommand>),
+    Memory(Box<MemoryCommand>),
 }
 
 // ---------------------------------------------------------------------------
@@ -140,6 +233,8 @@ pub trait ConsoleCore: Send {
 
     // -- audio --
     fn set_volume(&mut self, _volume: f32) {}
+    /// Drops generated audio instead of queueing it (e.g. during fast-forward).
+    fn set_audio_muted(&mut self, _muted: bool) {}
 
     // -- pause --
     fn paused(&self) -> bool;
@@ -149,6 +244,93 @@ pub trait ConsoleCore: Send {
     fn save_state(&self) -> Result<Vec<u8>, CoreError>;
     fn load_state(&mut self, data: &[u8]) -> Result<(), CoreError>;
 
+    // -- removable media (default: not supported) --
+    /// Ejects the inserted disk, if the loaded content uses one.
+    fn eject_disk(&mut self) {}
+    /// Ejects the disk and inserts its next side after a short delay.
+    fn switch_disk_side(&mut self) {}
+
+    // -- music player (default: not supported) --
+    /// Restarts playback from the next track of a multi-track music file.
+    fn next_track(&mut self) {}
+    /// Restarts playback from the previous track of a multi-track music file.
+    fn previous_track(&mut self) {}
+
+    // -- cheats (default: not supported) --
+    /// Appends a cheat. Fails when the code is not valid for this core.
+    fn add_cheat(&mut self, _cheat: cheat::Cheat) -> Result<(), CoreError> {
+        Err(CoreError::Core("cheats are not supported".into()))
+    }
+    fn set_cheat_enabled(&mut self, _index: usize, _enabled: bool) -> Result<(), CoreError> {
+        Err(CoreError::Core("cheats are not supported".into()))
+    }
+    fn remove_cheat(&mut self, _index: usize) -> Result<(), CoreError> {
+        Err(CoreError::Core("cheats are not supported".into()))
+    }
+    fn clear_cheats(&mut self) {}
+    fn cheats(&self) -> &[cheat::Cheat] {
+        &[]
+    }
+
+    // -- debugger (default: not supported) --
+    /// Replaces the breakpoints. Fails when a condition is not valid for this core.
+    fn set_breakpoints(&mut self, _breakpoints: Vec<debug::Breakpoint>) -> Result<(), CoreError> {
+        Err(CoreError::Core("debugging is not supported".into()))
+    }
+    fn breakpoints(&self) -> &[debug::Breakpoint] {
+        &[]
+    }
+    /// Arms a step. The step runs once the host resumes the core.
+    fn debug_step(&mut self, _mode: debug::StepMode) -> Result<(), CoreError> {
+        Err(CoreError::Core("debugging is not supported".into()))
+    }
+    /// Returns why the last `render_frame` stopped before the end of the frame.
+    /// The host pauses the core when this returns `Some`.
+    fn take_debug_break(&mut self) -> Option<debug::BreakReason> {
+        None
+    }
+    fn debug_state(&self) -> Result<debug::DebugState, CoreError> {
+        Err(CoreError::Core("debugging is not supported".into()))
+    }
+    /// Writes a register or flag by the name reported in `debug_state`.
+    fn set_register(&mut self, _name: &str, _value: u32) -> Result<(), CoreError> {
+        Err(CoreError::Core("debugging is not supported".into()))
+    }
+    /// Captures video memory each time the beam enters `scanline`; `None` stops.
+    /// A scanline past the end of the frame never captures.
+    fn set_video_capture(&mut self, _scanline: Option<u16>) -> Result<(), CoreError> {
+        Err(CoreError::Core(
+            "video memory viewer is not supported".into(),
+        ))
+    }
+    /// Returns the latest capture, or the current video memory before the first one.
+    /// Pattern tables are drawn with palette `pattern_palette`.
+    fn video_memory_view(&self, _pattern_palette: u8) -> Result<debug::VideoMemoryView, CoreError> {
+        Err(CoreError::Core(
+            "video memory viewer is not supported".into(),
+        ))
+    }
+
+    /// Reads up to `len` bytes from `start` without disturbing the hardware.
+    fn peek_memory(
+        &self,
+        _space: debug::MemorySpace,
+        _start: u32,
+        _len: u32,
+    ) -> Result<debug::MemoryBlock, CoreError> {
+        Err(CoreError::Core("memory viewer is not supported".into()))
+    }
+    /// Stores `bytes` from `start` like a debugger would: no mapper registers
+    /// change and no interrupts fire. Fails at the first byte that is not memory.
+    fn poke_memory(
+        &mut self,
+        _space: debug::MemorySpace,
+        _start: u32,
+        _bytes: &[u8],
+    ) -> Result<(), CoreError> {
+        Err(CoreError::Core("memory viewer is not supported".into()))
+    }
+
     // -- mapper save (system-specific, default: not supported) --
     fn mapper_save(&self) -> Result<Option<Vec<u8>>, CoreError> {
         Ok(None)
@@ -162,26 +344,26 @@ pub trait ConsoleCore: Send {
         Err(CoreError::NoRomLoaded)
     }
 
+    // -- pacing --
+    /// Returns the native frame rate of the loaded content in frames per second.
+    /// `None` lets the host keep its default pacing.
+    fn frame_rate(&self) -> Option<f64> {
+        None
+    }
+
     // -- rewind (default: not supported) --
     /// Returns `None` if rewind is not supported.
     fn rewind_state_size(&self) -> Option<usize> {
         None
     }
-    /// Saves the current state into `buf` for rewind.
-    ///
-    /// # Panics
-    /// Panics if the core does not support rewind.
-    /// Check `rewind_state_size()` returns `Some` before calling.
-    fn rewind_save(&self, _buf: &mut [u8]) {
-        panic!("rewind not supported")
-    }
-    /// Restores a previously saved rewind state.
-    ///
-    /// # Panics
-    /// Panics if the core does not support rewind.
-    /// Check `rewind_state_size()` returns `Some` before calling.
-    fn rewind_restore(&mut self, _buf: &[u8]) {
-        panic!("rewind not supported")
+    /// Saves the current state into `buf`, which is `rewind_state_size()` bytes long.
+    /// Fails when no content is loaded or the state does not fit in `buf`.
+    fn rewind_save(&self, _buf: &mut [u8]) -> Result<(), CoreError> {
+        Err(CoreError::Core("rewind is not supported".into()))
+    }
+    /// Restores a state written by `rewind_save`.
+    fn rewind_restore(&mut self, _buf: &[u8]) -> Result<(), CoreError> {
+        Err(CoreError::Core("rewind is not supported".into()))
     }
 }
 
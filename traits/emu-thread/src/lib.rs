@@ -1,3 +1,5 @@
+mod rewind;
+
 use std::{
     fmt,
     sync::{
@@ -8,11 +10,17 @@ use std::{
     thread::{self, JoinHandle},
 };
 
-use nerust_core_traits::{ConsoleCore, EmuCommand};
+use nerust_core_traits::{
+    ConsoleCore, CoreError, EmuCommand, EmuSpeed, RewindConfig,
+    cheat::{Cheat, CheatOp},
+    debug::{BreakReason, DebugOp, DebugState, MemoryBlock, MemoryOp},
+};
 use nerust_render_traits::{FrameBuffer, PixelFormat};
 use nerust_timer::Timer;
 use thiserror::Error;
 
+use crate::rewind::Rewind;
+
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ConsoleMetrics {
     pub frame_counter: u64,
@@ -38,6 +46,8 @@ pub struct EmuThread {
     thread: Option<JoinHandle<()>>,
     frame_count: Arc<std::sync::atomic::AtomicU64>,
     fps: Arc<AtomicU32>,
+    speed: Arc<AtomicU32>,
+    debug_break: Arc<Mutex<Option<BreakReason>>>,
 }
 
 impl fmt::Debug for EmuThread {
@@ -48,6 +58,8 @@ impl fmt::Debug for EmuThread {
             .field("thread", &self.thread)
             .field("frame_count", &self.frame_count)
             .field("fps", &self.fps.load(Ordering::Relaxed))
+            .field("speed", &self.speed.load(Ordering::Relaxed))
+            .field("debug_break", &self.debug_break)
             .finish()
     }
 }
@@ -66,17 +78,24 @@ impl EmuThread {
         let frame_count: Arc<std::sync::atomic::AtomicU64> =
             Arc::new(std::sync::atomic::AtomicU64::new(0));
         let fps: Arc<AtomicU32> = Arc::new(AtomicU32::new(0));
+        let speed_multiplier: Arc<AtomicU32> = Arc::new(AtomicU32::new(0));
+        let debug_break: Arc<Mutex<Option<BreakReason>>> = Arc::new(Mutex::new(None));
 
         let fb = Arc::clone(&shared_fb);
         let fc = Arc::clone(&frame_count);
         let fps_c = Arc::clone(&fps);
+        let speed_c = Arc::clone(&speed_multiplier);
         let fr = Arc::clone(&frame_ready);
+        let db = Arc::clone(&debug_break);
         let thread = thread::spawn(move || {
             let mut frame_slot =
                 FrameBuffer::with_capacity(256, 240, PixelFormat::PaletteIndex { palette });
             frame_slot.resize(256, 240);
 
             let mut timer = Timer::new();
+            let mut rewind = Rewind::new(RewindConfig::default());
+            let mut speed = EmuSpeed::default();
+            let mut native_rate = f64::from(nerust_timer::TARGET_FPS);
             let mut loaded = false;
             loop {
                 // When idle (no ROM loaded), block on recv() to avoid busy-looping.
@@ -86,9 +105,16 @@ impl EmuThread {
                             EmuCommand::Load(cmd) => {
                                 let result = core.load(&cmd.rom, &cmd.config);
                                 loaded = result.is_ok();
+                                native_rate = apply_pacing(&mut timer, core.as_mut(), speed);
+                                rewind.reset(core.as_ref());
                                 // reply send failure: receiver dropped (timeout/abort) — expected
                                 let _ = cmd.reply.send(result);
                             }
+                            EmuCommand::ConfigureRewind(config) => {
+                                rewind.configure(config, core.as_ref());
+                            }
+                            // 次のロード時に反映する
+                            EmuCommand::SetSpeed(next) if is_valid_speed(next) => speed = next,
                             EmuCommand::Quit => return,
                             _ => {}
                         },
@@ -102,16 +128,23 @@ impl EmuThread {
                         EmuCommand::Load(cmd) => {
                             let result = core.load(&cmd.rom, &cmd.config);
                             loaded = result.is_ok();
+                            native_rate = apply_pacing(&mut timer, core.as_mut(), speed);
+                            rewind.reset(core.as_ref());
                             // reply send failure: receiver dropped (timeout/abort) — expected
                             let _ = cmd.reply.send(result);
                         }
                         EmuCommand::Unload => {
                             core.unload();
+                            rewind.clear();
                             loaded = false;
                         }
                         EmuCommand::Pause => core.set_paused(true),
                         EmuCommand::Resume => core.set_paused(false),
                         EmuCommand::Reset => core.reset(),
+                        EmuCommand::EjectDisk => core.eject_disk(),
+                        EmuCommand::SwitchDiskSide => core.switch_disk_side(),
+                        EmuCommand::NextTrack => core.next_track(),
+                        EmuCommand::PreviousTrack => core.previous_track(),
                         EmuCommand::SetVolume(vol) => core.set_volume(vol),
                         EmuCommand::SaveState { reply } => {
                             let result = core.save_state();
@@ -120,6 +153,10 @@ impl EmuThread {
                         }
                         EmuCommand::LoadState(cmd) => {
                             let result = core.load_state(&cmd.data);
+                            // スロットロード前の履歴へ巻き戻さないよう破棄する
+                            if result.is_ok() {
+                                rewind.reset(core.as_ref());
+                            }
                             // reply send failure: receiver dropped (timeout/abort) — expected
                             let _ = cmd.reply.send(result);
                         }
@@ -138,11 +175,43 @@ impl EmuThread {
                             // reply send failure: receiver dropped (timeout/abort) — expected
                             let _ = reply.send(result);
                         }
+                        EmuCommand::SetRewinding(rewinding) => rewind.set_rewinding(rewinding),
+                        EmuCommand::ConfigureRewind(config) => {
+                            rewind.configure(config, core.as_ref());
+                        }
+                        EmuCommand::SetSpeed(next) => {
+                            if is_valid_speed(next) {
+                                speed = next;
+                                native_rate = apply_pacing(&mut timer, core.as_mut(), speed);
+                            }
+                        }
+                        EmuCommand::Cheat(cmd) => {
+                            let result = apply_cheat_op(core.as_mut(), cmd.op);
+                            // reply send failure: receiver dropped (timeout/abort) — expected
+                            let _ = cmd.reply.send(result);
+                        }
+                        EmuCommand::Debug(cmd) => {
+                            let result = apply_debug_op(core.as_mut(), cmd.op);
+                            // reply send failure: receiver dropped (timeout/abort) — expected
+                            let _ = cmd.reply.send(result);
+                        }
+                        EmuCommand::VideoView(cmd) => {
+                            let result = core
+                                .set_video_capture(cmd.capture_scanline)
+                                .and_then(|()| core.video_memory_view(cmd.pattern_palette));
+                            // reply send failure: receiver dropped (timeout/abort) — expected
+                            let _ = cmd.reply.send(result);
+                        }
+                        EmuCommand::Memory(cmd) => {
+                            let result = apply_memory_op(core.as_mut(), cmd.op);
+                            // reply send failure: receiver dropped (timeout/abort) — expected
+                            let _ = cmd.reply.send(result);
+                        }
                         EmuCommand::Quit => return,
                     }
                 }
 
-                if loaded && !core.paused() {
+                if loaded && !core.paused() && rewind.before_frame(core.as_mut()) {
                     // render_frame only fails with NoRomLoaded (guarded by loaded flag)
                     if core.render_frame(&mut frame_slot).is_ok() {
                         fc.fetch_add(1, Ordering::Relaxed);
@@ -150,11 +219,27 @@ impl EmuThread {
                             std::mem::swap(&mut *guard, &mut frame_slot);
                             fr.store(true, Ordering::Release);
                         }
+                        rewind.after_frame(core.as_ref());
+                        // ブレークしたフレームは途中までの描画をそのまま表示して止まる
+                        if let Some(reason) = core.take_debug_break() {
+                            core.set_paused(true);
+                            if let Ok(mut guard) = db.lock() {
+                                *guard = Some(reason);
+                            }
+                        }
                     }
                 }
 
-                timer.wait();
+                if speed == EmuSpeed::Uncapped && loaded && !core.paused() {
+                    timer.skip_wait();
+                } else {
+                    timer.wait();
+                }
                 fps_c.store(timer.as_fps().to_bits(), Ordering::Relaxed);
+                speed_c.store(
+                    ((f64::from(timer.as_fps()) / native_rate) as f32).to_bits(),
+                    Ordering::Relaxed,
+                );
             }
         });
 
@@ -164,6 +249,8 @@ impl EmuThread {
             thread: Some(thread),
             frame_count,
             fps,
+            speed: speed_multiplier,
+            debug_break,
         }
     }
 
@@ -185,6 +272,17 @@ impl EmuThread {
         f32::from_bits(self.fps.load(Ordering::Relaxed))
     }
 
+    /// Measured emulation speed relative to the content's native frame rate.
+    pub fn speed_multiplier(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::Relaxed))
+    }
+
+    /// Returns the reason the core last stopped on a breakpoint or finished a step,
+    /// clearing it. The core is paused until the next `Resume` or step.
+    pub fn take_debug_break(&self) -> Option<BreakReason> {
+        self.debug_break.lock().ok()?.take()
+    }
+
     pub fn join(&mut self) {
         if let Some(thread) = self.thread.take() {
             // Quit send failure: thread already exited — expected during cleanup
@@ -194,6 +292,66 @@ impl EmuThread {
     }
 }
 
+/// Paces the worker at the loaded content's native rate (50 Hz for PAL/Dendy NES)
+/// scaled by `speed`, and mutes audio while turbo is active. Returns the native rate.
+fn apply_pacing(timer: &mut Timer, core: &mut dyn ConsoleCore, speed: EmuSpeed) -> f64 {
+    let native_rate = core
+        .frame_rate()
+        .unwrap_or(f64::from(nerust_timer::TARGET_FPS));
+    let frame_rate = match speed {
+        EmuSpeed::Multiplier(multiplier) => native_rate * f64::from(multiplier),
+        // uncapped は skip_wait で待たないため、wait に戻った時のために等速で保持する
+        EmuSpeed::Uncapped => native_rate,
+    };
+    timer.set_frame_rate(frame_rate);
+    core.set_audio_muted(speed.is_turbo());
+    native_rate
+}
+
+fn apply_cheat_op(core: &mut dyn ConsoleCore, op: CheatOp) -> Result<Vec<Cheat>, CoreError> {
+    match op {
+        CheatOp::Add(cheat) => core.add_cheat(cheat)?,
+        CheatOp::SetEnabled { index, enabled } => core.set_cheat_enabled(index, enabled)?,
+        CheatOp::Remove(index) => core.remove_cheat(index)?,
+        CheatOp::Clear => core.clear_cheats(),
+    }
+    Ok(core.cheats().to_vec())
+}
+
+fn apply_debug_op(core: &mut dyn ConsoleCore, op: DebugOp) -> Result<DebugState, CoreError> {
+    match op {
+        DebugOp::Inspect => {}
+        DebugOp::SetBreakpoints(breakpoints) => core.set_breakpoints(breakpoints)?,
+        DebugOp::Step(mode) => {
+            core.debug_step(mode)?;
+            core.set_paused(false);
+        }
+        DebugOp::SetRegister { name, value } => core.set_register(&name, value)?,
+    }
+    core.debug_state()
+}
+
+fn apply_memory_op(core: &mut dyn ConsoleCore, op: MemoryOp) -> Result<MemoryBlock, CoreError> {
+    match op {
+        MemoryOp::Read { space, start, len } => core.peek_memory(space, start, len),
+        MemoryOp::Write {
+            space,
+            start,
+            bytes,
+        } => {
+            core.poke_memory(space, start, &bytes)?;
+            core.peek_memory(space, start, bytes.len() as u32)
+        }
+    }
+}
+
+fn is_valid_speed(speed: EmuSpeed) -> bool {
+    match speed {
+        EmuSpeed::Multiplier(multiplier) => multiplier.is_finite() && multiplier > 0.0,
+        EmuSpeed::Uncapped => true,
+    }
+}
+
 impl Drop for EmuThread {
     fn drop(&mut self) {
         self.join();
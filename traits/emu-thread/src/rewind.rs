@@ -0,0 +1,261 @@
+use nerust_core_traits::{ConsoleCore, RewindConfig};
+
+/// Fixed-size snapshot ring. Each slot holds one `rewind_save` image.
+#[derive(Debug)]
+struct RewindBuffer {
+    slot_size: usize,
+    capacity: usize,
+    data: Vec<u8>,
+    /// Slot written by the next `push_with`.
+    head: usize,
+    len: usize,
+}
+
+impl RewindBuffer {
+    fn new(slot_size: usize, capacity: usize) -> Option<Self> {
+        if slot_size == 0 || capacity == 0 {
+            return None;
+        }
+        let bytes = slot_size.checked_mul(capacity)?;
+        Some(Self {
+            slot_size,
+            capacity,
+            data: vec![0; bytes],
+            head: 0,
+            len: 0,
+        })
+    }
+
+    /// Fills the next slot with `write`. The slot is kept only when `write` succeeds,
+    /// so a failed capture never leaves an unusable entry in the ring.
+    fn push_with<E>(&mut self, write: impl FnOnce(&mut [u8]) -> Result<(), E>) -> Result<(), E> {
+        let start = self.head * self.slot_size;
+        write(&mut self.data[start..start + self.slot_size])?;
+        self.head = (self.head + 1) % self.capacity;
+        self.len = (self.len + 1).min(self.capacity);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<&[u8]> {
+        if self.len == 0 {
+            return None;
+        }
+        self.head = (self.head + self.capacity - 1) % self.capacity;
+        self.len -= 1;
+        let start = self.head * self.slot_size;
+        Some(&self.data[start..start + self.slot_size])
+    }
+}
+
+/// Emu-thread side of rewind: captures a snapshot every `interval_frames`
+/// frames and, while rewinding, restores one snapshot per emulated frame.
+#[derive(Debug)]
+pub(crate) struct Rewind {
+    config: RewindConfig,
+    buffer: Option<RewindBuffer>,
+    frames_since_capture: u32,
+    rewinding: bool,
+}
+
+impl Rewind {
+    pub(crate) fn new(config: RewindConfig) -> Self {
+        Self {
+            config,
+            buffer: None,
+            frames_since_capture: 0,
+            rewinding: false,
+        }
+    }
+
+    /// Drops every snapshot and sizes the ring for the currently loaded content.
+    /// Rewind stays disabled when the core reports no snapshot size.
+    pub(crate) fn reset(&mut self, core: &dyn ConsoleCore) {
+        self.buffer = core
+            .rewind_state_size()
+            .and_then(|size| RewindBuffer::new(size, self.config.capacity));
+        self.frames_since_capture = 0;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buffer = None;
+        self.frames_since_capture = 0;
+        self.rewinding = false;
+    }
+
+    pub(crate) fn configure(&mut self, config: RewindConfig, core: &dyn ConsoleCore) {
+        if self.config == config {
+            return;
+        }
+        self.config = config;
+        if self.buffer.is_some() {
+            self.reset(core);
+        }
+    }
+
+    pub(crate) fn set_rewinding(&mut self, rewinding: bool) {
+        self.rewinding = rewinding;
+        self.frames_since_capture = 0;
+    }
+
+    /// Called before a frame is emulated. Returns `false` when the frame should
+    /// be skipped because rewinding has run out of snapshots.
+    pub(crate) fn before_frame(&mut self, core: &mut dyn ConsoleCore) -> bool {
+        if !self.rewinding {
+            return true;
+        }
+        match self.buffer.as_mut().and_then(RewindBuffer::pop) {
+            Some(snapshot) => {
+                if let Err(e) = core.rewind_restore(snapshot) {
+                    log::warn!("rewind restore failed: {e}");
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn after_frame(&mut self, core: &dyn ConsoleCore) {
+        if self.rewinding {
+            return;
+        }
+        let Some(buffer) = self.buffer.as_mut() else {
+            return;
+        };
+        self.frames_since_capture += 1;
+        if self.frames_since_capture >= self.config.interval_frames.max(1) {
+            self.frames_since_capture = 0;
+            if let Err(e) = buffer.push_with(|slot| core.rewind_save(slot)) {
+                log::warn!("rewind snapshot dropped: {e}");
+            }
+        }
+    }
+
+    #[cfg(test)]
+    fn snapshot_count(&self) -> usize {
+        self.buffer.as_ref().map_or(0, |buffer| buffer.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nerust_core_traits::{
+        ConsoleCore, CoreCapabilities, CoreConfig, CoreError, RewindConfig, VideoSignalKind,
+    };
+    use nerust_render_traits::FrameBuffer;
+
+    use super::{Rewind, RewindBuffer};
+
+    /// Minimal core whose whole state is a frame counter.
+    struct CounterCore {
+        frame: u8,
+    }
+
+    impl ConsoleCore for CounterCore {
+        fn capabilities(&self) -> CoreCapabilities {
+            CoreCapabilities {
+                output_formats: Vec::new(),
+                video_signal: VideoSignalKind::Other,
+            }
+        }
+        fn render_frame(&mut self, _frame_slot: &mut FrameBuffer) -> Result<(), CoreError> {
+            self.frame += 1;
+            Ok(())
+        }
+        fn load(&mut self, _rom: &[u8], _config: &CoreConfig) -> Result<(), CoreError> {
+            Ok(())
+        }
+        fn unload(&mut self) {}
+        fn reset(&mut self) {}
+        fn paused(&self) -> bool {
+            false
+        }
+        fn set_paused(&mut self, _paused: bool) {}
+        fn save_state(&self) -> Result<Vec<u8>, CoreError> {
+            Ok(vec![self.frame])
+        }
+        fn load_state(&mut self, data: &[u8]) -> Result<(), CoreError> {
+            self.frame = data[0];
+            Ok(())
+        }
+        fn rewind_state_size(&self) -> Option<usize> {
+            Some(1)
+        }
+        fn rewind_save(&self, buf: &mut [u8]) -> Result<(), CoreError> {
+            buf[0] = self.frame;
+            Ok(())
+        }
+        fn rewind_restore(&mut self, buf: &[u8]) -> Result<(), CoreError> {
+            self.frame = buf[0];
+            Ok(())
+        }
+    }
+
+    fn run_frame(rewind: &mut Rewind, core: &mut CounterCore) {
+        if rewind.before_frame(core) {
+            core.frame += 1;
+        }
+        rewind.after_frame(core);
+    }
+
+    #[test]
+    fn ring_overwrites_oldest_snapshot() {
+        let mut buffer = RewindBuffer::new(1, 3).unwrap();
+        for value in 1..=5 {
+            buffer
+                .push_with(|slot| {
+                    slot[0] = value;
+                    Ok::<_, ()>(())
+                })
+                .unwrap();
+        }
+        assert_eq!(buffer.push_with(|_| Err(())), Err(()));
+
+        assert_eq!(buffer.pop(), Some(&[5][..]));
+        assert_eq!(buffer.pop(), Some(&[4][..]));
+        assert_eq!(buffer.pop(), Some(&[3][..]));
+        assert_eq!(buffer.pop(), None);
+    }
+
+    #[test]
+    fn unsupported_core_sizes_disable_rewind() {
+        assert!(RewindBuffer::new(0, 10).is_none());
+        assert!(RewindBuffer::new(10, 0).is_none());
+        assert!(RewindBuffer::new(usize::MAX, 2).is_none());
+    }
+
+    #[test]
+    fn rewinding_steps_back_one_interval_per_frame() {
+        let mut core = CounterCore { frame: 0 };
+        let mut rewind = Rewind::new(RewindConfig {
+            interval_frames: 2,
+            capacity: 8,
+        });
+        rewind.reset(&core);
+        for _ in 0..8 {
+            run_frame(&mut rewind, &mut core);
+        }
+        assert_eq!(core.frame, 8);
+        assert_eq!(rewind.snapshot_count(), 4);
+
+        rewind.set_rewinding(true);
+        run_frame(&mut rewind, &mut core);
+        // restored frame 8, then emulated one frame for display
+        assert_eq!(core.frame, 9);
+        run_frame(&mut rewind, &mut core);
+        assert_eq!(core.frame, 7);
+        assert_eq!(rewind.snapshot_count(), 2);
+
+        run_frame(&mut rewind, &mut core);
+        run_frame(&mut rewind, &mut core);
+        assert_eq!(core.frame, 3);
+        // out of snapshots: hold on the oldest restored frame
+        run_frame(&mut rewind, &mut core);
+        assert_eq!(core.frame, 3);
+
+        rewind.set_rewinding(false);
+        run_frame(&mut rewind, &mut core);
+        run_frame(&mut rewind, &mut core);
+        assert_eq!(core.frame, 5);
+        assert_eq!(rewind.snapshot_count(), 1);
+    }
+}
@@ -275,11 +275,30 @@ impl Port for SimplePort {
     }
 }
 
+/// The frame being drawn, as seen by a light-sensing controller (light gun)
+/// at the moment of a port read.
+pub trait LightSense {
+    /// Beam position `(x, y)` in output pixels: every row above `y` and the
+    /// pixels left of `x` on row `y` are already drawn. `None` before the
+    /// first visible line.
+    fn beam(&self) -> Option<(usize, usize)>;
+    /// Brightness (0.0–1.0) of a drawn pixel, or `None` outside the frame.
+    fn brightness(&self, x: usize, y: usize) -> Option<f32>;
+}
+
 /// Single physical controller (shift register logic).
 pub trait Controller: std::fmt::Debug + Send {
     fn read(&mut self, port: &dyn Port) -> OpenBusReadResult;
     fn write(&mut self, port: &dyn Port, value: u8);
     fn sync_input(&mut self, _state: &[u8]) {}
+    /// Whether `read_with_light` needs the video output. Cores only build a
+    /// `LightSense` view when some controller asks for it.
+    fn senses_light(&self) -> bool {
+        false
+    }
+    fn read_with_light(&mut self, port: &dyn Port, _light: &dyn LightSense) -> OpenBusReadResult {
+        self.read(port)
+    }
     /// Return field map entries (attachment, control, field_index) for this
     /// controller at the given port. Default returns empty (no inputs).
     fn field_map(&self, _port: &dyn Port) -> Vec<(AttachmentId, DigitalControlId, usize)> {
@@ -292,6 +311,16 @@ pub trait ControllerHub: std::fmt::Debug + Send {
     fn read_port(&mut self, port: &dyn Port) -> OpenBusReadResult;
     fn write_strobe(&mut self, value: u8);
     fn sync_input(&mut self, state: &[u8]);
+    fn senses_light(&self) -> bool {
+        false
+    }
+    fn read_port_with_light(
+        &mut self,
+        port: &dyn Port,
+        _light: &dyn LightSense,
+    ) -> OpenBusReadResult {
+        self.read_port(port)
+    }
 }
 
 /// A collection of per-port controllers.
@@ -342,6 +371,19 @@ impl ControllerHub for ControllerCollection {
             d.sync_input(state);
         }
     }
+    fn senses_light(&self) -> bool {
+        self.devices.iter().any(|d| d.senses_light())
+    }
+    fn read_port_with_light(
+        &mut self,
+        port: &dyn Port,
+        light: &dyn LightSense,
+    ) -> OpenBusReadResult {
+        self.devices.get_mut(port.index()).map_or_else(
+            || OpenBusReadResult::new(0, 0),
+            |d| d.read_with_light(port, light),
+        )
+    }
 }
 
 unsafe impl Send for ControllerCollection {}
@@ -413,7 +455,12 @@ pub struct ControlInfo {
 pub enum ControlKind {
     Digital,
     Analog,
-    AnalogStick { clickable: bool },
+    AnalogStick {
+        clickable: bool,
+    },
+    /// Absolute pointer position (`InputValue::Position`, normalized to the
+    /// frame). The device group's `AbstractKey::Button1` control is the
+    /// pointer's primary button.
     Mouse,
 }
 